@@ -0,0 +1,170 @@
+//! Auto-repeat
+//!
+//! Re-runs a held button's action on an interval. Buttons that repeat skip
+//! long-press detection, so their press is passed on (and their action run)
+//! as soon as it arrives. The repeat task is started on that same press and
+//! only fires after the initial delay, so a button released before then runs
+//! its action once. Releasing the button aborts the task.
+
+use crate::config::types::RepeatConfig;
+use crate::hid::types::ButtonType;
+use std::collections::HashMap;
+use std::future::Future;
+use tauri::async_runtime::JoinHandle;
+
+/// Per-button repeat tasks
+#[derive(Default)]
+pub struct AutoRepeat {
+    tasks: HashMap<(ButtonType, u8), JoinHandle<()>>,
+}
+
+impl AutoRepeat {
+    /// Create a scheduler with no running tasks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start repeating for a pressed button, replacing any task it already has
+    pub fn start<F, Fut>(&mut self, button_type: ButtonType, index: u8, config: RepeatConfig, fire: F)
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.stop(button_type, index);
+
+        let task = tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(config.initial_delay()).await;
+            loop {
+                fire().await;
+                tokio::time::sleep(config.interval()).await;
+            }
+        });
+        self.tasks.insert((button_type, index), task);
+    }
+
+    /// Stop repeating for a released button
+    pub fn stop(&mut self, button_type: ButtonType, index: u8) {
+        if let Some(task) = self.tasks.remove(&(button_type, index)) {
+            task.abort();
+        }
+    }
+
+    /// Stop every running repeat
+    pub fn stop_all(&mut self) {
+        for (_, task) in self.tasks.drain() {
+            task.abort();
+        }
+    }
+
+    /// Whether a button currently has a repeat task
+    pub fn is_repeating(&self, button_type: ButtonType, index: u8) -> bool {
+        self.tasks.contains_key(&(button_type, index))
+    }
+}
+
+impl Drop for AutoRepeat {
+    fn drop(&mut self) {
+        self.stop_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::types::MIN_REPEAT_INTERVAL_MS;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn counter() -> (Arc<AtomicUsize>, impl Fn() -> std::future::Ready<()> + Send + 'static) {
+        let count = Arc::new(AtomicUsize::new(0));
+        let fire_count = Arc::clone(&count);
+        let fire = move || {
+            fire_count.fetch_add(1, Ordering::SeqCst);
+            std::future::ready(())
+        };
+        (count, fire)
+    }
+
+    // ========== Timing Tests ==========
+
+    #[test]
+    fn test_initial_delay_defaults_to_interval() {
+        let config = RepeatConfig {
+            interval_ms: 80,
+            initial_delay_ms: None,
+        };
+        assert_eq!(config.initial_delay(), Duration::from_millis(80));
+
+        let config = RepeatConfig {
+            interval_ms: 80,
+            initial_delay_ms: Some(300),
+        };
+        assert_eq!(config.initial_delay(), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn test_zero_interval_is_clamped() {
+        let config = RepeatConfig {
+            interval_ms: 0,
+            initial_delay_ms: None,
+        };
+        assert_eq!(config.interval(), Duration::from_millis(MIN_REPEAT_INTERVAL_MS));
+    }
+
+    // ========== Scheduling Tests ==========
+
+    #[tokio::test]
+    async fn test_release_before_initial_delay_never_repeats() {
+        let mut repeat = AutoRepeat::new();
+        let (count, fire) = counter();
+        let config = RepeatConfig {
+            interval_ms: 20,
+            initial_delay_ms: Some(200),
+        };
+
+        repeat.start(ButtonType::Lcd, 0, config, fire);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        repeat.stop(ButtonType::Lcd, 0);
+        tokio::time::sleep(Duration::from_millis(250)).await;
+
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+        assert!(!repeat.is_repeating(ButtonType::Lcd, 0));
+    }
+
+    #[tokio::test]
+    async fn test_held_button_repeats_until_released() {
+        let mut repeat = AutoRepeat::new();
+        let (count, fire) = counter();
+        let config = RepeatConfig {
+            interval_ms: 20,
+            initial_delay_ms: Some(20),
+        };
+
+        repeat.start(ButtonType::Lcd, 3, config, fire);
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        repeat.stop(ButtonType::Lcd, 3);
+
+        let fired = count.load(Ordering::SeqCst);
+        assert!(fired >= 2, "expected several repeats, got {}", fired);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(count.load(Ordering::SeqCst), fired);
+    }
+
+    #[tokio::test]
+    async fn test_buttons_repeat_independently() {
+        let mut repeat = AutoRepeat::new();
+        let config = RepeatConfig {
+            interval_ms: 1000,
+            initial_delay_ms: None,
+        };
+
+        repeat.start(ButtonType::Lcd, 0, config, || std::future::ready(()));
+        repeat.start(ButtonType::Physical, 0, config, || std::future::ready(()));
+        repeat.stop(ButtonType::Lcd, 0);
+
+        assert!(!repeat.is_repeating(ButtonType::Lcd, 0));
+        assert!(repeat.is_repeating(ButtonType::Physical, 0));
+    }
+}
@@ -2,14 +2,23 @@
 //!
 //! Central engine for executing actions. Manages handler registration and execution.
 //! Supports cancellation of long-running actions via a cancellation token pattern.
+//!
+//! Callers that can't hold the engine across an await go through `submit` and
+//! `finish`: `submit` decides, per the `ExecutionMode`, whether the action runs
+//! now, waits its turn, or is rejected, and hands out a `Ticket` carrying the
+//! cancellation token for that run.
 
+use super::error::ActionError;
+use super::history_store::HistoryStore;
 use super::types::{Action, ActionResult};
+use super::IntegrationConfig;
+use std::collections::{HashMap, VecDeque};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 
 /// Action execution history entry
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct HistoryEntry {
     pub action_type: String,
@@ -19,6 +28,33 @@ pub struct HistoryEntry {
     pub error: Option<String>,
 }
 
+/// Filter for querying action history (all fields optional)
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    /// Only entries of this action type (e.g. "keyboard", "homeAssistant")
+    pub action_type: Option<String>,
+    /// Only entries with timestamp >= this (milliseconds since epoch)
+    pub since_ts: Option<u64>,
+    /// Only successful entries when true
+    pub success_only: Option<bool>,
+    /// Return at most this many of the most recent matching entries
+    pub limit: Option<usize>,
+}
+
+/// Aggregate statistics for one action type
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionTypeStats {
+    pub action_type: String,
+    pub count: usize,
+    pub success_count: usize,
+    pub failure_count: usize,
+    pub average_duration_ms: u64,
+}
+
+/// How often `CancellationToken::cancelled` checks the token
+const CANCEL_POLL_INTERVAL_MS: u64 = 25;
+
 /// Cancellation token for long-running actions
 ///
 /// This token can be cloned and shared across async tasks. When `cancel()` is called,
@@ -50,6 +86,13 @@ impl CancellationToken {
     pub fn reset(&self) {
         self.cancelled.store(false, Ordering::SeqCst);
     }
+
+    /// Resolve once cancellation has been requested
+    pub async fn cancelled(&self) {
+        while !self.is_cancelled() {
+            tokio::time::sleep(std::time::Duration::from_millis(CANCEL_POLL_INTERVAL_MS)).await;
+        }
+    }
 }
 
 impl Default for CancellationToken {
@@ -58,16 +101,71 @@ impl Default for CancellationToken {
     }
 }
 
+/// How the engine handles an action submitted while another is running
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExecutionMode {
+    /// Refuse the new action
+    #[default]
+    Reject,
+    /// Run actions one at a time, oldest first
+    Queue,
+    /// Run every action straight away, each with its own cancellation token
+    Parallel,
+}
+
+/// Permission to run one action, returned to `finish` when it completes
+#[derive(Debug, Clone)]
+pub struct Ticket {
+    pub id: u64,
+    /// Cancelled by `ActionEngine::cancel` while this action is running
+    pub token: CancellationToken,
+}
+
+/// What `ActionEngine::submit` decided for an action
+#[derive(Debug)]
+pub enum Admission {
+    /// Run the action now
+    Run(Ticket),
+    /// Run the action once the receiver yields its ticket
+    Queued(tokio::sync::oneshot::Receiver<Ticket>),
+    /// Another action is running and the engine is in `Reject` mode
+    Rejected,
+}
+
+/// Action waiting for its turn in `Queue` mode
+#[derive(Debug)]
+struct QueuedAction {
+    action: Action,
+    turn: tokio::sync::oneshot::Sender<Ticket>,
+}
+
 /// Central action execution engine
 pub struct ActionEngine {
     /// Execution history (limited to last 100 entries)
     history: Vec<HistoryEntry>,
     /// Maximum history size
     max_history: usize,
-    /// Whether an action is currently executing
+    /// File the history is also saved to, if any
+    history_store: Option<Arc<HistoryStore>>,
+    /// Whether an action is currently executing (`Reject` and `Queue` modes)
     is_executing: bool,
     /// Cancellation token for the current action
     cancellation_token: CancellationToken,
+    /// How actions submitted while one is running are handled
+    mode: ExecutionMode,
+    /// Ticket ID of the current action (`Reject` and `Queue` modes)
+    current_ticket: u64,
+    /// Ticket ID to hand out next
+    next_ticket: u64,
+    /// Actions waiting to run, oldest first
+    queue: VecDeque<QueuedAction>,
+    /// Tokens of actions started in `Parallel` mode, by ticket ID
+    running: HashMap<u64, CancellationToken>,
+    /// Latest values captured by actions (e.g. HTTP responses), by name
+    variables: super::template::Variables,
+    /// Home Assistant / Node-RED settings passed to handlers
+    integrations: IntegrationConfig,
 }
 
 impl ActionEngine {
@@ -76,11 +174,120 @@ impl ActionEngine {
         Self {
             history: Vec::new(),
             max_history: 100,
+            history_store: None,
             is_executing: false,
             cancellation_token: CancellationToken::new(),
+            mode: ExecutionMode::default(),
+            current_ticket: 0,
+            next_ticket: 1,
+            queue: VecDeque::new(),
+            running: HashMap::new(),
+            variables: super::template::Variables::new(),
+            integrations: IntegrationConfig::default(),
         }
     }
 
+    /// Set the integration settings used by `execute`
+    pub fn set_integrations(&mut self, integrations: IntegrationConfig) {
+        self.integrations = integrations;
+    }
+
+    /// Integration settings used by `execute`
+    pub fn integrations(&self) -> &IntegrationConfig {
+        &self.integrations
+    }
+
+    /// Save history to `store`, starting from the entries already in it
+    pub fn set_history_store(&mut self, store: Arc<HistoryStore>) {
+        self.history = store.load_tail(self.max_history);
+        self.history_store = Some(store);
+    }
+
+    /// File the history is saved to, if any
+    pub fn history_store(&self) -> Option<&Arc<HistoryStore>> {
+        self.history_store.as_ref()
+    }
+
+    /// Get the execution mode
+    pub fn execution_mode(&self) -> ExecutionMode {
+        self.mode
+    }
+
+    /// Change the execution mode
+    ///
+    /// Actions already queued still run in order as the current one finishes.
+    pub fn set_execution_mode(&mut self, mode: ExecutionMode) {
+        self.mode = mode;
+    }
+
+    /// Number of actions waiting to run
+    pub fn queue_len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Ask to run an action
+    ///
+    /// A `Run` or `Queued` admission must be followed by `finish` once the
+    /// action completes, so the next queued action can start.
+    pub fn submit(&mut self, action: &Action) -> Admission {
+        match self.mode {
+            ExecutionMode::Parallel => {
+                let ticket = self.issue_ticket();
+                self.running.insert(ticket.id, ticket.token.clone());
+                Admission::Run(ticket)
+            }
+            _ if !self.is_executing => Admission::Run(self.start_serial()),
+            ExecutionMode::Reject => Admission::Rejected,
+            ExecutionMode::Queue => {
+                let (turn, wait) = tokio::sync::oneshot::channel();
+                self.queue.push_back(QueuedAction {
+                    action: action.clone(),
+                    turn,
+                });
+                Admission::Queued(wait)
+            }
+        }
+    }
+
+    /// Record a submitted action's result and start the next queued one
+    pub fn finish(&mut self, ticket: &Ticket, action: &Action, result: &ActionResult) {
+        self.record_execution(action, result);
+
+        if self.running.remove(&ticket.id).is_some() || ticket.id != self.current_ticket {
+            return;
+        }
+
+        while let Some(next) = self.queue.pop_front() {
+            let ticket = self.start_serial();
+            if next.turn.send(ticket).is_ok() {
+                return;
+            }
+            log::debug!("Dropping queued {} action, its caller stopped waiting", self.get_action_type_name(&next.action));
+        }
+        self.is_executing = false;
+    }
+
+    fn issue_ticket(&mut self) -> Ticket {
+        let id = self.next_ticket;
+        self.next_ticket += 1;
+        Ticket {
+            id,
+            token: CancellationToken::new(),
+        }
+    }
+
+    /// Make a new ticket the current one
+    ///
+    /// Each run gets a fresh token, so starting the next action can't undo
+    /// the cancellation of one that is still winding down.
+    fn start_serial(&mut self) -> Ticket {
+        let ticket = self.issue_ticket();
+        self.is_executing = true;
+        self.current_ticket = ticket.id;
+        self.cancellation_token = ticket.token.clone();
+        ticket
+    }
+
     /// Get a clone of the current cancellation token
     ///
     /// This can be passed to handlers that support cancellation so they can
@@ -90,9 +297,12 @@ impl ActionEngine {
     }
 
     /// Execute an action
+    ///
+    /// Holds the engine for the whole run, so only `Reject` semantics apply
+    /// here; callers that want queueing or parallel runs use `submit`.
     pub async fn execute(&mut self, action: &Action) -> ActionResult {
         if self.is_executing {
-            return ActionResult::failure("Another action is currently executing".to_string(), 0);
+            return ActionResult::from_error(ActionError::Busy("Another action is currently executing".to_string()), 0);
         }
 
         self.is_executing = true;
@@ -100,41 +310,11 @@ impl ActionEngine {
         self.cancellation_token.reset();
         let start = Instant::now();
 
-        let result = match action {
-            Action::Keyboard(config) => {
-                super::handlers::keyboard::execute(config).await
-            }
-            Action::Media(config) => {
-                super::handlers::media::execute(config).await
-            }
-            Action::Launch(config) => {
-                super::handlers::launch::execute(config).await
-            }
-            Action::Script(config) => {
-                super::handlers::script::execute(config).await
-            }
-            Action::Http(config) => {
-                super::handlers::http::execute(config).await
-            }
-            Action::System(config) => {
-                super::handlers::system::execute(config).await
-            }
-            Action::Text(config) => {
-                super::handlers::text::execute(config).await
-            }
-            Action::Profile(config) => {
-                super::handlers::profile::execute(config).await
-            }
-            Action::HomeAssistant(config) => {
-                super::handlers::home_assistant::execute(config).await
-            }
-            Action::NodeRed(config) => {
-                super::handlers::node_red::execute(config).await
-            }
-            Action::Workspace(config) => {
-                super::handlers::workspace::execute(config).await
-            }
-        };
+        let result = super::execute_action_with_cancellation(
+            action,
+            &self.integrations,
+            &self.cancellation_token,
+        ).await;
 
         let duration = start.elapsed().as_millis() as u64;
 
@@ -150,10 +330,8 @@ impl ActionEngine {
             error: result.error.clone(),
         };
 
-        self.history.push(entry);
-        if self.history.len() > self.max_history {
-            self.history.remove(0);
-        }
+        self.push_history(entry);
+        self.store_variables(&result);
 
         self.is_executing = false;
 
@@ -171,16 +349,25 @@ impl ActionEngine {
     ///
     /// Note: This does not immediately stop the action - it signals a cancellation
     /// request that cooperative handlers will check and respond to.
+    ///
+    /// In `Queue` mode only the current action is cancelled; queued actions
+    /// still run. In `Parallel` mode every running action is cancelled.
     pub fn cancel(&mut self) {
         // Signal cancellation to any handler that supports it
         self.cancellation_token.cancel();
-        // Reset the executing flag to allow new actions
-        self.is_executing = false;
+        for token in self.running.values() {
+            token.cancel();
+        }
+        // Reset the executing flag to allow new actions; a queue instead
+        // moves on when the cancelled action finishes
+        if self.mode != ExecutionMode::Queue {
+            self.is_executing = false;
+        }
     }
 
     /// Check if an action is currently executing
     pub fn is_executing(&self) -> bool {
-        self.is_executing
+        self.is_executing || !self.running.is_empty()
     }
 
     /// Record an action execution to history
@@ -196,20 +383,88 @@ impl ActionEngine {
             error: result.error.clone(),
         };
 
+        self.push_history(entry);
+        self.store_variables(result);
+    }
+
+    /// Add an entry to the in-memory history and the history file
+    fn push_history(&mut self, entry: HistoryEntry) {
+        if let Some(store) = &self.history_store {
+            store.append(entry.clone());
+        }
         self.history.push(entry);
         if self.history.len() > self.max_history {
             self.history.remove(0);
         }
     }
 
+    /// Keep the variables an action captured
+    fn store_variables(&mut self, result: &ActionResult) {
+        if let Some(ref captured) = result.variables {
+            self.variables.extend(captured.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+    }
+
+    /// Latest captured variables, for `${...}` references in later actions
+    pub fn variables(&self) -> &super::template::Variables {
+        &self.variables
+    }
+
     /// Get execution history
     pub fn get_history(&self) -> &[HistoryEntry] {
         &self.history
     }
 
-    /// Clear execution history
-    pub fn clear_history(&mut self) {
+    /// Get execution history entries matching a filter, oldest first
+    pub fn get_history_filtered(&self, filter: &HistoryFilter) -> Vec<HistoryEntry> {
+        let matching: Vec<&HistoryEntry> = self
+            .history
+            .iter()
+            .filter(|e| filter.action_type.as_ref().is_none_or(|t| &e.action_type == t))
+            .filter(|e| filter.since_ts.is_none_or(|ts| e.timestamp >= ts))
+            .filter(|e| !filter.success_only.unwrap_or(false) || e.success)
+            .collect();
+
+        let skip = filter
+            .limit
+            .map_or(0, |limit| matching.len().saturating_sub(limit));
+
+        matching.into_iter().skip(skip).cloned().collect()
+    }
+
+    /// Get counts and average duration per action type, sorted by type name
+    pub fn get_stats(&self) -> Vec<ActionTypeStats> {
+        let mut by_type: std::collections::BTreeMap<&str, (usize, usize, u64)> =
+            std::collections::BTreeMap::new();
+
+        for entry in &self.history {
+            let stats = by_type.entry(entry.action_type.as_str()).or_default();
+            stats.0 += 1;
+            if entry.success {
+                stats.1 += 1;
+            }
+            stats.2 += entry.duration_ms;
+        }
+
+        by_type
+            .into_iter()
+            .map(|(action_type, (count, success_count, total_ms))| ActionTypeStats {
+                action_type: action_type.to_string(),
+                count,
+                success_count,
+                failure_count: count - success_count,
+                average_duration_ms: total_ms / count as u64,
+            })
+            .collect()
+    }
+
+    /// Clear execution history, including the history file
+    pub fn clear_history(&mut self) -> Result<(), String> {
         self.history.clear();
+        match &self.history_store {
+            Some(store) => store.clear(),
+            None => Ok(()),
+        }
     }
 
     fn get_action_type_name(&self, action: &Action) -> String {
@@ -225,6 +480,15 @@ impl ActionEngine {
             Action::HomeAssistant(_) => "homeAssistant".to_string(),
             Action::NodeRed(_) => "nodeRed".to_string(),
             Action::Workspace(_) => "workspace".to_string(),
+            Action::ReleaseAllKeys(_) => "releaseAllKeys".to_string(),
+            Action::Macro(_) => "macro".to_string(),
+            Action::Conditional(_) => "conditional".to_string(),
+            Action::Mqtt(_) => "mqtt".to_string(),
+            Action::Brightness(_) => "brightness".to_string(),
+            Action::Notify(_) => "notify".to_string(),
+            Action::Clipboard(_) => "clipboard".to_string(),
+            Action::Delay(_) => "delay".to_string(),
+            Action::ButtonFeedback(_) => "buttonFeedback".to_string(),
         }
     }
 }
@@ -242,6 +506,7 @@ mod tests {
         Action, KeyboardAction, MediaAction, MediaActionType, LaunchAction, ScriptAction,
         ScriptType, HttpAction, HttpMethod, SystemAction, SystemActionType, TextAction,
         ProfileAction, HomeAssistantAction, HomeAssistantActionType, NodeRedAction,
+        NodeRedOperationType, MacroAction, DelayAction,
     };
     use std::collections::HashMap;
 
@@ -249,58 +514,102 @@ mod tests {
 
     fn create_keyboard_action() -> Action {
         Action::Keyboard(KeyboardAction {
-            key: "A".to_string(),
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            keys: "A".to_string(),
             modifiers: vec![],
+            hold_duration: None,
+            sequence: Vec::new(),
         })
     }
 
     fn create_media_action() -> Action {
         Action::Media(MediaAction {
             action: MediaActionType::PlayPause,
+            ..Default::default()
         })
     }
 
     fn create_launch_action() -> Action {
         Action::Launch(LaunchAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
             path: "/usr/bin/test".to_string(),
             args: vec![],
             working_directory: None,
+            use_shell: None,
+            single_instance: None,
         })
     }
 
     fn create_script_action() -> Action {
         Action::Script(ScriptAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
             script_type: ScriptType::Bash,
-            content: "echo test".to_string(),
+            script: Some("echo test".to_string()),
+            content: None,
+            script_path: None,
+            timeout: None,
             timeout_ms: None,
+            env: HashMap::new(),
+            working_directory: None,
         })
     }
 
     fn create_http_action() -> Action {
         Action::Http(HttpAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
             method: HttpMethod::Get,
             url: "https://example.com".to_string(),
             headers: HashMap::new(),
+            body_type: None,
             body: None,
+            timeout: None,
             timeout_ms: None,
+            capture: None,
+            retries: None,
+            retry_backoff_ms: None,
         })
     }
 
     fn create_system_action() -> Action {
         Action::System(SystemAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
             action: SystemActionType::ShowDesktop,
         })
     }
 
     fn create_text_action() -> Action {
         Action::Text(TextAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
             text: "Hello".to_string(),
+            type_delay: None,
             delay_ms: None,
         })
     }
 
     fn create_profile_action() -> Action {
         Action::Profile(ProfileAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
             profile_id: Some("profile-1".to_string()),
             profile_name: None,
         })
@@ -308,8 +617,15 @@ mod tests {
 
     fn create_home_assistant_action() -> Action {
         Action::HomeAssistant(HomeAssistantAction {
-            action_type: HomeAssistantActionType::Toggle,
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            operation: HomeAssistantActionType::Toggle,
             entity_id: "light.living_room".to_string(),
+            brightness: None,
+            brightness_delta: None,
+            custom_service: None,
             service: None,
             service_data: None,
         })
@@ -317,8 +633,15 @@ mod tests {
 
     fn create_node_red_action() -> Action {
         Action::NodeRed(NodeRedAction {
-            flow_id: "flow-1".to_string(),
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            operation: NodeRedOperationType::TriggerFlow,
+            endpoint: "/flow-1".to_string(),
+            event_name: None,
             payload: None,
+            flow_id: None,
         })
     }
 
@@ -410,6 +733,33 @@ mod tests {
         assert_eq!(engine.get_action_type_name(&action), "nodeRed");
     }
 
+    #[test]
+    fn test_action_type_name_macro() {
+        let engine = ActionEngine::new();
+        let action = Action::Macro(MacroAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            steps: vec![],
+            stop_on_error: false,
+        });
+        assert_eq!(engine.get_action_type_name(&action), "macro");
+    }
+
+    #[test]
+    fn test_action_type_name_delay() {
+        let engine = ActionEngine::new();
+        let action = Action::Delay(DelayAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            ms: 100,
+        });
+        assert_eq!(engine.get_action_type_name(&action), "delay");
+    }
+
     // ========== History Recording Tests ==========
 
     #[test]
@@ -490,7 +840,7 @@ mod tests {
 
         assert_eq!(engine.get_history().len(), 2);
 
-        engine.clear_history();
+        engine.clear_history().unwrap();
 
         assert!(engine.get_history().is_empty());
     }
@@ -498,10 +848,33 @@ mod tests {
     #[test]
     fn test_clear_history_on_empty_is_safe() {
         let mut engine = ActionEngine::new();
-        engine.clear_history(); // Should not panic
+        engine.clear_history().unwrap(); // Should not fail
         assert!(engine.get_history().is_empty());
     }
 
+    #[test]
+    fn test_history_store_restores_and_records() {
+        use super::super::history_store::{HistoryStore, DEFAULT_MAX_FILE_BYTES, HISTORY_FILE};
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(HISTORY_FILE);
+
+        let mut engine = ActionEngine::new();
+        engine.set_history_store(Arc::new(HistoryStore::open(path.clone(), DEFAULT_MAX_FILE_BYTES).unwrap()));
+        engine.record_execution(&create_keyboard_action(), &ActionResult::success(10));
+        drop(engine);
+
+        // A new engine starts from the saved history
+        let mut engine = ActionEngine::new();
+        let store = Arc::new(HistoryStore::open(path, DEFAULT_MAX_FILE_BYTES).unwrap());
+        engine.set_history_store(Arc::clone(&store));
+        assert_eq!(engine.get_history().len(), 1);
+        assert_eq!(engine.get_history()[0].action_type, "keyboard");
+
+        engine.clear_history().unwrap();
+        assert!(store.load_tail(10).is_empty());
+    }
+
     // ========== Execution State Tests ==========
 
     #[test]
@@ -530,6 +903,120 @@ mod tests {
         assert!(!engine.is_executing());
     }
 
+    #[test]
+    fn test_record_execution_keeps_captured_variables() {
+        let mut engine = ActionEngine::new();
+        let action = create_http_action();
+        let captured = HashMap::from([("resp".to_string(), serde_json::json!({"status": 200}))]);
+        engine.record_execution(&action, &ActionResult::success(5).with_variables(captured));
+
+        assert_eq!(engine.variables()["resp"], serde_json::json!({"status": 200}));
+    }
+
+    // ========== History Query Tests ==========
+
+    /// Seed the engine with a known history
+    fn seed_history(engine: &mut ActionEngine) {
+        let entries = [
+            ("keyboard", true, 10, 1000),
+            ("http", false, 300, 2000),
+            ("keyboard", true, 30, 3000),
+            ("http", true, 100, 4000),
+            ("media", true, 5, 5000),
+        ];
+        for (action_type, success, duration_ms, timestamp) in entries {
+            engine.history.push(HistoryEntry {
+                action_type: action_type.to_string(),
+                success,
+                duration_ms,
+                timestamp,
+                error: if success { None } else { Some("failed".to_string()) },
+            });
+        }
+    }
+
+    #[test]
+    fn test_history_filtered_without_filter_returns_all() {
+        let mut engine = ActionEngine::new();
+        seed_history(&mut engine);
+
+        let entries = engine.get_history_filtered(&HistoryFilter::default());
+        assert_eq!(entries.len(), 5);
+    }
+
+    #[test]
+    fn test_history_filtered_by_type() {
+        let mut engine = ActionEngine::new();
+        seed_history(&mut engine);
+
+        let filter = HistoryFilter {
+            action_type: Some("http".to_string()),
+            ..Default::default()
+        };
+        let entries = engine.get_history_filtered(&filter);
+
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|e| e.action_type == "http"));
+    }
+
+    #[test]
+    fn test_history_filtered_by_time_and_success() {
+        let mut engine = ActionEngine::new();
+        seed_history(&mut engine);
+
+        let filter = HistoryFilter {
+            since_ts: Some(2000),
+            success_only: Some(true),
+            ..Default::default()
+        };
+        let entries = engine.get_history_filtered(&filter);
+
+        let timestamps: Vec<u64> = entries.iter().map(|e| e.timestamp).collect();
+        assert_eq!(timestamps, vec![3000, 4000, 5000]);
+    }
+
+    #[test]
+    fn test_history_filtered_limit_keeps_most_recent() {
+        let mut engine = ActionEngine::new();
+        seed_history(&mut engine);
+
+        let filter = HistoryFilter {
+            limit: Some(2),
+            ..Default::default()
+        };
+        let entries = engine.get_history_filtered(&filter);
+
+        let timestamps: Vec<u64> = entries.iter().map(|e| e.timestamp).collect();
+        assert_eq!(timestamps, vec![4000, 5000]);
+    }
+
+    #[test]
+    fn test_get_stats_per_action_type() {
+        let mut engine = ActionEngine::new();
+        seed_history(&mut engine);
+
+        let stats = engine.get_stats();
+
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats[0], ActionTypeStats {
+            action_type: "http".to_string(),
+            count: 2,
+            success_count: 1,
+            failure_count: 1,
+            average_duration_ms: 200,
+        });
+        assert_eq!(stats[1].action_type, "keyboard");
+        assert_eq!(stats[1].count, 2);
+        assert_eq!(stats[1].average_duration_ms, 20);
+        assert_eq!(stats[2].action_type, "media");
+    }
+
+    #[test]
+    fn test_get_stats_empty_history() {
+        let engine = ActionEngine::new();
+        assert!(engine.get_stats().is_empty());
+    }
+
     // ========== HistoryEntry Serialization Tests ==========
 
     #[test]
@@ -649,6 +1136,145 @@ mod tests {
         assert!(!token2.is_cancelled());
     }
 
+    #[test]
+    fn test_set_integrations() {
+        let mut engine = ActionEngine::new();
+        assert!(engine.integrations().home_assistant.is_none());
+
+        engine.set_integrations(IntegrationConfig {
+            home_assistant: Some(crate::config::types::HomeAssistantConfig {
+                url: "http://ha.local:8123".to_string(),
+                token: "secret".to_string(),
+            }),
+            node_red: None,
+            mqtt: None,
+            device: None,
+            block_private_networks: false,
+            variables: Default::default(),
+        });
+        assert_eq!(
+            engine.integrations().home_assistant.as_ref().map(|ha| ha.url.as_str()),
+            Some("http://ha.local:8123")
+        );
+    }
+
+    // ========== Execution Mode Tests ==========
+
+    fn ticket_of(admission: Admission) -> Ticket {
+        match admission {
+            Admission::Run(ticket) => ticket,
+            other => panic!("Expected Run, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_default_mode_rejects_while_executing() {
+        let mut engine = ActionEngine::new();
+        assert_eq!(engine.execution_mode(), ExecutionMode::Reject);
+
+        let action = create_keyboard_action();
+        let ticket = ticket_of(engine.submit(&action));
+        assert!(matches!(engine.submit(&action), Admission::Rejected));
+
+        engine.finish(&ticket, &action, &ActionResult::success(1));
+        assert!(!engine.is_executing());
+        assert!(matches!(engine.submit(&action), Admission::Run(_)));
+        assert_eq!(engine.get_history().len(), 1);
+    }
+
+    #[test]
+    fn test_queue_mode_runs_fifo() {
+        let mut engine = ActionEngine::new();
+        engine.set_execution_mode(ExecutionMode::Queue);
+        let action = create_keyboard_action();
+
+        let first = ticket_of(engine.submit(&action));
+        let Admission::Queued(mut second) = engine.submit(&action) else {
+            panic!("Expected Queued");
+        };
+        let Admission::Queued(mut third) = engine.submit(&action) else {
+            panic!("Expected Queued");
+        };
+        assert_eq!(engine.queue_len(), 2);
+        assert!(second.try_recv().is_err());
+
+        engine.finish(&first, &action, &ActionResult::success(1));
+        let second = second.try_recv().expect("second should start");
+        assert!(third.try_recv().is_err());
+
+        engine.finish(&second, &action, &ActionResult::success(1));
+        let third = third.try_recv().expect("third should start");
+
+        engine.finish(&third, &action, &ActionResult::success(1));
+        assert!(!engine.is_executing());
+        assert_eq!(engine.get_history().len(), 3);
+    }
+
+    #[test]
+    fn test_queue_cancel_only_affects_current() {
+        let mut engine = ActionEngine::new();
+        engine.set_execution_mode(ExecutionMode::Queue);
+        let action = create_keyboard_action();
+
+        let first = ticket_of(engine.submit(&action));
+        let Admission::Queued(mut next) = engine.submit(&action) else {
+            panic!("Expected Queued");
+        };
+
+        engine.cancel();
+        assert!(first.token.is_cancelled());
+        assert!(engine.is_executing());
+        assert_eq!(engine.queue_len(), 1);
+
+        engine.finish(&first, &action, &ActionResult::failure("cancelled".to_string(), 0));
+        let next = next.try_recv().expect("queued action should start");
+        assert!(!next.token.is_cancelled());
+        assert!(first.token.is_cancelled());
+    }
+
+    #[test]
+    fn test_queue_skips_abandoned_waiters() {
+        let mut engine = ActionEngine::new();
+        engine.set_execution_mode(ExecutionMode::Queue);
+        let action = create_keyboard_action();
+
+        let first = ticket_of(engine.submit(&action));
+        drop(engine.submit(&action));
+
+        engine.finish(&first, &action, &ActionResult::success(1));
+        assert!(!engine.is_executing());
+        assert_eq!(engine.queue_len(), 0);
+    }
+
+    #[test]
+    fn test_parallel_mode_uses_per_task_tokens() {
+        let mut engine = ActionEngine::new();
+        engine.set_execution_mode(ExecutionMode::Parallel);
+        let action = create_keyboard_action();
+
+        let a = ticket_of(engine.submit(&action));
+        let b = ticket_of(engine.submit(&action));
+        assert_ne!(a.id, b.id);
+        assert!(engine.is_executing());
+
+        engine.finish(&a, &action, &ActionResult::success(1));
+        assert!(engine.is_executing());
+
+        engine.cancel();
+        assert!(!a.token.is_cancelled());
+        assert!(b.token.is_cancelled());
+
+        engine.finish(&b, &action, &ActionResult::success(1));
+        assert!(!engine.is_executing());
+    }
+
+    #[test]
+    fn test_execution_mode_serializes_to_camel_case() {
+        assert_eq!(serde_json::to_string(&ExecutionMode::Queue).unwrap(), "\"queue\"");
+        let mode: ExecutionMode = serde_json::from_str("\"parallel\"").unwrap();
+        assert_eq!(mode, ExecutionMode::Parallel);
+    }
+
     #[test]
     fn test_engine_get_cancellation_token() {
         let engine = ActionEngine::new();
@@ -0,0 +1,116 @@
+//! Action Errors
+//!
+//! Categorized action failures. Each `ActionError` carries the human-readable
+//! message shown to the user, and its `ActionErrorCode` travels with the
+//! result as `errorCode` so the frontend can tell, say, a disconnected device
+//! from an HTTP 500 without parsing the message.
+
+use serde::{Deserialize, Serialize};
+
+/// An action failure, by category
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ActionError {
+    /// The action can't run on this OS or in this build
+    #[error("{0}")]
+    NotSupportedOnPlatform(String),
+    /// A request failed to connect or the server answered with an error
+    #[error("{0}")]
+    NetworkError(String),
+    /// The device isn't available or rejected a command
+    #[error("{0}")]
+    DeviceError(String),
+    /// An integration or other setting the action needs isn't set up
+    #[error("{0}")]
+    ConfigMissing(String),
+    /// The action's own configuration is incomplete or invalid
+    #[error("{0}")]
+    InvalidInput(String),
+    /// The action was cancelled before it finished
+    #[error("{0}")]
+    Cancelled(String),
+    /// Another action is running and the execution mode doesn't allow more
+    #[error("{0}")]
+    Busy(String),
+    /// The action ran out of time and was stopped
+    #[error("{0}")]
+    Timeout(String),
+    /// Settings or profile files couldn't be saved
+    #[error("{0}")]
+    Storage(String),
+    /// Anything else
+    #[error("{0}")]
+    Failed(String),
+}
+
+impl ActionError {
+    /// A plain "Cancelled" error
+    pub fn cancelled() -> Self {
+        Self::Cancelled("Cancelled".to_string())
+    }
+
+    /// Machine-readable category
+    pub fn code(&self) -> ActionErrorCode {
+        match self {
+            Self::NotSupportedOnPlatform(_) => ActionErrorCode::NotSupportedOnPlatform,
+            Self::NetworkError(_) => ActionErrorCode::Network,
+            Self::DeviceError(_) => ActionErrorCode::Device,
+            Self::ConfigMissing(_) => ActionErrorCode::ConfigMissing,
+            Self::InvalidInput(_) => ActionErrorCode::InvalidInput,
+            Self::Cancelled(_) => ActionErrorCode::Cancelled,
+            Self::Busy(_) => ActionErrorCode::Busy,
+            Self::Timeout(_) => ActionErrorCode::Timeout,
+            Self::Storage(_) => ActionErrorCode::Storage,
+            Self::Failed(_) => ActionErrorCode::Failed,
+        }
+    }
+}
+
+/// Category of a failed action, sent to the frontend as `errorCode`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ActionErrorCode {
+    NotSupportedOnPlatform,
+    Network,
+    Device,
+    ConfigMissing,
+    InvalidInput,
+    Cancelled,
+    Busy,
+    Timeout,
+    Storage,
+    Failed,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ========== Code Tests ==========
+
+    #[test]
+    fn test_message_is_display() {
+        let error = ActionError::ConfigMissing("MQTT not configured".to_string());
+        assert_eq!(error.to_string(), "MQTT not configured");
+        assert_eq!(error.code(), ActionErrorCode::ConfigMissing);
+        assert_eq!(ActionError::cancelled().to_string(), "Cancelled");
+    }
+
+    #[test]
+    fn test_codes_serialize_camel_case() {
+        let codes = [
+            (ActionErrorCode::NotSupportedOnPlatform, "\"notSupportedOnPlatform\""),
+            (ActionErrorCode::Network, "\"network\""),
+            (ActionErrorCode::Device, "\"device\""),
+            (ActionErrorCode::ConfigMissing, "\"configMissing\""),
+            (ActionErrorCode::InvalidInput, "\"invalidInput\""),
+            (ActionErrorCode::Cancelled, "\"cancelled\""),
+            (ActionErrorCode::Busy, "\"busy\""),
+            (ActionErrorCode::Timeout, "\"timeout\""),
+            (ActionErrorCode::Storage, "\"storage\""),
+            (ActionErrorCode::Failed, "\"failed\""),
+        ];
+        for (code, expected) in codes {
+            assert_eq!(serde_json::to_string(&code).unwrap(), expected);
+        }
+    }
+}
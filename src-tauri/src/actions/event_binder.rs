@@ -1,72 +1,225 @@
 //! Event Binder
 //!
 //! Routes device events to their configured actions based on the active profile.
+//! Bindings come from the profile's active workspace; the legacy top-level
+//! `buttons`/`encoders` are only used for profiles without workspaces.
+//!
+//! While shift is held (via the profile's shift modifier or `set_shift`),
+//! controls resolve to their `shift_*` actions.
 
 use super::types::Action;
-use crate::config::types::Profile;
-use crate::hid::types::{DeviceEvent, EncoderType};
+use crate::config::types::{ButtonConfig, EncoderConfig, Profile, RepeatConfig, ShiftModifier};
+use crate::hid::types::{ButtonEventType, DeviceEvent, EncoderEventType, EncoderType};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Window within which consecutive encoder ticks count toward the deadband
+const TICK_WINDOW: Duration = Duration::from_millis(300);
+
+/// Accumulated rotation ticks for one encoder
+#[derive(Debug, Clone, Copy)]
+struct TickState {
+    direction: EncoderEventType,
+    count: u8,
+    last_tick: Instant,
+}
 
 /// Maps device events to actions based on profile configuration
 pub struct EventBinder {
     /// Currently bound profile
     profile: Option<Profile>,
+    /// Per-encoder rotation ticks for deadband filtering (encoder index -> state)
+    encoder_ticks: HashMap<usize, TickState>,
+    /// Whether the shift layer is active
+    shift: bool,
 }
 
 impl EventBinder {
     /// Create a new event binder
     pub fn new() -> Self {
-        Self { profile: None }
+        Self {
+            profile: None,
+            encoder_ticks: HashMap::new(),
+            shift: false,
+        }
     }
 
     /// Bind a profile for event routing
     pub fn bind_profile(&mut self, profile: Profile) {
         log::info!("Binding profile: {}", profile.name);
         self.profile = Some(profile);
+        self.encoder_ticks.clear();
+        self.shift = false;
+    }
+
+    /// Bind the latest copy of a profile
+    ///
+    /// If it is the profile already bound, shift and encoder tick state carry
+    /// over; a different profile starts fresh as with `bind_profile`.
+    pub fn refresh_profile(&mut self, profile: Profile) {
+        match self.profile {
+            Some(ref mut bound) if bound.id == profile.id => *bound = profile,
+            _ => self.bind_profile(profile),
+        }
     }
 
     /// Unbind the current profile
     pub fn unbind(&mut self) {
         log::info!("Unbinding profile");
         self.profile = None;
+        self.encoder_ticks.clear();
+        self.shift = false;
+    }
+
+    /// Turn the shift layer on or off
+    pub fn set_shift(&mut self, held: bool) {
+        if self.shift != held {
+            log::debug!("Shift {}", if held { "held" } else { "released" });
+        }
+        self.shift = held;
+    }
+
+    /// Check if the shift layer is active
+    pub fn is_shift_held(&self) -> bool {
+        self.shift
+    }
+
+    /// Record a rotation tick and return true once `min_ticks` consecutive
+    /// same-direction ticks have accumulated within the tick window.
+    /// A direction change or a gap longer than the window restarts the count.
+    fn accumulate_tick(&mut self, index: usize, direction: EncoderEventType, min_ticks: u8) -> bool {
+        let now = Instant::now();
+        let state = self.encoder_ticks.entry(index).or_insert(TickState {
+            direction,
+            count: 0,
+            last_tick: now,
+        });
+
+        if state.direction != direction || now.duration_since(state.last_tick) > TICK_WINDOW {
+            state.direction = direction;
+            state.count = 0;
+        }
+        state.count = state.count.saturating_add(1);
+        state.last_tick = now;
+
+        if state.count >= min_ticks {
+            state.count = 0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Button and encoder bindings of the bound profile's active workspace
+    fn bindings(profile: &Profile) -> (&[ButtonConfig], &[EncoderConfig]) {
+        if profile.workspaces.is_empty() {
+            return (&profile.buttons, &profile.encoders);
+        }
+        match profile.active_workspace() {
+            Some(workspace) => (&workspace.buttons, &workspace.encoders),
+            None => (&[], &[]),
+        }
     }
 
     /// Get the action for a device event
-    pub fn get_action_for_event(&self, event: &DeviceEvent) -> Option<Action> {
+    ///
+    /// Encoder rotations are filtered through the encoder's `min_ticks` deadband,
+    /// so this may return `None` for a tick that is still accumulating. Events
+    /// from the shift modifier only toggle shift and never return an action.
+    pub fn get_action_for_event(&mut self, event: &DeviceEvent) -> Option<Action> {
         let profile = self.profile.as_ref()?;
+        let shift = profile.shift;
+
+        if shift.modifier.is_some_and(|modifier| is_modifier_event(modifier, event)) {
+            match event {
+                DeviceEvent::Button { event_type: ButtonEventType::Press, .. }
+                | DeviceEvent::Encoder { event_type: EncoderEventType::Press, .. } => self.set_shift(true),
+                DeviceEvent::Button { event_type: ButtonEventType::Release, .. }
+                | DeviceEvent::Encoder { event_type: EncoderEventType::Release, .. } => self.set_shift(false),
+                _ => {}
+            }
+            return None;
+        }
+
+        let held = self.shift;
+        let pick = |normal: &Option<Action>, shifted: &Option<Action>| pick(held, shift.fall_through, normal, shifted);
+
+        let (buttons, encoders) = Self::bindings(profile);
 
         match event {
             DeviceEvent::Button { index, event_type, .. } => {
                 // Find button config by index field (button_type is informational)
-                let button_config = profile.buttons.iter().find(|b| b.index == *index as usize)?;
+                let button_config = buttons.iter().find(|b| b.index == *index as usize)?;
 
                 match event_type {
-                    crate::hid::types::ButtonEventType::Press => button_config.action.clone(),
-                    crate::hid::types::ButtonEventType::Release => None, // Release not supported as direct field
-                    crate::hid::types::ButtonEventType::LongPress => button_config.long_press_action.clone(),
+                    ButtonEventType::Press => pick(&button_config.action, &button_config.shift_action),
+                    ButtonEventType::Release => None, // Release not supported as direct field
+                    ButtonEventType::LongPress => {
+                        pick(&button_config.long_press_action, &button_config.shift_long_press_action)
+                    }
                 }
             }
-            DeviceEvent::Encoder { encoder_type, event_type } => {
-                // Map encoder_type to index: Main=0, Side1=1, Side2=2
-                let index = match encoder_type {
-                    EncoderType::Main => 0,
-                    EncoderType::Side1 => 1,
-                    EncoderType::Side2 => 2,
-                };
+            DeviceEvent::Encoder { encoder_type, event_type, .. } => {
+                let index = encoder_index(*encoder_type);
 
                 // Find encoder config by index field
-                let encoder_config = profile.encoders.iter().find(|e| e.index == index)?;
+                let encoder_config = encoders.iter().find(|e| e.index == index)?;
+                let min_ticks = encoder_config.min_ticks;
+
+                let action = match event_type {
+                    EncoderEventType::RotateCW => {
+                        pick(&encoder_config.clockwise_action, &encoder_config.shift_clockwise_action)
+                    }
+                    EncoderEventType::RotateCCW => pick(
+                        &encoder_config.counter_clockwise_action,
+                        &encoder_config.shift_counter_clockwise_action,
+                    ),
+                    EncoderEventType::Press => {
+                        pick(&encoder_config.press_action, &encoder_config.shift_press_action)
+                    }
+                    EncoderEventType::Release => None, // Release not supported as direct field
+                    EncoderEventType::LongPress => {
+                        pick(&encoder_config.long_press_action, &encoder_config.shift_long_press_action)
+                    }
+                };
 
-                match event_type {
-                    crate::hid::types::EncoderEventType::RotateCW => encoder_config.clockwise_action.clone(),
-                    crate::hid::types::EncoderEventType::RotateCCW => encoder_config.counter_clockwise_action.clone(),
-                    crate::hid::types::EncoderEventType::Press => encoder_config.press_action.clone(),
-                    crate::hid::types::EncoderEventType::Release => None, // Release not supported as direct field
-                    crate::hid::types::EncoderEventType::LongPress => encoder_config.long_press_action.clone(),
+                let is_rotation = matches!(event_type, EncoderEventType::RotateCW | EncoderEventType::RotateCCW);
+                if is_rotation && min_ticks > 1 && !self.accumulate_tick(index, *event_type, min_ticks) {
+                    return None;
                 }
+
+                action
             }
+            DeviceEvent::Unknown { .. } => None,
         }
     }
 
+    /// Action and repeat settings for a button press, if the button repeats
+    ///
+    /// Resolves the action the same way `get_action_for_event` does for the
+    /// press, so a held shift repeats the shift action, but without changing
+    /// any state. Presses of the shift modifier never repeat.
+    pub fn repeat_for_event(&self, event: &DeviceEvent) -> Option<(Action, RepeatConfig)> {
+        let profile = self.profile.as_ref()?;
+        let DeviceEvent::Button { index, event_type: ButtonEventType::Press, .. } = event else {
+            return None;
+        };
+        if profile.shift.modifier.is_some_and(|modifier| is_modifier_event(modifier, event)) {
+            return None;
+        }
+
+        let (buttons, _) = Self::bindings(profile);
+        let button_config = buttons.iter().find(|b| b.index == *index as usize)?;
+        let repeat = button_config.repeat?;
+        let action = pick(
+            self.shift,
+            profile.shift.fall_through,
+            &button_config.action,
+            &button_config.shift_action,
+        )?;
+        Some((action, repeat))
+    }
+
     /// Check if a profile is currently bound
     pub fn has_profile(&self) -> bool {
         self.profile.is_some()
@@ -78,6 +231,41 @@ impl EventBinder {
     }
 }
 
+/// Shift action if shift is held, else the normal one; a missing shift
+/// action falls through to the normal one only if the profile allows it
+fn pick(held: bool, fall_through: bool, normal: &Option<Action>, shifted: &Option<Action>) -> Option<Action> {
+    match (held, shifted) {
+        (false, _) => normal.clone(),
+        (true, Some(action)) => Some(action.clone()),
+        (true, None) if fall_through => normal.clone(),
+        (true, None) => None,
+    }
+}
+
+/// Map encoder_type to index: Main=0, Side1=1, Side2=2
+pub(crate) fn encoder_index(encoder_type: EncoderType) -> usize {
+    match encoder_type {
+        EncoderType::Main => 0,
+        EncoderType::Side1 => 1,
+        EncoderType::Side2 => 2,
+    }
+}
+
+/// Whether an event comes from the shift modifier control
+///
+/// For an encoder-push modifier, only presses count; turning that encoder
+/// still runs its rotation actions.
+fn is_modifier_event(modifier: ShiftModifier, event: &DeviceEvent) -> bool {
+    match (modifier, event) {
+        (ShiftModifier::Button { index }, DeviceEvent::Button { index: pressed, .. }) => index == *pressed,
+        (ShiftModifier::EncoderPress { index }, DeviceEvent::Encoder { encoder_type, event_type, .. }) => {
+            encoder_index(*encoder_type) == index
+                && !matches!(event_type, EncoderEventType::RotateCW | EncoderEventType::RotateCCW)
+        }
+        _ => false,
+    }
+}
+
 impl Default for EventBinder {
     fn default() -> Self {
         Self::new()
@@ -88,7 +276,7 @@ impl Default for EventBinder {
 mod tests {
     use super::*;
     use crate::actions::types::{Action, KeyboardAction, MediaAction, MediaActionType};
-    use crate::config::types::{ButtonConfig, EncoderConfig, Profile};
+    use crate::config::types::{ButtonConfig, EncoderConfig, Profile, ShiftConfig};
     use crate::hid::types::{ButtonEventType, ButtonType, EncoderEventType, EncoderType, DeviceEvent};
 
     /// Create a test profile with specific button and encoder configurations
@@ -97,12 +285,24 @@ mod tests {
         let button0 = ButtonConfig {
             index: 0,
             action: Some(Action::Keyboard(KeyboardAction {
-                key: "A".to_string(),
+                id: None,
+                name: None,
+                icon: None,
+                enabled: None,
+                keys: "A".to_string(),
                 modifiers: vec![],
+                hold_duration: None,
+                sequence: Vec::new(),
             })),
             long_press_action: Some(Action::Keyboard(KeyboardAction {
-                key: "C".to_string(),
+                id: None,
+                name: None,
+                icon: None,
+                enabled: None,
+                keys: "C".to_string(),
                 modifiers: vec!["ctrl".to_string()],
+                hold_duration: None,
+                sequence: Vec::new(),
             })),
             ..Default::default()
         };
@@ -112,6 +312,7 @@ mod tests {
             index: 2,
             action: Some(Action::Media(MediaAction {
                 action: MediaActionType::PlayPause,
+                ..Default::default()
             })),
             ..Default::default()
         };
@@ -120,13 +321,16 @@ mod tests {
         let encoder0 = EncoderConfig {
             index: 0,
             press_action: Some(Action::Media(MediaAction {
-                action: MediaActionType::VolumeMute,
+                action: MediaActionType::Mute,
+                ..Default::default()
             })),
             clockwise_action: Some(Action::Media(MediaAction {
                 action: MediaActionType::VolumeUp,
+                ..Default::default()
             })),
             counter_clockwise_action: Some(Action::Media(MediaAction {
                 action: MediaActionType::VolumeDown,
+                ..Default::default()
             })),
             ..Default::default()
         };
@@ -136,6 +340,7 @@ mod tests {
             index: 1,
             long_press_action: Some(Action::Media(MediaAction {
                 action: MediaActionType::Stop,
+                ..Default::default()
             })),
             ..Default::default()
         };
@@ -236,7 +441,7 @@ mod tests {
 
         match action.unwrap() {
             Action::Keyboard(ka) => {
-                assert_eq!(ka.key, "A");
+                assert_eq!(ka.keys, "A");
                 assert!(ka.modifiers.is_empty());
             }
             _ => panic!("Expected Keyboard action"),
@@ -274,7 +479,7 @@ mod tests {
 
         match action.unwrap() {
             Action::Keyboard(ka) => {
-                assert_eq!(ka.key, "C");
+                assert_eq!(ka.keys, "C");
                 assert_eq!(ka.modifiers, vec!["ctrl".to_string()]);
             }
             _ => panic!("Expected Keyboard action"),
@@ -357,6 +562,7 @@ mod tests {
 
         let event = DeviceEvent::Encoder {
             encoder_type: EncoderType::Main,  // Maps to index 0
+            velocity: 1,
             event_type: EncoderEventType::RotateCW,
         };
 
@@ -378,6 +584,7 @@ mod tests {
 
         let event = DeviceEvent::Encoder {
             encoder_type: EncoderType::Main,  // Maps to index 0
+            velocity: 1,
             event_type: EncoderEventType::RotateCCW,
         };
 
@@ -399,6 +606,7 @@ mod tests {
 
         let event = DeviceEvent::Encoder {
             encoder_type: EncoderType::Main,  // Maps to index 0
+            velocity: 1,
             event_type: EncoderEventType::Press,
         };
 
@@ -407,7 +615,7 @@ mod tests {
 
         match action.unwrap() {
             Action::Media(ma) => {
-                assert_eq!(ma.action, MediaActionType::VolumeMute);
+                assert_eq!(ma.action, MediaActionType::Mute);
             }
             _ => panic!("Expected Media action"),
         }
@@ -420,6 +628,7 @@ mod tests {
 
         let event = DeviceEvent::Encoder {
             encoder_type: EncoderType::Side1,  // Maps to index 1
+            velocity: 1,
             event_type: EncoderEventType::LongPress,
         };
 
@@ -442,6 +651,7 @@ mod tests {
         // Encoder Main (index 0) has no release action
         let event = DeviceEvent::Encoder {
             encoder_type: EncoderType::Main,
+            velocity: 1,
             event_type: EncoderEventType::Release,
         };
 
@@ -456,17 +666,319 @@ mod tests {
         // Side2 encoder (index 2) is not configured in test profile
         let event = DeviceEvent::Encoder {
             encoder_type: EncoderType::Side2,
+            velocity: 1,
             event_type: EncoderEventType::RotateCW,
         };
 
         assert!(binder.get_action_for_event(&event).is_none());
     }
 
+    // ========== Encoder Deadband Tests ==========
+
+    /// Bind a test profile whose main encoder requires `min_ticks` ticks
+    fn bind_with_min_ticks(binder: &mut EventBinder, min_ticks: u8) {
+        let mut profile = create_test_profile();
+        let encoder = profile.workspaces[0].encoders.iter_mut().find(|e| e.index == 0).unwrap();
+        encoder.min_ticks = min_ticks;
+        binder.bind_profile(profile);
+    }
+
+    fn rotate(event_type: EncoderEventType) -> DeviceEvent {
+        DeviceEvent::Encoder {
+            encoder_type: EncoderType::Main,
+            velocity: 1,
+            event_type,
+        }
+    }
+
+    #[test]
+    fn test_encoder_min_ticks_defaults_to_one() {
+        let config: EncoderConfig = serde_json::from_str(r#"{"index":0}"#).unwrap();
+        assert_eq!(config.min_ticks, 1);
+    }
+
+    #[test]
+    fn test_encoder_deadband_fires_after_min_ticks() {
+        let mut binder = EventBinder::new();
+        bind_with_min_ticks(&mut binder, 3);
+
+        assert!(binder.get_action_for_event(&rotate(EncoderEventType::RotateCW)).is_none());
+        assert!(binder.get_action_for_event(&rotate(EncoderEventType::RotateCW)).is_none());
+        assert!(binder.get_action_for_event(&rotate(EncoderEventType::RotateCW)).is_some());
+        // Count restarts after firing
+        assert!(binder.get_action_for_event(&rotate(EncoderEventType::RotateCW)).is_none());
+    }
+
+    #[test]
+    fn test_encoder_deadband_resets_on_direction_change() {
+        let mut binder = EventBinder::new();
+        bind_with_min_ticks(&mut binder, 2);
+
+        assert!(binder.get_action_for_event(&rotate(EncoderEventType::RotateCW)).is_none());
+        assert!(binder.get_action_for_event(&rotate(EncoderEventType::RotateCCW)).is_none());
+        assert!(binder.get_action_for_event(&rotate(EncoderEventType::RotateCW)).is_none());
+        assert!(binder.get_action_for_event(&rotate(EncoderEventType::RotateCW)).is_some());
+    }
+
+    #[test]
+    fn test_encoder_deadband_does_not_affect_press() {
+        let mut binder = EventBinder::new();
+        bind_with_min_ticks(&mut binder, 3);
+
+        assert!(binder.get_action_for_event(&rotate(EncoderEventType::Press)).is_some());
+    }
+
+    // ========== Workspace Tests ==========
+
+    /// Profile whose bindings live only in `workspaces[0]`, as for profiles
+    /// created by the app
+    fn workspace_only_profile() -> Profile {
+        let mut profile = create_test_profile();
+        profile.buttons.clear();
+        profile.encoders.clear();
+        profile
+    }
+
+    #[test]
+    fn test_workspace_only_button_press_resolves() {
+        let mut binder = EventBinder::new();
+        binder.bind_profile(workspace_only_profile());
+
+        let event = DeviceEvent::Button {
+            index: 0,
+            button_type: ButtonType::Lcd,
+            event_type: ButtonEventType::Press,
+        };
+        match binder.get_action_for_event(&event) {
+            Some(Action::Keyboard(ka)) => assert_eq!(ka.keys, "A"),
+            other => panic!("Expected Keyboard action, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_workspace_only_encoder_rotation_resolves() {
+        let mut binder = EventBinder::new();
+        binder.bind_profile(workspace_only_profile());
+
+        match binder.get_action_for_event(&rotate(EncoderEventType::RotateCW)) {
+            Some(Action::Media(ma)) => assert_eq!(ma.action, MediaActionType::VolumeUp),
+            other => panic!("Expected Media action, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_legacy_bindings_used_without_workspaces() {
+        let mut profile = create_test_profile();
+        profile.workspaces.clear();
+
+        let mut binder = EventBinder::new();
+        binder.bind_profile(profile);
+
+        let event = DeviceEvent::Button {
+            index: 2,
+            button_type: ButtonType::Lcd,
+            event_type: ButtonEventType::Press,
+        };
+        assert!(matches!(binder.get_action_for_event(&event), Some(Action::Media(_))));
+    }
+
+    #[test]
+    fn test_legacy_bindings_ignored_when_workspaces_exist() {
+        let mut profile = create_test_profile();
+        profile.workspaces[0].buttons.clear();
+
+        let mut binder = EventBinder::new();
+        binder.bind_profile(profile);
+
+        let event = DeviceEvent::Button {
+            index: 0,
+            button_type: ButtonType::Lcd,
+            event_type: ButtonEventType::Press,
+        };
+        assert!(binder.get_action_for_event(&event).is_none());
+    }
+
+    #[test]
+    fn test_bindings_follow_active_workspace() {
+        let mut profile = create_test_profile();
+        let mut second = crate::config::types::Workspace::new("Second".to_string());
+        second.buttons = vec![ButtonConfig {
+            index: 0,
+            action: Some(Action::Media(MediaAction {
+                action: MediaActionType::Next,
+                ..Default::default()
+            })),
+            ..Default::default()
+        }];
+        profile.workspaces.push(second);
+        profile.active_workspace_index = 1;
+
+        let mut binder = EventBinder::new();
+        binder.bind_profile(profile);
+
+        let press = |index| DeviceEvent::Button {
+            index,
+            button_type: ButtonType::Lcd,
+            event_type: ButtonEventType::Press,
+        };
+        match binder.get_action_for_event(&press(0)) {
+            Some(Action::Media(ma)) => assert_eq!(ma.action, MediaActionType::Next),
+            other => panic!("Expected the second workspace's action, got {:?}", other),
+        }
+        // Button 2 is only bound in the first workspace
+        assert!(binder.get_action_for_event(&press(2)).is_none());
+    }
+
+    // ========== Shift Layer Tests ==========
+
+    fn media(action: MediaActionType) -> Option<Action> {
+        Some(Action::Media(MediaAction {
+            action,
+            ..Default::default()
+        }))
+    }
+
+    fn media_type(action: Option<Action>) -> Option<MediaActionType> {
+        match action {
+            Some(Action::Media(ma)) => Some(ma.action),
+            Some(other) => panic!("Unexpected action: {:?}", other),
+            None => None,
+        }
+    }
+
+    fn button(index: u8, event_type: ButtonEventType) -> DeviceEvent {
+        DeviceEvent::Button {
+            index,
+            button_type: ButtonType::Lcd,
+            event_type,
+        }
+    }
+
+    /// Test profile with button 5 as shift, a shifted action on button 2 and
+    /// shifted rotation on the main encoder
+    fn bind_shift_profile(binder: &mut EventBinder, fall_through: bool) {
+        let mut profile = workspace_only_profile();
+        profile.shift = ShiftConfig {
+            modifier: Some(ShiftModifier::Button { index: 5 }),
+            fall_through,
+        };
+        let workspace = &mut profile.workspaces[0];
+        workspace.buttons.push(ButtonConfig {
+            index: 5,
+            action: media(MediaActionType::Stop),
+            ..Default::default()
+        });
+        workspace.buttons.iter_mut().find(|b| b.index == 2).unwrap().shift_action =
+            media(MediaActionType::Next);
+        workspace.encoders.iter_mut().find(|e| e.index == 0).unwrap().shift_clockwise_action =
+            media(MediaActionType::Previous);
+        binder.bind_profile(profile);
+    }
+
+    #[test]
+    fn test_shift_press_routes_to_shift_action() {
+        let mut binder = EventBinder::new();
+        bind_shift_profile(&mut binder, false);
+
+        assert_eq!(media_type(binder.get_action_for_event(&button(2, ButtonEventType::Press))), Some(MediaActionType::PlayPause));
+
+        // The modifier itself runs nothing
+        assert!(binder.get_action_for_event(&button(5, ButtonEventType::Press)).is_none());
+        assert!(binder.is_shift_held());
+        assert_eq!(media_type(binder.get_action_for_event(&button(2, ButtonEventType::Press))), Some(MediaActionType::Next));
+
+        assert!(binder.get_action_for_event(&button(5, ButtonEventType::Release)).is_none());
+        assert!(!binder.is_shift_held());
+        assert_eq!(media_type(binder.get_action_for_event(&button(2, ButtonEventType::Press))), Some(MediaActionType::PlayPause));
+    }
+
+    #[test]
+    fn test_shift_rotate_routes_to_shift_action() {
+        let mut binder = EventBinder::new();
+        bind_shift_profile(&mut binder, false);
+        binder.get_action_for_event(&button(5, ButtonEventType::Press));
+
+        assert_eq!(
+            media_type(binder.get_action_for_event(&rotate(EncoderEventType::RotateCW))),
+            Some(MediaActionType::Previous)
+        );
+    }
+
+    #[test]
+    fn test_missing_shift_action_is_noop_by_default() {
+        let mut binder = EventBinder::new();
+        bind_shift_profile(&mut binder, false);
+        binder.set_shift(true);
+
+        // Counter-clockwise has no shifted action
+        assert!(binder.get_action_for_event(&rotate(EncoderEventType::RotateCCW)).is_none());
+        assert!(binder.get_action_for_event(&button(0, ButtonEventType::Press)).is_none());
+    }
+
+    #[test]
+    fn test_missing_shift_action_falls_through_when_enabled() {
+        let mut binder = EventBinder::new();
+        bind_shift_profile(&mut binder, true);
+        binder.set_shift(true);
+
+        assert_eq!(
+            media_type(binder.get_action_for_event(&rotate(EncoderEventType::RotateCCW))),
+            Some(MediaActionType::VolumeDown)
+        );
+        // Shift actions still win where they exist
+        assert_eq!(media_type(binder.get_action_for_event(&button(2, ButtonEventType::Press))), Some(MediaActionType::Next));
+    }
+
+    #[test]
+    fn test_encoder_push_modifier_keeps_rotation() {
+        let mut profile = workspace_only_profile();
+        profile.shift.modifier = Some(ShiftModifier::EncoderPress { index: 0 });
+        let mut binder = EventBinder::new();
+        binder.bind_profile(profile);
+
+        assert!(binder.get_action_for_event(&rotate(EncoderEventType::Press)).is_none());
+        assert!(binder.is_shift_held());
+        // No shifted rotation is configured and fall-through is off
+        assert!(binder.get_action_for_event(&rotate(EncoderEventType::RotateCW)).is_none());
+
+        binder.get_action_for_event(&rotate(EncoderEventType::Release));
+        assert_eq!(
+            media_type(binder.get_action_for_event(&rotate(EncoderEventType::RotateCW))),
+            Some(MediaActionType::VolumeUp)
+        );
+    }
+
+    #[test]
+    fn test_refresh_same_profile_keeps_shift() {
+        let mut binder = EventBinder::new();
+        bind_shift_profile(&mut binder, false);
+        binder.set_shift(true);
+
+        let mut updated = binder.profile.clone().unwrap();
+        updated.name = "Renamed".to_string();
+        binder.refresh_profile(updated);
+        assert!(binder.is_shift_held());
+        assert_eq!(binder.get_profile_name(), Some("Renamed"));
+
+        binder.refresh_profile(Profile::new("Other".to_string()));
+        assert!(!binder.is_shift_held());
+    }
+
+    #[test]
+    fn test_rebind_clears_shift() {
+        let mut binder = EventBinder::new();
+        bind_shift_profile(&mut binder, false);
+        binder.set_shift(true);
+
+        binder.bind_profile(create_test_profile());
+        assert!(!binder.is_shift_held());
+    }
+
     // ========== No Profile Bound Tests ==========
 
     #[test]
     fn test_button_event_without_profile_returns_none() {
-        let binder = EventBinder::new();
+        let mut binder = EventBinder::new();
 
         let event = DeviceEvent::Button {
             index: 0,
@@ -479,10 +991,11 @@ mod tests {
 
     #[test]
     fn test_encoder_event_without_profile_returns_none() {
-        let binder = EventBinder::new();
+        let mut binder = EventBinder::new();
 
         let event = DeviceEvent::Encoder {
             encoder_type: EncoderType::Main,
+            velocity: 1,
             event_type: EncoderEventType::RotateCW,
         };
 
@@ -516,10 +1029,11 @@ mod tests {
 
         // Create a different profile with different action
         let mut new_profile = Profile::new("New Profile".to_string());
-        new_profile.buttons = vec![ButtonConfig {
+        new_profile.workspaces[0].buttons = vec![ButtonConfig {
             index: 0,
             action: Some(Action::Media(MediaAction {
-                action: MediaActionType::NextTrack,
+                action: MediaActionType::Next,
+                ..Default::default()
             })),
             ..Default::default()
         }];
@@ -534,9 +1048,48 @@ mod tests {
 
         match binder.get_action_for_event(&event).unwrap() {
             Action::Media(ma) => {
-                assert_eq!(ma.action, MediaActionType::NextTrack);
+                assert_eq!(ma.action, MediaActionType::Next);
             }
             _ => panic!("Expected Media action after rebind"),
         }
     }
+
+    // ========== Repeat Tests ==========
+
+    /// The shift test profile with every button set to repeat
+    fn bind_repeat_profile(binder: &mut EventBinder) {
+        bind_shift_profile(binder, false);
+        let mut profile = binder.profile.take().unwrap();
+        for button in profile.workspaces[0].buttons.iter_mut() {
+            button.repeat = Some(RepeatConfig { interval_ms: 100, initial_delay_ms: None });
+        }
+        binder.bind_profile(profile);
+    }
+
+    #[test]
+    fn test_repeat_for_event_follows_shift() {
+        let mut binder = EventBinder::new();
+        bind_repeat_profile(&mut binder);
+
+        let (action, repeat) = binder.repeat_for_event(&button(2, ButtonEventType::Press)).unwrap();
+        assert_eq!(media_type(Some(action)), Some(MediaActionType::PlayPause));
+        assert_eq!(repeat.interval_ms, 100);
+
+        binder.set_shift(true);
+        let (action, _) = binder.repeat_for_event(&button(2, ButtonEventType::Press)).unwrap();
+        assert_eq!(media_type(Some(action)), Some(MediaActionType::Next));
+        // No shifted action and no fall-through: nothing repeats
+        assert!(binder.repeat_for_event(&button(0, ButtonEventType::Press)).is_none());
+    }
+
+    #[test]
+    fn test_repeat_for_event_skips_modifier_and_releases() {
+        let mut binder = EventBinder::new();
+        bind_repeat_profile(&mut binder);
+
+        assert!(binder.repeat_for_event(&button(5, ButtonEventType::Press)).is_none());
+        assert!(binder.repeat_for_event(&button(2, ButtonEventType::Release)).is_none());
+        assert!(binder.repeat_for_event(&rotate(EncoderEventType::RotateCW)).is_none());
+        assert!(!binder.is_shift_held());
+    }
 }
@@ -0,0 +1,297 @@
+//! Button Feedback Scheduler
+//!
+//! Keeps the values of the shown workspace's feedback buttons up to date.
+//! Each button gets one task that polls its source on the button's interval
+//! and draws the value only when it changed. A poll that runs past the next
+//! tick makes the task skip that tick rather than queue another poll, so a
+//! slow source never has overlapping updates.
+//!
+//! `sync` is called wherever a workspace's buttons are put on the device
+//! (profile activation, workspace switches, reconnects): it stops every task
+//! and starts those of the new buttons, whose first poll redraws them.
+
+use crate::actions::handlers::feedback::{poll_value, show_value, validate};
+use crate::actions::types::{Action, FeedbackAction};
+use crate::actions::{CancellationToken, IntegrationConfig};
+use crate::config::types::ButtonConfig;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+/// Feedback tasks of the workspace on the device
+static SCHEDULER: LazyLock<Mutex<FeedbackScheduler>> = LazyLock::new(|| Mutex::new(FeedbackScheduler::new()));
+
+/// Per-button feedback tasks
+#[derive(Default)]
+pub struct FeedbackScheduler {
+    tasks: HashMap<u8, JoinHandle<()>>,
+}
+
+impl FeedbackScheduler {
+    /// Create a scheduler with no running tasks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start polling a value for a button, replacing any task it already has
+    ///
+    /// `show` is called on a blocking thread with each changed value and
+    /// returns whether it was drawn; a value that failed to draw is retried
+    /// on the next poll.
+    pub fn start<P, Fut, S>(&mut self, index: u8, interval: Duration, poll: P, show: S)
+    where
+        P: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = String> + Send + 'static,
+        S: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.stop(index);
+
+        let show = Arc::new(show);
+        let task = tauri::async_runtime::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            let mut shown: Option<String> = None;
+            loop {
+                ticker.tick().await;
+                let value = poll().await;
+                if shown.as_ref() == Some(&value) {
+                    continue;
+                }
+                let show = Arc::clone(&show);
+                let drawn_value = value.clone();
+                let drawn = tauri::async_runtime::spawn_blocking(move || show(&drawn_value))
+                    .await
+                    .unwrap_or(false);
+                if drawn {
+                    shown = Some(value);
+                }
+            }
+        });
+        self.tasks.insert(index, task);
+    }
+
+    /// Stop polling for a button
+    pub fn stop(&mut self, index: u8) {
+        if let Some(task) = self.tasks.remove(&index) {
+            task.abort();
+        }
+    }
+
+    /// Stop every running task
+    pub fn stop_all(&mut self) {
+        for (_, task) in self.tasks.drain() {
+            task.abort();
+        }
+    }
+
+    /// Whether a button currently has a feedback task
+    pub fn is_running(&self, index: u8) -> bool {
+        self.tasks.contains_key(&index)
+    }
+}
+
+impl Drop for FeedbackScheduler {
+    fn drop(&mut self) {
+        self.stop_all();
+    }
+}
+
+/// Feedback actions bound to a workspace's buttons, one per target button
+///
+/// A later button targeting the same index replaces an earlier one.
+fn feedback_actions(buttons: &[ButtonConfig]) -> HashMap<u8, FeedbackAction> {
+    buttons
+        .iter()
+        .filter_map(|button| match &button.action {
+            Some(Action::ButtonFeedback(config)) => Some(config),
+            _ => None,
+        })
+        .filter(|config| match validate(config) {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!("Skipping button feedback: {}", e);
+                false
+            }
+        })
+        .map(|config| (config.button_index, config.clone()))
+        .collect()
+}
+
+/// Run the feedback of the buttons now on the device, stopping all others
+///
+/// Needs the device handle in `integrations`; without it every task stops.
+pub fn sync(buttons: &[ButtonConfig], integrations: &IntegrationConfig) {
+    let mut scheduler = SCHEDULER.lock();
+    scheduler.stop_all();
+
+    let Some(device) = integrations.device.as_ref() else {
+        return;
+    };
+    for (index, config) in feedback_actions(buttons) {
+        log::debug!("Starting button feedback on button {} every {:?}", index, config.interval());
+
+        let interval = config.interval();
+        let config = Arc::new(config);
+        let poll_config = Arc::clone(&config);
+        let poll_integrations = integrations.clone();
+        let hid = Arc::clone(&device.hid);
+        scheduler.start(
+            index,
+            interval,
+            move || {
+                let config = Arc::clone(&poll_config);
+                let integrations = poll_integrations.clone();
+                async move { poll_value(&config, &integrations, &CancellationToken::new()).await }
+            },
+            move |value| match show_value(&hid, index, value, &config.style) {
+                Ok(()) => true,
+                Err(e) => {
+                    log::debug!("Failed to show feedback on button {}: {}", index, e);
+                    false
+                }
+            },
+        );
+    }
+}
+
+/// Stop every feedback task
+pub fn stop_all() {
+    SCHEDULER.lock().stop_all();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::types::DelayAction;
+    use crate::image::text::TextStyle;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const TICK: Duration = Duration::from_millis(20);
+
+    fn feedback(button_index: u8) -> Action {
+        Action::ButtonFeedback(FeedbackAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            button_index,
+            source: Box::new(Action::Delay(DelayAction {
+                id: None,
+                name: None,
+                icon: None,
+                enabled: None,
+                ms: 0,
+            })),
+            text: None,
+            interval_ms: 1000,
+            style: TextStyle::default(),
+        })
+    }
+
+    fn button(index: usize, action: Option<Action>) -> ButtonConfig {
+        ButtonConfig {
+            index,
+            action,
+            ..Default::default()
+        }
+    }
+
+    /// Poll that yields the given values in turn, repeating the last
+    fn values(list: &'static [&'static str]) -> impl Fn() -> std::future::Ready<String> + Send + 'static {
+        let next = AtomicUsize::new(0);
+        move || {
+            let i = next.fetch_add(1, Ordering::SeqCst).min(list.len() - 1);
+            std::future::ready(list[i].to_string())
+        }
+    }
+
+    // ========== Scheduling Tests ==========
+
+    #[tokio::test]
+    async fn test_unchanged_value_is_drawn_once() {
+        let mut scheduler = FeedbackScheduler::new();
+        let drawn = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&drawn);
+        scheduler.start(0, TICK, values(&["1", "1", "1", "2", "2"]), move |value| {
+            log.lock().push(value.to_string());
+            true
+        });
+
+        tokio::time::sleep(TICK * 8).await;
+        scheduler.stop_all();
+        assert_eq!(*drawn.lock(), vec!["1".to_string(), "2".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_draw_is_retried() {
+        let mut scheduler = FeedbackScheduler::new();
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let count = Arc::clone(&attempts);
+        scheduler.start(0, TICK, values(&["1"]), move |_| count.fetch_add(1, Ordering::SeqCst) >= 1);
+
+        tokio::time::sleep(TICK * 6).await;
+        scheduler.stop_all();
+        // Failed once, then drawn, then left alone
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_slow_poll_does_not_overlap() {
+        let mut scheduler = FeedbackScheduler::new();
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (poll_running, poll_peak) = (Arc::clone(&running), Arc::clone(&peak));
+        scheduler.start(
+            0,
+            TICK,
+            move || {
+                let (running, peak) = (Arc::clone(&poll_running), Arc::clone(&poll_peak));
+                async move {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(TICK * 3).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    "x".to_string()
+                }
+            },
+            |_| true,
+        );
+
+        tokio::time::sleep(TICK * 10).await;
+        scheduler.stop_all();
+        assert_eq!(peak.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_start_replaces_and_stop_removes() {
+        let mut scheduler = FeedbackScheduler::new();
+        scheduler.start(3, TICK, values(&["a"]), |_| true);
+        scheduler.start(3, TICK, values(&["b"]), |_| true);
+        assert!(scheduler.is_running(3));
+        assert_eq!(scheduler.tasks.len(), 1);
+
+        scheduler.stop(3);
+        assert!(!scheduler.is_running(3));
+    }
+
+    // ========== Button Selection Tests ==========
+
+    #[test]
+    fn test_feedback_actions_from_buttons() {
+        let buttons = vec![
+            button(0, Some(feedback(0))),
+            button(1, None),
+            button(2, Some(feedback(9))),
+            button(3, Some(feedback(4))),
+        ];
+        let actions = feedback_actions(&buttons);
+        let mut indexes: Vec<u8> = actions.keys().copied().collect();
+        indexes.sort();
+        // Index 9 is out of range and skipped
+        assert_eq!(indexes, vec![0, 4]);
+    }
+}
@@ -0,0 +1,131 @@
+//! Brightness Handler
+//!
+//! Sets, raises or lowers the device display brightness and saves the new
+//! level to the app settings. Needs the device handle from the integration
+//! configuration.
+
+use crate::actions::error::ActionError;
+use crate::actions::types::{ActionResult, BrightnessAction, BrightnessOperation};
+use crate::actions::DeviceHandle;
+use crate::hid::protocol::SoomfonProtocol;
+
+/// Describe the brightness change an action would make, without making it
+pub fn describe(config: &BrightnessAction) -> String {
+    match config.operation {
+        BrightnessOperation::Set => format!("Would set brightness to {}", config.step.min(100)),
+        BrightnessOperation::Increase => format!("Would raise brightness by {}", config.step),
+        BrightnessOperation::Decrease => format!("Would lower brightness by {}", config.step),
+    }
+}
+
+/// Execute a brightness action
+pub async fn execute(config: &BrightnessAction, device: Option<&DeviceHandle>) -> ActionResult {
+    log::debug!("Executing brightness action: {:?} {}", config.operation, config.step);
+
+    let Some(device) = device else {
+        return ActionResult::from_error(ActionError::DeviceError("Device control not available".to_string()), 0);
+    };
+
+    let current = device.config.lock().get_brightness();
+    let level = target_level(config.operation, config.step, current);
+
+    {
+        let mut manager = device.hid.lock();
+        // Reopen handle if it was transferred to polling thread
+        if let Err(e) = manager.reopen_for_commands() {
+            return ActionResult::from_error(ActionError::DeviceError(format!("Failed to set brightness: {}", e)), 0);
+        }
+        if let Err(e) = SoomfonProtocol::new(&manager).set_brightness(level) {
+            return ActionResult::from_error(ActionError::DeviceError(format!("Failed to set brightness: {}", e)), 0);
+        }
+    }
+
+    if let Err(e) = device.config.lock().set_brightness(level) {
+        log::warn!("Failed to save brightness: {}", e);
+    }
+    ActionResult::success_with_message(format!("Brightness set to {}", level), 0)
+}
+
+/// New brightness level, clamped to 0-100
+fn target_level(operation: BrightnessOperation, step: u8, current: u8) -> u8 {
+    let level = match operation {
+        BrightnessOperation::Set => step,
+        BrightnessOperation::Increase => current.saturating_add(step),
+        BrightnessOperation::Decrease => current.saturating_sub(step),
+    };
+    level.min(100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ========== Level Tests ==========
+
+    #[test]
+    fn test_target_level() {
+        assert_eq!(target_level(BrightnessOperation::Set, 40, 80), 40);
+        assert_eq!(target_level(BrightnessOperation::Increase, 10, 80), 90);
+        assert_eq!(target_level(BrightnessOperation::Decrease, 10, 80), 70);
+    }
+
+    #[test]
+    fn test_target_level_clamps() {
+        assert_eq!(target_level(BrightnessOperation::Set, 150, 80), 100);
+        assert_eq!(target_level(BrightnessOperation::Increase, 30, 80), 100);
+        assert_eq!(target_level(BrightnessOperation::Increase, 200, 100), 100);
+        assert_eq!(target_level(BrightnessOperation::Decrease, 30, 20), 0);
+    }
+
+    // ========== Execution Tests ==========
+
+    fn increase(step: u8) -> BrightnessAction {
+        BrightnessAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            operation: BrightnessOperation::Increase,
+            step,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_without_device_fails() {
+        let result = execute(&increase(10), None).await;
+        assert_eq!(result.error.as_deref(), Some("Device control not available"));
+    }
+
+    #[tokio::test]
+    async fn test_disconnected_device_keeps_saved_level() {
+        use crate::config::manager::ConfigManager;
+        use crate::config::profiles::ProfileManager;
+        use crate::hid::manager::HidManager;
+        use parking_lot::Mutex;
+        use std::sync::Arc;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let device = DeviceHandle {
+            hid: Arc::new(Mutex::new(HidManager::new())),
+            config: Arc::new(Mutex::new(ConfigManager::new(dir.path().to_path_buf()))),
+            profiles: Arc::new(Mutex::new(ProfileManager::new(dir.path().join("profiles")))),
+        };
+        let before = device.config.lock().get_brightness();
+
+        let result = execute(&increase(10), Some(&device)).await;
+        assert!(result.error.unwrap().starts_with("Failed to set brightness"));
+        assert_eq!(device.config.lock().get_brightness(), before);
+    }
+
+    #[test]
+    fn test_brightness_action_deserialize() {
+        let json = r#"{"type": "brightness", "operation": "decrease", "step": 5}"#;
+        match serde_json::from_str::<crate::actions::types::Action>(json).unwrap() {
+            crate::actions::types::Action::Brightness(ba) => {
+                assert_eq!(ba.operation, BrightnessOperation::Decrease);
+                assert_eq!(ba.step, 5);
+            }
+            other => panic!("Unexpected action: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Clipboard Handler
+//!
+//! Sets, reads or clears the clipboard text, or pastes a snippet into the
+//! focused app by setting the clipboard and sending Ctrl+V (Cmd+V on macOS)
+//! through the keyboard handler.
+//!
+//! A paste with `restore` set puts the previous clipboard text back after a
+//! short delay, giving the target app time to read the snippet first. Only
+//! text is restored; a clipboard that held something else is left cleared.
+
+use crate::actions::handlers::keyboard::press_combo;
+use crate::actions::types::{ActionResult, ClipboardAction, ClipboardOperation, KeyboardAction};
+use arboard::Clipboard;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Time the focused app gets to read a pasted snippet before it is replaced
+const RESTORE_DELAY: Duration = Duration::from_millis(300);
+
+/// Variable the clipboard text is captured into by a `get`
+const CLIPBOARD_VARIABLE: &str = "clipboard";
+
+/// Describe what a clipboard action would do, without doing it
+pub fn describe(config: &ClipboardAction) -> String {
+    match config.operation {
+        ClipboardOperation::SetText => format!("Would copy {} characters to the clipboard", config.text.chars().count()),
+        ClipboardOperation::Get => "Would read the clipboard text".to_string(),
+        ClipboardOperation::Paste if config.restore => format!(
+            "Would paste {} characters, then restore the clipboard",
+            config.text.chars().count()
+        ),
+        ClipboardOperation::Paste => format!("Would paste {} characters", config.text.chars().count()),
+        ClipboardOperation::Clear => "Would clear the clipboard".to_string(),
+    }
+}
+
+/// Execute a clipboard action
+pub async fn execute(config: &ClipboardAction) -> ActionResult {
+    log::debug!("Executing clipboard action: {:?}", config.operation);
+
+    let config = config.clone();
+    // The clipboard and key presses block, and a paste waits out the restore delay
+    let task = tokio::task::spawn_blocking(move || {
+        let mut clipboard =
+            Clipboard::new().map_err(|e| format!("Failed to open clipboard: {}", e))?;
+        run(&mut clipboard, &config)
+    });
+
+    match task.await {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => ActionResult::failure(e, 0),
+        Err(e) => ActionResult::failure(format!("Clipboard task failed: {}", e), 0),
+    }
+}
+
+fn run(clipboard: &mut Clipboard, config: &ClipboardAction) -> Result<ActionResult, String> {
+    match config.operation {
+        ClipboardOperation::SetText => {
+            set_text(clipboard, &config.text)?;
+            Ok(ActionResult::success(0))
+        }
+        ClipboardOperation::Get => {
+            let text = clipboard
+                .get_text()
+                .map_err(|e| format!("Failed to read clipboard: {}", e))?;
+            let variables = HashMap::from([(CLIPBOARD_VARIABLE.to_string(), text.clone().into())]);
+            Ok(ActionResult::success_with_message(text, 0).with_variables(variables))
+        }
+        ClipboardOperation::Paste => paste(clipboard, &config.text, config.restore),
+        ClipboardOperation::Clear => {
+            clipboard
+                .clear()
+                .map_err(|e| format!("Failed to clear clipboard: {}", e))?;
+            Ok(ActionResult::success(0))
+        }
+    }
+}
+
+fn set_text(clipboard: &mut Clipboard, text: &str) -> Result<(), String> {
+    clipboard
+        .set_text(text)
+        .map_err(|e| format!("Failed to set clipboard: {}", e))
+}
+
+fn paste(clipboard: &mut Clipboard, text: &str, restore: bool) -> Result<ActionResult, String> {
+    let previous = if restore { clipboard.get_text().ok() } else { None };
+
+    set_text(clipboard, text)?;
+    let result = press_combo(&paste_keys());
+
+    if restore {
+        std::thread::sleep(RESTORE_DELAY);
+        let restored = match &previous {
+            Some(previous) => clipboard.set_text(previous.as_str()),
+            None => clipboard.clear(),
+        };
+        if let Err(e) = restored {
+            log::warn!("Failed to restore clipboard: {}", e);
+        }
+    }
+    Ok(result)
+}
+
+/// The platform paste shortcut
+fn paste_keys() -> KeyboardAction {
+    let modifier = if cfg!(target_os = "macos") { "cmd" } else { "ctrl" };
+    KeyboardAction {
+        id: None,
+        name: None,
+        icon: None,
+        enabled: None,
+        keys: "v".to_string(),
+        modifiers: vec![modifier.to_string()],
+        hold_duration: None,
+        sequence: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clipboard(operation: ClipboardOperation, text: &str, restore: bool) -> ClipboardAction {
+        ClipboardAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            operation,
+            text: text.to_string(),
+            restore,
+        }
+    }
+
+    // ========== Describe Tests ==========
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(
+            describe(&clipboard(ClipboardOperation::SetText, "hello", false)),
+            "Would copy 5 characters to the clipboard"
+        );
+        assert_eq!(
+            describe(&clipboard(ClipboardOperation::Paste, "héllo", true)),
+            "Would paste 5 characters, then restore the clipboard"
+        );
+        assert_eq!(describe(&clipboard(ClipboardOperation::Clear, "", false)), "Would clear the clipboard");
+    }
+
+    // ========== Paste Keys Tests ==========
+
+    #[test]
+    fn test_paste_keys() {
+        let keys = paste_keys();
+        assert_eq!(keys.keys, "v");
+        let expected = if cfg!(target_os = "macos") { "cmd" } else { "ctrl" };
+        assert_eq!(keys.modifiers, vec![expected.to_string()]);
+    }
+
+    // ========== Serialization Tests ==========
+
+    #[test]
+    fn test_clipboard_action_deserialize() {
+        let action: crate::actions::types::Action = serde_json::from_str(
+            r#"{"type": "clipboard", "operation": "paste", "text": "Thanks!", "restore": true}"#,
+        )
+        .unwrap();
+        match action {
+            crate::actions::types::Action::Clipboard(config) => {
+                assert_eq!(config.operation, ClipboardOperation::Paste);
+                assert_eq!(config.text, "Thanks!");
+                assert!(config.restore);
+            }
+            _ => panic!("Expected Clipboard action"),
+        }
+    }
+
+    #[test]
+    fn test_clipboard_action_defaults() {
+        let config: ClipboardAction = serde_json::from_str(r#"{"operation": "clear"}"#).unwrap();
+        assert_eq!(config.operation, ClipboardOperation::Clear);
+        assert!(config.text.is_empty());
+        assert!(!config.restore);
+    }
+}
@@ -0,0 +1,163 @@
+//! Conditional Handler
+//!
+//! Picks an action based on the foreground window. Branches are checked in
+//! order and the first match runs; otherwise the default action runs. The
+//! foreground window is only available on Windows, so other platforms always
+//! take the default branch.
+
+use crate::actions::types::{Action, ActionResult, ConditionalAction};
+use crate::actions::{describe_action, execute_action_with_cancellation, CancellationToken, IntegrationConfig};
+use crate::system::window::{foreground_window, ForegroundWindow};
+
+/// Execute a conditional action
+pub async fn execute(
+    config: &ConditionalAction,
+    integrations: &IntegrationConfig,
+    token: &CancellationToken,
+) -> ActionResult {
+    let window = foreground_window();
+    if window.is_none() && !config.branches.is_empty() {
+        log::warn!("Foreground window unavailable on this platform, using the default branch");
+    }
+
+    match select_action(config, window.as_ref()) {
+        Some(action) => {
+            // Branches may themselves be conditional, so the recursive call is boxed
+            Box::pin(execute_action_with_cancellation(action, integrations, token)).await
+        }
+        None => ActionResult::success_with_message("No branch matched".to_string(), 0),
+    }
+}
+
+/// Describe the branch a conditional would take now, without running it
+pub fn describe(config: &ConditionalAction) -> String {
+    match select_action(config, foreground_window().as_ref()) {
+        Some(action) => {
+            let result = describe_action(action);
+            result.message.or(result.error).unwrap_or_default()
+        }
+        None => "No branch would match".to_string(),
+    }
+}
+
+/// Action for the first branch matching the window, else the default
+pub fn select_action<'a>(
+    config: &'a ConditionalAction,
+    window: Option<&ForegroundWindow>,
+) -> Option<&'a Action> {
+    let matched = window.and_then(|window| {
+        config
+            .branches
+            .iter()
+            .find(|branch| branch.when.matches(&window.title, window.process.as_deref()))
+    });
+
+    match matched {
+        Some(branch) => {
+            log::debug!("Conditional branch matched: {:?}", branch.when);
+            Some(&branch.then)
+        }
+        None => config.default.as_deref(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::types::{
+        ConditionalBranch, WindowMatch, WorkspaceAction, WorkspaceDirection,
+    };
+
+    fn workspace(direction: WorkspaceDirection) -> Action {
+        Action::Workspace(WorkspaceAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            direction,
+            workspace_index: None,
+        })
+    }
+
+    fn direction_of(action: Option<&Action>) -> Option<WorkspaceDirection> {
+        match action {
+            Some(Action::Workspace(wa)) => Some(wa.direction.clone()),
+            Some(other) => panic!("Unexpected action: {:?}", other),
+            None => None,
+        }
+    }
+
+    fn branch(process: &str, direction: WorkspaceDirection) -> ConditionalBranch {
+        ConditionalBranch {
+            when: WindowMatch {
+                title: None,
+                process: Some(process.to_string()),
+            },
+            then: workspace(direction),
+        }
+    }
+
+    fn window(title: &str, process: &str) -> ForegroundWindow {
+        ForegroundWindow {
+            title: title.to_string(),
+            process: Some(process.to_string()),
+        }
+    }
+
+    fn conditional(default: Option<Action>) -> ConditionalAction {
+        ConditionalAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            branches: vec![
+                branch("code.exe", WorkspaceDirection::Next),
+                branch("code", WorkspaceDirection::Specific),
+                branch("spotify.exe", WorkspaceDirection::Previous),
+            ],
+            default: default.map(Box::new),
+        }
+    }
+
+    // ========== Branch Selection Tests ==========
+
+    #[test]
+    fn test_first_matching_branch_wins() {
+        let config = conditional(None);
+        let selected = select_action(&config, Some(&window("main.rs", "Code.exe")));
+        assert_eq!(direction_of(selected), Some(WorkspaceDirection::Next));
+
+        let selected = select_action(&config, Some(&window("Spotify", "Spotify.exe")));
+        assert_eq!(direction_of(selected), Some(WorkspaceDirection::Previous));
+    }
+
+    #[test]
+    fn test_no_match_uses_default() {
+        let config = conditional(Some(workspace(WorkspaceDirection::Previous)));
+        let selected = select_action(&config, Some(&window("Firefox", "firefox.exe")));
+        assert_eq!(direction_of(selected), Some(WorkspaceDirection::Previous));
+    }
+
+    #[test]
+    fn test_no_match_without_default_selects_nothing() {
+        let config = conditional(None);
+        assert!(select_action(&config, Some(&window("Firefox", "firefox.exe"))).is_none());
+    }
+
+    #[test]
+    fn test_unknown_window_uses_default() {
+        let config = conditional(Some(workspace(WorkspaceDirection::Previous)));
+        let selected = select_action(&config, None);
+        assert_eq!(direction_of(selected), Some(WorkspaceDirection::Previous));
+    }
+
+    // ========== Execution Tests ==========
+
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn test_execute_falls_back_to_default_off_windows() {
+        let config = conditional(Some(workspace(WorkspaceDirection::Previous)));
+        let result = execute(&config, &IntegrationConfig::default(), &CancellationToken::new()).await;
+        assert_eq!(result.message.as_deref(), Some("Workspace switch requested: previous"));
+    }
+}
@@ -0,0 +1,85 @@
+//! Delay Handler
+//!
+//! Waits for a fixed time, e.g. between actions chained through profiles or
+//! as a macro step. The wait ends early when the action is cancelled.
+
+use crate::actions::types::{ActionResult, DelayAction};
+use crate::actions::CancellationToken;
+use std::time::Duration;
+
+/// Describe how long a delay action would wait, without waiting
+pub fn describe(config: &DelayAction) -> String {
+    format!("Would wait {}ms", config.ms)
+}
+
+/// Execute a delay action
+pub async fn execute(config: &DelayAction, token: &CancellationToken) -> ActionResult {
+    log::debug!("Executing delay action: {}ms", config.ms);
+
+    tokio::select! {
+        _ = tokio::time::sleep(Duration::from_millis(config.ms)) => ActionResult::success(0),
+        _ = token.cancelled() => ActionResult::success_with_message("cancelled".to_string(), 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn delay(ms: u64) -> DelayAction {
+        DelayAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            ms,
+        }
+    }
+
+    // ========== Describe Tests ==========
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(describe(&delay(250)), "Would wait 250ms");
+    }
+
+    // ========== Execute Tests ==========
+
+    #[tokio::test]
+    async fn test_execute_waits_requested_time() {
+        let start = Instant::now();
+        let result = execute(&delay(100), &CancellationToken::new()).await;
+        let elapsed = start.elapsed();
+
+        assert!(result.success);
+        assert!(result.message.is_none());
+        assert!(elapsed >= Duration::from_millis(100), "returned after {:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(1000), "returned after {:?}", elapsed);
+    }
+
+    #[tokio::test]
+    async fn test_execute_returns_early_when_cancelled() {
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            canceller.cancel();
+        });
+
+        let start = Instant::now();
+        let result = execute(&delay(10_000), &token).await;
+
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(result.success);
+        assert_eq!(result.message.as_deref(), Some("cancelled"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_already_cancelled() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let result = execute(&delay(10_000), &token).await;
+        assert_eq!(result.message.as_deref(), Some("cancelled"));
+    }
+}
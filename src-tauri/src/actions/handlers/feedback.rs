@@ -0,0 +1,241 @@
+//! Button Feedback Handler
+//!
+//! Runs a feedback action's source once and draws the value on its button.
+//! The feedback scheduler (see `actions::feedback`) repeats this on an
+//! interval; executing the action directly, e.g. by pressing the button,
+//! refreshes the value straight away.
+//!
+//! Values are cut down to their first non-empty line and `MAX_VALUE_CHARS`
+//! characters, as only a few fit on a 60x60 button.
+
+use crate::actions::template::render;
+use crate::actions::error::ActionError;
+use crate::actions::types::{Action, ActionResult, FeedbackAction};
+use crate::actions::{describe_action, execute_action_with_cancellation, CancellationToken, IntegrationConfig};
+use crate::hid::manager::HidManager;
+use crate::hid::protocol::SoomfonProtocol;
+use crate::hid::types::LCD_BUTTON_COUNT;
+use crate::image::processor::create_text_image;
+use crate::image::text::TextStyle;
+use parking_lot::Mutex;
+
+/// Most characters of a value shown on a button
+const MAX_VALUE_CHARS: usize = 16;
+
+/// Shown when the source fails
+pub const ERROR_VALUE: &str = "ERR";
+
+/// Describe the value a feedback action would show, without fetching it
+pub fn describe(config: &FeedbackAction) -> String {
+    let source = describe_action(&config.source);
+    format!(
+        "Would show on button {} every {}ms the value from: {}",
+        config.button_index,
+        config.interval().as_millis(),
+        source.message.or(source.error).unwrap_or_default()
+    )
+}
+
+/// Execute a feedback action: fetch the value once and show it
+pub async fn execute(
+    config: &FeedbackAction,
+    integrations: &IntegrationConfig,
+    token: &CancellationToken,
+) -> ActionResult {
+    log::debug!("Executing button feedback for button {}", config.button_index);
+
+    if let Err(e) = validate(config) {
+        return ActionResult::from_error(ActionError::InvalidInput(e), 0);
+    }
+    let Some(device) = integrations.device.as_ref() else {
+        return ActionResult::from_error(ActionError::DeviceError("Device control not available".to_string()), 0);
+    };
+
+    let value = poll_value(config, integrations, token).await;
+    match show_value(&device.hid, config.button_index, &value, &config.style) {
+        Ok(()) => ActionResult::success_with_message(value, 0),
+        Err(e) => ActionResult::from_error(ActionError::DeviceError(format!("Failed to show feedback: {}", e)), 0),
+    }
+}
+
+/// Check that a feedback action can run
+pub fn validate(config: &FeedbackAction) -> Result<(), String> {
+    if config.button_index >= LCD_BUTTON_COUNT {
+        return Err(format!("Invalid button index: {}", config.button_index));
+    }
+    if matches!(*config.source, Action::ButtonFeedback(_)) {
+        return Err("A feedback source can't be another feedback action".to_string());
+    }
+    Ok(())
+}
+
+/// Run the source and turn its result into the value to show
+pub async fn poll_value(
+    config: &FeedbackAction,
+    integrations: &IntegrationConfig,
+    token: &CancellationToken,
+) -> String {
+    let result = execute_action_with_cancellation(&config.source, integrations, token).await;
+    value_from_result(config, &result)
+}
+
+/// Value shown for a source's result
+fn value_from_result(config: &FeedbackAction, result: &ActionResult) -> String {
+    if !result.success {
+        log::debug!(
+            "Feedback source for button {} failed: {}",
+            config.button_index,
+            result.error.as_deref().unwrap_or("unknown error")
+        );
+        return ERROR_VALUE.to_string();
+    }
+    let raw = match &config.text {
+        Some(text) => render(text, &result.variables.clone().unwrap_or_default()),
+        None => result.message.clone().unwrap_or_default(),
+    };
+    display_value(&raw)
+}
+
+/// First non-empty line of `raw`, trimmed and cut to `MAX_VALUE_CHARS`
+fn display_value(raw: &str) -> String {
+    let line = raw.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or("");
+    line.chars().take(MAX_VALUE_CHARS).collect()
+}
+
+/// Draw a value on a button, skipping the upload if it's already shown
+pub fn show_value(hid: &Mutex<HidManager>, index: u8, value: &str, style: &TextStyle) -> Result<(), String> {
+    let jpeg = create_text_image(value, style)?;
+
+    let mut manager = hid.lock();
+    if manager.is_button_image_current(index, &jpeg) {
+        return Ok(());
+    }
+    // Reopen handle if it was transferred to polling thread
+    manager.reopen_for_commands().map_err(|e| e.to_string())?;
+    SoomfonProtocol::new(&manager)
+        .set_button_image(index, &jpeg)
+        .map_err(|e| e.to_string())?;
+    manager.cache_button_image(index, jpeg);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::types::DelayAction;
+    use std::collections::HashMap;
+
+    fn feedback(text: Option<&str>) -> FeedbackAction {
+        FeedbackAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            button_index: 2,
+            source: Box::new(Action::Delay(DelayAction {
+                id: None,
+                name: None,
+                icon: None,
+                enabled: None,
+                ms: 0,
+            })),
+            text: text.map(str::to_string),
+            interval_ms: 0,
+            style: TextStyle::default(),
+        }
+    }
+
+    // ========== Value Tests ==========
+
+    #[test]
+    fn test_display_value_first_line_trimmed() {
+        assert_eq!(display_value("\n  42 %  \nsecond line"), "42 %");
+    }
+
+    #[test]
+    fn test_display_value_truncated() {
+        assert_eq!(display_value("abcdefghijklmnopqrstuvwxyz"), "abcdefghijklmnop");
+        assert_eq!(display_value("ünïcödé"), "ünïcödé");
+    }
+
+    #[test]
+    fn test_value_from_output() {
+        let result = ActionResult::success_with_message("17\n".to_string(), 0);
+        assert_eq!(value_from_result(&feedback(None), &result), "17");
+    }
+
+    #[test]
+    fn test_value_from_text_template() {
+        let variables = HashMap::from([(
+            "cpu".to_string(),
+            serde_json::json!({ "status": 200, "body": { "load": 37 } }),
+        )]);
+        let result = ActionResult::success_with_message("200 OK: ...".to_string(), 0).with_variables(variables);
+        assert_eq!(value_from_result(&feedback(Some("${cpu.body.load}%")), &result), "37%");
+    }
+
+    #[test]
+    fn test_value_on_failure() {
+        let result = ActionResult::failure("timed out".to_string(), 0);
+        assert_eq!(value_from_result(&feedback(None), &result), ERROR_VALUE);
+    }
+
+    // ========== Validation Tests ==========
+
+    #[test]
+    fn test_validate_button_index() {
+        let mut config = feedback(None);
+        assert!(validate(&config).is_ok());
+        config.button_index = LCD_BUTTON_COUNT;
+        assert!(validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_nested_feedback() {
+        let mut config = feedback(None);
+        *config.source = Action::ButtonFeedback(feedback(None));
+        assert!(validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_interval_clamped() {
+        let config = feedback(None);
+        assert_eq!(
+            config.interval(),
+            std::time::Duration::from_millis(crate::actions::types::MIN_FEEDBACK_INTERVAL_MS)
+        );
+    }
+
+    // ========== Execute Tests ==========
+
+    #[tokio::test]
+    async fn test_execute_without_device_fails() {
+        let result = execute(&feedback(None), &IntegrationConfig::default(), &CancellationToken::new()).await;
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("Device control not available"));
+    }
+
+    // ========== Serialization Tests ==========
+
+    #[test]
+    fn test_feedback_action_deserialize() {
+        let action: Action = serde_json::from_str(
+            r#"{
+                "type": "button_feedback",
+                "buttonIndex": 1,
+                "source": {"type": "script", "scriptType": "bash", "script": "echo 5"},
+                "intervalMs": 2000
+            }"#,
+        )
+        .unwrap();
+        match action {
+            Action::ButtonFeedback(config) => {
+                assert_eq!(config.button_index, 1);
+                assert!(matches!(*config.source, Action::Script(_)));
+                assert_eq!(config.interval_ms, 2000);
+                assert!(config.text.is_none());
+            }
+            _ => panic!("Expected ButtonFeedback action"),
+        }
+    }
+}
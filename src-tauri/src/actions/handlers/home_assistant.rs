@@ -6,33 +6,68 @@
 //! - Toggle: Toggle entity state
 //! - TurnOn: Turn entity on
 //! - TurnOff: Turn entity off
+//! - SetBrightnessRelative: Adjust a light's current brightness by a delta
 //! - CallService: Call any Home Assistant service
 //! - FireEvent: Fire a Home Assistant event
+//!
+//! Service calls report the entity's resulting state in the result message.
 
+use crate::actions::error::ActionError;
 use crate::actions::types::{ActionResult, HomeAssistantAction, HomeAssistantOperationType};
+use crate::actions::CancellationToken;
 use crate::config::types::HomeAssistantConfig;
 use std::time::Duration;
 
+/// Attempts per request; transient failures (connection errors, 5xx, 429) are retried
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry; doubled for each further retry
+const RETRY_BACKOFF_MS: u64 = 500;
+
+/// Describe the Home Assistant call an action would make, without making it
+pub fn describe(config: &HomeAssistantAction) -> String {
+    let service = match config.operation {
+        HomeAssistantOperationType::Toggle => "homeassistant.toggle".to_string(),
+        HomeAssistantOperationType::TurnOn => "homeassistant.turn_on".to_string(),
+        HomeAssistantOperationType::TurnOff => "homeassistant.turn_off".to_string(),
+        HomeAssistantOperationType::SetBrightness => "light.turn_on".to_string(),
+        HomeAssistantOperationType::SetBrightnessRelative => {
+            return format!(
+                "Would change the brightness of {} by {}",
+                config.entity_id,
+                config.brightness_delta.unwrap_or(0)
+            );
+        }
+        HomeAssistantOperationType::RunScript => "script.turn_on".to_string(),
+        HomeAssistantOperationType::TriggerAutomation => "automation.trigger".to_string(),
+        HomeAssistantOperationType::Custom | HomeAssistantOperationType::CallService => {
+            match (&config.service, &config.custom_service) {
+                (Some(service), _) => service.clone(),
+                (None, Some(custom)) => format!("{}.{}", custom.domain, custom.service),
+                (None, None) => return "Would fail: service not specified for Custom action".to_string(),
+            }
+        }
+        HomeAssistantOperationType::FireEvent => {
+            return format!("Would fire event {}", config.entity_id);
+        }
+    };
+    format!("Would call {} for {}", service, config.entity_id)
+}
+
 /// Execute a Home Assistant action with configuration
 pub async fn execute_with_config(
     config: &HomeAssistantAction,
     ha_config: Option<&HomeAssistantConfig>,
+    token: &CancellationToken,
 ) -> ActionResult {
     log::debug!("Executing Home Assistant action: {:?}", config.operation);
 
-    // Get Home Assistant URL and token from config, falling back to environment variables
-    let (ha_url, ha_token) = match ha_config {
-        Some(cfg) => (cfg.url.clone(), cfg.token.clone()),
-        None => {
-            // Fallback to environment variables for backwards compatibility
-            let url = std::env::var("HOME_ASSISTANT_URL").unwrap_or_default();
-            let token = std::env::var("HOME_ASSISTANT_TOKEN").unwrap_or_default();
-            (url, token)
-        }
-    };
+    let (ha_url, ha_token) = ha_config
+        .map(|cfg| (cfg.url.clone(), cfg.token.clone()))
+        .unwrap_or_default();
 
     if ha_url.is_empty() || ha_token.is_empty() {
-        return ActionResult::failure("Home Assistant not configured".to_string(), 0);
+        return ActionResult::from_error(ActionError::ConfigMissing("Home Assistant not configured".to_string()), 0);
     }
 
     let client = match reqwest::Client::builder()
@@ -40,63 +75,185 @@ pub async fn execute_with_config(
         .build()
     {
         Ok(c) => c,
-        Err(e) => return ActionResult::failure(format!("Failed to create HTTP client: {}", e), 0),
+        Err(e) => {
+            return ActionResult::from_error(ActionError::NetworkError(format!("Failed to create HTTP client: {}", e)), 0)
+        }
+    };
+
+    let ha = Connection {
+        client: &client,
+        url: &ha_url,
+        token: &ha_token,
+        cancel: token,
     };
 
     match config.operation {
         HomeAssistantOperationType::Toggle => {
-            call_service(&client, &ha_url, &ha_token, "homeassistant", "toggle", config).await
+            call_service(&ha, "homeassistant", "toggle", config).await
         }
         HomeAssistantOperationType::TurnOn => {
-            call_service(&client, &ha_url, &ha_token, "homeassistant", "turn_on", config).await
+            call_service(&ha, "homeassistant", "turn_on", config).await
         }
         HomeAssistantOperationType::TurnOff => {
-            call_service(&client, &ha_url, &ha_token, "homeassistant", "turn_off", config).await
+            call_service(&ha, "homeassistant", "turn_off", config).await
         }
         HomeAssistantOperationType::SetBrightness => {
             // Set brightness uses light.turn_on with brightness value
-            call_service(&client, &ha_url, &ha_token, "light", "turn_on", config).await
+            call_service(&ha, "light", "turn_on", config).await
+        }
+        HomeAssistantOperationType::SetBrightnessRelative => {
+            set_brightness_relative(&ha, config).await
         }
         HomeAssistantOperationType::RunScript => {
-            call_service(&client, &ha_url, &ha_token, "script", "turn_on", config).await
+            call_service(&ha, "script", "turn_on", config).await
         }
         HomeAssistantOperationType::TriggerAutomation => {
-            call_service(&client, &ha_url, &ha_token, "automation", "trigger", config).await
+            call_service(&ha, "automation", "trigger", config).await
         }
         HomeAssistantOperationType::Custom | HomeAssistantOperationType::CallService => {
             if let Some(ref service) = config.service {
                 let parts: Vec<&str> = service.split('.').collect();
                 if parts.len() == 2 {
-                    call_service(&client, &ha_url, &ha_token, parts[0], parts[1], config).await
+                    call_service(&ha, parts[0], parts[1], config).await
                 } else {
-                    ActionResult::failure(
-                        "Invalid service format. Expected 'domain.service' (e.g., 'light.turn_on')".to_string(),
+                    ActionResult::from_error(
+                        ActionError::InvalidInput(
+                            "Invalid service format. Expected 'domain.service' (e.g., 'light.turn_on')".to_string(),
+                        ),
                         0,
                     )
                 }
             } else if let Some(ref custom) = config.custom_service {
-                call_service(&client, &ha_url, &ha_token, &custom.domain, &custom.service, config).await
+                call_service(&ha, &custom.domain, &custom.service, config).await
             } else {
-                ActionResult::failure("Service not specified for Custom action".to_string(), 0)
+                ActionResult::from_error(ActionError::InvalidInput("Service not specified for Custom action".to_string()), 0)
             }
         }
         HomeAssistantOperationType::FireEvent => {
-            fire_event(&client, &ha_url, &ha_token, config).await
+            fire_event(&ha, config).await
+        }
+    }
+}
+
+/// State of a Home Assistant entity, as returned by `/api/states/{entity_id}`
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct EntityState {
+    pub entity_id: String,
+    /// State string, e.g. "on", "off", "unavailable" or a sensor reading
+    pub state: String,
+    #[serde(default)]
+    pub attributes: serde_json::Value,
+}
+
+/// Get the current state of an entity
+pub async fn get_state(ha_config: &HomeAssistantConfig, entity_id: &str) -> Result<EntityState, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let cancel = CancellationToken::new();
+    let ha = Connection {
+        client: &client,
+        url: &ha_config.url,
+        token: &ha_config.token,
+        cancel: &cancel,
+    };
+    ha.get_state(entity_id).await
+}
+
+/// Client and credentials for one action's requests
+struct Connection<'a> {
+    client: &'a reqwest::Client,
+    url: &'a str,
+    token: &'a str,
+    cancel: &'a CancellationToken,
+}
+
+impl Connection<'_> {
+    /// POST a JSON body, retrying transient failures
+    ///
+    /// Returns `None` if the action was cancelled between attempts.
+    async fn post(
+        &self,
+        url: &str,
+        body: &serde_json::Value,
+    ) -> Option<Result<reqwest::Response, reqwest::Error>> {
+        super::retry(
+            MAX_ATTEMPTS,
+            Duration::from_millis(RETRY_BACKOFF_MS),
+            self.cancel,
+            super::is_retryable,
+            || {
+                self.client
+                    .post(url)
+                    .header("Authorization", format!("Bearer {}", self.token))
+                    .header("Content-Type", "application/json")
+                    .json(body)
+                    .send()
+            },
+        )
+        .await
+    }
+
+    /// Read an entity's state, retrying transient failures
+    async fn get_state(&self, entity_id: &str) -> Result<EntityState, String> {
+        let url = format!("{}/api/states/{}", self.url, entity_id);
+        let response = super::retry(
+            MAX_ATTEMPTS,
+            Duration::from_millis(RETRY_BACKOFF_MS),
+            self.cancel,
+            super::is_retryable,
+            || {
+                self.client
+                    .get(&url)
+                    .header("Authorization", format!("Bearer {}", self.token))
+                    .send()
+            },
+        )
+        .await
+        .ok_or_else(|| "Cancelled".to_string())?
+        .map_err(|e| format!("Home Assistant request failed: {}", e))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(format!(
+                "Entity '{}' not found in Home Assistant (check the entity ID or whether it is available)",
+                entity_id
+            ));
+        }
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(format!("Home Assistant request failed ({}): {}", status, error_text));
+        }
+
+        response
+            .json::<EntityState>()
+            .await
+            .map_err(|e| format!("Invalid state from Home Assistant: {}", e))
+    }
+
+    /// " (state: on)" for a single entity whose state can be read, else ""
+    async fn state_suffix(&self, entity_id: &str) -> String {
+        if entity_id.is_empty() || entity_id.contains(',') {
+            return String::new();
+        }
+        match self.get_state(entity_id).await {
+            Ok(state) => format!(" (state: {})", state.state),
+            Err(e) => {
+                log::debug!("Could not read state of {}: {}", entity_id, e);
+                String::new()
+            }
         }
     }
 }
 
 /// Call a Home Assistant service
 async fn call_service(
-    client: &reqwest::Client,
-    ha_url: &str,
-    ha_token: &str,
+    ha: &Connection<'_>,
     domain: &str,
     service: &str,
     config: &HomeAssistantAction,
 ) -> ActionResult {
-    let url = format!("{}/api/services/{}/{}", ha_url, domain, service);
-
     // Build request body with entity_id and optional service_data
     let mut body = serde_json::json!({
         "entity_id": config.entity_id
@@ -113,92 +270,256 @@ async fn call_service(
         }
     }
 
-    let response = client
-        .post(&url)
-        .header("Authorization", format!("Bearer {}", ha_token))
-        .header("Content-Type", "application/json")
-        .json(&body)
-        .send()
-        .await;
+    send_service(ha, domain, service, &config.entity_id, &body).await
+}
 
-    match response {
-        Ok(resp) => {
+/// POST a service call and report the entity's resulting state
+async fn send_service(
+    ha: &Connection<'_>,
+    domain: &str,
+    service: &str,
+    entity_id: &str,
+    body: &serde_json::Value,
+) -> ActionResult {
+    let url = format!("{}/api/services/{}/{}", ha.url, domain, service);
+
+    match ha.post(&url, body).await {
+        None => ActionResult::from_error(ActionError::cancelled(), 0),
+        Some(Ok(resp)) => {
             if resp.status().is_success() {
+                let state = ha.state_suffix(entity_id).await;
                 ActionResult::success_with_message(
-                    format!("Called {}.{} for {}", domain, service, config.entity_id),
+                    format!("Called {}.{} for {}{}", domain, service, entity_id, state),
                     0,
                 )
             } else {
                 let status = resp.status();
                 let error_text = resp.text().await.unwrap_or_default();
-                ActionResult::failure(
-                    format!("Home Assistant request failed ({}): {}", status, error_text),
+                ActionResult::from_error(
+                    ActionError::NetworkError(format!("Home Assistant request failed ({}): {}", status, error_text)),
                     0,
                 )
             }
         }
-        Err(e) => ActionResult::failure(format!("Home Assistant request failed: {}", e), 0),
+        Some(Err(e)) => ActionResult::from_error(ActionError::NetworkError(format!("Home Assistant request failed: {}", e)), 0),
+    }
+}
+
+/// Read a light's brightness and set it `brightness_delta` higher or lower
+///
+/// Reaching 0 turns the light off.
+async fn set_brightness_relative(ha: &Connection<'_>, config: &HomeAssistantAction) -> ActionResult {
+    let Some(delta) = config.brightness_delta else {
+        return ActionResult::from_error(ActionError::InvalidInput("brightness_delta is required for set_brightness_relative".to_string()), 0);
+    };
+
+    let target = match ha.get_state(&config.entity_id).await {
+        Ok(state) => match relative_brightness(&state, delta) {
+            Ok(target) => target,
+            Err(e) => return ActionResult::failure(e, 0),
+        },
+        Err(e) => return ActionResult::from_error(ActionError::NetworkError(e), 0),
+    };
+
+    if target == 0 {
+        let body = serde_json::json!({ "entity_id": config.entity_id });
+        send_service(ha, "light", "turn_off", &config.entity_id, &body).await
+    } else {
+        let body = serde_json::json!({ "entity_id": config.entity_id, "brightness": target });
+        send_service(ha, "light", "turn_on", &config.entity_id, &body).await
     }
 }
 
+/// New brightness (0-255) after applying `delta` to the entity's current brightness
+///
+/// A light that is off counts as brightness 0.
+fn relative_brightness(state: &EntityState, delta: i16) -> Result<u8, String> {
+    let current = match state.state.as_str() {
+        "unavailable" | "unknown" => {
+            return Err(format!("Entity '{}' is {}", state.entity_id, state.state));
+        }
+        "off" => 0,
+        _ => state.attributes.get("brightness").and_then(|b| b.as_i64()).unwrap_or(0),
+    };
+    Ok((current + i64::from(delta)).clamp(0, 255) as u8)
+}
+
 /// Fire a Home Assistant event
 ///
 /// The event type is derived from entity_id (used as event_type)
 /// and event_data comes from service_data
-async fn fire_event(
-    client: &reqwest::Client,
-    ha_url: &str,
-    ha_token: &str,
-    config: &HomeAssistantAction,
-) -> ActionResult {
+async fn fire_event(ha: &Connection<'_>, config: &HomeAssistantAction) -> ActionResult {
     // For FireEvent, entity_id is used as the event_type
     // This aligns with how events are fired in Home Assistant
     let event_type = &config.entity_id;
 
     // Validate event type (must not be empty)
     if event_type.is_empty() {
-        return ActionResult::failure("Event type (entity_id) is required for FireEvent".to_string(), 0);
+        return ActionResult::from_error(ActionError::InvalidInput("Event type (entity_id) is required for FireEvent".to_string()), 0);
     }
 
-    let url = format!("{}/api/events/{}", ha_url, event_type);
+    let url = format!("{}/api/events/{}", ha.url, event_type);
 
     // Build event data from service_data (can be empty object)
     let body = config.service_data.clone().unwrap_or_else(|| serde_json::json!({}));
 
-    let response = client
-        .post(&url)
-        .header("Authorization", format!("Bearer {}", ha_token))
-        .header("Content-Type", "application/json")
-        .json(&body)
-        .send()
-        .await;
-
-    match response {
-        Ok(resp) => {
+    match ha.post(&url, &body).await {
+        None => ActionResult::from_error(ActionError::cancelled(), 0),
+        Some(Ok(resp)) => {
             if resp.status().is_success() {
                 ActionResult::success_with_message(format!("Fired event: {}", event_type), 0)
             } else {
                 let status = resp.status();
                 let error_text = resp.text().await.unwrap_or_default();
-                ActionResult::failure(
-                    format!("Failed to fire event ({}): {}", status, error_text),
+                ActionResult::from_error(
+                    ActionError::NetworkError(format!("Failed to fire event ({}): {}", status, error_text)),
                     0,
                 )
             }
         }
-        Err(e) => ActionResult::failure(format!("Failed to fire event: {}", e), 0),
+        Some(Err(e)) => ActionResult::from_error(ActionError::NetworkError(format!("Failed to fire event: {}", e)), 0),
     }
 }
 
-/// Execute a Home Assistant action (backwards-compatible, uses env vars)
-pub async fn execute(config: &HomeAssistantAction) -> ActionResult {
-    execute_with_config(config, None).await
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn entity(state: &str, attributes: serde_json::Value) -> EntityState {
+        EntityState {
+            entity_id: "light.desk".to_string(),
+            state: state.to_string(),
+            attributes,
+        }
+    }
+
+    /// Mock Home Assistant answering one request with the given status and body
+    fn mock_server(status: u16, body: &'static str) -> HomeAssistantConfig {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(&socket);
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            let response = format!(
+                "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).unwrap();
+        });
+
+        HomeAssistantConfig {
+            url: format!("http://{}", addr),
+            token: "token".to_string(),
+        }
+    }
+
+    // ========== Dry Run Tests ==========
+
+    fn ha_action(operation: HomeAssistantOperationType) -> HomeAssistantAction {
+        HomeAssistantAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            operation,
+            entity_id: "light.desk".to_string(),
+            brightness: None,
+            brightness_delta: Some(-20),
+            custom_service: None,
+            service: None,
+            service_data: None,
+        }
+    }
+
+    #[test]
+    fn test_describe_names_service_and_entity() {
+        assert_eq!(
+            describe(&ha_action(HomeAssistantOperationType::Toggle)),
+            "Would call homeassistant.toggle for light.desk"
+        );
+        assert_eq!(
+            describe(&ha_action(HomeAssistantOperationType::SetBrightnessRelative)),
+            "Would change the brightness of light.desk by -20"
+        );
+
+        let mut custom = ha_action(HomeAssistantOperationType::Custom);
+        custom.service = Some("light.turn_on".to_string());
+        assert_eq!(describe(&custom), "Would call light.turn_on for light.desk");
+    }
+
+    // ========== State Tests ==========
+
+    #[tokio::test]
+    async fn test_get_state_parses_entity() {
+        let config = mock_server(
+            200,
+            r#"{"entity_id": "light.desk", "state": "on", "attributes": {"brightness": 128}}"#,
+        );
+        let state = get_state(&config, "light.desk").await.unwrap();
+
+        assert_eq!(state.state, "on");
+        assert_eq!(state.attributes["brightness"], 128);
+    }
+
+    #[tokio::test]
+    async fn test_get_state_missing_entity_is_clear_error() {
+        let config = mock_server(404, r#"{"message": "Entity not found."}"#);
+        let err = get_state(&config, "light.nope").await.unwrap_err();
+
+        assert!(err.contains("'light.nope' not found"), "{}", err);
+    }
+
+    #[test]
+    fn test_relative_brightness() {
+        let on = entity("on", serde_json::json!({"brightness": 100}));
+        assert_eq!(relative_brightness(&on, 50), Ok(150));
+        assert_eq!(relative_brightness(&on, -30), Ok(70));
+        assert_eq!(relative_brightness(&on, 500), Ok(255));
+        assert_eq!(relative_brightness(&on, -500), Ok(0));
+    }
+
+    #[test]
+    fn test_relative_brightness_from_off() {
+        let off = entity("off", serde_json::json!({"brightness": null}));
+        assert_eq!(relative_brightness(&off, 25), Ok(25));
+        assert_eq!(relative_brightness(&off, -25), Ok(0));
+    }
+
+    #[test]
+    fn test_relative_brightness_unavailable_entity() {
+        let unavailable = entity("unavailable", serde_json::json!({}));
+        let err = relative_brightness(&unavailable, 10).unwrap_err();
+        assert_eq!(err, "Entity 'light.desk' is unavailable");
+    }
+
+    #[test]
+    fn test_set_brightness_relative_deserialize() {
+        let action: HomeAssistantAction = serde_json::from_str(
+            r#"{"operation": "set_brightness_relative", "entityId": "light.desk", "brightnessDelta": -20}"#,
+        )
+        .unwrap();
+        assert_eq!(action.operation, HomeAssistantOperationType::SetBrightnessRelative);
+        assert_eq!(action.brightness_delta, Some(-20));
+    }
+
+    #[tokio::test]
+    async fn test_unconfigured_fails_without_env_fallback() {
+        let action: HomeAssistantAction =
+            serde_json::from_str(r#"{"operation": "toggle", "entityId": "light.test"}"#).unwrap();
+        let result = execute_with_config(&action, None, &CancellationToken::new()).await;
+
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("Home Assistant not configured"));
+    }
+
     #[test]
     fn test_home_assistant_operation_type_deserialize() {
         let types = [
@@ -1,60 +1,722 @@
 //! HTTP Handler
 //!
 //! Makes HTTP requests (GET, POST, PUT, DELETE, PATCH).
+//!
+//! URLs must be absolute http or https URLs; anything else fails before a
+//! request is made. With the `block_private_networks` setting on, the host is
+//! resolved first and the request refused if any of its addresses is
+//! loopback, private or link-local. The client is then pinned to the checked
+//! addresses and only follows redirects on the same host, so neither a second
+//! DNS lookup nor a redirect can lead it onto the local network.
 
-use crate::actions::types::{ActionResult, HttpAction, HttpMethod};
-use std::time::Duration;
+use crate::actions::error::ActionError;
+use crate::actions::types::{ActionResult, HttpAction, HttpCapture, HttpMethod};
+use crate::actions::CancellationToken;
+use reqwest::Url;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
 
 /// Default HTTP timeout in milliseconds
 const DEFAULT_TIMEOUT_MS: u64 = 30000;
 
+/// Wait before the first retry; doubled for each further retry
+const DEFAULT_RETRY_BACKOFF_MS: u64 = 500;
+
+/// How much of the response body is echoed in the result message or error
+const BODY_PREVIEW_BYTES: usize = 500;
+
+/// Redirects followed before giving up, as in reqwest's default policy
+const MAX_REDIRECTS: usize = 10;
+
+/// Describe the request an HTTP action would send, without sending it
+pub fn describe(config: &HttpAction) -> String {
+    format!("Would send {} {}", config.method, config.url)
+}
+
 /// Execute an HTTP action
 pub async fn execute(config: &HttpAction) -> ActionResult {
+    execute_with_cancellation(config, false, &CancellationToken::new()).await
+}
+
+/// Execute an HTTP action that can be aborted through a cancellation token
+///
+/// The in-flight request is dropped as soon as the token is cancelled, which
+/// closes the underlying connection instead of waiting for the timeout.
+/// With `block_private_networks` set, requests to private addresses fail.
+pub async fn execute_with_cancellation(
+    config: &HttpAction,
+    block_private_networks: bool,
+    token: &CancellationToken,
+) -> ActionResult {
+    if token.is_cancelled() {
+        return ActionResult::from_error(ActionError::cancelled(), 0);
+    }
+
     log::debug!("Executing HTTP action: {} {}", config.method, config.url);
 
+    let url = match validate_url(&config.url) {
+        Ok(url) => url,
+        Err(e) => return ActionResult::from_error(ActionError::InvalidInput(e), 0),
+    };
+
     let timeout_ms = config.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS);
+    let mut timings = HashMap::new();
+    let step = Instant::now();
 
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_millis(timeout_ms))
-        .build();
+    let mut builder = reqwest::Client::builder().timeout(Duration::from_millis(timeout_ms));
+    if block_private_networks {
+        let addrs = tokio::select! {
+            addrs = public_addrs(&url) => addrs,
+            _ = token.cancelled() => return ActionResult::from_error(ActionError::cancelled(), 0),
+        };
+        match addrs {
+            Ok(addrs) => builder = pin_to(builder, &url, &addrs),
+            Err(e) => return ActionResult::from_error(e, 0),
+        }
+        timings.insert("resolve".to_string(), step.elapsed().as_millis() as u64);
+    }
+    let client = builder.build();
 
     let client = match client {
         Ok(c) => c,
-        Err(e) => return ActionResult::failure(format!("Failed to create HTTP client: {}", e), 0),
+        Err(e) => {
+            return ActionResult::from_error(ActionError::NetworkError(format!("Failed to create HTTP client: {}", e)), 0)
+        }
+    };
+
+    // Encode the body once; the request is rebuilt for each retry
+    let body = match build_body(config.body_type.as_deref(), config.body.as_ref()) {
+        Ok(body) => body,
+        Err(e) => return ActionResult::from_error(ActionError::InvalidInput(e), 0),
+    };
+    let has_content_type = config
+        .headers
+        .keys()
+        .any(|key| key.eq_ignore_ascii_case("content-type"));
+
+    let make_request = || {
+        let mut request = match config.method {
+            HttpMethod::Get => client.get(url.clone()),
+            HttpMethod::Post => client.post(url.clone()),
+            HttpMethod::Put => client.put(url.clone()),
+            HttpMethod::Delete => client.delete(url.clone()),
+            HttpMethod::Patch => client.patch(url.clone()),
+        };
+
+        // Add headers
+        for (key, value) in &config.headers {
+            request = request.header(key, value);
+        }
+
+        // Add body if present, encoded per body_type
+        if let Some(ref body) = body {
+            if !has_content_type {
+                request = request.header("Content-Type", body.content_type);
+            }
+            request = request.body(body.content.clone());
+        }
+        request
     };
+    let attempts = config.retries.unwrap_or(0).saturating_add(1);
+    let backoff = Duration::from_millis(config.retry_backoff_ms.unwrap_or(DEFAULT_RETRY_BACKOFF_MS));
 
-    let mut request = match config.method {
-        HttpMethod::Get => client.get(&config.url),
-        HttpMethod::Post => client.post(&config.url),
-        HttpMethod::Put => client.put(&config.url),
-        HttpMethod::Delete => client.delete(&config.url),
-        HttpMethod::Patch => client.patch(&config.url),
+    timings.insert("build".to_string(), step.elapsed().as_millis() as u64);
+
+    // The request future owns the client; dropping it on cancel aborts the connection
+    let exchange = async {
+        // "send" covers DNS, connect and waiting for the response headers, over all attempts
+        let step = Instant::now();
+        let response = super::retry(attempts, backoff, token, super::is_retryable, || {
+            make_request().send()
+        })
+        .await;
+        timings.insert("send".to_string(), step.elapsed().as_millis() as u64);
+
+        match response {
+            None => ActionResult::from_error(ActionError::cancelled(), 0),
+            Some(Ok(response)) => {
+                let status = response.status();
+                let step = Instant::now();
+                let text = response.text().await.unwrap_or_default();
+                timings.insert("body".to_string(), step.elapsed().as_millis() as u64);
+                response_result(status, &text, config.capture.as_ref())
+            }
+            Some(Err(e)) => ActionResult::from_error(ActionError::NetworkError(format!("HTTP request failed: {}", e)), 0),
+        }
     };
 
-    // Add headers
-    for (key, value) in &config.headers {
-        request = request.header(key, value);
+    let result = tokio::select! {
+        result = exchange => result,
+        _ = token.cancelled() => {
+            log::debug!("HTTP action cancelled: {}", config.url);
+            return ActionResult::from_error(ActionError::cancelled(), 0);
+        }
+    };
+    drop(client);
+
+    result.with_timings(timings)
+}
+
+/// Parse an action URL, which must be an absolute http or https URL with a host
+pub fn validate_url(url: &str) -> Result<Url, String> {
+    let parsed = Url::parse(url.trim()).map_err(|e| format!("Invalid URL \"{}\": {}", url, e))?;
+    match parsed.scheme() {
+        "http" | "https" => {}
+        scheme => {
+            return Err(format!(
+                "Unsupported URL scheme \"{}\" (only http and https are allowed)",
+                scheme
+            ))
+        }
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err(format!("Invalid URL \"{}\": missing host", url));
     }
+    Ok(parsed)
+}
+
+/// Addresses of the URL's host, or an error if any of them is private
+///
+/// Every address is checked, so a name resolving to both a public and a
+/// private address is refused.
+async fn public_addrs(url: &Url) -> Result<Vec<SocketAddr>, ActionError> {
+    let host = url.host_str().unwrap_or_default();
+    let port = url.port_or_known_default().unwrap_or(80);
 
-    // Add body if present
-    if let Some(ref body) = config.body {
-        // Convert JSON value to string for the request body
-        let body_str = serde_json::to_string(body).unwrap_or_default();
-        request = request.body(body_str);
+    let addrs: Vec<SocketAddr> = match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|e| ActionError::NetworkError(format!("Failed to resolve {}: {}", host, e)))?
+            .collect(),
+    };
+    if addrs.is_empty() {
+        return Err(ActionError::NetworkError(format!("Failed to resolve {}: no addresses", host)));
+    }
+    if let Some(private) = addrs.iter().find(|addr| is_private_ip(addr.ip())) {
+        return Err(ActionError::InvalidInput(format!(
+            "Blocked request to private network address {} ({}); turn off \"block private networks\" to allow it",
+            private.ip(),
+            host
+        )));
     }
+    Ok(addrs)
+}
+
+/// Pin the client to checked addresses and keep redirects on the same host
+fn pin_to(builder: reqwest::ClientBuilder, url: &Url, addrs: &[SocketAddr]) -> reqwest::ClientBuilder {
+    let host = url.host_str().unwrap_or_default().to_string();
+    let builder = builder.resolve_to_addrs(&host, addrs);
+    builder.redirect(reqwest::redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() > MAX_REDIRECTS {
+            attempt.error("too many redirects")
+        } else if attempt.url().host_str() == Some(host.as_str()) {
+            attempt.follow()
+        } else {
+            attempt.error("redirect to another host blocked by the private network guard")
+        }
+    }))
+}
+
+/// Whether an address is loopback, private, link-local or otherwise local
+fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                // 100.64.0.0/10, carrier-grade NAT
+                || (ip.octets()[0] == 100 && (ip.octets()[1] & 0xC0) == 64)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_private_ip(IpAddr::V4(v4)),
+            None => {
+                let first = ip.segments()[0];
+                ip.is_loopback()
+                    || ip.is_unspecified()
+                    // fc00::/7, unique local
+                    || (first & 0xFE00) == 0xFC00
+                    // fe80::/10, link-local
+                    || (first & 0xFFC0) == 0xFE80
+            }
+        },
+    }
+}
+
+/// Turn a response into the action result
+///
+/// Non-2xx statuses are failures. Either way the status and the start of the
+/// body are reported, and the response is captured if requested.
+fn response_result(
+    status: reqwest::StatusCode,
+    text: &str,
+    capture: Option<&HttpCapture>,
+) -> ActionResult {
+    let preview = truncate(text, BODY_PREVIEW_BYTES);
+    let result = if status.is_success() {
+        ActionResult::success_with_message(format!("{}: {}", status, preview), 0)
+    } else {
+        ActionResult::from_error(
+            ActionError::NetworkError(format!("HTTP request failed with status: {}: {}", status, preview)),
+            0,
+        )
+    };
+
+    match capture {
+        Some(capture) => {
+            // Keep JSON bodies structured so templates can walk into them
+            let body = serde_json::from_str(text)
+                .unwrap_or_else(|_| serde_json::Value::String(text.to_string()));
+            let value = serde_json::json!({ "status": status.as_u16(), "body": body });
+            result.with_variables(HashMap::from([(capture.variable.clone(), value)]))
+        }
+        None => result,
+    }
+}
+
+/// At most `max` bytes of `text`, cut at a character boundary
+fn truncate(text: &str, max: usize) -> &str {
+    if text.len() <= max {
+        return text;
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// Encoded request body and its content type
+#[derive(Debug, PartialEq, Eq)]
+struct RequestBody {
+    content_type: &'static str,
+    content: String,
+}
+
+/// Encode the action body according to `body_type`
+///
+/// Supported types are "json" (the default), "form", "text" and "none".
+/// Returns `Ok(None)` when there is nothing to send.
+fn build_body(
+    body_type: Option<&str>,
+    body: Option<&serde_json::Value>,
+) -> Result<Option<RequestBody>, String> {
+    let body_type = body_type.unwrap_or("json").to_ascii_lowercase();
+    let Some(body) = body.filter(|_| body_type != "none") else {
+        return Ok(None);
+    };
+
+    let (content_type, content) = match body_type.as_str() {
+        "json" => ("application/json", serde_json::to_string(body).unwrap_or_default()),
+        "form" => ("application/x-www-form-urlencoded", encode_form(body)?),
+        "text" => ("text/plain; charset=utf-8", value_to_text(body)),
+        other => {
+            return Err(format!(
+                "Unsupported body type: {} (expected json, form, text or none)",
+                other
+            ))
+        }
+    };
+
+    Ok(Some(RequestBody {
+        content_type,
+        content,
+    }))
+}
+
+/// Encode a flat JSON object as `application/x-www-form-urlencoded`
+///
+/// Nested objects and arrays are sent as their JSON text.
+fn encode_form(body: &serde_json::Value) -> Result<String, String> {
+    let fields = body
+        .as_object()
+        .ok_or_else(|| "Form body must be a JSON object".to_string())?;
+
+    Ok(fields
+        .iter()
+        .map(|(key, value)| format!("{}={}", form_escape(key), form_escape(&value_to_text(value))))
+        .collect::<Vec<_>>()
+        .join("&"))
+}
+
+/// Plain-text form of a JSON value: strings unquoted, null empty, the rest as JSON
+fn value_to_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Percent-encode a form key or value (spaces become '+')
+fn form_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'*' => out.push(byte as char),
+            b' ' => out.push('+'),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{SocketAddr, TcpListener};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn get_action(url: String) -> HttpAction {
+        HttpAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            method: HttpMethod::Get,
+            url,
+            headers: HashMap::new(),
+            body_type: None,
+            body: None,
+            timeout: None,
+            timeout_ms: Some(10_000),
+            capture: None,
+            retries: None,
+            retry_backoff_ms: None,
+        }
+    }
+
+    // ========== Body Encoding Tests ==========
+
+    #[test]
+    fn test_body_defaults_to_json() {
+        let body = serde_json::json!({"a": 1});
+        let built = build_body(None, Some(&body)).unwrap().unwrap();
+        assert_eq!(built.content_type, "application/json");
+        assert_eq!(built.content, r#"{"a":1}"#);
 
-    match request.send().await {
-        Ok(response) => {
-            let status = response.status();
-            if status.is_success() {
-                match response.text().await {
-                    Ok(text) => ActionResult::success_with_message(text, 0),
-                    Err(_) => ActionResult::success(0),
+        assert_eq!(build_body(Some("JSON"), Some(&body)).unwrap().unwrap(), built);
+    }
+
+    #[test]
+    fn test_body_form_encoding() {
+        let body = serde_json::json!({"name": "a b&c", "count": 3, "on": true, "empty": null});
+        let built = build_body(Some("form"), Some(&body)).unwrap().unwrap();
+        assert_eq!(built.content_type, "application/x-www-form-urlencoded");
+
+        let mut pairs: Vec<&str> = built.content.split('&').collect();
+        pairs.sort();
+        assert_eq!(pairs, vec!["count=3", "empty=", "name=a+b%26c", "on=true"]);
+    }
+
+    #[test]
+    fn test_body_form_escapes_unicode_and_nested_values() {
+        let body = serde_json::json!({"q": "é", "list": [1, 2]});
+        let built = build_body(Some("form"), Some(&body)).unwrap().unwrap();
+        assert!(built.content.contains("q=%C3%A9"));
+        assert!(built.content.contains("list=%5B1%2C2%5D"));
+    }
+
+    #[test]
+    fn test_body_form_requires_object() {
+        let body = serde_json::json!("not an object");
+        assert!(build_body(Some("form"), Some(&body)).is_err());
+    }
+
+    #[test]
+    fn test_body_text() {
+        let body = serde_json::json!("hello world");
+        let built = build_body(Some("text"), Some(&body)).unwrap().unwrap();
+        assert_eq!(built.content_type, "text/plain; charset=utf-8");
+        assert_eq!(built.content, "hello world");
+
+        let built = build_body(Some("text"), Some(&serde_json::json!(42))).unwrap().unwrap();
+        assert_eq!(built.content, "42");
+    }
+
+    #[test]
+    fn test_body_none_or_missing_sends_nothing() {
+        let body = serde_json::json!({"a": 1});
+        assert_eq!(build_body(Some("none"), Some(&body)).unwrap(), None);
+        assert_eq!(build_body(Some("json"), None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_body_unknown_type_is_error() {
+        let body = serde_json::json!({"a": 1});
+        let err = build_body(Some("xml"), Some(&body)).unwrap_err();
+        assert!(err.contains("xml"));
+    }
+
+    // ========== URL Validation Tests ==========
+
+    #[test]
+    fn test_validate_url_accepts_http_and_https() {
+        assert!(validate_url("http://homeassistant.local:8123/api").is_ok());
+        assert!(validate_url("https://example.com").is_ok());
+    }
+
+    #[test]
+    fn test_validate_url_rejects_other_schemes() {
+        let err = validate_url("file:///etc/passwd").unwrap_err();
+        assert!(err.contains("\"file\""), "{}", err);
+        let err = validate_url("ftp://example.com/file").unwrap_err();
+        assert!(err.contains("\"ftp\""), "{}", err);
+    }
+
+    #[test]
+    fn test_validate_url_rejects_unparseable() {
+        assert!(validate_url("not a url").unwrap_err().starts_with("Invalid URL"));
+        assert!(validate_url("example.com/path").is_err());
+        assert!(validate_url("http://").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_bad_url_before_sending() {
+        let result = execute(&get_action("file:///etc/passwd".to_string())).await;
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("only http and https"));
+    }
+
+    // ========== Private Network Guard Tests ==========
+
+    #[test]
+    fn test_private_addresses() {
+        let private = [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.10",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:192.168.0.1",
+        ];
+        for ip in private {
+            assert!(is_private_ip(ip.parse().unwrap()), "{} should be private", ip);
+        }
+        for ip in ["93.184.216.34", "8.8.8.8", "172.32.0.1", "2606:4700::1111"] {
+            assert!(!is_private_ip(ip.parse().unwrap()), "{} should be public", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_guard_rejects_private_ip() {
+        let url = validate_url("http://192.168.1.10:8123/api").unwrap();
+        let err = public_addrs(&url).await.unwrap_err().to_string();
+        assert!(err.contains("192.168.1.10"), "{}", err);
+
+        let url = validate_url("http://[::1]/").unwrap();
+        assert!(public_addrs(&url).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_guard_allows_public_ip() {
+        let url = validate_url("https://93.184.216.34/").unwrap();
+        let addrs = public_addrs(&url).await.unwrap();
+        assert_eq!(addrs, vec!["93.184.216.34:443".parse::<SocketAddr>().unwrap()]);
+    }
+
+    #[tokio::test]
+    async fn test_guard_blocks_request_only_when_on() {
+        let (addr, count) = status_server(&[200]);
+        let action = get_action(format!("http://{}/", addr));
+
+        let blocked = execute_with_cancellation(&action, true, &CancellationToken::new()).await;
+        assert!(!blocked.success);
+        assert!(blocked.error.unwrap().contains("private network"));
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+
+        let allowed = execute_with_cancellation(&action, false, &CancellationToken::new()).await;
+        assert!(allowed.success, "{:?}", allowed.error);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    // ========== Response Tests ==========
+
+    #[test]
+    fn test_success_reports_status_and_body() {
+        let result = response_result(reqwest::StatusCode::OK, "hello", None);
+        assert!(result.success);
+        assert_eq!(result.message.as_deref(), Some("200 OK: hello"));
+        assert!(result.variables.is_none());
+    }
+
+    #[test]
+    fn test_error_status_is_failure_with_truncated_body() {
+        let body = "x".repeat(BODY_PREVIEW_BYTES + 100);
+        let result = response_result(reqwest::StatusCode::NOT_FOUND, &body, None);
+        assert!(!result.success);
+
+        let error = result.error.unwrap();
+        assert!(error.starts_with("HTTP request failed with status: 404 Not Found: "));
+        assert!(error.ends_with(&"x".repeat(BODY_PREVIEW_BYTES)));
+        assert!(!error.ends_with(&"x".repeat(BODY_PREVIEW_BYTES + 1)));
+    }
+
+    #[test]
+    fn test_truncate_respects_char_boundaries() {
+        assert_eq!(truncate("héllo", 2), "h");
+        assert_eq!(truncate("héllo", 3), "hé");
+        assert_eq!(truncate("hi", 10), "hi");
+    }
+
+    #[test]
+    fn test_capture_stores_status_and_json_body() {
+        let capture = HttpCapture {
+            variable: "weather".to_string(),
+        };
+        let result = response_result(reqwest::StatusCode::OK, r#"{"temp": 21}"#, Some(&capture));
+
+        let vars = result.variables.unwrap();
+        assert_eq!(vars["weather"], serde_json::json!({"status": 200, "body": {"temp": 21}}));
+    }
+
+    #[test]
+    fn test_capture_keeps_non_json_body_as_text() {
+        let capture = HttpCapture {
+            variable: "page".to_string(),
+        };
+        let result = response_result(reqwest::StatusCode::BAD_GATEWAY, "oops", Some(&capture));
+
+        assert!(!result.success);
+        let vars = result.variables.unwrap();
+        assert_eq!(vars["page"], serde_json::json!({"status": 502, "body": "oops"}));
+    }
+
+    // ========== Retry Tests ==========
+
+    /// Mock server answering successive requests with the given statuses, counting requests
+    fn status_server(statuses: &'static [u16]) -> (SocketAddr, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let count = Arc::new(AtomicUsize::new(0));
+        let served = Arc::clone(&count);
+        std::thread::spawn(move || {
+            for (socket, status) in listener.incoming().zip(statuses) {
+                let mut socket = socket.unwrap();
+                // Read the request head before answering
+                let mut reader = BufReader::new(&socket);
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
                 }
-            } else {
-                ActionResult::failure(format!("HTTP request failed with status: {}", status), 0)
+                served.fetch_add(1, Ordering::SeqCst);
+                let response = format!("HTTP/1.1 {} X\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok", status);
+                socket.write_all(response.as_bytes()).unwrap();
             }
+        });
+        (addr, count)
+    }
+
+    fn retrying_action(addr: SocketAddr, retries: u32) -> HttpAction {
+        HttpAction {
+            retries: Some(retries),
+            retry_backoff_ms: Some(10),
+            ..get_action(format!("http://{}/", addr))
         }
-        Err(e) => ActionResult::failure(format!("HTTP request failed: {}", e), 0),
+    }
+
+    #[tokio::test]
+    async fn test_retries_server_errors_until_success() {
+        let (addr, count) = status_server(&[503, 429, 200]);
+        let result = execute(&retrying_action(addr, 3)).await;
+
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_are_not_retried() {
+        let (addr, count) = status_server(&[404, 200]);
+        let result = execute(&retrying_action(addr, 3)).await;
+
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("404"));
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retries_exhausted_returns_last_failure() {
+        let (addr, count) = status_server(&[500, 502]);
+        let result = execute(&retrying_action(addr, 1)).await;
+
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("502"));
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    // ========== Dry Run Tests ==========
+
+    #[tokio::test]
+    async fn test_dry_run_sends_no_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let action = crate::actions::types::Action::Http(get_action(url.clone()));
+
+        let result = crate::actions::execute_action_with_config(
+            &action,
+            &crate::actions::IntegrationConfig::default(),
+            true,
+        )
+        .await;
+
+        assert!(result.success);
+        assert_eq!(result.message, Some(format!("Would send GET {}", url)));
+        let err = listener.accept().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+    }
+
+    // ========== Cancellation Tests ==========
+
+    #[tokio::test]
+    async fn test_cancel_aborts_in_flight_request() {
+        // Mock server that reads the request but never answers
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            while let Ok(n) = socket.read(&mut buf) {
+                if n == 0 {
+                    break;
+                }
+            }
+        });
+
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            canceller.cancel();
+        });
+
+        let start = Instant::now();
+        let result = execute_with_cancellation(&get_action(format!("http://{}/slow", addr)), false, &token).await;
+
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("Cancelled"));
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_already_cancelled_token_skips_request() {
+        let token = CancellationToken::new();
+        token.cancel();
+
+        let result = execute_with_cancellation(&get_action("http://127.0.0.1:1/".to_string()), false, &token).await;
+
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("Cancelled"));
     }
 }
@@ -1,17 +1,44 @@
 //! Keyboard Handler
 //!
 //! Simulates keyboard input using platform-specific APIs.
-//! On Windows, uses SendInput from Win32 API.
+//! On Windows, uses SendInput from Win32 API. On Linux and macOS, uses `enigo`.
+//!
+//! Key and modifier names are first normalized to a canonical name (see
+//! `KEY_NAMES`), which each platform backend then maps to its own key code,
+//! so every platform accepts exactly the same names.
 
-use crate::actions::types::{ActionResult, KeyboardAction};
+use crate::actions::error::ActionError;
+use crate::actions::types::{ActionResult, KeyStep, KeyboardAction, ReleaseAllKeysAction};
 
 #[cfg(target_os = "windows")]
 use windows::Win32::UI::Input::KeyboardAndMouse::INPUT;
 
+#[cfg(not(target_os = "windows"))]
+use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+
 /// Execute a keyboard action
 pub async fn execute(config: &KeyboardAction) -> ActionResult {
+    if !config.sequence.is_empty() {
+        return execute_sequence(&config.sequence).await;
+    }
+
+    if let Some(hold_ms) = config.hold_duration.filter(|&ms| ms > 0) {
+        log::debug!("Holding key={} modifiers={:?} for {}ms", config.keys, config.modifiers, hold_ms);
+        return match plan_hold(config, hold_ms) {
+            Ok(steps) => execute_sequence(&steps).await,
+            Err(e) => ActionResult::from_error(e, 0),
+        };
+    }
+
     log::debug!("Executing keyboard action: key={}, modifiers={:?}", config.keys, config.modifiers);
+    press_combo(config)
+}
 
+/// Press and release `keys` with `modifiers` held, ignoring hold and sequence
+///
+/// Doesn't need the async runtime, so handlers running on a blocking
+/// thread (e.g. clipboard paste) can send keystrokes with it.
+pub(crate) fn press_combo(config: &KeyboardAction) -> ActionResult {
     #[cfg(target_os = "windows")]
     {
         execute_windows(config)
@@ -19,11 +46,315 @@ pub async fn execute(config: &KeyboardAction) -> ActionResult {
 
     #[cfg(not(target_os = "windows"))]
     {
-        let _ = config;
-        ActionResult::failure("Keyboard actions only supported on Windows".to_string(), 0)
+        execute_enigo(config)
+    }
+}
+
+/// Describe what a keyboard action would send, without sending it
+pub fn describe(config: &KeyboardAction) -> String {
+    if !config.sequence.is_empty() {
+        return format!("Would send {} key steps", config.sequence.len());
+    }
+
+    let combo: Vec<String> = config
+        .modifiers
+        .iter()
+        .chain(std::iter::once(&config.keys))
+        .map(|name| display_key_name(name))
+        .collect();
+    match config.hold_duration.filter(|&ms| ms > 0) {
+        Some(hold_ms) => format!("Would hold {} for {}ms", combo.join("+"), hold_ms),
+        None => format!("Would press {}", combo.join("+")),
+    }
+}
+
+/// Key name as shown to the user, e.g. "ctrl" -> "Ctrl"
+fn display_key_name(name: &str) -> String {
+    let mut chars = name.trim().chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Plan a held key combination as key steps
+///
+/// Modifiers go down first, then the key; after `hold_ms` the key comes up,
+/// followed by the modifiers in reverse order.
+fn plan_hold(config: &KeyboardAction, hold_ms: u64) -> Result<Vec<KeyStep>, ActionError> {
+    let key = normalize_key(&config.keys)
+        .ok_or_else(|| ActionError::InvalidInput(format!("Unknown key: {}", config.keys)))?;
+    let modifiers: Vec<&str> = config
+        .modifiers
+        .iter()
+        .filter_map(|m| normalize_modifier(m))
+        .collect();
+
+    let step = |key: &str, down: bool, delay_ms: u64| KeyStep {
+        key: key.to_string(),
+        down,
+        delay_ms,
+    };
+
+    let mut steps: Vec<KeyStep> = modifiers.iter().map(|m| step(m, true, 0)).collect();
+    steps.push(step(key, true, 0));
+    steps.push(step(key, false, hold_ms));
+    steps.extend(modifiers.iter().rev().map(|m| step(m, false, 0)));
+    Ok(steps)
+}
+
+/// Replay key steps in order, honouring the delay before each step
+async fn execute_sequence(sequence: &[KeyStep]) -> ActionResult {
+    log::debug!("Executing keyboard sequence: {} steps", sequence.len());
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::UI::Input::KeyboardAndMouse::SendInput;
+
+        for step in sequence {
+            let vk = match parse_key(&step.key) {
+                Some(vk) => vk,
+                None => {
+                    let _ = release_all_modifiers();
+                    return ActionResult::from_error(ActionError::InvalidInput(format!("Unknown key: {}", step.key)), 0);
+                }
+            };
+
+            if step.delay_ms > 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(step.delay_ms)).await;
+            }
+
+            let input = [create_key_input(vk, !step.down)];
+            let sent = unsafe { SendInput(&input, std::mem::size_of::<INPUT>() as i32) };
+            if sent != 1 {
+                let _ = release_all_modifiers();
+                return ActionResult::failure(format!("SendInput failed for key: {}", step.key), 0);
+            }
+        }
+
+        ActionResult::success(0)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        // enigo connections are blocking, so the whole replay runs off the async runtime
+        let steps = sequence.to_vec();
+        let replay = tokio::task::spawn_blocking(move || {
+            let mut enigo = enigo_connect().map_err(ActionError::Failed)?;
+            for step in &steps {
+                let key = parse_key(&step.key)
+                    .ok_or_else(|| ActionError::InvalidInput(format!("Unknown key: {}", step.key)))?;
+                if step.delay_ms > 0 {
+                    std::thread::sleep(std::time::Duration::from_millis(step.delay_ms));
+                }
+                let direction = if step.down { Direction::Press } else { Direction::Release };
+                enigo
+                    .key(key, direction)
+                    .map_err(|e| ActionError::Failed(format!("Failed to send key {}: {}", step.key, e)))?;
+            }
+            Ok::<(), ActionError>(())
+        });
+
+        match replay.await {
+            Ok(Ok(())) => ActionResult::success(0),
+            Ok(Err(e)) => {
+                let _ = release_all_modifiers();
+                ActionResult::from_error(e, 0)
+            }
+            Err(e) => ActionResult::failure(format!("Keyboard sequence task failed: {}", e), 0),
+        }
+    }
+}
+
+/// Describe a release-all-keys action
+pub fn describe_release_all(_config: &ReleaseAllKeysAction) -> String {
+    "Would release all modifier keys".to_string()
+}
+
+/// Execute a release-all-keys action
+pub async fn execute_release_all(_config: &ReleaseAllKeysAction) -> ActionResult {
+    match release_all_modifiers() {
+        Ok(()) => ActionResult::success_with_message("Released all modifier keys".to_string(), 0),
+        Err(e) => ActionResult::failure(e, 0),
     }
 }
 
+/// Release all modifier keys (Ctrl/Alt/Shift/Win, both sides)
+///
+/// Sends a key-up for every modifier so nothing stays logically held if a
+/// keyboard action was interrupted mid-press. Safe to call when no keys are held.
+pub fn release_all_modifiers() -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+        let inputs: Vec<INPUT> = [
+            VK_LCONTROL, VK_RCONTROL,
+            VK_LMENU, VK_RMENU,
+            VK_LSHIFT, VK_RSHIFT,
+            VK_LWIN, VK_RWIN,
+        ]
+        .iter()
+        .map(|&vk| create_key_input(vk, true))
+        .collect();
+
+        let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+        if sent as usize != inputs.len() {
+            return Err(format!(
+                "SendInput failed: sent {} of {} inputs",
+                sent,
+                inputs.len()
+            ));
+        }
+        log::debug!("Released all modifier keys");
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let mut enigo = enigo_connect()?;
+        for name in MODIFIER_NAMES {
+            // Not every platform has both sides of every modifier
+            if let Some(key) = enigo_key(name) {
+                enigo
+                    .key(key, Direction::Release)
+                    .map_err(|e| format!("Failed to release {}: {}", name, e))?;
+            }
+        }
+        log::debug!("Released all modifier keys");
+    }
+
+    Ok(())
+}
+
+// =============================================================================
+// Key Names (shared by all platforms)
+// =============================================================================
+
+/// Canonical key names accepted on every platform
+///
+/// `normalize_key` maps aliases onto these; each backend maps them to its
+/// own key codes.
+const KEY_NAMES: &[&str] = &[
+    "a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m",
+    "n", "o", "p", "q", "r", "s", "t", "u", "v", "w", "x", "y", "z",
+    "0", "1", "2", "3", "4", "5", "6", "7", "8", "9",
+    "f1", "f2", "f3", "f4", "f5", "f6", "f7", "f8", "f9", "f10", "f11", "f12",
+    "f13", "f14", "f15", "f16", "f17", "f18", "f19", "f20", "f21", "f22", "f23", "f24",
+    "up", "down", "left", "right", "home", "end", "pageup", "pagedown",
+    "enter", "tab", "space", "backspace", "delete", "insert", "escape",
+    "lctrl", "rctrl", "lalt", "ralt", "lshift", "rshift", "lwin", "rwin",
+    "capslock", "numlock", "scrolllock",
+    "pause", "printscreen", "menu",
+    "grave", "minus", "equal", "leftbracket", "rightbracket", "backslash",
+    "semicolon", "quote", "comma", "period", "slash",
+    "numpad0", "numpad1", "numpad2", "numpad3", "numpad4",
+    "numpad5", "numpad6", "numpad7", "numpad8", "numpad9",
+    "add", "subtract", "multiply", "divide", "decimal",
+    "mute", "volumedown", "volumeup", "playpause", "stop", "nexttrack", "previoustrack",
+    "browserback", "browserforward", "browserrefresh", "browserstop",
+    "browsersearch", "browserfavorites", "browserhome",
+];
+
+/// Canonical names of every modifier key, both sides
+#[cfg(not(target_os = "windows"))]
+const MODIFIER_NAMES: &[&str] = &["lctrl", "rctrl", "lalt", "ralt", "lshift", "rshift", "lwin", "rwin"];
+
+/// Map a modifier string to its canonical key name
+fn normalize_modifier(modifier: &str) -> Option<&'static str> {
+    match modifier.to_lowercase().as_str() {
+        "ctrl" | "control" | "lctrl" => Some("lctrl"),
+        "rctrl" => Some("rctrl"),
+        "alt" | "lalt" => Some("lalt"),
+        "ralt" => Some("ralt"),
+        "shift" | "lshift" => Some("lshift"),
+        "rshift" => Some("rshift"),
+        "win" | "windows" | "super" | "meta" | "cmd" | "lwin" => Some("lwin"),
+        "rwin" => Some("rwin"),
+        _ => None,
+    }
+}
+
+/// Map a key string (any accepted alias, any case) to its canonical key name
+fn normalize_key(key: &str) -> Option<&'static str> {
+    let key_lower = key.to_lowercase();
+    let key_str = key_lower.as_str();
+
+    if let Some(name) = normalize_modifier(key_str) {
+        return Some(name);
+    }
+
+    let alias = match key_str {
+        // Navigation
+        "uparrow" => "up",
+        "downarrow" => "down",
+        "leftarrow" => "left",
+        "rightarrow" => "right",
+        "pgup" | "prior" => "pageup",
+        "pgdn" | "next" => "pagedown",
+
+        // Editing
+        "return" => "enter",
+        " " => "space",
+        "back" => "backspace",
+        "del" => "delete",
+        "ins" => "insert",
+        "esc" => "escape",
+
+        // Lock keys
+        "caps" => "capslock",
+        "num" => "numlock",
+        "scroll" => "scrolllock",
+
+        // Special keys
+        "break" => "pause",
+        "print" | "prtsc" => "printscreen",
+        "apps" | "contextmenu" => "menu",
+
+        // Punctuation
+        "`" | "backtick" => "grave",
+        "-" => "minus",
+        "=" | "equals" => "equal",
+        "[" | "openbracket" => "leftbracket",
+        "]" | "closebracket" => "rightbracket",
+        "\\" => "backslash",
+        ";" => "semicolon",
+        "'" | "apostrophe" => "quote",
+        "," => "comma",
+        "." | "dot" => "period",
+        "/" | "forwardslash" => "slash",
+
+        // Numpad
+        "num0" => "numpad0",
+        "num1" => "numpad1",
+        "num2" => "numpad2",
+        "num3" => "numpad3",
+        "num4" => "numpad4",
+        "num5" => "numpad5",
+        "num6" => "numpad6",
+        "num7" => "numpad7",
+        "num8" => "numpad8",
+        "num9" => "numpad9",
+        "numpadplus" | "numpad+" => "add",
+        "numpadminus" | "numpad-" => "subtract",
+        "numpadmultiply" | "numpad*" => "multiply",
+        "numpaddivide" | "numpad/" => "divide",
+        "numpaddecimal" | "numpad." => "decimal",
+
+        // Media keys
+        "volumemute" | "audiomute" => "mute",
+        "voldown" | "audiovoldown" => "volumedown",
+        "volup" | "audiovolup" => "volumeup",
+        "play" | "mediaplaypause" => "playpause",
+        "mediastop" => "stop",
+        "medianext" => "nexttrack",
+        "prev" | "previous" | "mediaprev" => "previoustrack",
+
+        other => other,
+    };
+
+    KEY_NAMES.iter().copied().find(|name| *name == alias)
+}
+
 #[cfg(target_os = "windows")]
 fn execute_windows(config: &KeyboardAction) -> ActionResult {
     use windows::Win32::UI::Input::KeyboardAndMouse::*;
@@ -31,7 +362,7 @@ fn execute_windows(config: &KeyboardAction) -> ActionResult {
     // Parse the key to a virtual key code
     let vk = match parse_key(&config.keys) {
         Some(vk) => vk,
-        None => return ActionResult::failure(format!("Unknown key: {}", config.keys), 0),
+        None => return ActionResult::from_error(ActionError::InvalidInput(format!("Unknown key: {}", config.keys)), 0),
     };
 
     // Parse modifiers
@@ -132,242 +463,587 @@ fn is_extended_key(vk: VIRTUAL_KEY) -> bool {
 /// Parse a modifier string to a virtual key code
 #[cfg(target_os = "windows")]
 fn parse_modifier(modifier: &str) -> Option<VIRTUAL_KEY> {
+    normalize_modifier(modifier).and_then(vk_for_name)
+}
+
+/// Parse a key string to a virtual key code
+#[cfg(target_os = "windows")]
+fn parse_key(key: &str) -> Option<VIRTUAL_KEY> {
+    normalize_key(key).and_then(vk_for_name)
+}
+
+/// Map a canonical key name to a virtual key code
+#[cfg(target_os = "windows")]
+fn vk_for_name(name: &str) -> Option<VIRTUAL_KEY> {
     use windows::Win32::UI::Input::KeyboardAndMouse::*;
 
-    match modifier.to_lowercase().as_str() {
-        "ctrl" | "control" | "lctrl" => Some(VK_LCONTROL),
-        "rctrl" => Some(VK_RCONTROL),
-        "alt" | "lalt" => Some(VK_LMENU),
-        "ralt" => Some(VK_RMENU),
-        "shift" | "lshift" => Some(VK_LSHIFT),
-        "rshift" => Some(VK_RSHIFT),
-        "win" | "windows" | "super" | "meta" | "cmd" | "lwin" => Some(VK_LWIN),
-        "rwin" => Some(VK_RWIN),
-        _ => None,
+    // Letters and digits share their ASCII codes
+    if let [c] = name.as_bytes() {
+        if c.is_ascii_lowercase() || c.is_ascii_digit() {
+            return Some(VIRTUAL_KEY(c.to_ascii_uppercase() as u16));
+        }
     }
+    if let Some(n) = name.strip_prefix('f').and_then(|n| n.parse::<u16>().ok()) {
+        return (1..=24).contains(&n).then_some(VIRTUAL_KEY(VK_F1.0 + n - 1));
+    }
+    if let Some(n) = name.strip_prefix("numpad").and_then(|n| n.parse::<u16>().ok()) {
+        return (n <= 9).then_some(VIRTUAL_KEY(VK_NUMPAD0.0 + n));
+    }
+
+    let vk = match name {
+        // Navigation
+        "up" => VK_UP,
+        "down" => VK_DOWN,
+        "left" => VK_LEFT,
+        "right" => VK_RIGHT,
+        "home" => VK_HOME,
+        "end" => VK_END,
+        "pageup" => VK_PRIOR,
+        "pagedown" => VK_NEXT,
+
+        // Editing
+        "enter" => VK_RETURN,
+        "tab" => VK_TAB,
+        "space" => VK_SPACE,
+        "backspace" => VK_BACK,
+        "delete" => VK_DELETE,
+        "insert" => VK_INSERT,
+        "escape" => VK_ESCAPE,
+
+        // Modifiers (can also be used as keys)
+        "lctrl" => VK_LCONTROL,
+        "rctrl" => VK_RCONTROL,
+        "lalt" => VK_LMENU,
+        "ralt" => VK_RMENU,
+        "lshift" => VK_LSHIFT,
+        "rshift" => VK_RSHIFT,
+        "lwin" => VK_LWIN,
+        "rwin" => VK_RWIN,
+
+        // Lock keys
+        "capslock" => VK_CAPITAL,
+        "numlock" => VK_NUMLOCK,
+        "scrolllock" => VK_SCROLL,
+
+        // Special keys
+        "pause" => VK_PAUSE,
+        "printscreen" => VK_SNAPSHOT,
+        "menu" => VK_APPS,
+
+        // Punctuation
+        "grave" => VK_OEM_3,
+        "minus" => VK_OEM_MINUS,
+        "equal" => VK_OEM_PLUS,
+        "leftbracket" => VK_OEM_4,
+        "rightbracket" => VK_OEM_6,
+        "backslash" => VK_OEM_5,
+        "semicolon" => VK_OEM_1,
+        "quote" => VK_OEM_7,
+        "comma" => VK_OEM_COMMA,
+        "period" => VK_OEM_PERIOD,
+        "slash" => VK_OEM_2,
+
+        // Numpad operators
+        "add" => VK_ADD,
+        "subtract" => VK_SUBTRACT,
+        "multiply" => VK_MULTIPLY,
+        "divide" => VK_DIVIDE,
+        "decimal" => VK_DECIMAL,
+
+        // Media keys
+        "mute" => VK_VOLUME_MUTE,
+        "volumedown" => VK_VOLUME_DOWN,
+        "volumeup" => VK_VOLUME_UP,
+        "playpause" => VK_MEDIA_PLAY_PAUSE,
+        "stop" => VK_MEDIA_STOP,
+        "nexttrack" => VK_MEDIA_NEXT_TRACK,
+        "previoustrack" => VK_MEDIA_PREV_TRACK,
+
+        // Browser keys
+        "browserback" => VK_BROWSER_BACK,
+        "browserforward" => VK_BROWSER_FORWARD,
+        "browserrefresh" => VK_BROWSER_REFRESH,
+        "browserstop" => VK_BROWSER_STOP,
+        "browsersearch" => VK_BROWSER_SEARCH,
+        "browserfavorites" => VK_BROWSER_FAVORITES,
+        "browserhome" => VK_BROWSER_HOME,
+
+        _ => return None,
+    };
+
+    Some(vk)
 }
 
-/// Parse a key string to a virtual key code
+/// Convert a virtual key code back to the name `parse_key` accepts
+///
+/// Modifiers keep their side ("lctrl", "rshift") so recorded sequences replay
+/// exactly what was pressed.
 #[cfg(target_os = "windows")]
-fn parse_key(key: &str) -> Option<VIRTUAL_KEY> {
+pub(crate) fn vk_to_key_name(vk: VIRTUAL_KEY) -> Option<String> {
     use windows::Win32::UI::Input::KeyboardAndMouse::*;
 
-    let key_lower = key.to_lowercase();
-    let key_str = key_lower.as_str();
+    // Letters and digits share their ASCII codes
+    if (VK_A.0..=VK_Z.0).contains(&vk.0) || (VK_0.0..=VK_9.0).contains(&vk.0) {
+        return Some((vk.0 as u8 as char).to_ascii_lowercase().to_string());
+    }
+    if (VK_F1.0..=VK_F24.0).contains(&vk.0) {
+        return Some(format!("f{}", vk.0 - VK_F1.0 + 1));
+    }
+    if (VK_NUMPAD0.0..=VK_NUMPAD9.0).contains(&vk.0) {
+        return Some(format!("numpad{}", vk.0 - VK_NUMPAD0.0));
+    }
+
+    let name = match vk {
+        VK_UP => "up",
+        VK_DOWN => "down",
+        VK_LEFT => "left",
+        VK_RIGHT => "right",
+        VK_HOME => "home",
+        VK_END => "end",
+        VK_PRIOR => "pageup",
+        VK_NEXT => "pagedown",
+        VK_RETURN => "enter",
+        VK_TAB => "tab",
+        VK_SPACE => "space",
+        VK_BACK => "backspace",
+        VK_DELETE => "delete",
+        VK_INSERT => "insert",
+        VK_ESCAPE => "escape",
+        VK_LCONTROL | VK_CONTROL => "lctrl",
+        VK_RCONTROL => "rctrl",
+        VK_LMENU | VK_MENU => "lalt",
+        VK_RMENU => "ralt",
+        VK_LSHIFT | VK_SHIFT => "lshift",
+        VK_RSHIFT => "rshift",
+        VK_LWIN => "lwin",
+        VK_RWIN => "rwin",
+        VK_CAPITAL => "capslock",
+        VK_NUMLOCK => "numlock",
+        VK_SCROLL => "scrolllock",
+        VK_PAUSE => "pause",
+        VK_SNAPSHOT => "printscreen",
+        VK_APPS => "menu",
+        VK_OEM_3 => "grave",
+        VK_OEM_MINUS => "minus",
+        VK_OEM_PLUS => "equal",
+        VK_OEM_4 => "leftbracket",
+        VK_OEM_6 => "rightbracket",
+        VK_OEM_5 => "backslash",
+        VK_OEM_1 => "semicolon",
+        VK_OEM_7 => "quote",
+        VK_OEM_COMMA => "comma",
+        VK_OEM_PERIOD => "period",
+        VK_OEM_2 => "slash",
+        VK_ADD => "add",
+        VK_SUBTRACT => "subtract",
+        VK_MULTIPLY => "multiply",
+        VK_DIVIDE => "divide",
+        VK_DECIMAL => "decimal",
+        VK_VOLUME_MUTE => "mute",
+        VK_VOLUME_DOWN => "volumedown",
+        VK_VOLUME_UP => "volumeup",
+        VK_MEDIA_PLAY_PAUSE => "playpause",
+        VK_MEDIA_STOP => "stop",
+        VK_MEDIA_NEXT_TRACK => "nexttrack",
+        VK_MEDIA_PREV_TRACK => "previoustrack",
+        _ => return None,
+    };
 
-    // Try single character first
-    if key.len() == 1 {
-        let c = key.chars().next().unwrap();
-        if let Some(vk) = char_to_vk(c) {
-            return Some(vk);
+    Some(name.to_string())
+}
+
+// =============================================================================
+// Linux / macOS backend (enigo)
+// =============================================================================
+
+#[cfg(not(target_os = "windows"))]
+fn execute_enigo(config: &KeyboardAction) -> ActionResult {
+    let key = match parse_key(&config.keys) {
+        Some(key) => key,
+        None => return ActionResult::from_error(ActionError::InvalidInput(format!("Unknown key: {}", config.keys)), 0),
+    };
+
+    let modifiers: Vec<Key> = config
+        .modifiers
+        .iter()
+        .filter_map(|m| parse_modifier(m))
+        .collect();
+
+    match send_enigo_combination(&modifiers, key) {
+        Ok(()) => ActionResult::success(0),
+        Err(e) => ActionResult::failure(e, 0),
+    }
+}
+
+/// Send a key combination (modifiers + key)
+#[cfg(not(target_os = "windows"))]
+fn send_enigo_combination(modifiers: &[Key], key: Key) -> Result<(), String> {
+    let mut enigo = enigo_connect()?;
+
+    let result = (|| {
+        for &modifier in modifiers {
+            enigo.key(modifier, Direction::Press)?;
         }
+        enigo.key(key, Direction::Click)
+    })();
+
+    // Release modifiers in reverse order even if the key itself failed
+    for &modifier in modifiers.iter().rev() {
+        let _ = enigo.key(modifier, Direction::Release);
     }
 
-    // Named keys
-    match key_str {
-        // Letters (for explicit naming)
-        "a" => Some(VK_A),
-        "b" => Some(VK_B),
-        "c" => Some(VK_C),
-        "d" => Some(VK_D),
-        "e" => Some(VK_E),
-        "f" => Some(VK_F),
-        "g" => Some(VK_G),
-        "h" => Some(VK_H),
-        "i" => Some(VK_I),
-        "j" => Some(VK_J),
-        "k" => Some(VK_K),
-        "l" => Some(VK_L),
-        "m" => Some(VK_M),
-        "n" => Some(VK_N),
-        "o" => Some(VK_O),
-        "p" => Some(VK_P),
-        "q" => Some(VK_Q),
-        "r" => Some(VK_R),
-        "s" => Some(VK_S),
-        "t" => Some(VK_T),
-        "u" => Some(VK_U),
-        "v" => Some(VK_V),
-        "w" => Some(VK_W),
-        "x" => Some(VK_X),
-        "y" => Some(VK_Y),
-        "z" => Some(VK_Z),
-
-        // Numbers
-        "0" => Some(VK_0),
-        "1" => Some(VK_1),
-        "2" => Some(VK_2),
-        "3" => Some(VK_3),
-        "4" => Some(VK_4),
-        "5" => Some(VK_5),
-        "6" => Some(VK_6),
-        "7" => Some(VK_7),
-        "8" => Some(VK_8),
-        "9" => Some(VK_9),
+    result.map_err(|e| format!("Failed to send key: {}", e))
+}
+
+/// Open a connection to the platform input system
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn enigo_connect() -> Result<Enigo, String> {
+    Enigo::new(&Settings::default()).map_err(|e| format!("Failed to initialize keyboard input: {}", e))
+}
+
+/// Parse a modifier string to an enigo key
+#[cfg(not(target_os = "windows"))]
+fn parse_modifier(modifier: &str) -> Option<Key> {
+    normalize_modifier(modifier).and_then(enigo_key)
+}
+
+/// Parse a key string to an enigo key
+#[cfg(not(target_os = "windows"))]
+fn parse_key(key: &str) -> Option<Key> {
+    normalize_key(key).and_then(enigo_key)
+}
+
+/// Map a canonical key name to an enigo key
+///
+/// Returns `None` for the few keys the platform has no equivalent for
+/// (e.g. F21-F24 and the browser keys on macOS).
+#[cfg(not(target_os = "windows"))]
+fn enigo_key(name: &str) -> Option<Key> {
+    // Letters and digits
+    if let [c] = name.as_bytes() {
+        if c.is_ascii_lowercase() || c.is_ascii_digit() {
+            return Some(Key::Unicode(*c as char));
+        }
+    }
 
+    let key = match name {
         // Function keys
-        "f1" => Some(VK_F1),
-        "f2" => Some(VK_F2),
-        "f3" => Some(VK_F3),
-        "f4" => Some(VK_F4),
-        "f5" => Some(VK_F5),
-        "f6" => Some(VK_F6),
-        "f7" => Some(VK_F7),
-        "f8" => Some(VK_F8),
-        "f9" => Some(VK_F9),
-        "f10" => Some(VK_F10),
-        "f11" => Some(VK_F11),
-        "f12" => Some(VK_F12),
-        "f13" => Some(VK_F13),
-        "f14" => Some(VK_F14),
-        "f15" => Some(VK_F15),
-        "f16" => Some(VK_F16),
-        "f17" => Some(VK_F17),
-        "f18" => Some(VK_F18),
-        "f19" => Some(VK_F19),
-        "f20" => Some(VK_F20),
-        "f21" => Some(VK_F21),
-        "f22" => Some(VK_F22),
-        "f23" => Some(VK_F23),
-        "f24" => Some(VK_F24),
+        "f1" => Key::F1,
+        "f2" => Key::F2,
+        "f3" => Key::F3,
+        "f4" => Key::F4,
+        "f5" => Key::F5,
+        "f6" => Key::F6,
+        "f7" => Key::F7,
+        "f8" => Key::F8,
+        "f9" => Key::F9,
+        "f10" => Key::F10,
+        "f11" => Key::F11,
+        "f12" => Key::F12,
+        "f13" => Key::F13,
+        "f14" => Key::F14,
+        "f15" => Key::F15,
+        "f16" => Key::F16,
+        "f17" => Key::F17,
+        "f18" => Key::F18,
+        "f19" => Key::F19,
+        "f20" => Key::F20,
+        #[cfg(not(target_os = "macos"))]
+        "f21" => Key::F21,
+        #[cfg(not(target_os = "macos"))]
+        "f22" => Key::F22,
+        #[cfg(not(target_os = "macos"))]
+        "f23" => Key::F23,
+        #[cfg(not(target_os = "macos"))]
+        "f24" => Key::F24,
 
         // Navigation
-        "up" | "uparrow" => Some(VK_UP),
-        "down" | "downarrow" => Some(VK_DOWN),
-        "left" | "leftarrow" => Some(VK_LEFT),
-        "right" | "rightarrow" => Some(VK_RIGHT),
-        "home" => Some(VK_HOME),
-        "end" => Some(VK_END),
-        "pageup" | "pgup" | "prior" => Some(VK_PRIOR),
-        "pagedown" | "pgdn" | "next" => Some(VK_NEXT),
+        "up" => Key::UpArrow,
+        "down" => Key::DownArrow,
+        "left" => Key::LeftArrow,
+        "right" => Key::RightArrow,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
 
         // Editing
-        "enter" | "return" => Some(VK_RETURN),
-        "tab" => Some(VK_TAB),
-        "space" | " " => Some(VK_SPACE),
-        "backspace" | "back" => Some(VK_BACK),
-        "delete" | "del" => Some(VK_DELETE),
-        "insert" | "ins" => Some(VK_INSERT),
-        "escape" | "esc" => Some(VK_ESCAPE),
+        "enter" => Key::Return,
+        "tab" => Key::Tab,
+        "space" => Key::Space,
+        "backspace" => Key::Backspace,
+        "delete" => Key::Delete,
+        #[cfg(not(target_os = "macos"))]
+        "insert" => Key::Insert,
+        // Mac keyboards put Help where Insert would be
+        #[cfg(target_os = "macos")]
+        "insert" => Key::Help,
+        "escape" => Key::Escape,
 
-        // Modifiers (can also be used as keys)
-        "ctrl" | "control" | "lctrl" => Some(VK_LCONTROL),
-        "rctrl" => Some(VK_RCONTROL),
-        "alt" | "lalt" => Some(VK_LMENU),
-        "ralt" => Some(VK_RMENU),
-        "shift" | "lshift" => Some(VK_LSHIFT),
-        "rshift" => Some(VK_RSHIFT),
-        "win" | "windows" | "super" | "meta" | "cmd" | "lwin" => Some(VK_LWIN),
-        "rwin" => Some(VK_RWIN),
+        // Modifiers
+        "lctrl" => Key::LControl,
+        "rctrl" => Key::RControl,
+        "lalt" => Key::Alt,
+        "lshift" => Key::LShift,
+        "rshift" => Key::RShift,
+        "lwin" => Key::Meta,
+        #[cfg(target_os = "macos")]
+        "ralt" => Key::ROption,
+        #[cfg(target_os = "macos")]
+        "rwin" => Key::RCommand,
+        #[cfg(not(target_os = "macos"))]
+        "ralt" => Key::Other(x11_keysym::ALT_R),
+        #[cfg(not(target_os = "macos"))]
+        "rwin" => Key::Other(x11_keysym::SUPER_R),
 
         // Lock keys
-        "capslock" | "caps" => Some(VK_CAPITAL),
-        "numlock" | "num" => Some(VK_NUMLOCK),
-        "scrolllock" | "scroll" => Some(VK_SCROLL),
+        "capslock" => Key::CapsLock,
+        #[cfg(not(target_os = "macos"))]
+        "numlock" => Key::Numlock,
+        #[cfg(not(target_os = "macos"))]
+        "scrolllock" => Key::ScrollLock,
 
         // Special keys
-        "pause" | "break" => Some(VK_PAUSE),
-        "print" | "printscreen" | "prtsc" => Some(VK_SNAPSHOT),
-        "menu" | "apps" | "contextmenu" => Some(VK_APPS),
+        #[cfg(not(target_os = "macos"))]
+        "pause" => Key::Pause,
+        #[cfg(not(target_os = "macos"))]
+        "printscreen" => Key::PrintScr,
+        #[cfg(not(target_os = "macos"))]
+        "menu" => Key::LMenu,
 
         // Punctuation
-        "grave" | "`" | "backtick" => Some(VK_OEM_3),
-        "minus" | "-" => Some(VK_OEM_MINUS),
-        "equal" | "=" | "equals" => Some(VK_OEM_PLUS),
-        "leftbracket" | "[" | "openbracket" => Some(VK_OEM_4),
-        "rightbracket" | "]" | "closebracket" => Some(VK_OEM_6),
-        "backslash" | "\\" => Some(VK_OEM_5),
-        "semicolon" | ";" => Some(VK_OEM_1),
-        "quote" | "'" | "apostrophe" => Some(VK_OEM_7),
-        "comma" | "," => Some(VK_OEM_COMMA),
-        "period" | "." | "dot" => Some(VK_OEM_PERIOD),
-        "slash" | "/" | "forwardslash" => Some(VK_OEM_2),
+        "grave" => Key::Unicode('`'),
+        "minus" => Key::Unicode('-'),
+        "equal" => Key::Unicode('='),
+        "leftbracket" => Key::Unicode('['),
+        "rightbracket" => Key::Unicode(']'),
+        "backslash" => Key::Unicode('\\'),
+        "semicolon" => Key::Unicode(';'),
+        "quote" => Key::Unicode('\''),
+        "comma" => Key::Unicode(','),
+        "period" => Key::Unicode('.'),
+        "slash" => Key::Unicode('/'),
 
         // Numpad
-        "numpad0" | "num0" => Some(VK_NUMPAD0),
-        "numpad1" | "num1" => Some(VK_NUMPAD1),
-        "numpad2" | "num2" => Some(VK_NUMPAD2),
-        "numpad3" | "num3" => Some(VK_NUMPAD3),
-        "numpad4" | "num4" => Some(VK_NUMPAD4),
-        "numpad5" | "num5" => Some(VK_NUMPAD5),
-        "numpad6" | "num6" => Some(VK_NUMPAD6),
-        "numpad7" | "num7" => Some(VK_NUMPAD7),
-        "numpad8" | "num8" => Some(VK_NUMPAD8),
-        "numpad9" | "num9" => Some(VK_NUMPAD9),
-        "add" | "numpadplus" | "numpad+" => Some(VK_ADD),
-        "subtract" | "numpadminus" | "numpad-" => Some(VK_SUBTRACT),
-        "multiply" | "numpadmultiply" | "numpad*" => Some(VK_MULTIPLY),
-        "divide" | "numpaddivide" | "numpad/" => Some(VK_DIVIDE),
-        "decimal" | "numpaddecimal" | "numpad." => Some(VK_DECIMAL),
+        "numpad0" => Key::Numpad0,
+        "numpad1" => Key::Numpad1,
+        "numpad2" => Key::Numpad2,
+        "numpad3" => Key::Numpad3,
+        "numpad4" => Key::Numpad4,
+        "numpad5" => Key::Numpad5,
+        "numpad6" => Key::Numpad6,
+        "numpad7" => Key::Numpad7,
+        "numpad8" => Key::Numpad8,
+        "numpad9" => Key::Numpad9,
+        "add" => Key::Add,
+        "subtract" => Key::Subtract,
+        "multiply" => Key::Multiply,
+        "divide" => Key::Divide,
+        "decimal" => Key::Decimal,
 
         // Media keys
-        "mute" | "volumemute" | "audiomute" => Some(VK_VOLUME_MUTE),
-        "volumedown" | "voldown" | "audiovoldown" => Some(VK_VOLUME_DOWN),
-        "volumeup" | "volup" | "audiovolup" => Some(VK_VOLUME_UP),
-        "playpause" | "play" | "mediaplaypause" => Some(VK_MEDIA_PLAY_PAUSE),
-        "stop" | "mediastop" => Some(VK_MEDIA_STOP),
-        "nexttrack" | "medianext" => Some(VK_MEDIA_NEXT_TRACK),
-        "previoustrack" | "prev" | "previous" | "mediaprev" => Some(VK_MEDIA_PREV_TRACK),
+        "mute" => Key::VolumeMute,
+        "volumedown" => Key::VolumeDown,
+        "volumeup" => Key::VolumeUp,
+        "playpause" => Key::MediaPlayPause,
+        #[cfg(not(target_os = "macos"))]
+        "stop" => Key::MediaStop,
+        "nexttrack" => Key::MediaNextTrack,
+        "previoustrack" => Key::MediaPrevTrack,
 
         // Browser keys
-        "browserback" => Some(VK_BROWSER_BACK),
-        "browserforward" => Some(VK_BROWSER_FORWARD),
-        "browserrefresh" => Some(VK_BROWSER_REFRESH),
-        "browserstop" => Some(VK_BROWSER_STOP),
-        "browsersearch" => Some(VK_BROWSER_SEARCH),
-        "browserfavorites" => Some(VK_BROWSER_FAVORITES),
-        "browserhome" => Some(VK_BROWSER_HOME),
+        #[cfg(not(target_os = "macos"))]
+        "browserback" => Key::Other(x11_keysym::XF86_BACK),
+        #[cfg(not(target_os = "macos"))]
+        "browserforward" => Key::Other(x11_keysym::XF86_FORWARD),
+        #[cfg(not(target_os = "macos"))]
+        "browserrefresh" => Key::Other(x11_keysym::XF86_REFRESH),
+        #[cfg(not(target_os = "macos"))]
+        "browserstop" => Key::Other(x11_keysym::XF86_STOP),
+        #[cfg(not(target_os = "macos"))]
+        "browsersearch" => Key::Other(x11_keysym::XF86_SEARCH),
+        #[cfg(not(target_os = "macos"))]
+        "browserfavorites" => Key::Other(x11_keysym::XF86_FAVORITES),
+        #[cfg(not(target_os = "macos"))]
+        "browserhome" => Key::Other(x11_keysym::XF86_HOME_PAGE),
 
-        _ => None,
-    }
-}
+        _ => return None,
+    };
 
-/// Convert a character to a virtual key code
-#[cfg(target_os = "windows")]
-fn char_to_vk(c: char) -> Option<VIRTUAL_KEY> {
-    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+    Some(key)
+}
 
-    match c.to_ascii_uppercase() {
-        'A' => Some(VK_A),
-        'B' => Some(VK_B),
-        'C' => Some(VK_C),
-        'D' => Some(VK_D),
-        'E' => Some(VK_E),
-        'F' => Some(VK_F),
-        'G' => Some(VK_G),
-        'H' => Some(VK_H),
-        'I' => Some(VK_I),
-        'J' => Some(VK_J),
-        'K' => Some(VK_K),
-        'L' => Some(VK_L),
-        'M' => Some(VK_M),
-        'N' => Some(VK_N),
-        'O' => Some(VK_O),
-        'P' => Some(VK_P),
-        'Q' => Some(VK_Q),
-        'R' => Some(VK_R),
-        'S' => Some(VK_S),
-        'T' => Some(VK_T),
-        'U' => Some(VK_U),
-        'V' => Some(VK_V),
-        'W' => Some(VK_W),
-        'X' => Some(VK_X),
-        'Y' => Some(VK_Y),
-        'Z' => Some(VK_Z),
-        '0' => Some(VK_0),
-        '1' => Some(VK_1),
-        '2' => Some(VK_2),
-        '3' => Some(VK_3),
-        '4' => Some(VK_4),
-        '5' => Some(VK_5),
-        '6' => Some(VK_6),
-        '7' => Some(VK_7),
-        '8' => Some(VK_8),
-        '9' => Some(VK_9),
-        ' ' => Some(VK_SPACE),
-        _ => None,
-    }
+/// X11 keysyms for keys enigo has no named variant for
+#[cfg(all(unix, not(target_os = "macos")))]
+mod x11_keysym {
+    pub const ALT_R: u32 = 0xFFEA;
+    pub const SUPER_R: u32 = 0xFFEC;
+    pub const XF86_HOME_PAGE: u32 = 0x1008_FF18;
+    pub const XF86_SEARCH: u32 = 0x1008_FF1B;
+    pub const XF86_BACK: u32 = 0x1008_FF26;
+    pub const XF86_FORWARD: u32 = 0x1008_FF27;
+    pub const XF86_STOP: u32 = 0x1008_FF28;
+    pub const XF86_REFRESH: u32 = 0x1008_FF29;
+    pub const XF86_FAVORITES: u32 = 0x1008_FF30;
 }
 
 #[cfg(target_os = "windows")]
 use windows::Win32::UI::Input::KeyboardAndMouse::VIRTUAL_KEY;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ========== Key Name Tests ==========
+
+    #[test]
+    fn test_canonical_names_normalize_to_themselves() {
+        for name in KEY_NAMES {
+            assert_eq!(normalize_key(name), Some(*name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_normalize_key_aliases_and_case() {
+        assert_eq!(normalize_key("A"), Some("a"));
+        assert_eq!(normalize_key("Return"), Some("enter"));
+        assert_eq!(normalize_key(" "), Some("space"));
+        assert_eq!(normalize_key("PgDn"), Some("pagedown"));
+        assert_eq!(normalize_key("numpad+"), Some("add"));
+        assert_eq!(normalize_key("ctrl"), Some("lctrl"));
+        assert_eq!(normalize_key("\\"), Some("backslash"));
+        assert_eq!(normalize_key("f25"), None);
+        assert_eq!(normalize_key("nonsense"), None);
+    }
+
+    #[test]
+    fn test_normalize_modifier() {
+        assert_eq!(normalize_modifier("Control"), Some("lctrl"));
+        assert_eq!(normalize_modifier("cmd"), Some("lwin"));
+        assert_eq!(normalize_modifier("ralt"), Some("ralt"));
+        assert_eq!(normalize_modifier("a"), None);
+    }
+
+    // ========== Hold Tests ==========
+
+    fn keyboard(keys: &str, modifiers: &[&str]) -> KeyboardAction {
+        KeyboardAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            keys: keys.to_string(),
+            modifiers: modifiers.iter().map(|m| m.to_string()).collect(),
+            hold_duration: None,
+            sequence: Vec::new(),
+        }
+    }
+
+    fn step(key: &str, down: bool, delay_ms: u64) -> KeyStep {
+        KeyStep {
+            key: key.to_string(),
+            down,
+            delay_ms,
+        }
+    }
+
+    #[test]
+    fn test_plan_hold_space() {
+        let steps = plan_hold(&keyboard("space", &[]), 200).unwrap();
+        assert_eq!(steps, vec![step("space", true, 0), step("space", false, 200)]);
+    }
+
+    #[test]
+    fn test_plan_hold_with_modifiers() {
+        let steps = plan_hold(&keyboard("S", &["ctrl", "shift"]), 150).unwrap();
+        assert_eq!(
+            steps,
+            vec![
+                step("lctrl", true, 0),
+                step("lshift", true, 0),
+                step("s", true, 0),
+                step("s", false, 150),
+                step("lshift", false, 0),
+                step("lctrl", false, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_hold_unknown_key() {
+        assert!(plan_hold(&keyboard("nonsense", &[]), 200).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_held_key_is_invalid_input() {
+        // Planning fails before any key is sent
+        let mut config = keyboard("nonsense", &[]);
+        config.hold_duration = Some(200);
+        let result = execute(&config).await;
+
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("Unknown key: nonsense"));
+        assert_eq!(result.error_code, Some(crate::actions::error::ActionErrorCode::InvalidInput));
+    }
+
+    // ========== Dry Run Tests ==========
+
+    #[test]
+    fn test_describe_combination() {
+        assert_eq!(describe(&keyboard("s", &["ctrl", "shift"])), "Would press Ctrl+Shift+S");
+        assert_eq!(describe(&keyboard("f5", &[])), "Would press F5");
+    }
+
+    #[test]
+    fn test_describe_hold_and_sequence() {
+        let mut held = keyboard("space", &[]);
+        held.hold_duration = Some(300);
+        assert_eq!(describe(&held), "Would hold Space for 300ms");
+
+        let mut sequence = keyboard("", &[]);
+        sequence.sequence = vec![step("a", true, 0), step("a", false, 50)];
+        assert_eq!(describe(&sequence), "Would send 2 key steps");
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_every_key_name_has_a_virtual_key() {
+        for name in KEY_NAMES {
+            assert!(vk_for_name(name).is_some(), "no virtual key for {}", name);
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_every_key_name_has_an_enigo_key() {
+        // Keys macOS keyboards don't have
+        #[cfg(target_os = "macos")]
+        let unsupported: &[&str] = &[
+            "f21", "f22", "f23", "f24", "numlock", "scrolllock", "pause", "printscreen", "menu",
+            "stop", "browserback", "browserforward", "browserrefresh", "browserstop",
+            "browsersearch", "browserfavorites", "browserhome",
+        ];
+        #[cfg(not(target_os = "macos"))]
+        let unsupported: &[&str] = &[];
+
+        for name in KEY_NAMES {
+            if unsupported.contains(name) {
+                continue;
+            }
+            assert!(enigo_key(name).is_some(), "no enigo key for {}", name);
+        }
+        for name in MODIFIER_NAMES {
+            assert!(enigo_key(name).is_some(), "no enigo key for modifier {}", name);
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_parse_key_maps_through_enigo() {
+        assert_eq!(parse_key("Q"), Some(Key::Unicode('q')));
+        assert_eq!(parse_key("esc"), Some(Key::Escape));
+        assert_eq!(parse_key("f5"), Some(Key::F5));
+        assert_eq!(parse_modifier("shift"), Some(Key::LShift));
+        assert_eq!(parse_key("nonsense"), None);
+    }
+}
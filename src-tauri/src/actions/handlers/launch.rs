@@ -1,10 +1,38 @@
 //! Launch Handler
 //!
 //! Launches applications and opens URLs.
+//!
+//! With `single_instance` set, an application that is already running has its
+//! window brought to the front instead of a second instance being started
+//! (Windows only; other platforms always start it). The window is found by the
+//! executable named in `path`, so the option is ignored for URLs and when
+//! `use_shell` is set, where `path` is a command line rather than a program.
 
 use crate::actions::types::{ActionResult, LaunchAction};
+use crate::system::window::{executable_name, focus_window_of};
 use std::process::Command;
 
+/// Describe what a launch action would open, without opening it
+pub fn describe(config: &LaunchAction) -> String {
+    if config.path.starts_with("http://") || config.path.starts_with("https://") {
+        return format!("Would open {}", config.path);
+    }
+
+    let single = config.single_instance.unwrap_or(false) && !config.use_shell.unwrap_or(false);
+    let mut description = if single {
+        format!("Would focus or launch {}", config.path)
+    } else {
+        format!("Would launch {}", config.path)
+    };
+    if !config.args.is_empty() {
+        description.push_str(&format!(" {}", config.args.join(" ")));
+    }
+    if let Some(ref working_dir) = config.working_directory {
+        description.push_str(&format!(" in {}", working_dir));
+    }
+    description
+}
+
 /// Execute a launch action
 pub async fn execute(config: &LaunchAction) -> ActionResult {
     log::debug!("Executing launch action: {:?}", config.path);
@@ -14,6 +42,22 @@ pub async fn execute(config: &LaunchAction) -> ActionResult {
         return open_url(&config.path);
     }
 
+    if config.single_instance.unwrap_or(false) {
+        if config.use_shell.unwrap_or(false) {
+            log::debug!("single_instance is ignored for shell commands");
+        } else if cfg!(target_os = "windows") {
+            if focus_window_of(&config.path) {
+                return ActionResult::success_with_message(
+                    format!("Focused running {}", executable_name(&config.path)),
+                    0,
+                );
+            }
+            log::debug!("No window found for {}, launching it", config.path);
+        } else {
+            log::warn!("Focusing an existing window isn't implemented on this platform, launching {}", config.path);
+        }
+    }
+
     // Launch application
     let mut cmd = Command::new(&config.path);
 
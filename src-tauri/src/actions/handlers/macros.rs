@@ -0,0 +1,327 @@
+//! Macro Handler
+//!
+//! Runs a list of actions in order, pausing after each step as configured.
+//! The cancellation token is checked between steps and during pauses, so a
+//! cancelled macro stops before its next step.
+//!
+//! Variables captured by a step (e.g. an HTTP response) are substituted into
+//! the `${...}` references of the steps after it, on top of those captured
+//! by earlier actions (`IntegrationConfig::variables`).
+
+use crate::actions::template::{render_action, Variables};
+use crate::actions::error::ActionError;
+use crate::actions::types::{ActionResult, MacroAction};
+use crate::actions::{describe_action, execute_action_with_cancellation, CancellationToken, IntegrationConfig};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Describe every step a macro would run, without running them
+pub fn describe(config: &MacroAction) -> String {
+    let steps: Vec<String> = config
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(i, step)| {
+            let result = describe_action(&step.action);
+            format!("{}. {}", i + 1, result.message.or(result.error).unwrap_or_default())
+        })
+        .collect();
+    format!("Would run {} steps: {}", steps.len(), steps.join("; "))
+}
+
+/// Execute a macro action
+pub async fn execute(
+    config: &MacroAction,
+    integrations: &IntegrationConfig,
+    token: &CancellationToken,
+) -> ActionResult {
+    log::debug!("Executing macro with {} steps", config.steps.len());
+
+    let total = config.steps.len();
+    let mut timings = HashMap::new();
+    let mut errors = Vec::new();
+    let mut variables = integrations.variables.clone();
+    let mut captured = Variables::new();
+
+    for (i, step) in config.steps.iter().enumerate() {
+        if token.is_cancelled() {
+            return cancelled(i, total).with_timings(timings);
+        }
+
+        let result = match render_action(&step.action, &variables) {
+            // Steps may themselves be macros, so the recursive call is boxed
+            Ok(action) => Box::pin(execute_action_with_cancellation(&action, integrations, token)).await,
+            Err(e) => ActionResult::from_error(ActionError::InvalidInput(e), 0),
+        };
+        timings.insert(format!("step{}", i + 1), result.duration_ms);
+        if let Some(ref step_captured) = result.variables {
+            variables.extend(step_captured.iter().map(|(k, v)| (k.clone(), v.clone())));
+            captured.extend(step_captured.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+
+        if !result.success {
+            let error = format!(
+                "Step {} failed: {}",
+                i + 1,
+                result.error.as_deref().unwrap_or("unknown error")
+            );
+            log::warn!("Macro {}", error);
+            if config.stop_on_error {
+                // Keep the failed step's category
+                return ActionResult {
+                    error_code: result.error_code,
+                    ..ActionResult::failure(error, 0)
+                }
+                .with_timings(timings);
+            }
+            errors.push(error);
+        }
+
+        if let Some(delay_ms) = step.delay_after_ms.filter(|&ms| ms > 0 && i + 1 < total) {
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_millis(delay_ms)) => {}
+                _ = token.cancelled() => return cancelled(i + 1, total).with_timings(timings),
+            }
+        }
+    }
+
+    let result = if errors.is_empty() {
+        ActionResult::success_with_message(format!("Ran {} steps", total), 0)
+    } else {
+        ActionResult::failure(errors.join("; "), 0)
+    };
+    if captured.is_empty() {
+        result.with_timings(timings)
+    } else {
+        result.with_timings(timings).with_variables(captured)
+    }
+}
+
+fn cancelled(completed: usize, total: usize) -> ActionResult {
+    log::debug!("Macro cancelled after {} of {} steps", completed, total);
+    ActionResult::from_error(ActionError::Cancelled(format!("Cancelled after {} of {} steps", completed, total)), 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::types::{Action, MacroStep, WorkspaceAction, WorkspaceDirection};
+    use std::time::Instant;
+
+    fn workspace_step(direction: WorkspaceDirection, delay_after_ms: Option<u64>) -> MacroStep {
+        MacroStep {
+            action: Action::Workspace(WorkspaceAction {
+                id: None,
+                name: None,
+                icon: None,
+                enabled: None,
+                direction,
+                workspace_index: None,
+            }),
+            delay_after_ms,
+        }
+    }
+
+    /// Step that succeeds without touching the system
+    fn ok_step(delay_after_ms: Option<u64>) -> MacroStep {
+        workspace_step(WorkspaceDirection::Next, delay_after_ms)
+    }
+
+    /// Step that fails (specific workspace with no index)
+    fn failing_step() -> MacroStep {
+        workspace_step(WorkspaceDirection::Specific, None)
+    }
+
+    fn macro_of(steps: Vec<MacroStep>, stop_on_error: bool) -> MacroAction {
+        MacroAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            steps,
+            stop_on_error,
+        }
+    }
+
+    async fn run(config: &MacroAction, token: &CancellationToken) -> ActionResult {
+        execute(config, &IntegrationConfig::default(), token).await
+    }
+
+    // ========== Sequencing Tests ==========
+
+    #[tokio::test]
+    async fn test_runs_all_steps_in_order() {
+        let config = macro_of(vec![ok_step(None), ok_step(Some(10)), ok_step(None)], false);
+        let result = run(&config, &CancellationToken::new()).await;
+
+        assert!(result.success);
+        assert_eq!(result.message.as_deref(), Some("Ran 3 steps"));
+        assert_eq!(result.timings.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_empty_macro_succeeds() {
+        let result = run(&macro_of(vec![], false), &CancellationToken::new()).await;
+        assert!(result.success);
+    }
+
+    #[tokio::test]
+    async fn test_stop_on_error_skips_remaining_steps() {
+        let config = macro_of(vec![ok_step(None), failing_step(), ok_step(None)], true);
+        let result = run(&config, &CancellationToken::new()).await;
+
+        assert!(!result.success);
+        assert!(result.error.unwrap().starts_with("Step 2 failed"));
+        assert_eq!(result.timings.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_continue_on_error_reports_failures() {
+        let config = macro_of(vec![failing_step(), ok_step(None)], false);
+        let result = run(&config, &CancellationToken::new()).await;
+
+        assert!(!result.success);
+        assert!(result.error.unwrap().starts_with("Step 1 failed"));
+        assert_eq!(result.timings.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_nested_macro() {
+        let inner = Action::Macro(macro_of(vec![ok_step(None), ok_step(None)], true));
+        let config = macro_of(
+            vec![MacroStep {
+                action: inner,
+                delay_after_ms: None,
+            }],
+            true,
+        );
+        assert!(run(&config, &CancellationToken::new()).await.success);
+    }
+
+    #[cfg(feature = "http")]
+    /// Mock server answering `count` requests with a JSON body; reports the request lines
+    fn mock_server(count: usize) -> (std::net::SocketAddr, std::sync::mpsc::Receiver<String>) {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (seen_tx, seen_rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for socket in listener.incoming().take(count) {
+                let mut socket = socket.unwrap();
+                let mut request_line = String::new();
+                BufReader::new(&socket).read_line(&mut request_line).unwrap();
+                seen_tx.send(request_line).unwrap();
+                let body = r#"{"next": "second"}"#;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (addr, seen_rx)
+    }
+
+    fn http_step(addr: std::net::SocketAddr, path: &str, capture: Option<&str>) -> MacroStep {
+        use crate::actions::types::{HttpAction, HttpCapture, HttpMethod};
+
+        MacroStep {
+            action: Action::Http(HttpAction {
+                id: None,
+                name: None,
+                icon: None,
+                enabled: None,
+                method: HttpMethod::Get,
+                url: format!("http://{}/{}", addr, path),
+                headers: HashMap::new(),
+                body_type: None,
+                body: None,
+                timeout: None,
+                timeout_ms: Some(5_000),
+                capture: capture.map(|variable| HttpCapture {
+                    variable: variable.to_string(),
+                }),
+                retries: None,
+                retry_backoff_ms: None,
+            }),
+            delay_after_ms: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_captured_response_feeds_later_steps() {
+        let (addr, seen_rx) = mock_server(2);
+
+        let config = macro_of(
+            vec![http_step(addr, "first", Some("resp")), http_step(addr, "${resp.body.next}", None)],
+            true,
+        );
+        let result = run(&config, &CancellationToken::new()).await;
+
+        assert!(result.success, "{:?}", result.error);
+        assert!(seen_rx.recv().unwrap().starts_with("GET /first "));
+        assert!(seen_rx.recv().unwrap().starts_with("GET /second "));
+        assert_eq!(result.variables.unwrap()["resp"]["status"], 200);
+    }
+
+    #[tokio::test]
+    async fn test_steps_see_variables_from_earlier_actions() {
+        let (addr, seen_rx) = mock_server(1);
+        let mut integrations = IntegrationConfig::default();
+        integrations
+            .variables
+            .insert("earlier".to_string(), serde_json::json!({"body": {"page": "seeded"}}));
+
+        let config = macro_of(vec![http_step(addr, "${earlier.body.page}", None)], true);
+        let result = execute(&config, &integrations, &CancellationToken::new()).await;
+
+        assert!(result.success, "{:?}", result.error);
+        assert!(seen_rx.recv().unwrap().starts_with("GET /seeded "));
+        // Only this macro's own captures are reported
+        assert!(result.variables.is_none());
+    }
+
+    // ========== Dry Run Tests ==========
+
+    #[test]
+    fn test_describe_lists_steps() {
+        let config = macro_of(vec![ok_step(None), failing_step()], false);
+        assert_eq!(
+            describe(&config),
+            "Would run 2 steps: 1. Would switch to the next workspace; \
+             2. Would fail: workspace index required for specific navigation"
+        );
+    }
+
+    // ========== Cancellation Tests ==========
+
+    #[tokio::test]
+    async fn test_cancel_during_delay_aborts_before_next_step() {
+        let config = macro_of(vec![ok_step(Some(5_000)), ok_step(None)], false);
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            canceller.cancel();
+        });
+
+        let start = Instant::now();
+        let result = run(&config, &token).await;
+
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("Cancelled after 1 of 2 steps"));
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_already_cancelled_runs_nothing() {
+        let token = CancellationToken::new();
+        token.cancel();
+
+        let result = run(&macro_of(vec![ok_step(None)], false), &token).await;
+        assert_eq!(result.error.as_deref(), Some("Cancelled after 0 of 1 steps"));
+    }
+}
@@ -1,11 +1,35 @@
 //! Media Handler
 //!
-//! Simulates media key input for play/pause, volume, track navigation.
-//! Uses the same Windows SendInput API as the keyboard handler.
+//! Controls playback (play/pause, track navigation) and volume.
+//!
+//! - Windows: media keys through the same SendInput API as the keyboard handler.
+//! - Linux: playback goes to the active MPRIS player over D-Bus; volume uses
+//!   `wpctl` (PipeWire), falling back to `pactl` (PulseAudio).
+//! - macOS: `osascript` tells Spotify or Music (whichever is running) what to
+//!   do, and changes the output volume.
+//!
+//! `volume_amount` sets the volume step in percent. On Windows the volume is
+//! then changed through Core Audio, since media keys only move it in fixed
+//! system steps.
 
-use crate::actions::types::{ActionResult, MediaAction};
-#[cfg(target_os = "windows")]
-use crate::actions::types::MediaActionType;
+use crate::actions::types::{ActionResult, MediaAction, MediaActionType};
+
+/// Describe what a media action would do, without doing it
+pub fn describe(config: &MediaAction) -> String {
+    let key = match config.action {
+        MediaActionType::PlayPause => "play/pause",
+        MediaActionType::Next => "next track",
+        MediaActionType::Previous => "previous track",
+        MediaActionType::VolumeUp => "volume up",
+        MediaActionType::VolumeDown => "volume down",
+        MediaActionType::Mute => "mute",
+        MediaActionType::Stop => "stop",
+    };
+    match config.device_id.as_deref() {
+        Some(device_id) => format!("Would send {} to audio device {}", key, device_id),
+        None => format!("Would send {}", key),
+    }
+}
 
 /// Execute a media action
 pub async fn execute(config: &MediaAction) -> ActionResult {
@@ -16,17 +40,262 @@ pub async fn execute(config: &MediaAction) -> ActionResult {
         execute_windows(config)
     }
 
-    #[cfg(not(target_os = "windows"))]
+    #[cfg(target_os = "linux")]
+    {
+        execute_linux(config).await
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        execute_macos(config).await
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
     {
         let _ = config;
-        ActionResult::failure("Media actions only supported on Windows".to_string(), 0)
+        ActionResult::from_error(crate::actions::error::ActionError::NotSupportedOnPlatform("Media actions not supported on this platform".to_string()), 0)
+    }
+}
+
+/// Volume change to make through Core Audio instead of a media key
+///
+/// Media keys move the default output in fixed system steps, so Core Audio is
+/// used when an exact `volume_amount` is asked for or another device is
+/// targeted. `None` means the media key is sent.
+#[cfg(any(target_os = "windows", test))]
+fn endpoint_volume_change(
+    action: &MediaActionType,
+    volume_amount: Option<u32>,
+    other_device: bool,
+) -> Option<crate::system::audio::VolumeChange> {
+    use crate::system::audio::VolumeChange;
+
+    let amount = volume_amount.map(|amount| amount.min(100) as i32);
+    match (action, amount) {
+        (MediaActionType::VolumeUp, Some(amount)) => Some(VolumeChange::Adjust(amount)),
+        (MediaActionType::VolumeDown, Some(amount)) => Some(VolumeChange::Adjust(-amount)),
+        (MediaActionType::VolumeUp, None) if other_device => Some(VolumeChange::StepUp),
+        (MediaActionType::VolumeDown, None) if other_device => Some(VolumeChange::StepDown),
+        (MediaActionType::Mute, _) if amount.is_some() || other_device => Some(VolumeChange::ToggleMute),
+        _ => None,
+    }
+}
+
+/// Volume step in percent when `volume_amount` isn't set
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+const DEFAULT_VOLUME_STEP: u32 = 5;
+
+/// Volume step in percent (1-100)
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn volume_step(config: &MediaAction) -> u32 {
+    config.volume_amount.unwrap_or(DEFAULT_VOLUME_STEP).clamp(1, 100)
+}
+
+/// Run a command, failing with its stderr if it exits unsuccessfully
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+async fn run_command(program: &str, args: &[String]) -> Result<(), std::io::Error> {
+    let output = tokio::process::Command::new(program).args(args).output().await?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(std::io::Error::other(format!("{} failed: {}", program, stderr.trim())))
+    }
+}
+
+// =============================================================================
+// Linux: MPRIS and PipeWire / PulseAudio
+// =============================================================================
+
+#[cfg(target_os = "linux")]
+const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+#[cfg(target_os = "linux")]
+const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+#[cfg(target_os = "linux")]
+const MPRIS_PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+/// How long to wait for the D-Bus session bus
+#[cfg(target_os = "linux")]
+const DBUS_TIMEOUT_MS: u64 = 5_000;
+
+#[cfg(target_os = "linux")]
+async fn execute_linux(config: &MediaAction) -> ActionResult {
+    if config.device_id.is_some() {
+        log::debug!("Audio device selection isn't supported on Linux, using the default output");
+    }
+
+    let outcome = match mpris_method(&config.action) {
+        Some(method) => tokio::time::timeout(
+            std::time::Duration::from_millis(DBUS_TIMEOUT_MS),
+            send_to_player(method),
+        )
+        .await
+        .unwrap_or_else(|_| Err("Timed out talking to the media player".to_string())),
+        None => change_volume_linux(&config.action, volume_step(config)).await,
+    };
+
+    match outcome {
+        Ok(()) => ActionResult::success(0),
+        Err(e) => ActionResult::failure(e, 0),
+    }
+}
+
+/// MPRIS player method for a playback action; `None` for volume actions
+#[cfg(target_os = "linux")]
+fn mpris_method(action: &MediaActionType) -> Option<&'static str> {
+    match action {
+        MediaActionType::PlayPause => Some("PlayPause"),
+        MediaActionType::Next => Some("Next"),
+        MediaActionType::Previous => Some("Previous"),
+        MediaActionType::Stop => Some("Stop"),
+        MediaActionType::VolumeUp | MediaActionType::VolumeDown | MediaActionType::Mute => None,
+    }
+}
+
+/// Call a method on the active MPRIS player
+#[cfg(target_os = "linux")]
+async fn send_to_player(method: &str) -> Result<(), String> {
+    let connection = zbus::Connection::session()
+        .await
+        .map_err(|e| format!("D-Bus session bus unavailable: {}", e))?;
+    let dbus = zbus::fdo::DBusProxy::new(&connection)
+        .await
+        .map_err(|e| format!("D-Bus error: {}", e))?;
+    let names = dbus.list_names().await.map_err(|e| format!("D-Bus error: {}", e))?;
+
+    let mut players = Vec::new();
+    for name in names.iter().map(|name| name.as_str()).filter(|name| name.starts_with(MPRIS_PREFIX)) {
+        let status = playback_status(&connection, name).await.unwrap_or_default();
+        players.push((name.to_string(), status));
     }
+    let player = pick_player(&players).ok_or_else(|| "No media player found".to_string())?;
+    log::debug!("Sending {} to {}", method, player);
+
+    connection
+        .call_method(Some(player), MPRIS_PATH, Some(MPRIS_PLAYER_INTERFACE), method, &())
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("Media player command failed: {}", e))
+}
+
+#[cfg(target_os = "linux")]
+async fn playback_status(connection: &zbus::Connection, name: &str) -> zbus::Result<String> {
+    let proxy = zbus::Proxy::new(connection, name.to_string(), MPRIS_PATH, MPRIS_PLAYER_INTERFACE).await?;
+    proxy.get_property("PlaybackStatus").await
+}
+
+/// The player to control: one that is playing, else one that is paused,
+/// else the first
+#[cfg(target_os = "linux")]
+fn pick_player(players: &[(String, String)]) -> Option<&str> {
+    ["Playing", "Paused"]
+        .iter()
+        .find_map(|wanted| players.iter().find(|(_, status)| status == wanted))
+        .or(players.first())
+        .map(|(name, _)| name.as_str())
+}
+
+/// Volume commands to try in order: PipeWire's `wpctl`, then PulseAudio's `pactl`
+#[cfg(target_os = "linux")]
+fn linux_volume_commands(action: &MediaActionType, step: u32) -> Vec<(&'static str, Vec<String>)> {
+    let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+    match action {
+        MediaActionType::VolumeUp => vec![
+            ("wpctl", args(&["set-volume", "-l", "1.0", "@DEFAULT_AUDIO_SINK@", &format!("{}%+", step)])),
+            ("pactl", args(&["set-sink-volume", "@DEFAULT_SINK@", &format!("+{}%", step)])),
+        ],
+        MediaActionType::VolumeDown => vec![
+            ("wpctl", args(&["set-volume", "@DEFAULT_AUDIO_SINK@", &format!("{}%-", step)])),
+            ("pactl", args(&["set-sink-volume", "@DEFAULT_SINK@", &format!("-{}%", step)])),
+        ],
+        MediaActionType::Mute => vec![
+            ("wpctl", args(&["set-mute", "@DEFAULT_AUDIO_SINK@", "toggle"])),
+            ("pactl", args(&["set-sink-mute", "@DEFAULT_SINK@", "toggle"])),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(target_os = "linux")]
+async fn change_volume_linux(action: &MediaActionType, step: u32) -> Result<(), String> {
+    for (program, args) in linux_volume_commands(action, step) {
+        match run_command(program, &args).await {
+            Ok(()) => return Ok(()),
+            // Not installed; try the next tool
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+    Err("Volume control needs wpctl or pactl".to_string())
+}
+
+// =============================================================================
+// macOS: AppleScript
+// =============================================================================
+
+#[cfg(target_os = "macos")]
+async fn execute_macos(config: &MediaAction) -> ActionResult {
+    let script = applescript(&config.action, volume_step(config));
+    match run_command("osascript", &["-e".to_string(), script]).await {
+        Ok(()) => ActionResult::success(0),
+        Err(e) => ActionResult::failure(e.to_string(), 0),
+    }
+}
+
+/// AppleScript performing a media action
+#[cfg(target_os = "macos")]
+fn applescript(action: &MediaActionType, step: u32) -> String {
+    let player_command = match action {
+        MediaActionType::PlayPause => "playpause",
+        MediaActionType::Next => "next track",
+        MediaActionType::Previous => "previous track",
+        MediaActionType::Stop => "pause",
+        MediaActionType::VolumeUp => {
+            return format!(
+                "set volume output volume ((output volume of (get volume settings)) + {})",
+                step
+            );
+        }
+        MediaActionType::VolumeDown => {
+            return format!(
+                "set volume output volume ((output volume of (get volume settings)) - {})",
+                step
+            );
+        }
+        MediaActionType::Mute => {
+            return "set volume output muted (not (output muted of (get volume settings)))".to_string();
+        }
+    };
+    format!(
+        "if application \"Spotify\" is running then\n\
+         tell application \"Spotify\" to {cmd}\n\
+         else if application \"Music\" is running then\n\
+         tell application \"Music\" to {cmd}\n\
+         else\n\
+         error \"No media player running\"\n\
+         end if",
+        cmd = player_command
+    )
 }
 
 #[cfg(target_os = "windows")]
 fn execute_windows(config: &MediaAction) -> ActionResult {
+    use crate::system::audio::{change_default_volume, change_device_volume, DEFAULT_DEVICE_ID};
     use windows::Win32::UI::Input::KeyboardAndMouse::*;
 
+    // Volume keys always hit the default output, so target other devices directly
+    let device_id = config.device_id.as_deref().filter(|id| *id != DEFAULT_DEVICE_ID);
+    if let Some(change) = endpoint_volume_change(&config.action, config.volume_amount, device_id.is_some()) {
+        let result = match device_id {
+            Some(device_id) => change_device_volume(device_id, change),
+            None => change_default_volume(change),
+        };
+        return match result {
+            Ok(()) => ActionResult::success(0),
+            Err(e) => ActionResult::failure(e, 0),
+        };
+    }
+
     // Map media action to virtual key code
     let vk = match config.action {
         MediaActionType::PlayPause => VK_MEDIA_PLAY_PAUSE,
@@ -90,3 +359,95 @@ fn send_media_key(vk: VIRTUAL_KEY) -> Result<(), String> {
 
 #[cfg(target_os = "windows")]
 use windows::Win32::UI::Input::KeyboardAndMouse::VIRTUAL_KEY;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn media(action: MediaActionType, volume_amount: Option<u32>) -> MediaAction {
+        MediaAction {
+            action,
+            volume_amount,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(describe(&media(MediaActionType::Next, None)), "Would send next track");
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn test_volume_step_defaults_and_clamps() {
+        assert_eq!(volume_step(&media(MediaActionType::VolumeUp, None)), DEFAULT_VOLUME_STEP);
+        assert_eq!(volume_step(&media(MediaActionType::VolumeUp, Some(10))), 10);
+        assert_eq!(volume_step(&media(MediaActionType::VolumeUp, Some(0))), 1);
+        assert_eq!(volume_step(&media(MediaActionType::VolumeUp, Some(250))), 100);
+    }
+
+    #[test]
+    fn test_exact_amounts_go_through_core_audio() {
+        use crate::system::audio::VolumeChange;
+
+        assert_eq!(
+            endpoint_volume_change(&MediaActionType::VolumeUp, Some(5), false),
+            Some(VolumeChange::Adjust(5))
+        );
+        assert_eq!(
+            endpoint_volume_change(&MediaActionType::VolumeDown, Some(5), true),
+            Some(VolumeChange::Adjust(-5))
+        );
+        assert_eq!(
+            endpoint_volume_change(&MediaActionType::Mute, Some(5), false),
+            Some(VolumeChange::ToggleMute)
+        );
+    }
+
+    #[test]
+    fn test_without_amount_default_device_uses_media_keys() {
+        use crate::system::audio::VolumeChange;
+
+        assert_eq!(endpoint_volume_change(&MediaActionType::VolumeUp, None, false), None);
+        assert_eq!(endpoint_volume_change(&MediaActionType::Mute, None, false), None);
+        assert_eq!(endpoint_volume_change(&MediaActionType::PlayPause, Some(5), false), None);
+        assert_eq!(
+            endpoint_volume_change(&MediaActionType::VolumeUp, None, true),
+            Some(VolumeChange::StepUp)
+        );
+    }
+
+    // ========== Linux Tests ==========
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_playback_actions_map_to_mpris() {
+        assert_eq!(mpris_method(&MediaActionType::PlayPause), Some("PlayPause"));
+        assert_eq!(mpris_method(&MediaActionType::Previous), Some("Previous"));
+        assert_eq!(mpris_method(&MediaActionType::Mute), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_pick_player_prefers_playing() {
+        let player = |name: &str, status: &str| (name.to_string(), status.to_string());
+        let players = vec![
+            player("org.mpris.MediaPlayer2.vlc", "Stopped"),
+            player("org.mpris.MediaPlayer2.spotify", "Playing"),
+            player("org.mpris.MediaPlayer2.firefox", "Paused"),
+        ];
+        assert_eq!(pick_player(&players), Some("org.mpris.MediaPlayer2.spotify"));
+        assert_eq!(pick_player(&players[..1]), Some("org.mpris.MediaPlayer2.vlc"));
+        assert_eq!(pick_player(&[]), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_volume_commands_use_step() {
+        let commands = linux_volume_commands(&MediaActionType::VolumeDown, 10);
+        assert_eq!(commands[0].0, "wpctl");
+        assert_eq!(commands[0].1.last().map(String::as_str), Some("10%-"));
+        assert_eq!(commands[1].0, "pactl");
+        assert_eq!(commands[1].1.last().map(String::as_str), Some("-10%"));
+    }
+}
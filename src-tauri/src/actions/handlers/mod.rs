@@ -1,15 +1,169 @@
 //! Action Handlers
 //!
 //! Individual handler implementations for each action type.
+//! Heavier handlers are gated behind Cargo features (`http`, `home_assistant`,
+//! `node_red`, `mqtt`, `scripting`), all enabled by default.
 
 pub mod keyboard;
 pub mod media;
 pub mod launch;
+#[cfg(feature = "scripting")]
 pub mod script;
+#[cfg(feature = "http")]
 pub mod http;
 pub mod system;
 pub mod text;
 pub mod profile;
+#[cfg(feature = "home_assistant")]
 pub mod home_assistant;
+#[cfg(feature = "node_red")]
 pub mod node_red;
+#[cfg(feature = "node_red")]
+pub mod node_red_ws;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
 pub mod workspace;
+pub mod macros;
+pub mod conditional;
+pub mod brightness;
+pub mod notify;
+pub mod clipboard;
+pub mod delay;
+pub mod feedback;
+
+#[cfg(any(feature = "http", feature = "home_assistant"))]
+use crate::actions::CancellationToken;
+#[cfg(any(feature = "http", feature = "home_assistant"))]
+use std::time::Duration;
+
+/// Run `attempt` up to `max_attempts` times while `should_retry` says so
+///
+/// The wait before retry `n` is `base_backoff * 2^(n-1)`. Returns the last
+/// outcome, or `None` if the token was cancelled before or while waiting.
+#[cfg(any(feature = "http", feature = "home_assistant"))]
+pub async fn retry<T, F, Fut>(
+    max_attempts: u32,
+    base_backoff: Duration,
+    token: &CancellationToken,
+    should_retry: impl Fn(&T) -> bool,
+    mut attempt: F,
+) -> Option<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = T>,
+{
+    let mut n = 1;
+    loop {
+        if token.is_cancelled() {
+            return None;
+        }
+
+        let outcome = attempt().await;
+        if n >= max_attempts || !should_retry(&outcome) {
+            return Some(outcome);
+        }
+
+        let backoff = base_backoff.saturating_mul(2u32.saturating_pow(n - 1));
+        log::debug!("Attempt {} of {} failed, retrying in {:?}", n, max_attempts, backoff);
+        tokio::select! {
+            _ = tokio::time::sleep(backoff) => {}
+            _ = token.cancelled() => return None,
+        }
+        n += 1;
+    }
+}
+
+/// Whether a request outcome is transient: a connection error, 5xx or 429
+///
+/// Other 4xx responses mean the request itself is wrong, so they aren't retried.
+#[cfg(any(feature = "http", feature = "home_assistant"))]
+pub fn is_retryable(outcome: &Result<reqwest::Response, reqwest::Error>) -> bool {
+    match outcome {
+        Ok(response) => is_retryable_status(response.status()),
+        Err(e) => e.is_connect(),
+    }
+}
+
+#[cfg(any(feature = "http", feature = "home_assistant"))]
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+}
+
+#[cfg(all(test, any(feature = "http", feature = "home_assistant")))]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    // ========== Retry Tests ==========
+
+    #[tokio::test]
+    async fn test_retry_stops_on_success() {
+        let calls = Cell::new(0);
+        let outcome = retry(5, Duration::from_millis(1), &CancellationToken::new(), |ok: &bool| !ok, || {
+            calls.set(calls.get() + 1);
+            std::future::ready(calls.get() == 2)
+        })
+        .await;
+
+        assert_eq!(outcome, Some(true));
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_after_max_attempts() {
+        let calls = Cell::new(0);
+        let outcome = retry(3, Duration::from_millis(1), &CancellationToken::new(), |_: &bool| true, || {
+            calls.set(calls.get() + 1);
+            std::future::ready(false)
+        })
+        .await;
+
+        assert_eq!(outcome, Some(false));
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_single_attempt_never_waits() {
+        let calls = Cell::new(0);
+        let outcome = retry(1, Duration::from_secs(60), &CancellationToken::new(), |_: &u8| true, || {
+            calls.set(calls.get() + 1);
+            std::future::ready(7u8)
+        })
+        .await;
+
+        assert_eq!(outcome, Some(7));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_aborts_backoff() {
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            canceller.cancel();
+        });
+
+        let calls = Cell::new(0);
+        let start = std::time::Instant::now();
+        let outcome = retry(5, Duration::from_secs(10), &token, |_: &()| true, || {
+            calls.set(calls.get() + 1);
+            std::future::ready(())
+        })
+        .await;
+
+        assert_eq!(outcome, None);
+        assert_eq!(calls.get(), 1);
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_retryable_statuses() {
+        assert!(is_retryable_status(reqwest::StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(is_retryable_status(reqwest::StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_retryable_status(reqwest::StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_retryable_status(reqwest::StatusCode::NOT_FOUND));
+        assert!(!is_retryable_status(reqwest::StatusCode::UNAUTHORIZED));
+        assert!(!is_retryable_status(reqwest::StatusCode::OK));
+    }
+}
@@ -0,0 +1,412 @@
+//! MQTT Handler
+//!
+//! Publishes messages to an MQTT broker. The broker connection is opened on
+//! first use and kept for later actions; a background task drives it and
+//! reconnects after it drops. Settings changes replace the connection, after
+//! the old one has sent what was already queued.
+
+use crate::actions::error::ActionError;
+use crate::actions::types::{ActionResult, MqttAction};
+use crate::config::types::MqttConfig;
+use rumqttc::{
+    AsyncClient, ConnectionError, Event, EventLoop, MqttOptions, Outgoing, Packet, QoS,
+    TlsConfiguration, Transport,
+};
+use std::sync::LazyLock;
+use std::time::Duration;
+use tokio::sync::{watch, Mutex};
+
+/// How long an action waits for the broker to accept the connection
+const CONNECT_TIMEOUT_MS: u64 = 5_000;
+
+/// Pause between reconnection attempts
+const RECONNECT_DELAY_MS: u64 = 2_000;
+
+/// Keep-alive interval sent to the broker
+const KEEP_ALIVE_SECS: u64 = 30;
+
+/// Connection shared by all MQTT actions
+static POOL: LazyLock<ConnectionPool> = LazyLock::new(ConnectionPool::default);
+
+/// Describe what an MQTT action would publish, without publishing it
+pub fn describe(config: &MqttAction) -> String {
+    let retained = if config.retain { ", retained" } else { "" };
+    format!(
+        "Would publish {} bytes to {} (QoS {}{})",
+        payload_bytes(config.payload.as_ref()).len(),
+        config.topic,
+        config.qos,
+        retained
+    )
+}
+
+/// Execute an MQTT action with configuration
+pub async fn execute_with_config(
+    config: &MqttAction,
+    mqtt_config: Option<&MqttConfig>,
+) -> ActionResult {
+    log::debug!("Executing MQTT action: topic={}", config.topic);
+
+    let Some(mqtt_config) = mqtt_config.filter(|cfg| !cfg.broker_url.is_empty()) else {
+        return ActionResult::from_error(ActionError::ConfigMissing("MQTT not configured".to_string()), 0);
+    };
+    if config.topic.is_empty() {
+        return ActionResult::from_error(ActionError::InvalidInput("MQTT topic is empty".to_string()), 0);
+    }
+    let Ok(qos) = rumqttc::qos(config.qos) else {
+        return ActionResult::from_error(
+            ActionError::InvalidInput(format!("Invalid MQTT QoS: {} (expected 0, 1 or 2)", config.qos)),
+            0,
+        );
+    };
+
+    let payload = payload_bytes(config.payload.as_ref());
+    match POOL.publish(mqtt_config, &config.topic, qos, config.retain, payload).await {
+        Ok(()) => ActionResult::success_with_message(format!("Published to {}", config.topic), 0),
+        Err(e) => ActionResult::from_error(ActionError::NetworkError(format!("MQTT publish failed: {}", e)), 0),
+    }
+}
+
+/// Strings are sent as-is; other values as JSON
+fn payload_bytes(payload: Option<&serde_json::Value>) -> Vec<u8> {
+    match payload {
+        None => Vec::new(),
+        Some(serde_json::Value::String(s)) => s.as_bytes().to_vec(),
+        Some(other) => other.to_string().into_bytes(),
+    }
+}
+
+/// Broker address parsed from `MqttConfig::broker_url`
+#[derive(Debug, PartialEq, Eq)]
+struct Broker {
+    host: String,
+    port: u16,
+    tls: bool,
+}
+
+/// Parse "mqtt://host:port", "mqtts://host" or a bare "host[:port]"
+fn parse_broker_url(url: &str) -> Result<Broker, String> {
+    let (tls, rest) = match url.trim().split_once("://") {
+        Some(("mqtt" | "tcp", rest)) => (false, rest),
+        Some(("mqtts" | "ssl", rest)) => (true, rest),
+        Some((scheme, _)) => return Err(format!("Unsupported MQTT scheme: {}", scheme)),
+        None => (false, url.trim()),
+    };
+    let authority = rest.trim_end_matches('/');
+    let default_port = if tls { 8883 } else { 1883 };
+
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => {
+            let port = port
+                .parse()
+                .map_err(|_| format!("Invalid MQTT broker port: {}", port))?;
+            (host, port)
+        }
+        None => (authority, default_port),
+    };
+    if host.is_empty() {
+        return Err(format!("Invalid MQTT broker URL: {}", url));
+    }
+
+    Ok(Broker {
+        host: host.to_string(),
+        port,
+        tls,
+    })
+}
+
+/// State of a broker connection, as reported by its event loop
+#[derive(Debug, Clone)]
+enum State {
+    Connecting,
+    Connected,
+    Down(String),
+}
+
+/// An open broker connection
+///
+/// Dropping it lets the event loop flush queued messages and then stop.
+struct Connection {
+    config: MqttConfig,
+    client: AsyncClient,
+    state: watch::Receiver<State>,
+}
+
+impl Connection {
+    fn open(config: &MqttConfig) -> Result<Self, String> {
+        let broker = parse_broker_url(&config.broker_url)?;
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        // Client IDs up to 23 characters are accepted by every broker
+        let mut options = MqttOptions::new(format!("soomfon-{}", &id[..12]), broker.host, broker.port);
+        options.set_keep_alive(Duration::from_secs(KEEP_ALIVE_SECS));
+        if let Some(username) = config.username.as_deref().filter(|u| !u.is_empty()) {
+            options.set_credentials(username, config.password.clone().unwrap_or_default());
+        }
+        if broker.tls {
+            options.set_transport(Transport::Tls(TlsConfiguration::Native));
+        }
+
+        let (client, event_loop) = AsyncClient::new(options, 10);
+        let (state_tx, state) = watch::channel(State::Connecting);
+        tokio::spawn(drive(event_loop, state_tx, config.broker_url.clone()));
+
+        Ok(Self {
+            config: config.clone(),
+            client,
+            state,
+        })
+    }
+
+    /// Wait until the broker has accepted the connection
+    async fn ready(&mut self) -> Result<(), String> {
+        let state = tokio::time::timeout(
+            Duration::from_millis(CONNECT_TIMEOUT_MS),
+            self.state.wait_for(|state| !matches!(state, State::Connecting)),
+        )
+        .await
+        .map_err(|_| format!("timed out connecting to {}", self.config.broker_url))?
+        .map_err(|_| "connection task stopped".to_string())?
+        .clone();
+
+        match state {
+            State::Down(e) => Err(e),
+            State::Connected | State::Connecting => Ok(()),
+        }
+    }
+}
+
+/// Poll the event loop until its connection is closed or dropped,
+/// reconnecting after errors
+async fn drive(mut event_loop: EventLoop, state: watch::Sender<State>, broker_url: String) {
+    loop {
+        match event_loop.poll().await {
+            Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                log::info!("Connected to MQTT broker at {}", broker_url);
+                state.send_replace(State::Connected);
+            }
+            Ok(Event::Outgoing(Outgoing::Disconnect)) | Err(ConnectionError::RequestsDone) => break,
+            Ok(_) => {}
+            Err(_) if state.is_closed() => break,
+            Err(e) => {
+                if matches!(*state.borrow(), State::Connected) {
+                    log::warn!("MQTT connection to {} lost: {}", broker_url, e);
+                } else {
+                    log::debug!("MQTT connection to {} failed: {}", broker_url, e);
+                }
+                state.send_replace(State::Down(e.to_string()));
+                tokio::time::sleep(Duration::from_millis(RECONNECT_DELAY_MS)).await;
+                state.send_replace(State::Connecting);
+            }
+        }
+    }
+}
+
+/// Holds the broker connection between actions
+#[derive(Default)]
+struct ConnectionPool {
+    connection: Mutex<Option<Connection>>,
+}
+
+impl ConnectionPool {
+    async fn publish(
+        &self,
+        config: &MqttConfig,
+        topic: &str,
+        qos: QoS,
+        retain: bool,
+        payload: Vec<u8>,
+    ) -> Result<(), String> {
+        let mut pooled = self.connection.lock().await;
+        if pooled.as_ref().is_some_and(|connection| connection.config != *config) {
+            log::info!("MQTT settings changed, reconnecting");
+            if let Some(old) = pooled.take() {
+                let _ = old.client.try_disconnect();
+            }
+        }
+        let connection = match pooled.as_mut() {
+            Some(connection) => connection,
+            None => pooled.insert(Connection::open(config)?),
+        };
+
+        connection.ready().await?;
+        connection
+            .client
+            .publish(topic, qos, retain, payload)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    fn mqtt_config(broker_url: &str) -> MqttConfig {
+        MqttConfig {
+            broker_url: broker_url.to_string(),
+            username: None,
+            password: None,
+        }
+    }
+
+    fn action(topic: &str, qos: u8) -> MqttAction {
+        MqttAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            topic: topic.to_string(),
+            payload: Some(serde_json::json!("on")),
+            qos,
+            retain: false,
+        }
+    }
+
+    /// Read one MQTT packet: (fixed header byte, body)
+    async fn read_packet(stream: &mut TcpStream) -> Option<(u8, Vec<u8>)> {
+        let header = stream.read_u8().await.ok()?;
+        let mut len = 0usize;
+        for shift in (0..28).step_by(7) {
+            let byte = stream.read_u8().await.ok()?;
+            len |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        let mut body = vec![0; len];
+        stream.read_exact(&mut body).await.ok()?;
+        Some((header, body))
+    }
+
+    /// Minimal broker: accepts any client and reports (connection number,
+    /// topic, payload) for each QoS 0 publish
+    async fn mock_broker() -> (String, tokio::sync::mpsc::UnboundedReceiver<(usize, String, Vec<u8>)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("mqtt://{}", listener.local_addr().unwrap());
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            for connection in 1.. {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    while let Some((header, body)) = read_packet(&mut stream).await {
+                        match header >> 4 {
+                            // CONNECT -> CONNACK accepted
+                            1 => stream.write_all(&[0x20, 0x02, 0x00, 0x00]).await.unwrap(),
+                            3 => {
+                                let topic_len = u16::from_be_bytes([body[0], body[1]]) as usize;
+                                let topic = String::from_utf8(body[2..2 + topic_len].to_vec()).unwrap();
+                                let _ = tx.send((connection, topic, body[2 + topic_len..].to_vec()));
+                            }
+                            // PINGREQ -> PINGRESP
+                            12 => stream.write_all(&[0xd0, 0x00]).await.unwrap(),
+                            _ => {}
+                        }
+                    }
+                });
+            }
+        });
+        (url, rx)
+    }
+
+    // ========== Broker URL Tests ==========
+
+    #[test]
+    fn test_parse_broker_url() {
+        assert_eq!(
+            parse_broker_url("mqtt://broker.local:1884").unwrap(),
+            Broker { host: "broker.local".to_string(), port: 1884, tls: false }
+        );
+        assert_eq!(
+            parse_broker_url("mqtts://broker.local/").unwrap(),
+            Broker { host: "broker.local".to_string(), port: 8883, tls: true }
+        );
+        assert_eq!(
+            parse_broker_url("192.168.1.10").unwrap(),
+            Broker { host: "192.168.1.10".to_string(), port: 1883, tls: false }
+        );
+    }
+
+    #[test]
+    fn test_parse_broker_url_rejects_invalid() {
+        assert!(parse_broker_url("http://broker.local").is_err());
+        assert!(parse_broker_url("mqtt://broker.local:port").is_err());
+        assert!(parse_broker_url("mqtt://").is_err());
+    }
+
+    // ========== Payload Tests ==========
+
+    #[test]
+    fn test_payload_bytes() {
+        assert_eq!(payload_bytes(None), b"");
+        assert_eq!(payload_bytes(Some(&serde_json::json!("toggle"))), b"toggle");
+        assert_eq!(payload_bytes(Some(&serde_json::json!({"on": true}))), br#"{"on":true}"#);
+    }
+
+    #[test]
+    fn test_mqtt_action_deserialize() {
+        let json = r#"{"type": "mqtt", "topic": "home/desk/lamp", "payload": "on", "qos": 1, "retain": true}"#;
+        match serde_json::from_str::<crate::actions::types::Action>(json).unwrap() {
+            crate::actions::types::Action::Mqtt(ma) => {
+                assert_eq!(ma.topic, "home/desk/lamp");
+                assert_eq!(ma.qos, 1);
+                assert!(ma.retain);
+            }
+            other => panic!("Unexpected action: {:?}", other),
+        }
+    }
+
+    // ========== Execution Tests ==========
+
+    #[tokio::test]
+    async fn test_not_configured() {
+        let result = execute_with_config(&action("a/b", 0), None).await;
+        assert_eq!(result.error.as_deref(), Some("MQTT not configured"));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_qos() {
+        let config = mqtt_config("mqtt://127.0.0.1:1");
+        let result = execute_with_config(&action("a/b", 3), Some(&config)).await;
+        assert!(result.error.unwrap().starts_with("Invalid MQTT QoS: 3"));
+    }
+
+    #[tokio::test]
+    async fn test_publishes_reuse_one_connection() {
+        let (url, mut received) = mock_broker().await;
+        let pool = ConnectionPool::default();
+        let config = mqtt_config(&url);
+
+        pool.publish(&config, "desk/one", QoS::AtMostOnce, false, b"1".to_vec()).await.unwrap();
+        pool.publish(&config, "desk/two", QoS::AtMostOnce, false, b"2".to_vec()).await.unwrap();
+
+        assert_eq!(received.recv().await.unwrap(), (1, "desk/one".to_string(), b"1".to_vec()));
+        assert_eq!(received.recv().await.unwrap(), (1, "desk/two".to_string(), b"2".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_changed_settings_reconnect() {
+        let (url, mut received) = mock_broker().await;
+        let pool = ConnectionPool::default();
+
+        pool.publish(&mqtt_config(&url), "t", QoS::AtMostOnce, false, vec![]).await.unwrap();
+        let mut changed = mqtt_config(&url);
+        changed.username = Some("desk".to_string());
+        pool.publish(&changed, "t", QoS::AtMostOnce, false, vec![]).await.unwrap();
+
+        assert_eq!(received.recv().await.unwrap().0, 1);
+        assert_eq!(received.recv().await.unwrap().0, 2);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_broker_fails() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let pool = ConnectionPool::default();
+        let config = mqtt_config(&format!("mqtt://127.0.0.1:{}", port));
+
+        let err = pool.publish(&config, "t", QoS::AtMostOnce, false, vec![]).await.unwrap_err();
+        assert!(!err.is_empty());
+    }
+}
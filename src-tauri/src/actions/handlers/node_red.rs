@@ -1,11 +1,25 @@
 //! Node-RED Handler
 //!
-//! Triggers Node-RED flows via HTTP.
+//! Triggers Node-RED flows via HTTP. When a WebSocket URL is configured,
+//! `send_event` actions go over a persistent WebSocket instead (see
+//! `node_red_ws`), falling back to HTTP if it can't connect.
 
-use crate::actions::types::{ActionResult, NodeRedAction};
+use super::node_red_ws;
+use crate::actions::error::ActionError;
+use crate::actions::types::{ActionResult, NodeRedAction, NodeRedOperationType};
 use crate::config::types::NodeRedConfig;
 use std::time::Duration;
 
+/// Describe what a Node-RED action would send, without sending it
+pub fn describe(config: &NodeRedAction) -> String {
+    match (&config.operation, config.event_name.as_deref()) {
+        (NodeRedOperationType::SendEvent, Some(event)) => {
+            format!("Would send event {} to {}", event, config.endpoint)
+        }
+        _ => format!("Would POST to {}", config.endpoint),
+    }
+}
+
 /// Execute a Node-RED action with configuration
 pub async fn execute_with_config(
     config: &NodeRedAction,
@@ -13,17 +27,20 @@ pub async fn execute_with_config(
 ) -> ActionResult {
     log::debug!("Executing Node-RED action: endpoint={}", config.endpoint);
 
-    // Get Node-RED URL from config, falling back to environment variable
-    let nr_url = match nr_config {
-        Some(cfg) => cfg.url.clone(),
-        None => {
-            // Fallback to environment variable for backwards compatibility
-            std::env::var("NODE_RED_URL").unwrap_or_default()
+    let ws_url = nr_config
+        .and_then(|cfg| cfg.websocket_url.as_deref())
+        .filter(|url| !url.is_empty());
+    if let (NodeRedOperationType::SendEvent, Some(ws_url)) = (&config.operation, ws_url) {
+        match node_red_ws::send(ws_url, &event_message(config)).await {
+            Ok(()) => return ActionResult::success_with_message("Event sent over WebSocket".to_string(), 0),
+            Err(e) => log::warn!("Node-RED WebSocket unavailable, falling back to HTTP: {}", e),
         }
-    };
+    }
+
+    let nr_url = nr_config.map(|cfg| cfg.url.clone()).unwrap_or_default();
 
     if nr_url.is_empty() {
-        return ActionResult::failure("Node-RED not configured".to_string(), 0);
+        return ActionResult::from_error(ActionError::ConfigMissing("Node-RED not configured".to_string()), 0);
     }
 
     let client = reqwest::Client::builder()
@@ -32,7 +49,9 @@ pub async fn execute_with_config(
 
     let client = match client {
         Ok(c) => c,
-        Err(e) => return ActionResult::failure(format!("Failed to create HTTP client: {}", e), 0),
+        Err(e) => {
+            return ActionResult::from_error(ActionError::NetworkError(format!("Failed to create HTTP client: {}", e)), 0)
+        }
     };
 
     let url = format!("{}{}", nr_url, config.endpoint);
@@ -48,22 +67,25 @@ pub async fn execute_with_config(
             if response.status().is_success() {
                 ActionResult::success(0)
             } else {
-                ActionResult::failure(format!("Node-RED request failed: {}", response.status()), 0)
+                ActionResult::from_error(ActionError::NetworkError(format!("Node-RED request failed: {}", response.status())), 0)
             }
         }
-        Err(e) => ActionResult::failure(format!("Node-RED request failed: {}", e), 0),
+        Err(e) => ActionResult::from_error(ActionError::NetworkError(format!("Node-RED request failed: {}", e)), 0),
     }
 }
 
-/// Execute a Node-RED action (backwards-compatible, uses env vars)
-pub async fn execute(config: &NodeRedAction) -> ActionResult {
-    execute_with_config(config, None).await
+/// WebSocket message for a `send_event` action
+fn event_message(config: &NodeRedAction) -> serde_json::Value {
+    serde_json::json!({
+        "event": config.event_name,
+        "endpoint": config.endpoint,
+        "payload": config.payload,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::actions::types::NodeRedOperationType;
 
     #[test]
     fn test_node_red_action_deserialize() {
@@ -96,6 +118,7 @@ mod tests {
     fn test_node_red_config_serialization() {
         let config = NodeRedConfig {
             url: "http://nodered.local:1880".to_string(),
+            websocket_url: None,
         };
 
         let json = serde_json::to_string(&config).unwrap();
@@ -104,4 +127,79 @@ mod tests {
         let deserialized: NodeRedConfig = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized.url, config.url);
     }
+
+    // ========== WebSocket Tests ==========
+
+    fn send_event(endpoint: &str) -> NodeRedAction {
+        NodeRedAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            operation: NodeRedOperationType::SendEvent,
+            endpoint: endpoint.to_string(),
+            event_name: Some("button_pressed".to_string()),
+            payload: Some(serde_json::json!({"button": 3})),
+            flow_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_event_over_websocket() {
+        use futures_util::StreamExt;
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ws_url = format!("ws://{}/ws", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            match ws.next().await {
+                Some(Ok(Message::Text(text))) => text.to_string(),
+                other => panic!("Unexpected frame: {:?}", other),
+            }
+        });
+
+        let nr_config = NodeRedConfig {
+            url: String::new(),
+            websocket_url: Some(ws_url.clone()),
+        };
+        let result = execute_with_config(&send_event("/events"), Some(&nr_config)).await;
+        assert!(result.success, "{:?}", result.error);
+
+        let message: serde_json::Value = serde_json::from_str(&server.await.unwrap()).unwrap();
+        assert_eq!(message["event"], "button_pressed");
+        assert_eq!(message["endpoint"], "/events");
+        assert_eq!(message["payload"]["button"], 3);
+        node_red_ws::disconnect(&ws_url).await;
+    }
+
+    #[tokio::test]
+    async fn test_send_event_falls_back_to_http() {
+        use std::io::{BufRead, BufReader, Write};
+
+        let http = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let http_addr = http.local_addr().unwrap();
+        let (seen_tx, seen_rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let (mut socket, _) = http.accept().unwrap();
+            let mut request_line = String::new();
+            BufReader::new(&socket).read_line(&mut request_line).unwrap();
+            seen_tx.send(request_line).unwrap();
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                .unwrap();
+        });
+
+        // Nothing listens on this port
+        let closed_port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let nr_config = NodeRedConfig {
+            url: format!("http://{}", http_addr),
+            websocket_url: Some(format!("ws://127.0.0.1:{}/ws", closed_port)),
+        };
+        let result = execute_with_config(&send_event("/events"), Some(&nr_config)).await;
+
+        assert!(result.success, "{:?}", result.error);
+        assert!(seen_rx.recv().unwrap().starts_with("POST /events "));
+    }
 }
@@ -0,0 +1,256 @@
+//! Node-RED WebSocket Connection
+//!
+//! Keeps one WebSocket open per Node-RED endpoint so events don't pay for a
+//! new HTTP request each time. A background task drains incoming frames and
+//! marks the socket dead when the server closes it; the next send then
+//! reconnects. A send on a socket that turns out to be broken is retried once
+//! on a fresh connection.
+//!
+//! Each URL has its own lock, so a slow connect only holds up sends to that
+//! URL. A failed connect is remembered for `RETRY_AFTER_MS`, during which
+//! sends fail straight away and callers fall back to HTTP without waiting
+//! on the handshake again.
+
+use futures_util::stream::SplitSink;
+use futures_util::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+/// How long to wait for the WebSocket handshake
+const CONNECT_TIMEOUT_MS: u64 = 5_000;
+
+/// How long to wait for a message to be written
+const SEND_TIMEOUT_MS: u64 = 2_000;
+
+/// How long after a failed connect sends skip the WebSocket
+const RETRY_AFTER_MS: u64 = 30_000;
+
+type Sink = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
+
+/// An open connection
+struct Socket {
+    sink: Sink,
+    /// Cleared by the reader task once the connection closes
+    alive: Arc<AtomicBool>,
+    reader: JoinHandle<()>,
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+/// Connection state for one URL
+#[derive(Default)]
+struct Endpoint {
+    socket: Option<Socket>,
+    /// Set by a failed connect; no new connect is tried before then
+    retry_at: Option<Instant>,
+}
+
+/// Endpoints by URL, each behind its own lock
+static ENDPOINTS: LazyLock<parking_lot::Mutex<HashMap<String, Arc<Mutex<Endpoint>>>>> =
+    LazyLock::new(|| parking_lot::Mutex::new(HashMap::new()));
+
+fn endpoint(url: &str) -> Arc<Mutex<Endpoint>> {
+    Arc::clone(ENDPOINTS.lock().entry(url.to_string()).or_default())
+}
+
+/// Send a JSON message, reusing the open connection to `url` if there is one
+pub async fn send(url: &str, message: &serde_json::Value) -> Result<(), String> {
+    let text = message.to_string();
+    let endpoint = endpoint(url);
+    let mut endpoint = endpoint.lock().await;
+
+    if let Some(socket) = endpoint.socket.as_mut() {
+        if socket.alive.load(Ordering::Acquire) {
+            match send_text(&mut socket.sink, text.clone()).await {
+                Ok(()) => return Ok(()),
+                Err(e) => log::info!("Node-RED WebSocket send failed, reconnecting: {}", e),
+            }
+        } else {
+            log::info!("Node-RED WebSocket closed, reconnecting");
+        }
+        endpoint.socket = None;
+    }
+
+    if let Some(retry_at) = endpoint.retry_at {
+        if Instant::now() < retry_at {
+            return Err(format!("WebSocket connection to {} failed recently", url));
+        }
+    }
+    let mut socket = match connect(url).await {
+        Ok(socket) => socket,
+        Err(e) => {
+            endpoint.retry_at = Some(Instant::now() + Duration::from_millis(RETRY_AFTER_MS));
+            return Err(e);
+        }
+    };
+    endpoint.retry_at = None;
+    send_text(&mut socket.sink, text).await?;
+    endpoint.socket = Some(socket);
+    Ok(())
+}
+
+/// Close the connection to `url`, if open, and forget any failed connect
+pub async fn disconnect(url: &str) {
+    let endpoint = ENDPOINTS.lock().remove(url);
+    if let Some(endpoint) = endpoint {
+        if let Some(mut socket) = endpoint.lock().await.socket.take() {
+            let _ = socket.sink.close().await;
+        }
+    }
+}
+
+async fn send_text(sink: &mut Sink, text: String) -> Result<(), String> {
+    tokio::time::timeout(Duration::from_millis(SEND_TIMEOUT_MS), sink.send(Message::text(text)))
+        .await
+        .map_err(|_| "WebSocket send timed out".to_string())?
+        .map_err(|e| format!("WebSocket send failed: {}", e))
+}
+
+async fn connect(url: &str) -> Result<Socket, String> {
+    let (stream, _) = tokio::time::timeout(
+        Duration::from_millis(CONNECT_TIMEOUT_MS),
+        tokio_tungstenite::connect_async(url),
+    )
+    .await
+    .map_err(|_| format!("WebSocket connection to {} timed out", url))?
+    .map_err(|e| format!("WebSocket connection to {} failed: {}", url, e))?;
+    log::info!("Connected to Node-RED WebSocket at {}", url);
+
+    let (sink, mut incoming) = stream.split();
+    let alive = Arc::new(AtomicBool::new(true));
+    let reader_alive = alive.clone();
+    // Reading also answers pings; anything else Node-RED sends is ignored
+    let reader = tokio::spawn(async move {
+        while let Some(frame) = incoming.next().await {
+            match frame {
+                Ok(Message::Close(_)) | Err(_) => break,
+                Ok(_) => {}
+            }
+        }
+        reader_alive.store(false, Ordering::Release);
+    });
+
+    Ok(Socket { sink, alive, reader })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    /// WebSocket server that accepts `connections` clients in turn and
+    /// forwards the first `per_connection` text messages of each
+    async fn ws_server(
+        connections: usize,
+        per_connection: usize,
+    ) -> (String, tokio::sync::mpsc::UnboundedReceiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/ws", listener.local_addr().unwrap());
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            for _ in 0..connections {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                for _ in 0..per_connection {
+                    match ws.next().await {
+                        Some(Ok(Message::Text(text))) => tx.send(text.to_string()).unwrap(),
+                        _ => break,
+                    }
+                }
+                let _ = ws.close(None).await;
+            }
+        });
+        (url, rx)
+    }
+
+    // ========== Connection Tests ==========
+
+    #[tokio::test]
+    async fn test_messages_reuse_one_connection() {
+        let (url, mut received) = ws_server(1, 2).await;
+
+        send(&url, &json!({"n": 1})).await.unwrap();
+        send(&url, &json!({"n": 2})).await.unwrap();
+
+        assert_eq!(received.recv().await.unwrap(), r#"{"n":1}"#);
+        assert_eq!(received.recv().await.unwrap(), r#"{"n":2}"#);
+        disconnect(&url).await;
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_after_server_closes() {
+        // The server hangs up after each message
+        let (url, mut received) = ws_server(2, 1).await;
+
+        send(&url, &json!({"n": 1})).await.unwrap();
+        assert_eq!(received.recv().await.unwrap(), r#"{"n":1}"#);
+
+        // Give the reader task a moment to notice the close
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        send(&url, &json!({"n": 2})).await.unwrap();
+        assert_eq!(received.recv().await.unwrap(), r#"{"n":2}"#);
+        disconnect(&url).await;
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_server_errors() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let url = format!("ws://127.0.0.1:{}/ws", port);
+        let err = send(&url, &json!({})).await.unwrap_err();
+        assert!(err.contains("failed"), "{}", err);
+        disconnect(&url).await;
+    }
+
+    #[tokio::test]
+    async fn test_failed_connect_is_not_retried_straight_away() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}/ws", listener.local_addr().unwrap());
+        drop(listener);
+        send(&url, &json!({})).await.unwrap_err();
+
+        // Something is listening now, but the cool-down skips the connect
+        let listener = TcpListener::bind(url.trim_start_matches("ws://").trim_end_matches("/ws")).await;
+        let err = send(&url, &json!({})).await.unwrap_err();
+        assert!(err.contains("failed recently"), "{}", err);
+        if let Ok(listener) = listener {
+            assert!(tokio::time::timeout(Duration::from_millis(50), listener.accept()).await.is_err());
+        }
+        disconnect(&url).await;
+    }
+
+    #[tokio::test]
+    async fn test_slow_connect_does_not_block_other_urls() {
+        // Accepts TCP but never answers the handshake
+        let stalled = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stalled_url = format!("ws://{}/ws", stalled.local_addr().unwrap());
+        let (url, mut received) = ws_server(1, 1).await;
+
+        let pending = tokio::spawn({
+            let stalled_url = stalled_url.clone();
+            async move { send(&stalled_url, &json!({})).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        tokio::time::timeout(Duration::from_secs(2), send(&url, &json!({"n": 1})))
+            .await
+            .expect("send queued behind another URL's connect")
+            .unwrap();
+        assert_eq!(received.recv().await.unwrap(), r#"{"n":1}"#);
+
+        pending.abort();
+        disconnect(&url).await;
+        disconnect(&stalled_url).await;
+    }
+}
@@ -0,0 +1,177 @@
+//! Notify Handler
+//!
+//! Shows a desktop notification (a toast on Windows) with a title and body.
+//! Works while the app is hidden in the tray, as it needs no window.
+//!
+//! Notifications the OS refuses to show, including Windows toasts turned off
+//! in Settings, fail the action with a message saying so.
+
+use crate::actions::error::ActionError;
+use crate::actions::types::{ActionResult, NotifyAction};
+use notify_rust::{Notification, Timeout};
+
+/// App identifier, matching `identifier` in tauri.conf.json
+#[cfg(windows)]
+const APP_ID: &str = "com.soomfon.controller";
+
+/// Describe the notification an action would show, without showing it
+pub fn describe(config: &NotifyAction) -> String {
+    format!("Would show notification \"{}\"", config.title)
+}
+
+/// Execute a notify action
+pub async fn execute(config: &NotifyAction) -> ActionResult {
+    log::debug!("Executing notify action: {}", config.title);
+
+    if config.title.trim().is_empty() {
+        return ActionResult::from_error(ActionError::InvalidInput("Notification title is empty".to_string()), 0);
+    }
+    if notifications_disabled() {
+        return ActionResult::failure(
+            "Notifications are turned off for this app in the system settings".to_string(),
+            0,
+        );
+    }
+
+    let notification = build_notification(config);
+    // Showing talks to the OS notification service and can block
+    let shown = tokio::task::spawn_blocking(move || notification.show().map(|_| ()));
+
+    match shown.await {
+        Ok(Ok(())) => ActionResult::success_with_message(format!("Showed notification \"{}\"", config.title), 0),
+        Ok(Err(e)) => ActionResult::failure(
+            format!("Notification could not be shown (are notifications allowed?): {}", e),
+            0,
+        ),
+        Err(e) => ActionResult::failure(format!("Notification task failed: {}", e), 0),
+    }
+}
+
+fn build_notification(config: &NotifyAction) -> Notification {
+    let mut notification = Notification::new();
+    notification
+        .appname("SOOMFON Controller")
+        .summary(&config.title)
+        .body(&config.body)
+        .timeout(timeout(config.duration_ms));
+
+    // Toasts only show under an installed app's ID; a dev build falls back
+    // to the notify-rust default
+    #[cfg(windows)]
+    if !running_from_build_dir() {
+        notification.app_id(APP_ID);
+    }
+
+    notification
+}
+
+/// OS display time for a requested duration
+fn timeout(duration_ms: Option<u64>) -> Timeout {
+    match duration_ms {
+        Some(ms) => Timeout::Milliseconds(ms.min(u32::MAX as u64) as u32),
+        None => Timeout::Default,
+    }
+}
+
+/// Whether the executable is a cargo build rather than an installed copy
+#[cfg(windows)]
+fn running_from_build_dir() -> bool {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.to_path_buf()))
+        .is_some_and(|dir| dir.ends_with("target/debug") || dir.ends_with("target/release"))
+}
+
+/// Whether the user has turned off all toasts in Windows Settings
+///
+/// Windows accepts toasts silently when they are off, so this is checked up front.
+#[cfg(windows)]
+fn notifications_disabled() -> bool {
+    use windows::core::w;
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+
+    let mut enabled: u32 = 1;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            w!("Software\\Microsoft\\Windows\\CurrentVersion\\PushNotifications"),
+            w!("ToastEnabled"),
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut enabled as *mut u32 as *mut std::ffi::c_void),
+            Some(&mut size),
+        )
+    };
+    status.is_ok() && enabled == 0
+}
+
+/// Other platforms report refusal when the notification is shown
+#[cfg(not(windows))]
+fn notifications_disabled() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notify(title: &str) -> NotifyAction {
+        NotifyAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            title: title.to_string(),
+            body: "Live now".to_string(),
+            duration_ms: None,
+        }
+    }
+
+    // ========== Describe Tests ==========
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(describe(&notify("Stream started")), "Would show notification \"Stream started\"");
+    }
+
+    // ========== Timeout Tests ==========
+
+    #[test]
+    fn test_timeout_default_without_duration() {
+        assert_eq!(timeout(None), Timeout::Default);
+    }
+
+    #[test]
+    fn test_timeout_uses_duration() {
+        assert_eq!(timeout(Some(4000)), Timeout::Milliseconds(4000));
+        assert_eq!(timeout(Some(u64::MAX)), Timeout::Milliseconds(u32::MAX));
+    }
+
+    // ========== Execute Tests ==========
+
+    #[tokio::test]
+    async fn test_execute_rejects_empty_title() {
+        let result = execute(&notify("  ")).await;
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("Notification title is empty"));
+    }
+
+    // ========== Serialization Tests ==========
+
+    #[test]
+    fn test_notify_action_deserialize() {
+        let action: crate::actions::types::Action = serde_json::from_str(
+            r#"{"type": "notify", "title": "Stream started", "body": "Live now", "durationMs": 3000}"#,
+        )
+        .unwrap();
+        match action {
+            crate::actions::types::Action::Notify(config) => {
+                assert_eq!(config.title, "Stream started");
+                assert_eq!(config.body, "Live now");
+                assert_eq!(config.duration_ms, Some(3000));
+            }
+            _ => panic!("Expected Notify action"),
+        }
+    }
+}
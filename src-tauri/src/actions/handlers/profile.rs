@@ -6,8 +6,18 @@
 //! than through the action handler. This handler validates the request
 //! and returns the appropriate profile ID to switch to.
 
+use crate::actions::error::ActionError;
 use crate::actions::types::{ActionResult, ProfileAction};
 
+/// Describe the profile switch an action would request
+pub fn describe(config: &ProfileAction) -> String {
+    match (&config.profile_id, &config.profile_name) {
+        (Some(id), _) => format!("Would switch to profile {}", id),
+        (None, Some(name)) => format!("Would switch to profile named {}", name),
+        (None, None) => "Would fail: no profile ID or name specified".to_string(),
+    }
+}
+
 /// Execute a profile switch action
 ///
 /// This handler validates the profile action and returns success with the
@@ -31,6 +41,6 @@ pub async fn execute(config: &ProfileAction) -> ActionResult {
             0,
         )
     } else {
-        ActionResult::failure("No profile ID or name specified".to_string(), 0)
+        ActionResult::from_error(ActionError::InvalidInput("No profile ID or name specified".to_string()), 0)
     }
 }
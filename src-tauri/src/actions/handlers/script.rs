@@ -1,105 +1,538 @@
 //! Script Handler
 //!
 //! Executes scripts (PowerShell, Bash, CMD, or script files).
+//!
+//! The script runs as a child process raced against its timeout and the
+//! cancellation token. If either fires first, the whole process tree is
+//! killed, so helpers the script started don't outlive it. Output still
+//! open once the script has exited (a helper holding its pipes) is read
+//! until the same deadline, after which the helpers are killed and the
+//! output captured so far is used. Script files are run with the
+//! interpreter their extension calls for.
 
+use crate::actions::error::ActionError;
 use crate::actions::types::{ActionResult, ScriptAction, ScriptType};
-use std::process::Command;
+use crate::actions::CancellationToken;
+use parking_lot::Mutex;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::time::timeout;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::{Child, Command};
 
 /// Default script timeout in milliseconds
 const DEFAULT_TIMEOUT_MS: u64 = 30000;
 
+/// Most bytes kept from each of stdout and stderr
+const MAX_OUTPUT_BYTES: usize = 4096;
+
+#[cfg(target_os = "windows")]
+const POWERSHELL: &str = "powershell";
+#[cfg(not(target_os = "windows"))]
+const POWERSHELL: &str = "pwsh";
+
+#[cfg(target_os = "windows")]
+const POWERSHELL_ARGS: &[&str] = &["-NoProfile", "-ExecutionPolicy", "Bypass"];
+#[cfg(not(target_os = "windows"))]
+const POWERSHELL_ARGS: &[&str] = &["-NoProfile"];
+
+#[cfg(target_os = "windows")]
+const PYTHON: &str = "python";
+#[cfg(not(target_os = "windows"))]
+const PYTHON: &str = "python3";
+
+/// Describe which script an action would run, without running it
+pub fn describe(config: &ScriptAction) -> String {
+    match (&config.script_type, config.script_path.as_deref()) {
+        (ScriptType::File, Some(path)) => format!("Would run script file {}", path),
+        (script_type, _) => format!("Would run {:?} script", script_type),
+    }
+}
+
 /// Execute a script action
-pub async fn execute(config: &ScriptAction) -> ActionResult {
+///
+/// Succeeds with the script's stdout when it exits successfully, and fails
+/// with its stderr otherwise; both are truncated to `MAX_OUTPUT_BYTES`.
+pub async fn execute(config: &ScriptAction, token: &CancellationToken) -> ActionResult {
     log::debug!("Executing script action: {:?}", config.script_type);
 
-    let timeout_ms = config.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS);
+    let timeout_ms = timeout_ms(config);
+    let mut command = match build_command(config) {
+        Ok(command) => command,
+        Err(e) => return ActionResult::from_error(e, 0),
+    };
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    // Own process group, so the whole tree can be killed at once
+    #[cfg(unix)]
+    command.process_group(0);
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => return ActionResult::failure(format!("Script execution failed: {}", e), 0),
+    };
+    // The ID is gone once the script has been waited on, but its helpers may not be
+    let pid = child.id();
+    let deadline = tokio::time::Instant::now() + Duration::from_millis(timeout_ms);
+
+    // Drain both pipes while waiting, so a chatty script can't block on a full pipe
+    let stdout = Captured::default();
+    let stderr = Captured::default();
+    let mut stdout_reader = tokio::spawn(read_capped(child.stdout.take(), stdout.clone()));
+    let mut stderr_reader = tokio::spawn(read_capped(child.stderr.take(), stderr.clone()));
+
+    let status = tokio::select! {
+        status = child.wait() => status,
+        _ = tokio::time::sleep_until(deadline) => {
+            log::warn!("Script timed out after {}ms, killing it", timeout_ms);
+            kill_tree(pid, &mut child).await;
+            return ActionResult::from_error(
+                ActionError::Timeout(format!("Script timed out after {}ms", timeout_ms)),
+                timeout_ms,
+            );
+        }
+        _ = token.cancelled() => {
+            log::debug!("Script cancelled, killing it");
+            kill_tree(pid, &mut child).await;
+            return ActionResult::from_error(ActionError::cancelled(), 0);
+        }
+    };
+
+    let status = match status {
+        Ok(status) => status,
+        Err(e) => return ActionResult::failure(format!("Script execution failed: {}", e), 0),
+    };
 
-    let result = timeout(
-        Duration::from_millis(timeout_ms),
-        execute_script(config)
-    ).await;
+    // A helper the script left running can hold the pipes open, so the rest
+    // of the output is only waited for until the deadline or a cancel
+    let drained = tokio::select! {
+        _ = async {
+            let _ = (&mut stdout_reader).await;
+            let _ = (&mut stderr_reader).await;
+        } => true,
+        _ = tokio::time::sleep_until(deadline) => {
+            log::warn!("Script output still open after {}ms, killing leftover processes", timeout_ms);
+            false
+        }
+        _ = token.cancelled() => false,
+    };
+    if !drained {
+        kill_tree(pid, &mut child).await;
+        stdout_reader.abort();
+        stderr_reader.abort();
+        if token.is_cancelled() {
+            return ActionResult::from_error(ActionError::cancelled(), 0);
+        }
+    }
+    let stdout = output_text(stdout.take());
+    let stderr = output_text(stderr.take());
 
-    match result {
-        Ok(action_result) => action_result,
-        Err(_) => ActionResult::failure("Script execution timed out".to_string(), timeout_ms),
+    if status.success() {
+        ActionResult::success_with_message(stdout, 0)
+    } else if stderr.is_empty() {
+        ActionResult::failure(format!("Script failed ({})", status), 0)
+    } else {
+        ActionResult::failure(stderr, 0)
     }
 }
 
-async fn execute_script(config: &ScriptAction) -> ActionResult {
-    // Get script content - try `script` field first, then `content` for backwards compatibility
-    let script_content = config.script.as_ref()
-        .or(config.content.as_ref())
-        .map(|s| s.as_str());
+/// Timeout in milliseconds: `timeoutMs`, else `timeout` in seconds, else the default
+fn timeout_ms(config: &ScriptAction) -> u64 {
+    config
+        .timeout_ms
+        .or(config.timeout.map(|secs| secs.saturating_mul(1000)))
+        .unwrap_or(DEFAULT_TIMEOUT_MS)
+}
 
-    // Get script path if provided
-    let script_path = config.script_path.as_ref().map(|s| s.as_str());
+/// Command that runs the action's script, with its environment and working directory
+fn build_command(config: &ScriptAction) -> Result<Command, ActionError> {
+    let mut command = script_command(config)?;
+    command.envs(&config.env);
+    if let Some(dir) = config.working_directory.as_deref().filter(|dir| !dir.is_empty()) {
+        if !Path::new(dir).is_dir() {
+            return Err(ActionError::InvalidInput(format!("Working directory not found: {}", dir)));
+        }
+        command.current_dir(dir);
+    }
+    Ok(command)
+}
+
+/// Inline script content: `script`, falling back to the legacy `content`
+fn script_content(config: &ScriptAction) -> Result<&str, ActionError> {
+    [config.script.as_deref(), config.content.as_deref()]
+        .into_iter()
+        .flatten()
+        .find(|content| !content.trim().is_empty())
+        .ok_or_else(|| ActionError::InvalidInput("No script content provided (set script or content)".to_string()))
+}
 
-    let result = match config.script_type {
+/// Command for the script itself
+fn script_command(config: &ScriptAction) -> Result<Command, ActionError> {
+    match config.script_type {
         ScriptType::PowerShell => {
-            let content = match script_content {
-                Some(c) => c,
-                None => return ActionResult::failure("No script content provided".to_string(), 0),
-            };
-            #[cfg(target_os = "windows")]
-            {
-                Command::new("powershell")
-                    .args(["-NoProfile", "-ExecutionPolicy", "Bypass", "-Command", content])
-                    .output()
-            }
-            #[cfg(not(target_os = "windows"))]
-            {
-                Command::new("pwsh")
-                    .args(["-NoProfile", "-Command", content])
-                    .output()
-            }
+            let mut command = Command::new(POWERSHELL);
+            command.args(POWERSHELL_ARGS).args(["-Command", script_content(config)?]);
+            Ok(command)
         }
         ScriptType::Bash => {
-            let content = match script_content {
-                Some(c) => c,
-                None => return ActionResult::failure("No script content provided".to_string(), 0),
-            };
-            Command::new("bash")
-                .args(["-c", content])
-                .output()
+            let mut command = Command::new("bash");
+            command.args(["-c", script_content(config)?]);
+            Ok(command)
         }
         ScriptType::Cmd => {
-            let content = match script_content {
-                Some(c) => c,
-                None => return ActionResult::failure("No script content provided".to_string(), 0),
-            };
+            let content = script_content(config)?;
             #[cfg(target_os = "windows")]
             {
-                Command::new("cmd")
-                    .args(["/C", content])
-                    .output()
+                let mut command = Command::new("cmd");
+                command.args(["/C", content]);
+                Ok(command)
             }
             #[cfg(not(target_os = "windows"))]
             {
-                return ActionResult::failure("CMD is only supported on Windows".to_string(), 0);
+                let _ = content;
+                Err(ActionError::NotSupportedOnPlatform("CMD is only supported on Windows".to_string()))
             }
         }
         ScriptType::File => {
-            // Execute script file directly
-            let path = match script_path.or(script_content) {
-                Some(p) => p,
-                None => return ActionResult::failure("No script path provided".to_string(), 0),
+            let path = config
+                .script_path
+                .as_deref()
+                .filter(|path| !path.trim().is_empty())
+                .ok_or_else(|| ActionError::InvalidInput("No script path provided (set scriptPath)".to_string()))?;
+            let command = match file_interpreter(path) {
+                Some((program, args)) => {
+                    let mut command = Command::new(program);
+                    command.args(args).arg(path);
+                    command
+                }
+                None => Command::new(path),
             };
-            Command::new(path).output()
+            Ok(command)
         }
+    }
+}
+
+/// Interpreter and its arguments for a script file, by extension
+///
+/// `None` means the file is run directly (executables and files with a shebang).
+fn file_interpreter(path: &str) -> Option<(&'static str, Vec<&'static str>)> {
+    let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "ps1" => Some((POWERSHELL, [POWERSHELL_ARGS, &["-File"]].concat())),
+        "sh" | "bash" => Some(("bash", Vec::new())),
+        #[cfg(target_os = "windows")]
+        "bat" | "cmd" => Some(("cmd", vec!["/C"])),
+        "py" => Some((PYTHON, Vec::new())),
+        "js" => Some(("node", Vec::new())),
+        _ => None,
+    }
+}
+
+/// Output read from a pipe so far: the kept bytes and whether any were dropped
+///
+/// Shared with the reader task, so what was read is still there if the
+/// task is aborted.
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<(Vec<u8>, bool)>>);
+
+impl Captured {
+    fn take(&self) -> (Vec<u8>, bool) {
+        std::mem::take(&mut *self.0.lock())
+    }
+}
+
+/// Read a pipe to the end into `output`, keeping at most `MAX_OUTPUT_BYTES`
+async fn read_capped<R: AsyncRead + Unpin>(reader: Option<R>, output: Captured) {
+    let Some(mut reader) = reader else {
+        return;
     };
 
-    match result {
-        Ok(output) => {
-            if output.status.success() {
-                let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                ActionResult::success_with_message(stdout, 0)
-            } else {
-                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-                ActionResult::failure(stderr, 0)
+    let mut buf = [0u8; 4096];
+    loop {
+        match reader.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                let mut output = output.0.lock();
+                let (kept, truncated) = &mut *output;
+                let room = MAX_OUTPUT_BYTES.saturating_sub(kept.len());
+                *truncated |= n > room;
+                kept.extend_from_slice(&buf[..n.min(room)]);
+            }
+        }
+    }
+}
+
+fn output_text((bytes, truncated): (Vec<u8>, bool)) -> String {
+    let text = String::from_utf8_lossy(&bytes).into_owned();
+    if truncated {
+        format!("{}... (truncated)", text)
+    } else {
+        text
+    }
+}
+
+/// Kill the script (process ID `pid`) and every process it started
+async fn kill_tree(pid: Option<u32>, child: &mut Child) {
+    if let Some(pid) = pid {
+        // The script leads its own process group (see `execute`)
+        #[cfg(unix)]
+        let killed = Command::new("kill")
+            .args(["-KILL", "--", &format!("-{}", pid)])
+            .status()
+            .await;
+        #[cfg(windows)]
+        let killed = Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .status()
+            .await;
+        if let Err(e) = killed {
+            log::warn!("Failed to kill script process tree: {}", e);
+        }
+    }
+    // Make sure the script itself is gone even if the tree kill failed
+    let _ = child.kill().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn script(script_type: ScriptType, content: &str) -> ScriptAction {
+        ScriptAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            script_type,
+            script: Some(content.to_string()),
+            content: None,
+            script_path: None,
+            timeout: None,
+            timeout_ms: None,
+            env: std::collections::HashMap::new(),
+            working_directory: None,
+        }
+    }
+
+    // ========== Configuration Tests ==========
+
+    #[test]
+    fn test_timeout_prefers_milliseconds() {
+        let mut config = script(ScriptType::Bash, "true");
+        assert_eq!(timeout_ms(&config), DEFAULT_TIMEOUT_MS);
+
+        config.timeout = Some(2);
+        assert_eq!(timeout_ms(&config), 2000);
+
+        config.timeout_ms = Some(500);
+        assert_eq!(timeout_ms(&config), 500);
+    }
+
+    #[test]
+    fn test_file_interpreter_by_extension() {
+        assert_eq!(file_interpreter("deploy.sh"), Some(("bash", Vec::new())));
+        assert_eq!(file_interpreter("tool.PY"), Some((PYTHON, Vec::new())));
+        assert_eq!(
+            file_interpreter("setup.ps1").map(|(_, args)| args.last().copied()),
+            Some(Some("-File"))
+        );
+        assert_eq!(file_interpreter("run"), None);
+        assert_eq!(file_interpreter("app.exe"), None);
+    }
+
+    #[test]
+    fn test_content_prefers_script_over_legacy_field() {
+        let mut config = script(ScriptType::Bash, "echo new");
+        config.content = Some("echo old".to_string());
+        assert_eq!(script_content(&config), Ok("echo new"));
+
+        config.script = Some("  ".to_string());
+        assert_eq!(script_content(&config), Ok("echo old"));
+    }
+
+    #[test]
+    fn test_missing_content_fails() {
+        let mut config = script(ScriptType::Bash, "");
+        config.script = None;
+        // A script path doesn't stand in for inline content
+        config.script_path = Some("run.sh".to_string());
+        assert_eq!(
+            build_command(&config).unwrap_err(),
+            ActionError::InvalidInput("No script content provided (set script or content)".to_string())
+        );
+
+        config.script_type = ScriptType::File;
+        config.script_path = None;
+        config.content = Some("run.sh".to_string());
+        assert_eq!(
+            build_command(&config).unwrap_err(),
+            ActionError::InvalidInput("No script path provided (set scriptPath)".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_content_is_invalid_input() {
+        let mut config = script(ScriptType::Bash, "");
+        config.script = None;
+        let result = execute(&config, &CancellationToken::new()).await;
+
+        assert!(!result.success);
+        assert_eq!(result.error_code, Some(crate::actions::error::ActionErrorCode::InvalidInput));
+    }
+
+    #[test]
+    fn test_missing_working_directory_fails() {
+        let mut config = script(ScriptType::Bash, "pwd");
+        config.working_directory = Some("/definitely/not/here".to_string());
+        let err = build_command(&config).unwrap_err();
+        assert!(matches!(err, ActionError::InvalidInput(_)));
+        assert!(err.to_string().starts_with("Working directory not found"));
+    }
+
+    // ========== Execution Tests ==========
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_returns_stdout_on_success() {
+        let result = execute(&script(ScriptType::Bash, "echo hello"), &CancellationToken::new()).await;
+        assert!(result.success);
+        assert_eq!(result.message.as_deref(), Some("hello\n"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_returns_stderr_on_failure() {
+        let config = script(ScriptType::Bash, "echo oops >&2; exit 3");
+        let result = execute(&config, &CancellationToken::new()).await;
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("oops\n"));
+
+        let result = execute(&script(ScriptType::Bash, "exit 4"), &CancellationToken::new()).await;
+        assert!(result.error.unwrap().contains('4'));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_output_is_truncated() {
+        let config = script(ScriptType::Bash, "head -c 10000 /dev/zero | tr '\\0' a");
+        let result = execute(&config, &CancellationToken::new()).await;
+        let message = result.message.unwrap();
+        assert!(message.ends_with("... (truncated)"));
+        assert_eq!(message.len(), MAX_OUTPUT_BYTES + "... (truncated)".len());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_runs_file_with_interpreter() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("greet.sh");
+        // Not executable, so it only runs through bash
+        std::fs::write(&path, "echo from file").unwrap();
+
+        let mut config = script(ScriptType::File, "");
+        config.script = None;
+        config.script_path = Some(path.to_string_lossy().into_owned());
+        let result = execute(&config, &CancellationToken::new()).await;
+        assert_eq!(result.message.as_deref(), Some("from file\n"), "{:?}", result.error);
+    }
+
+    #[tokio::test]
+    async fn test_env_vars_are_passed_through() {
+        #[cfg(target_os = "windows")]
+        let mut config = script(ScriptType::Cmd, "echo %SOOMFON_TEST_VAR%");
+        #[cfg(not(target_os = "windows"))]
+        let mut config = script(ScriptType::Bash, "echo $SOOMFON_TEST_VAR");
+        config.env.insert("SOOMFON_TEST_VAR".to_string(), "from-env".to_string());
+
+        let result = execute(&config, &CancellationToken::new()).await;
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.message.unwrap().trim(), "from-env");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_runs_in_working_directory() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut config = script(ScriptType::Bash, "pwd");
+        config.working_directory = Some(dir.path().to_string_lossy().into_owned());
+
+        let result = execute(&config, &CancellationToken::new()).await;
+        let cwd = std::path::PathBuf::from(result.message.unwrap().trim());
+        assert_eq!(cwd.canonicalize().unwrap(), dir.path().canonicalize().unwrap());
+    }
+
+    // ========== Timeout and Cancellation Tests ==========
+
+    /// Whether a process has exited (zombies count as exited)
+    #[cfg(target_os = "linux")]
+    fn is_gone(pid: &str) -> bool {
+        match std::fs::read_to_string(format!("/proc/{}/stat", pid)) {
+            Ok(stat) => stat.rsplit(") ").next().is_some_and(|rest| rest.starts_with('Z')),
+            Err(_) => true,
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_timeout_kills_process_tree() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let pid_file = dir.path().join("child.pid");
+        let mut config = script(
+            ScriptType::Bash,
+            &format!("sleep 30 & echo $! > '{}'; wait", pid_file.display()),
+        );
+        config.timeout_ms = Some(300);
+
+        let start = Instant::now();
+        let result = execute(&config, &CancellationToken::new()).await;
+        assert_eq!(result.error.as_deref(), Some("Script timed out after 300ms"));
+        assert_eq!(result.error_code, Some(crate::actions::error::ActionErrorCode::Timeout));
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        let pid = pid.trim();
+        // Give the kernel a moment to tear the process down
+        for _ in 0..50 {
+            if is_gone(pid) {
+                break;
             }
+            std::thread::sleep(Duration::from_millis(20));
         }
-        Err(e) => ActionResult::failure(format!("Script execution failed: {}", e), 0),
+        assert!(is_gone(pid), "background process {} survived", pid);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_helper_holding_pipes_does_not_hang() {
+        // The helper inherits stdout and keeps it open after the script exits
+        let mut config = script(ScriptType::Bash, "sleep 30 & echo started");
+        config.timeout_ms = Some(300);
+
+        let start = Instant::now();
+        let result = execute(&config, &CancellationToken::new()).await;
+        assert!(result.success);
+        assert_eq!(result.message.as_deref(), Some("started\n"));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_cancellation_kills_script() {
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            canceller.cancel();
+        });
+
+        let start = Instant::now();
+        let result = execute(&script(ScriptType::Bash, "sleep 30"), &token).await;
+        assert_eq!(result.error.as_deref(), Some("Cancelled"));
+        assert!(start.elapsed() < Duration::from_secs(5));
     }
 }
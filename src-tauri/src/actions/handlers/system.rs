@@ -14,8 +14,26 @@
 //! - sleep: System sleep command
 //! - hibernate: System hibernate command
 
+use crate::actions::error::ActionError;
 use crate::actions::types::{ActionResult, SystemAction, SystemActionType};
 
+/// Describe what a system action would do, without doing it
+pub fn describe(config: &SystemAction) -> String {
+    let effect = match config.action {
+        SystemActionType::SwitchDesktopLeft => "switch to the desktop on the left",
+        SystemActionType::SwitchDesktopRight => "switch to the desktop on the right",
+        SystemActionType::ShowDesktop => "show the desktop",
+        SystemActionType::LockScreen => "lock the screen",
+        SystemActionType::Screenshot => "take a screenshot",
+        SystemActionType::StartMenu => "open the start menu",
+        SystemActionType::TaskView => "open task view",
+        SystemActionType::Sleep => "put the system to sleep",
+        SystemActionType::Hibernate => "hibernate the system",
+        SystemActionType::OpenUrl => "fail (OpenUrl should use Launch action instead)",
+    };
+    format!("Would {}", effect)
+}
+
 /// Execute a system action
 pub async fn execute(config: &SystemAction) -> ActionResult {
     log::debug!("Executing system action: {:?}", config.action);
@@ -31,7 +49,7 @@ pub async fn execute(config: &SystemAction) -> ActionResult {
         SystemActionType::Sleep => sleep_system(),
         SystemActionType::Hibernate => hibernate_system(),
         SystemActionType::OpenUrl => {
-            ActionResult::failure("OpenUrl should use Launch action instead".to_string(), 0)
+            ActionResult::from_error(ActionError::InvalidInput("OpenUrl should use Launch action instead".to_string()), 0)
         }
     }
 }
@@ -60,7 +78,7 @@ fn execute_shortcut(modifiers: &[Key], key: Key) -> ActionResult {
     #[cfg(not(target_os = "windows"))]
     {
         let _ = (modifiers, key);
-        ActionResult::failure("System shortcuts only supported on Windows".to_string(), 0)
+        ActionResult::from_error(ActionError::NotSupportedOnPlatform("System shortcuts only supported on Windows".to_string()), 0)
     }
 }
 
@@ -194,7 +212,7 @@ fn sleep_system() -> ActionResult {
         if Command::new("pm-suspend").spawn().is_ok() {
             return ActionResult::success(0);
         }
-        ActionResult::failure("No supported sleep mechanism found".to_string(), 0)
+        ActionResult::from_error(ActionError::NotSupportedOnPlatform("No supported sleep mechanism found".to_string()), 0)
     }
 }
 
@@ -229,7 +247,7 @@ fn hibernate_system() -> ActionResult {
         if Command::new("pm-hibernate").spawn().is_ok() {
             return ActionResult::success(0);
         }
-        ActionResult::failure("No supported hibernate mechanism found".to_string(), 0)
+        ActionResult::from_error(ActionError::NotSupportedOnPlatform("No supported hibernate mechanism found".to_string()), 0)
     }
 }
 
@@ -1,13 +1,58 @@
 //! Text Handler
 //!
 //! Types text using keyboard simulation with Unicode support.
-//! On Windows, uses SendInput with KEYEVENTF_UNICODE flag.
+//! On Windows, uses SendInput with KEYEVENTF_UNICODE flag. On Linux and
+//! macOS, uses `enigo`.
+//!
+//! Newlines and tabs are sent as Enter/Tab key presses rather than characters,
+//! since most applications ignore them as Unicode input.
 
 use crate::actions::types::{ActionResult, TextAction};
 
+#[cfg(not(target_os = "windows"))]
+use enigo::{Direction, Key, Keyboard};
+
+/// One unit of typed text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TextInput {
+    Enter,
+    Tab,
+    Char(char),
+}
+
+/// Split text into the inputs to send
+///
+/// `\r\n` and lone `\r` both become a single Enter.
+fn plan_text(text: &str) -> Vec<TextInput> {
+    let mut inputs = Vec::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        inputs.push(match c {
+            '\r' => {
+                chars.next_if_eq(&'\n');
+                TextInput::Enter
+            }
+            '\n' => TextInput::Enter,
+            '\t' => TextInput::Tab,
+            c => TextInput::Char(c),
+        });
+    }
+    inputs
+}
+
+/// Delay between characters (`typeDelay`, or the older `delayMs`)
+fn char_delay_ms(config: &TextAction) -> u64 {
+    config.type_delay.or(config.delay_ms).unwrap_or(0)
+}
+
+/// Describe what a text action would type, without typing it
+pub fn describe(config: &TextAction) -> String {
+    format!("Would type {} characters", plan_text(&config.text).len())
+}
+
 /// Execute a text typing action
 pub async fn execute(config: &TextAction) -> ActionResult {
-    log::debug!("Executing text action: {} chars", config.text.len());
+    log::debug!("Executing text action: {} chars", config.text.chars().count());
 
     #[cfg(target_os = "windows")]
     {
@@ -16,18 +61,24 @@ pub async fn execute(config: &TextAction) -> ActionResult {
 
     #[cfg(not(target_os = "windows"))]
     {
-        let _ = config;
-        ActionResult::failure("Text actions only supported on Windows".to_string(), 0)
+        execute_enigo(config).await
     }
 }
 
 #[cfg(target_os = "windows")]
 async fn execute_windows(config: &TextAction) -> ActionResult {
-    let delay_ms = config.delay_ms.unwrap_or(0);
+    use windows::Win32::UI::Input::KeyboardAndMouse::{VK_RETURN, VK_TAB};
+
+    let delay_ms = char_delay_ms(config);
 
     // Type each character
-    for c in config.text.chars() {
-        if let Err(e) = send_unicode_char(c) {
+    for input in plan_text(&config.text) {
+        let result = match input {
+            TextInput::Enter => send_key_press(VK_RETURN),
+            TextInput::Tab => send_key_press(VK_TAB),
+            TextInput::Char(c) => send_unicode_char(c),
+        };
+        if let Err(e) = result {
             return ActionResult::failure(e, 0);
         }
 
@@ -40,29 +91,54 @@ async fn execute_windows(config: &TextAction) -> ActionResult {
     ActionResult::success(0)
 }
 
+#[cfg(not(target_os = "windows"))]
+async fn execute_enigo(config: &TextAction) -> ActionResult {
+    use crate::actions::handlers::keyboard::enigo_connect;
+
+    let delay_ms = char_delay_ms(config);
+    let inputs = plan_text(&config.text);
+
+    // enigo connections are blocking, so typing runs off the async runtime
+    let typing = tokio::task::spawn_blocking(move || {
+        let mut enigo = enigo_connect()?;
+        for input in inputs {
+            let result = match input {
+                TextInput::Enter => enigo.key(Key::Return, Direction::Click),
+                TextInput::Tab => enigo.key(Key::Tab, Direction::Click),
+                TextInput::Char(c) => enigo.text(c.encode_utf8(&mut [0u8; 4])),
+            };
+            result.map_err(|e| format!("Failed to type text: {}", e))?;
+
+            if delay_ms > 0 {
+                std::thread::sleep(std::time::Duration::from_millis(delay_ms));
+            }
+        }
+        Ok::<(), String>(())
+    });
+
+    match typing.await {
+        Ok(Ok(())) => ActionResult::success(0),
+        Ok(Err(e)) => ActionResult::failure(e, 0),
+        Err(e) => ActionResult::failure(format!("Text typing task failed: {}", e), 0),
+    }
+}
+
+/// UTF-16 code units for a character (two for a surrogate pair above U+FFFF)
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn utf16_units(c: char) -> Vec<u16> {
+    let mut buf = [0u16; 2];
+    c.encode_utf16(&mut buf).to_vec()
+}
+
 /// Send a single Unicode character using SendInput
 #[cfg(target_os = "windows")]
 fn send_unicode_char(c: char) -> Result<(), String> {
     use windows::Win32::UI::Input::KeyboardAndMouse::*;
 
-    // Handle newline specially - send Enter key
-    if c == '\n' {
-        return send_key_press(VK_RETURN);
-    }
-
-    // Handle tab specially
-    if c == '\t' {
-        return send_key_press(VK_TAB);
-    }
-
-    // For regular characters, use Unicode input
     let mut inputs = Vec::new();
 
-    // UTF-16 encode the character (handles surrogate pairs for emoji etc.)
-    let mut utf16_buf = [0u16; 2];
-    let utf16 = c.encode_utf16(&mut utf16_buf);
-
-    for code_unit in utf16.iter().copied() {
+    // Characters above U+FFFF are sent as both halves of the surrogate pair
+    for code_unit in utf16_units(c) {
         // Key down
         inputs.push(INPUT {
             r#type: INPUT_KEYBOARD,
@@ -149,3 +225,57 @@ fn send_key_press(vk: VIRTUAL_KEY) -> Result<(), String> {
 
 #[cfg(target_os = "windows")]
 use windows::Win32::UI::Input::KeyboardAndMouse::VIRTUAL_KEY;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_action(text: &str) -> TextAction {
+        TextAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            text: text.to_string(),
+            type_delay: None,
+            delay_ms: None,
+        }
+    }
+
+    // ========== Text Planning Tests ==========
+
+    #[test]
+    fn test_plan_text_unicode_chars() {
+        assert_eq!(
+            plan_text("é😀"),
+            vec![TextInput::Char('é'), TextInput::Char('😀')]
+        );
+    }
+
+    #[test]
+    fn test_plan_text_newlines_and_tabs() {
+        use TextInput::*;
+        assert_eq!(
+            plan_text("a\nb\r\nc\rd\te"),
+            vec![Char('a'), Enter, Char('b'), Enter, Char('c'), Enter, Char('d'), Tab, Char('e')]
+        );
+    }
+
+    #[test]
+    fn test_surrogate_pair_emits_two_units() {
+        assert_eq!(utf16_units('é'), vec![0x00E9]);
+        assert_eq!(utf16_units('😀'), vec![0xD83D, 0xDE00]);
+    }
+
+    #[test]
+    fn test_char_delay_prefers_type_delay() {
+        let mut action = text_action("hi");
+        assert_eq!(char_delay_ms(&action), 0);
+
+        action.delay_ms = Some(10);
+        assert_eq!(char_delay_ms(&action), 10);
+
+        action.type_delay = Some(25);
+        assert_eq!(char_delay_ms(&action), 25);
+    }
+}
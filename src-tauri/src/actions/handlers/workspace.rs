@@ -1,48 +1,255 @@
 //! Workspace Handler
 //!
 //! Handles workspace navigation actions within the current profile.
-//! Note: Due to Tauri's state management architecture, workspace switching
-//! is typically performed via the frontend. This handler validates the request
-//! and returns the appropriate workspace information.
+//! With device access, the active profile's workspace is switched and the new
+//! workspace's button images are shown on the device. Without it (e.g. a
+//! standalone execution), the request is only validated and reported, and
+//! the switch is left to the frontend.
 
+use crate::actions::error::ActionError;
 use crate::actions::types::{ActionResult, WorkspaceAction, WorkspaceDirection};
+use crate::actions::feedback;
+use crate::actions::{DeviceHandle, IntegrationConfig};
+use crate::config::types::ButtonConfig;
+use crate::hid::manager::HidManager;
+use crate::hid::protocol::SoomfonProtocol;
+use crate::hid::types::LCD_BUTTON_COUNT;
+use crate::image::processor::{create_solid_color, process_image_source, ImageOptions};
+use parking_lot::Mutex;
 
 /// Execute a workspace navigation action
-///
-/// This handler validates the workspace action and returns success with the
-/// navigation information. The actual workspace switching should be performed
-/// by the frontend.
-pub async fn execute(config: &WorkspaceAction) -> ActionResult {
+pub async fn execute(config: &WorkspaceAction, integrations: &IntegrationConfig) -> ActionResult {
     log::debug!("Executing workspace action: {:?}", config);
 
+    if config.direction == WorkspaceDirection::Specific && config.workspace_index.is_none() {
+        return ActionResult::from_error(
+            ActionError::InvalidInput("Workspace index required for specific navigation".to_string()),
+            0,
+        );
+    }
+
+    match integrations.device.as_ref() {
+        Some(device) => switch(config, device, integrations),
+        None => requested(config),
+    }
+}
+
+/// Describe the workspace switch an action would make, without making it
+pub fn describe(config: &WorkspaceAction) -> String {
     match config.direction {
-        WorkspaceDirection::Next => {
-            log::info!("Workspace navigation: next");
-            ActionResult::success_with_message(
-                "Workspace switch requested: next".to_string(),
-                0,
-            )
-        }
-        WorkspaceDirection::Previous => {
-            log::info!("Workspace navigation: previous");
-            ActionResult::success_with_message(
-                "Workspace switch requested: previous".to_string(),
-                0,
-            )
+        WorkspaceDirection::Next => "Would switch to the next workspace".to_string(),
+        WorkspaceDirection::Previous => "Would switch to the previous workspace".to_string(),
+        WorkspaceDirection::Specific => match config.workspace_index {
+            Some(index) => format!("Would switch to workspace {}", index + 1),
+            None => "Would fail: workspace index required for specific navigation".to_string(),
+        },
+    }
+}
+
+/// Report the requested navigation for the frontend to perform
+fn requested(config: &WorkspaceAction) -> ActionResult {
+    let target = match config.direction {
+        WorkspaceDirection::Next => "next".to_string(),
+        WorkspaceDirection::Previous => "previous".to_string(),
+        WorkspaceDirection::Specific => format!("index {}", config.workspace_index.unwrap_or_default()),
+    };
+    log::info!("Workspace navigation: {}", target);
+    ActionResult::success_with_message(format!("Workspace switch requested: {}", target), 0)
+}
+
+/// Switch the active profile's workspace and show its buttons
+fn switch(config: &WorkspaceAction, device: &DeviceHandle, integrations: &IntegrationConfig) -> ActionResult {
+    let Some(profile_id) = device.config.lock().get_active_profile_id().map(str::to_string) else {
+        return ActionResult::from_error(ActionError::ConfigMissing("No active profile".to_string()), 0);
+    };
+
+    let profile = {
+        let mut profiles = device.profiles.lock();
+        let Some(current) = profiles.get(&profile_id) else {
+            return ActionResult::from_error(ActionError::ConfigMissing(format!("Profile not found: {}", profile_id)), 0);
+        };
+        let index = match target_index(
+            &config.direction,
+            config.workspace_index,
+            current.active_workspace_index,
+            current.workspaces.len(),
+        ) {
+            Ok(index) => index,
+            Err(e) => return ActionResult::from_error(ActionError::InvalidInput(e), 0),
+        };
+        // The profile and index were checked above, so this fails on saving
+        match profiles.set_active_workspace(&profile_id, index) {
+            Ok(profile) => profile,
+            Err(e) => return ActionResult::from_error(ActionError::Storage(e), 0),
         }
-        WorkspaceDirection::Specific => {
-            if let Some(index) = config.workspace_index {
-                log::info!("Workspace navigation: specific index {}", index);
-                ActionResult::success_with_message(
-                    format!("Workspace switch requested: index {}", index),
-                    0,
-                )
-            } else {
-                ActionResult::failure(
-                    "Workspace index required for specific navigation".to_string(),
-                    0,
-                )
-            }
+    };
+
+    let Some(workspace) = profile.active_workspace() else {
+        return ActionResult::failure("Active workspace missing".to_string(), 0);
+    };
+    log::info!("Switched to workspace {} ({})", profile.active_workspace_index + 1, workspace.name);
+
+    if let Err(e) = render_buttons(&device.hid, &workspace.buttons) {
+        log::warn!("Failed to show workspace buttons: {}", e);
+    }
+    feedback::sync(&workspace.buttons, integrations);
+
+    ActionResult::success_with_message(
+        format!("Switched to workspace {} ({})", profile.active_workspace_index + 1, workspace.name),
+        0,
+    )
+}
+
+/// Index of the workspace to switch to; next and previous wrap around
+fn target_index(
+    direction: &WorkspaceDirection,
+    index: Option<usize>,
+    current: usize,
+    count: usize,
+) -> Result<usize, String> {
+    if count == 0 {
+        return Err("Profile has no workspaces".to_string());
+    }
+    let current = current.min(count - 1);
+
+    match direction {
+        WorkspaceDirection::Next => Ok((current + 1) % count),
+        WorkspaceDirection::Previous => Ok((current + count - 1) % count),
+        WorkspaceDirection::Specific => match index {
+            Some(index) if index < count => Ok(index),
+            Some(index) => Err(format!("Workspace index {} out of range (profile has {})", index, count)),
+            None => Err("Workspace index required for specific navigation".to_string()),
+        },
+    }
+}
+
+/// Show button images on the device's LCD buttons
+///
+/// Buttons without an image (or whose image can't be loaded) are blanked, and
+/// buttons already showing the right image are skipped. All images are
+/// processed before the device is locked and then uploaded in one burst.
+pub fn render_buttons(hid: &Mutex<HidManager>, buttons: &[ButtonConfig]) -> Result<(), String> {
+    let options = ImageOptions::default();
+    let blank = create_solid_color(0, 0, 0)?;
+    let images: Vec<(u8, Vec<u8>)> = (0..LCD_BUTTON_COUNT)
+        .map(|index| {
+            let source = buttons
+                .iter()
+                .find(|b| b.index == index as usize)
+                .and_then(|b| b.image.as_deref());
+            let jpeg = match source.map(|source| process_image_source(source, &options)) {
+                Some(Ok(jpeg)) => jpeg,
+                Some(Err(e)) => {
+                    log::warn!("Button {}: {}", index, e);
+                    blank.clone()
+                }
+                None => blank.clone(),
+            };
+            (index, jpeg)
+        })
+        .collect();
+
+    let mut manager = hid.lock();
+    // Reopen handle if it was transferred to polling thread
+    manager.reopen_for_commands().map_err(|e| e.to_string())?;
+
+    let images: Vec<(u8, Vec<u8>)> = images
+        .into_iter()
+        .filter(|(index, jpeg)| !manager.is_button_image_current(*index, jpeg))
+        .collect();
+    if images.is_empty() {
+        return Ok(());
+    }
+
+    let protocol = SoomfonProtocol::new(&manager);
+    protocol.set_button_images_batch(&images).map_err(|e| e.to_string())?;
+    for (index, jpeg) in images {
+        manager.cache_button_image(index, jpeg);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::manager::ConfigManager;
+    use crate::config::profiles::ProfileManager;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn action(direction: WorkspaceDirection, workspace_index: Option<usize>) -> WorkspaceAction {
+        WorkspaceAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            direction,
+            workspace_index,
         }
     }
+
+    // ========== Target Index Tests ==========
+
+    #[test]
+    fn test_next_and_previous_wrap() {
+        assert_eq!(target_index(&WorkspaceDirection::Next, None, 1, 3), Ok(2));
+        assert_eq!(target_index(&WorkspaceDirection::Next, None, 2, 3), Ok(0));
+        assert_eq!(target_index(&WorkspaceDirection::Previous, None, 0, 3), Ok(2));
+        assert_eq!(target_index(&WorkspaceDirection::Previous, None, 2, 3), Ok(1));
+    }
+
+    #[test]
+    fn test_specific_index_must_exist() {
+        assert_eq!(target_index(&WorkspaceDirection::Specific, Some(1), 0, 2), Ok(1));
+        assert!(target_index(&WorkspaceDirection::Specific, Some(2), 0, 2).is_err());
+        assert!(target_index(&WorkspaceDirection::Specific, None, 0, 2).is_err());
+    }
+
+    // ========== Execution Tests ==========
+
+    #[tokio::test]
+    async fn test_without_device_only_reports() {
+        let result = execute(&action(WorkspaceDirection::Next, None), &IntegrationConfig::default()).await;
+        assert_eq!(result.message.as_deref(), Some("Workspace switch requested: next"));
+
+        let result = execute(&action(WorkspaceDirection::Specific, None), &IntegrationConfig::default()).await;
+        assert!(!result.success);
+    }
+
+    #[tokio::test]
+    async fn test_switches_active_profile_workspace() {
+        let dir = TempDir::new().unwrap();
+        let mut profiles = ProfileManager::new(dir.path().join("profiles"));
+        let profile = profiles.create("Layers".to_string()).unwrap();
+        profiles.add_workspace(&profile.id, Some("Media".to_string())).unwrap();
+        let mut config = ConfigManager::new(dir.path().to_path_buf());
+        config.set_active_profile_id(Some(profile.id.clone())).unwrap();
+
+        let device = DeviceHandle {
+            hid: Arc::new(Mutex::new(HidManager::new())),
+            config: Arc::new(Mutex::new(config)),
+            profiles: Arc::new(Mutex::new(profiles)),
+        };
+
+        // No device is connected, so only the image upload fails
+        let result = execute(&action(WorkspaceDirection::Next, None), &IntegrationConfig::default().with_device(device.clone())).await;
+        assert_eq!(result.message.as_deref(), Some("Switched to workspace 2 (Media)"));
+        assert_eq!(device.profiles.lock().get(&profile.id).unwrap().active_workspace_index, 1);
+
+        let result = execute(&action(WorkspaceDirection::Specific, Some(5)), &IntegrationConfig::default().with_device(device.clone())).await;
+        assert!(!result.success);
+    }
+
+    #[tokio::test]
+    async fn test_no_active_profile_fails() {
+        let dir = TempDir::new().unwrap();
+        let device = DeviceHandle {
+            hid: Arc::new(Mutex::new(HidManager::new())),
+            config: Arc::new(Mutex::new(ConfigManager::new(dir.path().to_path_buf()))),
+            profiles: Arc::new(Mutex::new(ProfileManager::new(dir.path().join("profiles")))),
+        };
+
+        let result = execute(&action(WorkspaceDirection::Next, None), &IntegrationConfig::default().with_device(device.clone())).await;
+        assert_eq!(result.error.as_deref(), Some("No active profile"));
+    }
 }
@@ -0,0 +1,384 @@
+//! Action History Store
+//!
+//! Persists action history to a JSONL file in the app data directory so it
+//! survives restarts. Entries are handed to a background writer thread and
+//! buffered there, so recording an action never waits on disk I/O; the buffer
+//! is flushed shortly after the last write, before reads, on app exit, and on
+//! drop.
+//!
+//! When the file grows past its size limit it is moved to `<name>.1`
+//! (replacing any older one) and a fresh file is started, so at most about
+//! twice the limit is kept on disk.
+
+use super::engine::HistoryEntry;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// History file name in the app data directory
+pub const HISTORY_FILE: &str = "action_history.jsonl";
+
+/// Default size at which the history file is rotated
+pub const DEFAULT_MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// How long buffered entries may wait before being flushed
+const FLUSH_INTERVAL_MS: u64 = 500;
+
+/// Requests handled by the writer thread
+enum Command {
+    Append(HistoryEntry),
+    /// Flush buffered entries, then acknowledge
+    Flush(Sender<()>),
+    /// Delete the history files, then acknowledge
+    Clear(Sender<Result<(), String>>),
+}
+
+/// Append-only action history file with a background writer
+pub struct HistoryStore {
+    path: PathBuf,
+    tx: Option<Sender<Command>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl HistoryStore {
+    /// Open (or create) the history file at `path`, rotating it once it
+    /// exceeds `max_bytes`
+    pub fn open(path: PathBuf, max_bytes: u64) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create history directory: {}", e))?;
+        }
+        let file = open_append(&path)?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+        let (tx, rx) = mpsc::channel();
+        let mut writer = Writer {
+            path: path.clone(),
+            max_bytes: max_bytes.max(1),
+            out: BufWriter::new(file),
+            size,
+        };
+        let handle = std::thread::Builder::new()
+            .name("history-writer".to_string())
+            .spawn(move || writer.run(rx))
+            .map_err(|e| format!("Failed to start history writer: {}", e))?;
+
+        Ok(Self {
+            path,
+            tx: Some(tx),
+            writer: Some(handle),
+        })
+    }
+
+    /// Path of the history file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Queue an entry to be written
+    pub fn append(&self, entry: HistoryEntry) {
+        if let Some(tx) = &self.tx {
+            if tx.send(Command::Append(entry)).is_err() {
+                log::warn!("History writer stopped; entry not saved");
+            }
+        }
+    }
+
+    /// Write out any buffered entries
+    pub fn flush(&self) {
+        let (ack_tx, ack_rx) = mpsc::channel();
+        if let Some(tx) = &self.tx {
+            if tx.send(Command::Flush(ack_tx)).is_ok() {
+                let _ = ack_rx.recv();
+            }
+        }
+    }
+
+    /// The most recent `limit` entries, oldest first
+    ///
+    /// Buffered entries are flushed first, and the rotated file is read too
+    /// when the current one holds fewer than `limit`.
+    pub fn load_tail(&self, limit: usize) -> Vec<HistoryEntry> {
+        self.flush();
+
+        let mut entries = read_entries(&self.path);
+        if entries.len() < limit {
+            let mut older = read_entries(&rotated_path(&self.path));
+            older.append(&mut entries);
+            entries = older;
+        }
+        let skip = entries.len().saturating_sub(limit);
+        entries.split_off(skip)
+    }
+
+    /// Delete all saved history
+    pub fn clear(&self) -> Result<(), String> {
+        let (ack_tx, ack_rx) = mpsc::channel();
+        let tx = self.tx.as_ref().ok_or("History writer stopped")?;
+        tx.send(Command::Clear(ack_tx))
+            .map_err(|_| "History writer stopped".to_string())?;
+        ack_rx.recv().map_err(|_| "History writer stopped".to_string())?
+    }
+}
+
+impl Drop for HistoryStore {
+    fn drop(&mut self) {
+        // Closing the channel makes the writer flush and exit
+        self.tx.take();
+        if let Some(handle) = self.writer.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl std::fmt::Debug for HistoryStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HistoryStore").field("path", &self.path).finish()
+    }
+}
+
+/// Background side of the store; owns the open file
+struct Writer {
+    path: PathBuf,
+    max_bytes: u64,
+    out: BufWriter<File>,
+    /// Bytes in the current file, including buffered ones
+    size: u64,
+}
+
+impl Writer {
+    fn run(&mut self, rx: mpsc::Receiver<Command>) {
+        let mut dirty = false;
+        loop {
+            let command = if dirty {
+                rx.recv_timeout(Duration::from_millis(FLUSH_INTERVAL_MS))
+            } else {
+                rx.recv().map_err(|_| RecvTimeoutError::Disconnected)
+            };
+
+            match command {
+                Ok(Command::Append(entry)) => {
+                    if let Err(e) = self.write(&entry) {
+                        log::warn!("Failed to save action history: {}", e);
+                    }
+                    dirty = true;
+                }
+                Ok(Command::Flush(ack)) => {
+                    self.flush();
+                    dirty = false;
+                    let _ = ack.send(());
+                }
+                Ok(Command::Clear(ack)) => {
+                    let _ = ack.send(self.clear());
+                    dirty = false;
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    self.flush();
+                    dirty = false;
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.flush();
+                    return;
+                }
+            }
+        }
+    }
+
+    fn write(&mut self, entry: &HistoryEntry) -> Result<(), String> {
+        let mut line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+        line.push('\n');
+        self.out.write_all(line.as_bytes()).map_err(|e| e.to_string())?;
+        self.size += line.len() as u64;
+
+        if self.size > self.max_bytes {
+            self.rotate()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) {
+        if let Err(e) = self.out.flush() {
+            log::warn!("Failed to flush action history: {}", e);
+        }
+    }
+
+    /// Move the current file aside and start a new one
+    fn rotate(&mut self) -> Result<(), String> {
+        self.out.flush().map_err(|e| e.to_string())?;
+        std::fs::rename(&self.path, rotated_path(&self.path))
+            .map_err(|e| format!("Failed to rotate history file: {}", e))?;
+        self.out = BufWriter::new(open_append(&self.path)?);
+        self.size = 0;
+        log::debug!("Rotated action history file {:?}", self.path);
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), String> {
+        let rotated = rotated_path(&self.path);
+        if rotated.exists() {
+            std::fs::remove_file(&rotated)
+                .map_err(|e| format!("Failed to delete history file: {}", e))?;
+        }
+        let file = File::create(&self.path)
+            .map_err(|e| format!("Failed to truncate history file: {}", e))?;
+        // Swap in the new file without flushing the old buffer into it
+        let old = std::mem::replace(&mut self.out, BufWriter::new(file));
+        drop(old.into_parts());
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> Result<File, String> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open history file: {}", e))
+}
+
+/// Where the previous history file is kept after rotation
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".1");
+    path.with_file_name(name)
+}
+
+/// Entries in a history file; missing files and unreadable lines are skipped
+fn read_entries(path: &Path) -> Vec<HistoryEntry> {
+    let Ok(file) = File::open(path) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(&line) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                log::warn!("Skipping invalid history line: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(action_type: &str, timestamp: u64) -> HistoryEntry {
+        HistoryEntry {
+            action_type: action_type.to_string(),
+            success: true,
+            duration_ms: 5,
+            timestamp,
+            error: None,
+        }
+    }
+
+    // ========== Persistence Tests ==========
+
+    #[test]
+    fn test_entries_survive_reopen() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(HISTORY_FILE);
+
+        let store = HistoryStore::open(path.clone(), DEFAULT_MAX_FILE_BYTES).unwrap();
+        store.append(entry("keyboard", 1));
+        store.append(entry("media", 2));
+        drop(store);
+
+        let store = HistoryStore::open(path, DEFAULT_MAX_FILE_BYTES).unwrap();
+        let types: Vec<String> = store.load_tail(10).into_iter().map(|e| e.action_type).collect();
+        assert_eq!(types, vec!["keyboard", "media"]);
+    }
+
+    #[test]
+    fn test_load_tail_keeps_most_recent() {
+        let dir = TempDir::new().unwrap();
+        let store = HistoryStore::open(dir.path().join(HISTORY_FILE), DEFAULT_MAX_FILE_BYTES).unwrap();
+        for ts in 0..5 {
+            store.append(entry("keyboard", ts));
+        }
+
+        let timestamps: Vec<u64> = store.load_tail(2).into_iter().map(|e| e.timestamp).collect();
+        assert_eq!(timestamps, vec![3, 4]);
+    }
+
+    #[test]
+    fn test_flush_writes_buffered_entries() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(HISTORY_FILE);
+        let store = HistoryStore::open(path.clone(), DEFAULT_MAX_FILE_BYTES).unwrap();
+        store.append(entry("keyboard", 1));
+        store.append(entry("media", 2));
+
+        // Entries are on disk once flush returns, without waiting for the
+        // flush interval or dropping the store
+        store.flush();
+        assert_eq!(read_entries(&path).len(), 2);
+    }
+
+    #[test]
+    fn test_invalid_lines_are_skipped() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(HISTORY_FILE);
+        let valid = serde_json::to_string(&entry("launch", 7)).unwrap();
+        std::fs::write(&path, format!("not json\n{}\n", valid)).unwrap();
+
+        let store = HistoryStore::open(path, DEFAULT_MAX_FILE_BYTES).unwrap();
+        let loaded = store.load_tail(10);
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].timestamp, 7);
+    }
+
+    // ========== Rotation Tests ==========
+
+    #[test]
+    fn test_rotates_when_file_exceeds_limit() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(HISTORY_FILE);
+        let line_len = serde_json::to_string(&entry("keyboard", 0)).unwrap().len() as u64 + 1;
+
+        // Rotate after every third entry
+        let store = HistoryStore::open(path.clone(), line_len * 3 - 1).unwrap();
+        for ts in 0..7 {
+            store.append(entry("keyboard", ts));
+        }
+        store.flush();
+
+        assert!(rotated_path(&path).exists());
+        assert_eq!(read_entries(&path).len(), 1);
+        assert_eq!(read_entries(&rotated_path(&path)).len(), 3);
+
+        // The tail spans both files
+        let timestamps: Vec<u64> = store.load_tail(3).into_iter().map(|e| e.timestamp).collect();
+        assert_eq!(timestamps, vec![4, 5, 6]);
+    }
+
+    // ========== Clear Tests ==========
+
+    #[test]
+    fn test_clear_removes_all_history() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(HISTORY_FILE);
+        let line_len = serde_json::to_string(&entry("keyboard", 0)).unwrap().len() as u64 + 1;
+
+        let store = HistoryStore::open(path.clone(), line_len).unwrap();
+        for ts in 0..3 {
+            store.append(entry("keyboard", ts));
+        }
+        store.clear().unwrap();
+
+        assert!(store.load_tail(10).is_empty());
+        assert!(!rotated_path(&path).exists());
+
+        store.append(entry("media", 9));
+        assert_eq!(store.load_tail(10).len(), 1);
+    }
+}
@@ -0,0 +1,295 @@
+//! Macro Recorder
+//!
+//! Captures physical keystrokes into a `KeyboardAction` sequence.
+//! On Windows, uses a low-level keyboard hook (WH_KEYBOARD_LL) running on
+//! its own message-loop thread.
+
+use crate::actions::types::{KeyStep, KeyboardAction};
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use std::time::Instant;
+
+/// Recording in progress, fed by the keyboard hook
+static RECORDING: Mutex<Option<MacroRecording>> = Mutex::new(None);
+
+/// Key names the recorder treats as modifiers
+const MODIFIER_KEYS: &[&str] = &["lctrl", "rctrl", "lalt", "ralt", "lshift", "rshift", "lwin", "rwin"];
+
+fn is_modifier(key: &str) -> bool {
+    MODIFIER_KEYS.contains(&key)
+}
+
+/// Key events captured during a recording session
+#[derive(Debug, Default)]
+pub struct MacroRecording {
+    steps: Vec<KeyStep>,
+    held: HashSet<String>,
+    last_event: Option<Instant>,
+}
+
+impl MacroRecording {
+    /// Create an empty recording
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a key event, timing it against the previous event
+    pub fn push(&mut self, key: &str, down: bool) {
+        self.push_at(key, down, Instant::now());
+    }
+
+    fn push_at(&mut self, key: &str, down: bool, at: Instant) {
+        if down {
+            // Holding a key produces repeated key-downs; keep only the first
+            if !self.held.insert(key.to_string()) {
+                return;
+            }
+        } else if !self.held.remove(key) {
+            // Release of a key pressed before recording started
+            return;
+        }
+
+        let delay_ms = self
+            .last_event
+            .map(|last| at.duration_since(last).as_millis() as u64)
+            .unwrap_or(0);
+        self.last_event = Some(at);
+
+        self.steps.push(KeyStep {
+            key: key.to_string(),
+            down,
+            delay_ms,
+        });
+    }
+
+    /// Finish the recording and build a bindable keyboard action
+    ///
+    /// Trailing modifier events (e.g. the modifier held to trigger stop) are
+    /// dropped, and any key still held is released so replay never leaves
+    /// keys stuck down.
+    pub fn into_action(mut self) -> KeyboardAction {
+        while self.steps.last().is_some_and(|step| is_modifier(&step.key)) {
+            let step = self.steps.pop().unwrap();
+            if step.down {
+                self.held.remove(&step.key);
+            } else {
+                self.held.insert(step.key);
+            }
+        }
+
+        let mut still_held: Vec<String> = self.held.into_iter().collect();
+        still_held.sort();
+        for key in still_held {
+            self.steps.push(KeyStep {
+                key,
+                down: false,
+                delay_ms: 0,
+            });
+        }
+
+        KeyboardAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            keys: String::new(),
+            modifiers: Vec::new(),
+            hold_duration: None,
+            sequence: self.steps,
+        }
+    }
+}
+
+/// Start capturing keystrokes
+pub fn start() -> Result<(), String> {
+    let mut recording = RECORDING.lock();
+    if recording.is_some() {
+        return Err("Macro recording already in progress".to_string());
+    }
+
+    install_hook()?;
+    *recording = Some(MacroRecording::new());
+    log::info!("Macro recording started");
+    Ok(())
+}
+
+/// Stop capturing and return the recorded sequence as a keyboard action
+pub fn stop() -> Result<KeyboardAction, String> {
+    uninstall_hook();
+
+    let recording = RECORDING
+        .lock()
+        .take()
+        .ok_or_else(|| "No macro recording in progress".to_string())?;
+
+    let action = recording.into_action();
+    log::info!("Macro recording stopped: {} steps", action.sequence.len());
+    Ok(action)
+}
+
+#[cfg(target_os = "windows")]
+fn install_hook() -> Result<(), String> {
+    hook::install()
+}
+
+#[cfg(not(target_os = "windows"))]
+fn install_hook() -> Result<(), String> {
+    Err("Macro recording only supported on Windows".to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn uninstall_hook() {
+    hook::uninstall();
+}
+
+#[cfg(not(target_os = "windows"))]
+fn uninstall_hook() {}
+
+#[cfg(target_os = "windows")]
+mod hook {
+    use super::RECORDING;
+    use crate::actions::handlers::keyboard::vk_to_key_name;
+    use parking_lot::Mutex;
+    use std::thread::JoinHandle;
+    use windows::Win32::Foundation::{LPARAM, LRESULT, WPARAM};
+    use windows::Win32::System::Threading::GetCurrentThreadId;
+    use windows::Win32::UI::Input::KeyboardAndMouse::VIRTUAL_KEY;
+    use windows::Win32::UI::WindowsAndMessaging::*;
+
+    /// Message-loop thread owning the hook: (thread id, handle)
+    static HOOK_THREAD: Mutex<Option<(u32, JoinHandle<()>)>> = Mutex::new(None);
+
+    unsafe extern "system" fn keyboard_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code == HC_ACTION as i32 {
+            let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+            // Skip input we synthesized ourselves (e.g. replaying a macro)
+            if (info.flags & LLKHF_INJECTED).0 == 0 {
+                let down = matches!(wparam.0 as u32, WM_KEYDOWN | WM_SYSKEYDOWN);
+                if let Some(name) = vk_to_key_name(VIRTUAL_KEY(info.vkCode as u16)) {
+                    if let Some(recording) = RECORDING.lock().as_mut() {
+                        recording.push(&name, down);
+                    }
+                }
+            }
+        }
+        CallNextHookEx(None, code, wparam, lparam)
+    }
+
+    pub fn install() -> Result<(), String> {
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let handle = std::thread::spawn(move || unsafe {
+            let hook = match SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_proc), None, 0) {
+                Ok(hook) => hook,
+                Err(e) => {
+                    let _ = tx.send(Err(format!("Failed to install keyboard hook: {}", e)));
+                    return;
+                }
+            };
+            let _ = tx.send(Ok(GetCurrentThreadId()));
+
+            // Low-level hooks are only called while this thread pumps messages
+            let mut msg = MSG::default();
+            while GetMessageW(&mut msg, None, 0, 0).as_bool() {}
+
+            let _ = UnhookWindowsHookEx(hook);
+        });
+
+        let thread_id = rx
+            .recv()
+            .map_err(|_| "Keyboard hook thread exited unexpectedly".to_string())??;
+        *HOOK_THREAD.lock() = Some((thread_id, handle));
+        Ok(())
+    }
+
+    pub fn uninstall() {
+        if let Some((thread_id, handle)) = HOOK_THREAD.lock().take() {
+            unsafe {
+                let _ = PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0));
+            }
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn record(events: &[(&str, bool, u64)]) -> KeyboardAction {
+        let mut recording = MacroRecording::new();
+        let start = Instant::now();
+        for &(key, down, at_ms) in events {
+            recording.push_at(key, down, start + Duration::from_millis(at_ms));
+        }
+        recording.into_action()
+    }
+
+    fn keys(action: &KeyboardAction) -> Vec<(String, bool)> {
+        action.sequence.iter().map(|s| (s.key.clone(), s.down)).collect()
+    }
+
+    // ========== Recording Tests ==========
+
+    #[test]
+    fn test_records_chord_with_delays() {
+        let action = record(&[
+            ("lctrl", true, 0),
+            ("c", true, 40),
+            ("c", false, 90),
+            ("lctrl", false, 120),
+            ("v", true, 500),
+            ("v", false, 550),
+        ]);
+
+        // Trailing modifiers are trimmed only after the last regular key
+        assert_eq!(action.sequence.len(), 6);
+        let delays: Vec<u64> = action.sequence.iter().map(|s| s.delay_ms).collect();
+        assert_eq!(delays, vec![0, 40, 50, 30, 380, 50]);
+    }
+
+    #[test]
+    fn test_auto_repeat_is_collapsed() {
+        let action = record(&[("a", true, 0), ("a", true, 30), ("a", true, 60), ("a", false, 90)]);
+        assert_eq!(keys(&action), vec![("a".to_string(), true), ("a".to_string(), false)]);
+    }
+
+    #[test]
+    fn test_release_without_press_is_ignored() {
+        let action = record(&[("lshift", false, 0), ("x", true, 10), ("x", false, 20)]);
+        assert_eq!(keys(&action), vec![("x".to_string(), true), ("x".to_string(), false)]);
+    }
+
+    #[test]
+    fn test_trailing_stop_modifier_is_filtered() {
+        let action = record(&[("a", true, 0), ("a", false, 50), ("lctrl", true, 900)]);
+        assert_eq!(keys(&action), vec![("a".to_string(), true), ("a".to_string(), false)]);
+    }
+
+    #[test]
+    fn test_held_keys_are_released() {
+        // Modifier re-pressed to stop: the original release is trimmed, so it is re-added
+        let action = record(&[
+            ("lctrl", true, 0),
+            ("k", true, 10),
+            ("k", false, 20),
+            ("lctrl", false, 30),
+            ("lctrl", true, 40),
+        ]);
+        assert_eq!(
+            keys(&action),
+            vec![
+                ("lctrl".to_string(), true),
+                ("k".to_string(), true),
+                ("k".to_string(), false),
+                ("lctrl".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stop_without_start_fails() {
+        assert!(stop().is_err());
+    }
+}
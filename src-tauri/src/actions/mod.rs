@@ -3,24 +3,78 @@
 //! Handles action execution including keyboard, media, launch, HTTP, and more.
 
 pub mod types;
+pub mod auto_repeat;
 pub mod engine;
+pub mod error;
 pub mod event_binder;
+pub mod feedback;
 pub mod handlers;
+pub mod history_store;
+pub mod macro_recorder;
+pub mod stats;
+pub mod template;
 
 // Re-export CancellationToken for use by handlers that support cancellation
 pub use engine::CancellationToken;
 
-use crate::config::types::{HomeAssistantConfig, NodeRedConfig};
+use crate::config::manager::ConfigManager;
+use crate::config::profiles::ProfileManager;
+use crate::config::types::{AppSettings, HomeAssistantConfig, MqttConfig, NodeRedConfig};
+use crate::hid::manager::HidManager;
+use parking_lot::Mutex;
+use std::sync::Arc;
 use types::{Action, ActionResult};
 
 /// Integration configuration for action execution
 ///
-/// Contains optional configuration for integrations like Home Assistant and Node-RED.
-/// When provided, handlers will use these settings instead of environment variables.
+/// Contains optional configuration for integrations like Home Assistant, Node-RED
+/// and MQTT, plus access to the device for actions that control it.
+/// Actions for an integration that isn't configured fail with "not configured".
 #[derive(Debug, Clone, Default)]
 pub struct IntegrationConfig {
     pub home_assistant: Option<HomeAssistantConfig>,
     pub node_red: Option<NodeRedConfig>,
+    pub mqtt: Option<MqttConfig>,
+    pub device: Option<DeviceHandle>,
+    /// Refuse HTTP actions to private network addresses
+    pub block_private_networks: bool,
+    /// Variables captured by earlier actions, for `${...}` references
+    pub variables: template::Variables,
+}
+
+impl IntegrationConfig {
+    /// Integration settings from the app settings
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            home_assistant: settings.home_assistant.clone(),
+            node_red: settings.node_red.clone(),
+            mqtt: settings.mqtt.clone(),
+            device: None,
+            block_private_networks: settings.block_private_networks,
+            variables: template::Variables::new(),
+        }
+    }
+
+    /// Give device-controlling actions (e.g. brightness, workspace switching)
+    /// access to the device
+    pub fn with_device(mut self, device: DeviceHandle) -> Self {
+        self.device = Some(device);
+        self
+    }
+}
+
+/// Shared managers used by actions that change the device itself
+#[derive(Clone)]
+pub struct DeviceHandle {
+    pub hid: Arc<Mutex<HidManager>>,
+    pub config: Arc<Mutex<ConfigManager>>,
+    pub profiles: Arc<Mutex<ProfileManager>>,
+}
+
+impl std::fmt::Debug for DeviceHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeviceHandle").finish_non_exhaustive()
+    }
 }
 
 /// Execute an action standalone (without engine state management)
@@ -28,16 +82,81 @@ pub struct IntegrationConfig {
 /// This is used by the Tauri command to execute actions without holding
 /// a mutex lock across await points.
 pub async fn execute_action_standalone(action: &Action) -> ActionResult {
-    execute_action_with_config(action, &IntegrationConfig::default()).await
+    execute_action_with_config(action, &IntegrationConfig::default(), false).await
 }
 
 /// Execute an action with integration configuration
 ///
-/// Allows passing Home Assistant and Node-RED configuration to handlers
-/// instead of relying on environment variables.
+/// Passes the Home Assistant, Node-RED and MQTT configuration to their handlers.
+/// With `dry_run` set, nothing is executed: the result describes what the
+/// action would do (see `describe_action`).
 pub async fn execute_action_with_config(
     action: &Action,
     integrations: &IntegrationConfig,
+    dry_run: bool,
+) -> ActionResult {
+    if dry_run {
+        return describe_action(action);
+    }
+    execute_action_with_cancellation(action, integrations, &CancellationToken::new()).await
+}
+
+/// Describe what an action would do, without side effects
+///
+/// Each handler's `describe` only inspects the action's configuration: no
+/// input is simulated, no requests are sent and the device is left alone.
+/// Macros describe each step, and conditionals the branch that matches the
+/// current foreground window.
+pub fn describe_action(action: &Action) -> ActionResult {
+    let description = match action {
+        Action::Keyboard(config) => handlers::keyboard::describe(config),
+        Action::Media(config) => handlers::media::describe(config),
+        Action::Launch(config) => handlers::launch::describe(config),
+        #[cfg(feature = "scripting")]
+        Action::Script(config) => handlers::script::describe(config),
+        #[cfg(not(feature = "scripting"))]
+        Action::Script(_) => return feature_disabled("scripting"),
+        #[cfg(feature = "http")]
+        Action::Http(config) => handlers::http::describe(config),
+        #[cfg(not(feature = "http"))]
+        Action::Http(_) => return feature_disabled("http"),
+        Action::System(config) => handlers::system::describe(config),
+        Action::Text(config) => handlers::text::describe(config),
+        Action::Profile(config) => handlers::profile::describe(config),
+        #[cfg(feature = "home_assistant")]
+        Action::HomeAssistant(config) => handlers::home_assistant::describe(config),
+        #[cfg(not(feature = "home_assistant"))]
+        Action::HomeAssistant(_) => return feature_disabled("home_assistant"),
+        #[cfg(feature = "node_red")]
+        Action::NodeRed(config) => handlers::node_red::describe(config),
+        #[cfg(not(feature = "node_red"))]
+        Action::NodeRed(_) => return feature_disabled("node_red"),
+        Action::Workspace(config) => handlers::workspace::describe(config),
+        Action::ReleaseAllKeys(config) => handlers::keyboard::describe_release_all(config),
+        Action::Macro(config) => handlers::macros::describe(config),
+        Action::Conditional(config) => handlers::conditional::describe(config),
+        Action::Brightness(config) => handlers::brightness::describe(config),
+        Action::Notify(config) => handlers::notify::describe(config),
+        Action::Clipboard(config) => handlers::clipboard::describe(config),
+        Action::Delay(config) => handlers::delay::describe(config),
+        Action::ButtonFeedback(config) => handlers::feedback::describe(config),
+        #[cfg(feature = "mqtt")]
+        Action::Mqtt(config) => handlers::mqtt::describe(config),
+        #[cfg(not(feature = "mqtt"))]
+        Action::Mqtt(_) => return feature_disabled("mqtt"),
+    };
+    ActionResult::success_with_message(description, 0)
+}
+
+/// Execute an action with integration configuration and a cancellation token
+///
+/// Handlers that support cancellation (HTTP, Home Assistant between retries,
+/// scripts, delays, and macros between steps) abort as soon as the token is
+/// cancelled; the others run to completion.
+pub async fn execute_action_with_cancellation(
+    action: &Action,
+    integrations: &IntegrationConfig,
+    token: &CancellationToken,
 ) -> ActionResult {
     let start = std::time::Instant::now();
 
@@ -45,26 +164,56 @@ pub async fn execute_action_with_config(
         Action::Keyboard(config) => handlers::keyboard::execute(config).await,
         Action::Media(config) => handlers::media::execute(config).await,
         Action::Launch(config) => handlers::launch::execute(config).await,
-        Action::Script(config) => handlers::script::execute(config).await,
-        Action::Http(config) => handlers::http::execute(config).await,
+        #[cfg(feature = "scripting")]
+        Action::Script(config) => handlers::script::execute(config, token).await,
+        #[cfg(not(feature = "scripting"))]
+        Action::Script(_) => feature_disabled("scripting"),
+        #[cfg(feature = "http")]
+        Action::Http(config) => {
+            handlers::http::execute_with_cancellation(config, integrations.block_private_networks, token).await
+        }
+        #[cfg(not(feature = "http"))]
+        Action::Http(_) => feature_disabled("http"),
         Action::System(config) => handlers::system::execute(config).await,
         Action::Text(config) => handlers::text::execute(config).await,
         Action::Profile(config) => handlers::profile::execute(config).await,
+        #[cfg(feature = "home_assistant")]
         Action::HomeAssistant(config) => {
             handlers::home_assistant::execute_with_config(
                 config,
                 integrations.home_assistant.as_ref(),
+                token,
             ).await
         }
+        #[cfg(not(feature = "home_assistant"))]
+        Action::HomeAssistant(_) => feature_disabled("home_assistant"),
+        #[cfg(feature = "node_red")]
         Action::NodeRed(config) => {
             handlers::node_red::execute_with_config(
                 config,
                 integrations.node_red.as_ref(),
             ).await
         }
+        #[cfg(not(feature = "node_red"))]
+        Action::NodeRed(_) => feature_disabled("node_red"),
         Action::Workspace(config) => {
-            handlers::workspace::execute(config).await
+            handlers::workspace::execute(config, integrations).await
         }
+        Action::ReleaseAllKeys(config) => {
+            handlers::keyboard::execute_release_all(config).await
+        }
+        Action::Macro(config) => handlers::macros::execute(config, integrations, token).await,
+        Action::Conditional(config) => handlers::conditional::execute(config, integrations, token).await,
+        Action::Brightness(config) => handlers::brightness::execute(config, integrations.device.as_ref()).await,
+        Action::Notify(config) => handlers::notify::execute(config).await,
+        Action::Clipboard(config) => handlers::clipboard::execute(config).await,
+        Action::Delay(config) => handlers::delay::execute(config, token).await,
+        // The source runs through this function again, so the call is boxed
+        Action::ButtonFeedback(config) => Box::pin(handlers::feedback::execute(config, integrations, token)).await,
+        #[cfg(feature = "mqtt")]
+        Action::Mqtt(config) => handlers::mqtt::execute_with_config(config, integrations.mqtt.as_ref()).await,
+        #[cfg(not(feature = "mqtt"))]
+        Action::Mqtt(_) => feature_disabled("mqtt"),
     };
 
     let duration = start.elapsed().as_millis() as u64;
@@ -74,3 +223,18 @@ pub async fn execute_action_with_config(
         ..result
     }
 }
+
+/// Result for an action whose handler was compiled out of this build
+#[cfg(not(all(
+    feature = "http",
+    feature = "home_assistant",
+    feature = "node_red",
+    feature = "mqtt",
+    feature = "scripting"
+)))]
+pub(crate) fn feature_disabled(feature: &str) -> ActionResult {
+    ActionResult::from_error(
+        error::ActionError::NotSupportedOnPlatform(format!("Action not available: feature not enabled ({})", feature)),
+        0,
+    )
+}
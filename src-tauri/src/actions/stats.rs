@@ -0,0 +1,358 @@
+//! Usage Statistics
+//!
+//! Counts how often the action of each button and encoder runs, per profile,
+//! along with when it last ran, so layouts can be tuned to how they're used.
+//!
+//! Counts are kept in memory and saved to a JSON file in the app data
+//! directory by a background writer thread. A change only marks the counts
+//! dirty; the writer saves them once no further change has come in for a
+//! while, on `flush`, and on drop, so held buttons and fast encoder turns
+//! don't rewrite the file for every action.
+
+use super::event_binder::encoder_index;
+use crate::config::storage::{read_json, write_atomic};
+use crate::hid::types::DeviceEvent;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Statistics file name in the app data directory
+pub const STATS_FILE: &str = "usage_stats.json";
+
+/// How long changed counts may wait before being saved
+const FLUSH_INTERVAL_MS: u64 = 2000;
+
+/// Kind of control an action is bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InputKind {
+    Button,
+    Encoder,
+}
+
+/// Usage of one control in a profile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputUsage {
+    pub kind: InputKind,
+    /// Button index, or encoder index (Main=0, Side1=1, Side2=2)
+    pub index: usize,
+    /// Times the control's action ran
+    pub count: u64,
+    /// When the action last ran (milliseconds since epoch)
+    pub last_used: u64,
+}
+
+/// Usage per profile ID
+type UsageData = HashMap<String, Vec<InputUsage>>;
+
+/// Control that produced a device event, if any
+pub fn input_for_event(event: &DeviceEvent) -> Option<(InputKind, usize)> {
+    match event {
+        DeviceEvent::Button { index, .. } => Some((InputKind::Button, *index as usize)),
+        DeviceEvent::Encoder { encoder_type, .. } => Some((InputKind::Encoder, encoder_index(*encoder_type))),
+        DeviceEvent::Unknown { .. } => None,
+    }
+}
+
+/// Requests handled by the writer thread
+enum Command {
+    /// Counts changed; save them once things settle
+    Changed,
+    /// Save now, then acknowledge
+    Flush(Sender<()>),
+}
+
+/// Per-profile usage counts with a background writer
+pub struct StatsStore {
+    path: PathBuf,
+    data: Arc<Mutex<UsageData>>,
+    tx: Option<Sender<Command>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl StatsStore {
+    /// Open the statistics file at `path`, starting from the counts in it
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create statistics directory: {}", e))?;
+        }
+        let data = Arc::new(Mutex::new(read_json::<UsageData>(&path).unwrap_or_default()));
+
+        let (tx, rx) = mpsc::channel();
+        let writer = Writer {
+            path: path.clone(),
+            data: Arc::clone(&data),
+        };
+        let handle = std::thread::Builder::new()
+            .name("stats-writer".to_string())
+            .spawn(move || writer.run(rx))
+            .map_err(|e| format!("Failed to start statistics writer: {}", e))?;
+
+        Ok(Self {
+            path,
+            data,
+            tx: Some(tx),
+            writer: Some(handle),
+        })
+    }
+
+    /// Path of the statistics file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Count one run of a control's action in a profile
+    pub fn record(&self, profile_id: &str, kind: InputKind, index: usize) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        self.record_at(profile_id, kind, index, now);
+    }
+
+    fn record_at(&self, profile_id: &str, kind: InputKind, index: usize, timestamp: u64) {
+        {
+            let mut data = self.data.lock();
+            let inputs = data.entry(profile_id.to_string()).or_default();
+            match inputs.iter_mut().find(|usage| usage.kind == kind && usage.index == index) {
+                Some(usage) => {
+                    usage.count += 1;
+                    usage.last_used = timestamp;
+                }
+                None => inputs.push(InputUsage {
+                    kind,
+                    index,
+                    count: 1,
+                    last_used: timestamp,
+                }),
+            }
+        }
+        self.changed();
+    }
+
+    /// Usage of a profile's controls, most used first
+    pub fn profile_stats(&self, profile_id: &str) -> Vec<InputUsage> {
+        let mut inputs = self.data.lock().get(profile_id).cloned().unwrap_or_default();
+        inputs.sort_by(|a, b| b.count.cmp(&a.count).then(a.kind.cmp(&b.kind)).then(a.index.cmp(&b.index)));
+        inputs
+    }
+
+    /// Forget the counts of one profile, or of all profiles when `None`
+    pub fn reset(&self, profile_id: Option<&str>) {
+        {
+            let mut data = self.data.lock();
+            match profile_id {
+                Some(id) => {
+                    data.remove(id);
+                }
+                None => data.clear(),
+            }
+        }
+        self.changed();
+    }
+
+    /// Save the current counts
+    pub fn flush(&self) {
+        let (ack_tx, ack_rx) = mpsc::channel();
+        if let Some(tx) = &self.tx {
+            if tx.send(Command::Flush(ack_tx)).is_ok() {
+                let _ = ack_rx.recv();
+            }
+        }
+    }
+
+    fn changed(&self) {
+        if let Some(tx) = &self.tx {
+            if tx.send(Command::Changed).is_err() {
+                log::warn!("Statistics writer stopped; counts not saved");
+            }
+        }
+    }
+}
+
+impl Drop for StatsStore {
+    fn drop(&mut self) {
+        // Closing the channel makes the writer save and exit
+        self.tx.take();
+        if let Some(handle) = self.writer.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl std::fmt::Debug for StatsStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StatsStore").field("path", &self.path).finish()
+    }
+}
+
+/// Background side of the store; saves the shared counts
+struct Writer {
+    path: PathBuf,
+    data: Arc<Mutex<UsageData>>,
+}
+
+impl Writer {
+    fn run(&self, rx: mpsc::Receiver<Command>) {
+        let mut dirty = false;
+        loop {
+            let command = if dirty {
+                rx.recv_timeout(Duration::from_millis(FLUSH_INTERVAL_MS))
+            } else {
+                rx.recv().map_err(|_| RecvTimeoutError::Disconnected)
+            };
+
+            match command {
+                Ok(Command::Changed) => dirty = true,
+                Ok(Command::Flush(ack)) => {
+                    if dirty {
+                        self.save();
+                        dirty = false;
+                    }
+                    let _ = ack.send(());
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    self.save();
+                    dirty = false;
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    if dirty {
+                        self.save();
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    fn save(&self) {
+        // Serialize under the lock, write without it
+        let json = serde_json::to_vec_pretty(&*self.data.lock());
+        let result = json
+            .map_err(|e| e.to_string())
+            .and_then(|json| write_atomic(&self.path, &json).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            log::warn!("Failed to save usage statistics: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hid::types::{ButtonEventType, ButtonType, EncoderEventType, EncoderType};
+    use tempfile::TempDir;
+
+    fn open(dir: &TempDir) -> StatsStore {
+        StatsStore::open(dir.path().join(STATS_FILE)).unwrap()
+    }
+
+    // ========== Counting Tests ==========
+
+    #[test]
+    fn test_record_counts_and_last_used() {
+        let dir = TempDir::new().unwrap();
+        let store = open(&dir);
+        store.record_at("p1", InputKind::Button, 2, 100);
+        store.record_at("p1", InputKind::Button, 2, 250);
+        store.record_at("p1", InputKind::Encoder, 0, 180);
+        store.record_at("p2", InputKind::Button, 2, 300);
+
+        assert_eq!(
+            store.profile_stats("p1"),
+            vec![
+                InputUsage { kind: InputKind::Button, index: 2, count: 2, last_used: 250 },
+                InputUsage { kind: InputKind::Encoder, index: 0, count: 1, last_used: 180 },
+            ]
+        );
+        assert_eq!(store.profile_stats("p2").len(), 1);
+        assert!(store.profile_stats("unknown").is_empty());
+    }
+
+    #[test]
+    fn test_stats_sorted_most_used_first() {
+        let dir = TempDir::new().unwrap();
+        let store = open(&dir);
+        store.record_at("p1", InputKind::Button, 0, 1);
+        for ts in 0..3 {
+            store.record_at("p1", InputKind::Button, 5, ts);
+        }
+
+        let indexes: Vec<usize> = store.profile_stats("p1").iter().map(|usage| usage.index).collect();
+        assert_eq!(indexes, vec![5, 0]);
+    }
+
+    #[test]
+    fn test_reset_one_profile_or_all() {
+        let dir = TempDir::new().unwrap();
+        let store = open(&dir);
+        store.record_at("p1", InputKind::Button, 0, 1);
+        store.record_at("p2", InputKind::Button, 0, 1);
+
+        store.reset(Some("p1"));
+        assert!(store.profile_stats("p1").is_empty());
+        assert_eq!(store.profile_stats("p2").len(), 1);
+
+        store.reset(None);
+        assert!(store.profile_stats("p2").is_empty());
+    }
+
+    // ========== Persistence Tests ==========
+
+    #[test]
+    fn test_counts_survive_reopen() {
+        let dir = TempDir::new().unwrap();
+        let store = open(&dir);
+        store.record_at("p1", InputKind::Encoder, 1, 42);
+        drop(store);
+
+        let store = open(&dir);
+        assert_eq!(
+            store.profile_stats("p1"),
+            vec![InputUsage { kind: InputKind::Encoder, index: 1, count: 1, last_used: 42 }]
+        );
+    }
+
+    #[test]
+    fn test_writes_are_batched() {
+        let dir = TempDir::new().unwrap();
+        let store = open(&dir);
+        for ts in 0..50 {
+            store.record_at("p1", InputKind::Button, 0, ts);
+        }
+        // Nothing is written until the writer settles or is flushed
+        assert!(!store.path().exists());
+
+        store.flush();
+        let saved: UsageData = read_json(store.path()).unwrap();
+        assert_eq!(saved["p1"][0].count, 50);
+    }
+
+    // ========== Event Mapping Tests ==========
+
+    #[test]
+    fn test_input_for_event() {
+        let button = DeviceEvent::Button {
+            index: 4,
+            button_type: ButtonType::Lcd,
+            event_type: ButtonEventType::Press,
+        };
+        let encoder = DeviceEvent::Encoder {
+            encoder_type: EncoderType::Side2,
+            event_type: EncoderEventType::RotateCW,
+            velocity: 1,
+        };
+        let unknown = DeviceEvent::Unknown { event_id: 0xAB, state: 1 };
+
+        assert_eq!(input_for_event(&button), Some((InputKind::Button, 4)));
+        assert_eq!(input_for_event(&encoder), Some((InputKind::Encoder, 2)));
+        assert_eq!(input_for_event(&unknown), None);
+    }
+}
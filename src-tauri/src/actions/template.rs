@@ -0,0 +1,144 @@
+//! Action Templates
+//!
+//! Substitutes `${name.path}` references in an action's string fields with
+//! values captured by earlier actions (see `HttpCapture`). The path after the
+//! variable name walks into the captured JSON: object keys by name, array
+//! elements by index, e.g. `${weather.body.current.temp}` or
+//! `${list.body.items.0.id}`. References that don't resolve are left as-is.
+
+use super::types::Action;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Captured variables, by name
+pub type Variables = HashMap<String, Value>;
+
+/// Replace every resolvable `${...}` reference in a string
+pub fn render(text: &str, variables: &Variables) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        let expr = &rest[start + 2..start + 2 + len];
+        out.push_str(&rest[..start]);
+        match lookup(expr, variables) {
+            Some(value) => out.push_str(&value_to_string(value)),
+            None => out.push_str(&rest[start..start + 3 + len]),
+        }
+        rest = &rest[start + 3 + len..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Copy of an action with references in all of its string fields replaced
+pub fn render_action(action: &Action, variables: &Variables) -> Result<Action, String> {
+    if variables.is_empty() {
+        return Ok(action.clone());
+    }
+
+    let mut value = serde_json::to_value(action).map_err(|e| format!("Failed to read action: {}", e))?;
+    render_value(&mut value, variables);
+    serde_json::from_value(value).map_err(|e| format!("Templated action is invalid: {}", e))
+}
+
+fn render_value(value: &mut Value, variables: &Variables) {
+    match value {
+        Value::String(s) if s.contains("${") => *s = render(s, variables),
+        Value::Array(items) => items.iter_mut().for_each(|item| render_value(item, variables)),
+        Value::Object(fields) => fields.values_mut().for_each(|field| render_value(field, variables)),
+        _ => {}
+    }
+}
+
+/// Resolve `name.path.to.value` against the variables
+fn lookup<'a>(expr: &str, variables: &'a Variables) -> Option<&'a Value> {
+    let mut parts = expr.trim().split('.');
+    let root = variables.get(parts.next()?)?;
+    parts.try_fold(root, |value, key| match value {
+        Value::Object(fields) => fields.get(key),
+        Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => None,
+    })
+}
+
+/// Strings are inserted without quotes; other values as JSON
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::types::TextAction;
+    use serde_json::json;
+
+    fn variables() -> Variables {
+        let mut vars = Variables::new();
+        vars.insert(
+            "weather".to_string(),
+            json!({"status": 200, "body": {"current": {"temp": 21.5, "summary": "Sunny"}, "days": ["mon", "tue"]}}),
+        );
+        vars
+    }
+
+    // ========== Render Tests ==========
+
+    #[test]
+    fn test_render_nested_paths() {
+        let vars = variables();
+        assert_eq!(render("It is ${weather.body.current.summary}", &vars), "It is Sunny");
+        assert_eq!(render("${weather.body.current.temp}C", &vars), "21.5C");
+        assert_eq!(render("${weather.status}", &vars), "200");
+        assert_eq!(render("${weather.body.days.1}", &vars), "tue");
+    }
+
+    #[test]
+    fn test_render_whole_value_as_json() {
+        let vars = variables();
+        assert_eq!(render("${weather.body.days}", &vars), r#"["mon","tue"]"#);
+    }
+
+    #[test]
+    fn test_render_leaves_unknown_references() {
+        let vars = variables();
+        assert_eq!(render("${missing.x} ${weather.body.nope}", &vars), "${missing.x} ${weather.body.nope}");
+        assert_eq!(render("cost: $5 and ${unterminated", &vars), "cost: $5 and ${unterminated");
+    }
+
+    #[test]
+    fn test_render_multiple_references() {
+        let vars = variables();
+        assert_eq!(
+            render("${weather.body.days.0}-${weather.body.days.1}", &vars),
+            "mon-tue"
+        );
+    }
+
+    // ========== Action Tests ==========
+
+    #[test]
+    fn test_render_action_string_fields() {
+        let action = Action::Text(TextAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            text: "Now ${weather.body.current.temp} degrees".to_string(),
+            type_delay: None,
+            delay_ms: None,
+        });
+
+        match render_action(&action, &variables()).unwrap() {
+            Action::Text(ta) => assert_eq!(ta.text, "Now 21.5 degrees"),
+            other => panic!("Unexpected action: {:?}", other),
+        }
+    }
+}
@@ -2,8 +2,11 @@
 //!
 //! Contains all action types and their configurations.
 
+use super::error::{ActionError, ActionErrorCode};
+use crate::image::text::TextStyle;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 
 /// Available action types
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -20,6 +23,7 @@ pub enum ActionType {
     Workspace,
     HomeAssistant,
     NodeRed,
+    ReleaseAllKeys,
 }
 
 /// Keyboard action configuration
@@ -43,10 +47,26 @@ pub struct KeyboardAction {
     pub modifiers: Vec<String>,
     #[serde(default)]
     pub hold_duration: Option<u64>,
+    /// Recorded key events to replay instead of `keys`/`modifiers` when non-empty
+    #[serde(default)]
+    pub sequence: Vec<KeyStep>,
+}
+
+/// A single key event in a recorded keyboard sequence
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyStep {
+    /// Key name as accepted by the keyboard handler (e.g. "a", "lctrl", "f5")
+    pub key: String,
+    /// True for key down, false for key up
+    pub down: bool,
+    /// Delay before this event, relative to the previous one
+    #[serde(default)]
+    pub delay_ms: u64,
 }
 
 /// Media action configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MediaAction {
     // Common action fields from frontend BaseAction
@@ -62,11 +82,15 @@ pub struct MediaAction {
     pub action: MediaActionType,
     #[serde(default)]
     pub volume_amount: Option<u32>,
+    /// Output device for volume/mute actions (from `list_audio_devices`); system default if None
+    #[serde(default)]
+    pub device_id: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum MediaActionType {
+    #[default]
     PlayPause,
     #[serde(alias = "nextTrack")]
     Next,
@@ -100,6 +124,11 @@ pub struct LaunchAction {
     pub working_directory: Option<String>,
     #[serde(default)]
     pub use_shell: Option<bool>,
+    /// Focus the application's existing window instead of starting another
+    /// instance (Windows only). Matched by the executable named in `path`, so
+    /// it doesn't apply to URLs or when `use_shell` is set.
+    #[serde(default)]
+    pub single_instance: Option<bool>,
 }
 
 /// Script action configuration
@@ -123,13 +152,19 @@ pub struct ScriptAction {
     /// Legacy field name for script content
     #[serde(default)]
     pub content: Option<String>,
-    /// Path to script file
+    /// Path to script file (only used for `ScriptType::File`)
     #[serde(default)]
     pub script_path: Option<String>,
     #[serde(default)]
     pub timeout: Option<u64>,
     #[serde(default)]
     pub timeout_ms: Option<u64>,
+    /// Extra environment variables for the script
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Directory the script runs in
+    #[serde(default)]
+    pub working_directory: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -159,6 +194,7 @@ pub struct HttpAction {
     pub url: String,
     #[serde(default)]
     pub headers: HashMap<String, String>,
+    /// Body encoding: "json" (default), "form", "text" or "none"
     #[serde(default)]
     pub body_type: Option<String>,
     #[serde(default)]
@@ -167,6 +203,25 @@ pub struct HttpAction {
     pub timeout: Option<u64>,
     #[serde(default)]
     pub timeout_ms: Option<u64>,
+    /// Store the response for later actions in a macro
+    #[serde(default)]
+    pub capture: Option<HttpCapture>,
+    /// Extra attempts after a connection error, 5xx or 429 (default 0)
+    #[serde(default)]
+    pub retries: Option<u32>,
+    /// Wait before the first retry, doubled for each further one (milliseconds)
+    #[serde(default)]
+    pub retry_backoff_ms: Option<u64>,
+}
+
+/// Where to store an HTTP response
+///
+/// The variable holds `{"status": <code>, "body": <JSON or text>}`, which
+/// later macro steps reference as `${variable.body.some.field}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpCapture {
+    pub variable: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -307,6 +362,124 @@ pub struct WorkspaceAction {
     pub workspace_index: Option<usize>,
 }
 
+/// Release-all-keys action configuration - releases any stuck modifier keys
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseAllKeysAction {
+    // Common action fields from frontend BaseAction
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
+/// One step of a macro: an action and an optional pause after it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroStep {
+    pub action: Action,
+    /// Wait this long before the next step (milliseconds)
+    #[serde(default)]
+    pub delay_after_ms: Option<u64>,
+}
+
+/// Macro action configuration - runs several actions in order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroAction {
+    // Common action fields from frontend BaseAction
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+
+    /// Steps run in order
+    #[serde(default)]
+    pub steps: Vec<MacroStep>,
+    /// Skip the remaining steps after the first failure
+    #[serde(default)]
+    pub stop_on_error: bool,
+}
+
+/// Foreground window test for a conditional branch
+///
+/// Every field that is set must match. A match with no fields set never matches.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowMatch {
+    /// Substring of the window title (case-insensitive)
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Executable name of the owning process, with or without ".exe" (case-insensitive)
+    #[serde(default)]
+    pub process: Option<String>,
+}
+
+impl WindowMatch {
+    /// Whether a window with this title and process matches
+    pub fn matches(&self, title: &str, process: Option<&str>) -> bool {
+        if self.title.is_none() && self.process.is_none() {
+            return false;
+        }
+
+        let title_ok = self
+            .title
+            .as_ref()
+            .is_none_or(|want| title.to_lowercase().contains(&want.to_lowercase()));
+        let process_ok = self.process.as_ref().is_none_or(|want| {
+            process.is_some_and(|have| strip_exe(have).eq_ignore_ascii_case(strip_exe(want)))
+        });
+        title_ok && process_ok
+    }
+}
+
+fn strip_exe(name: &str) -> &str {
+    let len = name.len();
+    if len > 4 && name.is_char_boundary(len - 4) && name[len - 4..].eq_ignore_ascii_case(".exe") {
+        &name[..len - 4]
+    } else {
+        name
+    }
+}
+
+/// One branch of a conditional action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConditionalBranch {
+    pub when: WindowMatch,
+    pub then: Action,
+}
+
+/// Conditional action configuration - picks an action by the foreground window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConditionalAction {
+    // Common action fields from frontend BaseAction
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+
+    /// Branches checked in order; the first match runs
+    #[serde(default)]
+    pub branches: Vec<ConditionalBranch>,
+    /// Action run when no branch matches
+    #[serde(default)]
+    pub default: Option<Box<Action>>,
+}
+
 /// Home Assistant action configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -327,6 +500,9 @@ pub struct HomeAssistantAction {
     pub entity_id: String,
     #[serde(default)]
     pub brightness: Option<u8>,
+    /// Brightness change (0-255 scale) for `set_brightness_relative`
+    #[serde(default)]
+    pub brightness_delta: Option<i16>,
     #[serde(default)]
     pub custom_service: Option<HomeAssistantCustomService>,
     // Legacy fields
@@ -353,6 +529,8 @@ pub enum HomeAssistantOperationType {
     TurnOn,
     TurnOff,
     SetBrightness,
+    /// Adjust a light's current brightness by `brightness_delta`
+    SetBrightnessRelative,
     RunScript,
     TriggerAutomation,
     Custom,
@@ -402,6 +580,182 @@ pub enum NodeRedOperationType {
     Custom,
 }
 
+/// MQTT publish action configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MqttAction {
+    // Common action fields from frontend BaseAction
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+
+    /// Topic to publish to
+    pub topic: String,
+    /// Message payload; strings are sent as-is, other values as JSON
+    #[serde(default)]
+    pub payload: Option<serde_json::Value>,
+    /// Quality of service level (0, 1 or 2)
+    #[serde(default)]
+    pub qos: u8,
+    /// Ask the broker to keep the message for new subscribers
+    #[serde(default)]
+    pub retain: bool,
+}
+
+/// Brightness action configuration - changes the device display brightness
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrightnessAction {
+    // Common action fields from frontend BaseAction
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+
+    pub operation: BrightnessOperation,
+    /// Amount to change by, or the new level for `set` (0-100)
+    pub step: u8,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BrightnessOperation {
+    Set,
+    Increase,
+    Decrease,
+}
+
+/// Notify action configuration - shows a desktop notification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotifyAction {
+    // Common action fields from frontend BaseAction
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    /// How long the notification stays up; None uses the OS default
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+}
+
+/// Clipboard action configuration - sets, reads, pastes or clears the clipboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipboardAction {
+    // Common action fields from frontend BaseAction
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+
+    pub operation: ClipboardOperation,
+    /// Text for `set_text` and `paste`
+    #[serde(default)]
+    pub text: String,
+    /// Put the previous clipboard text back after a `paste`
+    #[serde(default)]
+    pub restore: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardOperation {
+    SetText,
+    Get,
+    Paste,
+    Clear,
+}
+
+/// Delay action configuration - waits before the next action in a chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DelayAction {
+    // Common action fields from frontend BaseAction
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+
+    /// How long to wait, in milliseconds
+    pub ms: u64,
+}
+
+/// Button feedback action configuration - shows a polled value on a button
+///
+/// While the button's workspace is shown, `source` is run every `interval_ms`
+/// and its output (or `text`, rendered with the variables it captured) is
+/// drawn on `button_index`. Pressing the button refreshes it immediately.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedbackAction {
+    // Common action fields from frontend BaseAction
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+
+    /// LCD button the value is shown on (0-based)
+    pub button_index: u8,
+    /// Action run for the value, typically a script or HTTP request
+    pub source: Box<Action>,
+    /// Text shown instead of the source's output, with `${...}` references
+    /// to the variables it captured (e.g. `${cpu.body.load}%`)
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Time between refreshes (milliseconds)
+    #[serde(default = "default_feedback_interval_ms")]
+    pub interval_ms: u64,
+    #[serde(default)]
+    pub style: TextStyle,
+}
+
+/// Default time between feedback refreshes
+pub const DEFAULT_FEEDBACK_INTERVAL_MS: u64 = 5000;
+
+/// Lower bound on the feedback refresh interval, so sources aren't hammered
+pub const MIN_FEEDBACK_INTERVAL_MS: u64 = 1000;
+
+fn default_feedback_interval_ms() -> u64 {
+    DEFAULT_FEEDBACK_INTERVAL_MS
+}
+
+impl FeedbackAction {
+    /// Time between refreshes, clamped to `MIN_FEEDBACK_INTERVAL_MS`
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(self.interval_ms.max(MIN_FEEDBACK_INTERVAL_MS))
+    }
+}
+
 /// Unified action configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -419,6 +773,17 @@ pub enum Action {
     HomeAssistant(HomeAssistantAction),
     #[serde(alias = "nodeRed")]
     NodeRed(NodeRedAction),
+    #[serde(alias = "releaseAllKeys")]
+    ReleaseAllKeys(ReleaseAllKeysAction),
+    Macro(MacroAction),
+    Conditional(ConditionalAction),
+    Mqtt(MqttAction),
+    Brightness(BrightnessAction),
+    Notify(NotifyAction),
+    Clipboard(ClipboardAction),
+    Delay(DelayAction),
+    #[serde(alias = "buttonFeedback")]
+    ButtonFeedback(FeedbackAction),
 }
 
 /// Result of action execution
@@ -430,7 +795,16 @@ pub struct ActionResult {
     pub message: Option<String>,
     #[serde(default)]
     pub error: Option<String>,
+    /// Category of the failure, for the frontend to act on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<ActionErrorCode>,
     pub duration_ms: u64,
+    /// Optional per-step timing breakdown in milliseconds (e.g. "send", "body")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<HashMap<String, u64>>,
+    /// Variables captured by this action, by name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variables: Option<HashMap<String, serde_json::Value>>,
 }
 
 impl ActionResult {
@@ -439,7 +813,10 @@ impl ActionResult {
             success: true,
             message: None,
             error: None,
+            error_code: None,
             duration_ms,
+            timings: None,
+            variables: None,
         }
     }
 
@@ -448,16 +825,44 @@ impl ActionResult {
             success: true,
             message: Some(message),
             error: None,
+            error_code: None,
             duration_ms,
+            timings: None,
+            variables: None,
         }
     }
 
+    /// Uncategorized failure (code `failed`)
     pub fn failure(error: String, duration_ms: u64) -> Self {
+        Self::from_error(ActionError::Failed(error), duration_ms)
+    }
+
+    /// Failure with the error's message and code
+    pub fn from_error(error: ActionError, duration_ms: u64) -> Self {
         Self {
             success: false,
             message: None,
-            error: Some(error),
+            error_code: Some(error.code()),
+            error: Some(error.to_string()),
             duration_ms,
+            timings: None,
+            variables: None,
+        }
+    }
+
+    /// Attach a per-step timing breakdown
+    pub fn with_timings(self, timings: HashMap<String, u64>) -> Self {
+        Self {
+            timings: Some(timings),
+            ..self
+        }
+    }
+
+    /// Attach captured variables
+    pub fn with_variables(self, variables: HashMap<String, serde_json::Value>) -> Self {
+        Self {
+            variables: Some(variables),
+            ..self
         }
     }
 }
@@ -483,6 +888,7 @@ mod tests {
             (ActionType::Profile, "\"profile\""),
             (ActionType::HomeAssistant, "\"homeAssistant\""),
             (ActionType::NodeRed, "\"nodeRed\""),
+            (ActionType::ReleaseAllKeys, "\"releaseAllKeys\""),
         ];
         for (action_type, expected) in variants {
             let json = serde_json::to_string(&action_type).unwrap();
@@ -509,11 +915,17 @@ mod tests {
     #[test]
     fn test_keyboard_action_serializes() {
         let action = KeyboardAction {
-            key: "a".to_string(),
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            keys: "a".to_string(),
             modifiers: vec!["ctrl".to_string(), "shift".to_string()],
+            hold_duration: None,
+            sequence: Vec::new(),
         };
         let json = serde_json::to_string(&action).unwrap();
-        assert!(json.contains("\"key\":\"a\""));
+        assert!(json.contains("\"keys\":\"a\""));
         assert!(json.contains("\"modifiers\":[\"ctrl\",\"shift\"]"));
     }
 
@@ -521,7 +933,7 @@ mod tests {
     fn test_keyboard_action_empty_modifiers_default() {
         let json = r#"{"key":"enter"}"#;
         let action: KeyboardAction = serde_json::from_str(json).unwrap();
-        assert_eq!(action.key, "enter");
+        assert_eq!(action.keys, "enter");
         assert!(action.modifiers.is_empty());
     }
 
@@ -532,12 +944,12 @@ mod tests {
     #[test]
     fn test_media_action_type_all_variants_serialize() {
         let variants = [
-            (MediaActionType::PlayPause, "\"playPause\""),
-            (MediaActionType::NextTrack, "\"nextTrack\""),
-            (MediaActionType::PreviousTrack, "\"previousTrack\""),
-            (MediaActionType::VolumeUp, "\"volumeUp\""),
-            (MediaActionType::VolumeDown, "\"volumeDown\""),
-            (MediaActionType::VolumeMute, "\"volumeMute\""),
+            (MediaActionType::PlayPause, "\"play_pause\""),
+            (MediaActionType::Next, "\"next\""),
+            (MediaActionType::Previous, "\"previous\""),
+            (MediaActionType::VolumeUp, "\"volume_up\""),
+            (MediaActionType::VolumeDown, "\"volume_down\""),
+            (MediaActionType::Mute, "\"mute\""),
             (MediaActionType::Stop, "\"stop\""),
         ];
         for (action_type, expected) in variants {
@@ -550,9 +962,20 @@ mod tests {
     fn test_media_action_serializes() {
         let action = MediaAction {
             action: MediaActionType::PlayPause,
+            ..Default::default()
         };
         let json = serde_json::to_string(&action).unwrap();
-        assert!(json.contains("\"action\":\"playPause\""));
+        assert!(json.contains("\"action\":\"play_pause\""));
+    }
+
+    #[test]
+    fn test_media_action_device_id() {
+        let json = r#"{"action":"volume_up","deviceId":"{0.0.0.00000000}.{abc}"}"#;
+        let action: MediaAction = serde_json::from_str(json).unwrap();
+        assert_eq!(action.device_id.as_deref(), Some("{0.0.0.00000000}.{abc}"));
+
+        let action: MediaAction = serde_json::from_str(r#"{"action":"mute"}"#).unwrap();
+        assert!(action.device_id.is_none());
     }
 
     // ==========================================================================
@@ -562,9 +985,15 @@ mod tests {
     #[test]
     fn test_launch_action_serializes() {
         let action = LaunchAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
             path: "/usr/bin/code".to_string(),
             args: vec!["--new-window".to_string()],
             working_directory: Some("/home/user".to_string()),
+            use_shell: None,
+            single_instance: None,
         };
         let json = serde_json::to_string(&action).unwrap();
         assert!(json.contains("\"path\":\"/usr/bin/code\""));
@@ -602,9 +1031,18 @@ mod tests {
     #[test]
     fn test_script_action_serializes() {
         let action = ScriptAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
             script_type: ScriptType::PowerShell,
-            content: "Get-Process".to_string(),
+            script: None,
+            content: Some("Get-Process".to_string()),
+            script_path: None,
+            timeout: None,
             timeout_ms: Some(5000),
+            env: HashMap::new(),
+            working_directory: None,
         };
         let json = serde_json::to_string(&action).unwrap();
         assert!(json.contains("\"scriptType\":\"powerShell\""));
@@ -645,11 +1083,20 @@ mod tests {
         let mut headers = HashMap::new();
         headers.insert("Content-Type".to_string(), "application/json".to_string());
         let action = HttpAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
             method: HttpMethod::Post,
             url: "https://api.example.com/data".to_string(),
             headers,
-            body: Some(r#"{"key":"value"}"#.to_string()),
+            body_type: None,
+            body: Some(serde_json::json!({"key": "value"})),
+            timeout: None,
             timeout_ms: Some(10000),
+            capture: None,
+            retries: None,
+            retry_backoff_ms: None,
         };
         let json = serde_json::to_string(&action).unwrap();
         assert!(json.contains("\"method\":\"POST\""));
@@ -704,7 +1151,12 @@ mod tests {
     #[test]
     fn test_text_action_serializes() {
         let action = TextAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
             text: "Hello, World!".to_string(),
+            type_delay: None,
             delay_ms: Some(50),
         };
         let json = serde_json::to_string(&action).unwrap();
@@ -727,6 +1179,10 @@ mod tests {
     #[test]
     fn test_profile_action_by_id() {
         let action = ProfileAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
             profile_id: Some("uuid-123".to_string()),
             profile_name: None,
         };
@@ -737,6 +1193,10 @@ mod tests {
     #[test]
     fn test_profile_action_by_name() {
         let action = ProfileAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
             profile_id: None,
             profile_name: Some("Gaming".to_string()),
         };
@@ -751,11 +1211,11 @@ mod tests {
     #[test]
     fn test_home_assistant_action_type_all_variants() {
         let variants = [
-            (HomeAssistantActionType::CallService, "\"callService\""),
+            (HomeAssistantActionType::CallService, "\"call_service\""),
             (HomeAssistantActionType::Toggle, "\"toggle\""),
-            (HomeAssistantActionType::TurnOn, "\"turnOn\""),
-            (HomeAssistantActionType::TurnOff, "\"turnOff\""),
-            (HomeAssistantActionType::FireEvent, "\"fireEvent\""),
+            (HomeAssistantActionType::TurnOn, "\"turn_on\""),
+            (HomeAssistantActionType::TurnOff, "\"turn_off\""),
+            (HomeAssistantActionType::FireEvent, "\"fire_event\""),
         ];
         for (action_type, expected) in variants {
             let json = serde_json::to_string(&action_type).unwrap();
@@ -766,13 +1226,20 @@ mod tests {
     #[test]
     fn test_home_assistant_action_serializes() {
         let action = HomeAssistantAction {
-            action_type: HomeAssistantActionType::Toggle,
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            operation: HomeAssistantActionType::Toggle,
             entity_id: "light.living_room".to_string(),
+            brightness: None,
+            brightness_delta: None,
+            custom_service: None,
             service: None,
             service_data: None,
         };
         let json = serde_json::to_string(&action).unwrap();
-        assert!(json.contains("\"actionType\":\"toggle\""));
+        assert!(json.contains("\"operation\":\"toggle\""));
         assert!(json.contains("\"entityId\":\"light.living_room\""));
     }
 
@@ -783,8 +1250,15 @@ mod tests {
     #[test]
     fn test_node_red_action_serializes() {
         let action = NodeRedAction {
-            flow_id: "flow-123".to_string(),
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            operation: NodeRedOperationType::TriggerFlow,
+            endpoint: "/flow-123".to_string(),
+            event_name: None,
             payload: Some(serde_json::json!({"message": "hello"})),
+            flow_id: Some("flow-123".to_string()),
         };
         let json = serde_json::to_string(&action).unwrap();
         assert!(json.contains("\"flowId\":\"flow-123\""));
@@ -793,9 +1267,9 @@ mod tests {
 
     #[test]
     fn test_node_red_action_defaults() {
-        let json = r#"{"flowId":"test"}"#;
+        let json = r#"{"operation":"trigger_flow","endpoint":"/test","flowId":"test"}"#;
         let action: NodeRedAction = serde_json::from_str(json).unwrap();
-        assert_eq!(action.flow_id, "test");
+        assert_eq!(action.flow_id, Some("test".to_string()));
         assert!(action.payload.is_none());
     }
 
@@ -806,18 +1280,25 @@ mod tests {
     #[test]
     fn test_action_keyboard_serializes_with_tag() {
         let action = Action::Keyboard(KeyboardAction {
-            key: "space".to_string(),
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            keys: "space".to_string(),
             modifiers: vec![],
+            hold_duration: None,
+            sequence: Vec::new(),
         });
         let json = serde_json::to_string(&action).unwrap();
         assert!(json.contains("\"type\":\"keyboard\""));
-        assert!(json.contains("\"key\":\"space\""));
+        assert!(json.contains("\"keys\":\"space\""));
     }
 
     #[test]
     fn test_action_media_serializes_with_tag() {
         let action = Action::Media(MediaAction {
             action: MediaActionType::PlayPause,
+            ..Default::default()
         });
         let json = serde_json::to_string(&action).unwrap();
         assert!(json.contains("\"type\":\"media\""));
@@ -846,12 +1327,104 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_action_release_all_keys_round_trips() {
+        let action = Action::ReleaseAllKeys(ReleaseAllKeysAction::default());
+        let json = serde_json::to_string(&action).unwrap();
+        assert!(json.contains("\"type\":\"release_all_keys\""));
+
+        let parsed: Action = serde_json::from_str(r#"{"type":"releaseAllKeys"}"#).unwrap();
+        assert!(matches!(parsed, Action::ReleaseAllKeys(_)));
+    }
+
+    #[test]
+    fn test_action_macro_deserialize() {
+        let json = r#"{
+            "type": "macro",
+            "stopOnError": true,
+            "steps": [
+                {"action": {"type": "launch", "path": "notepad.exe"}, "delayAfterMs": 2000},
+                {"action": {"type": "keyboard", "keys": "enter"}}
+            ]
+        }"#;
+        let action: Action = serde_json::from_str(json).unwrap();
+        match action {
+            Action::Macro(ma) => {
+                assert!(ma.stop_on_error);
+                assert_eq!(ma.steps.len(), 2);
+                assert_eq!(ma.steps[0].delay_after_ms, Some(2000));
+                assert!(matches!(ma.steps[1].action, Action::Keyboard(_)));
+                assert_eq!(ma.steps[1].delay_after_ms, None);
+            }
+            _ => panic!("Expected Macro action"),
+        }
+    }
+
+    #[test]
+    fn test_action_conditional_deserialize() {
+        let json = r#"{
+            "type": "conditional",
+            "branches": [
+                {"when": {"process": "code.exe"}, "then": {"type": "keyboard", "keys": "f5"}}
+            ],
+            "default": {"type": "media", "action": "play_pause"}
+        }"#;
+        let action: Action = serde_json::from_str(json).unwrap();
+        match action {
+            Action::Conditional(ca) => {
+                assert_eq!(ca.branches.len(), 1);
+                assert_eq!(ca.branches[0].when.process.as_deref(), Some("code.exe"));
+                assert!(matches!(ca.branches[0].then, Action::Keyboard(_)));
+                assert!(matches!(ca.default.as_deref(), Some(Action::Media(_))));
+            }
+            _ => panic!("Expected Conditional action"),
+        }
+    }
+
+    #[test]
+    fn test_window_match_title_substring() {
+        let when = WindowMatch {
+            title: Some("visual studio".to_string()),
+            process: None,
+        };
+        assert!(when.matches("main.rs - Visual Studio Code", None));
+        assert!(!when.matches("Firefox", Some("firefox.exe")));
+    }
+
+    #[test]
+    fn test_window_match_process_ignores_case_and_extension() {
+        let when = WindowMatch {
+            title: None,
+            process: Some("Code".to_string()),
+        };
+        assert!(when.matches("anything", Some("code.exe")));
+        assert!(!when.matches("anything", Some("vscode.exe")));
+        assert!(!when.matches("anything", None));
+    }
+
+    #[test]
+    fn test_window_match_requires_all_fields() {
+        let when = WindowMatch {
+            title: Some("Spotify".to_string()),
+            process: Some("spotify.exe".to_string()),
+        };
+        assert!(when.matches("Spotify Premium", Some("Spotify.exe")));
+        assert!(!when.matches("Spotify Premium", Some("chrome.exe")));
+        assert!(!WindowMatch::default().matches("Spotify", Some("spotify.exe")));
+    }
+
     #[test]
     fn test_action_clone() {
         let action = Action::Launch(LaunchAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
             path: "notepad.exe".to_string(),
             args: vec![],
             working_directory: None,
+            use_shell: None,
+            single_instance: None,
         });
         let cloned = action.clone();
         if let Action::Launch(la) = cloned {
@@ -889,13 +1462,57 @@ mod tests {
         assert!(!result.success);
         assert!(result.message.is_none());
         assert_eq!(result.error, Some("Something went wrong".to_string()));
+        assert_eq!(result.error_code, Some(ActionErrorCode::Failed));
         assert_eq!(result.duration_ms, 200);
     }
 
+    #[test]
+    fn test_action_result_from_error() {
+        let result = ActionResult::from_error(ActionError::DeviceError("Device not connected".to_string()), 0);
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("Device not connected"));
+        assert_eq!(result.error_code, Some(ActionErrorCode::Device));
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["error"], "Device not connected");
+        assert_eq!(json["errorCode"], "device");
+    }
+
+    #[test]
+    fn test_action_result_error_code_optional() {
+        let json = serde_json::to_string(&ActionResult::success(1)).unwrap();
+        assert!(!json.contains("errorCode"));
+
+        // Results saved before error codes still parse
+        let parsed: ActionResult =
+            serde_json::from_str(r#"{"success":false,"error":"boom","durationMs":1}"#).unwrap();
+        assert_eq!(parsed.error.as_deref(), Some("boom"));
+        assert!(parsed.error_code.is_none());
+    }
+
     #[test]
     fn test_action_result_serializes_to_camel_case() {
         let result = ActionResult::success_with_message("OK".to_string(), 10);
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("\"durationMs\":10"));
     }
+
+    #[test]
+    fn test_action_result_timings_omitted_when_none() {
+        let result = ActionResult::success(10);
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(!json.contains("timings"));
+    }
+
+    #[test]
+    fn test_action_result_with_timings_serializes() {
+        let mut timings = HashMap::new();
+        timings.insert("send".to_string(), 12);
+        let result = ActionResult::success(15).with_timings(timings);
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(json.contains("\"timings\":{\"send\":12}"));
+
+        let parsed: ActionResult = serde_json::from_str(r#"{"success":true,"durationMs":1}"#).unwrap();
+        assert!(parsed.timings.is_none());
+    }
 }
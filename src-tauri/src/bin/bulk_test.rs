@@ -167,7 +167,7 @@ fn main() {
 
 fn find_soomfon_device<T: UsbContext>(context: &T) -> Option<Device<T>> {
     context.devices().ok()?.iter().find(|device| {
-        device.device_descriptor().map_or(false, |desc| {
+        device.device_descriptor().is_ok_and(|desc| {
             desc.vendor_id() == SOOMFON_VID && desc.product_id() == SOOMFON_PID
         })
     })
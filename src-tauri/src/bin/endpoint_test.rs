@@ -105,7 +105,7 @@ fn main() {
 
 fn find_device<T: UsbContext>(context: &T) -> Option<Device<T>> {
     context.devices().ok()?.iter().find(|d| {
-        d.device_descriptor().map_or(false, |desc| {
+        d.device_descriptor().is_ok_and(|desc| {
             desc.vendor_id() == SOOMFON_VID && desc.product_id() == SOOMFON_PID
         })
     })
@@ -131,9 +131,8 @@ fn main() {
     loop {
         // Periodically send keep-alive (CRT packets every 10s like official software)
         if last_send.elapsed() > Duration::from_secs(10) {
-            match device.write(&crt_packet) {
-                Ok(_) => println!("[KEEPALIVE] Sent CRT packet"),
-                Err(_) => {}
+            if device.write(&crt_packet).is_ok() {
+                println!("[KEEPALIVE] Sent CRT packet");
             }
             last_send = std::time::Instant::now();
         }
@@ -167,7 +166,7 @@ fn print_hex(data: &[u8]) {
             print!("  ");
         }
     }
-    if data.len() % 16 != 0 {
+    if !data.len().is_multiple_of(16) {
         println!();
     }
 }
@@ -111,7 +111,7 @@ fn main() {
 
     loop {
         // Send poll request
-        if let Ok(_) = device.write(&poll_packet) {
+        if device.write(&poll_packet).is_ok() {
             // Read response
             if let Ok(n) = device.read_timeout(&mut buf, 50) {
                 if n > 0 && buf[..n] != last_data[..n.min(last_data.len())] {
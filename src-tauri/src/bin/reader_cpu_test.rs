@@ -0,0 +1,98 @@
+//! Reader CPU Test - Compare idle cost of async and sync USB reads
+//!
+//! Sets the device up like `init_test`, then runs the event reader in each
+//! mode for a while without touching the device and reports how often the
+//! reader woke up and how much CPU time the process used.
+//!
+//! Run with: cargo run --bin reader_cpu_test [seconds per mode, default 30]
+
+use soomfon_controller_lib::hid::diagnostics;
+use soomfon_controller_lib::hid::usb_reader::{UsbReadMode, UsbReader};
+use soomfon_controller_lib::hid::{CRT_PACKET_SIZE, EP_IN};
+use rusb::Context;
+use std::time::{Duration, Instant};
+
+/// Read timeout of the sync reader, matching the polling thread
+const SYNC_TIMEOUT: Duration = Duration::from_millis(100);
+
+fn main() {
+    println!("=== SOOMFON Reader CPU Test ===\n");
+
+    let seconds: u64 = std::env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(30);
+
+    let context = Context::new().expect("Failed to create USB context");
+    let (report, handle) = diagnostics::run(&context);
+    for step in &report.steps {
+        let status = if step.ok { "[OK]  " } else { "[INFO]" };
+        println!("{} {}: {}", status, step.name, step.detail);
+    }
+    let Some(handle) = handle else {
+        eprintln!("\n[ERROR] Device could not be set up");
+        std::process::exit(1);
+    };
+
+    println!("\nLeave the device untouched for {} seconds per mode...\n", seconds);
+
+    for mode in [UsbReadMode::Sync, UsbReadMode::Async] {
+        let mut reader = UsbReader::open(&handle, EP_IN, mode, CRT_PACKET_SIZE, SYNC_TIMEOUT);
+        if reader.mode() != mode {
+            println!("{:?}: not available, skipped", mode);
+            continue;
+        }
+
+        let mut buf = [0u8; CRT_PACKET_SIZE];
+        let mut wakeups = 0u64;
+        let mut packets = 0u64;
+        let cpu_start = cpu_time();
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(seconds) {
+            wakeups += 1;
+            match reader.read(&mut buf) {
+                Ok(n) if n > 0 => packets += 1,
+                Ok(_) | Err(rusb::Error::Timeout) => {}
+                Err(e) => {
+                    eprintln!("[ERROR] {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        let elapsed = start.elapsed().as_secs_f64();
+        drop(reader);
+
+        print!(
+            "{:?}: {} wake-ups ({:.1}/s), {} packets",
+            mode,
+            wakeups,
+            wakeups as f64 / elapsed,
+            packets
+        );
+        match (cpu_start, cpu_time()) {
+            (Some(before), Some(after)) => {
+                let cpu = (after - before).as_secs_f64();
+                println!(", {:.1} ms CPU ({:.3}% of one core)", cpu * 1000.0, cpu / elapsed * 100.0);
+            }
+            _ => println!(", CPU time not available on this platform"),
+        }
+    }
+}
+
+/// User plus system CPU time used by this process so far
+#[cfg(unix)]
+fn cpu_time() -> Option<Duration> {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+    // SAFETY: getrusage fills the struct when it returns 0
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    let usage = unsafe { usage.assume_init() };
+    let micros = |tv: libc::timeval| tv.tv_sec as u64 * 1_000_000 + tv.tv_usec as u64;
+    Some(Duration::from_micros(micros(usage.ru_utime) + micros(usage.ru_stime)))
+}
+
+#[cfg(not(unix))]
+fn cpu_time() -> Option<Duration> {
+    None
+}
@@ -2,55 +2,161 @@
 //!
 //! Tauri commands for action execution.
 
-use crate::actions::engine::{ActionEngine, HistoryEntry};
-use crate::actions::types::{Action, ActionResult};
-use crate::actions::IntegrationConfig;
+use crate::actions::engine::{Admission, ActionEngine, ActionTypeStats, HistoryEntry, HistoryFilter};
+use crate::actions::event_binder::EventBinder;
+use crate::actions::stats::{input_for_event, StatsStore};
+use crate::actions::error::ActionError;
+use crate::actions::template::render_action;
+use crate::actions::types::{Action, ActionResult, KeyboardAction};
+use crate::actions::{DeviceHandle, IntegrationConfig};
 use crate::config::manager::ConfigManager;
+use crate::config::profiles::ProfileManager;
+use crate::hid::manager::HidManager;
+use crate::hid::types::DeviceEvent;
 use parking_lot::Mutex;
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, Manager, State};
+
+/// Entries returned by `get_action_history` when no limit is given
+const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// Integration configuration from the current settings, with device access
+/// for actions that control the device (e.g. brightness, workspace switching)
+pub(crate) fn current_integrations(app: &AppHandle) -> IntegrationConfig {
+    let Some(config) = app.try_state::<Arc<Mutex<ConfigManager>>>() else {
+        return IntegrationConfig::default();
+    };
+    let integrations = IntegrationConfig::from_settings(config.lock().get_settings());
+
+    let hid = app.try_state::<Arc<Mutex<HidManager>>>();
+    let profiles = app.try_state::<Arc<Mutex<ProfileManager>>>();
+    match (hid, profiles) {
+        (Some(hid), Some(profiles)) => integrations.with_device(DeviceHandle {
+            hid: Arc::clone(&hid),
+            config: Arc::clone(&config),
+            profiles: Arc::clone(&profiles),
+        }),
+        _ => integrations,
+    }
+}
+
+/// Run an action through the engine
+///
+/// Waits for a turn according to the engine's execution mode, executes the
+/// action with the current integrations, then records it in the history and
+/// lets the next queued action start.
+///
+/// Note: the engine lock is released before awaiting because
+/// parking_lot::MutexGuard is not Send.
+async fn run_through_engine(app: &AppHandle, engine: &Mutex<ActionEngine>, action: &Action) -> ActionResult {
+    // Ask the engine for a turn (without holding lock across await)
+    let admission = engine.lock().submit(action);
+    let ticket = match admission {
+        Admission::Run(ticket) => ticket,
+        Admission::Queued(turn) => match turn.await {
+            Ok(ticket) => ticket,
+            Err(_) => return ActionResult::from_error(ActionError::Cancelled("Queued action was dropped".to_string()), 0),
+        },
+        Admission::Rejected => {
+            return ActionResult::from_error(ActionError::Busy("Another action is currently executing".to_string()), 0);
+        }
+    };
+
+    // Get integration configuration from config manager, with the variables
+    // earlier actions captured
+    let mut integrations = current_integrations(app);
+    integrations.variables = engine.lock().variables().clone();
+
+    // Fill in `${...}` references; a macro does this per step, so that its
+    // own captures take precedence
+    let rendered = match action {
+        Action::Macro(_) => Ok(action.clone()),
+        _ => render_action(action, &integrations.variables),
+    };
+
+    // Execute the action with integration config outside of the mutex lock
+    let result = match rendered {
+        Ok(rendered) => crate::actions::execute_action_with_cancellation(&rendered, &integrations, &ticket.token).await,
+        Err(e) => ActionResult::from_error(ActionError::InvalidInput(e), 0),
+    };
+
+    // Record to history and let the next queued action start
+    engine.lock().finish(&ticket, action, &result);
+
+    result
+}
+
+/// Count a run of the action bound to a control in the active profile
+pub(crate) fn record_usage(app: &AppHandle, profile_id: &str, event: &DeviceEvent) {
+    let Some((kind, index)) = input_for_event(event) else {
+        return;
+    };
+    if let Some(stats) = app.try_state::<Arc<StatsStore>>() {
+        stats.record(profile_id, kind, index);
+    }
+}
 
 /// Execute an action
 ///
-/// Reads integration configuration (Home Assistant, Node-RED) from the config
-/// manager and passes it to action handlers for execution.
+/// Reads integration configuration (Home Assistant, Node-RED, MQTT) from the
+/// config manager and passes it, with the device, to action handlers for
+/// execution. Whether an action arriving while another runs is rejected,
+/// queued or run alongside it depends on the engine's execution mode.
 ///
-/// Note: We need to clone the action and release the lock before awaiting
-/// because parking_lot::MutexGuard is not Send.
+/// With `dry_run` set, the action isn't executed or recorded; the result
+/// message describes what it would do.
 #[tauri::command]
 pub async fn execute_action(
     action: Action,
+    dry_run: Option<bool>,
+    app: AppHandle,
     engine: State<'_, Arc<Mutex<ActionEngine>>>,
-    config_manager: State<'_, Arc<Mutex<ConfigManager>>>,
 ) -> Result<ActionResult, String> {
-    // Check if another action is executing (without holding lock across await)
-    {
-        let engine_guard = engine.lock();
-        if engine_guard.is_executing() {
-            return Ok(ActionResult::failure("Another action is currently executing".to_string(), 0));
-        }
+    if dry_run.unwrap_or(false) {
+        let integrations = current_integrations(&app);
+        return Ok(crate::actions::execute_action_with_config(&action, &integrations, true).await);
     }
+    Ok(run_through_engine(&app, &engine, &action).await)
+}
 
-    // Get integration configuration from config manager
-    let integrations = {
-        let config_guard = config_manager.lock();
-        let settings = config_guard.get_settings();
-        IntegrationConfig {
-            home_assistant: settings.home_assistant.clone(),
-            node_red: settings.node_red.clone(),
+/// Execute the action bound to a device event
+///
+/// Resolves the event against the active profile with the event binder (so
+/// shift and encoder deadband state carry over between events) and runs the
+/// action like `execute_action`, without a round-trip through the frontend.
+/// The run is counted in the profile's usage statistics. Returns `None` when
+/// nothing is bound to the event.
+#[tauri::command]
+pub async fn dispatch_event(
+    event: DeviceEvent,
+    app: AppHandle,
+    engine: State<'_, Arc<Mutex<ActionEngine>>>,
+    binder: State<'_, Arc<Mutex<EventBinder>>>,
+    config_manager: State<'_, Arc<Mutex<ConfigManager>>>,
+    profile_manager: State<'_, Arc<Mutex<ProfileManager>>>,
+) -> Result<Option<ActionResult>, String> {
+    let active_id = config_manager.lock().get_active_profile_id().map(str::to_string);
+    let profile = active_id.as_ref().and_then(|id| profile_manager.lock().get(id).cloned());
+
+    let action = {
+        let mut binder = binder.lock();
+        match profile {
+            Some(profile) => binder.refresh_profile(profile),
+            None => {
+                binder.unbind();
+                return Ok(None);
+            }
         }
+        binder.get_action_for_event(&event)
     };
 
-    // Execute the action with integration config outside of the mutex lock
-    let result = crate::actions::execute_action_with_config(&action, &integrations).await;
-
-    // Record to history
-    {
-        let mut engine_guard = engine.lock();
-        engine_guard.record_execution(&action, &result);
+    let Some(action) = action else {
+        return Ok(None);
+    };
+    if let Some(profile_id) = &active_id {
+        record_usage(&app, profile_id, &event);
     }
-
-    Ok(result)
+    Ok(Some(run_through_engine(&app, &engine, &action).await))
 }
 
 /// Cancel the currently running action
@@ -63,11 +169,73 @@ pub fn cancel_action(
     Ok(())
 }
 
-/// Get action execution history
+/// Get action execution history, oldest first
+///
+/// Reads the history file, so entries from earlier runs are included.
+/// `limit` keeps the most recent entries (default 100).
 #[tauri::command]
 pub fn get_action_history(
+    limit: Option<usize>,
+    engine: State<Arc<Mutex<ActionEngine>>>,
+) -> Vec<HistoryEntry> {
+    let limit = limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+    // Read the file without holding the engine lock
+    let store = engine.lock().history_store().cloned();
+    match store {
+        Some(store) => store.load_tail(limit),
+        None => {
+            let engine = engine.lock();
+            let history = engine.get_history();
+            history[history.len().saturating_sub(limit)..].to_vec()
+        }
+    }
+}
+
+/// Clear action execution history, in memory and on disk
+#[tauri::command]
+pub fn clear_action_history(
+    engine: State<Arc<Mutex<ActionEngine>>>,
+) -> Result<(), String> {
+    engine.lock().clear_history()
+}
+
+/// Get action execution history filtered by type, time and outcome
+///
+/// `limit` keeps the most recent matching entries.
+#[tauri::command]
+pub fn get_action_history_filtered(
+    action_type: Option<String>,
+    since_ts: Option<u64>,
+    success_only: Option<bool>,
+    limit: Option<usize>,
     engine: State<Arc<Mutex<ActionEngine>>>,
 ) -> Vec<HistoryEntry> {
     let engine = engine.lock();
-    engine.get_history().to_vec()
+    engine.get_history_filtered(&HistoryFilter {
+        action_type,
+        since_ts,
+        success_only,
+        limit,
+    })
+}
+
+/// Get execution counts and average duration per action type
+#[tauri::command]
+pub fn get_action_stats(
+    engine: State<Arc<Mutex<ActionEngine>>>,
+) -> Vec<ActionTypeStats> {
+    let engine = engine.lock();
+    engine.get_stats()
+}
+
+/// Start recording keystrokes for a keyboard macro
+#[tauri::command]
+pub fn start_macro_record() -> Result<(), String> {
+    crate::actions::macro_recorder::start()
+}
+
+/// Stop recording and return the captured keystrokes as a keyboard action
+#[tauri::command]
+pub fn stop_macro_record() -> Result<KeyboardAction, String> {
+    crate::actions::macro_recorder::stop()
 }
@@ -3,12 +3,26 @@
 //! Tauri commands for configuration and profile management.
 //! Emits Tauri events for profile and config changes to support frontend reactivity.
 
+use crate::actions::engine::ActionEngine;
+use crate::actions::event_binder::EventBinder;
+use crate::actions::feedback;
+use crate::actions::handlers::workspace::render_buttons;
+use crate::actions::stats::{InputUsage, StatsStore};
+use crate::commands::actions::current_integrations;
 use crate::config::manager::ConfigManager;
+use crate::config::profiles::safety::ImportReview;
 use crate::config::profiles::ProfileManager;
-use crate::config::types::{AppSettings, Profile, ProfileUpdate};
+use crate::config::types::{
+    AppSettings, HomeAssistantConfig, MqttConfig, NodeRedConfig, Profile, ProfileUpdate,
+    WorkspaceUpdate,
+};
+use crate::commands::device::apply_brightness;
+use crate::commands::system::apply_log_filter;
+use crate::hid::event_map::EventTarget;
+use crate::hid::manager::HidManager;
 use parking_lot::Mutex;
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 
 /// Profile change event payload
 #[derive(serde::Serialize, Clone)]
@@ -45,22 +59,119 @@ pub fn set_app_settings(
     settings: AppSettings,
     manager: State<Arc<Mutex<ConfigManager>>>,
 ) -> Result<(), String> {
-    let mut manager = manager.lock();
-    manager.set_settings(settings.clone())?;
+    manager.lock().set_settings(settings.clone())?;
 
-    // Emit config changed event
+    // The config lock is released first; the integrations read it again
+    if let Some(engine) = app.try_state::<Arc<Mutex<ActionEngine>>>() {
+        let mut engine = engine.lock();
+        engine.set_execution_mode(settings.action_execution_mode);
+        engine.set_integrations(current_integrations(&app));
+    }
+    if let Err(e) = apply_log_filter(&app, settings.log_level, settings.usb_logging) {
+        log::warn!("{}", e);
+    }
+
+    emit_config_changed(&app, "appSettings", &settings);
+    Ok(())
+}
+
+/// Set whether the app starts minimized to the tray
+/// Emits `config:changed` event on success
+#[tauri::command]
+pub fn set_start_minimized(
+    app: AppHandle,
+    enabled: bool,
+    manager: State<Arc<Mutex<ConfigManager>>>,
+) -> Result<(), String> {
+    manager.lock().set_start_minimized(enabled)?;
+    emit_config_changed(&app, "startMinimized", enabled);
+    Ok(())
+}
+
+/// Set or clear the Home Assistant connection
+/// Emits `config:changed` event on success
+#[tauri::command]
+pub fn set_home_assistant(
+    app: AppHandle,
+    config: Option<HomeAssistantConfig>,
+    manager: State<Arc<Mutex<ConfigManager>>>,
+) -> Result<(), String> {
+    manager.lock().set_home_assistant(config.clone())?;
+    refresh_integrations(&app);
+    emit_config_changed(&app, "homeAssistant", &config);
+    Ok(())
+}
+
+/// Set or clear the Node-RED connection
+/// Emits `config:changed` event on success
+#[tauri::command]
+pub fn set_node_red(
+    app: AppHandle,
+    config: Option<NodeRedConfig>,
+    manager: State<Arc<Mutex<ConfigManager>>>,
+) -> Result<(), String> {
+    manager.lock().set_node_red(config.clone())?;
+    refresh_integrations(&app);
+    emit_config_changed(&app, "nodeRed", &config);
+    Ok(())
+}
+
+/// Set or clear the MQTT broker connection
+/// Emits `config:changed` event on success
+#[tauri::command]
+pub fn set_mqtt(
+    app: AppHandle,
+    config: Option<MqttConfig>,
+    manager: State<Arc<Mutex<ConfigManager>>>,
+) -> Result<(), String> {
+    manager.lock().set_mqtt(config.clone())?;
+    refresh_integrations(&app);
+    emit_config_changed(&app, "mqtt", &config);
+    Ok(())
+}
+
+/// Map a raw event ID to a control, or remove its override with `None`
+///
+/// Used to save a mapping learned from a `device:unknownEvent` or a
+/// mismapped control. Applies to the next event from the device.
+/// Emits `config:changed` event on success
+#[tauri::command]
+pub fn save_event_mapping(
+    app: AppHandle,
+    event_id: u8,
+    target: Option<EventTarget>,
+    manager: State<Arc<Mutex<ConfigManager>>>,
+) -> Result<(), String> {
+    let event_map = {
+        let mut manager = manager.lock();
+        manager.set_event_mapping(event_id, target)?;
+        manager.get_settings().event_map.clone()
+    };
+    emit_config_changed(&app, "eventMap", &event_map);
+    Ok(())
+}
+
+/// Hand the action engine the integration settings now in the config
+///
+/// Must be called with the config lock released, as it is read again.
+fn refresh_integrations(app: &AppHandle) {
+    if let Some(engine) = app.try_state::<Arc<Mutex<ActionEngine>>>() {
+        engine.lock().set_integrations(current_integrations(app));
+    }
+}
+
+/// Emit `config:changed` for one changed setting (or `appSettings` for all)
+pub(crate) fn emit_config_changed(app: &AppHandle, change_type: &str, new_value: impl serde::Serialize) {
     let event = ConfigChangeEvent {
-        change_type: "appSettings".to_string(),
-        new_value: serde_json::to_value(&settings).unwrap_or(serde_json::Value::Null),
+        change_type: change_type.to_string(),
+        new_value: serde_json::to_value(new_value).unwrap_or(serde_json::Value::Null),
     };
     if let Err(e) = app.emit("config:changed", event) {
         log::warn!("Failed to emit config:changed event: {}", e);
     }
-
-    Ok(())
 }
 
-/// Get all profiles
+/// Get all profiles in display order
 #[tauri::command]
 pub fn get_profiles(
     manager: State<Arc<Mutex<ProfileManager>>>,
@@ -69,6 +180,60 @@ pub fn get_profiles(
     manager.list().into_iter().cloned().collect()
 }
 
+/// Search profiles by name, description, button labels and tags, in display
+/// order
+///
+/// `query` is a case-insensitive substring (blank matches all); `tag`, if
+/// given, keeps only profiles with that tag.
+#[tauri::command]
+pub fn search_profiles(
+    query: String,
+    tag: Option<String>,
+    manager: State<Arc<Mutex<ProfileManager>>>,
+) -> Vec<Profile> {
+    let manager = manager.lock();
+    let mut profiles = manager.search(&query);
+    if let Some(tag) = tag.as_deref().filter(|tag| !tag.trim().is_empty()) {
+        let tagged: Vec<&str> = manager.with_tag(tag).iter().map(|p| p.id.as_str()).collect();
+        profiles.retain(|profile| tagged.contains(&profile.id.as_str()));
+    }
+    profiles.into_iter().cloned().collect()
+}
+
+/// Replace a profile's tags
+/// Emits `profile:changed` event with type "updated" on success
+#[tauri::command]
+pub fn set_profile_tags(
+    app: AppHandle,
+    id: String,
+    tags: Vec<String>,
+    manager: State<Arc<Mutex<ProfileManager>>>,
+) -> Result<Profile, String> {
+    let profile = manager.lock().set_tags(&id, tags)?;
+    emit_profile_updated(&app, &profile);
+    Ok(profile)
+}
+
+/// Reorder profiles to match the given list of IDs
+/// Emits a single `profiles:changed` event listing the profile IDs in their new order
+#[tauri::command]
+pub fn reorder_profiles(
+    app: AppHandle,
+    ids: Vec<String>,
+    manager: State<Arc<Mutex<ProfileManager>>>,
+) -> Result<Vec<Profile>, String> {
+    let profiles = manager.lock().reorder(&ids)?;
+
+    let event = ProfilesChangedEvent {
+        profile_ids: profiles.iter().map(|p| p.id.clone()).collect(),
+    };
+    if let Err(e) = app.emit("profiles:changed", event) {
+        log::warn!("Failed to emit profiles:changed event: {}", e);
+    }
+
+    Ok(profiles)
+}
+
 /// Get active profile
 #[tauri::command]
 pub fn get_active_profile(
@@ -86,28 +251,105 @@ pub fn get_active_profile(
 /// Set active profile
 /// Emits `profile:changed` event with type "activated" on success
 #[tauri::command]
-pub fn set_active_profile(
-    app: AppHandle,
-    id: String,
-    manager: State<Arc<Mutex<ConfigManager>>>,
-    profile_manager: State<Arc<Mutex<ProfileManager>>>,
-) -> Result<(), String> {
-    let mut config = manager.lock();
-    config.set_active_profile_id(Some(id.clone()))?;
+pub fn set_active_profile(app: AppHandle, id: String) -> Result<(), String> {
+    activate_profile(&app, &id)
+}
+
+/// Make a profile the active one
+///
+/// Saves the choice, binds the profile for event routing, shows its current
+/// workspace's buttons on the device, applies its default brightness, and
+/// emits `profile:changed` with type "activated". Shared by `set_active_profile` and the tray menu.
+pub fn activate_profile(app: &AppHandle, id: &str) -> Result<(), String> {
+    let config = app
+        .try_state::<Arc<Mutex<ConfigManager>>>()
+        .ok_or("Config manager not available")?;
+    config.lock().set_active_profile_id(Some(id.to_string()))?;
+
+    let profile = app
+        .try_state::<Arc<Mutex<ProfileManager>>>()
+        .and_then(|profiles| profiles.lock().get(id).cloned());
+    let Some(profile) = profile else {
+        return Ok(());
+    };
+
+    if let Some(binder) = app.try_state::<Arc<Mutex<EventBinder>>>() {
+        binder.lock().bind_profile(profile.clone());
+    }
+    refresh_device_buttons(app, &profile);
+    apply_profile_brightness(app, &profile)?;
 
     // Emit profile changed event
-    let profiles = profile_manager.lock();
-    if let Some(profile) = profiles.get(&id) {
-        let event = ProfileChangeEvent {
-            event_type: "activated".to_string(),
-            profile: profile.clone(),
-            source_profile_id: None,
-        };
-        if let Err(e) = app.emit("profile:changed", event) {
-            log::warn!("Failed to emit profile:changed event: {}", e);
+    let event = ProfileChangeEvent {
+        event_type: "activated".to_string(),
+        profile,
+        source_profile_id: None,
+    };
+    if let Err(e) = app.emit("profile:changed", event) {
+        log::warn!("Failed to emit profile:changed event: {}", e);
+    }
+
+    Ok(())
+}
+
+/// Show the brightness of a profile being activated and save it
+/// Emits `config:changed` event with the brightness applied
+fn apply_profile_brightness(app: &AppHandle, profile: &Profile) -> Result<(), String> {
+    let Some(config) = app.try_state::<Arc<Mutex<ConfigManager>>>() else {
+        return Ok(());
+    };
+    let brightness = config.lock().apply_profile_brightness(profile)?;
+
+    if let Some(hid) = app.try_state::<Arc<Mutex<HidManager>>>() {
+        let connected = hid.lock().is_connected();
+        if connected {
+            apply_brightness(&hid, brightness);
         }
     }
+    emit_config_changed(app, "brightness", brightness);
+    Ok(())
+}
+
+/// Set or clear the brightness a profile applies when activated
+/// Emits `profile:changed` event with type "updated" on success, and applies
+/// the brightness straight away if the profile is active
+#[tauri::command]
+pub fn set_profile_brightness(
+    app: AppHandle,
+    profile_id: String,
+    brightness: Option<u8>,
+    manager: State<Arc<Mutex<ProfileManager>>>,
+) -> Result<Profile, String> {
+    let profile = manager.lock().set_default_brightness(&profile_id, brightness)?;
+
+    let is_active = app
+        .try_state::<Arc<Mutex<ConfigManager>>>()
+        .is_some_and(|config| config.lock().get_active_profile_id() == Some(profile.id.as_str()));
+    if is_active && profile.default_brightness.is_some() {
+        apply_profile_brightness(&app, &profile)?;
+    }
+    emit_profile_updated(&app, &profile);
+
+    Ok(profile)
+}
+
+/// Get how often each button and encoder action of a profile has run, and
+/// when it last ran, most used first
+#[tauri::command]
+pub fn get_profile_stats(app: AppHandle, profile_id: String) -> Vec<InputUsage> {
+    app.try_state::<Arc<StatsStore>>()
+        .map(|stats| stats.profile_stats(&profile_id))
+        .unwrap_or_default()
+}
 
+/// Reset the usage statistics of one profile, or of all profiles when no ID
+/// is given
+#[tauri::command]
+pub fn reset_stats(app: AppHandle, profile_id: Option<String>) -> Result<(), String> {
+    let stats = app
+        .try_state::<Arc<StatsStore>>()
+        .ok_or("Usage statistics not available")?;
+    stats.reset(profile_id.as_deref());
     Ok(())
 }
 
@@ -160,6 +402,154 @@ pub fn update_profile(
     Ok(profile)
 }
 
+/// Rename a profile
+/// Emits `profile:changed` event with type "updated" on success
+#[tauri::command]
+pub fn rename_profile(
+    app: AppHandle,
+    id: String,
+    new_name: String,
+    manager: State<Arc<Mutex<ProfileManager>>>,
+) -> Result<Profile, String> {
+    let profile = manager.lock().rename(&id, &new_name)?;
+    emit_profile_updated(&app, &profile);
+    Ok(profile)
+}
+
+/// Payload of the `profiles:changed` event
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesChangedEvent {
+    /// Profiles reloaded or removed after changes outside the app, or every
+    /// profile in its new order after a reorder
+    pub profile_ids: Vec<String>,
+}
+
+/// React to profile files changed outside the app
+///
+/// Emits `profiles:changed` and, if the active profile was among them, shows
+/// its current buttons on the device.
+pub fn handle_external_profile_changes(app: &AppHandle, profile_ids: Vec<String>) {
+    if let Some(profiles) = app.try_state::<Arc<Mutex<ProfileManager>>>() {
+        let reloaded: Vec<Profile> = {
+            let profiles = profiles.lock();
+            profile_ids.iter().filter_map(|id| profiles.get(id).cloned()).collect()
+        };
+        for profile in &reloaded {
+            refresh_device_buttons(app, profile);
+        }
+    }
+
+    let event = ProfilesChangedEvent { profile_ids };
+    if let Err(e) = app.emit("profiles:changed", event) {
+        log::warn!("Failed to emit profiles:changed event: {}", e);
+    }
+}
+
+/// Emit `profile:changed` with type "updated"
+fn emit_profile_updated(app: &AppHandle, profile: &Profile) {
+    let event = ProfileChangeEvent {
+        event_type: "updated".to_string(),
+        profile: profile.clone(),
+        source_profile_id: None,
+    };
+    if let Err(e) = app.emit("profile:changed", event) {
+        log::warn!("Failed to emit profile:changed event: {}", e);
+    }
+}
+
+/// Show the active workspace's button images if `profile` is the active profile
+fn refresh_device_buttons(app: &AppHandle, profile: &Profile) {
+    let is_active = app
+        .try_state::<Arc<Mutex<ConfigManager>>>()
+        .is_some_and(|config| config.lock().get_active_profile_id() == Some(profile.id.as_str()));
+    if !is_active {
+        return;
+    }
+
+    let (Some(hid), Some(workspace)) = (app.try_state::<Arc<Mutex<HidManager>>>(), profile.active_workspace()) else {
+        return;
+    };
+    if let Err(e) = render_buttons(&hid, &workspace.buttons) {
+        log::warn!("Failed to show workspace buttons: {}", e);
+    }
+    feedback::sync(&workspace.buttons, &current_integrations(app));
+}
+
+/// Switch a profile's active workspace
+/// Emits `profile:changed` event with type "updated" on success, and shows
+/// the workspace's buttons on the device if the profile is active
+#[tauri::command]
+pub fn set_active_workspace(
+    app: AppHandle,
+    profile_id: String,
+    index: usize,
+    manager: State<Arc<Mutex<ProfileManager>>>,
+) -> Result<Profile, String> {
+    let profile = manager.lock().set_active_workspace(&profile_id, index)?;
+
+    refresh_device_buttons(&app, &profile);
+    emit_profile_updated(&app, &profile);
+
+    Ok(profile)
+}
+
+/// Add an empty workspace to a profile
+/// Emits `profile:changed` event with type "updated" on success
+#[tauri::command]
+pub fn add_workspace(
+    app: AppHandle,
+    profile_id: String,
+    name: Option<String>,
+    manager: State<Arc<Mutex<ProfileManager>>>,
+) -> Result<Profile, String> {
+    let profile = manager.lock().add_workspace(&profile_id, name)?;
+
+    emit_profile_updated(&app, &profile);
+
+    Ok(profile)
+}
+
+/// Delete a workspace from a profile
+///
+/// If the active workspace is deleted, the active index is clamped to the
+/// remaining workspaces. Emits `profile:changed` event with type "updated" on
+/// success.
+#[tauri::command]
+pub fn delete_workspace(
+    app: AppHandle,
+    profile_id: String,
+    workspace_id: String,
+    manager: State<Arc<Mutex<ProfileManager>>>,
+) -> Result<Profile, String> {
+    let profile = manager.lock().delete_workspace(&profile_id, &workspace_id)?;
+
+    refresh_device_buttons(&app, &profile);
+    emit_profile_updated(&app, &profile);
+
+    Ok(profile)
+}
+
+/// Apply a partial update to one workspace of a profile
+/// Emits `profile:changed` event with type "updated" on success
+#[tauri::command]
+pub fn update_workspace(
+    app: AppHandle,
+    profile_id: String,
+    workspace_id: String,
+    updates: WorkspaceUpdate,
+    manager: State<Arc<Mutex<ProfileManager>>>,
+) -> Result<Profile, String> {
+    let profile = manager.lock().update_workspace(&profile_id, &workspace_id, updates)?;
+
+    if profile.active_workspace().is_some_and(|w| w.id == workspace_id) {
+        refresh_device_buttons(&app, &profile);
+    }
+    emit_profile_updated(&app, &profile);
+
+    Ok(profile)
+}
+
 /// Delete a profile
 /// Emits `profile:changed` event with type "deleted" on success
 #[tauri::command]
@@ -175,6 +565,10 @@ pub fn delete_profile(
 
     manager.delete(&id)?;
 
+    if let Some(stats) = app.try_state::<Arc<StatsStore>>() {
+        stats.reset(Some(&id));
+    }
+
     // Emit profile changed event
     if let Some(profile) = profile {
         let event = ProfileChangeEvent {
@@ -214,6 +608,90 @@ pub fn import_profile(
     Ok(profile)
 }
 
+/// Import a profile from JSON and list the actions in it that run programs
+/// or code, so the user can be warned before activating it
+/// Emits `profile:changed` event with type "created" on success
+#[tauri::command]
+pub fn import_profile_with_review(
+    app: AppHandle,
+    json: String,
+    manager: State<Arc<Mutex<ProfileManager>>>,
+) -> Result<ImportReview, String> {
+    let review = manager.lock().import_with_review(&json)?;
+    if !review.risks.is_empty() {
+        log::info!(
+            "Imported profile {} has {} action(s) that run programs or code",
+            review.profile.id,
+            review.risks.len()
+        );
+    }
+
+    let event = ProfileChangeEvent {
+        event_type: "created".to_string(),
+        profile: review.profile.clone(),
+        source_profile_id: None,
+    };
+    if let Err(e) = app.emit("profile:changed", event) {
+        log::warn!("Failed to emit profile:changed event: {}", e);
+    }
+
+    Ok(review)
+}
+
+/// Export a profile as a zip bundle with its button images as separate files
+#[tauri::command]
+pub fn export_profile_bundle(
+    id: String,
+    manager: State<Arc<Mutex<ProfileManager>>>,
+) -> Result<Vec<u8>, String> {
+    manager.lock().export_bundle(&id)
+}
+
+/// Import a profile from a zip bundle made by `export_profile_bundle`
+/// Emits `profile:changed` event with type "created" on success
+#[tauri::command]
+pub fn import_profile_bundle(
+    app: AppHandle,
+    bytes: Vec<u8>,
+    manager: State<Arc<Mutex<ProfileManager>>>,
+) -> Result<Profile, String> {
+    let profile = manager.lock().import_bundle(&bytes)?;
+
+    let event = ProfileChangeEvent {
+        event_type: "created".to_string(),
+        profile: profile.clone(),
+        source_profile_id: None,
+    };
+    if let Err(e) = app.emit("profile:changed", event) {
+        log::warn!("Failed to emit profile:changed event: {}", e);
+    }
+
+    Ok(profile)
+}
+
+/// Duplicate a profile, named "<name> (copy)" unless `new_name` is given
+/// Emits `profile:changed` event with type "created" and the source profile ID
+#[tauri::command]
+pub fn duplicate_profile(
+    app: AppHandle,
+    id: String,
+    new_name: Option<String>,
+    manager: State<Arc<Mutex<ProfileManager>>>,
+) -> Result<Profile, String> {
+    let profile = manager.lock().duplicate(&id, new_name)?;
+
+    let event = ProfileChangeEvent {
+        event_type: "created".to_string(),
+        profile: profile.clone(),
+        source_profile_id: Some(id),
+    };
+    if let Err(e) = app.emit("profile:changed", event) {
+        log::warn!("Failed to emit profile:changed event: {}", e);
+    }
+
+    Ok(profile)
+}
+
 /// Export a profile to JSON
 #[tauri::command]
 pub fn export_profile(
@@ -3,19 +3,47 @@
 //! Tauri commands for HID device operations.
 //! Emits Tauri events for device state changes to support frontend reactivity.
 
+use crate::actions::auto_repeat::AutoRepeat;
+use crate::actions::handlers::workspace::render_buttons;
+use crate::actions::engine::ActionEngine;
+use crate::actions::event_binder::EventBinder;
+use crate::actions::feedback;
+use crate::actions::types::Action;
+use crate::commands::actions::{current_integrations, record_usage};
+use crate::config::manager::ConfigManager;
+use crate::config::profiles::ProfileManager;
+use crate::config::types::{AppSettings, ButtonConfig, RepeatConfig};
+use crate::hid::acceleration::EncoderAccelerator;
+use crate::hid::animation;
+use crate::hid::debounce::Debouncer;
+use crate::hid::diagnostics::{self, format_hex, DiagnosticReport, SelfTestReport};
+use crate::hid::event_queue::{Next, PacketQueue, ReplySlot};
+use crate::hid::idle::IdleDimmer;
+use crate::hid::long_press::LongPressDetector;
 use crate::hid::manager::HidManager;
+use crate::hid::monitor::HotplugEvent;
 use crate::hid::packets::parse_ack_packet;
 use crate::hid::protocol::SoomfonProtocol;
+use crate::hid::reconnect::Backoff;
 use crate::hid::types::{
-    ButtonEventType, ButtonType, ConnectionState, DeviceEvent, DeviceInfo,
-    EncoderEventType, EncoderType, EP_IN,
+    ButtonEventType, ButtonType, ConnectionState, DeviceCapabilities, DeviceEvent, DeviceInfo,
+    EncoderEventType, EncoderType, HidError, HidErrorCode, CRT_PACKET_SIZE, DEBOUNCE_MS, EP_IN, KEEPALIVE_INTERVAL_MS,
+    LCD_BUTTON_COUNT, RawEvent, USB_LOG_TARGET,
 };
-use crate::image::processor::{process_image_source, ImageOptions};
+use crate::hid::usb_reader::UsbReader;
+use crate::image::processor::{
+    create_feedback_image, create_number_image, create_solid_color, create_solid_color_hex,
+    create_text_image, process_animated, process_image_source, read_image_source, BadgeSpec,
+    ImageOptions,
+};
+use crate::image::text::TextStyle;
+use crate::tray::{set_tray_status, TrayStatus};
 use parking_lot::Mutex;
-use std::sync::atomic::{AtomicBool, Ordering};
+use rusb::UsbContext;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 
 /// Device status response
 #[derive(serde::Serialize, Clone)]
@@ -56,9 +84,94 @@ pub struct EncoderEventPayload {
     pub timestamp: u64,
 }
 
+/// Unmapped event payload for frontend
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UnknownEventPayload {
+    /// Raw event ID byte
+    pub event_id: u8,
+    /// Raw state byte
+    pub state: u8,
+    /// Timestamp in milliseconds
+    pub timestamp: u64,
+}
+
+/// One entry of a multi-button image update
+#[derive(serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ButtonImageInput {
+    /// Button index (0-5)
+    pub index: u8,
+    /// Image source, in any format accepted by `set_button_image`
+    pub image_data: String,
+}
+
+/// Payload of the `device:restored` event
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceRestoredPayload {
+    /// Profile whose buttons were restored, if one was active
+    pub profile_id: Option<String>,
+    /// Brightness level re-applied to the display
+    pub brightness: u8,
+}
+
+/// Payload of the `device:reconnecting` event
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconnectingPayload {
+    /// Attempt about to be made, starting at 1
+    pub attempt: u32,
+    /// Attempts before giving up (0 = no limit)
+    pub max_attempts: u32,
+    /// Wait before this attempt in milliseconds
+    pub delay_ms: u64,
+}
+
 /// Global flag to control event polling
 static POLLING_ACTIVE: AtomicBool = AtomicBool::new(false);
 
+/// Incremented for each connection, so threads of an earlier one stop
+static POLLING_SESSION: AtomicU64 = AtomicU64::new(0);
+
+/// Session and USB context of the running polling thread, used to wake its reader
+static POLLING_CONTEXT: Mutex<Option<(u64, rusb::Context)>> = parking_lot::const_mutex(None);
+
+/// How often the keepalive thread checks whether it should stop
+const KEEPALIVE_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Read timeout of the sync USB reader, and the event thread's wait when no
+/// long press is pending
+const POLL_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Longest the app waits for the device shutdown sequence when quitting
+const EXIT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long the self-test pattern stays on the buttons
+const SELF_TEST_PATTERN_MS: u64 = 500;
+
+/// How long the self-test waits on the event endpoint
+const SELF_TEST_READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// How long the press-feedback image stays up before the original is restored
+const PRESS_FEEDBACK_MS: u64 = 120;
+
+/// How long `identify_button` shows the button's number
+const IDENTIFY_MS: u64 = 1000;
+
+/// Wakes the reconnect loop early; dropping it stops the loop
+static RECONNECT_WAKE: Mutex<Option<std::sync::mpsc::Sender<()>>> = parking_lot::const_mutex(None);
+
+/// How long `send_raw_packet` waits for a reply routed through the event thread
+const RAW_REPLY_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Armed by `send_raw_packet`; the event thread hands the reply to it
+static RAW_REPLY: ReplySlot = ReplySlot::new();
+
+/// Armed by `capture_next_event`; the event thread hands the next event to it
+static EVENT_CAPTURE: Mutex<Option<tokio::sync::oneshot::Sender<DeviceEvent>>> =
+    parking_lot::const_mutex(None);
+
 /// Connect to a SOOMFON device and initialize it
 /// Emits `device:connected` event on success, then starts event polling
 #[tauri::command]
@@ -66,23 +179,108 @@ pub fn connect_device(
     app: AppHandle,
     manager: State<Arc<Mutex<HidManager>>>,
 ) -> Result<DeviceInfo, String> {
+    stop_reconnecting();
+    connect_and_poll(app.clone(), &manager, None).map_err(|e| connection_failed(&app, e))
+}
+
+/// Connect to the SOOMFON device at a specific `bus:address:port` path
+///
+/// Use the `path` from `enumerate_devices` to target one of several identical units.
+/// Emits `device:connected` event on success, then starts event polling
+#[tauri::command]
+pub fn connect_device_by_path(
+    path: String,
+    app: AppHandle,
+    manager: State<Arc<Mutex<HidManager>>>,
+) -> Result<DeviceInfo, String> {
+    stop_reconnecting();
+    connect_and_poll(app.clone(), &manager, Some(&path)).map_err(|e| connection_failed(&app, e))
+}
+
+/// Active profile ID and the buttons of its current workspace
+fn active_buttons(app: &AppHandle) -> (Option<String>, Vec<ButtonConfig>) {
+    let profile_id = app
+        .try_state::<Arc<Mutex<ConfigManager>>>()
+        .and_then(|config| config.lock().get_active_profile_id().map(str::to_string));
+    let buttons = profile_id
+        .as_deref()
+        .and_then(|id| {
+            let profiles = app.try_state::<Arc<Mutex<ProfileManager>>>()?;
+            let profiles = profiles.lock();
+            Some(profiles.get(id)?.active_workspace()?.buttons.clone())
+        })
+        .unwrap_or_default();
+    (profile_id, buttons)
+}
+
+/// Payload of the `device:error` event
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceErrorPayload {
+    /// Category, for linking to the matching help page
+    pub code: HidErrorCode,
+    /// What went wrong and how to fix it
+    pub message: String,
+}
+
+/// Report a failed connection attempt on the tray icon and as a
+/// `device:error` event, returning the message for the user
+fn connection_failed(app: &AppHandle, error: HidError) -> String {
+    set_tray_status(app, TrayStatus::Error, None);
+    let payload = DeviceErrorPayload {
+        code: error.code(),
+        message: error.user_message(),
+    };
+    if let Err(e) = app.emit("device:error", &payload) {
+        log::warn!("Failed to emit device:error event: {}", e);
+    }
+    payload.message
+}
+
+/// Stop the polling thread and give it time to release its handle
+fn stop_polling() {
+    POLLING_ACTIVE.store(false, Ordering::SeqCst);
+    // The async reader sleeps until a packet arrives, so wake it to notice
+    if let Some((_, context)) = POLLING_CONTEXT.lock().as_ref() {
+        context.interrupt_handle_events();
+    }
+    std::thread::sleep(Duration::from_millis(150));
+}
+
+/// Connect (optionally to a specific path), initialize, and start event polling
+fn connect_and_poll(
+    app: AppHandle,
+    manager: &Arc<Mutex<HidManager>>,
+    path: Option<&str>,
+) -> Result<DeviceInfo, HidError> {
     // Check if already connected and polling
     if POLLING_ACTIVE.load(Ordering::SeqCst) {
-        let mgr = manager.lock();
-        if let Some(info) = mgr.get_device_info() {
-            log::info!("Already connected and polling, returning existing device info");
-            // Still emit the connected event so frontend updates its state
-            if let Err(e) = app.emit("device:connected", ()) {
-                log::warn!("Failed to emit device:connected event: {}", e);
+        let current = manager.lock().get_device_info().cloned();
+        if let Some(info) = current {
+            if path.is_none_or(|p| p == info.path) {
+                log::info!("Already connected and polling, returning existing device info");
+                // Still emit the connected event so frontend updates its state
+                // (without the manager locked, as listeners may read it)
+                if let Err(e) = app.emit("device:connected", ()) {
+                    log::warn!("Failed to emit device:connected event: {}", e);
+                }
+                return Ok(info);
             }
-            return Ok(info.clone());
         }
+
+        // Polling a different unit - stop it before switching devices
+        log::info!("Switching devices, stopping current event polling");
+        stop_polling();
+        manager.lock().disconnect();
     }
 
     let mut mgr = manager.lock();
 
     // Connect to the device
-    let result = mgr.connect().map_err(|e| e.to_string())?;
+    let result = match path {
+        Some(p) => mgr.connect_by_path(p),
+        None => mgr.connect(),
+    }?;
 
     // Initialize the device (CRITICAL - sends HID Feature Report to wake it up)
     log::info!("Initializing device...");
@@ -93,7 +291,7 @@ pub fn connect_device(
         Err(e) => {
             log::error!("Failed to initialize device: {}", e);
             mgr.disconnect();
-            return Err(format!("Failed to initialize device: {}", e));
+            return Err(e);
         }
     }
 
@@ -120,7 +318,7 @@ pub fn connect_device(
         Err(e) => {
             log::error!("Failed to take polling handle: {}", e);
             mgr.disconnect();
-            return Err(format!("Failed to take polling handle: {}", e));
+            return Err(e);
         }
     };
 
@@ -131,10 +329,29 @@ pub fn connect_device(
     if let Err(e) = app.emit("device:connected", ()) {
         log::warn!("Failed to emit device:connected event: {}", e);
     }
+    feedback::sync(&active_buttons(&app).1, &current_integrations(&app));
 
     // Start event polling in a background thread with dedicated USB handle
+    let session = POLLING_SESSION.fetch_add(1, Ordering::SeqCst) + 1;
     POLLING_ACTIVE.store(true, Ordering::SeqCst);
+    spawn_keepalive(Arc::clone(manager), session);
     let app_clone = app.clone();
+    let manager_clone = Arc::clone(manager);
+    let (curve, debounce_ms, read_mode) = app
+        .try_state::<Arc<Mutex<ConfigManager>>>()
+        .map(|config| {
+            let config = config.lock();
+            let settings = config.get_settings();
+            (settings.encoder_acceleration, settings.debounce_ms, settings.usb_read_mode)
+        })
+        .unwrap_or((Default::default(), DEBOUNCE_MS, Default::default()));
+
+    // The reader only moves packets off the USB endpoint; parsing, actions
+    // and emits happen on the event thread, so a slow consumer can't stall reads
+    let queue = Arc::new(PacketQueue::default());
+    let reader_queue = Arc::clone(&queue);
+    let reader_app = app.clone();
+    let reader_manager = Arc::clone(manager);
 
     std::thread::spawn(move || {
         log::info!("Event polling thread started with dedicated handle");
@@ -148,109 +365,590 @@ pub fn connect_device(
             Err(e) => log::warn!("Test read error: {}", e),
         }
 
-        while POLLING_ACTIVE.load(Ordering::SeqCst) {
+        *POLLING_CONTEXT.lock() = Some((session, polling_handle.context().clone()));
+        let mut reader = UsbReader::open(&polling_handle, EP_IN, read_mode, buf.len(), POLL_TIMEOUT);
+        log::info!("Reading device events with {:?} USB transfers", reader.mode());
+
+        let mut lost = false;
+        while POLLING_ACTIVE.load(Ordering::SeqCst) && POLLING_SESSION.load(Ordering::SeqCst) == session {
             // Direct USB read - no mutex needed
-            match polling_handle.read_interrupt(EP_IN, &mut buf, Duration::from_millis(100)) {
+            match reader.read(&mut buf) {
                 Ok(n) if n > 0 => {
-                    log::debug!("Read {} bytes from device: {:02X?}", n, &buf[..n.min(16)]);
+                    log::debug!(target: USB_LOG_TARGET, "Read {} bytes from device: {:02X?}", n, &buf[..n.min(16)]);
+                    reader_queue.push(buf[..n].to_vec());
+                }
+                Ok(_) => {
+                    // 0 bytes read, continue polling
+                }
+                Err(rusb::Error::Timeout) => {
+                    // Timeout (or a wake-up to check the flags) is normal, continue polling
+                }
+                Err(rusb::Error::NoDevice) => {
+                    log::warn!("Device connection lost");
+                    lost = true;
+                    break;
+                }
+                Err(e) => {
+                    log::warn!("Polling read error: {} - continuing...", e);
+                }
+            }
+        }
+        // Cancel the pending transfer before the handle is released
+        drop(reader);
+        {
+            // Leave the slot alone if a newer connection has taken it
+            let mut context = POLLING_CONTEXT.lock();
+            if context.as_ref().is_some_and(|(owner, _)| *owner == session) {
+                *context = None;
+            }
+        }
+        reader_queue.close();
+        // A newer connection may already be polling by the time this one fails
+        if lost && POLLING_SESSION.load(Ordering::SeqCst) == session {
+            POLLING_ACTIVE.store(false, Ordering::SeqCst);
+            connection_lost(&reader_app, &reader_manager);
+        }
+
+        // Release the interface when stopping
+        if let Err(e) = polling_handle.release_interface(crate::hid::types::VENDOR_INTERFACE) {
+            log::warn!("Failed to release polling interface: {}", e);
+        }
+        log::info!("Event polling thread stopped");
+    });
+
+    std::thread::spawn(move || {
+        let mut accelerator = EncoderAccelerator::new(curve);
+        let mut long_press = LongPressDetector::default();
+        let mut debouncer = Debouncer::new(debounce_ms);
+        let mut auto_repeat = AutoRepeat::new();
+        let mut dimmer = IdleDimmer::new();
+
+        loop {
+            // Wake up in time to report a long press even if no packet arrives
+            let timeout = long_press
+                .next_deadline()
+                .map_or(POLL_TIMEOUT, |deadline| deadline.clamp(Duration::from_millis(1), POLL_TIMEOUT));
+
+            match queue.pop_timeout(timeout) {
+                Next::Packet(packet) => {
+                    // A reply to a raw command goes to `send_raw_packet`; it
+                    // carries no event, so the parse below skips it
+                    if RAW_REPLY.offer(&packet) {
+                        log::debug!(target: USB_LOG_TARGET, "Routed raw command reply");
+                    }
                     // Parse ACK packet for events
-                    if let Some(raw_event) = parse_ack_packet(&buf[..n]) {
-                        log::debug!("Parsed raw event: id=0x{:02X}, state=0x{:02X}", raw_event.event_id, raw_event.state);
-                        if let Some(device_event) = raw_event.parse() {
+                    if let Some(raw_event) = parse_ack_packet(&packet) {
+                        log::debug!(target: USB_LOG_TARGET, "Parsed raw event: id=0x{:02X}, state=0x{:02X}", raw_event.event_id, raw_event.state);
+                        if !debouncer.accept(&raw_event) {
+                            log::debug!(target: USB_LOG_TARGET, "Dropped bounced event id=0x{:02X}", raw_event.event_id);
+                        } else if let Some(device_event) = parse_event(&app_clone, &raw_event).map(|e| accelerator.apply(e)) {
                             log::info!(">>> Device event: {:?}", device_event);
+                            deliver_capture(&device_event);
 
-                            // Get current timestamp
-                            let timestamp = std::time::SystemTime::now()
-                                .duration_since(std::time::UNIX_EPOCH)
-                                .map(|d| d.as_millis() as u64)
-                                .unwrap_or(0);
-
-                            // Emit appropriately typed event to frontend
-                            match &device_event {
-                                DeviceEvent::Button { index, button_type, event_type } => {
-                                    let payload = ButtonEventPayload {
-                                        event_type: match event_type {
-                                            ButtonEventType::Press => "press".to_string(),
-                                            ButtonEventType::Release => "release".to_string(),
-                                            ButtonEventType::LongPress => "longPress".to_string(),
-                                        },
-                                        button_index: *index,
-                                        button_type: match button_type {
-                                            ButtonType::Lcd => "lcd".to_string(),
-                                            ButtonType::Physical => "normal".to_string(),
-                                        },
-                                        timestamp,
-                                    };
-
-                                    let event_name = match event_type {
-                                        ButtonEventType::Press | ButtonEventType::LongPress => "device:buttonPress",
-                                        ButtonEventType::Release => "device:buttonRelease",
-                                    };
-
-                                    if let Err(e) = app_clone.emit(event_name, &payload) {
-                                        log::warn!("Failed to emit {}: {}", event_name, e);
-                                    }
+                            if dimmer.on_activity() {
+                                let (_, _, brightness) = dim_settings(&app_clone);
+                                log::debug!("Waking display to brightness {}", brightness);
+                                apply_brightness(&manager_clone, brightness);
+                            }
+
+                            // Flash on the physical press, before long-press resolution delays it
+                            if let DeviceEvent::Button { index, button_type: ButtonType::Lcd, event_type: ButtonEventType::Press } = device_event {
+                                if press_feedback_enabled(&app_clone) {
+                                    flash_button(Arc::clone(&manager_clone), index);
                                 }
-                                DeviceEvent::Encoder { encoder_type, event_type } => {
-                                    let encoder_index = match encoder_type {
-                                        EncoderType::Main => 0u8,
-                                        EncoderType::Side1 => 1u8,
-                                        EncoderType::Side2 => 2u8,
-                                    };
-
-                                    let payload = EncoderEventPayload {
-                                        event_type: match event_type {
-                                            EncoderEventType::RotateCW => "rotateCW".to_string(),
-                                            EncoderEventType::RotateCCW => "rotateCCW".to_string(),
-                                            EncoderEventType::Press => "press".to_string(),
-                                            EncoderEventType::Release => "release".to_string(),
-                                            EncoderEventType::LongPress => "longPress".to_string(),
-                                        },
-                                        encoder_index,
-                                        delta: match event_type {
-                                            EncoderEventType::RotateCW => Some(1),
-                                            EncoderEventType::RotateCCW => Some(-1),
-                                            _ => None,
-                                        },
-                                        timestamp,
-                                    };
-
-                                    let event_name = match event_type {
-                                        EncoderEventType::RotateCW | EncoderEventType::RotateCCW => "device:encoderRotate",
-                                        EncoderEventType::Press | EncoderEventType::Release | EncoderEventType::LongPress => "device:encoderPress",
-                                    };
-
-                                    if let Err(e) = app_clone.emit(event_name, &payload) {
-                                        log::warn!("Failed to emit {}: {}", event_name, e);
+                            }
+
+                            // A repeating button skips long-press detection so its
+                            // press goes out at once; the repeat then starts and
+                            // stops on the events the detector passes on
+                            let mut repeat = repeat_binding(&app_clone, &device_event);
+                            let events = if repeat.is_some() {
+                                long_press.on_event_immediate(device_event)
+                            } else {
+                                long_press.on_event(device_event)
+                            };
+                            for event in events {
+                                emit_device_event(&app_clone, &event);
+                                if let DeviceEvent::Button { index, button_type, event_type } = event {
+                                    match event_type {
+                                        ButtonEventType::Press => {
+                                            if let Some((action, config)) = repeat.take() {
+                                                let app = app_clone.clone();
+                                                auto_repeat.start(button_type, index, config, move || {
+                                                    run_repeat(app.clone(), action.clone(), event.clone())
+                                                });
+                                            }
+                                        }
+                                        ButtonEventType::Release => auto_repeat.stop(button_type, index),
+                                        ButtonEventType::LongPress => {}
                                     }
                                 }
                             }
                         }
                     }
                 }
-                Ok(0) => {
-                    // 0 bytes read, continue polling
+                Next::Timeout => {}
+                Next::Closed => break,
+            }
+
+            let dropped = queue.take_dropped();
+            if dropped > 0 {
+                log::warn!("Event queue full, dropped {} packet(s)", dropped);
+            }
+
+            for event in long_press.poll() {
+                log::info!(">>> Device event: {:?}", event);
+                emit_device_event(&app_clone, &event);
+            }
+
+            let (dim_after, dim_brightness, brightness) = dim_settings(&app_clone);
+            if dimmer.should_dim(dim_after) {
+                log::debug!("Dimming idle display to brightness {}", dim_brightness);
+                apply_brightness(&manager_clone, dim_brightness.min(brightness));
+            }
+        }
+
+        auto_repeat.stop_all();
+        log::info!("Event handling thread stopped");
+    });
+
+    Ok(result)
+}
+
+/// Idle-dimming settings: (dim after, dimmed level, configured level)
+///
+/// Read fresh each time so a wake restores the brightness configured now.
+fn dim_settings(app: &AppHandle) -> (Option<Duration>, u8, u8) {
+    let settings = app
+        .try_state::<Arc<Mutex<ConfigManager>>>()
+        .map(|config| config.lock().get_settings().clone())
+        .unwrap_or_default();
+    (
+        settings.dim_after_ms.map(Duration::from_millis),
+        settings.dim_brightness,
+        settings.brightness,
+    )
+}
+
+/// Set the display brightness from a background thread, logging failures
+pub(crate) fn apply_brightness(manager: &Mutex<HidManager>, level: u8) {
+    let mut mgr = manager.lock();
+    let result = mgr
+        .reopen_for_commands()
+        .and_then(|_| SoomfonProtocol::new(&mgr).set_brightness(level));
+    if let Err(e) = result {
+        log::warn!("Failed to set brightness to {}: {}", level, e);
+    }
+}
+
+/// Hand an event to a pending `capture_next_event`, if one is armed
+///
+/// The event is still emitted as usual.
+fn deliver_capture(event: &DeviceEvent) {
+    if let Some(capture) = EVENT_CAPTURE.lock().take() {
+        let _ = capture.send(event.clone());
+    }
+}
+
+/// Send the connect packet every `KEEPALIVE_INTERVAL_MS` while connected
+///
+/// Some units stop reporting events after a long idle period without it.
+/// The polling thread owns the read handle, so each keepalive goes through the
+/// command handle (reopened if needed) under a short-lived manager lock. The
+/// thread stops when polling stops or a new connection replaces this one.
+fn spawn_keepalive(manager: Arc<Mutex<HidManager>>, session: u64) {
+    std::thread::spawn(move || {
+        log::debug!("Keepalive thread started");
+        let interval = Duration::from_millis(KEEPALIVE_INTERVAL_MS);
+        let mut last_sent = std::time::Instant::now();
+
+        let active = || {
+            POLLING_ACTIVE.load(Ordering::SeqCst) && POLLING_SESSION.load(Ordering::SeqCst) == session
+        };
+        while active() {
+            std::thread::sleep(KEEPALIVE_CHECK_INTERVAL);
+            if last_sent.elapsed() < interval || !active() {
+                continue;
+            }
+            last_sent = std::time::Instant::now();
+
+            let mut mgr = manager.lock();
+            if !mgr.is_connected() {
+                break;
+            }
+            // Reopen handle if it was transferred to polling thread
+            if let Err(e) = mgr.reopen_for_commands().and_then(|_| mgr.send_keepalive()) {
+                log::warn!("Keepalive failed: {}", e);
+            }
+        }
+        log::debug!("Keepalive thread stopped");
+    });
+}
+
+/// Emit a device event to the frontend as its typed payload
+///
+/// Buttons go out as `device:buttonPress` (press and long press) or
+/// `device:buttonRelease`; encoders as `device:encoderRotate` or `device:encoderPress`;
+/// unmapped event IDs as `device:unknownEvent`.
+fn emit_device_event(app: &AppHandle, device_event: &DeviceEvent) {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+
+    match device_event {
+        DeviceEvent::Button { index, button_type, event_type } => {
+            let payload = ButtonEventPayload {
+                event_type: match event_type {
+                    ButtonEventType::Press => "press".to_string(),
+                    ButtonEventType::Release => "release".to_string(),
+                    ButtonEventType::LongPress => "longPress".to_string(),
+                },
+                button_index: *index,
+                button_type: match button_type {
+                    ButtonType::Lcd => "lcd".to_string(),
+                    ButtonType::Physical => "normal".to_string(),
+                },
+                timestamp,
+            };
+
+            let event_name = match event_type {
+                ButtonEventType::Press | ButtonEventType::LongPress => "device:buttonPress",
+                ButtonEventType::Release => "device:buttonRelease",
+            };
+
+            if let Err(e) = app.emit(event_name, &payload) {
+                log::warn!("Failed to emit {}: {}", event_name, e);
+            }
+        }
+        DeviceEvent::Encoder { encoder_type, event_type, velocity } => {
+            let encoder_index = match encoder_type {
+                EncoderType::Main => 0u8,
+                EncoderType::Side1 => 1u8,
+                EncoderType::Side2 => 2u8,
+            };
+
+            let payload = EncoderEventPayload {
+                event_type: match event_type {
+                    EncoderEventType::RotateCW => "rotateCW".to_string(),
+                    EncoderEventType::RotateCCW => "rotateCCW".to_string(),
+                    EncoderEventType::Press => "press".to_string(),
+                    EncoderEventType::Release => "release".to_string(),
+                    EncoderEventType::LongPress => "longPress".to_string(),
+                },
+                encoder_index,
+                delta: match event_type {
+                    EncoderEventType::RotateCW => Some(*velocity as i32),
+                    EncoderEventType::RotateCCW => Some(-(*velocity as i32)),
+                    _ => None,
+                },
+                timestamp,
+            };
+
+            let event_name = match event_type {
+                EncoderEventType::RotateCW | EncoderEventType::RotateCCW => "device:encoderRotate",
+                EncoderEventType::Press | EncoderEventType::Release | EncoderEventType::LongPress => "device:encoderPress",
+            };
+
+            if let Err(e) = app.emit(event_name, &payload) {
+                log::warn!("Failed to emit {}: {}", event_name, e);
+            }
+        }
+        DeviceEvent::Unknown { event_id, state } => {
+            log::debug!("Unmapped event ID 0x{:02X} (state 0x{:02X})", event_id, state);
+            let payload = UnknownEventPayload { event_id: *event_id, state: *state, timestamp };
+            if let Err(e) = app.emit("device:unknownEvent", &payload) {
+                log::warn!("Failed to emit device:unknownEvent: {}", e);
+            }
+        }
+    }
+}
+
+/// Parse a raw event, consulting the event map in the app settings first
+///
+/// Read per event so a newly saved mapping applies without reconnecting.
+fn parse_event(app: &AppHandle, raw_event: &RawEvent) -> Option<DeviceEvent> {
+    match app.try_state::<Arc<Mutex<ConfigManager>>>() {
+        Some(config) => raw_event.parse_with(&config.lock().get_settings().event_map),
+        None => raw_event.parse(),
+    }
+}
+
+/// Whether press feedback is turned on in the app settings
+fn press_feedback_enabled(app: &AppHandle) -> bool {
+    app.try_state::<Arc<Mutex<ConfigManager>>>()
+        .is_some_and(|config| config.lock().get_settings().press_feedback)
+}
+
+/// Action and repeat settings for a button press, if the button repeats
+///
+/// Resolved through the event binder like `dispatch_event` does, so the
+/// active workspace and shift layer pick the action that repeats.
+fn repeat_binding(app: &AppHandle, press: &DeviceEvent) -> Option<(Action, RepeatConfig)> {
+    if !matches!(press, DeviceEvent::Button { event_type: ButtonEventType::Press, .. }) {
+        return None;
+    }
+    let profile_id = app
+        .try_state::<Arc<Mutex<ConfigManager>>>()?
+        .lock()
+        .get_active_profile_id()?
+        .to_string();
+    let profile = app.try_state::<Arc<Mutex<ProfileManager>>>()?.lock().get(&profile_id)?.clone();
+    let binder = app.try_state::<Arc<Mutex<EventBinder>>>()?;
+    let mut binder = binder.lock();
+    binder.refresh_profile(profile);
+    binder.repeat_for_event(press)
+}
+
+/// Run one repeat of a held button's action and record it in the history
+/// and usage statistics
+async fn run_repeat(app: AppHandle, action: Action, press: DeviceEvent) {
+    let integrations = current_integrations(&app);
+
+    let result = crate::actions::execute_action_with_config(&action, &integrations, false).await;
+    if !result.success {
+        log::warn!("Repeated action failed: {:?}", result.error);
+    }
+    if let Some(engine) = app.try_state::<Arc<Mutex<ActionEngine>>>() {
+        engine.lock().record_execution(&action, &result);
+    }
+    let profile_id = app
+        .try_state::<Arc<Mutex<ConfigManager>>>()
+        .and_then(|config| config.lock().get_active_profile_id().map(str::to_string));
+    if let Some(profile_id) = profile_id {
+        record_usage(&app, &profile_id, &press);
+    }
+}
+
+/// Briefly show an inverted copy of a button's current image, then restore it
+///
+/// Runs on its own thread so the polling loop isn't held up. Buttons with no
+/// cached image are skipped, so nothing is uploaded for blank buttons.
+fn flash_button(manager: Arc<Mutex<HidManager>>, index: u8) {
+    std::thread::spawn(move || {
+        let original = match manager.lock().cached_button_image(index) {
+            Some(data) => data.to_vec(),
+            None => return,
+        };
+
+        let feedback = match create_feedback_image(&original) {
+            Ok(data) => data,
+            Err(e) => {
+                log::warn!("Failed to create feedback image for button {}: {}", index, e);
+                return;
+            }
+        };
+
+        if let Err(e) = upload_button_image(&manager, index, &feedback) {
+            log::warn!("Failed to show press feedback on button {}: {}", index, e);
+            return;
+        }
+
+        std::thread::sleep(Duration::from_millis(PRESS_FEEDBACK_MS));
+
+        // Don't clobber an image that was set while the feedback was showing
+        if manager.lock().cached_button_image(index) != Some(original.as_slice()) {
+            return;
+        }
+        if let Err(e) = upload_button_image(&manager, index, &original) {
+            log::warn!("Failed to restore button {} after press feedback: {}", index, e);
+        }
+    });
+}
+
+/// Upload a JPEG to a button without touching the image cache
+fn upload_button_image(
+    manager: &Arc<Mutex<HidManager>>,
+    index: u8,
+    jpeg_data: &[u8],
+) -> Result<(), String> {
+    let mut mgr = manager.lock();
+    mgr.reopen_for_commands().map_err(|e| e.user_message())?;
+    let protocol = SoomfonProtocol::new(&mgr);
+    protocol.set_button_image(index, jpeg_data).map_err(|e| e.user_message())
+}
+
+/// React to a device being plugged in or unplugged
+///
+/// Emits `device:attached` / `device:detached` with the device info. If the
+/// connected device is unplugged, polling stops and `device:disconnected` is
+/// emitted; when it comes back (possibly on a different port) and
+/// auto-reconnect is enabled, it is reconnected at its new path and its
+/// brightness and button images are restored.
+pub fn handle_hotplug_event(app: &AppHandle, manager: &Arc<Mutex<HidManager>>, event: &HotplugEvent) {
+    match event {
+        HotplugEvent::Detached(info) => {
+            if let Err(e) = app.emit("device:detached", info) {
+                log::warn!("Failed to emit device:detached event: {}", e);
+            }
+
+            let was_connected = manager
+                .lock()
+                .get_device_info()
+                .is_some_and(|current| current.path == info.path);
+            if was_connected {
+                stop_polling();
+                connection_lost(app, manager);
+            }
+        }
+        HotplugEvent::Attached(info) => {
+            if let Err(e) = app.emit("device:attached", info) {
+                log::warn!("Failed to emit device:attached event: {}", e);
+            }
+
+            // Try now rather than waiting out the backoff
+            if manager.lock().should_reconnect_to(info) {
+                if let Some(wake) = RECONNECT_WAKE.lock().as_ref() {
+                    let _ = wake.send(());
                 }
-                Ok(_) => {
-                    // Shouldn't happen with the guard above
+            }
+        }
+    }
+}
+
+/// Drop a connection whose device went away and tell the frontend
+///
+/// Releases any held modifier keys, then starts the reconnect loop if
+/// auto-reconnect is on, otherwise emits `device:disconnected`. Does nothing if the connection was already
+/// dropped, so the hot-plug monitor and the polling thread can both report
+/// the same loss.
+fn connection_lost(app: &AppHandle, manager: &Arc<Mutex<HidManager>>) {
+    let reconnecting = {
+        let mut mgr = manager.lock();
+        if mgr.get_device_info().is_none() {
+            return;
+        }
+        mgr.handle_unplugged();
+        mgr.is_reconnecting()
+    };
+
+    // A long press cut off by the unplug may have left modifiers held
+    if let Err(e) = crate::actions::handlers::keyboard::release_all_modifiers() {
+        log::warn!("Failed to release modifier keys: {}", e);
+    }
+
+    if reconnecting {
+        spawn_reconnect_loop(app.clone(), Arc::clone(manager));
+    } else if let Err(e) = app.emit("device:disconnected", ()) {
+        log::warn!("Failed to emit device:disconnected event: {}", e);
+    }
+}
+
+/// Stop a running reconnect loop, e.g. because the user took over
+fn stop_reconnecting() {
+    RECONNECT_WAKE.lock().take();
+}
+
+/// Retry the lost device with exponential backoff until it is back
+///
+/// Emits `device:reconnecting` before each wait with the attempt number.
+/// A hot-plug attach of the device cuts the wait short. The loop ends when
+/// the device is reconnected, the state leaves `Reconnecting` (explicit
+/// disconnect or auto-reconnect turned off), `stop_reconnecting` is called,
+/// or `max_reconnect_attempts` is used up, in which case the state becomes
+/// `Error` and `device:disconnected` is emitted.
+fn spawn_reconnect_loop(app: AppHandle, manager: Arc<Mutex<HidManager>>) {
+    let (wake_tx, wake_rx) = std::sync::mpsc::channel();
+    *RECONNECT_WAKE.lock() = Some(wake_tx);
+
+    std::thread::spawn(move || {
+        let mut backoff = Backoff::default();
+        loop {
+            let max_attempts = app
+                .try_state::<Arc<Mutex<ConfigManager>>>()
+                .map_or(0, |config| config.lock().get_settings().max_reconnect_attempts);
+            let delay = backoff.next_delay();
+            let payload = ReconnectingPayload {
+                attempt: manager.lock().reconnect_attempts() + 1,
+                max_attempts,
+                delay_ms: delay.as_millis() as u64,
+            };
+            if let Err(e) = app.emit("device:reconnecting", &payload) {
+                log::warn!("Failed to emit device:reconnecting event: {}", e);
+            }
+
+            if let Err(std::sync::mpsc::RecvTimeoutError::Disconnected) = wake_rx.recv_timeout(delay) {
+                log::info!("Reconnect cancelled");
+                return;
+            }
+
+            // Look for the lost unit, which may be back on another port
+            let target = {
+                let mut mgr = manager.lock();
+                if !mgr.is_reconnecting() {
+                    return;
                 }
-                Err(rusb::Error::Timeout) => {
-                    // Timeout is normal, continue polling
+                mgr.enumerate_devices()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .find(|info| mgr.should_reconnect_to(info))
+            };
+
+            let result = match target {
+                Some(info) => {
+                    log::info!("Reconnecting to SOOMFON device at {} (attempt {})", info.path, payload.attempt);
+                    reconnect_and_restore(&app, &manager, &info.path)
                 }
+                None => Err(HidError::DeviceNotFound),
+            };
+            match result {
+                Ok(_) => return,
                 Err(e) => {
-                    log::warn!("Polling read error: {} - continuing...", e);
+                    log::warn!("Auto-reconnect attempt {} failed: {}", payload.attempt, e);
+                    if manager.lock().reconnect_failed(max_attempts) {
+                        if let Err(e) = app.emit("device:disconnected", ()) {
+                            log::warn!("Failed to emit device:disconnected event: {}", e);
+                        }
+                        connection_failed(&app, e);
+                        return;
+                    }
                 }
             }
         }
+    });
+}
 
-        // Release the interface when stopping
-        if let Err(e) = polling_handle.release_interface(crate::hid::types::VENDOR_INTERFACE) {
-            log::warn!("Failed to release polling interface: {}", e);
+/// Reconnect to the device at `path` and put back what it was showing
+///
+/// The device comes back blank after being unplugged, so once it is connected
+/// and initialized the saved brightness is re-applied and the active profile's
+/// current workspace is rendered. With no active profile, all buttons are
+/// blanked instead. Emits `device:restored` when done; a failed restore is
+/// logged but does not undo the reconnect.
+fn reconnect_and_restore(
+    app: &AppHandle,
+    manager: &Arc<Mutex<HidManager>>,
+    path: &str,
+) -> Result<DeviceInfo, HidError> {
+    let info = connect_and_poll(app.clone(), manager, Some(path))?;
+
+    let brightness = app
+        .try_state::<Arc<Mutex<ConfigManager>>>()
+        .map(|config| config.lock().get_brightness())
+        .unwrap_or(AppSettings::default().brightness);
+    {
+        let mut mgr = manager.lock();
+        // Reopen handle if it was transferred to polling thread
+        let restored = mgr
+            .reopen_for_commands()
+            .and_then(|_| SoomfonProtocol::new(&mgr).set_brightness(brightness));
+        if let Err(e) = restored {
+            log::warn!("Failed to restore brightness: {}", e);
         }
-        log::info!("Event polling thread stopped");
-    });
+    }
 
-    Ok(result)
+    let (profile_id, buttons) = active_buttons(app);
+    if let Err(e) = render_buttons(manager, &buttons) {
+        log::warn!("Failed to restore button images: {}", e);
+    }
+    feedback::sync(&buttons, &current_integrations(app));
+
+    log::info!("Restored device state (profile: {:?})", profile_id);
+    let payload = DeviceRestoredPayload { profile_id, brightness };
+    if let Err(e) = app.emit("device:restored", &payload) {
+        log::warn!("Failed to emit device:restored event: {}", e);
+    }
+    Ok(info)
 }
 
 /// Disconnect from the device
@@ -261,13 +959,16 @@ pub fn disconnect_device(
     manager: State<Arc<Mutex<HidManager>>>,
 ) -> Result<(), String> {
     // Stop the polling thread first
-    POLLING_ACTIVE.store(false, Ordering::SeqCst);
+    stop_polling();
+    stop_reconnecting();
+    feedback::stop_all();
 
-    // Give the polling thread time to stop
-    std::thread::sleep(Duration::from_millis(150));
+    manager.lock().disconnect();
 
-    let mut mgr = manager.lock();
-    mgr.disconnect();
+    // Don't leave modifiers held if a keyboard action was interrupted
+    if let Err(e) = crate::actions::handlers::keyboard::release_all_modifiers() {
+        log::warn!("Failed to release modifier keys: {}", e);
+    }
 
     // Emit device disconnected event
     if let Err(e) = app.emit("device:disconnected", ()) {
@@ -277,6 +978,64 @@ pub fn disconnect_device(
     Ok(())
 }
 
+/// Put the device to sleep before the app exits
+///
+/// Stops event polling, then sends the shutdown sequence (CLE/CLB/HAH) and
+/// releases the interface via `HidManager::disconnect`. That runs on its own
+/// thread so a hung USB write or a command holding the manager lock can
+/// delay quitting by at most `EXIT_SHUTDOWN_TIMEOUT`.
+pub fn shutdown_on_exit(app: &AppHandle) {
+    let Some(manager) = app.try_state::<Arc<Mutex<HidManager>>>() else {
+        return;
+    };
+    stop_reconnecting();
+    feedback::stop_all();
+    if POLLING_ACTIVE.load(Ordering::SeqCst) {
+        stop_polling();
+    }
+
+    let manager = Arc::clone(&manager);
+    let (done_tx, done_rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        manager.lock().disconnect();
+        let _ = done_tx.send(());
+    });
+    if done_rx.recv_timeout(EXIT_SHUTDOWN_TIMEOUT).is_err() {
+        log::warn!("Device shutdown timed out, exiting anyway");
+    }
+}
+
+/// Reset the device to its factory display state, keeping the connection
+///
+/// Emits `device:resetting` before the reset and `device:reset` with the new
+/// device status once the device has been re-initialized.
+#[tauri::command]
+pub fn reset_device(
+    app: AppHandle,
+    manager: State<Arc<Mutex<HidManager>>>,
+) -> Result<DeviceStatus, String> {
+    let mut mgr = manager.lock();
+    // Reopen handle if it was transferred to polling thread
+    mgr.reopen_for_commands().map_err(|e| e.user_message())?;
+
+    if let Err(e) = app.emit("device:resetting", ()) {
+        log::warn!("Failed to emit device:resetting event: {}", e);
+    }
+
+    mgr.reset().map_err(|e| format!("Failed to reset device: {}", e))?;
+
+    let status = DeviceStatus {
+        state: mgr.get_connection_state(),
+        device_info: mgr.get_device_info().cloned(),
+    };
+
+    if let Err(e) = app.emit("device:reset", &status) {
+        log::warn!("Failed to emit device:reset event: {}", e);
+    }
+
+    Ok(status)
+}
+
 /// Get current device status
 #[tauri::command]
 pub fn get_device_status(
@@ -289,6 +1048,20 @@ pub fn get_device_status(
     }
 }
 
+/// Get the button/encoder layout of the connected device
+///
+/// Falls back to the standard SOOMFON layout when no device is connected.
+#[tauri::command]
+pub fn get_device_capabilities(
+    manager: State<Arc<Mutex<HidManager>>>,
+) -> DeviceCapabilities {
+    let manager = manager.lock();
+    let product = manager
+        .get_device_info()
+        .and_then(|info| info.product.as_deref());
+    DeviceCapabilities::for_product(product)
+}
+
 /// Set display brightness
 #[tauri::command]
 pub fn set_brightness(
@@ -297,9 +1070,9 @@ pub fn set_brightness(
 ) -> Result<(), String> {
     let mut manager = manager.lock();
     // Reopen handle if it was transferred to polling thread
-    manager.reopen_for_commands().map_err(|e| e.to_string())?;
+    manager.reopen_for_commands().map_err(|e| e.user_message())?;
     let protocol = SoomfonProtocol::new(&manager);
-    protocol.set_brightness(level).map_err(|e| e.to_string())
+    protocol.set_brightness(level).map_err(|e| e.user_message())
 }
 
 /// Set button image from file path, URL, or base64 data
@@ -308,10 +1081,17 @@ pub fn set_brightness(
 /// - File paths: `file:///path/to/image.png` or `/path/to/image.png` or `C:\path\to\image.png`
 /// - Data URLs: `data:image/png;base64,...`
 /// - Raw base64: `iVBORw0KGgoAAAANS...`
+///
+/// An optional `badge` (e.g. an unread count) is drawn over the image.
+///
+/// The upload is skipped if the button already shows the identical processed
+/// image; pass `force: true` to send it anyway.
 #[tauri::command]
 pub fn set_button_image(
     index: u8,
     image_data: String,
+    badge: Option<BadgeSpec>,
+    force: Option<bool>,
     manager: State<Arc<Mutex<HidManager>>>,
 ) -> Result<(), String> {
     log::info!("set_button_image called for button {} with source type: {}",
@@ -324,17 +1104,380 @@ pub fn set_button_image(
 
     let mut manager = manager.lock();
     // Reopen handle if it was transferred to polling thread
-    manager.reopen_for_commands().map_err(|e| e.to_string())?;
+    manager.reopen_for_commands().map_err(|e| e.user_message())?;
 
     // Process image from any source (file path, URL, or base64)
-    let options = ImageOptions::default();
+    let options = ImageOptions {
+        badge,
+        ..Default::default()
+    };
     let jpeg_data = process_image_source(&image_data, &options)?;
 
     log::info!("Processed image: {} bytes JPEG for button {}", jpeg_data.len(), index);
 
+    if !force.unwrap_or(false) && manager.is_button_image_current(index, &jpeg_data) {
+        log::debug!("Button {} already shows this image, skipping upload", index);
+        return Ok(());
+    }
+
     // Send to device
     let protocol = SoomfonProtocol::new(&manager);
-    protocol.set_button_image(index, &jpeg_data).map_err(|e| e.to_string())
+    protocol.set_button_image(index, &jpeg_data).map_err(|e| e.user_message())?;
+    manager.cache_button_image(index, jpeg_data);
+    Ok(())
+}
+
+/// Set several button images at once
+///
+/// All images are processed before the device is locked, then uploaded in a
+/// single burst so a profile switch doesn't show partially updated buttons.
+/// Buttons already showing the identical image are left alone.
+#[tauri::command]
+pub fn set_buttons(
+    buttons: Vec<ButtonImageInput>,
+    manager: State<Arc<Mutex<HidManager>>>,
+) -> Result<(), String> {
+    let options = ImageOptions::default();
+    let images = buttons
+        .iter()
+        .map(|button| {
+            process_image_source(&button.image_data, &options)
+                .map(|jpeg| (button.index, jpeg))
+                .map_err(|e| format!("Button {}: {}", button.index, e))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let mut manager = manager.lock();
+    // Reopen handle if it was transferred to polling thread
+    manager.reopen_for_commands().map_err(|e| e.user_message())?;
+
+    let images: Vec<(u8, Vec<u8>)> = images
+        .into_iter()
+        .filter(|(index, jpeg)| !manager.is_button_image_current(*index, jpeg))
+        .collect();
+    if images.is_empty() {
+        log::debug!("All {} buttons already up to date, skipping upload", buttons.len());
+        return Ok(());
+    }
+
+    let protocol = SoomfonProtocol::new(&manager);
+    protocol.set_button_images_batch(&images).map_err(|e| e.user_message())?;
+    for (index, jpeg) in images {
+        manager.cache_button_image(index, jpeg);
+    }
+    Ok(())
+}
+
+/// Fill a button with a flat color
+///
+/// Accepts `#RGB`, `#RRGGBB` or a CSS color name (e.g. `"orange"`).
+#[tauri::command]
+pub fn set_button_color(
+    index: u8,
+    color: String,
+    manager: State<Arc<Mutex<HidManager>>>,
+) -> Result<(), String> {
+    let jpeg_data = create_solid_color_hex(&color)?;
+
+    let mut manager = manager.lock();
+    // Reopen handle if it was transferred to polling thread
+    manager.reopen_for_commands().map_err(|e| e.user_message())?;
+
+    let protocol = SoomfonProtocol::new(&manager);
+    protocol.set_button_image(index, &jpeg_data).map_err(|e| e.user_message())?;
+    manager.cache_button_image(index, jpeg_data);
+    Ok(())
+}
+
+/// Play an animated GIF on a button
+///
+/// Frames cycle at the GIF's own timing (capped at the rate an upload can
+/// keep up with) until the button gets another image, is cleared, or the
+/// device disconnects. Still images are simply shown.
+#[tauri::command]
+pub fn set_button_animation(
+    index: u8,
+    image_data: String,
+    manager: State<Arc<Mutex<HidManager>>>,
+) -> Result<(), String> {
+    let frames = process_animated(&read_image_source(&image_data)?)?;
+    log::info!("Playing {}-frame animation on button {}", frames.len(), index);
+    animation::play(Arc::clone(&manager), index, frames).map_err(|e| e.user_message())
+}
+
+/// Send a raw CRT packet to the device and return its reply as hex
+///
+/// For protocol experimentation, mirroring `src/bin/init_test.rs`. Only
+/// available when `debug_mode` is enabled in the settings. The bytes are
+/// zero-padded (or truncated) to `CRT_PACKET_SIZE`, and every packet is
+/// logged at info level. Returns None if the device sends no reply.
+///
+/// While event polling runs, the polling thread owns the IN endpoint, so the
+/// reply is taken from the packets it reads (see `ReplySlot`) rather than
+/// read on the command handle.
+#[tauri::command]
+pub fn send_raw_packet(
+    bytes: Vec<u8>,
+    app: AppHandle,
+    manager: State<Arc<Mutex<HidManager>>>,
+) -> Result<Option<String>, String> {
+    let debug_mode = app
+        .try_state::<Arc<Mutex<ConfigManager>>>()
+        .is_some_and(|config| config.lock().get_settings().debug_mode);
+    if !debug_mode {
+        return Err("Raw packets require debug mode to be enabled".to_string());
+    }
+
+    if bytes.len() > CRT_PACKET_SIZE {
+        log::warn!(
+            "Raw packet truncated from {} to {} bytes",
+            bytes.len(),
+            CRT_PACKET_SIZE
+        );
+    }
+    let mut packet = [0u8; CRT_PACKET_SIZE];
+    let len = bytes.len().min(CRT_PACKET_SIZE);
+    packet[..len].copy_from_slice(&bytes[..len]);
+    log::info!("Sending raw packet: {}", format_hex(&packet[..len]));
+
+    let polling = POLLING_ACTIVE.load(Ordering::SeqCst);
+    // Armed before sending, so a fast reply can't slip past
+    let reply = polling.then(|| RAW_REPLY.expect());
+    let response = {
+        let mut manager = manager.lock();
+        // Reopen handle if it was transferred to polling thread
+        manager.reopen_for_commands().map_err(|e| e.user_message())?;
+
+        let protocol = SoomfonProtocol::new(&manager);
+        if let Err(e) = protocol.send_raw_command(&packet) {
+            RAW_REPLY.cancel();
+            return Err(e.user_message());
+        }
+        match reply {
+            Some(_) => None,
+            None => protocol.read_raw_response().map_err(|e| e.user_message())?,
+        }
+    };
+    // Wait without the manager locked, as the event thread may need it
+    let response = match reply {
+        Some(reply) => {
+            let response = reply.recv_timeout(RAW_REPLY_TIMEOUT).ok();
+            RAW_REPLY.cancel();
+            response
+        }
+        None => response,
+    };
+
+    let hex = response.map(|data| format_hex(&data));
+    match &hex {
+        Some(hex) => log::info!("Raw packet response: {}", hex),
+        None => log::info!("Raw packet got no response"),
+    }
+    Ok(hex)
+}
+
+/// Run the device diagnostic sequence and report which steps succeeded
+///
+/// Opens the device separately from the app's connection, so it must be
+/// disconnected first. Probes USB setup and HID requests and sends the init
+/// sequence, the same steps as `src/bin/init_test.rs`.
+#[tauri::command]
+pub async fn run_diagnostics(
+    manager: State<'_, Arc<Mutex<HidManager>>>,
+) -> Result<DiagnosticReport, String> {
+    if manager.lock().is_connected() {
+        return Err("Disconnect the device before running diagnostics".to_string());
+    }
+
+    tauri::async_runtime::spawn_blocking(|| {
+        let context = rusb::Context::new().map_err(|e| format!("Failed to create USB context: {}", e))?;
+        let (report, handle) = diagnostics::run(&context);
+        if let Some(handle) = handle {
+            let _ = handle.release_interface(crate::hid::types::VENDOR_INTERFACE);
+        }
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("Diagnostics failed: {}", e))?
+}
+
+/// Check that a unit works end to end, for support and CI
+///
+/// Enumerates, connects, initializes, reads the firmware version, shows each
+/// button's number for a moment and checks the event endpoint can be read,
+/// then disconnects. A connection the app already had is taken over and
+/// restored afterwards (including the active profile's buttons); otherwise
+/// the device is left disconnected and released.
+#[tauri::command]
+pub async fn self_test(app: AppHandle) -> Result<SelfTestReport, String> {
+    tauri::async_runtime::spawn_blocking(move || run_self_test(&app))
+        .await
+        .map_err(|e| format!("Self-test failed: {}", e))?
+}
+
+fn run_self_test(app: &AppHandle) -> Result<SelfTestReport, String> {
+    let manager = app
+        .try_state::<Arc<Mutex<HidManager>>>()
+        .ok_or("HID manager is not initialized")?
+        .inner()
+        .clone();
+    let mut report = SelfTestReport::default();
+
+    stop_reconnecting();
+    let previous = manager.lock().get_device_info().map(|info| info.path.clone());
+    if previous.is_some() {
+        stop_polling();
+        manager.lock().disconnect();
+    }
+
+    {
+        let mut mgr = manager.lock();
+        let ok = report.step("Enumerate", || {
+            let devices = mgr.enumerate_devices().map_err(|e| e.user_message())?;
+            if devices.is_empty() {
+                return Err("No SOOMFON device found".to_string());
+            }
+            Ok(format!("{} device(s) found", devices.len()))
+        }) && report.step("Connect", || {
+            let info = match &previous {
+                Some(path) => mgr.connect_by_path(path),
+                None => mgr.connect(),
+            }
+            .map_err(|e| e.user_message())?;
+            Ok(format!("Connected at {}", info.path))
+        }) && report.step("Initialize", || {
+            mgr.initialize().map_err(|e| e.user_message())?;
+            Ok("Initialized".to_string())
+        });
+
+        if ok {
+            report.step("Read firmware", || {
+                let info = mgr.get_device_info().ok_or("Device info missing")?;
+                info.firmware
+                    .as_ref()
+                    .map(|fw| fw.to_string())
+                    .or_else(|| info.firmware_version.clone())
+                    .ok_or_else(|| "Device did not report a firmware version".to_string())
+            });
+            report.step("Test pattern", || {
+                let protocol = SoomfonProtocol::new(&mgr);
+                for index in 0..LCD_BUTTON_COUNT {
+                    let pattern = create_number_image(index as u32)?;
+                    protocol.set_button_image(index, &pattern).map_err(|e| e.user_message())?;
+                }
+                std::thread::sleep(Duration::from_millis(SELF_TEST_PATTERN_MS));
+                protocol.clear_displays().map_err(|e| e.user_message())?;
+                Ok(format!("Showed a pattern on {} buttons", LCD_BUTTON_COUNT))
+            });
+            report.step("Event readiness", || {
+                if !mgr.is_initialized() {
+                    return Err("Device is not in event mode".to_string());
+                }
+                mgr.poll_event_timeout(SELF_TEST_READ_TIMEOUT).map_err(|e| e.user_message())?;
+                Ok("Event endpoint readable".to_string())
+            });
+        }
+
+        report.step("Disconnect", || {
+            mgr.disconnect();
+            Ok("Device released".to_string())
+        });
+    }
+
+    if let Some(path) = previous {
+        report.step("Restore connection", || {
+            reconnect_and_restore(app, &manager, &path).map_err(|e| e.user_message())?;
+            Ok(format!("Reconnected at {}", path))
+        });
+    }
+
+    Ok(report)
+}
+
+/// Render text onto a button and display it
+///
+/// The text is wrapped and centered on the button, so no image needs to be
+/// prepared by the frontend.
+#[tauri::command]
+pub fn set_button_text(
+    index: u8,
+    text: String,
+    style: Option<TextStyle>,
+    manager: State<Arc<Mutex<HidManager>>>,
+) -> Result<(), String> {
+    let jpeg_data = create_text_image(&text, &style.unwrap_or_default())?;
+
+    let mut manager = manager.lock();
+    // Reopen handle if it was transferred to polling thread
+    manager.reopen_for_commands().map_err(|e| e.user_message())?;
+
+    let protocol = SoomfonProtocol::new(&manager);
+    protocol.set_button_image(index, &jpeg_data).map_err(|e| e.user_message())?;
+    manager.cache_button_image(index, jpeg_data);
+    Ok(())
+}
+
+/// Briefly show a button's index on it, to tell which button is which
+///
+/// The number stays up for `IDENTIFY_MS`, then the button's previous image
+/// (from the image cache) comes back, or black if it had none.
+#[tauri::command]
+pub fn identify_button(
+    index: u8,
+    manager: State<Arc<Mutex<HidManager>>>,
+) -> Result<(), String> {
+    if index >= LCD_BUTTON_COUNT {
+        return Err(format!(
+            "Button index {} out of range (0-{})",
+            index,
+            LCD_BUTTON_COUNT - 1
+        ));
+    }
+    if !manager.lock().is_connected() {
+        return Err("Device not connected".to_string());
+    }
+
+    let highlight = create_number_image(index as u32)?;
+    upload_button_image(&manager, index, &highlight)?;
+
+    let manager = Arc::clone(&manager);
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(IDENTIFY_MS));
+
+        let previous = manager.lock().cached_button_image(index).map(<[u8]>::to_vec);
+        let restore = match previous {
+            Some(jpeg) => Ok(jpeg),
+            None => create_solid_color(0, 0, 0),
+        };
+        if let Err(e) = restore.and_then(|jpeg| upload_button_image(&manager, index, &jpeg)) {
+            log::warn!("Failed to restore button {} after identifying it: {}", index, e);
+        }
+    });
+    Ok(())
+}
+
+/// Wait for the next button or encoder event from the device (learn mode)
+///
+/// Returns the first event read within `timeout_ms`, or None on timeout.
+/// Events keep being emitted and bound actions keep running meanwhile.
+/// Arming a new capture cancels one still waiting, which then returns None.
+#[tauri::command]
+pub async fn capture_next_event(timeout_ms: u64) -> Option<DeviceEvent> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    *EVENT_CAPTURE.lock() = Some(tx);
+
+    let event = tokio::time::timeout(Duration::from_millis(timeout_ms), rx)
+        .await
+        .ok()
+        .and_then(Result::ok);
+    if event.is_none() {
+        // Disarm, unless a newer capture replaced ours
+        let mut capture = EVENT_CAPTURE.lock();
+        if capture.as_ref().is_some_and(|tx| tx.is_closed()) {
+            capture.take();
+        }
+    }
+    event
 }
 
 /// Clear a button display
@@ -345,9 +1488,47 @@ pub fn clear_button(
 ) -> Result<(), String> {
     let mut manager = manager.lock();
     // Reopen handle if it was transferred to polling thread
-    manager.reopen_for_commands().map_err(|e| e.to_string())?;
+    manager.reopen_for_commands().map_err(|e| e.user_message())?;
     let protocol = SoomfonProtocol::new(&manager);
-    protocol.clear_screen(index).map_err(|e| e.to_string())
+    protocol.clear_screen(index).map_err(|e| e.user_message())?;
+    manager.clear_cached_images(index);
+    Ok(())
+}
+
+/// Fill a button (or all six if `index` is None) with a solid color
+///
+/// `rgb` defaults to black, the same as `clear_button`, but the filled
+/// buttons keep the color as their cached image.
+#[tauri::command]
+pub fn clear_button_color(
+    index: Option<u8>,
+    rgb: Option<(u8, u8, u8)>,
+    manager: State<Arc<Mutex<HidManager>>>,
+) -> Result<(), String> {
+    if let Some(index) = index.filter(|&i| i >= LCD_BUTTON_COUNT) {
+        return Err(format!(
+            "Button index {} out of range (0-{})",
+            index,
+            LCD_BUTTON_COUNT - 1
+        ));
+    }
+
+    let mut manager = manager.lock();
+    // Reopen handle if it was transferred to polling thread
+    manager.reopen_for_commands().map_err(|e| e.user_message())?;
+    let protocol = SoomfonProtocol::new(&manager);
+    let jpeg_data = protocol
+        .clear_screen_color(index, rgb.unwrap_or((0, 0, 0)))
+        .map_err(|e| e.user_message())?;
+    match index {
+        Some(index) => manager.cache_button_image(index, jpeg_data),
+        None => {
+            for index in 0..LCD_BUTTON_COUNT {
+                manager.cache_button_image(index, jpeg_data.clone());
+            }
+        }
+    }
+    Ok(())
 }
 
 /// Enumerate available SOOMFON devices
@@ -356,5 +1537,5 @@ pub fn enumerate_devices(
     manager: State<Arc<Mutex<HidManager>>>,
 ) -> Result<Vec<DeviceInfo>, String> {
     let mut manager = manager.lock();
-    manager.enumerate_devices().map_err(|e| e.to_string())
+    manager.enumerate_devices().map_err(|e| e.user_message())
 }
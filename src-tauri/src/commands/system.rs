@@ -2,31 +2,154 @@
 //!
 //! Tauri commands for system-level operations including auto-launch and file dialogs.
 
+use crate::commands::config::emit_config_changed;
+use crate::config::manager::ConfigManager;
+use crate::config::profiles::ProfileManager;
+use crate::hid::manager::HidManager;
+use crate::system::audio::{self, AudioDevice};
 use crate::system::auto_launch;
-use tauri::AppHandle;
+use crate::system::logging::{LogLevel, Logging};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
 use tauri_plugin_dialog::DialogExt;
 
+/// Diagnostics for support requests
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemInfo {
+    /// Operating system (e.g. "windows", "macos", "linux")
+    pub os: String,
+    /// CPU architecture (e.g. "x86_64", "aarch64")
+    pub arch: String,
+    /// App version
+    pub app_version: String,
+    /// USB library used to talk to the device, with its version
+    pub hid_backend: String,
+    /// Whether a device is connected
+    pub device_connected: bool,
+    /// Name of the active profile, if any
+    pub active_profile_name: Option<String>,
+}
+
+/// Get OS, app and device details for diagnostics
+#[tauri::command]
+pub fn get_system_info(app: AppHandle) -> SystemInfo {
+    let device_connected = app
+        .try_state::<Arc<Mutex<HidManager>>>()
+        .is_some_and(|hid| hid.lock().is_connected());
+    let active_profile_id = app
+        .try_state::<Arc<Mutex<ConfigManager>>>()
+        .and_then(|config| config.lock().get_active_profile_id().map(str::to_string));
+    let active_profile_name = active_profile_id.and_then(|id| {
+        let profiles = app.try_state::<Arc<Mutex<ProfileManager>>>()?;
+        let profiles = profiles.lock();
+        profiles.get(&id).map(|profile| profile.name.clone())
+    });
+
+    let libusb = rusb::version();
+    SystemInfo {
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        hid_backend: format!(
+            "libusb {}.{}.{}.{}{}",
+            libusb.major(),
+            libusb.minor(),
+            libusb.micro(),
+            libusb.nano(),
+            libusb.rc().unwrap_or_default()
+        ),
+        device_connected,
+        active_profile_name,
+    }
+}
+
 /// Get auto-launch status
 #[tauri::command]
-pub fn get_auto_launch() -> bool {
-    auto_launch::is_enabled()
+pub fn get_auto_launch(app: AppHandle) -> bool {
+    auto_launch::is_enabled(&app)
 }
 
 /// Set auto-launch status
+///
+/// Updates the OS startup entry, then records the choice in the app
+/// settings, so the two only disagree if saving the settings fails.
+/// Emits `config:changed` event on success.
 #[tauri::command]
 pub fn set_auto_launch(
     enabled: bool,
-    _app: AppHandle,
+    app: AppHandle,
 ) -> Result<(), String> {
-    if enabled {
-        // Get the executable path
-        let exe_path = std::env::current_exe()
-            .map_err(|e| format!("Failed to get executable path: {}", e))?;
+    auto_launch::set_enabled(&app, enabled)?;
 
-        auto_launch::enable(exe_path.to_string_lossy().as_ref())
-    } else {
-        auto_launch::disable()
+    if let Some(config) = app.try_state::<Arc<Mutex<ConfigManager>>>() {
+        config.lock().set_auto_launch(enabled)?;
     }
+    emit_config_changed(&app, "autoLaunch", enabled);
+    Ok(())
+}
+
+/// Get the directory log files are written to
+#[tauri::command]
+pub fn get_log_path(app: AppHandle) -> Result<String, String> {
+    let logging = app
+        .try_state::<Logging>()
+        .ok_or("Logging is not initialized")?;
+    Ok(logging.directory().to_string_lossy().into_owned())
+}
+
+/// Set the minimum level written to the log
+///
+/// Takes effect immediately and is saved in the app settings.
+/// Emits `config:changed` event on success.
+#[tauri::command]
+pub fn set_log_level(level: LogLevel, app: AppHandle) -> Result<(), String> {
+    update_logging(&app, |config| config.set_log_level(level))?;
+    emit_config_changed(&app, "logLevel", level);
+    Ok(())
+}
+
+/// Turn logging of every USB packet on or off
+///
+/// Kept separate from the log level because it is very verbose.
+/// Emits `config:changed` event on success.
+#[tauri::command]
+pub fn set_usb_logging(enabled: bool, app: AppHandle) -> Result<(), String> {
+    update_logging(&app, |config| config.set_usb_logging(enabled))?;
+    emit_config_changed(&app, "usbLogging", enabled);
+    Ok(())
+}
+
+/// Save a logging setting, then apply the resulting filter
+fn update_logging(
+    app: &AppHandle,
+    update: impl FnOnce(&mut ConfigManager) -> Result<(), String>,
+) -> Result<(), String> {
+    let config = app
+        .try_state::<Arc<Mutex<ConfigManager>>>()
+        .ok_or("Config manager is not initialized")?;
+    let (level, usb_logging) = {
+        let mut config = config.lock();
+        update(&mut config)?;
+        let settings = config.get_settings();
+        (settings.log_level, settings.usb_logging)
+    };
+    apply_log_filter(app, level, usb_logging)
+}
+
+/// Apply a log filter to the running logger, if there is one
+pub(crate) fn apply_log_filter(app: &AppHandle, level: LogLevel, usb_logging: bool) -> Result<(), String> {
+    match app.try_state::<Logging>() {
+        Some(logging) => logging.set_filter(level, usb_logging),
+        None => Ok(()),
+    }
+}
+
+/// List audio output devices for targeting volume actions
+#[tauri::command]
+pub fn list_audio_devices() -> Result<Vec<AudioDevice>, String> {
+    audio::list_output_devices()
 }
 
 /// File filter configuration for file dialogs
@@ -0,0 +1,217 @@
+//! Profile Bundles
+//!
+//! A bundle is a zip holding `profile.json` and the profile's button images
+//! as separate files under `images/`. In the bundled `profile.json` each
+//! button's `image` is the name of its file in the zip rather than inline
+//! base64 or a local path, so bundles stay compact and portable.
+//!
+//! On import, images are turned back into data URLs. A button whose image
+//! file is missing from the zip ends up without an image.
+
+use super::profiles::migrations;
+use super::types::Profile;
+use crate::image::processor::read_image_source;
+use std::io::{Cursor, Read, Write};
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+/// Name of the profile entry in a bundle
+pub const PROFILE_ENTRY: &str = "profile.json";
+
+/// Folder holding the image entries
+const IMAGES_DIR: &str = "images";
+
+/// Largest entry accepted from a bundle
+const MAX_ENTRY_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Pack a profile and its button images into a zip
+///
+/// Images that can't be read (e.g. a file that no longer exists) are left
+/// out, and the button is saved without an image.
+pub fn pack(profile: &Profile) -> Result<Vec<u8>, String> {
+    // Images are only looked for in workspaces
+    let mut profile = profile.clone();
+    migrations::migrate(&mut profile);
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default();
+
+    for (w, workspace) in profile.workspaces.iter_mut().enumerate() {
+        for button in &mut workspace.buttons {
+            let Some(source) = button.image.take() else {
+                continue;
+            };
+            let data = match read_image_source(&source) {
+                Ok(data) => data,
+                Err(e) => {
+                    log::warn!("Leaving out image of button {} in bundle: {}", button.index, e);
+                    continue;
+                }
+            };
+            let extension = image::guess_format(&data)
+                .ok()
+                .and_then(|format| format.extensions_str().first().copied())
+                .unwrap_or("bin");
+            let name = format!("{}/{}-{}.{}", IMAGES_DIR, w, button.index, extension);
+
+            zip.start_file(name.as_str(), options)
+                .and_then(|_| zip.write_all(&data).map_err(Into::into))
+                .map_err(|e| format!("Failed to write bundle: {}", e))?;
+            button.image = Some(name);
+        }
+    }
+
+    let json = serde_json::to_vec_pretty(&profile)
+        .map_err(|e| format!("Failed to serialize profile: {}", e))?;
+    zip.start_file(PROFILE_ENTRY, options)
+        .and_then(|_| zip.write_all(&json).map_err(Into::into))
+        .map_err(|e| format!("Failed to write bundle: {}", e))?;
+
+    zip.finish()
+        .map(Cursor::into_inner)
+        .map_err(|e| format!("Failed to write bundle: {}", e))
+}
+
+/// Read a profile back from a bundle, with images as data URLs
+///
+/// The profile keeps its original ID; callers importing it assign a new one.
+pub fn unpack(bytes: &[u8]) -> Result<Profile, String> {
+    let mut zip = ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| format!("Invalid profile bundle: {}", e))?;
+
+    let json = read_entry(&mut zip, PROFILE_ENTRY)?
+        .ok_or_else(|| format!("Invalid profile bundle: {} missing", PROFILE_ENTRY))?;
+    let mut profile: Profile = serde_json::from_slice(&json)
+        .map_err(|e| format!("Failed to parse profile JSON: {}", e))?;
+    migrations::migrate(&mut profile);
+
+    for workspace in &mut profile.workspaces {
+        for button in &mut workspace.buttons {
+            let Some(name) = button.image.take() else {
+                continue;
+            };
+            match read_entry(&mut zip, &name)? {
+                Some(data) => button.image = Some(data_url(&data)),
+                None => log::warn!("Bundle image {} missing, button {} has no image", name, button.index),
+            }
+        }
+    }
+    Ok(profile)
+}
+
+/// Contents of a zip entry, or None if there is no such entry
+fn read_entry(zip: &mut ZipArchive<Cursor<&[u8]>>, name: &str) -> Result<Option<Vec<u8>>, String> {
+    let entry = match zip.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(format!("Failed to read bundle entry {}: {}", name, e)),
+    };
+    if entry.size() > MAX_ENTRY_BYTES {
+        return Err(format!("Bundle entry {} is too large", name));
+    }
+
+    let mut data = Vec::new();
+    entry
+        .take(MAX_ENTRY_BYTES)
+        .read_to_end(&mut data)
+        .map_err(|e| format!("Failed to read bundle entry {}: {}", name, e))?;
+    Ok(Some(data))
+}
+
+/// Inline image data as a data URL
+fn data_url(data: &[u8]) -> String {
+    let mime = image::guess_format(data)
+        .map(|format| format.to_mime_type())
+        .unwrap_or("application/octet-stream");
+    let encoded = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, data);
+    format!("data:{};base64,{}", mime, encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::types::ButtonConfig;
+
+    fn png() -> Vec<u8> {
+        let mut data = Vec::new();
+        image::RgbImage::from_pixel(2, 2, image::Rgb([255, 0, 0]))
+            .write_to(&mut Cursor::new(&mut data), image::ImageFormat::Png)
+            .unwrap();
+        data
+    }
+
+    fn profile_with_image() -> Profile {
+        let mut profile = Profile::new("Bundled".to_string());
+        profile.workspaces[0].buttons = vec![
+            ButtonConfig {
+                index: 2,
+                image: Some(data_url(&png())),
+                ..Default::default()
+            },
+            ButtonConfig {
+                index: 3,
+                label: Some("No image".to_string()),
+                ..Default::default()
+            },
+        ];
+        profile
+    }
+
+    // ========== Pack Tests ==========
+
+    #[test]
+    fn test_pack_extracts_images() {
+        let bytes = pack(&profile_with_image()).unwrap();
+
+        let mut zip = ZipArchive::new(Cursor::new(bytes.as_slice())).unwrap();
+        assert_eq!(read_entry(&mut zip, "images/0-2.png").unwrap(), Some(png()));
+
+        let json = read_entry(&mut zip, PROFILE_ENTRY).unwrap().unwrap();
+        let bundled: Profile = serde_json::from_slice(&json).unwrap();
+        assert_eq!(bundled.workspaces[0].buttons[0].image.as_deref(), Some("images/0-2.png"));
+        assert_eq!(bundled.workspaces[0].buttons[1].image, None);
+    }
+
+    #[test]
+    fn test_pack_skips_unreadable_image() {
+        let mut profile = profile_with_image();
+        profile.workspaces[0].buttons[0].image = Some("/nonexistent/icon.png".to_string());
+
+        let restored = unpack(&pack(&profile).unwrap()).unwrap();
+        assert_eq!(restored.workspaces[0].buttons[0].image, None);
+    }
+
+    // ========== Unpack Tests ==========
+
+    #[test]
+    fn test_round_trip_restores_images() {
+        let original = profile_with_image();
+
+        let restored = unpack(&pack(&original).unwrap()).unwrap();
+
+        assert_eq!(restored.name, "Bundled");
+        assert_eq!(restored.workspaces[0].buttons[0].image, original.workspaces[0].buttons[0].image);
+        assert_eq!(restored.workspaces[0].buttons[1].label.as_deref(), Some("No image"));
+    }
+
+    #[test]
+    fn test_missing_image_entry_leaves_no_image() {
+        let mut profile = Profile::new("Partial".to_string());
+        profile.workspaces[0].buttons = vec![ButtonConfig {
+            index: 0,
+            image: Some("images/0-0.png".to_string()),
+            ..Default::default()
+        }];
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file(PROFILE_ENTRY, SimpleFileOptions::default()).unwrap();
+        zip.write_all(&serde_json::to_vec(&profile).unwrap()).unwrap();
+        let bytes = zip.finish().unwrap().into_inner();
+
+        let restored = unpack(&bytes).unwrap();
+        assert_eq!(restored.workspaces[0].buttons[0].image, None);
+    }
+
+    #[test]
+    fn test_unpack_rejects_non_bundle() {
+        assert!(unpack(b"not a zip").unwrap_err().contains("Invalid profile bundle"));
+    }
+}
@@ -1,10 +1,32 @@
 //! Config Manager
 //!
 //! Manages application settings persistence.
-
-use super::types::AppSettings;
+//!
+//! Secrets (the Home Assistant token and MQTT password) are kept in the
+//! platform credential store, see `secrets`. Settings in memory always hold
+//! the real values; only the saved file has references in their place.
+
+use super::secrets::{self, SecretStore};
+use super::storage;
+use super::types::{AppSettings, HomeAssistantConfig, MqttConfig, NodeRedConfig, Profile};
+use crate::hid::event_map::EventTarget;
+use crate::system::logging::LogLevel;
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Name of the Home Assistant token secret
+const HOME_ASSISTANT_TOKEN: &str = "home-assistant-token";
+/// Name of the MQTT password secret
+const MQTT_PASSWORD: &str = "mqtt-password";
+
+/// A secret as last written to the credential store
+struct StoredSecret {
+    account: String,
+    value: String,
+}
 
 /// Manages application configuration
 pub struct ConfigManager {
@@ -12,11 +34,23 @@ pub struct ConfigManager {
     config_path: PathBuf,
     /// Current settings
     settings: AppSettings,
+    /// Credential store for secrets
+    secrets: Arc<dyn SecretStore>,
+    /// Secrets currently in the credential store, by name
+    stored: Mutex<HashMap<&'static str, StoredSecret>>,
 }
 
 impl ConfigManager {
     /// Create a new config manager
     pub fn new(app_data_dir: PathBuf) -> Self {
+        Self::with_secret_store(app_data_dir, secrets::default_store())
+    }
+
+    /// Create a config manager keeping secrets in `secrets`
+    ///
+    /// Plaintext secrets found in an existing config file are moved into the
+    /// store right away.
+    pub fn with_secret_store(app_data_dir: PathBuf, secrets: Arc<dyn SecretStore>) -> Self {
         let config_path = app_data_dir.join("config.json");
 
         // Ensure directory exists
@@ -25,12 +59,52 @@ impl ConfigManager {
         }
 
         // Load existing settings or use defaults
-        let settings = Self::load_from_file(&config_path).unwrap_or_default();
+        let mut settings = Self::load_from_file(&config_path).unwrap_or_default();
+        let mut stored = HashMap::new();
+        let mut has_plaintext = false;
+        for (name, field) in secret_fields(&mut settings) {
+            let Some(value) = field else {
+                continue;
+            };
+            let Some(account) = secrets::reference_account(value).map(str::to_string) else {
+                has_plaintext |= !value.is_empty();
+                continue;
+            };
+            match secrets.get(&account) {
+                Ok(Some(secret)) => {
+                    value.clone_from(&secret);
+                    stored.insert(name, StoredSecret { account, value: secret });
+                }
+                Ok(None) => {
+                    log::warn!("Secret {} is missing from the keychain", name);
+                    value.clear();
+                }
+                // The reference is kept, so the secret isn't lost on the next save
+                Err(e) => log::warn!("Could not load {}: {}", name, e),
+            }
+        }
 
-        Self {
+        let manager = Self {
             config_path,
             settings,
+            secrets,
+            stored: Mutex::new(stored),
+        };
+        if has_plaintext {
+            log::info!("Moving plaintext secrets from config file to keychain");
+            match manager.save() {
+                // The backup is still the plaintext file, which `read_json`
+                // could restore; replace it with the file just saved
+                Ok(()) => {
+                    let backup = storage::sibling(&manager.config_path, "bak");
+                    if let Err(e) = fs::copy(&manager.config_path, &backup) {
+                        log::warn!("Failed to replace config backup after moving secrets: {}", e);
+                    }
+                }
+                Err(e) => log::warn!("Failed to save config after moving secrets: {}", e),
+            }
         }
+        manager
     }
 
     /// Get current app settings
@@ -45,20 +119,74 @@ impl ConfigManager {
     }
 
     /// Save settings to file
+    ///
+    /// Secrets go to the credential store and the file gets references to
+    /// them. If the store can't be written, the secret is saved in plaintext.
     pub fn save(&self) -> Result<(), String> {
-        let json = serde_json::to_string_pretty(&self.settings)
+        let mut settings = self.settings.clone();
+        self.store_secrets(&mut settings);
+        let json = serde_json::to_string_pretty(&settings)
             .map_err(|e| format!("Failed to serialize settings: {}", e))?;
 
-        fs::write(&self.config_path, json)
+        storage::write_atomic(&self.config_path, json.as_bytes())
             .map_err(|e| format!("Failed to write config file: {}", e))?;
 
         Ok(())
     }
 
     /// Load settings from file
-    fn load_from_file(path: &PathBuf) -> Option<AppSettings> {
-        let content = fs::read_to_string(path).ok()?;
-        serde_json::from_str(&content).ok()
+    fn load_from_file(path: &Path) -> Option<AppSettings> {
+        storage::read_json(path)
+    }
+
+    /// Move secrets in `settings` into the credential store, leaving references
+    ///
+    /// Unchanged secrets aren't written again, and secrets that were removed
+    /// from the settings are deleted from the store.
+    fn store_secrets(&self, settings: &mut AppSettings) {
+        let mut stored = self.stored.lock();
+        let mut in_use = HashSet::new();
+
+        for (name, field) in secret_fields(settings) {
+            let Some(value) = field else {
+                continue;
+            };
+            // Empty, or a reference that couldn't be resolved on load
+            if value.is_empty() || secrets::reference_account(value).is_some() {
+                continue;
+            }
+            in_use.insert(name);
+
+            let account = match stored.get(name) {
+                Some(secret) if secret.value == *value => {
+                    *value = secrets::reference(&secret.account);
+                    continue;
+                }
+                Some(secret) => secret.account.clone(),
+                None => format!("{}-{}", name, uuid::Uuid::new_v4()),
+            };
+            match self.secrets.set(&account, value) {
+                Ok(()) => {
+                    let secret = StoredSecret {
+                        account,
+                        value: std::mem::take(value),
+                    };
+                    *value = secrets::reference(&secret.account);
+                    stored.insert(name, secret);
+                }
+                Err(e) => log::warn!("Saving {} in plaintext: {}", name, e),
+            }
+        }
+
+        stored.retain(|name, secret| {
+            if in_use.contains(name) {
+                return true;
+            }
+            if let Err(e) = self.secrets.delete(&secret.account) {
+                log::warn!("Failed to remove {} from keychain: {}", name, e);
+            }
+            false
+        });
     }
 
     /// Get active profile ID
@@ -82,12 +210,80 @@ impl ConfigManager {
         self.settings.brightness = level.min(100);
         self.save()
     }
+
+    /// Brightness to show when a profile is activated
+    ///
+    /// A profile's own default brightness is saved as the global brightness,
+    /// so it survives a restart; without one the global brightness is kept.
+    pub fn apply_profile_brightness(&mut self, profile: &Profile) -> Result<u8, String> {
+        if let Some(level) = profile.default_brightness {
+            self.set_brightness(level)?;
+        }
+        Ok(self.get_brightness())
+    }
+
+    /// Set whether the app starts minimized to the tray
+    pub fn set_start_minimized(&mut self, enabled: bool) -> Result<(), String> {
+        self.settings.start_minimized = enabled;
+        self.save()
+    }
+
+    /// Set whether the app starts on system boot
+    pub fn set_auto_launch(&mut self, enabled: bool) -> Result<(), String> {
+        self.settings.auto_launch = enabled;
+        self.save()
+    }
+
+    /// Set the minimum level written to the log
+    pub fn set_log_level(&mut self, level: LogLevel) -> Result<(), String> {
+        self.settings.log_level = level;
+        self.save()
+    }
+
+    /// Map a raw event ID to a control, or remove its override with `None`
+    pub fn set_event_mapping(&mut self, event_id: u8, target: Option<EventTarget>) -> Result<(), String> {
+        self.settings.event_map.set(event_id, target);
+        self.save()
+    }
+
+    /// Set whether every USB packet is logged
+    pub fn set_usb_logging(&mut self, enabled: bool) -> Result<(), String> {
+        self.settings.usb_logging = enabled;
+        self.save()
+    }
+
+    /// Set or clear the Home Assistant connection
+    pub fn set_home_assistant(&mut self, config: Option<HomeAssistantConfig>) -> Result<(), String> {
+        self.settings.home_assistant = config;
+        self.save()
+    }
+
+    /// Set or clear the Node-RED connection
+    pub fn set_node_red(&mut self, config: Option<NodeRedConfig>) -> Result<(), String> {
+        self.settings.node_red = config;
+        self.save()
+    }
+
+    /// Set or clear the MQTT broker connection
+    pub fn set_mqtt(&mut self, config: Option<MqttConfig>) -> Result<(), String> {
+        self.settings.mqtt = config;
+        self.save()
+    }
+}
+
+/// Secret fields of the settings, by name (None when not configured)
+fn secret_fields(settings: &mut AppSettings) -> [(&'static str, Option<&mut String>); 2] {
+    [
+        (HOME_ASSISTANT_TOKEN, settings.home_assistant.as_mut().map(|ha| &mut ha.token)),
+        (MQTT_PASSWORD, settings.mqtt.as_mut().and_then(|mqtt| mqtt.password.as_mut())),
+    ]
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::types::{HomeAssistantConfig, NodeRedConfig};
+    use crate::hid::acceleration::AccelerationCurve;
+    use crate::hid::types::DEBOUNCE_MS;
     use std::fs;
     use tempfile::TempDir;
 
@@ -110,6 +306,27 @@ mod tests {
         assert!(!settings.auto_launch);
         assert!(settings.home_assistant.is_none());
         assert!(settings.node_red.is_none());
+        assert!(!settings.press_feedback);
+    }
+
+    #[test]
+    fn test_new_loads_config_without_press_feedback() {
+        let temp_dir = create_test_dir();
+        let config_path = temp_dir.path().join("config.json");
+
+        // Config written before press feedback existed
+        let json = r#"{"activeProfileId":null,"brightness":60,"startMinimized":false,"autoLaunch":false,"homeAssistant":null,"nodeRed":null}"#;
+        fs::write(&config_path, json).unwrap();
+
+        let manager = ConfigManager::new(temp_dir.path().to_path_buf());
+
+        let settings = manager.get_settings();
+        assert_eq!(settings.brightness, 60);
+        assert!(!settings.press_feedback);
+        assert_eq!(settings.encoder_acceleration, AccelerationCurve::default());
+        assert_eq!(settings.debounce_ms, DEBOUNCE_MS);
+        assert_eq!(settings.dim_after_ms, None);
+        assert_eq!(settings.dim_brightness, 10);
     }
 
     #[test]
@@ -135,6 +352,7 @@ mod tests {
             auto_launch: true,
             home_assistant: None,
             node_red: None,
+            ..Default::default()
         };
         let json = serde_json::to_string(&custom_settings).unwrap();
         fs::write(&config_path, json).unwrap();
@@ -191,7 +409,9 @@ mod tests {
             }),
             node_red: Some(NodeRedConfig {
                 url: "http://nodered.local:1880".to_string(),
+                websocket_url: None,
             }),
+            ..Default::default()
         };
 
         manager.set_settings(new_settings.clone()).unwrap();
@@ -312,6 +532,92 @@ mod tests {
         assert_eq!(manager2.get_brightness(), 35);
     }
 
+    #[test]
+    fn test_apply_profile_brightness_uses_profile_default() {
+        let temp_dir = create_test_dir();
+        let mut manager = ConfigManager::new(temp_dir.path().to_path_buf());
+        let mut profile = Profile::new("Night".to_string());
+        profile.default_brightness = Some(20);
+
+        assert_eq!(manager.apply_profile_brightness(&profile).unwrap(), 20);
+        assert_eq!(manager.get_brightness(), 20);
+
+        let reloaded = ConfigManager::new(temp_dir.path().to_path_buf());
+        assert_eq!(reloaded.get_brightness(), 20);
+    }
+
+    #[test]
+    fn test_apply_profile_brightness_falls_back_to_global() {
+        let temp_dir = create_test_dir();
+        let mut manager = ConfigManager::new(temp_dir.path().to_path_buf());
+        manager.set_brightness(65).unwrap();
+
+        let profile = Profile::new("Day".to_string());
+        assert_eq!(manager.apply_profile_brightness(&profile).unwrap(), 65);
+        assert_eq!(manager.get_brightness(), 65);
+    }
+
+    #[test]
+    fn test_apply_profile_brightness_clamps() {
+        let temp_dir = create_test_dir();
+        let mut manager = ConfigManager::new(temp_dir.path().to_path_buf());
+        let mut profile = Profile::new("Bright".to_string());
+        profile.default_brightness = Some(200);
+
+        assert_eq!(manager.apply_profile_brightness(&profile).unwrap(), 100);
+    }
+
+    // ========== Individual Setting Tests ==========
+
+    #[test]
+    fn test_field_setters_change_only_their_field() {
+        let temp_dir = create_test_dir();
+        let mut manager = ConfigManager::new(temp_dir.path().to_path_buf());
+        manager.set_brightness(42).unwrap();
+
+        manager.set_start_minimized(true).unwrap();
+        manager.set_auto_launch(true).unwrap();
+
+        let settings = manager.get_settings();
+        assert!(settings.start_minimized);
+        assert!(settings.auto_launch);
+        assert_eq!(settings.brightness, 42);
+    }
+
+    #[test]
+    fn test_integration_setters_persist() {
+        let temp_dir = create_test_dir();
+
+        {
+            let mut manager = ConfigManager::new(temp_dir.path().to_path_buf());
+            manager
+                .set_home_assistant(Some(HomeAssistantConfig {
+                    url: "http://ha.local".to_string(),
+                    token: "secret".to_string(),
+                }))
+                .unwrap();
+            manager
+                .set_node_red(Some(NodeRedConfig {
+                    url: "http://nodered.local".to_string(),
+                    websocket_url: None,
+                }))
+                .unwrap();
+            manager
+                .set_mqtt(Some(MqttConfig {
+                    broker_url: "mqtt://broker.local".to_string(),
+                    username: None,
+                    password: None,
+                }))
+                .unwrap();
+            manager.set_node_red(None).unwrap();
+        }
+
+        let settings = ConfigManager::new(temp_dir.path().to_path_buf()).get_settings().clone();
+        assert_eq!(settings.home_assistant.unwrap().url, "http://ha.local");
+        assert!(settings.node_red.is_none());
+        assert_eq!(settings.mqtt.unwrap().broker_url, "mqtt://broker.local");
+    }
+
     // ========== Save/Load Tests ==========
 
     #[test]
@@ -338,6 +644,21 @@ mod tests {
         assert!(loaded.is_ok());
     }
 
+    #[test]
+    fn test_save_keeps_previous_version_as_backup() {
+        let temp_dir = create_test_dir();
+        let mut manager = ConfigManager::new(temp_dir.path().to_path_buf());
+        manager.set_brightness(30).unwrap();
+        manager.set_brightness(60).unwrap();
+
+        // A corrupt config falls back to the previous save
+        let config_path = temp_dir.path().join("config.json");
+        fs::write(&config_path, "{ \"brightness\": 6").unwrap();
+
+        let reloaded = ConfigManager::new(temp_dir.path().to_path_buf());
+        assert_eq!(reloaded.get_brightness(), 30);
+    }
+
     #[test]
     fn test_round_trip_preserves_all_fields() {
         let temp_dir = create_test_dir();
@@ -354,7 +675,9 @@ mod tests {
             }),
             node_red: Some(NodeRedConfig {
                 url: "http://nodered.local:1880".to_string(),
+                websocket_url: None,
             }),
+            ..Default::default()
         };
 
         manager.set_settings(settings).unwrap();
@@ -376,6 +699,142 @@ mod tests {
         assert_eq!(nr.url, "http://nodered.local:1880");
     }
 
+    // ========== Secret Storage Tests ==========
+
+    /// Store for a system without a credential store
+    struct UnavailableStore;
+
+    impl SecretStore for UnavailableStore {
+        fn get(&self, _account: &str) -> Result<Option<String>, String> {
+            Err("no keychain".to_string())
+        }
+        fn set(&self, _account: &str, _secret: &str) -> Result<(), String> {
+            Err("no keychain".to_string())
+        }
+        fn delete(&self, _account: &str) -> Result<(), String> {
+            Err("no keychain".to_string())
+        }
+    }
+
+    fn home_assistant(token: &str) -> Option<HomeAssistantConfig> {
+        Some(HomeAssistantConfig {
+            url: "http://ha.local".to_string(),
+            token: token.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_secrets_are_not_written_to_config_file() {
+        let temp_dir = create_test_dir();
+        let mut manager = ConfigManager::new(temp_dir.path().to_path_buf());
+        manager.set_home_assistant(home_assistant("ha-secret")).unwrap();
+        manager
+            .set_mqtt(Some(MqttConfig {
+                broker_url: "mqtt://broker.local".to_string(),
+                username: Some("user".to_string()),
+                password: Some("mqtt-secret".to_string()),
+            }))
+            .unwrap();
+
+        let content = fs::read_to_string(temp_dir.path().join("config.json")).unwrap();
+        assert!(!content.contains("ha-secret"));
+        assert!(!content.contains("mqtt-secret"));
+        assert!(content.contains(secrets::KEYCHAIN_PREFIX));
+
+        let reloaded = ConfigManager::new(temp_dir.path().to_path_buf());
+        let settings = reloaded.get_settings();
+        assert_eq!(settings.home_assistant.as_ref().unwrap().token, "ha-secret");
+        assert_eq!(settings.mqtt.as_ref().unwrap().password.as_deref(), Some("mqtt-secret"));
+    }
+
+    #[test]
+    fn test_plaintext_secrets_are_migrated_on_load() {
+        let temp_dir = create_test_dir();
+        let config_path = temp_dir.path().join("config.json");
+        let settings = AppSettings {
+            home_assistant: home_assistant("old-plaintext"),
+            ..Default::default()
+        };
+        fs::write(&config_path, serde_json::to_string(&settings).unwrap()).unwrap();
+
+        let manager = ConfigManager::new(temp_dir.path().to_path_buf());
+
+        assert_eq!(manager.get_settings().home_assistant.as_ref().unwrap().token, "old-plaintext");
+        let content = fs::read_to_string(&config_path).unwrap();
+        assert!(!content.contains("old-plaintext"));
+    }
+
+    #[test]
+    fn test_migration_leaves_no_plaintext_backup() {
+        let temp_dir = create_test_dir();
+        let config_path = temp_dir.path().join("config.json");
+        let settings = AppSettings {
+            home_assistant: home_assistant("old-ha-token"),
+            mqtt: Some(MqttConfig {
+                broker_url: "mqtt://broker.local".to_string(),
+                username: Some("user".to_string()),
+                password: Some("old-mqtt-password".to_string()),
+            }),
+            ..Default::default()
+        };
+        fs::write(&config_path, serde_json::to_string(&settings).unwrap()).unwrap();
+
+        let store = Arc::new(secrets::MemoryStore::default());
+        let manager = ConfigManager::with_secret_store(temp_dir.path().to_path_buf(), store);
+        assert_eq!(manager.get_settings().home_assistant.as_ref().unwrap().token, "old-ha-token");
+
+        for path in [config_path.clone(), storage::sibling(&config_path, "bak")] {
+            let content = fs::read_to_string(&path).unwrap();
+            assert!(!content.contains("old-ha-token"), "{:?} holds the token", path);
+            assert!(!content.contains("old-mqtt-password"), "{:?} holds the password", path);
+        }
+    }
+
+    #[test]
+    fn test_removed_secret_is_deleted_from_store() {
+        let temp_dir = create_test_dir();
+        let store = Arc::new(secrets::MemoryStore::default());
+        let mut manager = ConfigManager::with_secret_store(temp_dir.path().to_path_buf(), store.clone());
+        manager.set_home_assistant(home_assistant("ha-secret")).unwrap();
+        let account = manager.stored.lock()[HOME_ASSISTANT_TOKEN].account.clone();
+        assert_eq!(store.get(&account).unwrap().as_deref(), Some("ha-secret"));
+
+        manager.set_home_assistant(None).unwrap();
+
+        assert_eq!(store.get(&account).unwrap(), None);
+    }
+
+    #[test]
+    fn test_falls_back_to_plaintext_without_keychain() {
+        let temp_dir = create_test_dir();
+        let mut manager =
+            ConfigManager::with_secret_store(temp_dir.path().to_path_buf(), Arc::new(UnavailableStore));
+        manager.set_home_assistant(home_assistant("ha-secret")).unwrap();
+
+        let content = fs::read_to_string(temp_dir.path().join("config.json")).unwrap();
+        assert!(content.contains("ha-secret"));
+
+        let reloaded = ConfigManager::with_secret_store(temp_dir.path().to_path_buf(), Arc::new(UnavailableStore));
+        assert_eq!(reloaded.get_settings().home_assistant.as_ref().unwrap().token, "ha-secret");
+    }
+
+    #[test]
+    fn test_unreadable_reference_is_kept() {
+        let temp_dir = create_test_dir();
+        let config_path = temp_dir.path().join("config.json");
+        let settings = AppSettings {
+            home_assistant: home_assistant("keychain:home-assistant-token-x"),
+            ..Default::default()
+        };
+        fs::write(&config_path, serde_json::to_string(&settings).unwrap()).unwrap();
+
+        let mut manager = ConfigManager::with_secret_store(temp_dir.path().to_path_buf(), Arc::new(UnavailableStore));
+        manager.set_brightness(20).unwrap();
+
+        let content = fs::read_to_string(&config_path).unwrap();
+        assert!(content.contains("keychain:home-assistant-token-x"));
+    }
+
     // ========== AppSettings Default Tests ==========
 
     #[test]
@@ -401,6 +860,7 @@ mod tests {
             auto_launch: false,
             home_assistant: None,
             node_red: None,
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&settings).unwrap();
@@ -426,6 +886,7 @@ mod tests {
     fn test_node_red_config_serializes() {
         let config = NodeRedConfig {
             url: "http://nodered.local".to_string(),
+            websocket_url: None,
         };
 
         let json = serde_json::to_string(&config).unwrap();
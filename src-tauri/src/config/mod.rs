@@ -3,5 +3,9 @@
 //! Handles application settings and profile management.
 
 pub mod types;
+pub mod bundle;
 pub mod manager;
 pub mod profiles;
+pub mod secrets;
+pub mod storage;
+pub mod watcher;
@@ -1,11 +1,26 @@
 //! Profile Manager
 //!
 //! Manages device profiles (CRUD operations, import/export).
-
-use super::types::{Profile, ProfileUpdate};
+//!
+//! Files edited outside the app are picked up through `reload_from_disk`,
+//! driven by the `watcher` module. The manager remembers which files it wrote
+//! itself so those changes aren't reloaded.
+
+pub mod migrations;
+pub mod safety;
+
+use super::bundle;
+use super::storage;
+use super::types::{IndexLimits, Profile, ProfileUpdate, Workspace, WorkspaceUpdate};
+use parking_lot::Mutex;
+use safety::ImportReview;
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long after the app writes a profile file changes to it are its own
+const OWN_WRITE_WINDOW: Duration = Duration::from_secs(2);
 
 /// Manages device profiles
 pub struct ProfileManager {
@@ -13,6 +28,10 @@ pub struct ProfileManager {
     profiles_dir: PathBuf,
     /// Cached profiles (id -> profile)
     profiles: HashMap<String, Profile>,
+    /// Allowed button/encoder indices for profile changes
+    limits: IndexLimits,
+    /// Files this manager wrote or deleted recently, and when
+    recent_writes: Mutex<HashMap<PathBuf, Instant>>,
 }
 
 impl ProfileManager {
@@ -24,6 +43,8 @@ impl ProfileManager {
         let mut manager = Self {
             profiles_dir,
             profiles: HashMap::new(),
+            limits: IndexLimits::default(),
+            recent_writes: Mutex::new(HashMap::new()),
         };
 
         // Load existing profiles
@@ -44,34 +65,193 @@ impl ProfileManager {
         for entry in entries.flatten() {
             let path = entry.path();
             if path.extension().map(|e| e == "json").unwrap_or(false) {
-                if let Some(profile) = Self::load_profile_from_file(&path) {
+                if let Some((profile, migrated)) = Self::load_profile_from_file(&path) {
+                    if migrated {
+                        // Persist the workspace form so the migration only runs once
+                        if let Err(e) = self.save_profile(&profile) {
+                            log::warn!("Failed to save migrated profile {}: {}", profile.id, e);
+                        }
+                    }
                     self.profiles.insert(profile.id.clone(), profile);
                 }
             }
         }
     }
 
-    /// Load a single profile from file
-    fn load_profile_from_file(path: &PathBuf) -> Option<Profile> {
-        let content = fs::read_to_string(path).ok()?;
-        serde_json::from_str(&content).ok()
+    /// Load a single profile from file, migrating it to the current schema version
+    ///
+    /// Returns the profile and whether it was migrated (and so needs saving).
+    fn load_profile_from_file(path: &Path) -> Option<(Profile, bool)> {
+        let mut profile: Profile = storage::read_json(path)?;
+        let migrated = migrations::migrate(&mut profile);
+        Some((profile, migrated))
     }
 
     /// Save a profile to disk
     fn save_profile(&self, profile: &Profile) -> Result<(), String> {
-        let path = self.profiles_dir.join(format!("{}.json", profile.id));
+        let path = self.profile_path(&profile.id);
         let json = serde_json::to_string_pretty(profile)
             .map_err(|e| format!("Failed to serialize profile: {}", e))?;
 
-        fs::write(&path, json)
+        self.mark_written(&path);
+        storage::write_atomic(&path, json.as_bytes())
             .map_err(|e| format!("Failed to write profile file: {}", e))?;
 
         Ok(())
     }
 
-    /// List all profiles
+    /// Directory the profiles are stored in
+    pub fn profiles_dir(&self) -> &Path {
+        &self.profiles_dir
+    }
+
+    fn profile_path(&self, id: &str) -> PathBuf {
+        self.profiles_dir.join(format!("{}.json", id))
+    }
+
+    /// Note that the app is about to change `path`
+    fn mark_written(&self, path: &Path) {
+        self.recent_writes.lock().insert(path.to_path_buf(), Instant::now());
+    }
+
+    /// Whether a change to `path` was most likely made by this manager
+    pub fn is_own_write(&self, path: &Path) -> bool {
+        let mut recent = self.recent_writes.lock();
+        recent.retain(|_, at| at.elapsed() < OWN_WRITE_WINDOW);
+        recent.contains_key(path)
+    }
+
+    /// Bring the cache in line with a profile file changed outside the app
+    ///
+    /// A new or modified file is (re)loaded and a deleted one is dropped from
+    /// the cache. Returns the affected profile ID, or None if nothing changed
+    /// (e.g. the file isn't a profile or can't be parsed, in which case the
+    /// cached version is kept).
+    pub fn reload_from_disk(&mut self, path: &Path) -> Option<String> {
+        if path.parent() != Some(self.profiles_dir.as_path())
+            || path.extension().is_none_or(|e| e != "json")
+        {
+            return None;
+        }
+
+        if !path.exists() {
+            let id = path.file_stem()?.to_str()?;
+            return self.profiles.remove(id).map(|profile| {
+                log::info!("Profile {} was deleted externally", profile.id);
+                profile.id
+            });
+        }
+
+        match Self::load_profile_from_file(path) {
+            Some((profile, _)) => {
+                log::info!("Reloaded profile {} from {:?}", profile.id, path);
+                let id = profile.id.clone();
+                self.profiles.insert(id.clone(), profile);
+                Some(id)
+            }
+            None => {
+                log::warn!("Ignoring unreadable profile file {:?}", path);
+                None
+            }
+        }
+    }
+
+    /// List all profiles in display order
+    ///
+    /// Sorted by `order`, with creation time and ID as tie-breakers so the
+    /// result is stable for profiles saved before ordering existed.
     pub fn list(&self) -> Vec<&Profile> {
-        self.profiles.values().collect()
+        let mut profiles: Vec<&Profile> = self.profiles.values().collect();
+        profiles.sort_by(|a, b| {
+            a.order
+                .cmp(&b.order)
+                .then(a.created_at.cmp(&b.created_at))
+                .then(a.id.cmp(&b.id))
+        });
+        profiles
+    }
+
+    /// Profiles whose name, description, button labels or tags contain
+    /// `query` (case-insensitive), in display order
+    ///
+    /// A blank query matches every profile.
+    pub fn search(&self, query: &str) -> Vec<&Profile> {
+        let query = query.trim().to_lowercase();
+        self.list()
+            .into_iter()
+            .filter(|profile| query.is_empty() || matches_query(profile, &query))
+            .collect()
+    }
+
+    /// Profiles with a tag (case-insensitive), in display order
+    pub fn with_tag(&self, tag: &str) -> Vec<&Profile> {
+        let tag = tag.trim();
+        self.list()
+            .into_iter()
+            .filter(|profile| profile.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+            .collect()
+    }
+
+    /// Order value that places a profile at the end of the list
+    fn next_order(&self) -> u32 {
+        self.profiles
+            .values()
+            .map(|p| p.order + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Reorder profiles to match the given list of IDs
+    ///
+    /// Profiles not included keep their relative order after the listed ones.
+    /// Unknown IDs are an error and nothing is changed.
+    pub fn reorder(&mut self, ids: &[String]) -> Result<Vec<Profile>, String> {
+        if let Some(unknown) = ids.iter().find(|id| !self.profiles.contains_key(*id)) {
+            return Err(format!("Profile not found: {}", unknown));
+        }
+
+        let mut ordered: Vec<String> = Vec::new();
+        for id in ids {
+            if !ordered.contains(id) {
+                ordered.push(id.clone());
+            }
+        }
+        for profile in self.list() {
+            if !ordered.contains(&profile.id) {
+                ordered.push(profile.id.clone());
+            }
+        }
+
+        for (position, id) in ordered.iter().enumerate() {
+            let profile = self.profiles.get_mut(id).expect("id checked above");
+            if profile.order != position as u32 {
+                profile.order = position as u32;
+                let profile = profile.clone();
+                self.save_profile(&profile)?;
+            }
+        }
+
+        Ok(self.list().into_iter().cloned().collect())
+    }
+
+    /// Index limits applied when profiles are changed or imported
+    pub fn index_limits(&self) -> IndexLimits {
+        self.limits
+    }
+
+    /// Override the index limits, e.g. for a device with a different layout
+    ///
+    /// Profiles already saved are not re-checked.
+    pub fn set_index_limits(&mut self, limits: IndexLimits) {
+        self.limits = limits;
+    }
+
+    /// Check every workspace and the legacy bindings of a profile
+    fn validate_profile(&self, profile: &Profile) -> Result<(), String> {
+        for workspace in &profile.workspaces {
+            self.limits.validate(&format!("Workspace \"{}\"", workspace.name), &workspace.buttons, &workspace.encoders)?;
+        }
+        self.limits.validate("Profile", &profile.buttons, &profile.encoders)
     }
 
     /// Get a profile by ID
@@ -81,7 +261,8 @@ impl ProfileManager {
 
     /// Create a new profile
     pub fn create(&mut self, name: String) -> Result<Profile, String> {
-        let profile = Profile::new(name);
+        let mut profile = Profile::new(name);
+        profile.order = self.next_order();
 
         self.save_profile(&profile)?;
         self.profiles.insert(profile.id.clone(), profile.clone());
@@ -91,15 +272,81 @@ impl ProfileManager {
 
     /// Update an existing profile
     pub fn update(&mut self, id: &str, update: ProfileUpdate) -> Result<Profile, String> {
+        let limits = self.limits;
         let profile = self.profiles.get_mut(id)
             .ok_or_else(|| format!("Profile not found: {}", id))?;
 
+        // Validate patches up front so a bad ID doesn't leave a half-applied update
+        if let Some(ref patches) = update.workspace_patches {
+            for (workspace_id, patch) in patches {
+                let existing = profile.workspaces.iter()
+                    .chain(update.workspaces.iter().flatten())
+                    .find(|w| &w.id == workspace_id)
+                    .ok_or_else(|| format!("Workspace not found: {}", workspace_id))?;
+                limits.validate(
+                    &format!("Workspace \"{}\"", patch.name.as_ref().unwrap_or(&existing.name)),
+                    patch.buttons.as_deref().unwrap_or(&[]),
+                    patch.encoders.as_deref().unwrap_or(&[]),
+                )?;
+            }
+        }
+        for workspace in update.workspaces.iter().flatten() {
+            limits.validate(&format!("Workspace \"{}\"", workspace.name), &workspace.buttons, &workspace.encoders)?;
+        }
+        limits.validate(
+            "Profile",
+            update.buttons.as_deref().unwrap_or(&[]),
+            update.encoders.as_deref().unwrap_or(&[]),
+        )?;
+        if let Some(index) = update.active_workspace_index {
+            let workspace_count = match &update.workspaces {
+                Some(workspaces) if update.merge_workspaces => {
+                    profile.workspaces.len()
+                        + workspaces.iter()
+                            .filter(|w| !profile.workspaces.iter().any(|existing| existing.id == w.id))
+                            .count()
+                }
+                Some(workspaces) => workspaces.len(),
+                None => profile.workspaces.len(),
+            };
+            if index >= workspace_count {
+                return Err(format!(
+                    "Workspace index {} out of range (profile has {})",
+                    index, workspace_count
+                ));
+            }
+        }
+
         if let Some(name) = update.name {
             profile.name = name;
         }
         if let Some(description) = update.description {
             profile.description = Some(description);
         }
+        if let Some(workspaces) = update.workspaces {
+            if update.merge_workspaces {
+                for workspace in workspaces {
+                    match profile.workspaces.iter_mut().find(|w| w.id == workspace.id) {
+                        Some(existing) => *existing = workspace,
+                        None => profile.workspaces.push(workspace),
+                    }
+                }
+            } else {
+                profile.workspaces = workspaces;
+            }
+        }
+        if let Some(patches) = update.workspace_patches {
+            for (workspace_id, patch) in patches {
+                if let Some(workspace) = profile.workspaces.iter_mut().find(|w| w.id == workspace_id) {
+                    workspace.apply_update(patch);
+                }
+            }
+        }
+        if let Some(index) = update.active_workspace_index {
+            profile.active_workspace_index = index;
+        }
+        // Replacing the list can leave the old index past its end
+        profile.active_workspace_index = profile.active_workspace_index.min(profile.workspaces.len().saturating_sub(1));
         if let Some(buttons) = update.buttons {
             profile.buttons = buttons;
         }
@@ -118,14 +365,137 @@ impl ProfileManager {
         Ok(profile)
     }
 
+    /// Replace a profile's tags
+    ///
+    /// Tags are trimmed, blank ones dropped, and duplicates differing only in
+    /// case collapsed to their first spelling.
+    pub fn set_tags(&mut self, id: &str, tags: Vec<String>) -> Result<Profile, String> {
+        let mut cleaned: Vec<String> = Vec::new();
+        for tag in tags {
+            let tag = tag.trim();
+            if !tag.is_empty() && !cleaned.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                cleaned.push(tag.to_string());
+            }
+        }
+        self.modify(id, |profile| {
+            profile.tags = cleaned;
+            Ok(())
+        })
+    }
+
+    /// Rename a profile; surrounding whitespace is trimmed
+    pub fn rename(&mut self, id: &str, new_name: &str) -> Result<Profile, String> {
+        let name = new_name.trim();
+        if name.is_empty() {
+            return Err("Profile name cannot be empty".to_string());
+        }
+        self.modify(id, |profile| {
+            profile.name = name.to_string();
+            Ok(())
+        })
+    }
+
+    /// Apply a change to a profile, then stamp and save it
+    fn modify(
+        &mut self,
+        id: &str,
+        change: impl FnOnce(&mut Profile) -> Result<(), String>,
+    ) -> Result<Profile, String> {
+        let profile = self.profiles.get_mut(id)
+            .ok_or_else(|| format!("Profile not found: {}", id))?;
+
+        change(profile)?;
+        profile.updated_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        let profile = profile.clone();
+        self.save_profile(&profile)?;
+
+        Ok(profile)
+    }
+
+    /// Make the workspace at `index` the active one
+    pub fn set_active_workspace(&mut self, id: &str, index: usize) -> Result<Profile, String> {
+        self.modify(id, |profile| {
+            if index >= profile.workspaces.len() {
+                return Err(format!(
+                    "Workspace index {} out of range (profile has {})",
+                    index,
+                    profile.workspaces.len()
+                ));
+            }
+            profile.active_workspace_index = index;
+            Ok(())
+        })
+    }
+
+    /// Set or clear the brightness applied when a profile is activated (clamped to 100)
+    pub fn set_default_brightness(&mut self, id: &str, brightness: Option<u8>) -> Result<Profile, String> {
+        self.modify(id, |profile| {
+            profile.default_brightness = brightness.map(|level| level.min(100));
+            Ok(())
+        })
+    }
+
+    /// Append an empty workspace, named "Workspace N" if no name is given
+    pub fn add_workspace(&mut self, id: &str, name: Option<String>) -> Result<Profile, String> {
+        self.modify(id, |profile| {
+            let name = name.unwrap_or_else(|| format!("Workspace {}", profile.workspaces.len() + 1));
+            profile.workspaces.push(Workspace::new(name));
+            Ok(())
+        })
+    }
+
+    /// Remove a workspace, keeping the active index on the same workspace
+    /// where possible (or the last one if the active workspace was removed
+    /// from the end). A profile's only workspace can't be deleted.
+    pub fn delete_workspace(&mut self, id: &str, workspace_id: &str) -> Result<Profile, String> {
+        self.modify(id, |profile| {
+            let position = profile.workspaces.iter().position(|w| w.id == workspace_id)
+                .ok_or_else(|| format!("Workspace not found: {}", workspace_id))?;
+            if profile.workspaces.len() == 1 {
+                return Err("Cannot delete the only workspace".to_string());
+            }
+
+            profile.workspaces.remove(position);
+            if position < profile.active_workspace_index {
+                profile.active_workspace_index -= 1;
+            }
+            profile.active_workspace_index = profile.active_workspace_index.min(profile.workspaces.len() - 1);
+            Ok(())
+        })
+    }
+
+    /// Apply a partial update to one workspace
+    pub fn update_workspace(
+        &mut self,
+        id: &str,
+        workspace_id: &str,
+        update: WorkspaceUpdate,
+    ) -> Result<Profile, String> {
+        let limits = self.limits;
+        self.modify(id, |profile| {
+            let workspace = profile.workspaces.iter_mut().find(|w| w.id == workspace_id)
+                .ok_or_else(|| format!("Workspace not found: {}", workspace_id))?;
+            limits.validate(
+                &format!("Workspace \"{}\"", update.name.as_ref().unwrap_or(&workspace.name)),
+                update.buttons.as_deref().unwrap_or(&[]),
+                update.encoders.as_deref().unwrap_or(&[]),
+            )?;
+            workspace.apply_update(update);
+            Ok(())
+        })
+    }
+
     /// Delete a profile
     pub fn delete(&mut self, id: &str) -> Result<(), String> {
-        let path = self.profiles_dir.join(format!("{}.json", id));
+        let path = self.profile_path(id);
 
-        if path.exists() {
-            fs::remove_file(&path)
-                .map_err(|e| format!("Failed to delete profile file: {}", e))?;
-        }
+        self.mark_written(&path);
+        storage::remove(&path)
+            .map_err(|e| format!("Failed to delete profile file: {}", e))?;
 
         self.profiles.remove(id);
         Ok(())
@@ -133,11 +503,33 @@ impl ProfileManager {
 
     /// Import a profile from JSON string
     pub fn import(&mut self, json: &str) -> Result<Profile, String> {
-        let mut profile: Profile = serde_json::from_str(json)
+        let profile: Profile = serde_json::from_str(json)
             .map_err(|e| format!("Failed to parse profile JSON: {}", e))?;
+        self.add_imported(profile)
+    }
+
+    /// Import a profile from JSON, listing the actions in it that run programs
+    /// or code (see `safety`) so they can be reviewed before activating it
+    pub fn import_with_review(&mut self, json: &str) -> Result<ImportReview, String> {
+        let profile = self.import(json)?;
+        let risks = safety::analyze(&profile);
+        Ok(ImportReview { profile, risks })
+    }
+
+    /// Import a profile bundle (see `bundle`)
+    pub fn import_bundle(&mut self, bytes: &[u8]) -> Result<Profile, String> {
+        let profile = bundle::unpack(bytes)?;
+        self.add_imported(profile)
+    }
+
+    /// Migrate, validate and save an imported profile under a new ID
+    fn add_imported(&mut self, mut profile: Profile) -> Result<Profile, String> {
+        migrations::migrate(&mut profile);
+        self.validate_profile(&profile)?;
 
         // Generate new ID to avoid conflicts
         profile.id = uuid::Uuid::new_v4().to_string();
+        profile.order = self.next_order();
         profile.updated_at = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap_or_default()
@@ -149,6 +541,39 @@ impl ProfileManager {
         Ok(profile)
     }
 
+    /// Copy a profile under a new ID
+    ///
+    /// The copy is named "<name> (copy)" unless `new_name` is given, and is
+    /// placed at the end of the list.
+    pub fn duplicate(&mut self, id: &str, new_name: Option<String>) -> Result<Profile, String> {
+        let source = self.profiles.get(id)
+            .ok_or_else(|| format!("Profile not found: {}", id))?;
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let mut profile = source.clone();
+        profile.id = uuid::Uuid::new_v4().to_string();
+        profile.name = new_name.unwrap_or_else(|| format!("{} (copy)", source.name));
+        profile.order = self.next_order();
+        profile.created_at = now;
+        profile.updated_at = now;
+
+        self.save_profile(&profile)?;
+        self.profiles.insert(profile.id.clone(), profile.clone());
+
+        Ok(profile)
+    }
+
+    /// Export a profile as a bundle with its button images as separate files
+    pub fn export_bundle(&self, id: &str) -> Result<Vec<u8>, String> {
+        let profile = self.profiles.get(id)
+            .ok_or_else(|| format!("Profile not found: {}", id))?;
+
+        bundle::pack(profile)
+    }
+
     /// Export a profile to JSON string
     pub fn export(&self, id: &str) -> Result<String, String> {
         let profile = self.profiles.get(id)
@@ -159,10 +584,27 @@ impl ProfileManager {
     }
 }
 
+/// Whether a profile's name, description, button labels or tags contain a
+/// lowercase query
+fn matches_query(profile: &Profile, query: &str) -> bool {
+    let contains = |text: &str| text.to_lowercase().contains(query);
+
+    contains(&profile.name)
+        || profile.description.as_deref().is_some_and(contains)
+        || profile.tags.iter().any(|tag| contains(tag))
+        || profile
+            .workspaces
+            .iter()
+            .flat_map(|workspace| &workspace.buttons)
+            .chain(&profile.buttons)
+            .filter_map(|button| button.label.as_deref())
+            .any(contains)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::types::{ButtonConfig, EncoderConfig};
+    use crate::config::types::{ButtonConfig, EncoderConfig, IndexLimits, Workspace, WorkspaceUpdate};
     use crate::actions::types::{Action, MediaAction, MediaActionType, KeyboardAction};
     use tempfile::TempDir;
 
@@ -181,6 +623,7 @@ mod tests {
             label: Some("Volume".to_string()),
             action: Some(Action::Media(MediaAction {
                 action: MediaActionType::VolumeUp,
+                ..Default::default()
             })),
             ..Default::default()
         }];
@@ -190,8 +633,14 @@ mod tests {
             index: 0,
             label: Some("Brightness".to_string()),
             clockwise_action: Some(Action::Keyboard(KeyboardAction {
-                key: "Up".to_string(),
+                id: None,
+                name: None,
+                icon: None,
+                enabled: None,
+                keys: "Up".to_string(),
                 modifiers: vec![],
+                hold_duration: None,
+                sequence: Vec::new(),
             })),
             ..Default::default()
         }];
@@ -274,6 +723,38 @@ mod tests {
         assert_eq!(manager.list().len(), 1);
     }
 
+    #[test]
+    fn test_new_migrates_legacy_top_level_buttons() {
+        let temp_dir = create_test_dir();
+
+        // Pre-workspace profile format: buttons/encoders at the top level
+        let legacy_json = r#"{
+            "id": "legacy-profile",
+            "name": "Legacy",
+            "createdAt": 1,
+            "updatedAt": 1,
+            "buttons": [{ "index": 0, "label": "Old Button" }],
+            "encoders": [{ "index": 1, "label": "Old Encoder" }]
+        }"#;
+        let file_path = temp_dir.path().join("legacy-profile.json");
+        fs::write(&file_path, legacy_json).unwrap();
+
+        let manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = manager.get("legacy-profile").unwrap();
+
+        assert_eq!(profile.workspaces.len(), 1);
+        assert_eq!(profile.workspaces[0].buttons[0].label, Some("Old Button".to_string()));
+        assert_eq!(profile.workspaces[0].encoders[0].label, Some("Old Encoder".to_string()));
+        assert!(profile.buttons.is_empty());
+        assert!(profile.encoders.is_empty());
+
+        // Migrated form is written back to disk
+        let saved: Profile = serde_json::from_str(&fs::read_to_string(&file_path).unwrap()).unwrap();
+        assert!(saved.buttons.is_empty());
+        assert_eq!(saved.workspaces[0].buttons.len(), 1);
+        assert_eq!(saved.schema_version, migrations::CURRENT_SCHEMA_VERSION);
+    }
+
     // ========== List Tests ==========
 
     #[test]
@@ -288,6 +769,19 @@ mod tests {
         assert_eq!(manager.list().len(), 3);
     }
 
+    #[test]
+    fn test_list_returns_creation_order() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+
+        manager.create("Profile A".to_string()).unwrap();
+        manager.create("Profile B".to_string()).unwrap();
+        manager.create("Profile C".to_string()).unwrap();
+
+        let names: Vec<&str> = manager.list().iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Profile A", "Profile B", "Profile C"]);
+    }
+
     #[test]
     fn test_list_returns_empty_for_new_manager() {
         let temp_dir = create_test_dir();
@@ -296,6 +790,116 @@ mod tests {
         assert!(manager.list().is_empty());
     }
 
+    // ========== Search Tests ==========
+
+    #[test]
+    fn test_search_matches_name_description_and_button_label() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+
+        let streaming = manager.create("Streaming".to_string()).unwrap();
+        let update = ProfileUpdate {
+            description: Some("OBS scenes".to_string()),
+            ..Default::default()
+        };
+        manager.update(&streaming.id, update).unwrap();
+
+        let mut labelled = create_profile_with_actions("Office");
+        labelled.buttons[0].label = Some("Mute Mic".to_string());
+        let office = manager.import(&serde_json::to_string(&labelled).unwrap()).unwrap();
+        manager.create("Gaming".to_string()).unwrap();
+
+        let ids = |query: &str| -> Vec<String> { manager.search(query).iter().map(|p| p.id.clone()).collect() };
+        assert_eq!(ids("stream"), vec![streaming.id.clone()]);
+        assert_eq!(ids("obs"), vec![streaming.id.clone()]);
+        assert_eq!(ids("mute mic"), vec![office.id.clone()]);
+        assert_eq!(ids("MIC"), vec![office.id.clone()]);
+        assert!(ids("nothing like this").is_empty());
+        assert_eq!(ids("  ").len(), 3);
+    }
+
+    #[test]
+    fn test_tags_filter_and_search() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let work = manager.create("Work".to_string()).unwrap();
+        let play = manager.create("Play".to_string()).unwrap();
+
+        let tagged = manager
+            .set_tags(&work.id, vec![" Office ".to_string(), "office".to_string(), "".to_string(), "daily".to_string()])
+            .unwrap();
+        assert_eq!(tagged.tags, vec!["Office".to_string(), "daily".to_string()]);
+        manager.set_tags(&play.id, vec!["Daily".to_string()]).unwrap();
+
+        let names = |profiles: Vec<&Profile>| -> Vec<String> { profiles.iter().map(|p| p.name.clone()).collect() };
+        assert_eq!(names(manager.with_tag("OFFICE")), vec!["Work"]);
+        assert_eq!(names(manager.with_tag("daily")), vec!["Work", "Play"]);
+        assert!(manager.with_tag("offi").is_empty());
+        assert_eq!(names(manager.search("offi")), vec!["Work"]);
+
+        let reloaded = ProfileManager::new(temp_dir.path().to_path_buf());
+        assert_eq!(reloaded.get(&work.id).unwrap().tags, vec!["Office".to_string(), "daily".to_string()]);
+    }
+
+    // ========== Reorder Tests ==========
+
+    #[test]
+    fn test_reorder_sets_list_order_and_persists() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+
+        let a = manager.create("A".to_string()).unwrap();
+        let b = manager.create("B".to_string()).unwrap();
+        let c = manager.create("C".to_string()).unwrap();
+
+        let reordered = manager.reorder(&[c.id.clone(), a.id.clone(), b.id.clone()]).unwrap();
+        let names: Vec<&str> = reordered.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["C", "A", "B"]);
+
+        // Order survives a reload from disk
+        let reloaded = ProfileManager::new(temp_dir.path().to_path_buf());
+        let names: Vec<&str> = reloaded.list().iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["C", "A", "B"]);
+    }
+
+    #[test]
+    fn test_reorder_partial_keeps_unlisted_after() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+
+        manager.create("A".to_string()).unwrap();
+        manager.create("B".to_string()).unwrap();
+        let c = manager.create("C".to_string()).unwrap();
+
+        let reordered = manager.reorder(&[c.id]).unwrap();
+        let names: Vec<&str> = reordered.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["C", "A", "B"]);
+    }
+
+    #[test]
+    fn test_reorder_unknown_id_fails() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        manager.create("A".to_string()).unwrap();
+
+        let result = manager.reorder(&["missing".to_string()]);
+        assert!(result.unwrap_err().contains("Profile not found"));
+    }
+
+    #[test]
+    fn test_new_profile_appends_after_reorder() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+
+        let a = manager.create("A".to_string()).unwrap();
+        let b = manager.create("B".to_string()).unwrap();
+        manager.reorder(&[b.id, a.id]).unwrap();
+        manager.create("New".to_string()).unwrap();
+
+        let names: Vec<&str> = manager.list().iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["B", "A", "New"]);
+    }
+
     // ========== Get Tests ==========
 
     #[test]
@@ -393,11 +997,7 @@ mod tests {
 
         let update = ProfileUpdate {
             name: Some("Updated Name".to_string()),
-            description: None,
-            workspaces: None,
-            active_workspace_index: None,
-            buttons: None,
-            encoders: None,
+            ..Default::default()
         };
 
         let updated = manager.update(&profile.id, update).unwrap();
@@ -413,12 +1013,8 @@ mod tests {
         let profile = manager.create("Profile".to_string()).unwrap();
 
         let update = ProfileUpdate {
-            name: None,
             description: Some("New description".to_string()),
-            workspaces: None,
-            active_workspace_index: None,
-            buttons: None,
-            encoders: None,
+            ..Default::default()
         };
 
         let updated = manager.update(&profile.id, update).unwrap();
@@ -440,12 +1036,8 @@ mod tests {
         }];
 
         let update = ProfileUpdate {
-            name: None,
-            description: None,
-            workspaces: None,
-            active_workspace_index: None,
             buttons: Some(new_buttons),
-            encoders: None,
+            ..Default::default()
         };
 
         let updated = manager.update(&profile.id, update).unwrap();
@@ -468,10 +1060,8 @@ mod tests {
         }];
 
         let update = ProfileUpdate {
-            name: None,
-            description: None,
-            buttons: None,
             encoders: Some(new_encoders),
+            ..Default::default()
         };
 
         let updated = manager.update(&profile.id, update).unwrap();
@@ -493,9 +1083,7 @@ mod tests {
 
         let update = ProfileUpdate {
             name: Some("New Name".to_string()),
-            description: None,
-            buttons: None,
-            encoders: None,
+            ..Default::default()
         };
 
         let updated = manager.update(&profile.id, update).unwrap();
@@ -512,9 +1100,7 @@ mod tests {
 
         let update = ProfileUpdate {
             name: Some("Persisted Name".to_string()),
-            description: None,
-            buttons: None,
-            encoders: None,
+            ..Default::default()
         };
 
         manager.update(&profile.id, update).unwrap();
@@ -534,9 +1120,7 @@ mod tests {
 
         let update = ProfileUpdate {
             name: Some("New Name".to_string()),
-            description: None,
-            buttons: None,
-            encoders: None,
+            ..Default::default()
         };
 
         let result = manager.update("nonexistent-id", update);
@@ -545,6 +1129,441 @@ mod tests {
         assert!(result.unwrap_err().contains("Profile not found"));
     }
 
+    // ========== Rename Tests ==========
+
+    #[test]
+    fn test_rename_changes_only_name_and_persists() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let created = manager.create("Old".to_string()).unwrap();
+
+        let renamed = manager.rename(&created.id, "  Streaming  ").unwrap();
+        assert_eq!(renamed.name, "Streaming");
+        assert_eq!(renamed.order, created.order);
+        assert_eq!(renamed.workspaces.len(), created.workspaces.len());
+
+        let reloaded = ProfileManager::new(temp_dir.path().to_path_buf());
+        assert_eq!(reloaded.get(&created.id).unwrap().name, "Streaming");
+    }
+
+    #[test]
+    fn test_rename_rejects_blank_name_and_unknown_id() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let created = manager.create("Old".to_string()).unwrap();
+
+        assert!(manager.rename(&created.id, "   ").is_err());
+        assert_eq!(manager.get(&created.id).unwrap().name, "Old");
+        assert!(manager.rename("nonexistent-id", "New").unwrap_err().contains("Profile not found"));
+    }
+
+    // ========== Partial Workspace Update Tests ==========
+
+    /// Create a profile with two named workspaces
+    fn create_profile_with_workspaces(manager: &mut ProfileManager) -> Profile {
+        let profile = manager.create("Workspaces".to_string()).unwrap();
+        let mut second = Workspace::new("Second".to_string());
+        second.buttons = vec![ButtonConfig {
+            index: 1,
+            label: Some("Keep Me".to_string()),
+            ..Default::default()
+        }];
+
+        let mut workspaces = profile.workspaces.clone();
+        workspaces.push(second);
+        manager
+            .update(
+                &profile.id,
+                ProfileUpdate {
+                    workspaces: Some(workspaces),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_update_workspaces_replaces_list_by_default() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = create_profile_with_workspaces(&mut manager);
+        assert_eq!(profile.workspaces.len(), 2);
+
+        let update = ProfileUpdate {
+            workspaces: Some(vec![Workspace::new("Only".to_string())]),
+            ..Default::default()
+        };
+        let updated = manager.update(&profile.id, update).unwrap();
+
+        assert_eq!(updated.workspaces.len(), 1);
+        assert_eq!(updated.workspaces[0].name, "Only");
+    }
+
+    #[test]
+    fn test_update_shrinking_workspaces_clamps_active_index() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = create_profile_with_workspaces(&mut manager);
+        let profile = manager.set_active_workspace(&profile.id, 1).unwrap();
+
+        let update = ProfileUpdate {
+            workspaces: Some(vec![Workspace::new("Only".to_string())]),
+            ..Default::default()
+        };
+        let updated = manager.update(&profile.id, update).unwrap();
+
+        assert_eq!(updated.active_workspace_index, 0);
+        assert_eq!(updated.active_workspace().unwrap().name, "Only");
+    }
+
+    #[test]
+    fn test_update_rejects_out_of_range_active_index() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = create_profile_with_workspaces(&mut manager);
+
+        let update = ProfileUpdate {
+            workspaces: Some(vec![Workspace::new("Only".to_string())]),
+            active_workspace_index: Some(1),
+            ..Default::default()
+        };
+        let err = manager.update(&profile.id, update).unwrap_err();
+        assert!(err.contains("Workspace index 1 out of range"));
+
+        // Nothing was applied
+        let unchanged = manager.get(&profile.id).unwrap();
+        assert_eq!(unchanged.workspaces.len(), 2);
+
+        let update = ProfileUpdate { active_workspace_index: Some(1), ..Default::default() };
+        assert_eq!(manager.update(&profile.id, update).unwrap().active_workspace_index, 1);
+    }
+
+    #[test]
+    fn test_update_workspaces_merge_keeps_unlisted() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = create_profile_with_workspaces(&mut manager);
+
+        let mut renamed = profile.workspaces[0].clone();
+        renamed.name = "Renamed".to_string();
+        let added = Workspace::new("Third".to_string());
+
+        let update = ProfileUpdate {
+            workspaces: Some(vec![renamed, added]),
+            merge_workspaces: true,
+            ..Default::default()
+        };
+        let updated = manager.update(&profile.id, update).unwrap();
+
+        assert_eq!(updated.workspaces.len(), 3);
+        assert_eq!(updated.workspaces[0].name, "Renamed");
+        assert_eq!(updated.workspaces[1].name, "Second");
+        assert_eq!(updated.workspaces[1].buttons[0].label, Some("Keep Me".to_string()));
+        assert_eq!(updated.workspaces[2].name, "Third");
+    }
+
+    #[test]
+    fn test_update_workspace_patch_changes_only_specified_fields() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = create_profile_with_workspaces(&mut manager);
+        let second_id = profile.workspaces[1].id.clone();
+
+        let mut patches = HashMap::new();
+        patches.insert(
+            second_id.clone(),
+            WorkspaceUpdate {
+                name: Some("Patched".to_string()),
+                ..Default::default()
+            },
+        );
+        let update = ProfileUpdate {
+            workspace_patches: Some(patches),
+            ..Default::default()
+        };
+        let updated = manager.update(&profile.id, update).unwrap();
+
+        assert_eq!(updated.workspaces.len(), 2);
+        assert_eq!(updated.workspaces[0].name, profile.workspaces[0].name);
+        assert_eq!(updated.workspaces[1].id, second_id);
+        assert_eq!(updated.workspaces[1].name, "Patched");
+        // Buttons were not in the patch and must survive
+        assert_eq!(updated.workspaces[1].buttons[0].label, Some("Keep Me".to_string()));
+    }
+
+    #[test]
+    fn test_update_workspace_patch_unknown_id_fails_without_changes() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = create_profile_with_workspaces(&mut manager);
+
+        let mut patches = HashMap::new();
+        patches.insert("missing".to_string(), WorkspaceUpdate::default());
+        let update = ProfileUpdate {
+            name: Some("Should Not Apply".to_string()),
+            workspace_patches: Some(patches),
+            ..Default::default()
+        };
+        let result = manager.update(&profile.id, update);
+
+        assert!(result.unwrap_err().contains("Workspace not found"));
+        assert_eq!(manager.get(&profile.id).unwrap().name, "Workspaces");
+    }
+
+    #[test]
+    fn test_profile_update_merge_fields_default_from_json() {
+        let update: ProfileUpdate = serde_json::from_str(r#"{"name":"X"}"#).unwrap();
+
+        assert!(!update.merge_workspaces);
+        assert!(update.workspace_patches.is_none());
+    }
+
+    // ========== Index Validation Tests ==========
+
+    fn button(index: usize) -> ButtonConfig {
+        ButtonConfig { index, ..Default::default() }
+    }
+
+    fn encoder(index: usize) -> EncoderConfig {
+        EncoderConfig { index, ..Default::default() }
+    }
+
+    #[test]
+    fn test_update_rejects_out_of_range_indices() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = create_profile_with_workspaces(&mut manager);
+
+        let mut workspace = Workspace::new("Bad".to_string());
+        workspace.buttons = vec![button(0), button(6), button(99)];
+        workspace.encoders = vec![encoder(3)];
+        let update = ProfileUpdate {
+            name: Some("Should Not Apply".to_string()),
+            workspaces: Some(vec![workspace]),
+            ..Default::default()
+        };
+        let err = manager.update(&profile.id, update).unwrap_err();
+
+        assert!(err.contains("Workspace \"Bad\""));
+        assert!(err.contains("button index out of range (0-5): 6, 99"));
+        assert!(err.contains("encoder index out of range (0-2): 3"));
+        assert_eq!(manager.get(&profile.id).unwrap().name, "Workspaces");
+    }
+
+    #[test]
+    fn test_update_rejects_duplicate_indices() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = create_profile_with_workspaces(&mut manager);
+        let workspace_id = profile.workspaces[0].id.clone();
+
+        let mut patches = HashMap::new();
+        patches.insert(workspace_id.clone(), WorkspaceUpdate {
+            buttons: Some(vec![button(1), button(2), button(1)]),
+            ..Default::default()
+        });
+        let update = ProfileUpdate { workspace_patches: Some(patches), ..Default::default() };
+        let err = manager.update(&profile.id, update).unwrap_err();
+        assert!(err.contains("duplicate button index: 1"));
+
+        let update = WorkspaceUpdate { encoders: Some(vec![encoder(0), encoder(0)]), ..Default::default() };
+        let err = manager.update_workspace(&profile.id, &workspace_id, update).unwrap_err();
+        assert!(err.contains("duplicate encoder index: 0"));
+    }
+
+    #[test]
+    fn test_import_rejects_out_of_range_indices() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let mut profile = Profile::new("Imported".to_string());
+        profile.workspaces[0].buttons = vec![button(7)];
+
+        let result = manager.import(&serde_json::to_string(&profile).unwrap());
+
+        assert!(result.unwrap_err().contains("button index out of range (0-5): 7"));
+        assert!(manager.list().is_empty());
+    }
+
+    #[test]
+    fn test_index_limits_can_be_overridden() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        manager.set_index_limits(IndexLimits { buttons: 15, encoders: 3 });
+        let mut profile = Profile::new("Larger Device".to_string());
+        profile.workspaces[0].buttons = vec![button(14)];
+
+        assert!(manager.import(&serde_json::to_string(&profile).unwrap()).is_ok());
+    }
+
+    // ========== Default Brightness Tests ==========
+
+    #[test]
+    fn test_set_default_brightness_persists_and_clamps() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = manager.create("Night".to_string()).unwrap();
+        assert_eq!(profile.default_brightness, None);
+
+        manager.set_default_brightness(&profile.id, Some(150)).unwrap();
+        let reloaded = ProfileManager::new(temp_dir.path().to_path_buf());
+        assert_eq!(reloaded.get(&profile.id).unwrap().default_brightness, Some(100));
+
+        let cleared = manager.set_default_brightness(&profile.id, None).unwrap();
+        assert_eq!(cleared.default_brightness, None);
+    }
+
+    // ========== Workspace Management Tests ==========
+
+    /// Saved profile with three workspaces, the second one active
+    fn create_three_workspace_profile(manager: &mut ProfileManager) -> Profile {
+        let profile = manager.create("Layers".to_string()).unwrap();
+        manager.add_workspace(&profile.id, Some("Second".to_string())).unwrap();
+        manager.add_workspace(&profile.id, None).unwrap();
+        manager.set_active_workspace(&profile.id, 1).unwrap()
+    }
+
+    #[test]
+    fn test_add_workspace_names_by_position() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = create_three_workspace_profile(&mut manager);
+
+        let names: Vec<&str> = profile.workspaces.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, vec!["Workspace 1", "Second", "Workspace 3"]);
+    }
+
+    #[test]
+    fn test_set_active_workspace_persists() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = create_three_workspace_profile(&mut manager);
+
+        let reloaded = ProfileManager::new(temp_dir.path().to_path_buf());
+        assert_eq!(reloaded.get(&profile.id).unwrap().active_workspace_index, 1);
+    }
+
+    #[test]
+    fn test_set_active_workspace_rejects_out_of_range() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = create_three_workspace_profile(&mut manager);
+
+        assert!(manager.set_active_workspace(&profile.id, 3).is_err());
+        assert_eq!(manager.get(&profile.id).unwrap().active_workspace_index, 1);
+    }
+
+    #[test]
+    fn test_delete_workspace_before_active_shifts_index() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = create_three_workspace_profile(&mut manager);
+
+        let updated = manager.delete_workspace(&profile.id, &profile.workspaces[0].id).unwrap();
+        assert_eq!(updated.active_workspace_index, 0);
+        assert_eq!(updated.active_workspace().unwrap().name, "Second");
+    }
+
+    #[test]
+    fn test_delete_active_last_workspace_clamps_index() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = create_three_workspace_profile(&mut manager);
+        manager.set_active_workspace(&profile.id, 2).unwrap();
+
+        let updated = manager.delete_workspace(&profile.id, &profile.workspaces[2].id).unwrap();
+        assert_eq!(updated.workspaces.len(), 2);
+        assert_eq!(updated.active_workspace_index, 1);
+    }
+
+    #[test]
+    fn test_delete_only_workspace_fails() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = manager.create("Single".to_string()).unwrap();
+
+        let err = manager.delete_workspace(&profile.id, &profile.workspaces[0].id).unwrap_err();
+        assert_eq!(err, "Cannot delete the only workspace");
+    }
+
+    #[test]
+    fn test_update_workspace_applies_partial_update() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = create_three_workspace_profile(&mut manager);
+
+        let updated = manager
+            .update_workspace(
+                &profile.id,
+                &profile.workspaces[2].id,
+                WorkspaceUpdate {
+                    name: Some("Media".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(updated.workspaces[2].name, "Media");
+        assert!(manager.update_workspace(&profile.id, "missing", WorkspaceUpdate::default()).is_err());
+    }
+
+    // ========== Crash Safety Tests ==========
+
+    #[test]
+    fn test_partial_temp_file_leaves_original_intact() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = manager.create("Survivor".to_string()).unwrap();
+
+        // A crash mid-save leaves a truncated temp file behind
+        let path = temp_dir.path().join(format!("{}.json", profile.id));
+        fs::write(storage::sibling(&path, "tmp"), "{\"id\": \"trunc").unwrap();
+
+        let reloaded = ProfileManager::new(temp_dir.path().to_path_buf());
+        assert_eq!(reloaded.list().len(), 1);
+        assert_eq!(reloaded.get(&profile.id).unwrap().name, "Survivor");
+    }
+
+    #[test]
+    fn test_corrupt_profile_restored_from_backup() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = manager.create("Before".to_string()).unwrap();
+        manager.update(&profile.id, ProfileUpdate { name: Some("After".to_string()), ..Default::default() }).unwrap();
+
+        let path = temp_dir.path().join(format!("{}.json", profile.id));
+        fs::write(&path, "{ corrupt").unwrap();
+
+        let reloaded = ProfileManager::new(temp_dir.path().to_path_buf());
+        assert_eq!(reloaded.get(&profile.id).unwrap().name, "Before");
+    }
+
+    // ========== External Change Tests ==========
+
+    #[test]
+    fn test_own_writes_are_tracked() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = manager.create("Mine".to_string()).unwrap();
+
+        let path = temp_dir.path().join(format!("{}.json", profile.id));
+        assert!(manager.is_own_write(&path));
+        assert!(!manager.is_own_write(&temp_dir.path().join("other.json")));
+    }
+
+    #[test]
+    fn test_reload_keeps_cache_for_unreadable_file() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let profile = manager.create("Cached".to_string()).unwrap();
+
+        let path = temp_dir.path().join(format!("{}.json", profile.id));
+        storage::remove(&path).unwrap();
+        fs::write(&path, "{ half saved").unwrap();
+
+        assert_eq!(manager.reload_from_disk(&path), None);
+        assert_eq!(manager.get(&profile.id).unwrap().name, "Cached");
+        assert_eq!(manager.reload_from_disk(&temp_dir.path().join("notes.txt")), None);
+    }
+
     // ========== Delete Tests ==========
 
     #[test]
@@ -613,8 +1632,10 @@ mod tests {
         let imported = manager.import(&json).unwrap();
 
         assert_eq!(imported.name, "Imported Profile");
-        assert_eq!(imported.buttons[0].label, Some("Volume".to_string()));
-        assert_eq!(imported.encoders[0].label, Some("Brightness".to_string()));
+        // Legacy top-level buttons/encoders are migrated into the first workspace
+        assert!(imported.buttons.is_empty());
+        assert_eq!(imported.workspaces[0].buttons[0].label, Some("Volume".to_string()));
+        assert_eq!(imported.workspaces[0].encoders[0].label, Some("Brightness".to_string()));
     }
 
     #[test]
@@ -683,6 +1704,113 @@ mod tests {
         assert!(result.unwrap_err().contains("Failed to parse profile JSON"));
     }
 
+    #[test]
+    fn test_import_with_review_lists_risky_actions_and_still_imports() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+
+        let mut profile = create_profile_with_actions("Shared");
+        profile.buttons.push(ButtonConfig {
+            index: 1,
+            action: Some(serde_json::from_value(serde_json::json!({
+                "type": "launch", "path": "cmd.exe", "args": ["/c", "echo hi"], "useShell": true
+            })).unwrap()),
+            ..Default::default()
+        });
+
+        let review = manager.import_with_review(&serde_json::to_string(&profile).unwrap()).unwrap();
+
+        assert_eq!(review.risks.len(), 1);
+        assert_eq!(review.risks[0].kind, safety::RiskKind::Shell);
+        assert_eq!(review.risks[0].detail, "cmd.exe /c echo hi");
+        assert!(manager.get(&review.profile.id).is_some());
+    }
+
+    // ========== Duplicate Tests ==========
+
+    #[test]
+    fn test_duplicate_copies_workspaces_under_new_id() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let original = manager.import(&serde_json::to_string(&create_profile_with_actions("Streaming")).unwrap()).unwrap();
+
+        let copy = manager.duplicate(&original.id, None).unwrap();
+
+        assert_ne!(copy.id, original.id);
+        assert_eq!(copy.name, "Streaming (copy)");
+        assert_eq!(copy.workspaces.len(), original.workspaces.len());
+        assert_eq!(copy.workspaces[0].buttons[0].label, Some("Volume".to_string()));
+        assert!(copy.order > original.order);
+        assert_eq!(manager.list().len(), 2);
+    }
+
+    #[test]
+    fn test_duplicate_is_independent_of_source() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let original = manager.create("Source".to_string()).unwrap();
+        let copy = manager.duplicate(&original.id, Some("Renamed".to_string())).unwrap();
+        assert_eq!(copy.name, "Renamed");
+
+        manager.add_workspace(&copy.id, None).unwrap();
+
+        assert_eq!(manager.get(&original.id).unwrap().workspaces.len(), 1);
+        assert_eq!(manager.get(&copy.id).unwrap().workspaces.len(), 2);
+    }
+
+    #[test]
+    fn test_duplicate_saves_to_disk_with_fresh_timestamps() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let mut source = Profile::new("Old".to_string());
+        source.created_at = 1000;
+        source.updated_at = 2000;
+        let original = manager.import(&serde_json::to_string(&source).unwrap()).unwrap();
+
+        let copy = manager.duplicate(&original.id, None).unwrap();
+
+        assert!(copy.created_at > 2000);
+        assert_eq!(copy.created_at, copy.updated_at);
+        assert!(temp_dir.path().join(format!("{}.json", copy.id)).exists());
+    }
+
+    #[test]
+    fn test_duplicate_returns_error_for_unknown_id() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+
+        let result = manager.duplicate("nonexistent-id", None);
+
+        assert!(result.unwrap_err().contains("Profile not found"));
+    }
+
+    // ========== Bundle Tests ==========
+
+    #[test]
+    fn test_bundle_round_trip_creates_new_profile() {
+        let temp_dir = create_test_dir();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf());
+        let original = manager.import(&serde_json::to_string(&create_profile_with_actions("Bundle")).unwrap()).unwrap();
+
+        let bytes = manager.export_bundle(&original.id).unwrap();
+        let imported = manager.import_bundle(&bytes).unwrap();
+
+        assert_ne!(imported.id, original.id);
+        assert_eq!(imported.name, "Bundle");
+        assert_eq!(imported.workspaces[0].buttons[0].label, Some("Volume".to_string()));
+        assert_eq!(manager.list().len(), 2);
+    }
+
+    #[test]
+    fn test_export_bundle_returns_error_for_unknown_id() {
+        let temp_dir = create_test_dir();
+        let manager = ProfileManager::new(temp_dir.path().to_path_buf());
+
+        let result = manager.export_bundle("nonexistent-id");
+
+        assert!(result.unwrap_err().contains("Profile not found"));
+    }
+
     // ========== Export Tests ==========
 
     #[test]
@@ -713,10 +1841,9 @@ mod tests {
         }];
 
         let update = ProfileUpdate {
-            name: None,
             description: Some("Test description".to_string()),
             buttons: Some(buttons),
-            encoders: None,
+            ..Default::default()
         };
         manager.update(&profile.id, update).unwrap();
 
@@ -753,10 +1880,9 @@ mod tests {
         }];
 
         let update = ProfileUpdate {
-            name: None,
             description: Some("Roundtrip description".to_string()),
             buttons: Some(buttons),
-            encoders: None,
+            ..Default::default()
         };
         manager.update(&original.id, update).unwrap();
 
@@ -770,8 +1896,8 @@ mod tests {
         assert_ne!(imported.id, original.id);
         assert_eq!(imported.name, "Roundtrip Test");
         assert_eq!(imported.description, Some("Roundtrip description".to_string()));
-        assert_eq!(imported.buttons.len(), 1);
-        assert_eq!(imported.buttons[0].label, Some("Roundtrip Button".to_string()));
+        assert_eq!(imported.workspaces[0].buttons.len(), 1);
+        assert_eq!(imported.workspaces[0].buttons[0].label, Some("Roundtrip Button".to_string()));
     }
 
     // ========== Profile Type Tests ==========
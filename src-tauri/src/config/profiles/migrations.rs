@@ -0,0 +1,130 @@
+//! Profile Migrations
+//!
+//! Upgrades profile files written by older versions of the app. Each entry in
+//! `MIGRATIONS` turns a profile of one schema version into the next, so a
+//! file is brought up to date by running every step from its version onward.
+//!
+//! Versions:
+//! - 1: buttons/encoders at the top level (files without `schemaVersion`)
+//! - 2: buttons/encoders inside workspaces
+
+use crate::config::types::Profile;
+
+/// Schema version written by this build
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+/// Upgrade step from the version it is keyed by to the next
+type Migration = fn(&mut Profile);
+
+/// Migration steps, keyed by the version they upgrade from
+const MIGRATIONS: &[(u32, Migration)] = &[(1, v1_to_v2)];
+
+/// Bring a profile up to the current schema version
+///
+/// Returns true if the profile changed and should be saved. Profiles from a
+/// newer version are left untouched. Legacy top-level buttons/encoders can
+/// still be set through the deprecated update fields, so they are folded
+/// into the first workspace whatever the version.
+pub fn migrate(profile: &mut Profile) -> bool {
+    if profile.schema_version > CURRENT_SCHEMA_VERSION {
+        log::warn!(
+            "Profile {} has schema version {} (newer than {}), loading as is",
+            profile.id,
+            profile.schema_version,
+            CURRENT_SCHEMA_VERSION
+        );
+        return false;
+    }
+
+    let mut changed = false;
+    for (from, step) in MIGRATIONS {
+        if profile.schema_version == *from {
+            step(profile);
+            profile.schema_version = from + 1;
+            log::info!("Migrated profile {} to schema version {}", profile.id, profile.schema_version);
+            changed = true;
+        }
+    }
+
+    changed | profile.migrate_legacy_config()
+}
+
+/// Move top-level buttons/encoders into the first workspace
+fn v1_to_v2(profile: &mut Profile) {
+    profile.migrate_legacy_config();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ========== Migration Tests ==========
+
+    #[test]
+    fn test_migrates_realistic_v1_profile() {
+        // As saved before workspaces existed
+        let json = r#"{
+            "id": "9b2f6c1e-2d4a-4b8e-9c61-0f3d5a7e8b21",
+            "name": "Streaming",
+            "description": "OBS and music",
+            "createdAt": 1717000000000,
+            "updatedAt": 1717100000000,
+            "buttons": [
+                {
+                    "index": 0,
+                    "label": "Mute",
+                    "image": "data:image/png;base64,iVBORw0KGgo=",
+                    "action": { "type": "media", "action": "mute" }
+                },
+                {
+                    "index": 1,
+                    "label": "Scene",
+                    "action": { "type": "keyboard", "keys": "F1", "modifiers": ["ctrl"] }
+                }
+            ],
+            "encoders": [
+                {
+                    "index": 0,
+                    "label": "Volume",
+                    "clockwiseAction": { "type": "media", "action": "volume_up" },
+                    "counterClockwiseAction": { "type": "media", "action": "volume_down" }
+                }
+            ]
+        }"#;
+        let mut profile: Profile = serde_json::from_str(json).unwrap();
+        assert_eq!(profile.schema_version, 1);
+
+        assert!(migrate(&mut profile));
+
+        assert_eq!(profile.schema_version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(profile.workspaces.len(), 1);
+        let workspace = &profile.workspaces[0];
+        assert_eq!(workspace.buttons.len(), 2);
+        assert_eq!(workspace.buttons[1].label.as_deref(), Some("Scene"));
+        assert!(workspace.encoders[0].clockwise_action.is_some());
+        assert!(profile.buttons.is_empty());
+        assert!(profile.encoders.is_empty());
+
+        // Saved form carries the new version and no legacy fields
+        let saved = serde_json::to_value(&profile).unwrap();
+        assert_eq!(saved["schemaVersion"], CURRENT_SCHEMA_VERSION);
+        assert!(saved.get("buttons").is_none());
+    }
+
+    #[test]
+    fn test_current_profile_is_unchanged() {
+        let mut profile = Profile::new("Current".to_string());
+        assert!(!migrate(&mut profile));
+        assert_eq!(profile.schema_version, CURRENT_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_newer_profile_is_left_alone() {
+        let mut profile = Profile::new("Future".to_string());
+        profile.schema_version = CURRENT_SCHEMA_VERSION + 1;
+        profile.buttons = vec![Default::default()];
+
+        assert!(!migrate(&mut profile));
+        assert_eq!(profile.buttons.len(), 1);
+    }
+}
@@ -0,0 +1,278 @@
+//! Import Safety Review
+//!
+//! Lists the actions in an imported profile that run programs or code on
+//! this machine, so the user can check them before activating a profile
+//! from someone else. Nothing is blocked; the import goes ahead either way.
+//!
+//! Script actions, launch actions and launch actions run through the shell
+//! are reported, including those nested in macros, conditionals and button
+//! feedback sources.
+
+use crate::actions::types::{Action, LaunchAction, ScriptAction, ScriptType};
+use crate::config::types::{ButtonConfig, EncoderConfig, Profile};
+use serde::Serialize;
+
+/// Most characters of a script or command line shown in a finding
+const MAX_DETAIL_CHARS: usize = 120;
+
+/// Why an action was flagged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RiskKind {
+    /// Runs inline script code or a script file
+    Script,
+    /// Starts a program, file or URL
+    Launch,
+    /// Starts a command line through the system shell
+    Shell,
+}
+
+/// An action in a profile that runs programs or code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskyAction {
+    /// Where the action is bound, e.g. "Default / Button 2 / long press"
+    pub location: String,
+    pub kind: RiskKind,
+    /// What it would run: the start of the script, or the command line
+    pub detail: String,
+}
+
+/// An imported profile with the actions worth reviewing before using it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReview {
+    pub profile: Profile,
+    pub risks: Vec<RiskyAction>,
+}
+
+/// Find the actions in a profile that run programs or code
+pub fn analyze(profile: &Profile) -> Vec<RiskyAction> {
+    let mut risks = Vec::new();
+    for workspace in &profile.workspaces {
+        for button in &workspace.buttons {
+            check_button(&workspace.name, button, &mut risks);
+        }
+        for encoder in &workspace.encoders {
+            check_encoder(&workspace.name, encoder, &mut risks);
+        }
+    }
+    risks
+}
+
+fn check_button(workspace: &str, button: &ButtonConfig, risks: &mut Vec<RiskyAction>) {
+    let slots = [
+        ("press", &button.action),
+        ("long press", &button.long_press_action),
+        ("shift press", &button.shift_action),
+        ("shift long press", &button.shift_long_press_action),
+    ];
+    for (slot, action) in slots {
+        if let Some(action) = action {
+            let location = format!("{} / Button {} / {}", workspace, button.index + 1, slot);
+            check_action(action, &location, risks);
+        }
+    }
+}
+
+fn check_encoder(workspace: &str, encoder: &EncoderConfig, risks: &mut Vec<RiskyAction>) {
+    let slots = [
+        ("press", &encoder.press_action),
+        ("long press", &encoder.long_press_action),
+        ("clockwise", &encoder.clockwise_action),
+        ("counter-clockwise", &encoder.counter_clockwise_action),
+        ("shift press", &encoder.shift_press_action),
+        ("shift long press", &encoder.shift_long_press_action),
+        ("shift clockwise", &encoder.shift_clockwise_action),
+        ("shift counter-clockwise", &encoder.shift_counter_clockwise_action),
+    ];
+    for (slot, action) in slots {
+        if let Some(action) = action {
+            let location = format!("{} / Encoder {} / {}", workspace, encoder.index + 1, slot);
+            check_action(action, &location, risks);
+        }
+    }
+}
+
+/// Check an action and any actions nested in it
+fn check_action(action: &Action, location: &str, risks: &mut Vec<RiskyAction>) {
+    match action {
+        Action::Script(config) => risks.push(RiskyAction {
+            location: location.to_string(),
+            kind: RiskKind::Script,
+            detail: script_detail(config),
+        }),
+        Action::Launch(config) => risks.push(RiskyAction {
+            location: location.to_string(),
+            kind: if config.use_shell.unwrap_or(false) { RiskKind::Shell } else { RiskKind::Launch },
+            detail: launch_detail(config),
+        }),
+        Action::Macro(config) => {
+            for (i, step) in config.steps.iter().enumerate() {
+                check_action(&step.action, &format!("{} / step {}", location, i + 1), risks);
+            }
+        }
+        Action::Conditional(config) => {
+            for (i, branch) in config.branches.iter().enumerate() {
+                check_action(&branch.then, &format!("{} / branch {}", location, i + 1), risks);
+            }
+            if let Some(default) = &config.default {
+                check_action(default, &format!("{} / default", location), risks);
+            }
+        }
+        Action::ButtonFeedback(config) => check_action(&config.source, &format!("{} / source", location), risks),
+        _ => {}
+    }
+}
+
+fn script_detail(config: &ScriptAction) -> String {
+    let body = match config.script_type {
+        ScriptType::File => config.script_path.clone().unwrap_or_default(),
+        _ => config.script.clone().or_else(|| config.content.clone()).unwrap_or_default(),
+    };
+    shorten(&format!("{:?}: {}", config.script_type, body.trim()))
+}
+
+fn launch_detail(config: &LaunchAction) -> String {
+    let mut command = config.path.clone();
+    for arg in &config.args {
+        command.push(' ');
+        command.push_str(arg);
+    }
+    shorten(&command)
+}
+
+/// First `MAX_DETAIL_CHARS` characters on one line, marked when cut
+fn shorten(text: &str) -> String {
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if flat.chars().count() <= MAX_DETAIL_CHARS {
+        return flat;
+    }
+    let mut cut: String = flat.chars().take(MAX_DETAIL_CHARS).collect();
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::types::{ConditionalAction, ConditionalBranch, MacroAction, MacroStep, WindowMatch};
+    use crate::config::types::Workspace;
+
+    fn profile_with(buttons: Vec<ButtonConfig>, encoders: Vec<EncoderConfig>) -> Profile {
+        let mut profile = Profile::new("Imported".to_string());
+        let mut workspace = Workspace::new("Main".to_string());
+        workspace.buttons = buttons;
+        workspace.encoders = encoders;
+        profile.workspaces = vec![workspace];
+        profile
+    }
+
+    fn script(code: &str) -> Action {
+        serde_json::from_value(serde_json::json!({
+            "type": "script", "scriptType": "powerShell", "script": code
+        }))
+        .unwrap()
+    }
+
+    fn launch(path: &str, use_shell: bool) -> Action {
+        serde_json::from_value(serde_json::json!({
+            "type": "launch", "path": path, "args": ["--flag"], "useShell": use_shell
+        }))
+        .unwrap()
+    }
+
+    fn media() -> Action {
+        serde_json::from_value(serde_json::json!({"type": "media", "action": "play_pause"})).unwrap()
+    }
+
+    fn button(index: usize, action: Action) -> ButtonConfig {
+        ButtonConfig {
+            index,
+            action: Some(action),
+            ..Default::default()
+        }
+    }
+
+    // ========== Analysis Tests ==========
+
+    #[test]
+    fn test_safe_profile_has_no_risks() {
+        let profile = profile_with(vec![button(0, media())], Vec::new());
+        assert!(analyze(&profile).is_empty());
+    }
+
+    #[test]
+    fn test_flags_script_launch_and_shell() {
+        let mut shifted = button(2, launch("notepad.exe", false));
+        shifted.shift_long_press_action = Some(launch("del /q C:\\temp", true));
+        let encoder = EncoderConfig {
+            index: 0,
+            clockwise_action: Some(script("Remove-Item -Recurse ~")),
+            ..Default::default()
+        };
+        let risks = analyze(&profile_with(vec![shifted], vec![encoder]));
+
+        assert_eq!(
+            risks,
+            vec![
+                RiskyAction {
+                    location: "Main / Button 3 / press".to_string(),
+                    kind: RiskKind::Launch,
+                    detail: "notepad.exe --flag".to_string(),
+                },
+                RiskyAction {
+                    location: "Main / Button 3 / shift long press".to_string(),
+                    kind: RiskKind::Shell,
+                    detail: "del /q C:\\temp --flag".to_string(),
+                },
+                RiskyAction {
+                    location: "Main / Encoder 1 / clockwise".to_string(),
+                    kind: RiskKind::Script,
+                    detail: "PowerShell: Remove-Item -Recurse ~".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_finds_nested_actions() {
+        let conditional = Action::Conditional(ConditionalAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            branches: vec![ConditionalBranch {
+                when: WindowMatch::default(),
+                then: media(),
+            }],
+            default: Some(Box::new(script("echo hi"))),
+        });
+        let macro_action = Action::Macro(MacroAction {
+            id: None,
+            name: None,
+            icon: None,
+            enabled: None,
+            steps: vec![
+                MacroStep { action: media(), delay_after_ms: None },
+                MacroStep { action: conditional, delay_after_ms: None },
+            ],
+            stop_on_error: false,
+        });
+
+        let risks = analyze(&profile_with(vec![button(0, macro_action)], Vec::new()));
+        assert_eq!(risks.len(), 1);
+        assert_eq!(risks[0].location, "Main / Button 1 / press / step 2 / default");
+        assert_eq!(risks[0].kind, RiskKind::Script);
+    }
+
+    #[test]
+    fn test_long_detail_is_shortened() {
+        let code = format!("line one\n{}", "x".repeat(500));
+        let risks = analyze(&profile_with(vec![button(0, script(&code))], Vec::new()));
+        let detail = &risks[0].detail;
+        assert!(detail.starts_with("PowerShell: line one x"));
+        assert_eq!(detail.chars().count(), MAX_DETAIL_CHARS + 1);
+        assert!(detail.ends_with('…'));
+    }
+}
@@ -0,0 +1,122 @@
+//! Secret Storage
+//!
+//! Keeps integration secrets (the Home Assistant token and MQTT password) in
+//! the platform credential store instead of `config.json`. The config file
+//! holds a `keychain:<account>` reference in place of each secret, and
+//! `ConfigManager` swaps the real value in on load and back out on save.
+//!
+//! When no credential store is available (e.g. headless Linux without a
+//! Secret Service), secrets stay in the config file in plaintext and a
+//! warning is logged.
+
+use std::sync::Arc;
+
+/// Credential store service name
+const SERVICE: &str = "soomfon-controller";
+
+/// Prefix marking a config value as a reference to a stored secret
+pub const KEYCHAIN_PREFIX: &str = "keychain:";
+
+/// Where secrets are kept
+pub trait SecretStore: Send + Sync {
+    /// Read a secret, or None if there is no such entry
+    fn get(&self, account: &str) -> Result<Option<String>, String>;
+    /// Create or replace a secret
+    fn set(&self, account: &str, secret: &str) -> Result<(), String>;
+    /// Remove a secret; removing a missing entry is not an error
+    fn delete(&self, account: &str) -> Result<(), String>;
+}
+
+/// The platform credential store (Keychain, Credential Manager, Secret Service)
+pub struct KeyringStore;
+
+impl KeyringStore {
+    fn entry(account: &str) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(SERVICE, account).map_err(|e| format!("Keychain unavailable: {}", e))
+    }
+}
+
+impl SecretStore for KeyringStore {
+    fn get(&self, account: &str) -> Result<Option<String>, String> {
+        match Self::entry(account)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(format!("Failed to read from keychain: {}", e)),
+        }
+    }
+
+    fn set(&self, account: &str, secret: &str) -> Result<(), String> {
+        Self::entry(account)?
+            .set_password(secret)
+            .map_err(|e| format!("Failed to write to keychain: {}", e))
+    }
+
+    fn delete(&self, account: &str) -> Result<(), String> {
+        match Self::entry(account)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Failed to delete from keychain: {}", e)),
+        }
+    }
+}
+
+/// Store used by `ConfigManager::new`
+///
+/// Tests get a shared in-memory store so they never touch the real keychain.
+pub fn default_store() -> Arc<dyn SecretStore> {
+    #[cfg(not(test))]
+    {
+        Arc::new(KeyringStore)
+    }
+    #[cfg(test)]
+    {
+        static SHARED: std::sync::OnceLock<Arc<MemoryStore>> = std::sync::OnceLock::new();
+        SHARED.get_or_init(Default::default).clone()
+    }
+}
+
+/// Account named by a `keychain:` reference, if the value is one
+pub fn reference_account(value: &str) -> Option<&str> {
+    value.strip_prefix(KEYCHAIN_PREFIX)
+}
+
+/// `keychain:` reference to an account
+pub fn reference(account: &str) -> String {
+    format!("{}{}", KEYCHAIN_PREFIX, account)
+}
+
+/// In-memory store for tests
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryStore(parking_lot::Mutex<std::collections::HashMap<String, String>>);
+
+#[cfg(test)]
+impl SecretStore for MemoryStore {
+    fn get(&self, account: &str) -> Result<Option<String>, String> {
+        Ok(self.0.lock().get(account).cloned())
+    }
+
+    fn set(&self, account: &str, secret: &str) -> Result<(), String> {
+        self.0.lock().insert(account.to_string(), secret.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, account: &str) -> Result<(), String> {
+        self.0.lock().remove(account);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ========== Reference Tests ==========
+
+    #[test]
+    fn test_reference_round_trip() {
+        let value = reference("home-assistant-token-1");
+        assert_eq!(value, "keychain:home-assistant-token-1");
+        assert_eq!(reference_account(&value), Some("home-assistant-token-1"));
+        assert_eq!(reference_account("plain-token"), None);
+    }
+}
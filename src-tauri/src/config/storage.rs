@@ -0,0 +1,134 @@
+//! Config File Storage
+//!
+//! Crash-safe writes for settings and profile files. New contents go to
+//! `<file>.tmp`, which is synced and then renamed over the target, so a crash
+//! mid-write leaves either the old or the new file, never a truncated one.
+//! The previous version is copied to `<file>.bak` first and is used when the
+//! main file can't be read.
+
+use serde::de::DeserializeOwned;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Atomically replace `path` with `contents`, keeping a backup of the old file
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp = sibling(path, "tmp");
+    let result = (|| {
+        let mut file = File::create(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        drop(file);
+
+        if path.exists() {
+            fs::copy(path, sibling(path, "bak"))?;
+        }
+        fs::rename(&tmp, path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// Read and parse a JSON file, falling back to its backup if it is missing
+/// or unreadable
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    match parse(path) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            let backup = sibling(path, "bak");
+            let value = parse(&backup).ok()?;
+            log::warn!("Could not read {:?} ({}), restored from backup", path, e);
+            Some(value)
+        }
+    }
+}
+
+/// Delete a file along with its backup and any leftover temp file
+pub fn remove(path: &Path) -> io::Result<()> {
+    for extra in [sibling(path, "bak"), sibling(path, "tmp")] {
+        if extra.exists() {
+            fs::remove_file(extra)?;
+        }
+    }
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// `path` with `.suffix` appended to the file name
+pub fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+fn parse<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    // ========== Write Tests ==========
+
+    #[test]
+    fn test_write_replaces_and_keeps_backup() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.json");
+
+        write_atomic(&path, b"{\"v\":1}").unwrap();
+        write_atomic(&path, b"{\"v\":2}").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"v\":2}");
+        assert_eq!(fs::read_to_string(sibling(&path, "bak")).unwrap(), "{\"v\":1}");
+        assert!(!sibling(&path, "tmp").exists());
+    }
+
+    #[test]
+    fn test_failed_write_preserves_original() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.json");
+        write_atomic(&path, b"{\"v\":1}").unwrap();
+
+        // A directory in the temp file's place makes the write fail part way
+        fs::create_dir(sibling(&path, "tmp")).unwrap();
+        assert!(write_atomic(&path, b"{\"v\":2}").is_err());
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"v\":1}");
+    }
+
+    // ========== Read Tests ==========
+
+    #[test]
+    fn test_read_falls_back_to_backup() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.json");
+        write_atomic(&path, b"{\"v\":1}").unwrap();
+        write_atomic(&path, b"{\"v\":2}").unwrap();
+        fs::write(&path, "{\"v\":").unwrap();
+
+        let value: serde_json::Value = read_json(&path).unwrap();
+        assert_eq!(value["v"], 1);
+    }
+
+    #[test]
+    fn test_remove_deletes_backup() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.json");
+        write_atomic(&path, b"{}").unwrap();
+        write_atomic(&path, b"{}").unwrap();
+
+        remove(&path).unwrap();
+
+        assert!(!path.exists());
+        assert!(!sibling(&path, "bak").exists());
+    }
+}
@@ -3,7 +3,15 @@
 //! Types for application settings, profiles, and button/encoder configurations.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::actions::engine::ExecutionMode;
 use crate::actions::types::Action;
+use crate::hid::acceleration::AccelerationCurve;
+use crate::hid::event_map::EventMap;
+use crate::hid::usb_reader::UsbReadMode;
+use crate::system::logging::LogLevel;
+use crate::hid::types::{DeviceCapabilities, DEBOUNCE_MS, ENCODER_COUNT, LCD_BUTTON_COUNT};
+use super::profiles::migrations::CURRENT_SCHEMA_VERSION;
 
 /// Application settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +29,66 @@ pub struct AppSettings {
     pub home_assistant: Option<HomeAssistantConfig>,
     /// Node-RED configuration
     pub node_red: Option<NodeRedConfig>,
+    /// MQTT broker configuration
+    #[serde(default)]
+    pub mqtt: Option<MqttConfig>,
+    /// Briefly flash an LCD button's image when it is pressed
+    #[serde(default)]
+    pub press_feedback: bool,
+    /// How fast encoder spins scale up rotation steps
+    #[serde(default)]
+    pub encoder_acceleration: AccelerationCurve,
+    /// Window for dropping duplicated button events (0 disables)
+    #[serde(default = "default_debounce_ms")]
+    pub debounce_ms: u64,
+    /// What happens to an action triggered while another is running
+    #[serde(default)]
+    pub action_execution_mode: ExecutionMode,
+    /// Size in KB at which the action history file is rotated
+    #[serde(default = "default_history_file_max_kb")]
+    pub history_file_max_kb: u64,
+    /// Dim the display after this long without device events (None/0 disables)
+    #[serde(default)]
+    pub dim_after_ms: Option<u64>,
+    /// Brightness (0-100) used while dimmed
+    #[serde(default = "default_dim_brightness")]
+    pub dim_brightness: u8,
+    /// Enable developer tools such as sending raw packets to the device
+    #[serde(default)]
+    pub debug_mode: bool,
+    /// Minimum level written to the log
+    #[serde(default)]
+    pub log_level: LogLevel,
+    /// Log every USB packet (very verbose)
+    #[serde(default)]
+    pub usb_logging: bool,
+    /// Auto-reconnect attempts before giving up on a lost device (0 = keep trying)
+    #[serde(default)]
+    pub max_reconnect_attempts: u32,
+    /// Raw event ID overrides for units whose firmware reports other IDs
+    #[serde(default)]
+    pub event_map: EventMap,
+    /// Refuse HTTP actions to loopback, private (RFC 1918) and link-local
+    /// addresses, for profiles imported from untrusted sources. Off by
+    /// default, as Home Assistant and similar services usually run on the LAN.
+    #[serde(default)]
+    pub block_private_networks: bool,
+    /// How device events are read; `sync` is a fallback for platforms where
+    /// async USB transfers misbehave. Applies from the next connection.
+    #[serde(default)]
+    pub usb_read_mode: UsbReadMode,
+}
+
+fn default_debounce_ms() -> u64 {
+    DEBOUNCE_MS
+}
+
+fn default_history_file_max_kb() -> u64 {
+    crate::actions::history_store::DEFAULT_MAX_FILE_BYTES / 1024
+}
+
+fn default_dim_brightness() -> u8 {
+    10
 }
 
 impl Default for AppSettings {
@@ -32,6 +100,21 @@ impl Default for AppSettings {
             auto_launch: false,
             home_assistant: None,
             node_red: None,
+            mqtt: None,
+            press_feedback: false,
+            encoder_acceleration: AccelerationCurve::default(),
+            debounce_ms: DEBOUNCE_MS,
+            action_execution_mode: ExecutionMode::default(),
+            history_file_max_kb: default_history_file_max_kb(),
+            dim_after_ms: None,
+            dim_brightness: default_dim_brightness(),
+            debug_mode: false,
+            log_level: LogLevel::default(),
+            usb_logging: false,
+            max_reconnect_attempts: 0,
+            event_map: EventMap::default(),
+            block_private_networks: false,
+            usb_read_mode: UsbReadMode::default(),
         }
     }
 }
@@ -49,6 +132,22 @@ pub struct HomeAssistantConfig {
 #[serde(rename_all = "camelCase")]
 pub struct NodeRedConfig {
     pub url: String,
+    /// WebSocket endpoint (e.g. "ws://nodered.local:1880/ws/soomfon") for
+    /// sending events over a persistent connection; HTTP is used when unset
+    #[serde(default)]
+    pub websocket_url: Option<String>,
+}
+
+/// MQTT broker configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MqttConfig {
+    /// Broker address, e.g. "mqtt://broker.local:1883" or "mqtts://broker.local"
+    pub broker_url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
 }
 
 /// Workspace containing button and encoder configurations
@@ -78,6 +177,102 @@ impl Workspace {
             encoders: vec![],
         }
     }
+
+    /// Apply a partial update, leaving unspecified fields untouched
+    pub fn apply_update(&mut self, update: WorkspaceUpdate) {
+        if let Some(name) = update.name {
+            self.name = name;
+        }
+        if let Some(buttons) = update.buttons {
+            self.buttons = buttons;
+        }
+        if let Some(encoders) = update.encoders {
+            self.encoders = encoders;
+        }
+    }
+}
+
+/// Number of button and encoder slots a profile may bind
+///
+/// Physical buttons share indices with the LCD buttons, so `buttons` is the
+/// LCD button count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexLimits {
+    pub buttons: usize,
+    pub encoders: usize,
+}
+
+impl Default for IndexLimits {
+    fn default() -> Self {
+        Self {
+            buttons: LCD_BUTTON_COUNT as usize,
+            encoders: ENCODER_COUNT as usize,
+        }
+    }
+}
+
+impl From<&DeviceCapabilities> for IndexLimits {
+    fn from(caps: &DeviceCapabilities) -> Self {
+        Self {
+            buttons: caps.lcd_button_count as usize,
+            encoders: caps.encoder_count as usize,
+        }
+    }
+}
+
+impl IndexLimits {
+    /// Check that button and encoder indices are in range and unique
+    ///
+    /// The error names `context` (e.g. the workspace) and lists every
+    /// offending index.
+    pub fn validate(
+        &self,
+        context: &str,
+        buttons: &[ButtonConfig],
+        encoders: &[EncoderConfig],
+    ) -> Result<(), String> {
+        let mut problems = index_problems("button", buttons.iter().map(|b| b.index), self.buttons);
+        problems.extend(index_problems("encoder", encoders.iter().map(|e| e.index), self.encoders));
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("{}: {}", context, problems.join("; ")))
+        }
+    }
+}
+
+/// Out-of-range and duplicate indices, one message per kind of problem
+fn index_problems(kind: &str, indices: impl Iterator<Item = usize>, count: usize) -> Vec<String> {
+    let mut seen = Vec::new();
+    let mut out_of_range = Vec::new();
+    let mut duplicates = Vec::new();
+    for index in indices {
+        if index >= count {
+            if !out_of_range.contains(&index) {
+                out_of_range.push(index);
+            }
+        } else if seen.contains(&index) {
+            if !duplicates.contains(&index) {
+                duplicates.push(index);
+            }
+        } else {
+            seen.push(index);
+        }
+    }
+
+    let list = |indices: &[usize]| indices.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(", ");
+    let mut problems = Vec::new();
+    if !out_of_range.is_empty() {
+        let range = match count {
+            0 => "none available".to_string(),
+            n => format!("0-{}", n - 1),
+        };
+        problems.push(format!("{} index out of range ({}): {}", kind, range, list(&out_of_range)));
+    }
+    if !duplicates.is_empty() {
+        problems.push(format!("duplicate {} index: {}", kind, list(&duplicates)));
+    }
+    problems
 }
 
 impl Default for Workspace {
@@ -103,6 +298,22 @@ pub struct Profile {
     /// Index of the currently active workspace (0-based)
     #[serde(default)]
     pub active_workspace_index: usize,
+    /// Shift layer settings
+    #[serde(default)]
+    pub shift: ShiftConfig,
+    /// Position in the profile list (ascending)
+    #[serde(default)]
+    pub order: u32,
+    /// Brightness (0-100) applied when the profile is activated; None keeps
+    /// the global brightness
+    #[serde(default)]
+    pub default_brightness: Option<u8>,
+    /// User-defined tags for grouping and filtering profiles
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// File format version; files from before versioning are version 1
+    #[serde(default = "default_schema_version")]
+    pub schema_version: u32,
     /// Creation timestamp
     pub created_at: u64,
     /// Last modified timestamp
@@ -115,6 +326,10 @@ pub struct Profile {
     pub encoders: Vec<EncoderConfig>,
 }
 
+fn default_schema_version() -> u32 {
+    1
+}
+
 fn default_workspaces() -> Vec<Workspace> {
     vec![Workspace::default()]
 }
@@ -133,6 +348,11 @@ impl Profile {
             description: None,
             workspaces: vec![Workspace::default()],
             active_workspace_index: 0,
+            shift: ShiftConfig::default(),
+            order: 0,
+            default_brightness: None,
+            tags: Vec::new(),
+            schema_version: CURRENT_SCHEMA_VERSION,
             created_at: now,
             updated_at: now,
             buttons: vec![],
@@ -151,7 +371,9 @@ impl Profile {
     }
 
     /// Migrate legacy buttons/encoders to workspace format
-    pub fn migrate_legacy_config(&mut self) {
+    ///
+    /// Returns true if legacy configuration was present and the profile changed.
+    pub fn migrate_legacy_config(&mut self) -> bool {
         if !self.buttons.is_empty() || !self.encoders.is_empty() {
             if self.workspaces.is_empty() {
                 self.workspaces.push(Workspace::default());
@@ -164,10 +386,38 @@ impl Profile {
                     workspace.encoders = std::mem::take(&mut self.encoders);
                 }
             }
+            return true;
         }
+        false
     }
 }
 
+/// Control that acts as shift while held
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ShiftModifier {
+    /// A button, by index
+    Button { index: u8 },
+    /// An encoder's push, by encoder index (0 = main)
+    EncoderPress { index: usize },
+}
+
+/// Shift layer settings
+///
+/// While the modifier is held, controls run their `shift_*` actions instead of
+/// the normal ones. The modifier itself runs no actions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShiftConfig {
+    /// Control held for shift (none: shift can only be set programmatically)
+    #[serde(default)]
+    pub modifier: Option<ShiftModifier>,
+    /// When a control has no shift action, run its normal action instead of
+    /// doing nothing
+    #[serde(default)]
+    pub fall_through: bool,
+}
+
 /// Configuration for a single button
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -193,6 +443,39 @@ pub struct ButtonConfig {
     /// Action executed on long press while shift is held
     #[serde(default)]
     pub shift_long_press_action: Option<Action>,
+    /// Keep re-running the press action while the button is held
+    ///
+    /// A repeating button runs its action as soon as it's pressed, so its
+    /// long-press actions never fire.
+    #[serde(default)]
+    pub repeat: Option<RepeatConfig>,
+}
+
+/// Auto-fire settings for a held button
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepeatConfig {
+    /// Time between repeats
+    pub interval_ms: u64,
+    /// Time from press to the first repeat (defaults to `interval_ms`)
+    #[serde(default)]
+    pub initial_delay_ms: Option<u64>,
+}
+
+/// Lower bound on the repeat interval, so a zero interval can't spin
+pub const MIN_REPEAT_INTERVAL_MS: u64 = 10;
+
+impl RepeatConfig {
+    /// Time between repeats, clamped to `MIN_REPEAT_INTERVAL_MS`
+    pub fn interval(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.interval_ms.max(MIN_REPEAT_INTERVAL_MS))
+    }
+
+    /// Time from press to the first repeat
+    pub fn initial_delay(&self) -> std::time::Duration {
+        self.initial_delay_ms
+            .map_or_else(|| self.interval(), std::time::Duration::from_millis)
+    }
 }
 
 /// Configuration for a single encoder
@@ -229,10 +512,28 @@ pub struct EncoderConfig {
     /// Action executed on counter-clockwise rotation while shift is held
     #[serde(default)]
     pub shift_counter_clockwise_action: Option<Action>,
+    /// Deadband: consecutive same-direction ticks required before a rotation
+    /// action fires (0 or 1 = fire on every tick)
+    #[serde(default = "default_min_ticks")]
+    pub min_ticks: u8,
+}
+
+fn default_min_ticks() -> u8 {
+    1
 }
 
 /// Profile update request
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Workspace changes can be sent in two ways:
+/// - `workspaces` replaces the whole list (the original behavior). With
+///   `merge_workspaces` set, each entry instead replaces the workspace with the
+///   same ID (or is appended if new), and workspaces not listed are kept.
+/// - `workspace_patches` maps workspace IDs to partial `WorkspaceUpdate`s, so a
+///   client can change one field of one workspace without resending the rest.
+///
+/// Patches are applied after `workspaces`. An unknown workspace ID in
+/// `workspace_patches` fails the whole update.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProfileUpdate {
     #[serde(default)]
@@ -241,6 +542,12 @@ pub struct ProfileUpdate {
     pub description: Option<String>,
     #[serde(default)]
     pub workspaces: Option<Vec<Workspace>>,
+    /// Merge `workspaces` by ID instead of replacing the whole list
+    #[serde(default)]
+    pub merge_workspaces: bool,
+    /// Partial updates keyed by workspace ID
+    #[serde(default)]
+    pub workspace_patches: Option<HashMap<String, WorkspaceUpdate>>,
     #[serde(default)]
     pub active_workspace_index: Option<usize>,
     /// Legacy field for backward compatibility
@@ -252,7 +559,7 @@ pub struct ProfileUpdate {
 }
 
 /// Workspace update request
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkspaceUpdate {
     #[serde(default)]
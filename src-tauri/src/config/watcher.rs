@@ -0,0 +1,165 @@
+//! Profile Watcher
+//!
+//! Watches the profiles directory so profiles edited by hand or synced from
+//! another machine are reloaded without a restart. Editors often save a file
+//! in several steps, so events are collected until the directory has been
+//! quiet for `DEBOUNCE_MS` before anything is reloaded. Changes the app made
+//! itself are skipped (see `ProfileManager::is_own_write`).
+
+use super::profiles::ProfileManager;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Quiet period after the last change before profiles are reloaded
+pub const DEBOUNCE_MS: u64 = 300;
+
+/// Running watcher; stops when dropped
+pub struct ProfileWatcher {
+    watcher: Option<RecommendedWatcher>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ProfileWatcher {
+    /// Start watching `manager`'s profiles directory
+    ///
+    /// `on_change` is called with the IDs of the profiles that were reloaded
+    /// or removed after each burst of external changes.
+    pub fn start(
+        manager: Arc<Mutex<ProfileManager>>,
+        on_change: impl Fn(Vec<String>) + Send + 'static,
+    ) -> Result<Self, String> {
+        let dir = manager.lock().profiles_dir().to_path_buf();
+        let (tx, rx) = mpsc::channel();
+
+        // Rebuild paths from the file name, as some platforms report the
+        // directory in a different (canonical) form than it was given
+        let profiles_dir = dir.clone();
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+            match result {
+                Ok(event) => {
+                    for name in event.paths.iter().filter_map(|path| path.file_name()) {
+                        let _ = tx.send(profiles_dir.join(name));
+                    }
+                }
+                Err(e) => log::warn!("Profile watcher error: {}", e),
+            }
+        })
+        .map_err(|e| format!("Failed to create profile watcher: {}", e))?;
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch {:?}: {}", dir, e))?;
+
+        let thread = std::thread::Builder::new()
+            .name("profile-watcher".to_string())
+            .spawn(move || {
+                // Ends when the watcher, and so the sender, is dropped
+                while let Ok(first) = rx.recv() {
+                    let mut paths = HashSet::from([first]);
+                    let disconnected = loop {
+                        match rx.recv_timeout(Duration::from_millis(DEBOUNCE_MS)) {
+                            Ok(path) => {
+                                paths.insert(path);
+                            }
+                            Err(RecvTimeoutError::Timeout) => break false,
+                            Err(RecvTimeoutError::Disconnected) => break true,
+                        }
+                    };
+
+                    let changed = reload(&manager, paths);
+                    if !changed.is_empty() {
+                        on_change(changed);
+                    }
+                    if disconnected {
+                        break;
+                    }
+                }
+                log::debug!("Profile watcher stopped");
+            })
+            .map_err(|e| format!("Failed to start profile watcher: {}", e))?;
+
+        log::info!("Watching {:?} for profile changes", dir);
+        Ok(Self {
+            watcher: Some(watcher),
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for ProfileWatcher {
+    fn drop(&mut self) {
+        self.watcher.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Reload changed profile files, skipping the app's own writes
+fn reload(manager: &Mutex<ProfileManager>, paths: HashSet<PathBuf>) -> Vec<String> {
+    let mut manager = manager.lock();
+    let mut changed = Vec::new();
+    for path in paths {
+        if manager.is_own_write(&path) {
+            continue;
+        }
+        changed.extend(manager.reload_from_disk(&path));
+    }
+    changed.sort();
+    changed.dedup();
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::types::Profile;
+    use tempfile::TempDir;
+
+    fn start(dir: &TempDir) -> (Arc<Mutex<ProfileManager>>, ProfileWatcher, mpsc::Receiver<Vec<String>>) {
+        let manager = Arc::new(Mutex::new(ProfileManager::new(dir.path().to_path_buf())));
+        let (tx, rx) = mpsc::channel();
+        let watcher = ProfileWatcher::start(Arc::clone(&manager), move |ids| {
+            let _ = tx.send(ids);
+        })
+        .unwrap();
+        (manager, watcher, rx)
+    }
+
+    // ========== Reload Tests ==========
+
+    #[test]
+    fn test_external_edit_is_reloaded() {
+        let dir = TempDir::new().unwrap();
+        let (manager, _watcher, rx) = start(&dir);
+
+        let profile = Profile::new("Hand Edited".to_string());
+        let path = dir.path().join(format!("{}.json", profile.id));
+        std::fs::write(&path, serde_json::to_string(&profile).unwrap()).unwrap();
+
+        let ids = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(ids, vec![profile.id.clone()]);
+        assert_eq!(manager.lock().get(&profile.id).unwrap().name, "Hand Edited");
+
+        std::fs::remove_file(&path).unwrap();
+        let ids = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(ids, vec![profile.id.clone()]);
+        assert!(manager.lock().get(&profile.id).is_none());
+    }
+
+    #[test]
+    fn test_own_writes_are_ignored() {
+        let dir = TempDir::new().unwrap();
+        let (manager, _watcher, rx) = start(&dir);
+
+        manager.lock().create("From App".to_string()).unwrap();
+
+        let waited = Duration::from_millis(DEBOUNCE_MS * 3);
+        assert!(rx.recv_timeout(waited).is_err());
+    }
+}
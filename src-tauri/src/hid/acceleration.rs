@@ -0,0 +1,198 @@
+//! Encoder Acceleration
+//!
+//! The device reports one rotate event per detent. This module tracks how
+//! quickly consecutive detents of the same encoder arrive and turns a fast
+//! spin into a larger `velocity` on the event.
+
+use super::types::{DeviceEvent, EncoderEventType, EncoderType, DEBOUNCE_MS};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How rotation speed maps to velocity
+///
+/// Each detent that arrives within `window_ms` of the previous one in the
+/// same direction extends the streak; velocity is the streak length capped at
+/// `max_velocity`. A `max_velocity` of 1 disables acceleration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccelerationCurve {
+    /// Maximum gap between detents that still counts as one fast spin
+    pub window_ms: u64,
+    /// Upper bound on the reported velocity
+    pub max_velocity: u8,
+}
+
+impl AccelerationCurve {
+    /// Curve that always reports velocity 1, for precise controls
+    pub fn disabled() -> Self {
+        Self {
+            window_ms: 0,
+            max_velocity: 1,
+        }
+    }
+
+    /// Whether this curve can ever report more than one step
+    pub fn is_enabled(&self) -> bool {
+        self.max_velocity > 1 && self.window_ms > 0
+    }
+}
+
+impl Default for AccelerationCurve {
+    fn default() -> Self {
+        Self {
+            window_ms: DEBOUNCE_MS,
+            max_velocity: 3,
+        }
+    }
+}
+
+/// Last rotation seen on an encoder
+#[derive(Debug, Clone, Copy)]
+struct Streak {
+    direction: EncoderEventType,
+    at: Instant,
+    length: u8,
+}
+
+/// Per-encoder rotation speed tracker
+#[derive(Debug)]
+pub struct EncoderAccelerator {
+    curve: AccelerationCurve,
+    streaks: HashMap<EncoderType, Streak>,
+}
+
+impl EncoderAccelerator {
+    /// Create a tracker using the given curve
+    pub fn new(curve: AccelerationCurve) -> Self {
+        Self {
+            curve,
+            streaks: HashMap::new(),
+        }
+    }
+
+    /// Set the velocity on a rotate event; other events pass through unchanged
+    pub fn apply(&mut self, event: DeviceEvent) -> DeviceEvent {
+        self.apply_at(event, Instant::now())
+    }
+
+    fn apply_at(&mut self, event: DeviceEvent, now: Instant) -> DeviceEvent {
+        match event {
+            DeviceEvent::Encoder {
+                encoder_type,
+                event_type: direction @ (EncoderEventType::RotateCW | EncoderEventType::RotateCCW),
+                ..
+            } => DeviceEvent::Encoder {
+                encoder_type,
+                event_type: direction,
+                velocity: self.velocity(encoder_type, direction, now),
+            },
+            other => other,
+        }
+    }
+
+    fn velocity(&mut self, encoder: EncoderType, direction: EncoderEventType, now: Instant) -> u8 {
+        if !self.curve.is_enabled() {
+            return 1;
+        }
+
+        let window = Duration::from_millis(self.curve.window_ms);
+        let length = match self.streaks.get(&encoder) {
+            Some(prev) if prev.direction == direction && now.duration_since(prev.at) <= window => {
+                prev.length.saturating_add(1)
+            }
+            _ => 1,
+        };
+
+        self.streaks.insert(
+            encoder,
+            Streak {
+                direction,
+                at: now,
+                length,
+            },
+        );
+        length.min(self.curve.max_velocity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rotate(encoder_type: EncoderType, event_type: EncoderEventType) -> DeviceEvent {
+        DeviceEvent::Encoder {
+            encoder_type,
+            event_type,
+            velocity: 1,
+        }
+    }
+
+    fn velocity_of(event: &DeviceEvent) -> u8 {
+        match event {
+            DeviceEvent::Encoder { velocity, .. } => *velocity,
+            _ => panic!("Expected Encoder event"),
+        }
+    }
+
+    /// Feed rotations at the given millisecond offsets and collect velocities
+    fn spin(acc: &mut EncoderAccelerator, ticks: &[(EncoderEventType, u64)]) -> Vec<u8> {
+        let start = Instant::now();
+        ticks
+            .iter()
+            .map(|&(direction, at_ms)| {
+                let event = rotate(EncoderType::Main, direction);
+                velocity_of(&acc.apply_at(event, start + Duration::from_millis(at_ms)))
+            })
+            .collect()
+    }
+
+    // ========== Acceleration Tests ==========
+
+    #[test]
+    fn test_fast_ticks_accelerate() {
+        let mut acc = EncoderAccelerator::new(AccelerationCurve::default());
+        let cw = EncoderEventType::RotateCW;
+        assert_eq!(spin(&mut acc, &[(cw, 0), (cw, 20), (cw, 40), (cw, 60)]), vec![1, 2, 3, 3]);
+    }
+
+    #[test]
+    fn test_slow_ticks_stay_at_one() {
+        let mut acc = EncoderAccelerator::new(AccelerationCurve::default());
+        let cw = EncoderEventType::RotateCW;
+        assert_eq!(spin(&mut acc, &[(cw, 0), (cw, 200), (cw, 400)]), vec![1, 1, 1]);
+    }
+
+    #[test]
+    fn test_direction_change_resets_streak() {
+        let mut acc = EncoderAccelerator::new(AccelerationCurve::default());
+        let cw = EncoderEventType::RotateCW;
+        let ccw = EncoderEventType::RotateCCW;
+        assert_eq!(spin(&mut acc, &[(cw, 0), (cw, 10), (ccw, 20), (ccw, 30)]), vec![1, 2, 1, 2]);
+    }
+
+    #[test]
+    fn test_encoders_tracked_independently() {
+        let mut acc = EncoderAccelerator::new(AccelerationCurve::default());
+        let start = Instant::now();
+        let cw = EncoderEventType::RotateCW;
+
+        acc.apply_at(rotate(EncoderType::Main, cw), start);
+        let side = acc.apply_at(rotate(EncoderType::Side1, cw), start + Duration::from_millis(5));
+        assert_eq!(velocity_of(&side), 1);
+    }
+
+    #[test]
+    fn test_disabled_curve_never_accelerates() {
+        let mut acc = EncoderAccelerator::new(AccelerationCurve::disabled());
+        let cw = EncoderEventType::RotateCW;
+        assert_eq!(spin(&mut acc, &[(cw, 0), (cw, 5), (cw, 10)]), vec![1, 1, 1]);
+    }
+
+    #[test]
+    fn test_press_events_pass_through() {
+        let mut acc = EncoderAccelerator::new(AccelerationCurve::default());
+        let press = rotate(EncoderType::Main, EncoderEventType::Press);
+        assert_eq!(velocity_of(&acc.apply(press)), 1);
+    }
+}
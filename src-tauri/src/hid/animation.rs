@@ -0,0 +1,173 @@
+//! Button Animations
+//!
+//! Plays a sequence of JPEG frames on an LCD button from a background thread.
+//! Each animation is registered with the `HidManager`, which stops it when the
+//! button gets a new image, is cleared, or the device disconnects. The frame
+//! rate is capped at what an image upload can keep up with, and frames whose
+//! time has already passed are dropped rather than played late.
+
+use super::manager::HidManager;
+use super::protocol::SoomfonProtocol;
+use super::types::{HidError, HidResult};
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Shortest time a frame stays up (caps animations at 20 fps)
+pub const MIN_FRAME_MS: u32 = 50;
+
+/// Longest sleep between checks for a stop request
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Show the first frame on a button and play the rest in the background
+///
+/// `frames` are JPEGs with the time in milliseconds each stays up. The first
+/// frame becomes the button's cached image. A single frame is shown without
+/// starting a thread.
+pub fn play(manager: Arc<Mutex<HidManager>>, index: u8, frames: Vec<(Vec<u8>, u32)>) -> HidResult<()> {
+    let Some((first, _)) = frames.first() else {
+        return Err(HidError::InvalidData("Animation has no frames".to_string()));
+    };
+
+    let running = {
+        let mut mgr = manager.lock();
+        if !mgr.is_connected() {
+            return Err(HidError::NotConnected);
+        }
+        mgr.reopen_for_commands()?;
+        SoomfonProtocol::new(&mgr).set_button_image(index, first)?;
+        mgr.cache_button_image(index, first.clone());
+        if frames.len() == 1 {
+            return Ok(());
+        }
+        mgr.start_animation(index)
+    };
+
+    std::thread::Builder::new()
+        .name(format!("button-animation-{}", index))
+        .spawn(move || run(&manager, index, &frames, &running))
+        .map(|_| ())
+        .map_err(|e| HidError::UsbError(format!("Failed to start animation thread: {}", e)))
+}
+
+/// Animation loop; frame 0 is already on the button
+fn run(manager: &Mutex<HidManager>, index: u8, frames: &[(Vec<u8>, u32)], running: &AtomicBool) {
+    let delays: Vec<u32> = frames.iter().map(|(_, delay)| *delay).collect();
+    let mut current = 0;
+    let mut due = Instant::now();
+
+    loop {
+        (current, due) = next_frame(&delays, current, due, Instant::now());
+        if !wait_until(due, running) {
+            break;
+        }
+
+        // The stop flag is only cleared under the manager lock, so checking it
+        // here guarantees no frame lands after the button was changed
+        let mut mgr = manager.lock();
+        if !running.load(Ordering::SeqCst) {
+            break;
+        }
+        let result = mgr
+            .reopen_for_commands()
+            .and_then(|_| SoomfonProtocol::new(&mgr).set_button_image(index, &frames[current].0));
+        if let Err(e) = result {
+            log::warn!("Stopping animation on button {}: {}", index, e);
+            mgr.stop_animation(index);
+            break;
+        }
+    }
+    log::debug!("Animation on button {} stopped", index);
+}
+
+/// Sleep until `due`, returning false early if the animation is stopped
+fn wait_until(due: Instant, running: &AtomicBool) -> bool {
+    loop {
+        if !running.load(Ordering::SeqCst) {
+            return false;
+        }
+        let now = Instant::now();
+        if now >= due {
+            return true;
+        }
+        std::thread::sleep((due - now).min(STOP_CHECK_INTERVAL));
+    }
+}
+
+/// How long a frame stays up, after the frame rate cap
+fn frame_duration(delay_ms: u32) -> Duration {
+    Duration::from_millis(delay_ms.max(MIN_FRAME_MS) as u64)
+}
+
+/// Pick the frame to show after `current` (shown at `shown_at`) and when
+///
+/// Frames whose whole display time has already passed by `now` are skipped,
+/// so an animation that fell behind catches up instead of running slow.
+fn next_frame(delays: &[u32], current: usize, shown_at: Instant, now: Instant) -> (usize, Instant) {
+    let mut index = (current + 1) % delays.len();
+    let mut due = shown_at + frame_duration(delays[current]);
+    // Bounded by one full cycle so a stalled device can't spin here
+    for _ in 0..delays.len() {
+        let next_due = due + frame_duration(delays[index]);
+        if next_due > now {
+            break;
+        }
+        index = (index + 1) % delays.len();
+        due = next_due;
+    }
+    (index, due)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ========== Scheduling Tests ==========
+
+    #[test]
+    fn test_next_frame_on_time() {
+        let start = Instant::now();
+        let (index, due) = next_frame(&[100, 200, 300], 0, start, start);
+        assert_eq!(index, 1);
+        assert_eq!(due, start + Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_next_frame_wraps_around() {
+        let start = Instant::now();
+        let (index, _) = next_frame(&[100, 100], 1, start, start);
+        assert_eq!(index, 0);
+    }
+
+    #[test]
+    fn test_next_frame_caps_frame_rate() {
+        let start = Instant::now();
+        let (_, due) = next_frame(&[10, 10], 0, start, start);
+        assert_eq!(due, start + Duration::from_millis(MIN_FRAME_MS as u64));
+    }
+
+    #[test]
+    fn test_next_frame_drops_frames_when_behind() {
+        let start = Instant::now();
+        // Frame 0 was due to end at 100ms and frame 1 at 200ms; at 250ms
+        // frame 1 is over, so frame 2 (due at 200ms) is next
+        let now = start + Duration::from_millis(250);
+        let (index, due) = next_frame(&[100, 100, 100, 100], 0, start, now);
+        assert_eq!(index, 2);
+        assert_eq!(due, start + Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_play_requires_frames() {
+        let manager = Arc::new(Mutex::new(HidManager::new()));
+        assert!(matches!(play(manager, 0, Vec::new()), Err(HidError::InvalidData(_))));
+    }
+
+    #[test]
+    fn test_play_requires_connection() {
+        let manager = Arc::new(Mutex::new(HidManager::new()));
+        let frames = vec![(vec![0xFF, 0xD8], 100), (vec![0xFF, 0xD8], 100)];
+        assert!(matches!(play(manager, 0, frames), Err(HidError::NotConnected)));
+    }
+}
@@ -0,0 +1,138 @@
+//! Debounce Filter
+//!
+//! Some units report a single physical press as two rapid identical events.
+//! The filter drops any raw event that repeats the same `(event_id, state)`
+//! within the debounce window. Encoder rotations are never filtered, since a
+//! fast spin legitimately produces a burst of identical events.
+
+use super::types::{main_encoder, side_encoder_1, side_encoder_2, RawEvent};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Whether a raw event ID is an encoder rotation
+fn is_rotation(event_id: u8) -> bool {
+    matches!(
+        event_id,
+        main_encoder::ROTATE_CW
+            | main_encoder::ROTATE_CCW
+            | side_encoder_1::ROTATE_CW
+            | side_encoder_1::ROTATE_CCW
+            | side_encoder_2::ROTATE_CW
+            | side_encoder_2::ROTATE_CCW
+    )
+}
+
+/// Drops repeated raw events that arrive within the debounce window
+#[derive(Debug)]
+pub struct Debouncer {
+    window: Duration,
+    last_seen: HashMap<(u8, u8), Instant>,
+}
+
+impl Debouncer {
+    /// Create a filter with the given window; 0 disables debouncing
+    pub fn new(window_ms: u64) -> Self {
+        Self {
+            window: Duration::from_millis(window_ms),
+            last_seen: HashMap::new(),
+        }
+    }
+
+    /// Whether the event should be processed (false = bounce, drop it)
+    pub fn accept(&mut self, event: &RawEvent) -> bool {
+        self.accept_at(event, Instant::now())
+    }
+
+    fn accept_at(&mut self, event: &RawEvent, now: Instant) -> bool {
+        if self.window.is_zero() || is_rotation(event.event_id) {
+            return true;
+        }
+
+        let key = (event.event_id, event.state);
+        let is_bounce = self
+            .last_seen
+            .get(&key)
+            .is_some_and(|last| now.duration_since(*last) < self.window);
+
+        // Only accepted events restart the window, so a steady stream of
+        // bounces can't suppress a genuine later press indefinitely
+        if !is_bounce {
+            self.last_seen.insert(key, now);
+        }
+        !is_bounce
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hid::types::{event_state, lcd_buttons, DEBOUNCE_MS};
+
+    fn press(event_id: u8) -> RawEvent {
+        RawEvent {
+            event_id,
+            state: event_state::PRESS,
+        }
+    }
+
+    fn ms(start: Instant, offset: u64) -> Instant {
+        start + Duration::from_millis(offset)
+    }
+
+    // ========== Debounce Tests ==========
+
+    #[test]
+    fn test_duplicate_press_within_window_dropped() {
+        let mut debouncer = Debouncer::new(DEBOUNCE_MS);
+        let start = Instant::now();
+
+        assert!(debouncer.accept_at(&press(lcd_buttons::BUTTON_1), start));
+        assert!(!debouncer.accept_at(&press(lcd_buttons::BUTTON_1), ms(start, 10)));
+        assert!(debouncer.accept_at(&press(lcd_buttons::BUTTON_1), ms(start, DEBOUNCE_MS + 1)));
+    }
+
+    #[test]
+    fn test_press_and_release_not_confused() {
+        let mut debouncer = Debouncer::new(DEBOUNCE_MS);
+        let start = Instant::now();
+        let release = RawEvent {
+            event_id: lcd_buttons::BUTTON_1,
+            state: event_state::RELEASE,
+        };
+
+        assert!(debouncer.accept_at(&press(lcd_buttons::BUTTON_1), start));
+        assert!(debouncer.accept_at(&release, ms(start, 5)));
+    }
+
+    #[test]
+    fn test_different_buttons_not_debounced() {
+        let mut debouncer = Debouncer::new(DEBOUNCE_MS);
+        let start = Instant::now();
+
+        assert!(debouncer.accept_at(&press(lcd_buttons::BUTTON_1), start));
+        assert!(debouncer.accept_at(&press(lcd_buttons::BUTTON_2), ms(start, 1)));
+    }
+
+    #[test]
+    fn test_rotations_never_debounced() {
+        let mut debouncer = Debouncer::new(DEBOUNCE_MS);
+        let start = Instant::now();
+        let tick = RawEvent {
+            event_id: main_encoder::ROTATE_CW,
+            state: 0x00,
+        };
+
+        for i in 0..5 {
+            assert!(debouncer.accept_at(&tick, ms(start, i)));
+        }
+    }
+
+    #[test]
+    fn test_zero_window_disables_filter() {
+        let mut debouncer = Debouncer::new(0);
+        let start = Instant::now();
+
+        assert!(debouncer.accept_at(&press(lcd_buttons::BUTTON_1), start));
+        assert!(debouncer.accept_at(&press(lcd_buttons::BUTTON_1), start));
+    }
+}
@@ -0,0 +1,447 @@
+//! Device Diagnostics
+//!
+//! The USB setup probes and init sequence from `bin/init_test.rs`, run step by
+//! step against a freshly opened device and recorded in a report. The app runs
+//! it from the `run_diagnostics` command so problems can be looked into in the
+//! field without a dev toolchain; `init_test` runs it and then dumps events.
+//!
+//! Many probes (SET_IDLE, GET_REPORT, vendor requests) are expected to fail on
+//! some platforms. A failed step is information, not necessarily a fault; only
+//! a failure to find, open or claim the device stops the run.
+//!
+//! `SelfTestReport` records the pass/fail steps of the `self_test` command,
+//! which exercises the device through the normal `HidManager` path instead.
+
+use super::packets::{
+    build_brightness_packet, build_display_init_packet, build_quick_command_packet,
+    parse_ack_packet,
+};
+use super::types::{
+    lcd_buttons, main_encoder, side_encoder_1, side_encoder_2, small_buttons, CRT_PACKET_SIZE,
+    EP_IN, EP_OUT, SOOMFON_PID, SOOMFON_VID, USB_TIMEOUT_MS, VENDOR_INTERFACE,
+};
+use rusb::{Context, Device, DeviceHandle, UsbContext};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// Timeout for setup requests and packet writes
+const TIMEOUT: Duration = Duration::from_millis(USB_TIMEOUT_MS);
+
+/// Timeout for vendor-specific probes, which most devices ignore
+const VENDOR_REQUEST_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Wait before reading the reply to a command
+const RESPONSE_DELAY: Duration = Duration::from_millis(50);
+
+/// Timeout when reading the reply to a command
+const RESPONSE_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Bytes of a reply included in a step's detail
+const HEX_PREVIEW_BYTES: usize = 32;
+
+/// Vendor request numbers probed on the device and on the interface
+const VENDOR_REQUESTS: [u8; 4] = [0x00, 0x01, 0x09, 0x0A];
+
+/// Minimal 1x1 black JPEG header; the official software sends images after init
+const MINIMAL_JPEG: [u8; 135] = [
+    0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, 0x4A, 0x46, 0x49, 0x46, 0x00, 0x01,
+    0x01, 0x00, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00, 0xFF, 0xDB, 0x00, 0x43,
+    0x00, 0x08, 0x06, 0x06, 0x07, 0x06, 0x05, 0x08, 0x07, 0x07, 0x07, 0x09,
+    0x09, 0x08, 0x0A, 0x0C, 0x14, 0x0D, 0x0C, 0x0B, 0x0B, 0x0C, 0x19, 0x12,
+    0x13, 0x0F, 0x14, 0x1D, 0x1A, 0x1F, 0x1E, 0x1D, 0x1A, 0x1C, 0x1C, 0x20,
+    0x24, 0x2E, 0x27, 0x20, 0x22, 0x2C, 0x23, 0x1C, 0x1C, 0x28, 0x37, 0x29,
+    0x2C, 0x30, 0x31, 0x34, 0x34, 0x34, 0x1F, 0x27, 0x39, 0x3D, 0x38, 0x32,
+    0x3C, 0x2E, 0x33, 0x34, 0x32, 0xFF, 0xC0, 0x00, 0x0B, 0x08, 0x00, 0x01,
+    0x00, 0x01, 0x01, 0x01, 0x11, 0x00, 0xFF, 0xC4, 0x00, 0x1F, 0x00, 0x00,
+    0x01, 0x05, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+    0x09, 0x0A, 0x0B,
+];
+
+/// Outcome of one diagnostic step
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticStep {
+    /// What was tried (e.g. "SET_IDLE")
+    pub name: String,
+    /// Whether it succeeded
+    pub ok: bool,
+    /// Result or error message
+    pub detail: String,
+}
+
+/// Steps run by a diagnostic pass, in order
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticReport {
+    pub steps: Vec<DiagnosticStep>,
+}
+
+impl DiagnosticReport {
+    /// Record a step, logging it as it happens
+    pub fn record(&mut self, name: &str, ok: bool, detail: impl Into<String>) {
+        let detail = detail.into();
+        log::info!("Diagnostics: {} [{}] {}", name, if ok { "OK" } else { "FAILED" }, detail);
+        self.steps.push(DiagnosticStep {
+            name: name.to_string(),
+            ok,
+            detail,
+        });
+    }
+
+    /// Whether the named step ran and succeeded
+    pub fn succeeded(&self, name: &str) -> bool {
+        self.steps.iter().any(|step| step.name == name && step.ok)
+    }
+}
+
+/// Outcome of one self-test step, with how long it took
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestStep {
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
+    pub duration_ms: u64,
+}
+
+/// Pass/fail report of a device self-test
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestReport {
+    pub steps: Vec<SelfTestStep>,
+}
+
+impl SelfTestReport {
+    /// Run and time a step; returns whether it passed
+    pub fn step(&mut self, name: &str, run: impl FnOnce() -> Result<String, String>) -> bool {
+        let started = Instant::now();
+        let result = run();
+        let duration_ms = started.elapsed().as_millis() as u64;
+
+        let ok = result.is_ok();
+        let detail = result.unwrap_or_else(|e| e);
+        log::info!("Self-test: {} [{}] {} ({} ms)", name, if ok { "PASS" } else { "FAIL" }, detail, duration_ms);
+        self.steps.push(SelfTestStep {
+            name: name.to_string(),
+            ok,
+            detail,
+            duration_ms,
+        });
+        ok
+    }
+
+    /// Whether every step passed (false if none ran)
+    pub fn passed(&self) -> bool {
+        !self.steps.is_empty() && self.steps.iter().all(|step| step.ok)
+    }
+}
+
+/// Run the full diagnostic sequence
+///
+/// Returns the report and, if the interface was claimed, the open handle so
+/// the caller can go on to read events from it.
+pub fn run(context: &Context) -> (DiagnosticReport, Option<DeviceHandle<Context>>) {
+    let mut report = DiagnosticReport::default();
+
+    let Some(handle) = open_device(context, &mut report) else {
+        return (report, None);
+    };
+    if !setup_usb(&handle, &mut report) {
+        return (report, None);
+    }
+    probe_hid_requests(&handle, &mut report);
+    send_init_sequence(&handle, &mut report);
+
+    (report, Some(handle))
+}
+
+/// Find and open the first SOOMFON device
+pub fn open_device(context: &Context, report: &mut DiagnosticReport) -> Option<DeviceHandle<Context>> {
+    let Some(device) = find_device(context) else {
+        report.record(
+            "Find device",
+            false,
+            format!("No device with VID {:04X} PID {:04X}", SOOMFON_VID, SOOMFON_PID),
+        );
+        return None;
+    };
+    report.record(
+        "Find device",
+        true,
+        format!("Bus {} address {}", device.bus_number(), device.address()),
+    );
+
+    match device.open() {
+        Ok(handle) => {
+            report.record("Open device", true, "Opened");
+            Some(handle)
+        }
+        Err(e) => {
+            report.record("Open device", false, e.to_string());
+            None
+        }
+    }
+}
+
+/// USB-level setup: configuration, interface claim, alternate setting, halt
+///
+/// Returns false if the interface could not be claimed, since nothing after
+/// this can work without it.
+pub fn setup_usb(handle: &DeviceHandle<Context>, report: &mut DiagnosticReport) -> bool {
+    match handle.active_configuration() {
+        Ok(config) => report.record("Active configuration", true, format!("Configuration {}", config)),
+        Err(e) => report.record("Active configuration", false, e.to_string()),
+    }
+
+    // Usually already set, in which case this fails harmlessly
+    match handle.set_active_configuration(1) {
+        Ok(()) => report.record("Set configuration", true, "Set configuration 1"),
+        Err(e) => report.record("Set configuration", false, e.to_string()),
+    }
+
+    if let Err(e) = handle.claim_interface(VENDOR_INTERFACE) {
+        report.record("Claim interface", false, e.to_string());
+        return false;
+    }
+    report.record("Claim interface", true, format!("Claimed interface {}", VENDOR_INTERFACE));
+
+    match handle.set_alternate_setting(VENDOR_INTERFACE, 0) {
+        Ok(()) => report.record("Set alternate setting", true, "Set alternate setting 0"),
+        Err(e) => report.record("Set alternate setting", false, e.to_string()),
+    }
+
+    match handle.clear_halt(EP_IN) {
+        Ok(()) => report.record("Clear halt", true, format!("Cleared halt on EP 0x{:02X}", EP_IN)),
+        Err(e) => report.record("Clear halt", false, e.to_string()),
+    }
+
+    true
+}
+
+/// HID class and vendor requests that might enable event mode
+pub fn probe_hid_requests(handle: &DeviceHandle<Context>, report: &mut DiagnosticReport) {
+    // SET_IDLE: Host-to-device, Class, Interface; duration 0 (infinite), report ID 0
+    match handle.write_control(0x21, 0x0A, 0x0000, VENDOR_INTERFACE as u16, &[], TIMEOUT) {
+        Ok(_) => report.record("SET_IDLE", true, "Accepted"),
+        Err(e) => report.record("SET_IDLE", false, e.to_string()),
+    }
+
+    // SET_PROTOCOL: 1 = Report Protocol (0 = Boot Protocol)
+    match handle.write_control(0x21, 0x0B, 0x0001, VENDOR_INTERFACE as u16, &[], TIMEOUT) {
+        Ok(_) => report.record("SET_PROTOCOL", true, "Accepted"),
+        Err(e) => report.record("SET_PROTOCOL", false, e.to_string()),
+    }
+
+    let mut buf = [0u8; 512];
+
+    // GET_REPORT: Device-to-host, Class, Interface; Input (0x01) then Feature (0x03)
+    for (name, value) in [("GET_REPORT (Input)", 0x0100), ("GET_REPORT (Feature)", 0x0300)] {
+        match handle.read_control(0xA1, 0x01, value, VENDOR_INTERFACE as u16, &mut buf, TIMEOUT) {
+            Ok(n) => report.record(name, true, describe_reply(&buf[..n])),
+            Err(e) => report.record(name, false, e.to_string()),
+        }
+    }
+
+    // Vendor requests to the device (0xC0) and to the interface (0xC1)
+    for (name, request_type) in [("Vendor requests (device)", 0xC0), ("Vendor requests (interface)", 0xC1)] {
+        let answered: Vec<String> = VENDOR_REQUESTS
+            .iter()
+            .filter_map(|&request| {
+                match handle.read_control(request_type, request, 0, 0, &mut buf, VENDOR_REQUEST_TIMEOUT) {
+                    Ok(n) if n > 0 => Some(format!("0x{:02X}: {}", request, describe_reply(&buf[..n]))),
+                    _ => None,
+                }
+            })
+            .collect();
+        if answered.is_empty() {
+            report.record(name, false, "No vendor request answered");
+        } else {
+            report.record(name, true, answered.join("; "));
+        }
+    }
+}
+
+/// The official software's init sequence: DIS, LIG, QUCMD, LIG again, JPEG
+pub fn send_init_sequence(handle: &DeviceHandle<Context>, report: &mut DiagnosticReport) {
+    send_and_receive(handle, &build_display_init_packet(), "DIS", report);
+    send_and_receive(handle, &build_brightness_packet(100), "LIG", report);
+    send_and_receive(handle, &build_quick_command_packet(), "QUCMD", report);
+    // The official software sends brightness twice
+    send_and_receive(handle, &build_brightness_packet(100), "LIG (2nd time)", report);
+
+    let mut jpeg_packet = [0u8; CRT_PACKET_SIZE];
+    jpeg_packet[..MINIMAL_JPEG.len()].copy_from_slice(&MINIMAL_JPEG);
+    match handle.write_interrupt(EP_OUT, &jpeg_packet, TIMEOUT) {
+        Ok(n) => report.record("JPEG data", true, format!("Sent {} bytes", n)),
+        Err(e) => report.record("JPEG data", false, e.to_string()),
+    }
+    std::thread::sleep(Duration::from_millis(100));
+}
+
+/// Send a command packet and record whether it was written and answered
+fn send_and_receive(
+    handle: &DeviceHandle<Context>,
+    packet: &[u8],
+    name: &str,
+    report: &mut DiagnosticReport,
+) {
+    if let Err(e) = handle.write_interrupt(EP_OUT, packet, TIMEOUT) {
+        report.record(name, false, format!("Send failed: {}", e));
+        return;
+    }
+
+    let mut buf = [0u8; CRT_PACKET_SIZE];
+    std::thread::sleep(RESPONSE_DELAY);
+    match handle.read_interrupt(EP_IN, &mut buf, RESPONSE_TIMEOUT) {
+        Ok(n) if n > 0 => report.record(name, true, format!("Response: {}", describe_reply(&buf[..n]))),
+        Ok(_) | Err(rusb::Error::Timeout) => report.record(name, true, "Sent, no response"),
+        Err(e) => report.record(name, false, format!("Sent, read failed: {}", e)),
+    }
+}
+
+fn find_device<T: UsbContext>(context: &T) -> Option<Device<T>> {
+    context.devices().ok()?.iter().find(|d| {
+        d.device_descriptor().is_ok_and(|desc| {
+            desc.vendor_id() == SOOMFON_VID && desc.product_id() == SOOMFON_PID
+        })
+    })
+}
+
+/// A reply as text if it is printable, otherwise as a hex preview
+fn describe_reply(data: &[u8]) -> String {
+    match std::str::from_utf8(data) {
+        Ok(text) if !text.trim_matches('\0').is_empty() => text.trim_matches('\0').to_string(),
+        _ => format!("{} bytes: {}", data.len(), format_hex(&data[..data.len().min(HEX_PREVIEW_BYTES)])),
+    }
+}
+
+/// Format bytes as space-separated uppercase hex
+pub fn format_hex(data: &[u8]) -> String {
+    data.iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Describe an input packet for an event dump
+///
+/// Names the control and state of an ACK event packet (e.g. "Dial 1 Push:
+/// Press"), or returns None for anything else.
+pub fn describe_packet(data: &[u8]) -> Option<String> {
+    if data.starts_with(b"CRT") {
+        return Some("CRT response".to_string());
+    }
+    let raw = parse_ack_packet(data)?;
+
+    let control = match raw.event_id {
+        lcd_buttons::BUTTON_1..=lcd_buttons::BUTTON_6 => format!("LCD Button {}", raw.event_id),
+        small_buttons::BUTTON_1 => "Small Button 1".to_string(),
+        small_buttons::BUTTON_2 => "Small Button 2".to_string(),
+        small_buttons::BUTTON_3 => "Small Button 3".to_string(),
+        main_encoder::ROTATE_CCW => "Dial 1 Left (CCW)".to_string(),
+        main_encoder::ROTATE_CW => "Dial 1 Right (CW)".to_string(),
+        main_encoder::PUSH => "Dial 1 Push".to_string(),
+        side_encoder_1::ROTATE_CCW => "Dial 2 Left (CCW)".to_string(),
+        side_encoder_1::ROTATE_CW => "Dial 2 Right (CW)".to_string(),
+        side_encoder_1::PUSH => "Dial 2 Push".to_string(),
+        side_encoder_2::ROTATE_CCW => "Dial 3 Left (CCW)".to_string(),
+        side_encoder_2::ROTATE_CW => "Dial 3 Right (CW)".to_string(),
+        side_encoder_2::PUSH => "Dial 3 Push".to_string(),
+        id => format!("Unknown (0x{:02X})", id),
+    };
+    let state = match raw.state {
+        0x00 => "Release",
+        0x01 => "Press",
+        _ => "Unknown",
+    };
+    Some(format!("{}: {}", control, state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ack_packet(event_id: u8, state: u8) -> Vec<u8> {
+        let mut data = vec![0u8; 16];
+        data[0..3].copy_from_slice(b"ACK");
+        data[5..7].copy_from_slice(b"OK");
+        data[9] = event_id;
+        data[10] = state;
+        data
+    }
+
+    // ========== Packet Description Tests ==========
+
+    #[test]
+    fn test_describe_button_press() {
+        assert_eq!(
+            describe_packet(&ack_packet(0x03, 0x01)).as_deref(),
+            Some("LCD Button 3: Press")
+        );
+    }
+
+    #[test]
+    fn test_describe_encoder_rotation() {
+        assert_eq!(
+            describe_packet(&ack_packet(side_encoder_2::ROTATE_CW, 0x00)).as_deref(),
+            Some("Dial 3 Right (CW): Release")
+        );
+    }
+
+    #[test]
+    fn test_describe_unknown_event() {
+        assert_eq!(
+            describe_packet(&ack_packet(0x7F, 0x01)).as_deref(),
+            Some("Unknown (0x7F): Press")
+        );
+    }
+
+    #[test]
+    fn test_describe_other_packets() {
+        assert_eq!(describe_packet(b"CRT\0\0DIS").as_deref(), Some("CRT response"));
+        assert_eq!(describe_packet(b"garbage"), None);
+    }
+
+    // ========== Report Tests ==========
+
+    #[test]
+    fn test_report_succeeded() {
+        let mut report = DiagnosticReport::default();
+        report.record("SET_IDLE", false, "Pipe error");
+        report.record("DIS", true, "Sent, no response");
+
+        assert!(report.succeeded("DIS"));
+        assert!(!report.succeeded("SET_IDLE"));
+        assert!(!report.succeeded("LIG"));
+    }
+
+    // ========== Self-Test Report Tests ==========
+
+    #[test]
+    fn test_self_test_step_records_result() {
+        let mut report = SelfTestReport::default();
+        assert!(report.step("Connect", || Ok("1:4:2".to_string())));
+        assert!(!report.step("Initialize", || Err("Timeout".to_string())));
+
+        assert_eq!(report.steps.len(), 2);
+        assert_eq!(report.steps[0].detail, "1:4:2");
+        assert!(!report.steps[1].ok);
+        assert_eq!(report.steps[1].detail, "Timeout");
+        assert!(!report.passed());
+    }
+
+    #[test]
+    fn test_self_test_passed() {
+        let mut report = SelfTestReport::default();
+        assert!(!report.passed());
+        report.step("Enumerate", || Ok("1 device found".to_string()));
+        assert!(report.passed());
+    }
+
+    #[test]
+    fn test_describe_reply() {
+        assert_eq!(describe_reply(b"VER 1.0\0\0"), "VER 1.0");
+        assert_eq!(describe_reply(&[0xFF, 0x00]), "2 bytes: FF 00");
+    }
+}
@@ -0,0 +1,172 @@
+//! Event ID Remapping
+//!
+//! Firmware revisions don't always agree on which event ID a control sends.
+//! The event map, saved in the app settings, assigns raw event IDs to logical
+//! controls so a mismapped unit can be fixed without a rebuild. IDs not in the
+//! map fall back to the built-in constants (see `RawEvent::parse_with`).
+
+use super::types::{
+    event_state, ButtonEventType, ButtonType, DeviceEvent, EncoderEventType, EncoderType,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Encoder input a raw event ID can stand for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EncoderInput {
+    RotateCW,
+    RotateCCW,
+    Push,
+}
+
+/// Logical control a raw event ID is mapped to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum EventTarget {
+    /// A button; the state byte gives press or release
+    Button { button_type: ButtonType, index: u8 },
+    /// An encoder rotation or push
+    Encoder { encoder_type: EncoderType, input: EncoderInput },
+}
+
+impl EventTarget {
+    /// Device event for this control with the given raw state byte
+    pub fn event(&self, state: u8) -> DeviceEvent {
+        let is_press = state == event_state::PRESS;
+        match *self {
+            EventTarget::Button { button_type, index } => DeviceEvent::Button {
+                index,
+                button_type,
+                event_type: if is_press {
+                    ButtonEventType::Press
+                } else {
+                    ButtonEventType::Release
+                },
+            },
+            EventTarget::Encoder { encoder_type, input } => DeviceEvent::Encoder {
+                encoder_type,
+                velocity: 1,
+                event_type: match input {
+                    EncoderInput::RotateCW => EncoderEventType::RotateCW,
+                    EncoderInput::RotateCCW => EncoderEventType::RotateCCW,
+                    EncoderInput::Push if is_press => EncoderEventType::Press,
+                    EncoderInput::Push => EncoderEventType::Release,
+                },
+            },
+        }
+    }
+}
+
+/// Raw event ID to logical control overrides
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EventMap(BTreeMap<u8, EventTarget>);
+
+impl EventMap {
+    /// Control an event ID is mapped to, if overridden
+    pub fn get(&self, event_id: u8) -> Option<EventTarget> {
+        self.0.get(&event_id).copied()
+    }
+
+    /// Map an event ID to a control, or remove its override with `None`
+    pub fn set(&mut self, event_id: u8, target: Option<EventTarget>) {
+        match target {
+            Some(target) => {
+                self.0.insert(event_id, target);
+            }
+            None => {
+                self.0.remove(&event_id);
+            }
+        }
+    }
+
+    /// Whether no event IDs are overridden
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hid::types::{lcd_buttons, RawEvent};
+
+    fn lcd(index: u8) -> EventTarget {
+        EventTarget::Button { button_type: ButtonType::Lcd, index }
+    }
+
+    // ========== Lookup Tests ==========
+
+    #[test]
+    fn test_remapped_id_produces_mapped_event() {
+        let mut map = EventMap::default();
+        map.set(0x25, Some(lcd(4)));
+
+        let raw = RawEvent { event_id: 0x25, state: event_state::PRESS };
+        assert_eq!(
+            raw.parse_with(&map),
+            Some(DeviceEvent::Button {
+                index: 4,
+                button_type: ButtonType::Lcd,
+                event_type: ButtonEventType::Press,
+            })
+        );
+    }
+
+    #[test]
+    fn test_map_overrides_builtin_id() {
+        let mut map = EventMap::default();
+        map.set(
+            lcd_buttons::BUTTON_1,
+            Some(EventTarget::Encoder { encoder_type: EncoderType::Side2, input: EncoderInput::Push }),
+        );
+
+        let raw = RawEvent { event_id: lcd_buttons::BUTTON_1, state: event_state::RELEASE };
+        assert_eq!(
+            raw.parse_with(&map),
+            Some(DeviceEvent::Encoder {
+                encoder_type: EncoderType::Side2,
+                event_type: EncoderEventType::Release,
+                velocity: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_unmapped_id_falls_back_to_builtin() {
+        let map = EventMap::default();
+        let raw = RawEvent { event_id: lcd_buttons::BUTTON_2, state: event_state::PRESS };
+        assert_eq!(raw.parse_with(&map), raw.parse());
+    }
+
+    #[test]
+    fn test_set_none_removes_override() {
+        let mut map = EventMap::default();
+        map.set(0x40, Some(lcd(0)));
+        map.set(0x40, None);
+        assert!(map.is_empty());
+        assert_eq!(map.get(0x40), None);
+    }
+
+    // ========== Serialization Tests ==========
+
+    #[test]
+    fn test_event_map_json_round_trip() {
+        let mut map = EventMap::default();
+        map.set(0x25, Some(lcd(2)));
+        map.set(
+            0x30,
+            Some(EventTarget::Encoder { encoder_type: EncoderType::Main, input: EncoderInput::RotateCW }),
+        );
+
+        let json = serde_json::to_value(&map).unwrap();
+        assert_eq!(json["37"]["kind"], "button");
+        assert_eq!(json["37"]["buttonType"], "lcd");
+        assert_eq!(json["48"]["encoderType"], "main");
+        assert_eq!(json["48"]["input"], "rotateCW");
+
+        let parsed: EventMap = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, map);
+    }
+}
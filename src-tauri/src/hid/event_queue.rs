@@ -0,0 +1,292 @@
+//! Event Packet Queue
+//!
+//! Bounded hand-off between the USB reader thread and the thread that parses
+//! packets and emits events, so a slow consumer never stalls USB reads. When
+//! the queue is full the oldest encoder rotation packet is dropped first,
+//! since losing a tick of a fast spin is harmless while losing a button
+//! press or release is not. Only when no rotation is queued does the oldest
+//! packet of any kind go.
+//!
+//! `ReplySlot` picks replies to raw commands out of the same stream.
+
+use super::packets::parse_ack_packet;
+use super::types::{DeviceEvent, EncoderEventType};
+use parking_lot::{Condvar, Mutex};
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Packets held before the oldest are dropped
+pub const EVENT_QUEUE_CAPACITY: usize = 64;
+
+/// Result of waiting for a packet
+#[derive(Debug, PartialEq, Eq)]
+pub enum Next {
+    /// A packet arrived
+    Packet(Vec<u8>),
+    /// Nothing arrived before the timeout
+    Timeout,
+    /// The reader has stopped and the queue is empty
+    Closed,
+}
+
+#[derive(Default)]
+struct State {
+    packets: VecDeque<Vec<u8>>,
+    dropped: u64,
+    closed: bool,
+}
+
+/// Bounded packet queue, shared between one reader and one consumer
+pub struct PacketQueue {
+    state: Mutex<State>,
+    ready: Condvar,
+    capacity: usize,
+}
+
+impl Default for PacketQueue {
+    fn default() -> Self {
+        Self::new(EVENT_QUEUE_CAPACITY)
+    }
+}
+
+impl PacketQueue {
+    /// Create a queue holding at most `capacity` packets
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: Mutex::new(State::default()),
+            ready: Condvar::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Queue a packet, dropping an older one if the queue is full
+    pub fn push(&self, packet: Vec<u8>) {
+        let mut state = self.state.lock();
+        if state.packets.len() >= self.capacity {
+            let victim = state.packets.iter().position(|p| is_rotation(p)).unwrap_or(0);
+            state.packets.remove(victim);
+            state.dropped += 1;
+        }
+        state.packets.push_back(packet);
+        drop(state);
+        self.ready.notify_one();
+    }
+
+    /// Wait up to `timeout` for the next packet
+    ///
+    /// Packets still queued when the reader closes are delivered before
+    /// `Next::Closed`.
+    pub fn pop_timeout(&self, timeout: Duration) -> Next {
+        let mut state = self.state.lock();
+        if state.packets.is_empty() && !state.closed {
+            self.ready.wait_for(&mut state, timeout);
+        }
+        match state.packets.pop_front() {
+            Some(packet) => Next::Packet(packet),
+            None if state.closed => Next::Closed,
+            None => Next::Timeout,
+        }
+    }
+
+    /// Mark the reader as stopped, waking the consumer
+    pub fn close(&self) {
+        self.state.lock().closed = true;
+        self.ready.notify_all();
+    }
+
+    /// Number of packets dropped since the last call
+    pub fn take_dropped(&self) -> u64 {
+        std::mem::take(&mut self.state.lock().dropped)
+    }
+
+    /// Number of packets waiting
+    pub fn len(&self) -> usize {
+        self.state.lock().packets.len()
+    }
+
+    /// Whether no packets are waiting
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// One-shot hand-off of the device's reply to a raw command
+///
+/// While the polling thread owns the IN endpoint, a reply read on the
+/// command handle would race the poller. Instead the sender arms the slot
+/// before sending, and the event thread offers every packet it pops: the
+/// first one that isn't an input event is the reply and goes to the sender,
+/// while input events carry on to event handling as usual.
+pub struct ReplySlot {
+    pending: Mutex<Option<mpsc::Sender<Vec<u8>>>>,
+}
+
+impl Default for ReplySlot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReplySlot {
+    /// Create an unarmed slot
+    pub const fn new() -> Self {
+        Self {
+            pending: parking_lot::const_mutex(None),
+        }
+    }
+
+    /// Wait for the next reply, replacing any earlier wait
+    pub fn expect(&self) -> mpsc::Receiver<Vec<u8>> {
+        let (tx, rx) = mpsc::channel();
+        *self.pending.lock() = Some(tx);
+        rx
+    }
+
+    /// Stop waiting for a reply
+    pub fn cancel(&self) {
+        self.pending.lock().take();
+    }
+
+    /// Hand a packet to the waiting sender if it's a reply
+    ///
+    /// Returns whether the packet was taken.
+    pub fn offer(&self, packet: &[u8]) -> bool {
+        if parse_ack_packet(packet).is_some() {
+            return false;
+        }
+        match self.pending.lock().take() {
+            Some(tx) => tx.send(packet.to_vec()).is_ok(),
+            None => false,
+        }
+    }
+}
+
+/// Whether a packet is an encoder rotation event
+fn is_rotation(packet: &[u8]) -> bool {
+    matches!(
+        parse_ack_packet(packet).and_then(|raw| raw.parse()),
+        Some(DeviceEvent::Encoder {
+            event_type: EncoderEventType::RotateCW | EncoderEventType::RotateCCW,
+            ..
+        })
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hid::types::{lcd_buttons, main_encoder};
+    use std::sync::Arc;
+
+    fn ack_packet(event_id: u8, state: u8) -> Vec<u8> {
+        let mut data = vec![0u8; 16];
+        data[0..3].copy_from_slice(b"ACK");
+        data[5..7].copy_from_slice(b"OK");
+        data[9] = event_id;
+        data[10] = state;
+        data
+    }
+
+    const SHORT: Duration = Duration::from_millis(10);
+
+    // ========== Ordering Tests ==========
+
+    #[test]
+    fn test_delivers_in_order() {
+        let queue = PacketQueue::new(4);
+        queue.push(ack_packet(lcd_buttons::BUTTON_1, 1));
+        queue.push(ack_packet(lcd_buttons::BUTTON_1, 0));
+
+        assert_eq!(queue.pop_timeout(SHORT), Next::Packet(ack_packet(lcd_buttons::BUTTON_1, 1)));
+        assert_eq!(queue.pop_timeout(SHORT), Next::Packet(ack_packet(lcd_buttons::BUTTON_1, 0)));
+        assert_eq!(queue.pop_timeout(SHORT), Next::Timeout);
+    }
+
+    #[test]
+    fn test_wakes_waiting_consumer() {
+        let queue = Arc::new(PacketQueue::new(4));
+        let reader = Arc::clone(&queue);
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(SHORT);
+            reader.push(ack_packet(lcd_buttons::BUTTON_2, 1));
+        });
+
+        assert_eq!(
+            queue.pop_timeout(Duration::from_secs(5)),
+            Next::Packet(ack_packet(lcd_buttons::BUTTON_2, 1))
+        );
+        handle.join().unwrap();
+    }
+
+    // ========== Drop Policy Tests ==========
+
+    #[test]
+    fn test_full_queue_drops_oldest_rotation_first() {
+        let queue = PacketQueue::new(3);
+        queue.push(ack_packet(lcd_buttons::BUTTON_1, 1));
+        queue.push(ack_packet(main_encoder::ROTATE_CW, 1));
+        queue.push(ack_packet(lcd_buttons::BUTTON_1, 0));
+        queue.push(ack_packet(lcd_buttons::BUTTON_2, 1));
+
+        assert_eq!(queue.take_dropped(), 1);
+        assert_eq!(queue.take_dropped(), 0);
+        assert_eq!(queue.pop_timeout(SHORT), Next::Packet(ack_packet(lcd_buttons::BUTTON_1, 1)));
+        assert_eq!(queue.pop_timeout(SHORT), Next::Packet(ack_packet(lcd_buttons::BUTTON_1, 0)));
+        assert_eq!(queue.pop_timeout(SHORT), Next::Packet(ack_packet(lcd_buttons::BUTTON_2, 1)));
+    }
+
+    #[test]
+    fn test_full_queue_without_rotation_drops_oldest() {
+        let queue = PacketQueue::new(2);
+        queue.push(ack_packet(lcd_buttons::BUTTON_1, 1));
+        queue.push(ack_packet(lcd_buttons::BUTTON_2, 1));
+        queue.push(ack_packet(lcd_buttons::BUTTON_3, 1));
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.take_dropped(), 1);
+        assert_eq!(queue.pop_timeout(SHORT), Next::Packet(ack_packet(lcd_buttons::BUTTON_2, 1)));
+    }
+
+    // ========== Close Tests ==========
+
+    #[test]
+    fn test_close_drains_before_closed() {
+        let queue = PacketQueue::new(4);
+        queue.push(ack_packet(lcd_buttons::BUTTON_1, 1));
+        queue.close();
+
+        assert_eq!(queue.pop_timeout(SHORT), Next::Packet(ack_packet(lcd_buttons::BUTTON_1, 1)));
+        assert_eq!(queue.pop_timeout(SHORT), Next::Closed);
+        assert!(queue.is_empty());
+    }
+
+    // ========== Reply Tests ==========
+
+    #[test]
+    fn test_reply_goes_to_waiting_sender() {
+        let slot = ReplySlot::new();
+        let reply = slot.expect();
+        let mut ack = ack_packet(0x00, 0);
+        ack[11] = 0x42;
+
+        // An input event arriving first is left for event handling
+        assert!(!slot.offer(&ack_packet(lcd_buttons::BUTTON_1, 1)));
+        assert!(slot.offer(&ack));
+        assert_eq!(reply.recv_timeout(SHORT), Ok(ack));
+
+        // Only one reply per request
+        assert!(!slot.offer(&ack_packet(0x00, 0)));
+    }
+
+    #[test]
+    fn test_reply_not_taken_when_unarmed() {
+        let slot = ReplySlot::new();
+        assert!(!slot.offer(b"CRT\x00\x00DIS"));
+
+        let reply = slot.expect();
+        slot.cancel();
+        assert!(!slot.offer(b"CRT\x00\x00DIS"));
+        assert!(reply.recv_timeout(SHORT).is_err());
+    }
+}
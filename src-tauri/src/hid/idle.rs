@@ -0,0 +1,114 @@
+//! Idle Dimming
+//!
+//! Tracks device activity so the display can be dimmed after a period with no
+//! events and brought back on the next one. Only the timing lives here; the
+//! polling thread applies the brightness changes, reading the configured
+//! levels fresh each time so a wake never restores a stale value.
+
+use std::time::{Duration, Instant};
+
+/// Inactivity timer for auto-dimming
+#[derive(Debug)]
+pub struct IdleDimmer {
+    last_activity: Instant,
+    dimmed: bool,
+}
+
+impl Default for IdleDimmer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdleDimmer {
+    /// Start the timer from now, undimmed
+    pub fn new() -> Self {
+        Self {
+            last_activity: Instant::now(),
+            dimmed: false,
+        }
+    }
+
+    /// Record a device event; returns true if the display should be woken
+    pub fn on_activity(&mut self) -> bool {
+        self.on_activity_at(Instant::now())
+    }
+
+    /// Returns true once the display should be dimmed
+    ///
+    /// `dim_after` of None or zero disables dimming.
+    pub fn should_dim(&mut self, dim_after: Option<Duration>) -> bool {
+        self.should_dim_at(dim_after, Instant::now())
+    }
+
+    /// Whether the display is currently dimmed
+    pub fn is_dimmed(&self) -> bool {
+        self.dimmed
+    }
+
+    fn on_activity_at(&mut self, now: Instant) -> bool {
+        self.last_activity = now;
+        std::mem::take(&mut self.dimmed)
+    }
+
+    fn should_dim_at(&mut self, dim_after: Option<Duration>, now: Instant) -> bool {
+        let Some(dim_after) = dim_after.filter(|d| !d.is_zero()) else {
+            return false;
+        };
+        if self.dimmed || now.duration_since(self.last_activity) < dim_after {
+            return false;
+        }
+        self.dimmed = true;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIM_AFTER: Option<Duration> = Some(Duration::from_secs(30));
+
+    // ========== Dimming Tests ==========
+
+    #[test]
+    fn test_dims_once_after_inactivity() {
+        let mut dimmer = IdleDimmer::new();
+        let start = dimmer.last_activity;
+
+        assert!(!dimmer.should_dim_at(DIM_AFTER, start + Duration::from_secs(29)));
+        assert!(dimmer.should_dim_at(DIM_AFTER, start + Duration::from_secs(30)));
+        assert!(dimmer.is_dimmed());
+        assert!(!dimmer.should_dim_at(DIM_AFTER, start + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_activity_resets_timer() {
+        let mut dimmer = IdleDimmer::new();
+        let start = dimmer.last_activity;
+
+        assert!(!dimmer.on_activity_at(start + Duration::from_secs(20)));
+        assert!(!dimmer.should_dim_at(DIM_AFTER, start + Duration::from_secs(40)));
+        assert!(dimmer.should_dim_at(DIM_AFTER, start + Duration::from_secs(50)));
+    }
+
+    #[test]
+    fn test_activity_wakes_dimmed_display() {
+        let mut dimmer = IdleDimmer::new();
+        let start = dimmer.last_activity;
+        dimmer.should_dim_at(DIM_AFTER, start + Duration::from_secs(30));
+
+        assert!(dimmer.on_activity_at(start + Duration::from_secs(31)));
+        assert!(!dimmer.is_dimmed());
+        assert!(!dimmer.on_activity_at(start + Duration::from_secs(32)));
+    }
+
+    #[test]
+    fn test_disabled_when_unset_or_zero() {
+        let mut dimmer = IdleDimmer::new();
+        let later = dimmer.last_activity + Duration::from_secs(3600);
+
+        assert!(!dimmer.should_dim_at(None, later));
+        assert!(!dimmer.should_dim_at(Some(Duration::ZERO), later));
+    }
+}
@@ -0,0 +1,337 @@
+//! Long-press Detection
+//!
+//! The raw protocol only reports press and release, so long presses are
+//! synthesized here. A press is held back until either the release arrives
+//! (emitted as a normal Press + Release) or `LONG_PRESS_THRESHOLD_MS` passes
+//! (emitted as LongPress, with the Release following later). A press that
+//! becomes a long press therefore never also triggers the short-press action.
+//!
+//! Presses fed through `on_event_immediate` skip detection and pass on at once.
+
+use super::types::{
+    ButtonEventType, ButtonType, DeviceEvent, EncoderEventType, EncoderType,
+    LONG_PRESS_THRESHOLD_MS,
+};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// A pressable control: an LCD/physical button or an encoder push
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Control {
+    Button(ButtonType, u8),
+    Encoder(EncoderType),
+}
+
+/// Press currently being held
+#[derive(Debug, Clone, Copy)]
+struct HeldPress {
+    since: Instant,
+    long_press_sent: bool,
+}
+
+/// Per-control press timer that turns held presses into LongPress events
+#[derive(Debug)]
+pub struct LongPressDetector {
+    threshold: Duration,
+    held: HashMap<Control, HeldPress>,
+}
+
+impl Default for LongPressDetector {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(LONG_PRESS_THRESHOLD_MS))
+    }
+}
+
+impl LongPressDetector {
+    /// Create a detector with a custom hold threshold
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            held: HashMap::new(),
+        }
+    }
+
+    /// Feed a device event and get the events to emit now
+    pub fn on_event(&mut self, event: DeviceEvent) -> Vec<DeviceEvent> {
+        self.on_event_at(event, Instant::now())
+    }
+
+    /// Feed a device event whose press must not wait for long-press detection
+    ///
+    /// The press is passed on at once and never becomes a LongPress; its
+    /// release then passes straight through too. Used for buttons that
+    /// auto-repeat, whose action should run as soon as they're pressed.
+    pub fn on_event_immediate(&mut self, event: DeviceEvent) -> Vec<DeviceEvent> {
+        match press_state(&event) {
+            Some((control, true)) => {
+                self.held.remove(&control);
+                vec![event]
+            }
+            _ => self.on_event(event),
+        }
+    }
+
+    /// Emit LongPress for presses held past the threshold
+    pub fn poll(&mut self) -> Vec<DeviceEvent> {
+        self.poll_at(Instant::now())
+    }
+
+    /// Time until the next pending press reaches the threshold
+    pub fn next_deadline(&self) -> Option<Duration> {
+        self.next_deadline_at(Instant::now())
+    }
+
+    fn on_event_at(&mut self, event: DeviceEvent, now: Instant) -> Vec<DeviceEvent> {
+        let Some((control, pressed)) = press_state(&event) else {
+            // Rotations and unmapped events pass straight through
+            return vec![event];
+        };
+
+        if pressed {
+            // A repeated press without release restarts the timer
+            self.held.insert(
+                control,
+                HeldPress {
+                    since: now,
+                    long_press_sent: false,
+                },
+            );
+            return Vec::new();
+        }
+
+        match self.held.remove(&control) {
+            Some(press) if press.long_press_sent => vec![event],
+            // Releasing exactly at the threshold counts as a long press, same as poll()
+            Some(press) if now.duration_since(press.since) >= self.threshold => {
+                vec![make_event(control, PressKind::Long), event]
+            }
+            Some(_) => vec![make_event(control, PressKind::Short), event],
+            // Release of a press we never saw (e.g. held during connect)
+            None => vec![event],
+        }
+    }
+
+    fn poll_at(&mut self, now: Instant) -> Vec<DeviceEvent> {
+        let mut events = Vec::new();
+        for (control, press) in self.held.iter_mut() {
+            if !press.long_press_sent && now.duration_since(press.since) >= self.threshold {
+                press.long_press_sent = true;
+                events.push(make_event(*control, PressKind::Long));
+            }
+        }
+        events
+    }
+
+    fn next_deadline_at(&self, now: Instant) -> Option<Duration> {
+        self.held
+            .values()
+            .filter(|press| !press.long_press_sent)
+            .map(|press| (press.since + self.threshold).saturating_duration_since(now))
+            .min()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum PressKind {
+    Short,
+    Long,
+}
+
+/// Which control an event belongs to and whether it is a press (true) or release (false)
+fn press_state(event: &DeviceEvent) -> Option<(Control, bool)> {
+    match event {
+        DeviceEvent::Button { index, button_type, event_type } => {
+            let control = Control::Button(*button_type, *index);
+            match event_type {
+                ButtonEventType::Press => Some((control, true)),
+                ButtonEventType::Release => Some((control, false)),
+                ButtonEventType::LongPress => None,
+            }
+        }
+        DeviceEvent::Encoder { encoder_type, event_type, .. } => {
+            let control = Control::Encoder(*encoder_type);
+            match event_type {
+                EncoderEventType::Press => Some((control, true)),
+                EncoderEventType::Release => Some((control, false)),
+                _ => None,
+            }
+        }
+        DeviceEvent::Unknown { .. } => None,
+    }
+}
+
+fn make_event(control: Control, kind: PressKind) -> DeviceEvent {
+    match control {
+        Control::Button(button_type, index) => DeviceEvent::Button {
+            index,
+            button_type,
+            event_type: match kind {
+                PressKind::Short => ButtonEventType::Press,
+                PressKind::Long => ButtonEventType::LongPress,
+            },
+        },
+        Control::Encoder(encoder_type) => DeviceEvent::Encoder {
+            encoder_type,
+            event_type: match kind {
+                PressKind::Short => EncoderEventType::Press,
+                PressKind::Long => EncoderEventType::LongPress,
+            },
+            velocity: 1,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn button(event_type: ButtonEventType) -> DeviceEvent {
+        DeviceEvent::Button {
+            index: 2,
+            button_type: ButtonType::Lcd,
+            event_type,
+        }
+    }
+
+    fn encoder(event_type: EncoderEventType) -> DeviceEvent {
+        DeviceEvent::Encoder {
+            encoder_type: EncoderType::Main,
+            event_type,
+            velocity: 1,
+        }
+    }
+
+    fn button_types(events: &[DeviceEvent]) -> Vec<ButtonEventType> {
+        events
+            .iter()
+            .map(|e| match e {
+                DeviceEvent::Button { event_type, .. } => *event_type,
+                _ => panic!("Expected Button event"),
+            })
+            .collect()
+    }
+
+    fn ms(start: Instant, offset: u64) -> Instant {
+        start + Duration::from_millis(offset)
+    }
+
+    // ========== Button Tests ==========
+
+    #[test]
+    fn test_short_press_emits_press_and_release_on_release() {
+        let mut detector = LongPressDetector::default();
+        let start = Instant::now();
+
+        assert!(detector.on_event_at(button(ButtonEventType::Press), start).is_empty());
+        assert!(detector.poll_at(ms(start, 100)).is_empty());
+
+        let events = detector.on_event_at(button(ButtonEventType::Release), ms(start, 150));
+        assert_eq!(button_types(&events), vec![ButtonEventType::Press, ButtonEventType::Release]);
+    }
+
+    #[test]
+    fn test_held_press_becomes_long_press() {
+        let mut detector = LongPressDetector::default();
+        let start = Instant::now();
+
+        detector.on_event_at(button(ButtonEventType::Press), start);
+        let events = detector.poll_at(ms(start, LONG_PRESS_THRESHOLD_MS + 10));
+        assert_eq!(button_types(&events), vec![ButtonEventType::LongPress]);
+
+        // Only fires once, and the release follows without a Press
+        assert!(detector.poll_at(ms(start, LONG_PRESS_THRESHOLD_MS + 200)).is_empty());
+        let events = detector.on_event_at(button(ButtonEventType::Release), ms(start, 900));
+        assert_eq!(button_types(&events), vec![ButtonEventType::Release]);
+    }
+
+    #[test]
+    fn test_release_exactly_at_threshold_is_long_press() {
+        let mut detector = LongPressDetector::default();
+        let start = Instant::now();
+
+        detector.on_event_at(button(ButtonEventType::Press), start);
+        let events = detector.on_event_at(button(ButtonEventType::Release), ms(start, LONG_PRESS_THRESHOLD_MS));
+        assert_eq!(button_types(&events), vec![ButtonEventType::LongPress, ButtonEventType::Release]);
+    }
+
+    #[test]
+    fn test_release_without_press_passes_through() {
+        let mut detector = LongPressDetector::default();
+        let events = detector.on_event(button(ButtonEventType::Release));
+        assert_eq!(button_types(&events), vec![ButtonEventType::Release]);
+    }
+
+    #[test]
+    fn test_buttons_are_tracked_independently() {
+        let mut detector = LongPressDetector::default();
+        let start = Instant::now();
+        let other = DeviceEvent::Button {
+            index: 0,
+            button_type: ButtonType::Physical,
+            event_type: ButtonEventType::Press,
+        };
+
+        detector.on_event_at(button(ButtonEventType::Press), start);
+        detector.on_event_at(other, ms(start, 300));
+
+        // Only the first button has been held long enough
+        assert_eq!(detector.poll_at(ms(start, LONG_PRESS_THRESHOLD_MS)).len(), 1);
+    }
+
+    #[test]
+    fn test_next_deadline() {
+        let mut detector = LongPressDetector::default();
+        let start = Instant::now();
+        assert!(detector.next_deadline_at(start).is_none());
+
+        detector.on_event_at(button(ButtonEventType::Press), start);
+        assert_eq!(
+            detector.next_deadline_at(ms(start, 200)),
+            Some(Duration::from_millis(LONG_PRESS_THRESHOLD_MS - 200))
+        );
+
+        detector.poll_at(ms(start, LONG_PRESS_THRESHOLD_MS));
+        assert!(detector.next_deadline_at(ms(start, LONG_PRESS_THRESHOLD_MS)).is_none());
+    }
+
+    #[test]
+    fn test_immediate_press_skips_detection() {
+        let mut detector = LongPressDetector::default();
+        let start = Instant::now();
+
+        let events = detector.on_event_immediate(button(ButtonEventType::Press));
+        assert_eq!(button_types(&events), vec![ButtonEventType::Press]);
+        assert!(detector.next_deadline().is_none());
+        assert!(detector.poll_at(ms(start, LONG_PRESS_THRESHOLD_MS * 2)).is_empty());
+
+        let events = detector.on_event(button(ButtonEventType::Release));
+        assert_eq!(button_types(&events), vec![ButtonEventType::Release]);
+    }
+
+    // ========== Encoder Tests ==========
+
+    #[test]
+    fn test_encoder_push_long_press() {
+        let mut detector = LongPressDetector::default();
+        let start = Instant::now();
+
+        detector.on_event_at(encoder(EncoderEventType::Press), start);
+        let events = detector.poll_at(ms(start, LONG_PRESS_THRESHOLD_MS));
+        assert!(matches!(
+            events.as_slice(),
+            [DeviceEvent::Encoder { event_type: EncoderEventType::LongPress, .. }]
+        ));
+    }
+
+    #[test]
+    fn test_encoder_rotation_passes_through_while_pressed() {
+        let mut detector = LongPressDetector::default();
+        detector.on_event(encoder(EncoderEventType::Press));
+
+        let events = detector.on_event(encoder(EncoderEventType::RotateCW));
+        assert!(matches!(
+            events.as_slice(),
+            [DeviceEvent::Encoder { event_type: EncoderEventType::RotateCW, .. }]
+        ));
+    }
+}
@@ -5,14 +5,124 @@
 //!
 //! Based on reverse-engineered protocol from usb-protocol-reverse-engineering.md
 
+use super::monitor::{is_same_device, HotplugEvent};
 use super::packets::*;
 use super::types::*;
-use rusb::{Context, DeviceHandle, UsbContext};
+use rusb::{Context, Device, DeviceHandle, UsbContext};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
+/// Callback invoked by the hot-plug monitor when a device is attached or detached
+pub type HotplugCallback = Arc<dyn Fn(&HotplugEvent) + Send + Sync>;
+
 /// USB timeout for operations
 const USB_TIMEOUT: Duration = Duration::from_millis(USB_TIMEOUT_MS);
 
+/// Delay before reopening the device when recovering a busy interface (Windows)
+#[cfg(target_os = "windows")]
+const CLAIM_RETRY_DELAY_MS: u64 = 500;
+
+/// Format the `bus:address:port` path used to identify a specific device
+pub(crate) fn device_path(device: &Device<Context>) -> String {
+    format!(
+        "{}:{}:{}",
+        device.bus_number(),
+        device.address(),
+        device.port_number()
+    )
+}
+
+/// Whether a USB device is a SOOMFON controller
+pub(crate) fn is_soomfon(device: &Device<Context>) -> bool {
+    device.device_descriptor().is_ok_and(|desc| {
+        desc.vendor_id() == SOOMFON_VID && desc.product_id() == SOOMFON_PID
+    })
+}
+
+/// Build the `DeviceInfo` for a SOOMFON device, reading its string descriptors if possible
+pub(crate) fn describe_device(device: &Device<Context>) -> Option<DeviceInfo> {
+    let desc = device.device_descriptor().ok()?;
+    if desc.vendor_id() != SOOMFON_VID || desc.product_id() != SOOMFON_PID {
+        return None;
+    }
+
+    // Get string descriptors if possible
+    let handle = device.open().ok();
+    let (serial, manufacturer, product) = if let Some(ref h) = handle {
+        (
+            h.read_serial_number_string_ascii(&desc).ok(),
+            h.read_manufacturer_string_ascii(&desc).ok(),
+            h.read_product_string_ascii(&desc).ok(),
+        )
+    } else {
+        (None, None, None)
+    };
+
+    Some(DeviceInfo {
+        path: device_path(device),
+        serial_number: serial,
+        manufacturer,
+        product,
+        firmware_version: None,
+        firmware: None,
+    })
+}
+
+/// Find a SOOMFON device, optionally restricted to a specific path
+fn find_device(ctx: &Context, path: Option<&str>) -> HidResult<Device<Context>> {
+    ctx.devices()
+        .map_err(|e| HidError::OpenFailed(e.to_string()))?
+        .iter()
+        .find(|d| is_soomfon(d) && path.is_none_or(|p| device_path(d) == p))
+        .ok_or(HidError::DeviceNotFound)
+}
+
+/// Claim the vendor interface, recovering from a stale claim if needed
+///
+/// If a previous instance crashed without releasing the interface, the claim
+/// fails with `Busy`. In that case reset the device and retry once; on Windows
+/// also try reopening the device after a short delay. If recovery fails, the
+/// original claim error is returned.
+fn claim_with_recovery(
+    device: &Device<Context>,
+    handle: DeviceHandle<Context>,
+) -> HidResult<DeviceHandle<Context>> {
+    let err = match handle.claim_interface(VENDOR_INTERFACE) {
+        Ok(()) => return Ok(handle),
+        Err(rusb::Error::Busy) => rusb::Error::Busy,
+        Err(e) => return Err(HidError::ClaimFailed(e.to_string())),
+    };
+
+    log::warn!("Interface busy (stale handle?), resetting device and retrying claim");
+    match handle.reset() {
+        Ok(()) => {
+            if handle.claim_interface(VENDOR_INTERFACE).is_ok() {
+                log::info!("Recovered interface claim after device reset");
+                return Ok(handle);
+            }
+        }
+        Err(e) => log::warn!("Device reset failed: {}", e),
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        drop(handle);
+        std::thread::sleep(Duration::from_millis(CLAIM_RETRY_DELAY_MS));
+        if let Ok(handle) = device.open() {
+            if handle.claim_interface(VENDOR_INTERFACE).is_ok() {
+                log::info!("Recovered interface claim after reopening device");
+                return Ok(handle);
+            }
+        }
+    }
+    #[cfg(not(target_os = "windows"))]
+    let _ = device;
+
+    Err(HidError::ClaimFailed(err.to_string()))
+}
+
 /// Manages HID device connections using rusb
 pub struct HidManager {
     /// Current connection state
@@ -27,6 +137,16 @@ pub struct HidManager {
     initialized: bool,
     /// Whether auto-reconnect is enabled
     auto_reconnect: bool,
+    /// Last JPEG uploaded to each LCD button
+    button_images: HashMap<u8, Vec<u8>>,
+    /// Run flags of the animations playing on buttons
+    animations: HashMap<u8, Arc<AtomicBool>>,
+    /// Device that was unplugged while connected, to reconnect when it returns
+    reconnect_target: Option<DeviceInfo>,
+    /// Failed auto-reconnects since the connection was lost
+    reconnect_attempts: u32,
+    /// Hot-plug notification hook
+    hotplug_callback: Option<HotplugCallback>,
 }
 
 impl HidManager {
@@ -39,12 +159,17 @@ impl HidManager {
             handle: None,
             initialized: false,
             auto_reconnect: true,
+            button_images: HashMap::new(),
+            animations: HashMap::new(),
+            reconnect_target: None,
+            reconnect_attempts: 0,
+            hotplug_callback: None,
         }
     }
 
     /// Use hidapi to get feature report (uses Windows HID driver)
     /// This may work where rusb control transfers fail
-    fn get_feature_report_via_hidapi() -> Result<String, String> {
+    fn get_feature_report_via_hidapi() -> Result<Vec<u8>, String> {
         let api = hidapi::HidApi::new().map_err(|e| format!("hidapi init failed: {}", e))?;
 
         // Find and open the SOOMFON device
@@ -53,19 +178,30 @@ impl HidManager {
             .map_err(|e| format!("hidapi open failed: {}", e))?;
 
         // Get feature report with report ID 0
-        let mut buf = [0u8; 512];
+        let mut buf = [0u8; FEATURE_REPORT_SIZE];
         buf[0] = 0; // Report ID
 
         let n = device
             .get_feature_report(&mut buf)
             .map_err(|e| format!("get_feature_report failed: {}", e))?;
 
-        let version = std::str::from_utf8(&buf[1..n])
-            .unwrap_or("")
-            .trim_matches('\0')
-            .to_string();
+        Ok(buf[..n].to_vec())
+    }
 
-        Ok(version)
+    /// Read the raw firmware feature report, via hidapi or the rusb fallback
+    fn read_firmware_report(handle: &DeviceHandle<Context>) -> Option<Vec<u8>> {
+        match Self::get_feature_report_via_hidapi() {
+            Ok(report) => Some(report),
+            Err(e) => {
+                log::debug!("Feature report failed (not critical): {}", e);
+                // Try rusb fallback
+                let mut report_buf = [0u8; FEATURE_REPORT_SIZE];
+                handle
+                    .read_control(0xA1, 0x01, 0x0100, 0x0000, &mut report_buf, USB_TIMEOUT)
+                    .ok()
+                    .map(|n| report_buf[..n].to_vec())
+            }
+        }
     }
 
     /// Get current connection state
@@ -91,6 +227,99 @@ impl HidManager {
     /// Set auto-reconnect behavior
     pub fn set_auto_reconnect(&mut self, enabled: bool) {
         self.auto_reconnect = enabled;
+        if !enabled && self.is_reconnecting() {
+            self.reconnect_target = None;
+            self.state = ConnectionState::Disconnected;
+        }
+    }
+
+    /// Get auto-reconnect behavior
+    pub fn auto_reconnect(&self) -> bool {
+        self.auto_reconnect
+    }
+
+    /// Register the hook the hot-plug monitor calls on attach/detach
+    pub fn set_hotplug_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&HotplugEvent) + Send + Sync + 'static,
+    {
+        self.hotplug_callback = Some(Arc::new(callback));
+    }
+
+    /// Get the registered hot-plug hook, if any
+    pub fn hotplug_callback(&self) -> Option<HotplugCallback> {
+        self.hotplug_callback.clone()
+    }
+
+    /// Device to reconnect to once it is plugged back in
+    pub fn reconnect_target(&self) -> Option<&DeviceInfo> {
+        self.reconnect_target.as_ref()
+    }
+
+    /// Whether the connection was lost and is waiting to be reconnected
+    pub fn is_reconnecting(&self) -> bool {
+        self.state == ConnectionState::Reconnecting
+    }
+
+    /// Failed auto-reconnects since the connection was lost
+    pub fn reconnect_attempts(&self) -> u32 {
+        self.reconnect_attempts
+    }
+
+    /// Record a failed auto-reconnect; returns true once it is given up
+    ///
+    /// After `max_attempts` failures (0 = never give up) the unplugged device
+    /// is forgotten and the state moves to `Error`.
+    pub fn reconnect_failed(&mut self, max_attempts: u32) -> bool {
+        self.reconnect_attempts += 1;
+        if max_attempts > 0 && self.reconnect_attempts >= max_attempts {
+            log::warn!("Giving up on reconnecting after {} attempts", self.reconnect_attempts);
+            self.reconnect_target = None;
+            self.state = ConnectionState::Error;
+            true
+        } else {
+            self.state = ConnectionState::Reconnecting;
+            false
+        }
+    }
+
+    /// Whether a newly attached device should be auto-reconnected
+    ///
+    /// True when auto-reconnect is on, nothing is connected, and the device
+    /// matches the one that was unplugged. The path may differ if it was
+    /// re-plugged into another port.
+    pub fn should_reconnect_to(&self, attached: &DeviceInfo) -> bool {
+        self.auto_reconnect
+            && !self.is_connected()
+            && self
+                .reconnect_target
+                .as_ref()
+                .is_some_and(|target| is_same_device(target, attached))
+    }
+
+    /// Drop the connection after the device was physically unplugged
+    ///
+    /// Unlike `disconnect()`, skips the shutdown sequence (the device is gone)
+    /// and remembers the device so it can be reconnected when it returns. The
+    /// state becomes `Reconnecting` if auto-reconnect is on, `Disconnected`
+    /// otherwise.
+    pub fn handle_unplugged(&mut self) {
+        log::info!("SOOMFON device unplugged");
+
+        if let Some(info) = self.device_info.take() {
+            self.reconnect_target = Some(info);
+            self.reconnect_attempts = 0;
+        }
+        self.handle = None;
+        self.context = None;
+        self.state = if self.auto_reconnect && self.reconnect_target.is_some() {
+            ConnectionState::Reconnecting
+        } else {
+            ConnectionState::Disconnected
+        };
+        self.initialized = false;
+        self.button_images.clear();
+        self.stop_all_animations();
     }
 
     /// Get or initialize the USB context
@@ -110,67 +339,47 @@ impl HidManager {
             .devices()
             .map_err(|e| HidError::OpenFailed(e.to_string()))?
             .iter()
-            .filter_map(|device| {
-                let desc = device.device_descriptor().ok()?;
-                if desc.vendor_id() == SOOMFON_VID && desc.product_id() == SOOMFON_PID {
-                    // Get string descriptors if possible
-                    let handle = device.open().ok();
-                    let (serial, manufacturer, product) = if let Some(ref h) = handle {
-                        (
-                            h.read_serial_number_string_ascii(&desc).ok(),
-                            h.read_manufacturer_string_ascii(&desc).ok(),
-                            h.read_product_string_ascii(&desc).ok(),
-                        )
-                    } else {
-                        (None, None, None)
-                    };
-
-                    Some(DeviceInfo {
-                        path: format!(
-                            "{}:{}:{}",
-                            device.bus_number(),
-                            device.address(),
-                            device.port_number()
-                        ),
-                        serial_number: serial,
-                        manufacturer,
-                        product,
-                        firmware_version: None,
-                    })
-                } else {
-                    None
-                }
-            })
+            .filter_map(|device| describe_device(&device))
             .collect();
 
         Ok(devices)
     }
 
-    /// Connect to a SOOMFON device
+    /// Connect to the first SOOMFON device found
     pub fn connect(&mut self) -> HidResult<DeviceInfo> {
+        self.connect_to(None)
+    }
+
+    /// Connect to the SOOMFON device at a specific `bus:address:port` path
+    ///
+    /// The path is the one reported in `DeviceInfo.path` by `enumerate_devices()`.
+    /// Returns `DeviceNotFound` if no device matches the path anymore.
+    pub fn connect_by_path(&mut self, path: &str) -> HidResult<DeviceInfo> {
+        self.connect_to(Some(path))
+    }
+
+    fn connect_to(&mut self, path: Option<&str>) -> HidResult<DeviceInfo> {
         if self.is_connected() {
             if let Some(info) = &self.device_info {
-                return Ok(info.clone());
+                if path.is_none_or(|p| p == info.path) {
+                    return Ok(info.clone());
+                }
             }
+            // Connected to a different unit - release it before switching
+            self.disconnect();
         }
 
         self.state = ConnectionState::Connecting;
-        log::info!("Attempting to connect to SOOMFON device...");
+        match path {
+            Some(p) => log::info!("Attempting to connect to SOOMFON device at {}...", p),
+            None => log::info!("Attempting to connect to SOOMFON device..."),
+        }
 
         // Create new context for this connection
         let ctx = Context::new().map_err(|e| HidError::OpenFailed(e.to_string()))?;
 
         // Find the device
-        let device = ctx
-            .devices()
-            .map_err(|e| HidError::OpenFailed(e.to_string()))?
-            .iter()
-            .find(|d| {
-                d.device_descriptor().map_or(false, |desc| {
-                    desc.vendor_id() == SOOMFON_VID && desc.product_id() == SOOMFON_PID
-                })
-            })
-            .ok_or(HidError::DeviceNotFound)?;
+        let device = find_device(&ctx, path)?;
 
         let desc = device
             .device_descriptor()
@@ -187,16 +396,12 @@ impl HidManager {
         let product = handle.read_product_string_ascii(&desc).ok();
 
         let device_info = DeviceInfo {
-            path: format!(
-                "{}:{}:{}",
-                device.bus_number(),
-                device.address(),
-                device.port_number()
-            ),
+            path: device_path(&device),
             serial_number: serial,
             manufacturer,
             product,
             firmware_version: None,
+            firmware: None,
         };
 
         // Claim interface 0 (vendor interface)
@@ -210,15 +415,15 @@ impl HidManager {
             }
         }
 
-        handle
-            .claim_interface(VENDOR_INTERFACE)
-            .map_err(|e| HidError::ClaimFailed(e.to_string()))?;
+        let handle = claim_with_recovery(&device, handle)?;
 
         self.context = Some(ctx);
         self.handle = Some(handle);
         self.device_info = Some(device_info.clone());
         self.state = ConnectionState::Connected;
         self.initialized = false;
+        self.reconnect_target = None;
+        self.reconnect_attempts = 0;
 
         log::info!("Connected to SOOMFON device: {:?}", device_info.path);
         Ok(device_info)
@@ -243,33 +448,29 @@ impl HidManager {
         log::info!("Initializing SOOMFON device (mirajazz-compatible sequence)...");
 
         // Try to get firmware version (optional - doesn't affect event mode)
-        let firmware_version = match Self::get_feature_report_via_hidapi() {
-            Ok(version) => {
-                log::info!("Firmware version: {}", version);
-                Some(version)
-            }
-            Err(e) => {
-                log::debug!("Feature report failed (not critical): {}", e);
-                // Try rusb fallback
-                let mut report_buf = [0u8; FEATURE_REPORT_SIZE];
-                match handle.read_control(0xA1, 0x01, 0x0100, 0x0000, &mut report_buf, USB_TIMEOUT) {
-                    Ok(n) => {
-                        let version = std::str::from_utf8(&report_buf[..n])
-                            .unwrap_or("")
-                            .trim_matches('\0')
-                            .to_string();
-                        log::info!("Firmware version (rusb): {}", version);
-                        Some(version)
-                    }
-                    Err(_) => None,
-                }
+        let report = Self::read_firmware_report(handle);
+        let firmware = report.as_deref().and_then(parse_firmware_report);
+        let firmware_version = match (&firmware, &report) {
+            (Some(parsed), _) => Some(parsed.to_string()),
+            // Keep whatever text the device sent even if it isn't a dotted version
+            (None, Some(report)) => {
+                let text = String::from_utf8_lossy(report)
+                    .trim_matches(|c: char| c == '\0' || c.is_whitespace())
+                    .to_string();
+                (!text.is_empty()).then_some(text)
             }
+            (None, None) => None,
         };
+        match &firmware {
+            Some(parsed) => log::info!("Firmware version: {} ({})", parsed, parsed.raw),
+            None => log::info!("Firmware version unparsed: {:?}", firmware_version),
+        }
 
         // Update device info with firmware version
-        if let Some(ref version) = firmware_version {
+        if firmware_version.is_some() {
             if let Some(ref mut info) = self.device_info {
-                info.firmware_version = Some(version.clone());
+                info.firmware_version = firmware_version.clone();
+                info.firmware = firmware;
             }
         }
 
@@ -323,10 +524,96 @@ impl HidManager {
         self.device_info = None;
         self.state = ConnectionState::Disconnected;
         self.initialized = false;
+        self.button_images.clear();
+        self.stop_all_animations();
+        self.reconnect_target = None;
 
         log::info!("Disconnected from SOOMFON device");
     }
 
+    /// Reset the device to its factory display state without disconnecting
+    ///
+    /// Runs the shutdown sequence (clear LCDs, clear buttons, halt) followed by
+    /// a fresh `initialize()`. Returns the firmware version like `initialize()`.
+    pub fn reset(&mut self) -> HidResult<String> {
+        log::info!("Resetting SOOMFON device...");
+        self.shutdown()?;
+        std::thread::sleep(Duration::from_millis(100));
+
+        self.initialized = false;
+        self.state = ConnectionState::Connected;
+        self.button_images.clear();
+        self.stop_all_animations();
+        self.initialize()
+    }
+
+    /// Remember the JPEG last uploaded to a button
+    ///
+    /// A new image replaces any animation playing on the button.
+    pub fn cache_button_image(&mut self, index: u8, jpeg_data: Vec<u8>) {
+        self.stop_animation(index);
+        self.button_images.insert(index, jpeg_data);
+    }
+
+    /// Get the JPEG last uploaded to a button, if any
+    pub fn cached_button_image(&self, index: u8) -> Option<&[u8]> {
+        self.button_images.get(&index).map(|data| data.as_slice())
+    }
+
+    /// Whether a button is already showing exactly this image
+    ///
+    /// Compares the full processed bytes, so an upload can be skipped without
+    /// any risk of a hash collision showing a stale image.
+    pub fn is_button_image_current(&self, index: u8, jpeg_data: &[u8]) -> bool {
+        !self.is_animating(index) && self.cached_button_image(index) == Some(jpeg_data)
+    }
+
+    /// Forget cached images for one button, or all buttons when `index` is None
+    ///
+    /// Animations on those buttons are stopped too.
+    pub fn clear_cached_images(&mut self, index: Option<u8>) {
+        match index {
+            Some(i) => {
+                self.button_images.remove(&i);
+                self.stop_animation(i);
+            }
+            None => {
+                self.button_images.clear();
+                self.stop_all_animations();
+            }
+        }
+    }
+
+    /// Register a new animation on a button, stopping any already playing
+    ///
+    /// Returns the run flag the animation thread polls; it is cleared when the
+    /// animation should stop.
+    pub fn start_animation(&mut self, index: u8) -> Arc<AtomicBool> {
+        self.stop_animation(index);
+        let running = Arc::new(AtomicBool::new(true));
+        self.animations.insert(index, Arc::clone(&running));
+        running
+    }
+
+    /// Stop the animation playing on a button, if any
+    pub fn stop_animation(&mut self, index: u8) {
+        if let Some(running) = self.animations.remove(&index) {
+            running.store(false, Ordering::SeqCst);
+        }
+    }
+
+    /// Stop every button animation
+    fn stop_all_animations(&mut self) {
+        for (_, running) in self.animations.drain() {
+            running.store(false, Ordering::SeqCst);
+        }
+    }
+
+    /// Whether an animation is playing on a button
+    pub fn is_animating(&self, index: u8) -> bool {
+        self.animations.contains_key(&index)
+    }
+
     /// Send shutdown sequence to device
     pub fn shutdown(&mut self) -> HidResult<()> {
         if !self.is_connected() {
@@ -371,7 +658,7 @@ impl HidManager {
         for _ in 0..5 {
             match self.read_response_timeout(Duration::from_millis(50)) {
                 Ok(Some(data)) => {
-                    log::trace!("Drained {} bytes", data.len());
+                    log::trace!(target: USB_LOG_TARGET, "Drained {} bytes", data.len());
                 }
                 _ => break,
             }
@@ -386,9 +673,10 @@ impl HidManager {
         match handle.read_interrupt(EP_IN, &mut buf, timeout) {
             Ok(0) => Ok(None),
             Ok(n) => {
-                log::trace!("Read {} bytes from device", n);
+                log::trace!(target: USB_LOG_TARGET, "Read {} bytes from device", n);
                 if n >= 11 {
                     log::debug!(
+                        target: USB_LOG_TARGET,
                         "Raw data: {:02X} {:02X} {:02X} ... {:02X} {:02X}",
                         buf[0], buf[1], buf[2], buf[9], buf[10]
                     );
@@ -456,16 +744,9 @@ impl HidManager {
 
         let ctx = self.context.as_ref().ok_or(HidError::NotConnected)?;
 
-        let device = ctx
-            .devices()
-            .map_err(|e| HidError::OpenFailed(e.to_string()))?
-            .iter()
-            .find(|d| {
-                d.device_descriptor().map_or(false, |desc| {
-                    desc.vendor_id() == SOOMFON_VID && desc.product_id() == SOOMFON_PID
-                })
-            })
-            .ok_or(HidError::DeviceNotFound)?;
+        // Reopen the same unit we connected to, not just the first match
+        let path = self.device_info.as_ref().map(|info| info.path.as_str());
+        let device = find_device(ctx, path)?;
 
         let handle = device
             .open()
@@ -588,3 +869,166 @@ impl Drop for HidManager {
         self.disconnect();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ========== Image Cache Tests ==========
+
+    #[test]
+    fn test_image_cache_detects_identical_upload() {
+        let mut manager = HidManager::new();
+        assert!(!manager.is_button_image_current(0, &[1, 2, 3]));
+
+        manager.cache_button_image(0, vec![1, 2, 3]);
+        assert!(manager.is_button_image_current(0, &[1, 2, 3]));
+        assert!(!manager.is_button_image_current(0, &[1, 2, 4]));
+        assert!(!manager.is_button_image_current(1, &[1, 2, 3]));
+    }
+
+    #[test]
+    fn test_clearing_cache_invalidates_entries() {
+        let mut manager = HidManager::new();
+        manager.cache_button_image(0, vec![1]);
+        manager.cache_button_image(1, vec![2]);
+
+        manager.clear_cached_images(Some(0));
+        assert!(!manager.is_button_image_current(0, &[1]));
+        assert!(manager.is_button_image_current(1, &[2]));
+
+        manager.clear_cached_images(None);
+        assert!(!manager.is_button_image_current(1, &[2]));
+    }
+
+    // ========== Animation Tests ==========
+
+    #[test]
+    fn test_new_image_stops_animation() {
+        let mut manager = HidManager::new();
+        manager.cache_button_image(0, vec![1]);
+        let running = manager.start_animation(0);
+        assert!(manager.is_animating(0));
+        // The first frame is cached, but an animation must still be replaced
+        assert!(!manager.is_button_image_current(0, &[1]));
+
+        manager.cache_button_image(0, vec![1]);
+        assert!(!running.load(Ordering::SeqCst));
+        assert!(!manager.is_animating(0));
+    }
+
+    #[test]
+    fn test_restarting_animation_stops_previous() {
+        let mut manager = HidManager::new();
+        let first = manager.start_animation(2);
+        let second = manager.start_animation(2);
+        assert!(!first.load(Ordering::SeqCst));
+        assert!(second.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_clear_and_disconnect_stop_animations() {
+        let mut manager = HidManager::new();
+        let cleared = manager.start_animation(0);
+        let other = manager.start_animation(1);
+
+        manager.clear_cached_images(Some(0));
+        assert!(!cleared.load(Ordering::SeqCst));
+        assert!(other.load(Ordering::SeqCst));
+
+        manager.disconnect();
+        assert!(!other.load(Ordering::SeqCst));
+    }
+
+    // ========== Connection State Tests ==========
+
+    fn connected_manager() -> HidManager {
+        let mut manager = HidManager::new();
+        manager.device_info = Some(DeviceInfo {
+            path: "1:4:2".to_string(),
+            serial_number: Some("SN1".to_string()),
+            manufacturer: None,
+            product: None,
+            firmware_version: None,
+            firmware: None,
+        });
+        manager.state = ConnectionState::Initialized;
+        manager
+    }
+
+    #[test]
+    fn test_unplug_moves_to_reconnecting() {
+        let mut manager = connected_manager();
+        manager.handle_unplugged();
+
+        assert_eq!(manager.get_connection_state(), ConnectionState::Reconnecting);
+        assert!(manager.is_reconnecting());
+        assert!(!manager.is_connected());
+        assert_eq!(manager.reconnect_target().map(|info| info.path.as_str()), Some("1:4:2"));
+    }
+
+    #[test]
+    fn test_unplug_without_auto_reconnect_disconnects() {
+        let mut manager = connected_manager();
+        manager.set_auto_reconnect(false);
+        manager.handle_unplugged();
+
+        assert_eq!(manager.get_connection_state(), ConnectionState::Disconnected);
+    }
+
+    #[test]
+    fn test_repeated_unplug_keeps_reconnect_target() {
+        let mut manager = connected_manager();
+        manager.handle_unplugged();
+        manager.handle_unplugged();
+
+        assert!(manager.is_reconnecting());
+        assert!(manager.reconnect_target().is_some());
+    }
+
+    #[test]
+    fn test_reconnect_gives_up_after_max_attempts() {
+        let mut manager = connected_manager();
+        manager.handle_unplugged();
+
+        for attempt in 1..3 {
+            assert!(!manager.reconnect_failed(3));
+            assert_eq!(manager.reconnect_attempts(), attempt);
+            assert!(manager.is_reconnecting());
+        }
+        assert!(manager.reconnect_failed(3));
+        assert_eq!(manager.get_connection_state(), ConnectionState::Error);
+        assert!(manager.reconnect_target().is_none());
+    }
+
+    #[test]
+    fn test_reconnect_without_limit_never_gives_up() {
+        let mut manager = connected_manager();
+        manager.handle_unplugged();
+
+        for _ in 0..100 {
+            assert!(!manager.reconnect_failed(0));
+        }
+        assert!(manager.is_reconnecting());
+    }
+
+    #[test]
+    fn test_disabling_auto_reconnect_stops_reconnecting() {
+        let mut manager = connected_manager();
+        manager.handle_unplugged();
+        manager.set_auto_reconnect(false);
+
+        assert_eq!(manager.get_connection_state(), ConnectionState::Disconnected);
+        assert!(manager.reconnect_target().is_none());
+    }
+
+    #[test]
+    fn test_disconnect_from_reconnecting() {
+        let mut manager = connected_manager();
+        manager.handle_unplugged();
+        manager.disconnect();
+
+        assert_eq!(manager.get_connection_state(), ConnectionState::Disconnected);
+        assert!(manager.reconnect_target().is_none());
+    }
+}
@@ -6,9 +6,19 @@
 //! ## Architecture
 //!
 //! - `types`: Core types, constants, and error definitions
+//! - `acceleration`: Encoder rotation speed tracking
+//! - `animation`: Frame playback for animated button images
+//! - `diagnostics`: Step-by-step USB setup and init probes for troubleshooting
+//! - `event_queue`: Bounded packet hand-off from the USB reader to event handling
+//! - `idle`: Inactivity timer for auto-dimming the display
+//! - `long_press`: Long-press synthesis from press/release timing
+//! - `debounce`: Filter for duplicated raw button events
 //! - `packets`: CRT command packet builders and ACK response parsers
 //! - `manager`: Low-level USB communication using rusb
+//! - `monitor`: Background hot-plug detection
 //! - `protocol`: High-level protocol interface
+//! - `reconnect`: Backoff schedule for auto-reconnect attempts
+//! - `usb_reader`: Async (or sync fallback) reads of the interrupt endpoint
 //!
 //! ## Usage
 //!
@@ -31,17 +41,32 @@
 //! # Ok::<(), soomfon_controller_lib::hid::HidError>(())
 //! ```
 
+pub mod acceleration;
+pub mod animation;
+pub mod debounce;
+pub mod diagnostics;
+pub mod event_map;
+pub mod event_queue;
+pub mod idle;
+pub mod long_press;
 pub mod manager;
+pub mod monitor;
 pub mod packets;
 pub mod protocol;
+pub mod reconnect;
 pub mod types;
+pub mod usb_reader;
 
 // Re-export commonly used items
+pub use acceleration::{AccelerationCurve, EncoderAccelerator};
+pub use debounce::Debouncer;
+pub use long_press::LongPressDetector;
 pub use manager::HidManager;
+pub use monitor::{HotplugEvent, HotplugMonitor};
 pub use packets::{
     build_brightness_packet, build_clear_buttons_packet, build_clear_lcd_packet,
     build_connect_packet, build_display_init_packet, build_halt_packet, build_quick_command_packet,
-    is_ack_response, is_crt_response, parse_ack_packet,
+    is_ack_response, is_crt_response, parse_ack_packet, parse_firmware_report,
 };
 pub use protocol::SoomfonProtocol;
 pub use types::{
@@ -68,11 +93,14 @@ pub use types::{
     ButtonEventType,
     ButtonType,
     ConnectionState,
+    DeviceCapabilities,
     DeviceEvent,
     DeviceInfo,
     EncoderEventType,
     EncoderType,
+    FirmwareVersion,
     HidError,
+    HidErrorCode,
     HidResult,
     RawEvent,
 };
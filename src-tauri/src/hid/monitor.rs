@@ -0,0 +1,240 @@
+//! Hot-plug Monitor
+//!
+//! Background thread that watches for SOOMFON devices being plugged in or
+//! unplugged. Enumerates the USB bus every `RECONNECT_INTERVAL_MS` and diffs
+//! the result against the previous scan, which works on every platform
+//! (libusb hot-plug callbacks are unavailable on Windows).
+//!
+//! Events are delivered through the callback registered with
+//! `HidManager::set_hotplug_callback`.
+
+use super::manager::{describe_device, device_path, is_soomfon, HidManager};
+use super::types::{DeviceInfo, RECONNECT_INTERVAL_MS};
+use parking_lot::Mutex;
+use rusb::{Context, UsbContext};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Device attach/detach notification
+#[derive(Debug, Clone)]
+pub enum HotplugEvent {
+    /// A SOOMFON device was plugged in
+    Attached(DeviceInfo),
+    /// A SOOMFON device was unplugged
+    Detached(DeviceInfo),
+}
+
+/// Whether two device infos describe the same physical unit
+///
+/// Serial numbers are compared when both are known. Otherwise any SOOMFON
+/// (same VID/PID) counts as the same unit, so re-plugging into a different
+/// port, which changes the path, is still treated as the same device.
+pub fn is_same_device(a: &DeviceInfo, b: &DeviceInfo) -> bool {
+    match (&a.serial_number, &b.serial_number) {
+        (Some(sa), Some(sb)) => sa == sb,
+        _ => true,
+    }
+}
+
+/// Compare two scans (keyed by path) and list what changed
+///
+/// Detaches come first so a unit that moved ports is released before it is
+/// reported at its new path.
+pub fn diff_devices(
+    previous: &HashMap<String, DeviceInfo>,
+    current: &HashMap<String, DeviceInfo>,
+) -> Vec<HotplugEvent> {
+    let mut detached: Vec<&DeviceInfo> = previous
+        .iter()
+        .filter(|(path, _)| !current.contains_key(*path))
+        .map(|(_, info)| info)
+        .collect();
+    let mut attached: Vec<&DeviceInfo> = current
+        .iter()
+        .filter(|(path, _)| !previous.contains_key(*path))
+        .map(|(_, info)| info)
+        .collect();
+    detached.sort_by(|a, b| a.path.cmp(&b.path));
+    attached.sort_by(|a, b| a.path.cmp(&b.path));
+
+    detached
+        .into_iter()
+        .map(|info| HotplugEvent::Detached(info.clone()))
+        .chain(attached.into_iter().map(|info| HotplugEvent::Attached(info.clone())))
+        .collect()
+}
+
+/// Enumerate SOOMFON devices, only reading descriptors for paths not seen before
+fn scan(ctx: &Context, known: &HashMap<String, DeviceInfo>) -> HashMap<String, DeviceInfo> {
+    let devices = match ctx.devices() {
+        Ok(devices) => devices,
+        Err(e) => {
+            log::warn!("Hot-plug scan failed: {}", e);
+            // Keep the previous view rather than reporting everything as detached
+            return known.clone();
+        }
+    };
+
+    devices
+        .iter()
+        .filter(is_soomfon)
+        .filter_map(|device| {
+            let path = device_path(&device);
+            match known.get(&path) {
+                Some(info) => Some((path, info.clone())),
+                None => describe_device(&device).map(|info| (path, info)),
+            }
+        })
+        .collect()
+}
+
+/// Handle to the running monitor thread
+pub struct HotplugMonitor {
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl HotplugMonitor {
+    /// Start watching for devices
+    ///
+    /// Devices present at startup are recorded silently; only later changes
+    /// are reported.
+    pub fn start(manager: Arc<Mutex<HidManager>>) -> Result<Self, String> {
+        let ctx = Context::new().map_err(|e| format!("Failed to create USB context: {}", e))?;
+        let running = Arc::new(AtomicBool::new(true));
+        let running_clone = Arc::clone(&running);
+
+        let thread = std::thread::spawn(move || {
+            log::info!("Hot-plug monitor started");
+            let mut known = scan(&ctx, &HashMap::new());
+
+            while running_clone.load(Ordering::SeqCst) {
+                std::thread::sleep(Duration::from_millis(RECONNECT_INTERVAL_MS));
+                if !running_clone.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                let current = scan(&ctx, &known);
+                let events = diff_devices(&known, &current);
+                known = current;
+
+                if events.is_empty() {
+                    continue;
+                }
+
+                // Call the hook without holding the manager lock
+                let callback = manager.lock().hotplug_callback();
+                for event in &events {
+                    log::info!("Hot-plug: {:?}", event);
+                    if let Some(ref callback) = callback {
+                        callback(event);
+                    }
+                }
+            }
+
+            log::info!("Hot-plug monitor stopped");
+        });
+
+        Ok(Self {
+            running,
+            thread: Some(thread),
+        })
+    }
+
+    /// Stop the monitor thread and wait for it to exit
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for HotplugMonitor {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(path: &str, serial: Option<&str>) -> DeviceInfo {
+        DeviceInfo {
+            path: path.to_string(),
+            serial_number: serial.map(|s| s.to_string()),
+            manufacturer: None,
+            product: None,
+            firmware_version: None,
+            firmware: None,
+        }
+    }
+
+    fn scan_of(devices: &[DeviceInfo]) -> HashMap<String, DeviceInfo> {
+        devices.iter().map(|d| (d.path.clone(), d.clone())).collect()
+    }
+
+    // ========== Identity Tests ==========
+
+    #[test]
+    fn test_same_device_matches_serial_across_ports() {
+        assert!(is_same_device(&info("1:4:2", Some("ABC")), &info("1:7:3", Some("ABC"))));
+        assert!(!is_same_device(&info("1:4:2", Some("ABC")), &info("1:4:2", Some("XYZ"))));
+    }
+
+    #[test]
+    fn test_same_device_without_serial_falls_back_to_vid_pid() {
+        assert!(is_same_device(&info("1:4:2", None), &info("2:9:1", None)));
+        assert!(is_same_device(&info("1:4:2", Some("ABC")), &info("2:9:1", None)));
+    }
+
+    // ========== Diff Tests ==========
+
+    #[test]
+    fn test_diff_no_changes() {
+        let scan = scan_of(&[info("1:4:2", None)]);
+        assert!(diff_devices(&scan, &scan).is_empty());
+    }
+
+    #[test]
+    fn test_diff_attach_and_detach() {
+        let before = scan_of(&[info("1:4:2", None)]);
+        let after = scan_of(&[info("1:4:2", None), info("1:5:3", None)]);
+
+        let events = diff_devices(&before, &after);
+        assert!(matches!(events.as_slice(), [HotplugEvent::Attached(d)] if d.path == "1:5:3"));
+
+        let events = diff_devices(&after, &before);
+        assert!(matches!(events.as_slice(), [HotplugEvent::Detached(d)] if d.path == "1:5:3"));
+    }
+
+    #[test]
+    fn test_diff_port_change_detaches_before_attaching() {
+        let before = scan_of(&[info("1:4:2", Some("ABC"))]);
+        let after = scan_of(&[info("1:9:5", Some("ABC"))]);
+
+        let events = diff_devices(&before, &after);
+        assert_eq!(events.len(), 2);
+        match (&events[0], &events[1]) {
+            (HotplugEvent::Detached(old), HotplugEvent::Attached(new)) => {
+                assert_eq!(old.path, "1:4:2");
+                assert_eq!(new.path, "1:9:5");
+                assert!(is_same_device(old, new));
+            }
+            other => panic!("unexpected events: {:?}", other),
+        }
+    }
+
+    // ========== Reconnect Decision Tests ==========
+
+    #[test]
+    fn test_no_reconnect_without_unplugged_device() {
+        let manager = HidManager::new();
+        assert!(manager.reconnect_target().is_none());
+        assert!(!manager.should_reconnect_to(&info("1:4:2", None)));
+    }
+}
@@ -5,7 +5,7 @@
 //!
 //! Protocol based on reverse-engineered USB captures from usb-protocol-reverse-engineering.md
 
-use super::types::{RawEvent, CRT_PACKET_SIZE};
+use super::types::{FirmwareVersion, RawEvent, CRT_PACKET_SIZE};
 
 // =============================================================================
 // CRT Command Packet Builders
@@ -205,6 +205,53 @@ pub fn is_crt_response(data: &[u8]) -> bool {
     data.len() >= 3 && &data[0..3] == b"CRT"
 }
 
+// =============================================================================
+// Feature Report Parsing
+// =============================================================================
+
+/// Parse the firmware version out of a feature report
+///
+/// The report carries a NUL-padded ASCII string such as `"V3.1.0.7"`, but
+/// where it starts depends on the transport: hidapi prefixes the report ID,
+/// the rusb control transfer does not, and some firmware adds a short binary
+/// header. Each run of printable ASCII is therefore scanned for the first
+/// dotted number, so a short or truncated report still parses as long as the
+/// version text made it through.
+///
+/// Missing trailing components (e.g. `"1.2"`) are reported as 0. At least a
+/// major and minor number are required. Returns `None` if no version is found.
+pub fn parse_firmware_report(data: &[u8]) -> Option<FirmwareVersion> {
+    data.split(|b| !(0x20..=0x7E).contains(b))
+        .filter_map(|run| std::str::from_utf8(run).ok())
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .find_map(parse_version_text)
+}
+
+/// Find the first `a.b[.c[.d]]` number sequence in a string
+fn parse_version_text(text: &str) -> Option<FirmwareVersion> {
+    text.split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .find_map(|token| {
+            let parts: Vec<u16> = token
+                .split('.')
+                .take_while(|part| !part.is_empty())
+                .map_while(|part| part.parse().ok())
+                .take(4)
+                .collect();
+            if parts.len() < 2 {
+                return None;
+            }
+            let part = |i: usize| parts.get(i).copied().unwrap_or(0);
+            Some(FirmwareVersion {
+                major: part(0),
+                minor: part(1),
+                patch: part(2),
+                build: part(3),
+                raw: text.to_string(),
+            })
+        })
+}
+
 // =============================================================================
 // Image Transfer (based on mirajazz library reverse engineering)
 // =============================================================================
@@ -212,12 +259,17 @@ pub fn is_crt_response(data: &[u8]) -> bool {
 /// Build an image transfer BAT (batch) command packet
 ///
 /// Protocol based on mirajazz library:
-/// - Header: ['C', 'R', 'T', 0x00, 0x00, 'B', 'A', 'T', 0x00, 0x00, size_hi, size_lo, key+1]
+/// - Header: ['C', 'R', 'T', 0x00, 0x00, 'B', 'A', 'T', size (4 bytes), key+1]
 /// - Format matches other CRT packets (no explicit Report ID - handled by USB layer)
 ///
+/// mirajazz only ever writes the low two size bytes at offsets 10-11 and
+/// leaves 8-9 zero, which is the same as a 4-byte big-endian length at
+/// offsets 8-11 for images under 64KB. The full 32-bit length is written
+/// there so larger images (72x72, raw RGB565) aren't truncated.
+///
 /// # Arguments
 /// * `button_index` - Button index (0-5)
-/// * `data_length` - Total JPEG data length in bytes
+/// * `data_length` - Total image data length in bytes
 pub fn build_image_bat_packet(button_index: u8, data_length: u32) -> [u8; CRT_PACKET_SIZE] {
     let mut packet = [0u8; CRT_PACKET_SIZE];
 
@@ -227,13 +279,9 @@ pub fn build_image_bat_packet(button_index: u8, data_length: u32) -> [u8; CRT_PA
 
     // Command: BAT
     packet[5..8].copy_from_slice(b"BAT");
-    // packet[8..10] are already 0x00
 
-    // Image size (big-endian, 2 bytes)
-    // Note: data_length is truncated to u16 for protocol compatibility
-    let size = (data_length as u16).min(u16::MAX);
-    packet[10] = (size >> 8) as u8;  // High byte
-    packet[11] = (size & 0xFF) as u8; // Low byte
+    // Image size (big-endian, 4 bytes)
+    packet[8..12].copy_from_slice(&data_length.to_be_bytes());
 
     // Button index + 1 (buttons are 1-indexed in protocol)
     packet[12] = button_index + 1;
@@ -266,6 +314,7 @@ pub fn build_image_data_packet(data: &[u8], _offset: usize) -> [u8; CRT_PACKET_S
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::hid::types::ACK_PACKET_SIZE;
 
     #[test]
     fn test_display_init_packet_format() {
@@ -435,6 +484,15 @@ mod tests {
         assert_eq!(packet[12], 0x01);
     }
 
+    #[test]
+    fn test_image_bat_packet_size_over_64kb() {
+        let packet = build_image_bat_packet(2, 100_000);
+
+        // 100000 = 0x000186A0, must not wrap to 0x86A0
+        assert_eq!(&packet[8..12], &[0x00, 0x01, 0x86, 0xA0]);
+        assert_eq!(packet[12], 0x03);
+    }
+
     #[test]
     fn test_image_bat_packet_button_index() {
         // Test button 5 (index 5 -> protocol value 6)
@@ -464,4 +522,43 @@ mod tests {
         assert_eq!(packet[100], 0x00);
         assert_eq!(packet[1023], 0x00);
     }
+
+    // ========== Firmware Report Tests ==========
+
+    #[test]
+    fn test_parse_firmware_report_with_report_id() {
+        let mut report = vec![0x00];
+        report.extend_from_slice(b"V3.1.0.7");
+        report.resize(512, 0x00);
+
+        let version = parse_firmware_report(&report).unwrap();
+        assert_eq!((version.major, version.minor, version.patch, version.build), (3, 1, 0, 7));
+        assert_eq!(version.raw, "V3.1.0.7");
+        assert_eq!(version.to_string(), "3.1.0.7");
+    }
+
+    #[test]
+    fn test_parse_firmware_report_skips_binary_header() {
+        let mut report = vec![0x01, 0x04, 0xFF, 0x00, 0x10];
+        report.extend_from_slice(b"SOOMFON_2.05.13");
+
+        let version = parse_firmware_report(&report).unwrap();
+        assert_eq!((version.major, version.minor, version.patch, version.build), (2, 5, 13, 0));
+        assert_eq!(version.raw, "SOOMFON_2.05.13");
+    }
+
+    #[test]
+    fn test_parse_firmware_report_truncated() {
+        // rusb fallback returned fewer bytes than the full version string
+        let version = parse_firmware_report(b"V1.2").unwrap();
+        assert_eq!((version.major, version.minor, version.patch, version.build), (1, 2, 0, 0));
+    }
+
+    #[test]
+    fn test_parse_firmware_report_without_version() {
+        assert!(parse_firmware_report(&[]).is_none());
+        assert!(parse_firmware_report(&[0x00; 64]).is_none());
+        assert!(parse_firmware_report(b"V1").is_none());
+        assert!(parse_firmware_report(b"SOOMFON").is_none());
+    }
 }
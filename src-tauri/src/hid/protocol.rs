@@ -8,6 +8,7 @@
 use super::manager::HidManager;
 use super::packets::*;
 use super::types::*;
+use crate::image::processor::create_solid_color;
 use std::time::Duration;
 
 /// High-level protocol interface for SOOMFON devices
@@ -31,6 +32,19 @@ impl<'a> SoomfonProtocol<'a> {
         self.manager.set_brightness(level)
     }
 
+    /// Read and parse the firmware version from the device's feature report
+    ///
+    /// Fails with `InvalidData` if the report doesn't contain a dotted version.
+    pub fn get_firmware_version(&self) -> HidResult<FirmwareVersion> {
+        let report = self.manager.get_feature_report(0, FEATURE_REPORT_SIZE)?;
+        parse_firmware_report(&report).ok_or_else(|| {
+            HidError::InvalidData(format!(
+                "No firmware version in {}-byte feature report",
+                report.len()
+            ))
+        })
+    }
+
     /// Send keepalive to maintain connection
     pub fn send_keepalive(&self) -> HidResult<()> {
         self.manager.send_keepalive()
@@ -58,14 +72,33 @@ impl<'a> SoomfonProtocol<'a> {
         Ok(())
     }
 
-    /// Clear screen (clears LCD displays)
-    /// If button_index is Some, only that button is cleared (not implemented yet)
-    /// If button_index is None, all buttons are cleared
-    pub fn clear_screen(&self, _button_index: Option<u8>) -> HidResult<()> {
-        // Currently we only support clearing all displays
-        // Individual button clearing needs protocol reverse engineering
-        self.manager.send_command(&build_clear_lcd_packet())?;
-        Ok(())
+    /// Clear screen to black
+    /// If button_index is Some, only that button is cleared (by uploading a black image)
+    /// If button_index is None, all displays are cleared with CLE.DC
+    pub fn clear_screen(&self, button_index: Option<u8>) -> HidResult<()> {
+        match button_index {
+            Some(index) => self.clear_screen_color(Some(index), (0, 0, 0)).map(|_| ()),
+            None => {
+                self.manager.send_command(&build_clear_lcd_packet())?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Fill a button (or all buttons if None) with a solid RGB color
+    ///
+    /// Returns the uploaded JPEG so the caller can cache it.
+    pub fn clear_screen_color(&self, button_index: Option<u8>, rgb: (u8, u8, u8)) -> HidResult<Vec<u8>> {
+        let (r, g, b) = rgb;
+        let jpeg_data = create_solid_color(r, g, b).map_err(HidError::InvalidData)?;
+        let indices = match button_index {
+            Some(index) => index..=index,
+            None => 0..=LCD_BUTTON_COUNT - 1,
+        };
+        for index in indices {
+            self.set_button_image(index, &jpeg_data)?;
+        }
+        Ok(jpeg_data)
     }
 
     /// Send a raw CRT command packet
@@ -93,25 +126,7 @@ impl<'a> SoomfonProtocol<'a> {
     /// * `button_index` - Button index (0-5)
     /// * `jpeg_data` - JPEG image data (should be 60x60 from image processor)
     pub fn set_button_image(&self, button_index: u8, jpeg_data: &[u8]) -> HidResult<()> {
-        // Validate button index
-        if button_index > 5 {
-            return Err(HidError::InvalidData(format!(
-                "Button index {} out of range (0-5)",
-                button_index
-            )));
-        }
-
-        // Validate JPEG data
-        if jpeg_data.len() < 3 {
-            return Err(HidError::InvalidData("Image data too small".to_string()));
-        }
-
-        // Check JPEG magic bytes (FF D8 FF)
-        if jpeg_data[0] != 0xFF || jpeg_data[1] != 0xD8 || jpeg_data[2] != 0xFF {
-            return Err(HidError::InvalidData(
-                "Invalid JPEG data - missing magic bytes".to_string(),
-            ));
-        }
+        validate_button_image(button_index, jpeg_data)?;
 
         log::info!(
             "Setting button {} image ({} bytes JPEG)",
@@ -119,27 +134,12 @@ impl<'a> SoomfonProtocol<'a> {
             jpeg_data.len()
         );
 
-        // Step 1: Send BAT header packet
-        let bat_packet = build_image_bat_packet(button_index, jpeg_data.len() as u32);
-        self.manager.send_command(&bat_packet)?;
-        log::debug!("Sent BAT header for button {}", button_index);
-
-        // Step 2: Send image data in chunks
-        let chunk_size = CRT_PACKET_SIZE;
-        let mut offset = 0;
-
-        while offset < jpeg_data.len() {
-            let end = (offset + chunk_size).min(jpeg_data.len());
-            let chunk = &jpeg_data[offset..end];
-            let data_packet = build_image_data_packet(chunk, offset);
-            self.manager.send_command(&data_packet)?;
-            log::trace!("Sent image chunk at offset {}", offset);
-            offset = end;
-        }
-
+        // Steps 1 and 2: BAT header + data chunks
+        send_packets(&image_packets(button_index, jpeg_data), |p| self.manager.send_command(p))?;
         log::debug!(
-            "Sent {} image data chunks",
-            (jpeg_data.len() + chunk_size - 1) / chunk_size
+            "Sent BAT header and {} image data chunks for button {}",
+            jpeg_data.len().div_ceil(CRT_PACKET_SIZE),
+            button_index
         );
 
         // Step 3: Send STP packet to commit
@@ -150,17 +150,189 @@ impl<'a> SoomfonProtocol<'a> {
         Ok(())
     }
 
-    /// Clear a single button's image
+    /// Set several button images in one burst
     ///
-    /// Currently clears all displays - individual button clearing not yet implemented.
-    pub fn clear_button_image(&self, _button_index: u8) -> HidResult<()> {
-        // For now, clearing a single button clears all (protocol limitation)
-        self.clear_displays()
+    /// All images are validated before anything is sent, then every BAT header
+    /// and data chunk is written back-to-back with a single STP at the end, so
+    /// the buttons update together instead of one by one.
+    pub fn set_button_images_batch(&self, images: &[(u8, Vec<u8>)]) -> HidResult<()> {
+        let packets = build_image_batch_packets(images)?;
+        if images.is_empty() {
+            return Ok(());
+        }
+
+        log::info!(
+            "Setting {} button images in one batch ({} packets)",
+            images.len(),
+            packets.len()
+        );
+
+        send_packets(&packets, |p| self.manager.send_command(p))?;
+
+        log::info!("Batch of {} button images set successfully", images.len());
+        Ok(())
+    }
+
+    /// Clear a single button's image to black
+    pub fn clear_button_image(&self, button_index: u8) -> HidResult<()> {
+        self.clear_screen(Some(button_index))
+    }
+}
+
+/// Check that a button index is in range and the data looks like a JPEG
+fn validate_button_image(button_index: u8, jpeg_data: &[u8]) -> HidResult<()> {
+    if button_index > 5 {
+        return Err(HidError::InvalidData(format!(
+            "Button index {} out of range (0-5)",
+            button_index
+        )));
+    }
+
+    if jpeg_data.len() < 3 {
+        return Err(HidError::InvalidData("Image data too small".to_string()));
+    }
+
+    // Check JPEG magic bytes (FF D8 FF)
+    if jpeg_data[0] != 0xFF || jpeg_data[1] != 0xD8 || jpeg_data[2] != 0xFF {
+        return Err(HidError::InvalidData(
+            "Invalid JPEG data - missing magic bytes".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Write packets in order, stopping at the first failure
+fn send_packets<F>(packets: &[[u8; CRT_PACKET_SIZE]], mut send: F) -> HidResult<()>
+where
+    F: FnMut(&[u8; CRT_PACKET_SIZE]) -> HidResult<usize>,
+{
+    for packet in packets {
+        send(packet)?;
     }
+    Ok(())
+}
+
+/// BAT header followed by the data chunks for one image (no STP)
+fn image_packets(button_index: u8, jpeg_data: &[u8]) -> Vec<[u8; CRT_PACKET_SIZE]> {
+    let mut packets = Vec::with_capacity(1 + jpeg_data.len().div_ceil(CRT_PACKET_SIZE));
+    packets.push(build_image_bat_packet(button_index, jpeg_data.len() as u32));
+
+    let mut offset = 0;
+    while offset < jpeg_data.len() {
+        let end = (offset + CRT_PACKET_SIZE).min(jpeg_data.len());
+        packets.push(build_image_data_packet(&jpeg_data[offset..end], offset));
+        offset = end;
+    }
+
+    packets
+}
+
+/// Build every packet for a multi-button update, ending with a single STP
+///
+/// Fails without producing packets if any image is invalid. Returns no
+/// packets for an empty batch.
+pub fn build_image_batch_packets(images: &[(u8, Vec<u8>)]) -> HidResult<Vec<[u8; CRT_PACKET_SIZE]>> {
+    for (index, data) in images {
+        validate_button_image(*index, data)?;
+    }
+    if images.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut packets: Vec<[u8; CRT_PACKET_SIZE]> = images
+        .iter()
+        .flat_map(|(index, data)| image_packets(*index, data))
+        .collect();
+    packets.push(build_stp_packet());
+    Ok(packets)
 }
 
 #[cfg(test)]
 mod tests {
-    // Protocol tests would require a connected device
-    // Unit tests are in the individual modules
+    // Device I/O tests would require a connected device; packet layout is tested here
+    use super::*;
+
+    fn fake_jpeg(len: usize) -> Vec<u8> {
+        let mut data = vec![0u8; len];
+        data[..3].copy_from_slice(&[0xFF, 0xD8, 0xFF]);
+        data
+    }
+
+    // ========== Batch Packet Tests ==========
+
+    #[test]
+    fn test_batch_packets_single_stp_at_end() {
+        let images = vec![(0, fake_jpeg(100)), (1, fake_jpeg(CRT_PACKET_SIZE + 1))];
+        let packets = build_image_batch_packets(&images).unwrap();
+
+        // 1 BAT + 1 chunk, 1 BAT + 2 chunks, 1 STP
+        assert_eq!(packets.len(), 6);
+        assert_eq!(packets[0], build_image_bat_packet(0, 100));
+        assert_eq!(packets[2], build_image_bat_packet(1, (CRT_PACKET_SIZE + 1) as u32));
+        assert_eq!(packets[5], build_stp_packet());
+        assert_eq!(packets.iter().filter(|p| **p == build_stp_packet()).count(), 1);
+    }
+
+    #[test]
+    fn test_batch_rejects_any_invalid_image() {
+        let images = vec![(0, fake_jpeg(100)), (6, fake_jpeg(100))];
+        assert!(build_image_batch_packets(&images).is_err());
+
+        let images = vec![(0, fake_jpeg(100)), (1, vec![0x00, 0x01, 0x02])];
+        assert!(build_image_batch_packets(&images).is_err());
+    }
+
+    #[test]
+    fn test_batch_empty_produces_nothing() {
+        assert!(build_image_batch_packets(&[]).unwrap().is_empty());
+    }
+
+    // ========== Large Image Tests ==========
+
+    #[test]
+    fn test_large_image_round_trips_through_send() {
+        let mut image = fake_jpeg(100_000);
+        for (i, byte) in image.iter_mut().enumerate().skip(3) {
+            *byte = (i % 251) as u8;
+        }
+
+        let mut sent: Vec<[u8; CRT_PACKET_SIZE]> = Vec::new();
+        let packets = build_image_batch_packets(&[(4, image.clone())]).unwrap();
+        send_packets(&packets, |p| {
+            sent.push(*p);
+            Ok(p.len())
+        })
+        .unwrap();
+
+        // Device side: read the length from the BAT header and reassemble
+        let bat = &sent[0];
+        assert_eq!(&bat[5..8], b"BAT");
+        assert_eq!(bat[12], 5);
+        let length = u32::from_be_bytes([bat[8], bat[9], bat[10], bat[11]]) as usize;
+        assert_eq!(length, 100_000);
+
+        let chunks = &sent[1..sent.len() - 1];
+        assert_eq!(chunks.len(), length.div_ceil(CRT_PACKET_SIZE));
+        let received: Vec<u8> = chunks.iter().flatten().copied().take(length).collect();
+        assert_eq!(received, image);
+        assert_eq!(sent.last(), Some(&build_stp_packet()));
+    }
+
+    #[test]
+    fn test_send_packets_stops_on_error() {
+        let packets = build_image_batch_packets(&[(0, fake_jpeg(3 * CRT_PACKET_SIZE))]).unwrap();
+        let mut attempts = 0;
+        let result = send_packets(&packets, |_| {
+            attempts += 1;
+            if attempts == 2 {
+                Err(HidError::WriteFailed("pipe".to_string()))
+            } else {
+                Ok(CRT_PACKET_SIZE)
+            }
+        });
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 2);
+    }
 }
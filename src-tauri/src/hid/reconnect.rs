@@ -0,0 +1,70 @@
+//! Reconnect Backoff
+//!
+//! Delay schedule for auto-reconnect attempts after the connection is lost:
+//! starts at `RECONNECT_INTERVAL_MS` and doubles after each attempt up to
+//! `MAX_RECONNECT_INTERVAL_MS`, so a device that stays away isn't probed
+//! every couple of seconds forever. A fresh schedule starts with each lost
+//! connection.
+
+use super::types::{MAX_RECONNECT_INTERVAL_MS, RECONNECT_INTERVAL_MS};
+use std::time::Duration;
+
+/// Exponential backoff between reconnect attempts
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    next: Duration,
+    max: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(
+            Duration::from_millis(RECONNECT_INTERVAL_MS),
+            Duration::from_millis(MAX_RECONNECT_INTERVAL_MS),
+        )
+    }
+}
+
+impl Backoff {
+    /// Start at `base`, never waiting longer than `max`
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self {
+            next: base.min(max),
+            max,
+        }
+    }
+
+    /// Delay before the next attempt; each call doubles the following one
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.next;
+        self.next = (delay * 2).min(self.max);
+        delay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ========== Backoff Tests ==========
+
+    #[test]
+    fn test_default_starts_at_reconnect_interval() {
+        let mut backoff = Backoff::default();
+        assert_eq!(backoff.next_delay(), Duration::from_millis(RECONNECT_INTERVAL_MS));
+        assert_eq!(backoff.next_delay(), Duration::from_millis(RECONNECT_INTERVAL_MS * 2));
+    }
+
+    #[test]
+    fn test_doubles_up_to_cap() {
+        let mut backoff = Backoff::new(Duration::from_secs(2), Duration::from_secs(30));
+        let delays: Vec<u64> = (0..7).map(|_| backoff.next_delay().as_secs()).collect();
+        assert_eq!(delays, vec![2, 4, 8, 16, 30, 30, 30]);
+    }
+
+    #[test]
+    fn test_base_above_cap_is_capped() {
+        let mut backoff = Backoff::new(Duration::from_secs(60), Duration::from_secs(30));
+        assert_eq!(backoff.next_delay(), Duration::from_secs(30));
+    }
+}
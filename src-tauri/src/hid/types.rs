@@ -4,6 +4,7 @@
 //! Based on reverse-engineered USB protocol from usb-protocol-reverse-engineering.md
 
 use serde::{Deserialize, Serialize};
+use super::event_map::EventMap;
 
 // =============================================================================
 // Device Identification
@@ -57,10 +58,18 @@ pub const REPORT_SIZE: usize = 64;
 pub const LCD_WIDTH: u32 = 60;
 pub const LCD_HEIGHT: u32 = 60;
 
+/// Control layout of the current SOOMFON model
+pub const LCD_BUTTON_COUNT: u8 = 6;
+pub const PHYSICAL_BUTTON_COUNT: u8 = 3;
+pub const ENCODER_COUNT: u8 = 3;
+
 // =============================================================================
 // Timing Constants
 // =============================================================================
 
+/// Log target for per-packet USB traffic, filtered separately from other logs
+pub const USB_LOG_TARGET: &str = "usb";
+
 /// USB timeout in milliseconds
 pub const USB_TIMEOUT_MS: u64 = 500;
 
@@ -76,6 +85,9 @@ pub const DEBOUNCE_MS: u64 = 50;
 /// Reconnect interval in milliseconds
 pub const RECONNECT_INTERVAL_MS: u64 = 2000;
 
+/// Longest wait between auto-reconnect attempts in milliseconds
+pub const MAX_RECONNECT_INTERVAL_MS: u64 = 30000;
+
 /// Keep-alive interval in milliseconds (for CRT..CONNECT packets)
 pub const KEEPALIVE_INTERVAL_MS: u64 = 10000;
 
@@ -144,6 +156,8 @@ pub enum ConnectionState {
     Connected,
     /// Device initialized and ready for events
     Initialized,
+    /// Connection was lost; waiting for the device to come back
+    Reconnecting,
     /// Connection error occurred
     Error,
 }
@@ -162,6 +176,72 @@ pub struct DeviceInfo {
     pub product: Option<String>,
     /// Firmware version (from HID feature report)
     pub firmware_version: Option<String>,
+    /// Parsed firmware version, when the feature report could be decoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub firmware: Option<FirmwareVersion>,
+}
+
+/// Firmware version decoded from the device's feature report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FirmwareVersion {
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+    pub build: u16,
+    /// Printable text the version was parsed from
+    pub raw: String,
+}
+
+impl std::fmt::Display for FirmwareVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}.{}", self.major, self.minor, self.patch, self.build)
+    }
+}
+
+/// Physical layout of a device model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceCapabilities {
+    pub lcd_button_count: u8,
+    pub physical_button_count: u8,
+    pub encoder_count: u8,
+    pub lcd_width: u32,
+    pub lcd_height: u32,
+}
+
+impl Default for DeviceCapabilities {
+    fn default() -> Self {
+        Self {
+            lcd_button_count: LCD_BUTTON_COUNT,
+            physical_button_count: PHYSICAL_BUTTON_COUNT,
+            encoder_count: ENCODER_COUNT,
+            lcd_width: LCD_WIDTH,
+            lcd_height: LCD_HEIGHT,
+        }
+    }
+}
+
+impl DeviceCapabilities {
+    /// Product strings reported by known models (matched case-insensitively)
+    const KNOWN_PRODUCTS: &'static [&'static str] = &["soomfon", "stream controller"];
+
+    /// Look up the layout for a USB product string
+    ///
+    /// Only one layout is known so far, so every device gets the defaults;
+    /// unrecognized product strings are logged so new models can be added.
+    pub fn for_product(product: Option<&str>) -> Self {
+        if let Some(product) = product {
+            let lower = product.to_ascii_lowercase();
+            if !Self::KNOWN_PRODUCTS.iter().any(|known| lower.contains(known)) {
+                log::warn!(
+                    "Unrecognized product string {:?}, assuming the standard SOOMFON layout",
+                    product
+                );
+            }
+        }
+        Self::default()
+    }
 }
 
 /// Event types from button interactions
@@ -185,7 +265,7 @@ pub enum EncoderEventType {
 }
 
 /// Button type classification
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum ButtonType {
     /// LCD button with display (1-6)
@@ -195,7 +275,7 @@ pub enum ButtonType {
 }
 
 /// Encoder identification
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum EncoderType {
     /// Main encoder (large center dial)
@@ -207,7 +287,7 @@ pub enum EncoderType {
 }
 
 /// Device event from SOOMFON hardware
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum DeviceEvent {
     /// Button press/release event
@@ -225,7 +305,21 @@ pub enum DeviceEvent {
         encoder_type: EncoderType,
         /// Event type
         event_type: EncoderEventType,
+        /// Rotation speed multiplier (1 = a single detent, always 1 for presses)
+        #[serde(default = "default_velocity")]
+        velocity: u8,
     },
+    /// Event ID with no known mapping, e.g. a control on newer hardware
+    Unknown {
+        /// Event ID byte from the ACK packet
+        event_id: u8,
+        /// State byte from the ACK packet
+        state: u8,
+    },
+}
+
+fn default_velocity() -> u8 {
+    1
 }
 
 /// Raw ACK packet event data
@@ -238,7 +332,18 @@ pub struct RawEvent {
 }
 
 impl RawEvent {
+    /// Parse raw event, consulting the event map before the built-in IDs
+    pub fn parse_with(&self, map: &EventMap) -> Option<DeviceEvent> {
+        match map.get(self.event_id) {
+            Some(target) => Some(target.event(self.state)),
+            None => self.parse(),
+        }
+    }
+
     /// Parse raw event into a DeviceEvent
+    ///
+    /// Unmapped event IDs come back as `DeviceEvent::Unknown` rather than
+    /// being dropped, so new controls can be discovered.
     pub fn parse(&self) -> Option<DeviceEvent> {
         let is_press = self.state == event_state::PRESS;
 
@@ -280,18 +385,21 @@ impl RawEvent {
             main_encoder::ROTATE_CCW => {
                 return Some(DeviceEvent::Encoder {
                     encoder_type: EncoderType::Main,
+                    velocity: 1,
                     event_type: EncoderEventType::RotateCCW,
                 });
             }
             main_encoder::ROTATE_CW => {
                 return Some(DeviceEvent::Encoder {
                     encoder_type: EncoderType::Main,
+                    velocity: 1,
                     event_type: EncoderEventType::RotateCW,
                 });
             }
             main_encoder::PUSH => {
                 return Some(DeviceEvent::Encoder {
                     encoder_type: EncoderType::Main,
+                    velocity: 1,
                     event_type: if is_press {
                         EncoderEventType::Press
                     } else {
@@ -307,18 +415,21 @@ impl RawEvent {
             side_encoder_1::ROTATE_CCW => {
                 return Some(DeviceEvent::Encoder {
                     encoder_type: EncoderType::Side1,
+                    velocity: 1,
                     event_type: EncoderEventType::RotateCCW,
                 });
             }
             side_encoder_1::ROTATE_CW => {
                 return Some(DeviceEvent::Encoder {
                     encoder_type: EncoderType::Side1,
+                    velocity: 1,
                     event_type: EncoderEventType::RotateCW,
                 });
             }
             side_encoder_1::PUSH => {
                 return Some(DeviceEvent::Encoder {
                     encoder_type: EncoderType::Side1,
+                    velocity: 1,
                     event_type: if is_press {
                         EncoderEventType::Press
                     } else {
@@ -334,18 +445,21 @@ impl RawEvent {
             side_encoder_2::ROTATE_CCW => {
                 return Some(DeviceEvent::Encoder {
                     encoder_type: EncoderType::Side2,
+                    velocity: 1,
                     event_type: EncoderEventType::RotateCCW,
                 });
             }
             side_encoder_2::ROTATE_CW => {
                 return Some(DeviceEvent::Encoder {
                     encoder_type: EncoderType::Side2,
+                    velocity: 1,
                     event_type: EncoderEventType::RotateCW,
                 });
             }
             side_encoder_2::PUSH => {
                 return Some(DeviceEvent::Encoder {
                     encoder_type: EncoderType::Side2,
+                    velocity: 1,
                     event_type: if is_press {
                         EncoderEventType::Press
                     } else {
@@ -356,7 +470,10 @@ impl RawEvent {
             _ => {}
         }
 
-        None
+        Some(DeviceEvent::Unknown {
+            event_id: self.event_id,
+            state: self.state,
+        })
     }
 }
 
@@ -413,10 +530,147 @@ impl From<rusb::Error> for HidError {
     }
 }
 
+/// Category of a HID error, sent to the frontend so it can link to the
+/// matching help page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HidErrorCode {
+    DeviceNotFound,
+    NotConnected,
+    NotInitialized,
+    AccessDenied,
+    DeviceBusy,
+    DriverMissing,
+    OpenFailed,
+    ClaimFailed,
+    WriteFailed,
+    ReadFailed,
+    InvalidData,
+    ConnectionLost,
+    Timeout,
+    UsbError,
+}
+
+impl HidError {
+    /// Machine-readable category
+    ///
+    /// Permission and busy failures are told apart from other open and claim
+    /// failures by the underlying libusb message.
+    pub fn code(&self) -> HidErrorCode {
+        match self {
+            HidError::DeviceNotFound => HidErrorCode::DeviceNotFound,
+            HidError::NotConnected => HidErrorCode::NotConnected,
+            HidError::NotInitialized => HidErrorCode::NotInitialized,
+            HidError::OpenFailed(e) | HidError::ClaimFailed(e) | HidError::UsbError(e) if is_access_error(e) => {
+                HidErrorCode::AccessDenied
+            }
+            HidError::OpenFailed(e) | HidError::ClaimFailed(e) | HidError::UsbError(e) if is_busy_error(e) => {
+                HidErrorCode::DeviceBusy
+            }
+            HidError::ClaimFailed(e) if is_driver_error(e) => HidErrorCode::DriverMissing,
+            HidError::OpenFailed(_) => HidErrorCode::OpenFailed,
+            HidError::ClaimFailed(_) => HidErrorCode::ClaimFailed,
+            HidError::WriteFailed(_) => HidErrorCode::WriteFailed,
+            HidError::ReadFailed(_) => HidErrorCode::ReadFailed,
+            HidError::InvalidData(_) => HidErrorCode::InvalidData,
+            HidError::ConnectionLost => HidErrorCode::ConnectionLost,
+            HidError::Timeout => HidErrorCode::Timeout,
+            HidError::UsbError(_) => HidErrorCode::UsbError,
+        }
+    }
+
+    /// The error with a hint on how to fix it, for showing to the user
+    pub fn user_message(&self) -> String {
+        self.user_message_for(std::env::consts::OS)
+    }
+
+    /// `user_message` with the hints for the given OS (`std::env::consts::OS`)
+    fn user_message_for(&self, os: &str) -> String {
+        let hint = match (self.code(), os) {
+            (HidErrorCode::DeviceNotFound, _) => {
+                "Check that the device is plugged in, or try another USB port or cable."
+            }
+            (HidErrorCode::NotConnected, _) => "Connect to the device first.",
+            (HidErrorCode::NotInitialized, _) => "Disconnect and connect to the device again.",
+            (HidErrorCode::AccessDenied, "linux") => {
+                "Your user isn't allowed to open the device. Add a udev rule such as \
+                 SUBSYSTEM==\"usb\", ATTR{idVendor}==\"1500\", MODE=\"0666\" \
+                 to /etc/udev/rules.d/99-soomfon.rules, run \
+                 `sudo udevadm control --reload-rules`, then unplug and replug the device."
+            }
+            (HidErrorCode::AccessDenied, "windows") => {
+                "Close any other app using the device, such as the SOOMFON software. If it \
+                 still fails, install the WinUSB driver for the device with Zadig."
+            }
+            (HidErrorCode::AccessDenied, _) => "Close any other app using the device and try again.",
+            (HidErrorCode::DeviceBusy, _) => {
+                "Another app is using the device. Close it, or unplug and replug the device."
+            }
+            (HidErrorCode::DriverMissing | HidErrorCode::ClaimFailed, "windows") => {
+                "Windows needs the WinUSB driver for the device's control interface. Install \
+                 it with Zadig (select the SOOMFON device, interface 0, and WinUSB), then \
+                 unplug and replug the device."
+            }
+            (HidErrorCode::DriverMissing | HidErrorCode::ClaimFailed, "linux") => {
+                "Another program or kernel driver may be holding the device. Close other \
+                 SOOMFON apps, then unplug and replug the device."
+            }
+            (HidErrorCode::ConnectionLost, _) => "Unplug and replug the device, then connect again.",
+            (HidErrorCode::Timeout, _) => {
+                "The device didn't answer in time. If this keeps happening, unplug and replug it."
+            }
+            (HidErrorCode::InvalidData, _) => return self.to_string(),
+            _ => "Unplug and replug the device. If the problem persists, try another USB port.",
+        };
+        format!("{}. {}", self, hint)
+    }
+}
+
+/// libusb message for `LIBUSB_ERROR_ACCESS`
+fn is_access_error(message: &str) -> bool {
+    message.contains("Access denied") || message.contains("insufficient permissions")
+}
+
+/// libusb message for `LIBUSB_ERROR_BUSY`
+fn is_busy_error(message: &str) -> bool {
+    message.contains("busy")
+}
+
+/// libusb message when no usable driver is bound (WinUSB missing on Windows)
+fn is_driver_error(message: &str) -> bool {
+    message.contains("not supported") || message.contains("Entity not found")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // ========== Capabilities Tests ==========
+
+    #[test]
+    fn test_capabilities_default_matches_constants() {
+        let caps = DeviceCapabilities::for_product(None);
+        assert_eq!(caps.lcd_button_count, 6);
+        assert_eq!(caps.physical_button_count, 3);
+        assert_eq!(caps.encoder_count, 3);
+        assert_eq!((caps.lcd_width, caps.lcd_height), (LCD_WIDTH, LCD_HEIGHT));
+    }
+
+    #[test]
+    fn test_capabilities_unknown_product_falls_back() {
+        assert_eq!(
+            DeviceCapabilities::for_product(Some("Mystery Pad 9000")),
+            DeviceCapabilities::default()
+        );
+    }
+
+    #[test]
+    fn test_capabilities_serializes_camel_case() {
+        let json = serde_json::to_string(&DeviceCapabilities::default()).unwrap();
+        assert!(json.contains("\"lcdButtonCount\":6"));
+        assert!(json.contains("\"lcdWidth\":60"));
+    }
+
     #[test]
     fn test_soomfon_vid_is_correct() {
         assert_eq!(SOOMFON_VID, 0x1500);
@@ -513,9 +767,11 @@ mod tests {
             DeviceEvent::Encoder {
                 encoder_type,
                 event_type,
+                velocity,
             } => {
                 assert_eq!(encoder_type, EncoderType::Main);
                 assert_eq!(event_type, EncoderEventType::RotateCW);
+                assert_eq!(velocity, 1);
             }
             _ => panic!("Expected Encoder event"),
         }
@@ -524,10 +780,16 @@ mod tests {
     #[test]
     fn test_raw_event_parse_unknown() {
         let raw = RawEvent {
-            event_id: 0xFF,
-            state: 0x00,
+            event_id: 0xAB,
+            state: 0x01,
         };
-        assert!(raw.parse().is_none());
+        match raw.parse() {
+            Some(DeviceEvent::Unknown { event_id, state }) => {
+                assert_eq!(event_id, 0xAB);
+                assert_eq!(state, 0x01);
+            }
+            other => panic!("Expected Unknown event, got {:?}", other),
+        }
     }
 
     #[test]
@@ -540,4 +802,40 @@ mod tests {
         let err: HidError = rusb::Error::NotFound.into();
         assert!(matches!(err, HidError::DeviceNotFound));
     }
+
+    // ========== Error Message Tests ==========
+
+    #[test]
+    fn test_hid_error_codes() {
+        let access: HidError = rusb::Error::Access.into();
+        assert_eq!(access.code(), HidErrorCode::AccessDenied);
+        assert_eq!(HidError::ClaimFailed(rusb::Error::Busy.to_string()).code(), HidErrorCode::DeviceBusy);
+        assert_eq!(
+            HidError::ClaimFailed(rusb::Error::NotSupported.to_string()).code(),
+            HidErrorCode::DriverMissing
+        );
+        assert_eq!(HidError::ClaimFailed("Pipe error".to_string()).code(), HidErrorCode::ClaimFailed);
+        assert_eq!(HidError::ConnectionLost.code(), HidErrorCode::ConnectionLost);
+        assert_eq!(serde_json::to_string(&HidErrorCode::AccessDenied).unwrap(), "\"accessDenied\"");
+    }
+
+    #[test]
+    fn test_user_message_platform_hints() {
+        let access: HidError = rusb::Error::Access.into();
+        let linux = access.user_message_for("linux");
+        assert!(linux.starts_with("Failed to open device: Access denied. "));
+        assert!(linux.contains("udev"));
+
+        let claim = HidError::ClaimFailed(rusb::Error::NotSupported.to_string());
+        assert!(claim.user_message_for("windows").contains("Zadig"));
+        assert!(!claim.user_message_for("linux").contains("Zadig"));
+    }
+
+    #[test]
+    fn test_user_message_replug_hint() {
+        let message = HidError::ConnectionLost.user_message_for("macos");
+        assert_eq!(message, "Connection lost. Unplug and replug the device, then connect again.");
+        let invalid = HidError::InvalidData("bad image".to_string());
+        assert_eq!(invalid.user_message_for("linux"), "Invalid data: bad image");
+    }
 }
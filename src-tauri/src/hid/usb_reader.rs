@@ -0,0 +1,306 @@
+//! USB Event Reader
+//!
+//! Reads packets from the device's interrupt IN endpoint for the polling
+//! thread. The async reader keeps one interrupt transfer submitted and sleeps
+//! in libusb's event handling until it completes, so an idle device costs
+//! about one wake-up per `ASYNC_IDLE_WAIT`. The sync reader calls
+//! `read_interrupt` with a short timeout and wakes on every timeout; it is
+//! kept for platforms where async transfers misbehave, and is used
+//! automatically when the async transfer can't be submitted.
+//!
+//! Either way a reader waiting for data can be woken early with
+//! `UsbContext::interrupt_handle_events` on the handle's context.
+
+use libusb1_sys as ffi;
+use libusb1_sys::constants::*;
+use rusb::{Context, DeviceHandle, UsbContext};
+use serde::{Deserialize, Serialize};
+use std::os::raw::{c_int, c_void};
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::time::Duration;
+
+/// Longest the async reader sleeps without a packet before returning
+/// `Timeout`, as a backstop for stop requests that don't wake it
+pub const ASYNC_IDLE_WAIT: Duration = Duration::from_secs(1);
+
+/// Rounds of event handling to wait for a cancelled transfer on drop
+const CANCEL_WAIT_ROUNDS: u32 = 10;
+
+/// How the polling thread reads the interrupt endpoint
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsbReadMode {
+    /// Keep an async transfer pending (falls back to `Sync` if it can't be submitted)
+    #[default]
+    Async,
+    /// Blocking `read_interrupt` calls with a timeout
+    Sync,
+}
+
+/// Reader for the interrupt endpoint of a claimed device handle
+pub enum UsbReader<'h> {
+    Async(AsyncReader<'h>),
+    Sync {
+        handle: &'h DeviceHandle<Context>,
+        endpoint: u8,
+        timeout: Duration,
+    },
+}
+
+impl<'h> UsbReader<'h> {
+    /// Open a reader in the requested mode
+    ///
+    /// `packet_size` bounds a single async read; `sync_timeout` is the
+    /// timeout of each sync read.
+    pub fn open(
+        handle: &'h DeviceHandle<Context>,
+        endpoint: u8,
+        mode: UsbReadMode,
+        packet_size: usize,
+        sync_timeout: Duration,
+    ) -> Self {
+        if mode == UsbReadMode::Async {
+            match AsyncReader::new(handle, endpoint, packet_size) {
+                Ok(reader) => return UsbReader::Async(reader),
+                Err(e) => log::warn!("Async USB reads unavailable ({}), falling back to sync reads", e),
+            }
+        }
+        UsbReader::Sync {
+            handle,
+            endpoint,
+            timeout: sync_timeout,
+        }
+    }
+
+    /// The mode actually in use
+    pub fn mode(&self) -> UsbReadMode {
+        match self {
+            UsbReader::Async(_) => UsbReadMode::Async,
+            UsbReader::Sync { .. } => UsbReadMode::Sync,
+        }
+    }
+
+    /// Wait for the next packet and copy it into `buf`
+    ///
+    /// Returns `Err(Timeout)` when the wait ends without a packet, including
+    /// when the reader is woken early.
+    pub fn read(&mut self, buf: &mut [u8]) -> rusb::Result<usize> {
+        match self {
+            UsbReader::Async(reader) => reader.read(buf, ASYNC_IDLE_WAIT),
+            UsbReader::Sync { handle, endpoint, timeout } => handle.read_interrupt(*endpoint, buf, *timeout),
+        }
+    }
+}
+
+/// Interrupt IN reader backed by a single resubmitted libusb transfer
+pub struct AsyncReader<'h> {
+    handle: &'h DeviceHandle<Context>,
+    transfer: NonNull<ffi::libusb_transfer>,
+    /// Filled by libusb while the transfer is pending
+    buffer: Box<[u8]>,
+    /// Set to 1 by the completion callback, in whichever thread handles the event
+    completed: Box<AtomicI32>,
+    pending: bool,
+}
+
+impl<'h> AsyncReader<'h> {
+    /// Allocate a transfer for `endpoint` and submit it
+    pub fn new(handle: &'h DeviceHandle<Context>, endpoint: u8, packet_size: usize) -> rusb::Result<Self> {
+        // SAFETY: a null return is handled; the transfer is freed in Drop
+        let transfer = NonNull::new(unsafe { ffi::libusb_alloc_transfer(0) }).ok_or(rusb::Error::NoMem)?;
+        let mut reader = Self {
+            handle,
+            transfer,
+            buffer: vec![0u8; packet_size].into_boxed_slice(),
+            completed: Box::new(AtomicI32::new(0)),
+            pending: false,
+        };
+
+        // SAFETY: the buffer and flag are heap allocations owned by the
+        // reader, which keeps them until the transfer is no longer pending
+        unsafe {
+            ffi::libusb_fill_interrupt_transfer(
+                transfer.as_ptr(),
+                handle.as_raw(),
+                endpoint,
+                reader.buffer.as_mut_ptr(),
+                packet_size as c_int,
+                transfer_done,
+                reader.completed.as_ptr() as *mut c_void,
+                // No timeout: the transfer stays pending until data arrives
+                0,
+            );
+        }
+        reader.submit()?;
+        Ok(reader)
+    }
+
+    fn submit(&mut self) -> rusb::Result<()> {
+        self.completed.store(0, Ordering::Release);
+        // SAFETY: the transfer was filled in `new` and is not pending
+        match unsafe { ffi::libusb_submit_transfer(self.transfer.as_ptr()) } {
+            0 => {
+                self.pending = true;
+                Ok(())
+            }
+            code => Err(error_from_code(code)),
+        }
+    }
+
+    /// Wait up to `wait` for the pending transfer, then resubmit it
+    pub fn read(&mut self, buf: &mut [u8], wait: Duration) -> rusb::Result<usize> {
+        if !self.pending {
+            self.submit()?;
+        }
+
+        if self.completed.load(Ordering::Acquire) == 0 {
+            let tv = libc::timeval {
+                tv_sec: wait.as_secs() as _,
+                tv_usec: wait.subsec_micros() as _,
+            };
+            // SAFETY: the context outlives the handle borrowed by the reader,
+            // and `completed` is the flag the callback sets
+            let code = unsafe {
+                ffi::libusb_handle_events_timeout_completed(
+                    self.handle.context().as_raw(),
+                    &tv,
+                    self.completed.as_ptr(),
+                )
+            };
+            // Interrupted means `interrupt_handle_events` woke us
+            if code < 0 && code != LIBUSB_ERROR_INTERRUPTED {
+                return Err(error_from_code(code));
+            }
+            if self.completed.load(Ordering::Acquire) == 0 {
+                return Err(rusb::Error::Timeout);
+            }
+        }
+
+        self.pending = false;
+        // SAFETY: the transfer has completed, so libusb no longer writes to it
+        let (status, length) = unsafe {
+            let transfer = self.transfer.as_ref();
+            (transfer.status, transfer.actual_length.max(0) as usize)
+        };
+        let result = if status == LIBUSB_TRANSFER_COMPLETED {
+            let n = length.min(buf.len()).min(self.buffer.len());
+            buf[..n].copy_from_slice(&self.buffer[..n]);
+            Ok(n)
+        } else {
+            Err(error_from_status(status))
+        };
+
+        if !matches!(result, Err(rusb::Error::NoDevice)) {
+            // A failed resubmit is retried, and reported, by the next read
+            let _ = self.submit();
+        }
+        result
+    }
+}
+
+impl Drop for AsyncReader<'_> {
+    fn drop(&mut self) {
+        if self.pending {
+            // SAFETY: the transfer is pending, so it may be cancelled
+            unsafe { ffi::libusb_cancel_transfer(self.transfer.as_ptr()) };
+
+            // libusb owns the transfer until its callback has run
+            let ctx = self.handle.context().as_raw();
+            let tv = libc::timeval { tv_sec: 0, tv_usec: 100_000 };
+            for _ in 0..CANCEL_WAIT_ROUNDS {
+                if self.completed.load(Ordering::Acquire) != 0 {
+                    break;
+                }
+                // SAFETY: as in `read`
+                unsafe { ffi::libusb_handle_events_timeout_completed(ctx, &tv, self.completed.as_ptr()) };
+            }
+
+            if self.completed.load(Ordering::Acquire) == 0 {
+                // Leaking beats freeing memory libusb may still write to
+                log::warn!("USB transfer did not finish cancelling; leaking it");
+                std::mem::forget(std::mem::take(&mut self.buffer));
+                std::mem::forget(std::mem::replace(&mut self.completed, Box::new(AtomicI32::new(0))));
+                return;
+            }
+        }
+        // SAFETY: the transfer is not pending and is freed only here
+        unsafe { ffi::libusb_free_transfer(self.transfer.as_ptr()) };
+    }
+}
+
+/// Completion callback; marks the reader's flag
+extern "system" fn transfer_done(transfer: *mut ffi::libusb_transfer) {
+    // SAFETY: `user_data` is the reader's `completed` flag, which outlives
+    // the pending transfer
+    unsafe {
+        let completed = &*((*transfer).user_data as *const AtomicI32);
+        completed.store(1, Ordering::Release);
+    }
+}
+
+/// Map a finished transfer's status to the matching error
+fn error_from_status(status: c_int) -> rusb::Error {
+    match status {
+        LIBUSB_TRANSFER_TIMED_OUT => rusb::Error::Timeout,
+        LIBUSB_TRANSFER_CANCELLED => rusb::Error::Interrupted,
+        LIBUSB_TRANSFER_STALL => rusb::Error::Pipe,
+        LIBUSB_TRANSFER_NO_DEVICE => rusb::Error::NoDevice,
+        LIBUSB_TRANSFER_OVERFLOW => rusb::Error::Overflow,
+        _ => rusb::Error::Io,
+    }
+}
+
+/// Map a libusb error code to the matching error
+fn error_from_code(code: c_int) -> rusb::Error {
+    match code {
+        LIBUSB_ERROR_IO => rusb::Error::Io,
+        LIBUSB_ERROR_INVALID_PARAM => rusb::Error::InvalidParam,
+        LIBUSB_ERROR_ACCESS => rusb::Error::Access,
+        LIBUSB_ERROR_NO_DEVICE => rusb::Error::NoDevice,
+        LIBUSB_ERROR_NOT_FOUND => rusb::Error::NotFound,
+        LIBUSB_ERROR_BUSY => rusb::Error::Busy,
+        LIBUSB_ERROR_TIMEOUT => rusb::Error::Timeout,
+        LIBUSB_ERROR_OVERFLOW => rusb::Error::Overflow,
+        LIBUSB_ERROR_PIPE => rusb::Error::Pipe,
+        LIBUSB_ERROR_INTERRUPTED => rusb::Error::Interrupted,
+        LIBUSB_ERROR_NO_MEM => rusb::Error::NoMem,
+        LIBUSB_ERROR_NOT_SUPPORTED => rusb::Error::NotSupported,
+        _ => rusb::Error::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ========== Mode Tests ==========
+
+    #[test]
+    fn test_async_is_default_mode() {
+        assert_eq!(UsbReadMode::default(), UsbReadMode::Async);
+    }
+
+    #[test]
+    fn test_mode_serializes_lowercase() {
+        assert_eq!(serde_json::to_string(&UsbReadMode::Sync).unwrap(), "\"sync\"");
+        let mode: UsbReadMode = serde_json::from_str("\"async\"").unwrap();
+        assert_eq!(mode, UsbReadMode::Async);
+    }
+
+    // ========== Error Mapping Tests ==========
+
+    #[test]
+    fn test_unplug_maps_to_no_device() {
+        assert_eq!(error_from_status(LIBUSB_TRANSFER_NO_DEVICE), rusb::Error::NoDevice);
+        assert_eq!(error_from_code(LIBUSB_ERROR_NO_DEVICE), rusb::Error::NoDevice);
+    }
+
+    #[test]
+    fn test_transfer_status_mapping() {
+        assert_eq!(error_from_status(LIBUSB_TRANSFER_TIMED_OUT), rusb::Error::Timeout);
+        assert_eq!(error_from_status(LIBUSB_TRANSFER_CANCELLED), rusb::Error::Interrupted);
+        assert_eq!(error_from_status(LIBUSB_TRANSFER_STALL), rusb::Error::Pipe);
+        assert_eq!(error_from_status(LIBUSB_TRANSFER_ERROR), rusb::Error::Io);
+    }
+}
@@ -3,5 +3,7 @@
 //! Handles image processing for LCD button displays.
 
 pub mod processor;
+pub mod text;
 
 pub use processor::*;
+pub use text::TextStyle;
@@ -4,9 +4,13 @@
 //!
 //! Based on mirajazz library reverse engineering:
 //! - Device expects JPEG images at 60x60 pixels
+//! - Some older firmware variants take raw RGB565 instead of JPEG
 //! - Protocol v2/v3 devices use 1024-byte packet size
 
-use image::{DynamicImage, GenericImageView, ImageBuffer, Rgb, RgbImage};
+use super::text::{draw_fitted_text, draw_wrapped_text, line_height, render_text, TextStyle};
+use image::codecs::gif::GifDecoder;
+use image::{AnimationDecoder, DynamicImage, GenericImageView, ImageBuffer, Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
 use std::io::Cursor;
 
 /// LCD button width in pixels (from mirajazz - device expects 60x60)
@@ -16,28 +20,253 @@ pub const LCD_HEIGHT: u32 = 60;
 /// JPEG quality for encoding (90% as per mirajazz)
 pub const JPEG_QUALITY: u8 = 90;
 
+/// Pixel encoding sent to the device
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImageFormat {
+    /// Baseline JPEG (current firmware)
+    #[default]
+    Jpeg,
+    /// Raw 16-bit RGB565, big-endian, row-major (older firmware)
+    Rgb565,
+}
+
 /// Image processing options
 #[derive(Debug, Clone, Default)]
 pub struct ImageOptions {
+    /// Output encoding
+    pub format: ImageFormat,
     /// Maintain aspect ratio when resizing
     pub preserve_aspect_ratio: bool,
     /// Background color for letterboxing (RGB)
     pub background_color: Option<(u8, u8, u8)>,
+    /// Badge composited onto the resized image before encoding
+    pub badge: Option<BadgeSpec>,
+}
+
+/// Corner of the button a badge is drawn in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BadgePosition {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Small badge (circle with optional short text) drawn over a button image
+///
+/// Text is drawn with the label font, shrunk to fit inside the circle, so
+/// it should stay short (a count or a word like "NEW").
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BadgeSpec {
+    /// Text shown in the badge (e.g. "3", "9+"); a plain dot if empty
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Corner to draw the badge in
+    #[serde(default)]
+    pub position: BadgePosition,
+    /// Badge fill color (`#RRGGBB`, `#RGB` or CSS name), default red
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Text color, default white
+    #[serde(default)]
+    pub text_color: Option<String>,
+    /// Circle radius in pixels, default 9
+    #[serde(default)]
+    pub radius: Option<u32>,
+}
+
+/// Default badge radius in pixels
+const DEFAULT_BADGE_RADIUS: u32 = 9;
+
+/// Edge of the button a label band is drawn along
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LabelPosition {
+    Top,
+    #[default]
+    Bottom,
+}
+
+/// Caption drawn in a band above or below an icon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LabelOptions {
+    /// Edge the band is drawn along
+    pub position: LabelPosition,
+    /// Font size in pixels
+    pub font_size: f32,
+    /// Text color (`#RRGGBB`, `#RGB` or CSS name)
+    pub color: String,
+    /// Band color (`#RRGGBB`, `#RGB` or CSS name)
+    pub background: String,
+    /// Opacity of the band color over the black button background (0-1)
+    pub background_opacity: f32,
+}
+
+impl Default for LabelOptions {
+    fn default() -> Self {
+        Self {
+            position: LabelPosition::Bottom,
+            font_size: 11.0,
+            color: "#FFFFFF".to_string(),
+            background: "#000000".to_string(),
+            background_opacity: 1.0,
+        }
+    }
 }
 
+/// Space kept above and below label text, in pixels
+const LABEL_PADDING: f32 = 2.0;
+
+/// Delay used for GIF frames that don't specify one
+const DEFAULT_FRAME_DELAY_MS: u32 = 100;
+
+/// Most frames kept from an animated image
+const MAX_ANIMATION_FRAMES: usize = 300;
+
 /// Process an image for LCD display
 ///
-/// Resizes to 60x60 and encodes as JPEG (device protocol requirement).
+/// Resizes to 60x60, applies any badge and encodes in `options.format`
+/// (JPEG unless the firmware needs raw RGB565).
 pub fn process_image(image_data: &[u8], options: &ImageOptions) -> Result<Vec<u8>, String> {
     let img = image::load_from_memory(image_data)
         .map_err(|e| format!("Failed to load image: {}", e))?;
 
-    let resized = resize_image(&img, options);
-    convert_to_jpeg(&resized)
+    let mut resized = resize_image(&img, options);
+    if let Some(ref badge) = options.badge {
+        overlay_badge(&mut resized, badge)?;
+    }
+    match options.format {
+        ImageFormat::Jpeg => convert_to_jpeg(&resized),
+        ImageFormat::Rgb565 => Ok(convert_to_rgb565(&resized)),
+    }
+}
+
+/// Process an icon with a caption for LCD display, as JPEG
+///
+/// The icon keeps its aspect ratio and is fitted into the part of the button
+/// not taken by the label band. The band is one line tall (at most half the
+/// button); longer labels are cut off with an ellipsis.
+pub fn process_image_with_label(image_data: &[u8], label: &str, options: &LabelOptions) -> Result<Vec<u8>, String> {
+    let img = image::load_from_memory(image_data)
+        .map_err(|e| format!("Failed to load image: {}", e))?;
+    let band_rgb = parse_color(&options.background)?;
+
+    let band_height = ((line_height(options.font_size)? + 2.0 * LABEL_PADDING).ceil() as u32).min(LCD_HEIGHT / 2);
+    let icon_height = LCD_HEIGHT - band_height;
+    let (icon_top, band_top) = match options.position {
+        LabelPosition::Top => (band_height, 0),
+        LabelPosition::Bottom => (0, icon_height),
+    };
+
+    let icon_options = ImageOptions {
+        preserve_aspect_ratio: true,
+        ..Default::default()
+    };
+    let icon = resize_image_to(&img, &icon_options, LCD_WIDTH, icon_height);
+    let mut output: RgbImage = ImageBuffer::from_pixel(LCD_WIDTH, LCD_HEIGHT, Rgb([0, 0, 0]));
+    image::imageops::replace(&mut output, &icon, 0, icon_top as i64);
+
+    let opacity = options.background_opacity.clamp(0.0, 1.0);
+    let band = Rgb([band_rgb.0, band_rgb.1, band_rgb.2].map(|c| (c as f32 * opacity).round() as u8));
+    for y in band_top..band_top + band_height {
+        for x in 0..LCD_WIDTH {
+            output.put_pixel(x, y, band);
+        }
+    }
+    draw_wrapped_text(&mut output, label, options.font_size, &options.color, band_top, band_height)?;
+
+    convert_to_jpeg(&output)
+}
+
+/// Process an animated GIF into per-frame JPEGs with their delays (ms)
+///
+/// Frames are composited by the decoder, so each one is a full picture.
+/// Frames without a delay get `DEFAULT_FRAME_DELAY_MS`, as in browsers, and
+/// only the first `MAX_ANIMATION_FRAMES` are kept. Anything that isn't a GIF
+/// comes back as a single frame.
+pub fn process_animated(image_data: &[u8]) -> Result<Vec<(Vec<u8>, u32)>, String> {
+    let options = ImageOptions::default();
+    if image::guess_format(image_data).ok() != Some(image::ImageFormat::Gif) {
+        return Ok(vec![(process_image(image_data, &options)?, 0)]);
+    }
+
+    let decoder = GifDecoder::new(Cursor::new(image_data))
+        .map_err(|e| format!("Failed to load GIF: {}", e))?;
+    let mut frames = Vec::new();
+    for frame in decoder.into_frames().take(MAX_ANIMATION_FRAMES) {
+        let frame = frame.map_err(|e| format!("Failed to decode GIF frame: {}", e))?;
+        let (numer, denom) = frame.delay().numer_denom_ms();
+        let delay = match numer.checked_div(denom) {
+            Some(0) | None => DEFAULT_FRAME_DELAY_MS,
+            Some(ms) => ms,
+        };
+        let resized = resize_image(&DynamicImage::ImageRgba8(frame.into_buffer()), &options);
+        frames.push((convert_to_jpeg(&resized)?, delay));
+    }
+    if frames.is_empty() {
+        return Err("GIF has no frames".to_string());
+    }
+    Ok(frames)
+}
+
+/// Composite a badge onto an image in place
+pub fn overlay_badge(base_image: &mut RgbImage, badge: &BadgeSpec) -> Result<(), String> {
+    let fill = match badge.color {
+        Some(ref c) => parse_color(c)?,
+        None => (255, 0, 0),
+    };
+    let text_rgb = match badge.text_color {
+        Some(ref c) => parse_color(c)?,
+        None => (255, 255, 255),
+    };
+
+    let (width, height) = base_image.dimensions();
+    let max_radius = width.min(height) / 2;
+    if max_radius == 0 {
+        // No room for a badge on a 1px image
+        return Ok(());
+    }
+    let radius = badge.radius.unwrap_or(DEFAULT_BADGE_RADIUS).max(2).min(max_radius);
+    let margin = 1;
+    let diameter = radius * 2;
+
+    let cx = match badge.position {
+        BadgePosition::TopLeft | BadgePosition::BottomLeft => margin + radius,
+        BadgePosition::TopRight | BadgePosition::BottomRight => width - margin - radius,
+    } as i64;
+    let cy = match badge.position {
+        BadgePosition::TopLeft | BadgePosition::TopRight => margin + radius,
+        BadgePosition::BottomLeft | BadgePosition::BottomRight => height - margin - radius,
+    } as i64;
+
+    // Filled circle
+    let r = radius as i64;
+    for y in (cy - r).max(0)..(cy + r + 1).min(height as i64) {
+        for x in (cx - r).max(0)..(cx + r + 1).min(width as i64) {
+            let (dx, dy) = (x - cx, y - cy);
+            if dx * dx + dy * dy <= r * r {
+                base_image.put_pixel(x as u32, y as u32, Rgb([fill.0, fill.1, fill.2]));
+            }
+        }
+    }
+
+    // Centered text, scaled to fit inside the circle
+    let inner = diameter * 3 / 4;
+    draw_fitted_text(base_image, badge.text.as_deref().unwrap_or(""), (cx, cy), inner, text_rgb)
 }
 
 /// Process a base64-encoded image
 pub fn process_base64_image(base64_data: &str, options: &ImageOptions) -> Result<Vec<u8>, String> {
+    process_image(&decode_base64_image(base64_data)?, options)
+}
+
+/// Decode base64 image data, with or without a data URL prefix
+fn decode_base64_image(base64_data: &str) -> Result<Vec<u8>, String> {
     // Strip data URL prefix if present
     let data = if base64_data.contains(',') {
         base64_data.split(',').next_back().unwrap_or(base64_data)
@@ -45,18 +274,18 @@ pub fn process_base64_image(base64_data: &str, options: &ImageOptions) -> Result
         base64_data
     };
 
-    let decoded = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, data)
-        .map_err(|e| format!("Failed to decode base64: {}", e))?;
-
-    process_image(&decoded, options)
+    base64::Engine::decode(&base64::engine::general_purpose::STANDARD, data)
+        .map_err(|e| format!("Failed to decode base64: {}", e))
 }
 
 /// Process an image from a file path
 pub fn process_file_image(file_path: &str, options: &ImageOptions) -> Result<Vec<u8>, String> {
-    let image_data = std::fs::read(file_path)
-        .map_err(|e| format!("Failed to read image file '{}': {}", file_path, e))?;
+    process_image(&read_image_file(file_path)?, options)
+}
 
-    process_image(&image_data, options)
+fn read_image_file(file_path: &str) -> Result<Vec<u8>, String> {
+    std::fs::read(file_path)
+        .map_err(|e| format!("Failed to read image file '{}': {}", file_path, e))
 }
 
 /// Process image data from various sources:
@@ -64,33 +293,36 @@ pub fn process_file_image(file_path: &str, options: &ImageOptions) -> Result<Vec
 /// - Base64-encoded data (with or without data URL prefix)
 /// - HTTP/HTTPS URLs (not supported yet)
 pub fn process_image_source(source: &str, options: &ImageOptions) -> Result<Vec<u8>, String> {
+    process_image(&read_image_source(source)?, options)
+}
+
+/// Original (unprocessed) bytes of an image source, in any format accepted
+/// by `process_image_source`
+pub fn read_image_source(source: &str) -> Result<Vec<u8>, String> {
     // Handle file:// URLs
     if source.starts_with("file://") {
         // Strip file:// prefix and handle platform differences
         let path = if cfg!(windows) {
             // Windows: file:///C:/path/to/file -> C:/path/to/file
-            source.strip_prefix("file:///").unwrap_or(&source[7..])
+            source
+                .strip_prefix("file:///")
+                .unwrap_or_else(|| &source["file://".len()..])
         } else {
             // Unix: file:///path/to/file -> /path/to/file
             source.strip_prefix("file://").unwrap_or(source)
         };
         // URL decode the path (handles %20 for spaces, etc.)
         let decoded_path = urlencoding_decode(path);
-        return process_file_image(&decoded_path, options);
+        return read_image_file(&decoded_path);
     }
 
     // Handle absolute file paths (Windows: C:\... or D:\..., Unix: /...)
     if is_absolute_path(source) {
-        return process_file_image(source, options);
-    }
-
-    // Handle data URLs (data:image/png;base64,...)
-    if source.starts_with("data:") {
-        return process_base64_image(source, options);
+        return read_image_file(source);
     }
 
-    // Assume base64 if none of the above
-    process_base64_image(source, options)
+    // Data URLs (data:image/png;base64,...) and bare base64
+    decode_base64_image(source)
 }
 
 /// Simple URL decoding for file paths
@@ -145,36 +377,138 @@ pub fn create_solid_color(r: u8, g: u8, b: u8) -> Result<Vec<u8>, String> {
     convert_to_jpeg(&img)
 }
 
+/// Create a bright image showing a number, as JPEG
+///
+/// Used to point out a button on the device (dark digits on yellow).
+pub fn create_number_image(number: u32) -> Result<Vec<u8>, String> {
+    let mut img: RgbImage = ImageBuffer::from_pixel(LCD_WIDTH, LCD_HEIGHT, Rgb([255, 214, 0]));
+    let center = ((LCD_WIDTH / 2) as i64, (LCD_HEIGHT / 2) as i64);
+    draw_fitted_text(&mut img, &number.to_string(), center, LCD_WIDTH * 2 / 3, (0, 0, 0))?;
+    convert_to_jpeg(&img)
+}
+
+/// Create an image of wrapped, centered text as JPEG
+pub fn create_text_image(text: &str, style: &TextStyle) -> Result<Vec<u8>, String> {
+    convert_to_jpeg(&render_text(text, style)?)
+}
+
+/// Create a solid color image as JPEG from a color string
+///
+/// Accepts `#RGB`, `#RRGGBB` (the `#` is optional) or a CSS color name.
+pub fn create_solid_color_hex(color: &str) -> Result<Vec<u8>, String> {
+    let (r, g, b) = parse_color(color)?;
+    create_solid_color(r, g, b)
+}
+
+/// Create the inverted version of a button image used for press feedback
+pub fn create_feedback_image(jpeg_data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut img = image::load_from_memory(jpeg_data)
+        .map_err(|e| format!("Failed to decode image: {}", e))?
+        .to_rgb8();
+    image::imageops::invert(&mut img);
+    convert_to_jpeg(&img)
+}
+
+/// Parse `#RGB`, `#RRGGBB` or a named CSS color into RGB components
+pub fn parse_color(color: &str) -> Result<(u8, u8, u8), String> {
+    let trimmed = color.trim();
+    if let Some(rgb) = named_color(&trimmed.to_ascii_lowercase()) {
+        return Ok(rgb);
+    }
+
+    let hex = trimmed.strip_prefix('#').unwrap_or(trimmed);
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid color: {}", color));
+    }
+
+    let channel = |s: &str| u8::from_str_radix(s, 16).map_err(|_| format!("Invalid color: {}", color));
+    match hex.len() {
+        // #RGB expands each digit (e.g. #F80 -> #FF8800)
+        3 => Ok((
+            channel(&hex[0..1].repeat(2))?,
+            channel(&hex[1..2].repeat(2))?,
+            channel(&hex[2..3].repeat(2))?,
+        )),
+        6 => Ok((channel(&hex[0..2])?, channel(&hex[2..4])?, channel(&hex[4..6])?)),
+        _ => Err(format!("Invalid color: {}", color)),
+    }
+}
+
+/// Look up a CSS color name (basic colors plus common extended names)
+fn named_color(name: &str) -> Option<(u8, u8, u8)> {
+    let rgb = match name {
+        "black" => (0, 0, 0),
+        "silver" => (192, 192, 192),
+        "gray" | "grey" => (128, 128, 128),
+        "white" => (255, 255, 255),
+        "maroon" => (128, 0, 0),
+        "red" => (255, 0, 0),
+        "purple" => (128, 0, 128),
+        "fuchsia" | "magenta" => (255, 0, 255),
+        "green" => (0, 128, 0),
+        "lime" => (0, 255, 0),
+        "olive" => (128, 128, 0),
+        "yellow" => (255, 255, 0),
+        "navy" => (0, 0, 128),
+        "blue" => (0, 0, 255),
+        "teal" => (0, 128, 128),
+        "aqua" | "cyan" => (0, 255, 255),
+        "orange" => (255, 165, 0),
+        "pink" => (255, 192, 203),
+        "brown" => (165, 42, 42),
+        "gold" => (255, 215, 0),
+        "indigo" => (75, 0, 130),
+        "violet" => (238, 130, 238),
+        "darkgray" | "darkgrey" => (169, 169, 169),
+        "lightgray" | "lightgrey" => (211, 211, 211),
+        "darkred" => (139, 0, 0),
+        "darkgreen" => (0, 100, 0),
+        "darkblue" => (0, 0, 139),
+        "skyblue" => (135, 206, 235),
+        "crimson" => (220, 20, 60),
+        "coral" => (255, 127, 80),
+        "tomato" => (255, 99, 71),
+        "turquoise" => (64, 224, 208),
+        _ => return None,
+    };
+    Some(rgb)
+}
+
 /// Resize image to LCD dimensions
 fn resize_image(img: &DynamicImage, options: &ImageOptions) -> RgbImage {
+    resize_image_to(img, options, LCD_WIDTH, LCD_HEIGHT)
+}
+
+/// Resize an image to `width` x `height`, letterboxing if aspect ratio is kept
+fn resize_image_to(img: &DynamicImage, options: &ImageOptions, width: u32, height: u32) -> RgbImage {
     if options.preserve_aspect_ratio {
-        // Calculate scaling to fit within LCD dimensions
+        // Calculate scaling to fit within the target dimensions
         let (orig_width, orig_height) = img.dimensions();
-        let scale = (LCD_WIDTH as f32 / orig_width as f32)
-            .min(LCD_HEIGHT as f32 / orig_height as f32);
+        let scale = (width as f32 / orig_width as f32)
+            .min(height as f32 / orig_height as f32);
 
-        let new_width = (orig_width as f32 * scale) as u32;
-        let new_height = (orig_height as f32 * scale) as u32;
+        let new_width = ((orig_width as f32 * scale) as u32).clamp(1, width);
+        let new_height = ((orig_height as f32 * scale) as u32).clamp(1, height);
 
         let resized = img.resize_exact(new_width, new_height, image::imageops::FilterType::Lanczos3);
 
         // Create output image with background color
         let bg = options.background_color.unwrap_or((0, 0, 0));
-        let mut output = ImageBuffer::from_pixel(LCD_WIDTH, LCD_HEIGHT, Rgb([bg.0, bg.1, bg.2]));
+        let mut output = ImageBuffer::from_pixel(width, height, Rgb([bg.0, bg.1, bg.2]));
 
         // Center the resized image
-        let x_offset = (LCD_WIDTH - new_width) / 2;
-        let y_offset = (LCD_HEIGHT - new_height) / 2;
+        let x_offset = (width - new_width) / 2;
+        let y_offset = (height - new_height) / 2;
 
         for (x, y, pixel) in resized.to_rgb8().enumerate_pixels() {
-            if x + x_offset < LCD_WIDTH && y + y_offset < LCD_HEIGHT {
+            if x + x_offset < width && y + y_offset < height {
                 output.put_pixel(x + x_offset, y + y_offset, *pixel);
             }
         }
 
         output
     } else {
-        img.resize_exact(LCD_WIDTH, LCD_HEIGHT, image::imageops::FilterType::Lanczos3)
+        img.resize_exact(width, height, image::imageops::FilterType::Lanczos3)
             .to_rgb8()
     }
 }
@@ -196,6 +530,19 @@ fn convert_to_jpeg(img: &RgbImage) -> Result<Vec<u8>, String> {
     Ok(buffer.into_inner())
 }
 
+/// Convert RGB image to raw RGB565 pixels
+///
+/// Each pixel is 16 bits (5 red, 6 green, 5 blue), written big-endian in
+/// row-major order, so a 60x60 button is 7200 bytes.
+pub fn convert_to_rgb565(img: &RgbImage) -> Vec<u8> {
+    img.pixels()
+        .flat_map(|Rgb([r, g, b])| {
+            let pixel = ((*r as u16 >> 3) << 11) | ((*g as u16 >> 2) << 5) | (*b as u16 >> 3);
+            pixel.to_be_bytes()
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -231,6 +578,228 @@ mod tests {
         assert!(data.len() < 10000);
     }
 
+    #[test]
+    fn test_create_feedback_image_inverts_colors() {
+        let original = create_solid_color(255, 0, 0).unwrap();
+        let feedback = create_feedback_image(&original).unwrap();
+
+        let img = image::load_from_memory(&feedback).unwrap().to_rgb8();
+        assert_eq!(img.dimensions(), (LCD_WIDTH, LCD_HEIGHT));
+        let Rgb([r, g, b]) = *img.get_pixel(LCD_WIDTH / 2, LCD_HEIGHT / 2);
+        // JPEG is lossy, so allow some slack around cyan
+        assert!(r < 20 && g > 235 && b > 235, "got ({}, {}, {})", r, g, b);
+    }
+
+    #[test]
+    fn test_create_feedback_image_rejects_garbage() {
+        assert!(create_feedback_image(&[0x00, 0x01, 0x02]).is_err());
+    }
+
+    #[test]
+    fn test_parse_color_six_digit_hex() {
+        assert_eq!(parse_color("#FF8000").unwrap(), (255, 128, 0));
+        assert_eq!(parse_color("00ff7f").unwrap(), (0, 255, 127));
+    }
+
+    #[test]
+    fn test_parse_color_three_digit_hex() {
+        assert_eq!(parse_color("#F80").unwrap(), (255, 136, 0));
+        assert_eq!(parse_color("#abc").unwrap(), (170, 187, 204));
+    }
+
+    #[test]
+    fn test_parse_color_named() {
+        assert_eq!(parse_color("red").unwrap(), (255, 0, 0));
+        assert_eq!(parse_color(" Orange ").unwrap(), (255, 165, 0));
+        assert_eq!(parse_color("grey").unwrap(), parse_color("gray").unwrap());
+    }
+
+    #[test]
+    fn test_parse_color_rejects_malformed() {
+        assert!(parse_color("").is_err());
+        assert!(parse_color("#12").is_err());
+        assert!(parse_color("#12345").is_err());
+        assert!(parse_color("#GGHHII").is_err());
+        assert!(parse_color("notacolor").is_err());
+    }
+
+    #[test]
+    fn test_create_solid_color_hex_is_jpeg() {
+        let data = create_solid_color_hex("#00FF00").unwrap();
+        assert_eq!(&data[0..3], &[0xFF, 0xD8, 0xFF]);
+        assert!(create_solid_color_hex("#xyz").is_err());
+    }
+
+    #[test]
+    fn test_create_number_image_draws_digits() {
+        let data = create_number_image(5).unwrap();
+        assert_eq!(&data[0..3], &[0xFF, 0xD8, 0xFF]);
+
+        let img = image::load_from_memory(&data).unwrap().to_rgb8();
+        // Corner keeps the background, the middle of the "5" is dark
+        assert!(img.get_pixel(2, 2)[0] > 200);
+        assert!(img.get_pixel(LCD_WIDTH / 2, LCD_HEIGHT / 2)[0] < 80);
+    }
+
+    // ========== Label Tests ==========
+
+    fn png(width: u32, height: u32, color: [u8; 3]) -> Vec<u8> {
+        let mut data = Vec::new();
+        RgbImage::from_pixel(width, height, Rgb(color))
+            .write_to(&mut Cursor::new(&mut data), image::ImageFormat::Png)
+            .unwrap();
+        data
+    }
+
+    #[test]
+    fn test_process_image_with_label_is_lcd_sized_jpeg() {
+        for (width, height) in [(60, 60), (200, 50), (30, 300), (1, 1)] {
+            let data = process_image_with_label(&png(width, height, [0, 200, 0]), "Mute", &LabelOptions::default()).unwrap();
+            assert_eq!(&data[0..3], &[0xFF, 0xD8, 0xFF]);
+            let img = image::load_from_memory(&data).unwrap();
+            assert_eq!(img.dimensions(), (LCD_WIDTH, LCD_HEIGHT));
+        }
+    }
+
+    #[test]
+    fn test_process_image_with_label_places_band() {
+        let options = LabelOptions {
+            background: "#0000FF".to_string(),
+            ..Default::default()
+        };
+        let bottom = image::load_from_memory(&process_image_with_label(&png(60, 60, [0, 200, 0]), "", &options).unwrap())
+            .unwrap()
+            .to_rgb8();
+        assert!(bottom.get_pixel(30, 10)[1] > 150);
+        assert!(bottom.get_pixel(30, LCD_HEIGHT - 2)[2] > 200);
+
+        let top_options = LabelOptions {
+            position: LabelPosition::Top,
+            ..options
+        };
+        let top = image::load_from_memory(&process_image_with_label(&png(60, 60, [0, 200, 0]), "", &top_options).unwrap())
+            .unwrap()
+            .to_rgb8();
+        assert!(top.get_pixel(30, 1)[2] > 200);
+        assert!(top.get_pixel(30, LCD_HEIGHT - 10)[1] > 150);
+    }
+
+    #[test]
+    fn test_process_animated_splits_gif_frames() {
+        let mut data = Vec::new();
+        {
+            let mut encoder = image::codecs::gif::GifEncoder::new(&mut data);
+            for (color, delay) in [([255, 0, 0, 255], 40), ([0, 0, 255, 255], 0)] {
+                let buffer = image::RgbaImage::from_pixel(10, 10, image::Rgba(color));
+                let delay = image::Delay::from_numer_denom_ms(delay, 1);
+                encoder.encode_frame(image::Frame::from_parts(buffer, 0, 0, delay)).unwrap();
+            }
+        }
+
+        let frames = process_animated(&data).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].1, 40);
+        assert_eq!(frames[1].1, DEFAULT_FRAME_DELAY_MS);
+        let second = image::load_from_memory(&frames[1].0).unwrap().to_rgb8();
+        assert_eq!(second.dimensions(), (LCD_WIDTH, LCD_HEIGHT));
+        assert!(second.get_pixel(30, 30)[2] > 200);
+    }
+
+    #[test]
+    fn test_process_animated_still_image_is_one_frame() {
+        let frames = process_animated(&png(20, 20, [255, 0, 0])).unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(&frames[0].0[0..3], &[0xFF, 0xD8, 0xFF]);
+    }
+
+    #[test]
+    fn test_process_image_with_label_rejects_garbage() {
+        assert!(process_image_with_label(b"not an image", "x", &LabelOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_overlay_badge_fills_requested_corner() {
+        let mut img: RgbImage = ImageBuffer::from_pixel(60, 60, Rgb([0, 0, 0]));
+        let badge = BadgeSpec {
+            position: BadgePosition::BottomLeft,
+            color: Some("#00FF00".to_string()),
+            radius: Some(8),
+            ..Default::default()
+        };
+
+        overlay_badge(&mut img, &badge).unwrap();
+
+        // Circle center is at (1 + 8, 60 - 1 - 8)
+        assert_eq!(img.get_pixel(9, 51), &Rgb([0, 255, 0]));
+        // Opposite corner untouched
+        assert_eq!(img.get_pixel(50, 9), &Rgb([0, 0, 0]));
+    }
+
+    #[test]
+    fn test_overlay_badge_draws_text() {
+        let mut img: RgbImage = ImageBuffer::from_pixel(60, 60, Rgb([0, 0, 0]));
+        let badge = BadgeSpec {
+            text: Some("8".to_string()),
+            ..Default::default()
+        };
+
+        overlay_badge(&mut img, &badge).unwrap();
+
+        let white = img.pixels().filter(|p| **p == Rgb([255, 255, 255])).count();
+        let red = img.pixels().filter(|p| **p == Rgb([255, 0, 0])).count();
+        assert!(white > 0);
+        assert!(red > white);
+    }
+
+    #[test]
+    fn test_overlay_badge_draws_letters() {
+        let mut img: RgbImage = ImageBuffer::from_pixel(60, 60, Rgb([0, 0, 0]));
+        let badge = BadgeSpec {
+            text: Some("NEW".to_string()),
+            radius: Some(12),
+            ..Default::default()
+        };
+
+        overlay_badge(&mut img, &badge).unwrap();
+
+        // Text pixels inside the circle centered at (60 - 1 - 12, 1 + 12)
+        let text = (35..59)
+            .flat_map(|x| (1..25).map(move |y| (x, y)))
+            .filter(|&(x, y)| img.get_pixel(x, y).0[1] > 128)
+            .count();
+        assert!(text > 10);
+    }
+
+    #[test]
+    fn test_overlay_badge_on_tiny_image() {
+        for size in 0..5 {
+            let mut img: RgbImage = ImageBuffer::from_pixel(size, size, Rgb([0, 0, 0]));
+            let badge = BadgeSpec {
+                text: Some("3".to_string()),
+                ..Default::default()
+            };
+            overlay_badge(&mut img, &badge).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_overlay_badge_rejects_bad_color() {
+        let mut img: RgbImage = ImageBuffer::from_pixel(60, 60, Rgb([0, 0, 0]));
+        let badge = BadgeSpec {
+            color: Some("#nope".to_string()),
+            ..Default::default()
+        };
+        assert!(overlay_badge(&mut img, &badge).is_err());
+    }
+
+    #[test]
+    fn test_badge_spec_deserializes_camel_case() {
+        let badge: BadgeSpec =
+            serde_json::from_str(r##"{"text":"3","position":"bottomRight","textColor":"#000"}"##).unwrap();
+        assert_eq!(badge.position, BadgePosition::BottomRight);
+        assert_eq!(badge.text_color, Some("#000".to_string()));
+    }
+
     #[test]
     fn test_convert_to_jpeg_valid() {
         let img: RgbImage = ImageBuffer::from_pixel(60, 60, Rgb([128, 128, 128]));
@@ -241,6 +810,33 @@ mod tests {
         assert_eq!(jpeg[2], 0xFF);
     }
 
+    #[test]
+    fn test_convert_to_rgb565_packs_big_endian() {
+        let mut img: RgbImage = ImageBuffer::from_pixel(2, 1, Rgb([255, 0, 0]));
+        img.put_pixel(1, 0, Rgb([0, 255, 255]));
+
+        // Red = 0xF800, cyan = 0x07FF
+        assert_eq!(convert_to_rgb565(&img), vec![0xF8, 0x00, 0x07, 0xFF]);
+    }
+
+    #[test]
+    fn test_process_image_rgb565_size() {
+        let png = {
+            let img: RgbImage = ImageBuffer::from_pixel(120, 80, Rgb([255, 255, 255]));
+            let mut buffer = Cursor::new(Vec::new());
+            img.write_to(&mut buffer, image::ImageFormat::Png).unwrap();
+            buffer.into_inner()
+        };
+        let options = ImageOptions {
+            format: ImageFormat::Rgb565,
+            ..Default::default()
+        };
+
+        let raw = process_image(&png, &options).unwrap();
+        assert_eq!(raw.len(), (LCD_WIDTH * LCD_HEIGHT * 2) as usize);
+        assert!(raw.iter().all(|&b| b == 0xFF));
+    }
+
     #[test]
     fn test_urlencoding_decode_basic() {
         assert_eq!(urlencoding_decode("hello%20world"), "hello world");
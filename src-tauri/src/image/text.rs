@@ -0,0 +1,376 @@
+//! Text Rendering
+//!
+//! Renders button labels straight to a 60x60 image, so simple profiles don't
+//! need the frontend to rasterize text first. Uses the bundled DejaVu Sans
+//! Bold font (see `fonts/LICENSE-DejaVu.txt`).
+//!
+//! Text is word-wrapped to the button width and centered both ways. Explicit
+//! newlines start a new line, words too long for a line are broken, and text
+//! that still doesn't fit is cut off with an ellipsis on the last line.
+
+use super::processor::{parse_color, LCD_HEIGHT, LCD_WIDTH};
+use ab_glyph::{point, Font, FontRef, OutlinedGlyph, Point, PxScale, Rect, ScaleFont};
+use image::{ImageBuffer, Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+
+/// Bundled font
+const FONT_DATA: &[u8] = include_bytes!("../../fonts/DejaVuSans-Bold.ttf");
+
+/// Blank border kept around the text, in pixels
+const TEXT_PADDING: f32 = 3.0;
+
+/// Appended to the last line when text is cut off
+const ELLIPSIS: char = '…';
+
+/// How text is drawn on a button
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TextStyle {
+    /// Font size in pixels
+    pub font_size: f32,
+    /// Text color (`#RRGGBB`, `#RGB` or CSS name)
+    pub color: String,
+    /// Background color (`#RRGGBB`, `#RGB` or CSS name)
+    pub background: String,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            font_size: 14.0,
+            color: "#FFFFFF".to_string(),
+            background: "#000000".to_string(),
+        }
+    }
+}
+
+/// Render text onto a new button-sized image
+pub fn render_text(text: &str, style: &TextStyle) -> Result<RgbImage, String> {
+    let (br, bg, bb) = parse_color(&style.background)?;
+    let mut img: RgbImage = ImageBuffer::from_pixel(LCD_WIDTH, LCD_HEIGHT, Rgb([br, bg, bb]));
+    draw_wrapped_text(&mut img, text, style.font_size, &style.color, 0, LCD_HEIGHT)?;
+    Ok(img)
+}
+
+/// Height of one line of text at `font_size`, in pixels
+pub fn line_height(font_size: f32) -> Result<f32, String> {
+    let font = scaled_font(font_size)?;
+    Ok(font.height() + font.line_gap())
+}
+
+/// Draw text wrapped and centered in a full-width horizontal band of `img`
+///
+/// The band starts at row `top` and is `height` rows tall. Nothing is drawn
+/// outside it.
+pub fn draw_wrapped_text(
+    img: &mut RgbImage,
+    text: &str,
+    font_size: f32,
+    color: &str,
+    top: u32,
+    height: u32,
+) -> Result<(), String> {
+    let font = scaled_font(font_size)?;
+    let (fr, fg, fb) = parse_color(color)?;
+    let width = img.width();
+    let bottom = (top + height).min(img.height());
+
+    let max_width = width as f32 - 2.0 * TEXT_PADDING;
+    let line_height = font.height() + font.line_gap();
+    let max_lines = (((height as f32 - 2.0 * TEXT_PADDING) + font.line_gap()) / line_height).floor().max(1.0) as usize;
+    let lines = layout_lines(&font, text, max_width, max_lines);
+
+    let block_height = lines.len() as f32 * line_height - font.line_gap();
+    let mut baseline = top as f32 + (height as f32 - block_height) / 2.0 + font.ascent();
+
+    for line in &lines {
+        let x = (width as f32 - text_width(&font, line)) / 2.0;
+        for outline in layout_line(&font, line, point(x, baseline)) {
+            blend_outline(img, &outline, (0, 0), top, bottom, (fr, fg, fb));
+        }
+        baseline += line_height;
+    }
+    Ok(())
+}
+
+/// Draw one line of text centered on `center`, as large as fits in a
+/// `size` x `size` box
+///
+/// Centering uses the drawn outlines rather than the font's line metrics, so
+/// short text like a badge count sits in the middle of the box.
+pub fn draw_fitted_text(
+    img: &mut RgbImage,
+    text: &str,
+    center: (i64, i64),
+    size: u32,
+    color: (u8, u8, u8),
+) -> Result<(), String> {
+    let text = text.trim();
+    if text.is_empty() || size == 0 {
+        return Ok(());
+    }
+
+    // Outlines scale with the font size, so measure once and rescale
+    let probe = size as f32;
+    let Some(probed) = outline_bounds(&layout_line(&scaled_font(probe)?, text, point(0.0, 0.0))) else {
+        return Ok(());
+    };
+    let fit = (size as f32 / probed.width().max(1.0)).min(size as f32 / probed.height().max(1.0));
+    let outlines = layout_line(&scaled_font(probe * fit)?, text, point(0.0, 0.0));
+    let Some(bounds) = outline_bounds(&outlines) else {
+        return Ok(());
+    };
+
+    let offset = (
+        center.0 - ((bounds.min.x + bounds.max.x) / 2.0).round() as i64,
+        center.1 - ((bounds.min.y + bounds.max.y) / 2.0).round() as i64,
+    );
+    for outline in &outlines {
+        blend_outline(img, outline, offset, 0, img.height(), color);
+    }
+    Ok(())
+}
+
+/// The bundled font at `font_size` pixels
+fn scaled_font(font_size: f32) -> Result<ab_glyph::PxScaleFont<FontRef<'static>>, String> {
+    if !(font_size.is_finite() && font_size > 0.0) {
+        return Err(format!("Invalid font size: {}", font_size));
+    }
+    let font = FontRef::try_from_slice(FONT_DATA).map_err(|e| format!("Failed to load font: {}", e))?;
+    Ok(font.into_scaled(PxScale::from(font_size)))
+}
+
+/// Outlines of a single line of text, starting at `origin` on the baseline
+///
+/// Characters without an outline (such as spaces) only advance the pen.
+fn layout_line<F: Font>(font: &impl ScaleFont<F>, text: &str, origin: Point) -> Vec<OutlinedGlyph> {
+    let mut outlines = Vec::new();
+    let mut x = origin.x;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = font.glyph_id(c);
+        if let Some(prev) = previous {
+            x += font.kern(prev, id);
+        }
+        let glyph = id.with_scale_and_position(font.scale(), point(x, origin.y));
+        x += font.h_advance(id);
+        previous = Some(id);
+        outlines.extend(font.outline_glyph(glyph));
+    }
+    outlines
+}
+
+/// Pixel bounds covering all the outlines, if there are any
+fn outline_bounds(outlines: &[OutlinedGlyph]) -> Option<Rect> {
+    outlines.iter().map(|o| o.px_bounds()).reduce(|a, b| Rect {
+        min: point(a.min.x.min(b.min.x), a.min.y.min(b.min.y)),
+        max: point(a.max.x.max(b.max.x), a.max.y.max(b.max.y)),
+    })
+}
+
+/// Blend an outline into `img`, shifted by `offset` and clipped to rows
+/// `top..bottom`
+fn blend_outline(img: &mut RgbImage, outline: &OutlinedGlyph, offset: (i64, i64), top: u32, bottom: u32, color: (u8, u8, u8)) {
+    let width = img.width() as i64;
+    let bounds = outline.px_bounds();
+    outline.draw(|gx, gy, coverage| {
+        let px = bounds.min.x as i64 + gx as i64 + offset.0;
+        let py = bounds.min.y as i64 + gy as i64 + offset.1;
+        if px < 0 || py < top as i64 || px >= width || py >= bottom as i64 {
+            return;
+        }
+        let pixel = img.get_pixel_mut(px as u32, py as u32);
+        let coverage = coverage.clamp(0.0, 1.0);
+        for (channel, fore) in pixel.0.iter_mut().zip([color.0, color.1, color.2]) {
+            *channel = (*channel as f32 * (1.0 - coverage) + fore as f32 * coverage).round() as u8;
+        }
+    });
+}
+
+/// Width of a single line of text
+fn text_width<F: Font>(font: &impl ScaleFont<F>, text: &str) -> f32 {
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = font.glyph_id(c);
+        if let Some(prev) = previous {
+            width += font.kern(prev, id);
+        }
+        width += font.h_advance(id);
+        previous = Some(id);
+    }
+    width
+}
+
+/// Split text into lines that fit `max_width`, at most `max_lines` of them
+fn layout_lines<F: Font>(font: &impl ScaleFont<F>, text: &str, max_width: f32, max_lines: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut current = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if current.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", current, word)
+            };
+            if text_width(font, &candidate) <= max_width {
+                current = candidate;
+                continue;
+            }
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            // Break words that don't fit on a line of their own
+            for c in word.chars() {
+                current.push(c);
+                if text_width(font, &current) > max_width && current.chars().count() > 1 {
+                    current.pop();
+                    lines.push(std::mem::replace(&mut current, c.to_string()));
+                }
+            }
+        }
+        lines.push(current);
+    }
+
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        if let Some(last) = lines.last_mut() {
+            *last = ellipsize(font, last, max_width);
+        }
+    }
+    lines
+}
+
+/// Shorten a line until it fits with an ellipsis appended
+fn ellipsize<F: Font>(font: &impl ScaleFont<F>, line: &str, max_width: f32) -> String {
+    let mut kept: String = line.trim_end().to_string();
+    loop {
+        let candidate = format!("{}{}", kept, ELLIPSIS);
+        if kept.is_empty() || text_width(font, &candidate) <= max_width {
+            return candidate;
+        }
+        kept.pop();
+        kept = kept.trim_end().to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn font() -> ab_glyph::PxScaleFont<FontRef<'static>> {
+        scaled_font(14.0).unwrap()
+    }
+
+    fn has_text_pixels(img: &RgbImage, rows: std::ops::Range<u32>) -> bool {
+        rows.into_iter()
+            .any(|y| (0..LCD_WIDTH).any(|x| img.get_pixel(x, y)[0] > 128))
+    }
+
+    // ========== Layout Tests ==========
+
+    #[test]
+    fn test_short_text_is_one_line() {
+        assert_eq!(layout_lines(&font(), "Mute", 54.0, 3), vec!["Mute"]);
+    }
+
+    #[test]
+    fn test_words_wrap_to_width() {
+        let font = font();
+        let lines = layout_lines(&font, "Start Stream Now", 54.0, 3);
+        assert!(lines.len() > 1);
+        assert_eq!(lines.join(" "), "Start Stream Now");
+        assert!(lines.iter().all(|line| text_width(&font, line) <= 54.0));
+    }
+
+    #[test]
+    fn test_newlines_start_new_lines() {
+        assert_eq!(layout_lines(&font(), "A\nB", 54.0, 3), vec!["A", "B"]);
+    }
+
+    #[test]
+    fn test_long_word_is_broken() {
+        let font = font();
+        let lines = layout_lines(&font, "Supercalifragilistic", 54.0, 5);
+        assert!(lines.len() > 1);
+        assert_eq!(lines.concat(), "Supercalifragilistic");
+        assert!(lines.iter().all(|line| text_width(&font, line) <= 54.0));
+    }
+
+    #[test]
+    fn test_overflow_is_ellipsized() {
+        let font = font();
+        let lines = layout_lines(&font, "one two three four five six seven eight", 54.0, 2);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].ends_with(ELLIPSIS));
+        assert!(text_width(&font, &lines[1]) <= 54.0);
+    }
+
+    // ========== Render Tests ==========
+
+    #[test]
+    fn test_render_centers_text() {
+        let img = render_text("Hi", &TextStyle::default()).unwrap();
+        assert_eq!(img.dimensions(), (LCD_WIDTH, LCD_HEIGHT));
+        assert!(has_text_pixels(&img, 25..35));
+        assert!(!has_text_pixels(&img, 0..10));
+        assert!(!has_text_pixels(&img, 50..60));
+    }
+
+    #[test]
+    fn test_render_uses_background() {
+        let style = TextStyle {
+            background: "#0000FF".to_string(),
+            ..Default::default()
+        };
+        let img = render_text("", &style).unwrap();
+        assert_eq!(img.get_pixel(0, 0), &Rgb([0, 0, 255]));
+    }
+
+    #[test]
+    fn test_render_rejects_bad_style() {
+        let bad_color = TextStyle {
+            color: "notacolor".to_string(),
+            ..Default::default()
+        };
+        assert!(render_text("x", &bad_color).is_err());
+        let bad_size = TextStyle {
+            font_size: 0.0,
+            ..Default::default()
+        };
+        assert!(render_text("x", &bad_size).is_err());
+    }
+
+    #[test]
+    fn test_fitted_text_is_centered_in_box() {
+        let mut img: RgbImage = ImageBuffer::from_pixel(LCD_WIDTH, LCD_HEIGHT, Rgb([0, 0, 0]));
+        draw_fitted_text(&mut img, "NEW", (30, 30), 20, (255, 255, 255)).unwrap();
+
+        let lit: Vec<(u32, u32)> = img
+            .enumerate_pixels()
+            .filter(|(_, _, p)| p.0[0] > 128)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert!(!lit.is_empty());
+        assert!(lit.iter().all(|&(x, y)| (19..=41).contains(&x) && (19..=41).contains(&y)));
+        // Wide text is limited by the box width
+        let (min_x, max_x) = (lit.iter().map(|p| p.0).min().unwrap(), lit.iter().map(|p| p.0).max().unwrap());
+        assert!(max_x - min_x >= 15);
+    }
+
+    #[test]
+    fn test_fitted_text_ignores_blank_text() {
+        let mut img: RgbImage = ImageBuffer::from_pixel(10, 10, Rgb([0, 0, 0]));
+        draw_fitted_text(&mut img, "  ", (5, 5), 8, (255, 255, 255)).unwrap();
+        draw_fitted_text(&mut img, "x", (5, 5), 0, (255, 255, 255)).unwrap();
+        assert!(img.pixels().all(|p| *p == Rgb([0, 0, 0])));
+    }
+
+    #[test]
+    fn test_style_deserializes_with_defaults() {
+        let style: TextStyle = serde_json::from_str(r##"{"fontSize": 20, "color": "red"}"##).unwrap();
+        assert_eq!(style.font_size, 20.0);
+        assert_eq!(style.color, "red");
+        assert_eq!(style.background, "#000000");
+    }
+}
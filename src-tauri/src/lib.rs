@@ -19,32 +19,124 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(system::auto_launch::plugin())
         .setup(|app| {
-            // Initialize logging (debug level to see device communication)
-            env_logger::Builder::from_env(
-                env_logger::Env::default().default_filter_or("debug")
-            ).init();
+            let app_data_dir = app.path().app_data_dir()
+                .expect("Failed to get app data directory");
 
+            // Start with the default level; the configured one is applied
+            // once the settings are loaded
+            let logging = system::logging::Logging::init(
+                &app_data_dir,
+                system::logging::LogLevel::default(),
+                false,
+            );
             log::info!("SOOMFON Controller starting...");
 
             // Initialize HID manager state
-            let hid_manager = hid::manager::HidManager::new();
-            app.manage(std::sync::Arc::new(parking_lot::Mutex::new(hid_manager)));
+            let hid_manager = std::sync::Arc::new(parking_lot::Mutex::new(hid::manager::HidManager::new()));
+            {
+                // Weak reference avoids a cycle through the manager's own callback
+                let app_handle = app.handle().clone();
+                let weak_manager = std::sync::Arc::downgrade(&hid_manager);
+                hid_manager.lock().set_hotplug_callback(move |event| {
+                    if let Some(manager) = weak_manager.upgrade() {
+                        commands::device::handle_hotplug_event(&app_handle, &manager, event);
+                    }
+                });
+            }
+            match hid::monitor::HotplugMonitor::start(std::sync::Arc::clone(&hid_manager)) {
+                Ok(monitor) => {
+                    app.manage(parking_lot::Mutex::new(monitor));
+                }
+                Err(e) => log::warn!("Hot-plug monitor unavailable: {}", e),
+            }
+            app.manage(std::sync::Arc::clone(&hid_manager));
 
             // Initialize config manager state
-            let app_data_dir = app.path().app_data_dir()
-                .expect("Failed to get app data directory");
-            let config_manager = config::manager::ConfigManager::new(app_data_dir.clone());
-            app.manage(std::sync::Arc::new(parking_lot::Mutex::new(config_manager)));
+            let mut config_manager = config::manager::ConfigManager::new(app_data_dir.clone());
+            let auto_launch = config_manager.get_settings().auto_launch;
+            if system::auto_launch::reconcile(app.handle(), auto_launch) != auto_launch {
+                if let Err(e) = config_manager.set_auto_launch(!auto_launch) {
+                    log::warn!("Failed to save auto-launch state: {}", e);
+                }
+            }
+            let settings = config_manager.get_settings();
+            if let Err(e) = logging.set_filter(settings.log_level, settings.usb_logging) {
+                log::warn!("{}", e);
+            }
+            app.manage(logging);
+            let start_minimized = config_manager.get_settings().start_minimized;
+            let execution_mode = config_manager.get_settings().action_execution_mode;
+            let history_max_bytes = config_manager.get_settings().history_file_max_kb * 1024;
+            let integrations = actions::IntegrationConfig::from_settings(config_manager.get_settings());
+            let config_manager = std::sync::Arc::new(parking_lot::Mutex::new(config_manager));
+            app.manage(std::sync::Arc::clone(&config_manager));
 
             // Initialize profile manager state
             let profiles_dir = app_data_dir.join("profiles");
             let profile_manager = config::profiles::ProfileManager::new(profiles_dir);
-            app.manage(std::sync::Arc::new(parking_lot::Mutex::new(profile_manager)));
+            let profile_manager = std::sync::Arc::new(parking_lot::Mutex::new(profile_manager));
+            app.manage(std::sync::Arc::clone(&profile_manager));
+            let app_handle = app.handle().clone();
+            match config::watcher::ProfileWatcher::start(std::sync::Arc::clone(&profile_manager), move |ids| {
+                commands::config::handle_external_profile_changes(&app_handle, ids);
+            }) {
+                Ok(watcher) => {
+                    app.manage(parking_lot::Mutex::new(watcher));
+                }
+                Err(e) => log::warn!("Profile changes on disk won't be picked up: {}", e),
+            }
+
+            let integrations = integrations.with_device(actions::DeviceHandle {
+                hid: hid_manager,
+                config: config_manager,
+                profiles: profile_manager,
+            });
 
             // Initialize action engine state
-            let action_engine = actions::engine::ActionEngine::new();
+            let mut action_engine = actions::engine::ActionEngine::new();
+            action_engine.set_execution_mode(execution_mode);
+            action_engine.set_integrations(integrations);
+            match actions::history_store::HistoryStore::open(
+                app_data_dir.join(actions::history_store::HISTORY_FILE),
+                history_max_bytes,
+            ) {
+                Ok(store) => action_engine.set_history_store(std::sync::Arc::new(store)),
+                Err(e) => log::warn!("Action history will not be saved: {}", e),
+            }
             app.manage(std::sync::Arc::new(parking_lot::Mutex::new(action_engine)));
+            match actions::stats::StatsStore::open(app_data_dir.join(actions::stats::STATS_FILE)) {
+                Ok(stats) => {
+                    app.manage(std::sync::Arc::new(stats));
+                }
+                Err(e) => log::warn!("Usage statistics will not be kept: {}", e),
+            }
+            app.manage(std::sync::Arc::new(parking_lot::Mutex::new(actions::event_binder::EventBinder::new())));
+
+            // Built after the managers so the menu can list the profiles
+            let has_tray = match tray::build_tray(app.handle()) {
+                Ok(tray) => {
+                    app.manage(tray);
+                    true
+                }
+                Err(e) => {
+                    log::warn!("Failed to create tray icon: {}", e);
+                    false
+                }
+            };
+
+            // The window starts hidden (see tauri.conf.json). It stays hidden
+            // when starting minimized or at login, but only if the tray is
+            // there to bring it back.
+            let launched_at_login = std::env::args().any(|arg| arg == system::auto_launch::AUTOSTART_ARG);
+            if has_tray && (start_minimized || launched_at_login) {
+                log::info!("Starting minimized to tray");
+            } else if let Some(window) = app.get_webview_window("main") {
+                if let Err(e) = window.show() {
+                    log::warn!("Failed to show main window: {}", e);
+                }
+            }
 
             log::info!("SOOMFON Controller initialized successfully");
             Ok(())
@@ -52,32 +144,99 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             // Device commands
             commands::device::connect_device,
+            commands::device::connect_device_by_path,
             commands::device::disconnect_device,
             commands::device::get_device_status,
+            commands::device::get_device_capabilities,
+            commands::device::reset_device,
             commands::device::set_brightness,
             commands::device::set_button_image,
+            commands::device::set_buttons,
+            commands::device::set_button_color,
+            commands::device::set_button_text,
+            commands::device::send_raw_packet,
+            commands::device::run_diagnostics,
+            commands::device::self_test,
+            commands::device::set_button_animation,
+            commands::device::identify_button,
+            commands::device::capture_next_event,
             commands::device::clear_button,
+            commands::device::clear_button_color,
             commands::device::enumerate_devices,
             // Config commands
             commands::config::get_app_settings,
             commands::config::set_app_settings,
+            commands::config::set_start_minimized,
+            commands::config::set_home_assistant,
+            commands::config::set_node_red,
+            commands::config::set_mqtt,
+            commands::config::save_event_mapping,
             commands::config::get_profiles,
+            commands::config::reorder_profiles,
+            commands::config::rename_profile,
+            commands::config::search_profiles,
+            commands::config::set_profile_tags,
             commands::config::get_active_profile,
             commands::config::set_active_profile,
             commands::config::create_profile,
             commands::config::update_profile,
             commands::config::delete_profile,
             commands::config::import_profile,
+            commands::config::import_profile_with_review,
             commands::config::export_profile,
+            commands::config::duplicate_profile,
+            commands::config::export_profile_bundle,
+            commands::config::import_profile_bundle,
+            commands::config::set_active_workspace,
+            commands::config::set_profile_brightness,
+            commands::config::get_profile_stats,
+            commands::config::reset_stats,
+            commands::config::add_workspace,
+            commands::config::delete_workspace,
+            commands::config::update_workspace,
             // Action commands
             commands::actions::execute_action,
+            commands::actions::dispatch_event,
             commands::actions::cancel_action,
             commands::actions::get_action_history,
+            commands::actions::get_action_history_filtered,
+            commands::actions::clear_action_history,
+            commands::actions::get_action_stats,
+            commands::actions::start_macro_record,
+            commands::actions::stop_macro_record,
             // System commands
             commands::system::get_auto_launch,
             commands::system::set_auto_launch,
+            commands::system::get_log_path,
+            commands::system::set_log_level,
+            commands::system::set_usb_logging,
+            commands::system::get_system_info,
             commands::system::open_file_dialog,
+            commands::system::list_audio_devices,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Fires for tray "Quit", the last window closing, and on macOS
+            // and Windows also for OS logout/shutdown
+            if let tauri::RunEvent::Exit = event {
+                commands::device::shutdown_on_exit(app);
+
+                if let Some(stats) = app.try_state::<std::sync::Arc<actions::stats::StatsStore>>() {
+                    stats.flush();
+                }
+                if let Some(engine) = app.try_state::<std::sync::Arc<parking_lot::Mutex<actions::engine::ActionEngine>>>() {
+                    // Clone the store out so the engine isn't locked while it flushes
+                    let history = engine.lock().history_store().cloned();
+                    if let Some(history) = history {
+                        history.flush();
+                    }
+                }
+
+                // Don't leave modifiers held if a keyboard action was interrupted
+                if let Err(e) = actions::handlers::keyboard::release_all_modifiers() {
+                    log::warn!("Failed to release modifier keys on exit: {}", e);
+                }
+            }
+        });
 }
@@ -0,0 +1,260 @@
+//! Audio Devices
+//!
+//! Lists audio output devices and controls the volume of a specific one or
+//! of the default output.
+//! On Windows, uses the Core Audio API (IMMDeviceEnumerator / IAudioEndpointVolume).
+
+use serde::Serialize;
+
+/// ID reported for the system default output on platforms without device enumeration
+pub const DEFAULT_DEVICE_ID: &str = "default";
+
+/// An audio output device
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioDevice {
+    /// Endpoint ID, usable as a media action's `device_id`
+    pub id: String,
+    /// Human-readable device name
+    pub name: String,
+    /// Whether this is the current default output
+    pub is_default: bool,
+}
+
+/// Volume change to apply to an output device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VolumeChange {
+    /// One system volume step up
+    StepUp,
+    /// One system volume step down
+    StepDown,
+    ToggleMute,
+    /// Change the master volume by this many percentage points
+    Adjust(i32),
+}
+
+/// List active audio output devices
+pub fn list_output_devices() -> Result<Vec<AudioDevice>, String> {
+    #[cfg(target_os = "windows")]
+    {
+        list_output_devices_windows()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        Ok(vec![AudioDevice {
+            id: DEFAULT_DEVICE_ID.to_string(),
+            name: "Default".to_string(),
+            is_default: true,
+        }])
+    }
+}
+
+/// Change the volume of the output device with the given ID
+pub fn change_device_volume(device_id: &str, change: VolumeChange) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
+        change_device_volume_windows(device_id, change)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (device_id, change);
+        Err("Per-device volume control only supported on Windows".to_string())
+    }
+}
+
+/// Change the volume of the default output device
+pub fn change_default_volume(change: VolumeChange) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
+        change_default_volume_windows(change)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = change;
+        Err("Volume control through Core Audio only supported on Windows".to_string())
+    }
+}
+
+/// Volume level (0.0-1.0) after adjusting by `delta` percentage points
+///
+/// The current level is rounded to a whole percent first, so repeated
+/// adjustments land on exact steps.
+#[cfg(any(target_os = "windows", test))]
+fn adjusted_level(current: f32, delta: i32) -> f32 {
+    let percent = (current * 100.0).round() as i32;
+    (percent + delta).clamp(0, 100) as f32 / 100.0
+}
+
+#[cfg(target_os = "windows")]
+fn device_enumerator() -> Result<windows::Win32::Media::Audio::IMMDeviceEnumerator, String> {
+    use windows::Win32::Media::Audio::{IMMDeviceEnumerator, MMDeviceEnumerator};
+    use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED};
+
+    unsafe {
+        // Already-initialized threads return S_FALSE or RPC_E_CHANGED_MODE; both are fine
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        CoCreateInstance::<_, IMMDeviceEnumerator>(&MMDeviceEnumerator, None, CLSCTX_ALL)
+            .map_err(|e| format!("Failed to create audio device enumerator: {}", e))
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn endpoint_id(device: &windows::Win32::Media::Audio::IMMDevice) -> Result<String, String> {
+    use windows::Win32::System::Com::CoTaskMemFree;
+
+    unsafe {
+        let id = device
+            .GetId()
+            .map_err(|e| format!("Failed to get audio device ID: {}", e))?;
+        let result = id.to_string().map_err(|e| format!("Invalid audio device ID: {}", e));
+        CoTaskMemFree(Some(id.0 as *const _));
+        result
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn friendly_name(device: &windows::Win32::Media::Audio::IMMDevice) -> Option<String> {
+    use windows::Win32::Devices::FunctionDiscovery::PKEY_Device_FriendlyName;
+    use windows::Win32::System::Com::STGM_READ;
+
+    unsafe {
+        let store = device.OpenPropertyStore(STGM_READ).ok()?;
+        let value = store.GetValue(&PKEY_Device_FriendlyName).ok()?;
+        let name = value.to_string();
+        (!name.is_empty()).then_some(name)
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn list_output_devices_windows() -> Result<Vec<AudioDevice>, String> {
+    use windows::Win32::Media::Audio::{eConsole, eRender, DEVICE_STATE_ACTIVE};
+
+    let enumerator = device_enumerator()?;
+
+    unsafe {
+        let default_id = enumerator
+            .GetDefaultAudioEndpoint(eRender, eConsole)
+            .ok()
+            .and_then(|device| endpoint_id(&device).ok());
+
+        let collection = enumerator
+            .EnumAudioEndpoints(eRender, DEVICE_STATE_ACTIVE)
+            .map_err(|e| format!("Failed to enumerate audio devices: {}", e))?;
+        let count = collection
+            .GetCount()
+            .map_err(|e| format!("Failed to count audio devices: {}", e))?;
+
+        let mut devices = Vec::with_capacity(count as usize);
+        for i in 0..count {
+            let device = match collection.Item(i) {
+                Ok(device) => device,
+                Err(e) => {
+                    log::warn!("Skipping audio device {}: {}", i, e);
+                    continue;
+                }
+            };
+            let id = endpoint_id(&device)?;
+            let name = friendly_name(&device).unwrap_or_else(|| id.clone());
+            devices.push(AudioDevice {
+                is_default: default_id.as_deref() == Some(id.as_str()),
+                id,
+                name,
+            });
+        }
+
+        Ok(devices)
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn change_device_volume_windows(device_id: &str, change: VolumeChange) -> Result<(), String> {
+    use windows::core::HSTRING;
+
+    let enumerator = device_enumerator()?;
+    let device = unsafe { enumerator.GetDevice(&HSTRING::from(device_id)) }
+        .map_err(|e| format!("Audio device not found: {} ({})", device_id, e))?;
+    apply_volume_change(&device, change)
+}
+
+#[cfg(target_os = "windows")]
+fn change_default_volume_windows(change: VolumeChange) -> Result<(), String> {
+    use windows::Win32::Media::Audio::{eConsole, eRender};
+
+    let enumerator = device_enumerator()?;
+    let device = unsafe { enumerator.GetDefaultAudioEndpoint(eRender, eConsole) }
+        .map_err(|e| format!("No default audio device: {}", e))?;
+    apply_volume_change(&device, change)
+}
+
+#[cfg(target_os = "windows")]
+fn apply_volume_change(
+    device: &windows::Win32::Media::Audio::IMMDevice,
+    change: VolumeChange,
+) -> Result<(), String> {
+    use windows::Win32::Media::Audio::Endpoints::IAudioEndpointVolume;
+    use windows::Win32::System::Com::CLSCTX_ALL;
+
+    unsafe {
+        let volume: IAudioEndpointVolume = device
+            .Activate(CLSCTX_ALL, None)
+            .map_err(|e| format!("Failed to open audio device volume: {}", e))?;
+
+        let result = match change {
+            VolumeChange::StepUp => volume.VolumeStepUp(std::ptr::null()),
+            VolumeChange::StepDown => volume.VolumeStepDown(std::ptr::null()),
+            VolumeChange::ToggleMute => {
+                let muted = volume
+                    .GetMute()
+                    .map_err(|e| format!("Failed to read mute state: {}", e))?;
+                volume.SetMute(!muted.as_bool(), std::ptr::null())
+            }
+            VolumeChange::Adjust(delta) => {
+                let current = volume
+                    .GetMasterVolumeLevelScalar()
+                    .map_err(|e| format!("Failed to read volume: {}", e))?;
+                volume.SetMasterVolumeLevelScalar(adjusted_level(current, delta), std::ptr::null())
+            }
+        };
+
+        result.map_err(|e| format!("Failed to change volume: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_output_devices_has_one_default() {
+        let devices = list_output_devices().unwrap_or_default();
+        assert!(devices.iter().filter(|d| d.is_default).count() <= 1);
+    }
+
+    #[test]
+    fn test_audio_device_serializes_camel_case() {
+        let device = AudioDevice {
+            id: "abc".to_string(),
+            name: "Speakers".to_string(),
+            is_default: true,
+        };
+        let json = serde_json::to_string(&device).unwrap();
+        assert_eq!(json, r#"{"id":"abc","name":"Speakers","isDefault":true}"#);
+    }
+
+    #[test]
+    fn test_adjusted_level_moves_exact_steps() {
+        assert_eq!(adjusted_level(0.40, 5), 0.45);
+        assert_eq!(adjusted_level(0.40, -5), 0.35);
+        // Off-step levels snap to the whole percent first
+        assert_eq!(adjusted_level(0.333, 5), 0.38);
+    }
+
+    #[test]
+    fn test_adjusted_level_clamps() {
+        assert_eq!(adjusted_level(0.98, 5), 1.0);
+        assert_eq!(adjusted_level(0.02, -5), 0.0);
+    }
+}
@@ -1,64 +1,106 @@
 //! Auto-Launch
 //!
-//! Manages auto-launch on system startup.
+//! Manages auto-launch on system startup through `tauri-plugin-autostart`,
+//! which writes the Windows registry Run entry, macOS LaunchAgent or Linux
+//! XDG autostart file.
+//!
+//! `AppSettings.auto_launch` records what the user chose. On startup
+//! `reconcile` brings the OS entry in line with it, and removes the entries
+//! earlier versions wrote themselves under different names.
+
+use tauri::AppHandle;
+use tauri_plugin_autostart::{MacosLauncher, ManagerExt};
+
+/// Argument passed to the app when started at login (starts in the tray)
+pub const AUTOSTART_ARG: &str = "--hidden";
+
+/// Autostart plugin to register with the app
+pub fn plugin<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri_plugin_autostart::init(MacosLauncher::LaunchAgent, Some(vec![AUTOSTART_ARG]))
+}
 
 /// Check if auto-launch is enabled
-pub fn is_enabled() -> bool {
-    #[cfg(target_os = "windows")]
-    {
-        is_enabled_windows()
-    }
+pub fn is_enabled(app: &AppHandle) -> bool {
+    app.autolaunch().is_enabled().unwrap_or_else(|e| {
+        log::warn!("Failed to read auto-launch state: {}", e);
+        false
+    })
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        is_enabled_macos()
+/// Enable or disable auto-launch
+pub fn set_enabled(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    let autolaunch = app.autolaunch();
+    let result = if enabled { autolaunch.enable() } else { autolaunch.disable() };
+    result.map_err(|e| format!("Failed to {} auto-launch: {}", if enabled { "enable" } else { "disable" }, e))
+}
+
+/// Make the OS auto-launch entry match the stored setting
+///
+/// Returns the state actually in effect, which differs from `wanted` only if
+/// the entry couldn't be changed.
+pub fn reconcile(app: &AppHandle, wanted: bool) -> bool {
+    if has_legacy_entry() {
+        log::info!("Replacing auto-launch entry from an earlier version");
+        if let Err(e) = remove_legacy_entry() {
+            log::warn!("Failed to remove old auto-launch entry: {}", e);
+        }
     }
 
-    #[cfg(target_os = "linux")]
-    {
-        is_enabled_linux()
+    let actual = is_enabled(app);
+    if actual == wanted {
+        return actual;
+    }
+    log::info!("Auto-launch is {} but set to {}, updating", actual, wanted);
+    match set_enabled(app, wanted) {
+        Ok(()) => wanted,
+        Err(e) => {
+            log::warn!("{}", e);
+            actual
+        }
     }
 }
 
-/// Enable auto-launch
-pub fn enable(app_path: &str) -> Result<(), String> {
+/// Whether an auto-launch entry from an earlier version exists
+fn has_legacy_entry() -> bool {
     #[cfg(target_os = "windows")]
     {
-        enable_windows(app_path)
+        has_legacy_entry_windows()
     }
 
     #[cfg(target_os = "macos")]
     {
-        enable_macos(app_path)
+        has_legacy_entry_macos()
     }
 
     #[cfg(target_os = "linux")]
     {
-        enable_linux(app_path)
+        has_legacy_entry_linux()
     }
 }
 
-/// Disable auto-launch
-pub fn disable() -> Result<(), String> {
+/// Remove the auto-launch entry written by an earlier version
+fn remove_legacy_entry() -> Result<(), String> {
     #[cfg(target_os = "windows")]
     {
-        disable_windows()
+        remove_legacy_entry_windows()
     }
 
     #[cfg(target_os = "macos")]
     {
-        disable_macos()
+        remove_legacy_entry_macos()
     }
 
     #[cfg(target_os = "linux")]
     {
-        disable_linux()
+        remove_legacy_entry_linux()
     }
 }
 
-// Windows implementation using registry
+// Entries written by earlier versions, before the autostart plugin
+
+// Windows registry Run key
 #[cfg(target_os = "windows")]
-fn is_enabled_windows() -> bool {
+fn has_legacy_entry_windows() -> bool {
     use std::process::Command;
 
     let output = Command::new("reg")
@@ -74,32 +116,7 @@ fn is_enabled_windows() -> bool {
 }
 
 #[cfg(target_os = "windows")]
-fn enable_windows(app_path: &str) -> Result<(), String> {
-    use std::process::Command;
-
-    let result = Command::new("reg")
-        .args([
-            "add",
-            r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run",
-            "/v",
-            "SoomfonController",
-            "/t",
-            "REG_SZ",
-            "/d",
-            &format!("\"{}\" --hidden", app_path),
-            "/f",
-        ])
-        .output();
-
-    match result {
-        Ok(output) if output.status.success() => Ok(()),
-        Ok(output) => Err(String::from_utf8_lossy(&output.stderr).to_string()),
-        Err(e) => Err(e.to_string()),
-    }
-}
-
-#[cfg(target_os = "windows")]
-fn disable_windows() -> Result<(), String> {
+fn remove_legacy_entry_windows() -> Result<(), String> {
     use std::process::Command;
 
     let result = Command::new("reg")
@@ -119,68 +136,16 @@ fn disable_windows() -> Result<(), String> {
     }
 }
 
-// macOS implementation using LaunchAgent
-//
-// LaunchAgent is the preferred method for user-level auto-launch on macOS.
-// The plist file is placed in ~/Library/LaunchAgents/ and loaded by launchd.
+// macOS LaunchAgent in ~/Library/LaunchAgents/
 #[cfg(target_os = "macos")]
-fn is_enabled_macos() -> bool {
+fn has_legacy_entry_macos() -> bool {
     get_launch_agent_path()
         .map(|p| p.exists())
         .unwrap_or(false)
 }
 
 #[cfg(target_os = "macos")]
-fn enable_macos(app_path: &str) -> Result<(), String> {
-    use std::fs;
-
-    let plist_path = get_launch_agent_path()
-        .ok_or("Could not determine LaunchAgents directory")?;
-
-    // Ensure the LaunchAgents directory exists
-    if let Some(parent) = plist_path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create LaunchAgents directory: {}", e))?;
-    }
-
-    // Create the LaunchAgent plist content
-    // The --hidden flag tells the app to start minimized to tray
-    let plist_content = format!(
-        r#"<?xml version="1.0" encoding="UTF-8"?>
-<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
-<plist version="1.0">
-<dict>
-    <key>Label</key>
-    <string>com.soomfon.controller</string>
-    <key>ProgramArguments</key>
-    <array>
-        <string>{}</string>
-        <string>--hidden</string>
-    </array>
-    <key>RunAtLoad</key>
-    <true/>
-    <key>KeepAlive</key>
-    <false/>
-</dict>
-</plist>
-"#,
-        app_path
-    );
-
-    fs::write(&plist_path, plist_content)
-        .map_err(|e| format!("Failed to write LaunchAgent plist: {}", e))?;
-
-    // Load the LaunchAgent (optional - it will be loaded on next login anyway)
-    // Using launchctl to load immediately
-    let _ = std::process::Command::new("launchctl")
-        .args(["load", plist_path.to_str().unwrap_or("")])
-        .output();
-
-    Ok(())
-}
-
-#[cfg(target_os = "macos")]
-fn disable_macos() -> Result<(), String> {
+fn remove_legacy_entry_macos() -> Result<(), String> {
     use std::fs;
 
     let plist_path = get_launch_agent_path()
@@ -207,9 +172,9 @@ fn get_launch_agent_path() -> Option<std::path::PathBuf> {
     })
 }
 
-// Linux implementation using XDG autostart
+// Linux XDG autostart entry
 #[cfg(target_os = "linux")]
-fn is_enabled_linux() -> bool {
+fn has_legacy_entry_linux() -> bool {
     let autostart_path = dirs::config_dir()
         .map(|p| p.join("autostart/soomfon-controller.desktop"));
 
@@ -217,37 +182,7 @@ fn is_enabled_linux() -> bool {
 }
 
 #[cfg(target_os = "linux")]
-fn enable_linux(app_path: &str) -> Result<(), String> {
-    use std::fs;
-
-    let autostart_dir = dirs::config_dir()
-        .ok_or("Could not find config directory")?
-        .join("autostart");
-
-    fs::create_dir_all(&autostart_dir)
-        .map_err(|e| format!("Failed to create autostart directory: {}", e))?;
-
-    let desktop_entry = format!(
-        "[Desktop Entry]\n\
-         Type=Application\n\
-         Name=SOOMFON Controller\n\
-         Exec={} --hidden\n\
-         Hidden=false\n\
-         NoDisplay=false\n\
-         X-GNOME-Autostart-enabled=true\n",
-        app_path
-    );
-
-    let desktop_path = autostart_dir.join("soomfon-controller.desktop");
-
-    fs::write(&desktop_path, desktop_entry)
-        .map_err(|e| format!("Failed to write desktop file: {}", e))?;
-
-    Ok(())
-}
-
-#[cfg(target_os = "linux")]
-fn disable_linux() -> Result<(), String> {
+fn remove_legacy_entry_linux() -> Result<(), String> {
     use std::fs;
 
     let desktop_path = dirs::config_dir()
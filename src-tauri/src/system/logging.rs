@@ -0,0 +1,165 @@
+//! Logging
+//!
+//! Writes the log to rotating files under `<app data>/logs/` so packaged-app
+//! users can share them, and mirrors it to stderr in debug builds. The level
+//! comes from the app settings and can be changed at runtime.
+//!
+//! Per-poll USB traffic is logged under the `usb` target (see
+//! `USB_LOG_TARGET`) and is filtered separately, off by default, so it can be
+//! switched on for device debugging without flooding everyday logs.
+
+use crate::hid::types::USB_LOG_TARGET;
+use flexi_logger::{
+    Age, Cleanup, Criterion, Duplicate, FileSpec, Logger, LoggerHandle, Naming, WriteMode,
+};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Log directory under the app data directory
+pub const LOG_DIR: &str = "logs";
+
+/// Log file name stem
+const LOG_BASENAME: &str = "soomfon-controller";
+
+/// Size at which the current log file is rotated, even within a day
+const MAX_LOG_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Rotated log files kept before the oldest is deleted
+const KEEP_LOG_FILES: usize = 7;
+
+/// Minimum level written to the log
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Off => "off",
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+}
+
+/// Filter spec for a level, with USB traffic logging on or off
+pub fn filter_spec(level: LogLevel, usb_logging: bool) -> String {
+    let usb = if usb_logging { "trace" } else { "off" };
+    format!("{}, {}={}", level.as_str(), USB_LOG_TARGET, usb)
+}
+
+/// The running logger, kept in managed state
+pub struct Logging {
+    handle: Option<LoggerHandle>,
+    directory: PathBuf,
+}
+
+impl Logging {
+    /// Start logging to `<app_data_dir>/logs/`
+    ///
+    /// Falls back to stderr alone if the log directory can't be used. Must
+    /// only be called once per process.
+    pub fn init(app_data_dir: &Path, level: LogLevel, usb_logging: bool) -> Self {
+        let directory = app_data_dir.join(LOG_DIR);
+        let spec = filter_spec(level, usb_logging);
+
+        let file_logger = Logger::try_with_str(&spec).and_then(|logger| {
+            logger
+                .log_to_file(
+                    FileSpec::default()
+                        .directory(&directory)
+                        .basename(LOG_BASENAME),
+                )
+                .format_for_files(flexi_logger::detailed_format)
+                .rotate(
+                    Criterion::AgeOrSize(Age::Day, MAX_LOG_FILE_BYTES),
+                    Naming::Timestamps,
+                    Cleanup::KeepLogFiles(KEEP_LOG_FILES),
+                )
+                .write_mode(WriteMode::Direct)
+                .duplicate_to_stderr(if cfg!(debug_assertions) {
+                    Duplicate::All
+                } else {
+                    Duplicate::None
+                })
+                .start()
+        });
+
+        let handle = match file_logger {
+            Ok(handle) => Some(handle),
+            Err(file_error) => {
+                let handle = Logger::try_with_str(&spec)
+                    .and_then(|logger| logger.log_to_stderr().start())
+                    .ok();
+                log::warn!(
+                    "Logging to stderr only, log directory {} unusable: {}",
+                    directory.display(),
+                    file_error
+                );
+                handle
+            }
+        };
+
+        Self { handle, directory }
+    }
+
+    /// Directory the log files are written to
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Change the level and USB traffic logging
+    pub fn set_filter(&self, level: LogLevel, usb_logging: bool) -> Result<(), String> {
+        let Some(handle) = &self.handle else {
+            return Err("Logging is not running".to_string());
+        };
+        handle
+            .parse_new_spec(&filter_spec(level, usb_logging))
+            .map_err(|e| format!("Failed to change log level: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ========== Filter Spec Tests ==========
+
+    #[test]
+    fn test_filter_spec_usb_off() {
+        assert_eq!(filter_spec(LogLevel::Info, false), "info, usb=off");
+    }
+
+    #[test]
+    fn test_filter_spec_usb_on() {
+        assert_eq!(filter_spec(LogLevel::Warn, true), "warn, usb=trace");
+    }
+
+    #[test]
+    fn test_filter_specs_parse() {
+        for level in [LogLevel::Off, LogLevel::Error, LogLevel::Debug, LogLevel::Trace] {
+            assert!(flexi_logger::LogSpecification::parse(filter_spec(level, true)).is_ok());
+        }
+    }
+
+    // ========== Log Level Tests ==========
+
+    #[test]
+    fn test_log_level_serde() {
+        assert_eq!(serde_json::to_string(&LogLevel::Debug).unwrap(), "\"debug\"");
+        let level: LogLevel = serde_json::from_str("\"warn\"").unwrap();
+        assert_eq!(level, LogLevel::Warn);
+        assert_eq!(LogLevel::default(), LogLevel::Info);
+    }
+}
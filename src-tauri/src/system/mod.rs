@@ -1,7 +1,11 @@
 //! System Integration Module
 //!
-//! Handles system-level features like auto-launch.
+//! Handles system-level features like auto-launch, audio devices, logging and
+//! the foreground window.
 
+pub mod audio;
 pub mod auto_launch;
+pub mod logging;
+pub mod window;
 
 pub use auto_launch::*;
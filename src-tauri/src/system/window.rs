@@ -0,0 +1,170 @@
+//! Foreground Window
+//!
+//! Reports the title and executable of the window that currently has focus,
+//! and brings an application's window to the front.
+//! On Windows, uses GetForegroundWindow / GetWindowTextW / EnumWindows and the
+//! owning process image name. Other platforms report and focus nothing.
+
+/// The focused window
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForegroundWindow {
+    /// Window title
+    pub title: String,
+    /// Executable file name of the owning process (e.g. "notepad.exe")
+    pub process: Option<String>,
+}
+
+/// Get the window that currently has focus
+pub fn foreground_window() -> Option<ForegroundWindow> {
+    #[cfg(target_os = "windows")]
+    {
+        foreground_window_windows()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        None
+    }
+}
+
+/// Bring a visible window of the given executable to the front
+///
+/// `executable` may be a full path or a bare name, with or without ".exe".
+/// Returns whether a window was found and focused.
+pub fn focus_window_of(executable: &str) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        focus_window_of_windows(&executable_name(executable))
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = executable;
+        false
+    }
+}
+
+/// File name of an executable path, lowercased and without ".exe"
+pub fn executable_name(path: &str) -> String {
+    let name = path
+        .trim()
+        .trim_matches('"')
+        .rsplit(['\\', '/'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    match name.strip_suffix(".exe") {
+        Some(stem) => stem.to_string(),
+        None => name,
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn focus_window_of_windows(name: &str) -> bool {
+    use windows::core::BOOL;
+    use windows::Win32::Foundation::{HWND, LPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetWindowTextLengthW, GetWindowThreadProcessId, IsIconic, IsWindowVisible,
+        SetForegroundWindow, ShowWindow, SW_RESTORE,
+    };
+
+    struct Search<'a> {
+        name: &'a str,
+        found: Option<HWND>,
+    }
+
+    unsafe extern "system" fn visit(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let search = unsafe { &mut *(lparam.0 as *mut Search) };
+        // Only visible, titled top-level windows; skips tool and message windows
+        if !unsafe { IsWindowVisible(hwnd) }.as_bool() || unsafe { GetWindowTextLengthW(hwnd) } == 0 {
+            return BOOL(1);
+        }
+        let mut pid = 0u32;
+        unsafe { GetWindowThreadProcessId(hwnd, Some(&mut pid)) };
+        let matches = (pid != 0)
+            .then(|| process_image_name(pid))
+            .flatten()
+            .is_some_and(|image| executable_name(&image) == search.name);
+        if matches {
+            search.found = Some(hwnd);
+            return BOOL(0);
+        }
+        BOOL(1)
+    }
+
+    let mut search = Search { name, found: None };
+    // EnumWindows reports an error when the callback stops early
+    let _ = unsafe { EnumWindows(Some(visit), LPARAM(&mut search as *mut Search as isize)) };
+
+    let Some(hwnd) = search.found else {
+        return false;
+    };
+    unsafe {
+        if IsIconic(hwnd).as_bool() {
+            let _ = ShowWindow(hwnd, SW_RESTORE);
+        }
+        SetForegroundWindow(hwnd).as_bool()
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn foreground_window_windows() -> Option<ForegroundWindow> {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetForegroundWindow, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId,
+    };
+
+    let hwnd = unsafe { GetForegroundWindow() };
+    if hwnd.is_invalid() {
+        return None;
+    }
+
+    let len = unsafe { GetWindowTextLengthW(hwnd) }.max(0) as usize;
+    let mut buf = vec![0u16; len + 1];
+    let copied = unsafe { GetWindowTextW(hwnd, &mut buf) }.max(0) as usize;
+    let title = String::from_utf16_lossy(&buf[..copied]);
+
+    let mut pid = 0u32;
+    unsafe { GetWindowThreadProcessId(hwnd, Some(&mut pid)) };
+    let process = (pid != 0).then(|| process_image_name(pid)).flatten();
+
+    Some(ForegroundWindow { title, process })
+}
+
+/// Executable file name of a process, without its directory
+#[cfg(target_os = "windows")]
+fn process_image_name(pid: u32) -> Option<String> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    let handle = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) }.ok()?;
+
+    let mut buf = [0u16; 1024];
+    let mut size = buf.len() as u32;
+    let queried = unsafe {
+        QueryFullProcessImageNameW(handle, PROCESS_NAME_WIN32, PWSTR(buf.as_mut_ptr()), &mut size)
+    };
+    let _ = unsafe { CloseHandle(handle) };
+    queried.ok()?;
+
+    let path = String::from_utf16_lossy(&buf[..size as usize]);
+    path.rsplit(['\\', '/']).next().map(|name| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ========== Executable Name Tests ==========
+
+    #[test]
+    fn test_executable_name_strips_directory_and_extension() {
+        assert_eq!(executable_name(r"C:\Users\me\AppData\Roaming\Spotify\Spotify.exe"), "spotify");
+        assert_eq!(executable_name("/usr/bin/firefox"), "firefox");
+        assert_eq!(executable_name("notepad"), "notepad");
+        assert_eq!(executable_name(r#""C:\Program Files\App\app.EXE""#), "app");
+    }
+}
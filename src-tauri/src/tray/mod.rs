@@ -1,19 +1,38 @@
 //! System Tray Module
 //!
 //! Handles system tray icon and menu with connection status indication.
+//! The menu lists the profiles so the active one can be switched without
+//! opening the window; it is rebuilt whenever profiles change.
 
+use crate::commands::config::activate_profile;
+use crate::config::manager::ConfigManager;
+use crate::config::profiles::ProfileManager;
+use crate::hid::manager::HidManager;
+use crate::hid::types::DeviceInfo;
+use parking_lot::Mutex;
+use std::sync::Arc;
 use tauri::{
-    AppHandle, Manager,
+    AppHandle, Listener, Manager,
     image::Image,
-    menu::{Menu, MenuItem},
+    menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
     tray::{TrayIcon, TrayIconBuilder},
 };
 
+/// ID of the app's tray icon
+pub const TRAY_ID: &str = "main";
+
+/// Prefix of the menu item IDs that switch profiles
+const PROFILE_MENU_PREFIX: &str = "profile:";
+
+/// Events after which the profile list in the menu may be stale
+const PROFILE_EVENTS: [&str; 2] = ["profile:changed", "profiles:changed"];
+
 /// Connection status for tray icon
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TrayStatus {
     Connected,
     Disconnected,
+    Reconnecting,
     Error,
 }
 
@@ -23,20 +42,50 @@ impl TrayStatus {
         match self {
             TrayStatus::Connected => (76, 175, 80),     // Green (#4CAF50)
             TrayStatus::Disconnected => (100, 100, 100), // Gray (#646464)
+            TrayStatus::Reconnecting => (255, 193, 7),   // Amber (#FFC107)
             TrayStatus::Error => (244, 67, 54),          // Red (#F44336)
         }
     }
 
-    /// Get a tooltip text for this status
-    pub fn tooltip(&self) -> &'static str {
-        match self {
-            TrayStatus::Connected => "SOOMFON Controller - Connected",
-            TrayStatus::Disconnected => "SOOMFON Controller - Disconnected",
-            TrayStatus::Error => "SOOMFON Controller - Error",
+    /// Tooltip text for this status
+    ///
+    /// When connected, names the device (product, serial and firmware where
+    /// known); the other statuses have fixed text.
+    pub fn tooltip(&self, device: Option<&DeviceInfo>) -> String {
+        match (self, device) {
+            (TrayStatus::Connected, Some(info)) => format!("{} - Connected", describe_device(info)),
+            (TrayStatus::Connected, None) => "SOOMFON Controller - Connected".to_string(),
+            (TrayStatus::Disconnected, _) => "SOOMFON Controller - Disconnected".to_string(),
+            (TrayStatus::Reconnecting, _) => "SOOMFON Controller - Reconnecting".to_string(),
+            (TrayStatus::Error, _) => "SOOMFON Controller - Error".to_string(),
         }
     }
 }
 
+/// Device name with serial and firmware, e.g. "SOOMFON Stream Deck (SN: 1234, FW: 1.0.2.0)"
+fn describe_device(info: &DeviceInfo) -> String {
+    let name = info.product.as_deref().unwrap_or("SOOMFON Controller");
+    let firmware = info
+        .firmware
+        .as_ref()
+        .map(|fw| fw.to_string())
+        .or_else(|| info.firmware_version.clone());
+
+    let details: Vec<String> = [
+        info.serial_number.as_ref().map(|sn| format!("SN: {}", sn)),
+        firmware.map(|fw| format!("FW: {}", fw)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    if details.is_empty() {
+        name.to_string()
+    } else {
+        format!("{} ({})", name, details.join(", "))
+    }
+}
+
 /// Build the system tray
 pub fn build_tray(app: &AppHandle) -> Result<TrayIcon, tauri::Error> {
     let menu = build_tray_menu(app)?;
@@ -44,9 +93,29 @@ pub fn build_tray(app: &AppHandle) -> Result<TrayIcon, tauri::Error> {
     // Start with disconnected status icon
     let icon = create_status_icon(TrayStatus::Disconnected);
 
-    TrayIconBuilder::new()
+    for event in PROFILE_EVENTS {
+        let app_handle = app.clone();
+        app.listen_any(event, move |_| refresh_tray_menu(&app_handle));
+    }
+    let app_handle = app.clone();
+    app.listen_any("device:connected", move |_| {
+        let info = app_handle
+            .try_state::<Arc<Mutex<HidManager>>>()
+            .and_then(|manager| manager.lock().get_device_info().cloned());
+        set_tray_status(&app_handle, TrayStatus::Connected, info.as_ref());
+    });
+    let app_handle = app.clone();
+    app.listen_any("device:disconnected", move |_| {
+        set_tray_status(&app_handle, TrayStatus::Disconnected, None);
+    });
+    let app_handle = app.clone();
+    app.listen_any("device:reconnecting", move |_| {
+        set_tray_status(&app_handle, TrayStatus::Reconnecting, None);
+    });
+
+    TrayIconBuilder::with_id(TRAY_ID)
         .icon(icon)
-        .tooltip(TrayStatus::Disconnected.tooltip())
+        .tooltip(TrayStatus::Disconnected.tooltip(None))
         .menu(&menu)
         .show_menu_on_left_click(false)
         .on_menu_event(|app, event| {
@@ -68,14 +137,75 @@ pub fn build_tray(app: &AppHandle) -> Result<TrayIcon, tauri::Error> {
 /// Build the tray context menu
 fn build_tray_menu(app: &AppHandle) -> Result<Menu<tauri::Wry>, tauri::Error> {
     let show = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
-    let separator = MenuItem::with_id(app, "sep", "---", false, None::<&str>)?;
+    let profiles = build_profiles_submenu(app)?;
+    let separator = PredefinedMenuItem::separator(app)?;
     let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
 
-    Menu::with_items(app, &[&show, &separator, &quit])
+    Menu::with_items(app, &[&show, &profiles, &separator, &quit])
+}
+
+/// Submenu listing all profiles, with the active one checked
+fn build_profiles_submenu(app: &AppHandle) -> Result<Submenu<tauri::Wry>, tauri::Error> {
+    let submenu = Submenu::with_id(app, "profiles", "Profiles", true)?;
+
+    let active_id = app
+        .try_state::<Arc<Mutex<ConfigManager>>>()
+        .and_then(|config| config.lock().get_active_profile_id().map(str::to_string));
+    let profiles: Vec<(String, String)> = app
+        .try_state::<Arc<Mutex<ProfileManager>>>()
+        .map(|manager| {
+            manager.lock().list().into_iter().map(|p| (p.id.clone(), p.name.clone())).collect()
+        })
+        .unwrap_or_default();
+
+    if profiles.is_empty() {
+        let none = MenuItem::with_id(app, "no-profiles", "No profiles", false, None::<&str>)?;
+        submenu.append(&none)?;
+    }
+    for (id, name) in profiles {
+        let checked = active_id.as_deref() == Some(id.as_str());
+        let item = CheckMenuItem::with_id(app, profile_menu_id(&id), name, true, checked, None::<&str>)?;
+        submenu.append(&item)?;
+    }
+    Ok(submenu)
+}
+
+/// Rebuild the tray menu so the profile list and checkmark are current
+pub fn refresh_tray_menu(app: &AppHandle) {
+    let Some(tray) = app.try_state::<TrayIcon>() else {
+        return;
+    };
+    match build_tray_menu(app) {
+        Ok(menu) => {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                log::warn!("Failed to update tray menu: {}", e);
+            }
+        }
+        Err(e) => log::warn!("Failed to build tray menu: {}", e),
+    }
+}
+
+/// Menu item ID for switching to a profile
+fn profile_menu_id(profile_id: &str) -> String {
+    format!("{}{}", PROFILE_MENU_PREFIX, profile_id)
+}
+
+/// Profile ID from a profile menu item ID
+fn parse_profile_menu_id(menu_id: &str) -> Option<&str> {
+    menu_id.strip_prefix(PROFILE_MENU_PREFIX).filter(|id| !id.is_empty())
 }
 
 /// Handle tray menu events
 fn handle_menu_event(app: &AppHandle, id: &str) {
+    if let Some(profile_id) = parse_profile_menu_id(id) {
+        if let Err(e) = activate_profile(app, profile_id) {
+            log::warn!("Failed to switch profile from tray: {}", e);
+            // Put the checkmark back on the profile that is still active
+            refresh_tray_menu(app);
+        }
+        return;
+    }
+
     match id {
         "show" => {
             if let Some(window) = app.get_webview_window("main") {
@@ -90,51 +220,93 @@ fn handle_menu_event(app: &AppHandle, id: &str) {
     }
 }
 
-/// Create a status-colored icon
+/// App logo the tray icon is drawn from
+const APP_ICON_PNG: &[u8] = include_bytes!("../../icons/32x32.png");
+
+/// Tray icon size in pixels
+const ICON_SIZE: u32 = 32;
+
+/// Radius of the status dot drawn over the logo
+const BADGE_RADIUS: f32 = 6.0;
+
+/// Create the tray icon for a status
 ///
-/// Generates a 32x32 RGBA icon with the status color.
-/// This provides visual feedback about connection state in the system tray.
+/// Draws the app logo with a status-colored dot in the bottom-right corner,
+/// falling back to a plain status-colored circle if the logo can't be decoded.
 fn create_status_icon(status: TrayStatus) -> Image<'static> {
-    const SIZE: u32 = 32;
+    let rgba = match badged_app_icon(status) {
+        Ok(rgba) => rgba,
+        Err(e) => {
+            log::warn!("Failed to load tray logo, using a plain icon: {}", e);
+            solid_status_icon(status)
+        }
+    };
+
+    // Convert to owned data for 'static lifetime
+    Image::new_owned(rgba, ICON_SIZE, ICON_SIZE)
+}
+
+/// App logo with the status dot composited over it, as 32x32 RGBA
+fn badged_app_icon(status: TrayStatus) -> Result<Vec<u8>, String> {
+    let logo = image::load_from_memory(APP_ICON_PNG).map_err(|e| e.to_string())?;
+    let mut logo = logo
+        .resize_exact(ICON_SIZE, ICON_SIZE, image::imageops::FilterType::Lanczos3)
+        .to_rgba8();
+
+    let (r, g, b) = status.color();
+    let center = ICON_SIZE as f32 - BADGE_RADIUS - 1.0;
+    for (x, y, pixel) in logo.enumerate_pixels_mut() {
+        let coverage = circle_coverage(x, y, center, BADGE_RADIUS);
+        if coverage <= 0.0 {
+            continue;
+        }
+        // Source-over blend of the opaque dot onto the logo
+        let [lr, lg, lb, la] = pixel.0;
+        let blend = |dot: u8, base: u8| (dot as f32 * coverage + base as f32 * (1.0 - coverage)).round() as u8;
+        let alpha = (255.0 * coverage + la as f32 * (1.0 - coverage)).round() as u8;
+        pixel.0 = [blend(r, lr), blend(g, lg), blend(b, lb), alpha];
+    }
+
+    Ok(logo.into_raw())
+}
+
+/// Plain status-colored circle filling the icon, as 32x32 RGBA
+fn solid_status_icon(status: TrayStatus) -> Vec<u8> {
     let (r, g, b) = status.color();
 
     // Create RGBA buffer: SIZE * SIZE pixels, 4 bytes each (RGBA)
-    let mut rgba = Vec::with_capacity((SIZE * SIZE * 4) as usize);
-
-    for y in 0..SIZE {
-        for x in 0..SIZE {
-            // Create a rounded square icon
-            let center = SIZE as f32 / 2.0;
-            let radius = SIZE as f32 / 2.0 - 2.0; // Leave 2px border
-            let dx = x as f32 - center;
-            let dy = y as f32 - center;
-            let dist = (dx * dx + dy * dy).sqrt();
-
-            if dist <= radius {
-                // Inside the circle - draw with status color
-                rgba.push(r);
-                rgba.push(g);
-                rgba.push(b);
-                rgba.push(255); // Fully opaque
-            } else if dist <= radius + 1.0 {
-                // Anti-aliased edge
-                let alpha = ((radius + 1.0 - dist) * 255.0) as u8;
-                rgba.push(r);
-                rgba.push(g);
-                rgba.push(b);
-                rgba.push(alpha);
+    let mut rgba = Vec::with_capacity((ICON_SIZE * ICON_SIZE * 4) as usize);
+
+    let center = ICON_SIZE as f32 / 2.0;
+    let radius = ICON_SIZE as f32 / 2.0 - 2.0; // Leave 2px border
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let alpha = (circle_coverage(x, y, center, radius) * 255.0) as u8;
+            if alpha > 0 {
+                rgba.extend_from_slice(&[r, g, b, alpha]);
             } else {
                 // Outside - transparent
-                rgba.push(0);
-                rgba.push(0);
-                rgba.push(0);
-                rgba.push(0);
+                rgba.extend_from_slice(&[0, 0, 0, 0]);
             }
         }
     }
 
-    // Convert to owned data for 'static lifetime
-    Image::new_owned(rgba, SIZE, SIZE)
+    rgba
+}
+
+/// How much of pixel (x, y) a circle covers, with a 1px anti-aliased edge
+fn circle_coverage(x: u32, y: u32, center: f32, radius: f32) -> f32 {
+    let dx = x as f32 - center;
+    let dy = y as f32 - center;
+    let dist = (dx * dx + dy * dy).sqrt();
+
+    if dist <= radius {
+        1.0
+    } else if dist <= radius + 1.0 {
+        radius + 1.0 - dist
+    } else {
+        0.0
+    }
 }
 
 /// Update tray icon based on connection status
@@ -144,13 +316,22 @@ fn create_status_icon(status: TrayStatus) -> Image<'static> {
 /// - Connected (green): Device is connected and ready
 /// - Disconnected (gray): No device connected
 /// - Error (red): Connection error occurred
-pub fn update_tray_status(tray: &TrayIcon, status: TrayStatus) {
+pub fn update_tray_status(tray: &TrayIcon, status: TrayStatus, device: Option<&DeviceInfo>) {
     // Update the icon with the new status color
     let icon = create_status_icon(status);
     let _ = tray.set_icon(Some(icon));
 
     // Update the tooltip
-    let _ = tray.set_tooltip(Some(status.tooltip()));
+    let _ = tray.set_tooltip(Some(status.tooltip(device)));
+}
+
+/// Update the app's tray icon, if it has one
+///
+/// The icon is kept in managed state once `run()` has built it.
+pub fn set_tray_status(app: &AppHandle, status: TrayStatus, device: Option<&DeviceInfo>) {
+    if let Some(tray) = app.try_state::<TrayIcon>() {
+        update_tray_status(&tray, status, device);
+    }
 }
 
 #[cfg(test)]
@@ -173,6 +354,14 @@ mod tests {
         assert_eq!(b, 100);
     }
 
+    #[test]
+    fn test_tray_status_reconnecting_color() {
+        let (r, g, b) = TrayStatus::Reconnecting.color();
+        assert_eq!(r, 255);
+        assert_eq!(g, 193);
+        assert_eq!(b, 7);
+    }
+
     #[test]
     fn test_tray_status_error_color() {
         let (r, g, b) = TrayStatus::Error.color();
@@ -183,17 +372,55 @@ mod tests {
 
     #[test]
     fn test_tray_status_connected_tooltip() {
-        assert_eq!(TrayStatus::Connected.tooltip(), "SOOMFON Controller - Connected");
+        assert_eq!(TrayStatus::Connected.tooltip(None), "SOOMFON Controller - Connected");
     }
 
     #[test]
     fn test_tray_status_disconnected_tooltip() {
-        assert_eq!(TrayStatus::Disconnected.tooltip(), "SOOMFON Controller - Disconnected");
+        assert_eq!(TrayStatus::Disconnected.tooltip(None), "SOOMFON Controller - Disconnected");
+    }
+
+    #[test]
+    fn test_tray_status_reconnecting_tooltip() {
+        assert_eq!(TrayStatus::Reconnecting.tooltip(None), "SOOMFON Controller - Reconnecting");
     }
 
     #[test]
     fn test_tray_status_error_tooltip() {
-        assert_eq!(TrayStatus::Error.tooltip(), "SOOMFON Controller - Error");
+        assert_eq!(TrayStatus::Error.tooltip(None), "SOOMFON Controller - Error");
+    }
+
+    fn device(product: Option<&str>, serial: Option<&str>, firmware: Option<&str>) -> DeviceInfo {
+        DeviceInfo {
+            path: "1:2:3".to_string(),
+            serial_number: serial.map(str::to_string),
+            manufacturer: None,
+            product: product.map(str::to_string),
+            firmware_version: firmware.map(str::to_string),
+            firmware: None,
+        }
+    }
+
+    #[test]
+    fn test_tray_status_connected_tooltip_names_device() {
+        let info = device(Some("SOOMFON Stream Deck"), Some("1234"), Some("1.0.2"));
+        assert_eq!(
+            TrayStatus::Connected.tooltip(Some(&info)),
+            "SOOMFON Stream Deck (SN: 1234, FW: 1.0.2) - Connected"
+        );
+
+        let info = device(Some("SOOMFON Stream Deck"), Some("1234"), None);
+        assert_eq!(TrayStatus::Connected.tooltip(Some(&info)), "SOOMFON Stream Deck (SN: 1234) - Connected");
+
+        let info = device(None, None, None);
+        assert_eq!(TrayStatus::Connected.tooltip(Some(&info)), "SOOMFON Controller - Connected");
+    }
+
+    #[test]
+    fn test_tray_status_other_tooltips_ignore_device() {
+        let info = device(Some("SOOMFON Stream Deck"), Some("1234"), None);
+        assert_eq!(TrayStatus::Disconnected.tooltip(Some(&info)), "SOOMFON Controller - Disconnected");
+        assert_eq!(TrayStatus::Error.tooltip(Some(&info)), "SOOMFON Controller - Error");
     }
 
     #[test]
@@ -205,6 +432,60 @@ mod tests {
         assert_eq!(icon.height(), 32);
     }
 
+    #[test]
+    fn test_profile_menu_id_round_trip() {
+        let id = profile_menu_id("3f2a-profile");
+        assert_eq!(parse_profile_menu_id(&id), Some("3f2a-profile"));
+    }
+
+    #[test]
+    fn test_parse_profile_menu_id_ignores_other_items() {
+        assert_eq!(parse_profile_menu_id("show"), None);
+        assert_eq!(parse_profile_menu_id("quit"), None);
+        assert_eq!(parse_profile_menu_id("profile:"), None);
+    }
+
+    #[test]
+    fn test_status_icon_keeps_logo_and_adds_badge() {
+        let rgba = badged_app_icon(TrayStatus::Error).unwrap();
+        assert_eq!(rgba.len(), (ICON_SIZE * ICON_SIZE * 4) as usize);
+
+        let pixel = |x: u32, y: u32| {
+            let i = ((y * ICON_SIZE + x) * 4) as usize;
+            [rgba[i], rgba[i + 1], rgba[i + 2], rgba[i + 3]]
+        };
+        // Badge center is the status color
+        let center = (ICON_SIZE as f32 - BADGE_RADIUS - 1.0) as u32;
+        assert_eq!(pixel(center, center), [244, 67, 54, 255]);
+
+        // Away from the badge the logo is untouched
+        let logo = image::load_from_memory(APP_ICON_PNG)
+            .unwrap()
+            .resize_exact(ICON_SIZE, ICON_SIZE, image::imageops::FilterType::Lanczos3)
+            .to_rgba8();
+        assert_eq!(pixel(4, 4), logo.get_pixel(4, 4).0);
+    }
+
+    #[test]
+    fn test_badge_edge_is_anti_aliased() {
+        let center = ICON_SIZE as f32 - BADGE_RADIUS - 1.0;
+        assert_eq!(circle_coverage(center as u32, center as u32, center, BADGE_RADIUS), 1.0);
+        // Distance ~6.4 from the center falls within the 1px edge
+        let edge = circle_coverage(center as u32 + 5, center as u32 + 4, center, BADGE_RADIUS);
+        assert!(edge > 0.0 && edge < 1.0);
+        assert_eq!(circle_coverage(0, 0, center, BADGE_RADIUS), 0.0);
+    }
+
+    #[test]
+    fn test_solid_status_icon_fallback() {
+        let rgba = solid_status_icon(TrayStatus::Connected);
+        assert_eq!(rgba.len(), (ICON_SIZE * ICON_SIZE * 4) as usize);
+
+        let middle = ((16 * ICON_SIZE + 16) * 4) as usize;
+        assert_eq!(&rgba[middle..middle + 4], &[76, 175, 80, 255]);
+        assert_eq!(&rgba[0..4], &[0, 0, 0, 0]);
+    }
+
     #[test]
     fn test_create_status_icon_different_statuses() {
         // All statuses should create valid icons